@@ -157,15 +157,14 @@ impl Problem {
 /// Each node must be present in the tour exactly once.
 struct Tour(Vec<usize>);
 
-impl Tour {
-    fn to_string(&self) -> String {
-        self.0
-            .iter()
-            .map(|n| (n + 1).to_string())
-            .collect::<Vec<_>>()
-            .join(" ")
+impl std::fmt::Display for Tour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nodes = self.0.iter().map(|n| (n + 1).to_string());
+        f.write_str(&nodes.collect::<Vec<_>>().join(" "))
     }
+}
 
+impl Tour {
     fn to_svg(&self, problem: &Problem) -> String {
         let cmp_f64 = |x: &f64, y: &f64| x.partial_cmp(y).unwrap();
         let min_x = problem.points.iter().map(|p| p.x).min_by(cmp_f64).unwrap();
@@ -208,6 +207,7 @@ impl Tour {
     }
 }
 
+#[allow(clippy::needless_range_loop)]
 fn solve(problem: &Problem) -> Tour {
     info!("starting, problem name: {}", problem.name);
 
@@ -533,7 +533,7 @@ fn find_min_cut(size: usize, weights: &mut [f64]) -> (f64, Vec<bool>) {
     }
 
     assert!(best_cut_weight.is_finite());
-    return (best_cut_weight, best_cut);
+    (best_cut_weight, best_cut)
 }
 
 #[cfg(test)]
@@ -639,6 +639,6 @@ fn main() {
     if enable_svg_output {
         print!("{}", tour.to_svg(&problem));
     } else {
-        println!("{}", tour.to_string());
+        println!("{}", tour);
     }
 }
@@ -0,0 +1,62 @@
+use crate::{BasisVar, ComparisonOp, Problem, Solution};
+use std::io;
+
+/// Write the basis of the solution in the MPS basis format.
+///
+/// Basic variables are paired with the non-basic constraints in order: the `XU` line means
+/// that the constraint is at its upper bound (a `<=` constraint), `XL` - at its lower bound.
+/// Non-basic variables at their upper bound are listed on the `UL` lines, the remaining
+/// variables are at their lower bounds.
+pub(crate) fn write_basis(
+    solution: &Solution,
+    problem: &Problem,
+    name: &str,
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let basic_vars = (0..problem.obj_coeffs.len())
+        .filter(|&v| solution.is_basic(BasisVar::Var(solution.var_ref(v))));
+    let nb_constraints = (0..problem.constraints.len())
+        .filter(|&constr| !solution.is_basic(BasisVar::Slack(solution.constraint_ref(constr))));
+
+    writeln!(out, "NAME          {}", name)?;
+    for (var, constr) in basic_vars.zip(nb_constraints) {
+        let kind = match problem.constraints[constr].1 {
+            ComparisonOp::Le => "XU",
+            ComparisonOp::Ge | ComparisonOp::Eq => "XL",
+        };
+        writeln!(out, " {} {} c{}", kind, problem.var_name_at(var), constr)?;
+    }
+
+    for (v, (&min, &max)) in problem.var_mins.iter().zip(&problem.var_maxs).enumerate() {
+        let var = solution.var_ref(v);
+        if min != max && solution[var] == max && !solution.is_basic(BasisVar::Var(var)) {
+            writeln!(out, " UL {}", problem.var_name_at(v))?;
+        }
+    }
+    writeln!(out, "ENDATA")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn write() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (0.0, 3.0));
+        let z = problem.add_var(-1.0, (0.0, 5.0));
+        problem.set_var_name(x, "x");
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(z, 1.0), (y, 1.0)], ComparisonOp::Ge, 4.0);
+
+        let solution = problem.solve().unwrap();
+        assert_eq!(solution[z], 1.0);
+        let mut out = vec![];
+        solution.write_basis(&problem, "test", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "NAME          test\n XU x c0\n XL x2 c1\n UL x1\nENDATA\n"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    parallel, Error, LinearExpr, OptimizationDirection, Problem, Solution, Solver, SolverOptions,
+    Variable,
+};
+
+pub(crate) fn solve_rhs_batch(
+    problem: &Problem,
+    rhs_batch: &[Vec<f64>],
+    options: &SolverOptions,
+) -> Vec<Result<Solution, Error>> {
+    let num_constraints = problem.constraints.len();
+    for rhs in rhs_batch {
+        assert_eq!(rhs.len(), num_constraints, "rhs length mismatch");
+        for &val in rhs {
+            assert!(val.is_finite(), "invalid rhs: {}", val);
+        }
+    }
+
+    // Right-hand sides of the removed constraints stay zero.
+    let effective_rhs = |rhs: &[f64]| -> Vec<f64> {
+        rhs.iter()
+            .zip(&problem.is_constraint_removed)
+            .map(|(&val, &is_removed)| if is_removed { 0.0 } else { val })
+            .collect()
+    };
+
+    let base = match problem.solve_with(options) {
+        Ok(base) => base,
+        // Without a dual feasible basis to start from, each problem is solved from scratch.
+        Err(_) => {
+            return rhs_batch
+                .iter()
+                .map(|rhs| {
+                    let mut problem = problem.clone();
+                    for (constr, val) in problem.constraints.iter_mut().zip(effective_rhs(rhs)) {
+                        constr.2 = val;
+                    }
+                    problem.solve_with(options)
+                })
+                .collect();
+        }
+    };
+
+    let chunks = parallel::map_chunks_with_min_len(options.threads, rhs_batch.len(), 1, |range| {
+        let mut results: Vec<Result<Solution, Error>> = vec![];
+        // Each solve is started from the last successful one in the chunk.
+        let mut last_ok = None;
+        for rhs in &rhs_batch[range] {
+            let prev = last_ok.and_then(|idx: usize| results[idx].as_ref().ok());
+            let mut solution = prev.unwrap_or(&base).clone();
+            let res = solution
+                .solver
+                .set_rhs(&effective_rhs(rhs))
+                .map(|()| solution)
+                .map_err(|err| err.in_generation(problem.generation));
+            if res.is_ok() {
+                last_ok = Some(results.len());
+            }
+            results.push(res);
+        }
+        results
+    });
+    chunks.into_iter().flatten().collect()
+}
+
+pub(crate) fn solve_objective_sequence(
+    problem: &Problem,
+    objectives: &[LinearExpr],
+) -> Vec<Result<Solution, Error>> {
+    let num_vars = problem.obj_coeffs.len();
+    let to_min_form = |expr: &LinearExpr| -> Vec<f64> {
+        if expr.generation.is_some_and(|gen| gen != problem.generation) {
+            panic!("objective expression refers to variables of an older generation");
+        }
+        let mut obj_coeffs = vec![0.0; num_vars];
+        for (&var, &coeff) in expr.vars.iter().zip(&expr.coeffs) {
+            if let Err(err) = problem.check_var(Variable(var, problem.generation)) {
+                panic!("{}", err);
+            }
+            assert!(coeff.is_finite(), "invalid coefficient: {}", coeff);
+            obj_coeffs[var] += match problem.direction {
+                OptimizationDirection::Minimize => coeff,
+                OptimizationDirection::Maximize => -coeff,
+            };
+        }
+        obj_coeffs
+    };
+
+    // The cutoff refers to the objective of the problem, not to the objectives of the sequence.
+    let options = SolverOptions {
+        objective_cutoff: None,
+        ..SolverOptions::default()
+    };
+    let solve_from_scratch = |obj_coeffs: &[f64]| -> Result<Solution, Error> {
+        let mut solver = Solver::try_new_with_options(
+            obj_coeffs,
+            &problem.var_mins,
+            &problem.var_maxs,
+            &problem.constraints,
+            None,
+            &options,
+        )?;
+        solver.initial_solve()?;
+        Ok(Solution {
+            num_vars,
+            direction: problem.direction,
+            obj_offset: 0.0,
+            generation: problem.generation,
+            solver,
+        })
+    };
+
+    let mut results: Vec<Result<Solution, Error>> = vec![];
+    // Each objective is optimized starting from the basis of the last successful solve.
+    let mut last_ok = None;
+    for expr in objectives {
+        let obj_coeffs = to_min_form(expr);
+        let res = match last_ok.map(|idx: usize| results[idx].as_ref().unwrap()) {
+            Some(prev) => {
+                let mut solution = prev.clone();
+                solution
+                    .solver
+                    .set_obj_coeffs(&obj_coeffs)
+                    .map(|()| solution)
+            }
+            None => solve_from_scratch(&obj_coeffs),
+        }
+        .map_err(|err| err.in_generation(problem.generation));
+        if res.is_ok() {
+            last_ok = Some(results.len());
+        }
+        results.push(res);
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn rhs_batch() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (0.0, 3.0));
+        let c1 = problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        let c2 = problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+
+        let rhs_batch = vec![
+            vec![4.0, 2.0],
+            vec![2.0, 2.0],
+            vec![1.0, 5.0],
+            vec![6.0, 1.0],
+            vec![5.0, 0.0],
+        ];
+        let expected = [Some(7.0), Some(4.0), None, Some(9.0), Some(8.0)];
+        for (threads, scaling) in [(1, ScalingMode::None), (3, ScalingMode::Equilibration)] {
+            let options = SolverOptions {
+                threads,
+                scaling,
+                ..Default::default()
+            };
+            let results = problem.solve_rhs_batch(&rhs_batch, &options);
+            assert_eq!(results.len(), rhs_batch.len());
+            for ((res, rhs), expected) in results.iter().zip(&rhs_batch).zip(&expected) {
+                match (res, expected) {
+                    (Ok(sol), Some(obj)) => {
+                        assert_eq!(sol.objective(), *obj);
+                        // Same as solving the modified problem from scratch.
+                        let mut modified = Problem::new(OptimizationDirection::Maximize);
+                        let x = modified.add_var(1.0, (0.0, f64::INFINITY));
+                        let y = modified.add_var(2.0, (0.0, 3.0));
+                        modified.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, rhs[0]);
+                        modified.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, rhs[1]);
+                        assert_eq!(modified.solve().unwrap().objective(), *obj);
+                    }
+                    (Err(err), None) => assert_eq!(err.kind(), ErrorKind::Infeasible),
+                    (res, _) => panic!("unexpected result: {:?}", res),
+                }
+            }
+
+            // The solutions can be used for incremental solving.
+            let sol = results.into_iter().next().unwrap().unwrap();
+            assert_eq!(sol.dual_value(c1), 1.0);
+            assert_eq!(sol.dual_value(c2), 0.0);
+            let sol = sol.fix_var(y, 1.0).unwrap();
+            assert_eq!(sol.objective(), 5.0);
+        }
+
+        // The problem itself is infeasible, so the batch is solved from scratch.
+        let mut infeasible = problem.clone();
+        infeasible.add_constraint([(x, 1.0)], ComparisonOp::Ge, 10.0);
+        let results = infeasible.solve_rhs_batch(
+            &[vec![4.0, 2.0, 10.0], vec![20.0, 2.0, 10.0]],
+            &SolverOptions::default(),
+        );
+        assert_eq!(
+            results[0].as_ref().unwrap_err().kind(),
+            ErrorKind::Infeasible
+        );
+        assert_eq!(results[1].as_ref().unwrap().objective(), 23.0);
+    }
+
+    #[test]
+    fn objective_sequence() {
+        let make_problem = |obj: [f64; 2], y_max: f64| {
+            let mut problem = Problem::new(OptimizationDirection::Minimize);
+            let x = problem.add_var(obj[0], (0.0, 3.0));
+            let y = problem.add_var(obj[1], (-1.0, y_max));
+            problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 1.0);
+            problem.add_constraint([(x, 1.0), (y, -1.0)], ComparisonOp::Le, 2.0);
+            (problem, x, y)
+        };
+
+        let (problem, x, y) = make_problem([5.0, -1.0], 2.0);
+        let objectives = [
+            [1.0, 1.0],
+            [-1.0, 0.0],
+            [1.0, -1.0],
+            [0.0, -1.0],
+            [0.0, 0.0],
+        ];
+        let exprs = objectives
+            .iter()
+            .map(|obj| LinearExpr::from([(x, obj[0]), (y, obj[1])]))
+            .collect::<Vec<_>>();
+        let results = problem.solve_multi_objective_sequence(&exprs);
+        assert_eq!(results.len(), objectives.len());
+        for (res, obj) in results.iter().zip(&objectives) {
+            // Same as solving with the objective from scratch.
+            let expected = make_problem(*obj, 2.0).0.solve().unwrap().objective();
+            assert!(f64::abs(res.as_ref().unwrap().objective() - expected) < 1e-8);
+        }
+
+        // Repeated terms are summed up and the objective offset is not included.
+        let mut with_offset = problem.clone();
+        with_offset.set_objective_offset(10.0);
+        let results =
+            with_offset.solve_multi_objective_sequence(&[LinearExpr::from([(y, 2.0), (y, -3.0)])]);
+        assert_eq!(results[0].as_ref().unwrap().objective(), -2.0);
+
+        // An unbounded objective doesn't interrupt the sequence.
+        let (unbounded, x, y) = make_problem([0.0, 0.0], f64::INFINITY);
+        let results = unbounded.solve_multi_objective_sequence(&[
+            LinearExpr::from([(y, -1.0)]),
+            LinearExpr::from([(x, 1.0)]),
+            LinearExpr::from([(x, 1.0), (y, 1.0)]),
+        ]);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().kind(),
+            ErrorKind::Unbounded
+        );
+        assert_eq!(results[1].as_ref().unwrap().objective(), 0.0);
+        assert_eq!(results[2].as_ref().unwrap().objective(), 1.0);
+
+        // The solutions can be used for incremental solving.
+        let sol = results.into_iter().nth(2).unwrap().unwrap();
+        let sol = sol.fix_var(y, 3.0).unwrap();
+        assert_eq!(sol.objective(), 3.0);
+    }
+}
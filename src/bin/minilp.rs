@@ -0,0 +1,372 @@
+use minilp::{
+    Error, ErrorKind, LpFile, MpsFile, MpsFormat, OptimizationDirection, Pricing, Problem,
+    Solution, SolverOptions,
+};
+use std::{
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "\
+Solve, convert or inspect a problem in the MPS or LP format.
+
+USAGE:
+    minilp --help
+    minilp [OPTIONS] INPUT_FILE
+    minilp convert [OPTIONS] INPUT_FILE OUTPUT_FILE
+    minilp stats [OPTIONS] INPUT_FILE
+
+The first form solves the problem and prints the status and the optimal objective
+value. The exit code is 0 if the problem was solved (even if it is infeasible or
+unbounded) and 1 otherwise. `convert` writes the problem in another format and
+`stats` prints its size and sparsity statistics.
+
+INPUT_FILE is a file in the (free) MPS format or, if its extension is .lp, in the
+CPLEX LP format. Use - for stdin. OUTPUT_FILE is written in the LP format if its
+extension is .lp and in the free MPS format otherwise. Use - for stdout.
+
+OPTIONS:
+    --from FORMAT           Format of INPUT_FILE: mps or lp.
+    --to FORMAT             Format of OUTPUT_FILE: lp, free-mps or fixed-mps.
+    --max                   Maximize the objective of an MPS problem without the
+                            OBJSENSE section (minimized by default).
+    --time-limit SECONDS    Stop if the problem is not solved in time and print the
+                            best objective value and bound found so far.
+    --pricing RULE          Pricing rule of the primal simplex: full (default),
+                            partial or multiple.
+    --presolve on|off       Merge the pairs of inequality constraints that form an
+                            equality before solving (off by default). The merged
+                            constraints keep their names, the dual value of a pair is
+                            reported on its first constraint.
+    --sol FILE              Write the objective value and the nonzero variable and dual
+                            values to FILE (INPUT_FILE with the .sol extension by default).
+    --bas FILE              Write the optimal basis in the MPS basis format to FILE
+                            (INPUT_FILE with the .bas extension by default).
+
+Constraints are named c0, c1, ... in the order of their definition (ranged rows of
+MPS files are split in two constraints) in the output files.
+";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Solve,
+    Convert,
+    Stats,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Lp,
+    Mps(MpsFormat),
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "lp" => Ok(Format::Lp),
+            "mps" | "free-mps" => Ok(Format::Mps(MpsFormat::Free)),
+            "fixed-mps" => Ok(Format::Mps(MpsFormat::Fixed)),
+            _ => Err(format!("unknown format: {}", name)),
+        }
+    }
+
+    fn from_path(path: &str) -> Self {
+        let is_lp = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lp"));
+        if is_lp {
+            Format::Lp
+        } else {
+            Format::Mps(MpsFormat::Free)
+        }
+    }
+}
+
+struct Args {
+    command: Command,
+    input: String,
+    input_format: Format,
+    output: Option<(String, Format)>,
+    direction: OptimizationDirection,
+    time_limit: Option<Duration>,
+    presolve: bool,
+    options: SolverOptions,
+    sol_file: Option<PathBuf>,
+    bas_file: Option<PathBuf>,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut positional = vec![];
+    let mut input_format = None;
+    let mut output_format = None;
+    let mut direction = OptimizationDirection::Minimize;
+    let mut time_limit = None;
+    let mut presolve = false;
+    let mut options = SolverOptions::default();
+    let mut sol_file = None;
+    let mut bas_file = None;
+    let mut solve_options = vec![];
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--from" => input_format = Some(Format::parse(value()?)?),
+            "--to" => output_format = Some(Format::parse(value()?)?),
+            "--max" => direction = OptimizationDirection::Maximize,
+            "--time-limit" => {
+                let secs = value()?;
+                let limit = (secs.parse::<f64>().ok())
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .ok_or("invalid time limit")?;
+                time_limit = Some(limit);
+            }
+            "--pricing" => {
+                options.pricing = match value()?.as_str() {
+                    "full" => Pricing::Full,
+                    "partial" => Pricing::Partial,
+                    "multiple" => Pricing::Multiple,
+                    rule => return Err(format!("unknown pricing rule: {}", rule)),
+                }
+            }
+            "--presolve" => {
+                presolve = match value()?.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    value => return Err(format!("invalid value for --presolve: {}", value)),
+                }
+            }
+            "--sol" => sol_file = Some(PathBuf::from(value()?)),
+            "--bas" => bas_file = Some(PathBuf::from(value()?)),
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            _ => positional.push(arg.clone()),
+        }
+        if ["--time-limit", "--pricing", "--presolve", "--sol", "--bas"].contains(&arg.as_str()) {
+            solve_options.push(arg);
+        }
+    }
+
+    let command = match positional.first().map(|arg| arg.as_str()) {
+        Some("convert") => Command::Convert,
+        Some("stats") => Command::Stats,
+        _ => Command::Solve,
+    };
+    if command != Command::Solve {
+        positional.remove(0);
+        if let Some(option) = solve_options.first() {
+            return Err(format!("{} is valid only when solving", option));
+        }
+    }
+    if command != Command::Convert && output_format.is_some() {
+        return Err("--to is valid only for convert".to_string());
+    }
+
+    let num_files = if command == Command::Convert { 2 } else { 1 };
+    if positional.len() != num_files {
+        return Err("wrong number of arguments".to_string());
+    }
+    let input = positional.remove(0);
+    let input_format = input_format.unwrap_or_else(|| Format::from_path(&input));
+    let output = positional.pop().map(|output| {
+        let format = output_format.unwrap_or_else(|| Format::from_path(&output));
+        (output, format)
+    });
+
+    if command == Command::Solve && input != "-" {
+        let path = Path::new(&input);
+        sol_file = sol_file.or_else(|| Some(path.with_extension("sol")));
+        bas_file = bas_file.or_else(|| Some(path.with_extension("bas")));
+    }
+    Ok(Args {
+        command,
+        input,
+        input_format,
+        output,
+        direction,
+        time_limit,
+        presolve,
+        options,
+        sol_file,
+        bas_file,
+    })
+}
+
+/// Read the problem and its name (from the MPS file or the file name).
+fn read_problem(args: &Args) -> Result<(String, Problem), String> {
+    let parse = |input: &mut dyn io::BufRead| match args.input_format {
+        Format::Lp => {
+            let name = Path::new(&args.input)
+                .file_stem()
+                .map_or("PROBLEM".into(), |stem| stem.to_string_lossy());
+            LpFile::parse(input).map(|file| (name.into_owned(), file.problem))
+        }
+        Format::Mps(_) => {
+            MpsFile::parse(input, args.direction).map(|file| (file.problem_name, file.problem))
+        }
+    };
+    if args.input == "-" {
+        parse(&mut io::stdin().lock())
+    } else {
+        fs::File::open(&args.input).and_then(|file| parse(&mut io::BufReader::new(file)))
+    }
+    .map_err(|err| format!("failed to read {}: {}", args.input, err))
+}
+
+fn write_err(path: &Path, err: io::Error) -> String {
+    format!("failed to write {}: {}", path.display(), err)
+}
+
+fn solve(args: &Args, mut problem: Problem, name: &str) -> Result<(), String> {
+    // A time limit too large to represent is no limit.
+    let deadline = (args.time_limit).and_then(|time_limit| Instant::now().checked_add(time_limit));
+    if args.presolve {
+        // The removed constraints are left empty (not compacted) so that the constraint
+        // names in the output files stay the same.
+        let merged = problem.merge_inequality_pairs_with(&args.options);
+        println!("presolve: merged {} inequality pairs", merged.len());
+    }
+
+    // A panic of the solver is an internal error, report it like the other errors.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match deadline {
+        Some(deadline) => solve_until(&problem, &args.options, deadline),
+        None => Some(problem.solve_with(&args.options)),
+    }))
+    .map_err(|_| "internal error: the solver panicked")?;
+    let result = match result {
+        Some(result) => result,
+        None => return Err("time limit reached".to_string()),
+    };
+
+    let solution = match result {
+        Ok(solution) => solution,
+        Err(err) => {
+            let status = match err.kind() {
+                ErrorKind::Infeasible => "infeasible",
+                ErrorKind::Unbounded => "unbounded",
+                ErrorKind::Cutoff => "cutoff",
+            };
+            println!("status: {}", status);
+            println!("{}", err);
+            return Ok(());
+        }
+    };
+    println!("status: optimal");
+    println!("objective value: {}", solution.objective());
+
+    if let Some(path) = &args.sol_file {
+        let contents = solution.display(&problem).with_duals().to_string();
+        fs::write(path, contents).map_err(|err| write_err(path, err))?;
+    }
+    if let Some(path) = &args.bas_file {
+        let mut contents = vec![];
+        solution
+            .write_basis(&problem, name, &mut contents)
+            .and_then(|()| fs::write(path, contents))
+            .map_err(|err| write_err(path, err))?;
+    }
+    Ok(())
+}
+
+/// Solve the problem a few iterations at a time until it is solved or the deadline passes.
+/// In the latter case print what is known about the solution and return `None`.
+fn solve_until(
+    problem: &Problem,
+    options: &SolverOptions,
+    deadline: Instant,
+) -> Option<Result<Solution, Error>> {
+    const STEP_ITERATIONS: usize = 10;
+    let mut handle = problem.start_solve(options);
+    while Instant::now() < deadline {
+        if let Poll::Ready(res) = handle.step(STEP_ITERATIONS) {
+            return Some(res);
+        }
+    }
+
+    let partial = handle.stop();
+    println!("status: time limit reached");
+    println!("iterations: {}", partial.iterations);
+    if let Some(solution) = &partial.solution {
+        println!("best objective value: {}", solution.objective());
+    }
+    if let Some(bound) = partial.bound {
+        println!("objective bound: {}", bound);
+    }
+    None
+}
+
+fn convert(problem: &Problem, name: &str, output: &str, format: Format) -> Result<(), String> {
+    let mut contents = vec![];
+    match format {
+        Format::Lp => contents = problem.to_string().into_bytes(),
+        Format::Mps(format) => problem
+            .write_mps(name, format, &mut contents)
+            .map_err(|err| format!("failed to convert: {}", err))?,
+    }
+    if output == "-" {
+        io::Write::write_all(&mut io::stdout(), &contents)
+    } else {
+        fs::write(output, contents)
+    }
+    .map_err(|err| write_err(Path::new(output), err))
+}
+
+fn print_stats(problem: &Problem, name: &str) {
+    let stats = problem.stats();
+    println!("name: {}", name);
+    println!("variables: {}", stats.num_vars);
+    println!(
+        "constraints: {} ({} <=, {} >=, {} =)",
+        stats.num_constraints,
+        stats.num_le_constraints,
+        stats.num_ge_constraints,
+        stats.num_eq_constraints
+    );
+    println!(
+        "nonzeros: {} (density {:.4})",
+        stats.num_nonzeros,
+        stats.density()
+    );
+    println!(
+        "coefficient range: [{}, {}]",
+        stats.min_abs_coeff, stats.max_abs_coeff
+    );
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let (name, problem) = read_problem(&args)?;
+    match (args.command, &args.output) {
+        (Command::Solve, _) => solve(&args, problem, &name),
+        (Command::Convert, Some((output, format))) => convert(&problem, &name, output, *format),
+        (Command::Convert, None) => unreachable!(),
+        (Command::Stats, _) => {
+            print_stats(&problem, &name);
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "--help") {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = run(args) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
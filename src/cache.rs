@@ -0,0 +1,335 @@
+use crate::{fingerprint, Error, Fingerprint, Problem, Solution, SolverOptions};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs, io,
+    path::PathBuf,
+};
+
+/// Persistent storage of the solutions for a [`SolveCache`].
+///
+/// Solutions are stored as the values of the variables. When a stored solution is found,
+/// the cache solves the problem starting from these values instead of from scratch: getting
+/// to an optimal basis takes about one iteration for each variable strictly between its
+/// bounds. So a stored solution can't make the result wrong, at worst it is useless and
+/// the problem is solved as usual.
+///
+/// [`SolveCache`]: struct.SolveCache.html
+pub trait CacheBackend {
+    /// Load the values of the variables stored for the problem with the `fingerprint`.
+    fn load(&mut self, fingerprint: Fingerprint) -> io::Result<Option<Vec<f64>>>;
+
+    /// Store the values of the variables in the optimal solution of the problem with
+    /// the `fingerprint`.
+    fn store(&mut self, fingerprint: Fingerprint, values: &[f64]) -> io::Result<()>;
+}
+
+/// A [`CacheBackend`] storing each solution in a separate file in a directory.
+///
+/// Files are named after the fingerprint with the `.sol` extension and contain the values
+/// of the variables, one per line.
+///
+/// [`CacheBackend`]: trait.CacheBackend.html
+#[derive(Clone, Debug)]
+pub struct DirCacheBackend {
+    dir: PathBuf,
+}
+
+impl DirCacheBackend {
+    /// Create the backend storing the solutions in `dir` (it is created if necessary).
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(DirCacheBackend { dir })
+    }
+
+    fn path(&self, fingerprint: Fingerprint) -> PathBuf {
+        self.dir.join(format!("{}.sol", fingerprint))
+    }
+}
+
+impl CacheBackend for DirCacheBackend {
+    fn load(&mut self, fingerprint: Fingerprint) -> io::Result<Option<Vec<f64>>> {
+        let contents = match fs::read_to_string(self.path(fingerprint)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let values = contents
+            .lines()
+            .map(|line| {
+                line.parse()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Some(values))
+    }
+
+    fn store(&mut self, fingerprint: Fingerprint, values: &[f64]) -> io::Result<()> {
+        let contents: String = values.iter().map(|val| format!("{:?}\n", val)).collect();
+        // Write to a temporary file first so that readers never see a partial solution.
+        let tmp_path = self.dir.join(format!("{}.sol.tmp", fingerprint));
+        fs::write(&tmp_path, contents)?;
+        fs::rename(tmp_path, self.path(fingerprint))
+    }
+}
+
+struct CacheEntry {
+    /// Solutions are interchangeable only between problems with the same layout.
+    layout: u128,
+    result: Result<Solution, Error>,
+}
+
+/// An in-process cache of the results of solving problems, keyed by the
+/// [fingerprint](struct.Problem.html#method.fingerprint) of the problem.
+///
+/// Useful e.g. in simulation loops that regenerate identical problems. A cached result is
+/// returned if a problem with the same fingerprint and the same order of the variables and
+/// constraints was already solved (so that the variables of the cached solution refer to the
+/// same variables of the new problem), otherwise the problem is solved and the result is
+/// cached. When the number of cached results exceeds the capacity, the oldest ones
+/// are evicted.
+///
+/// Optionally, optimal solutions are also saved to a persistent [`CacheBackend`] and
+/// problems missing in the in-process cache are solved starting from the saved solutions.
+///
+/// [`CacheBackend`]: trait.CacheBackend.html
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut cache = SolveCache::new(SolverOptions::default(), 100);
+/// for _ in 0..3 {
+///     let mut problem = Problem::new(OptimizationDirection::Maximize);
+///     let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+///     let y = problem.add_var(2.0, (0.0, 3.0));
+///     problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+///
+///     let solution = cache.solve(&problem).unwrap();
+///     assert_eq!(solution.objective(), 7.0);
+/// }
+/// assert_eq!((cache.hits(), cache.misses()), (2, 1));
+/// ```
+pub struct SolveCache {
+    options: SolverOptions,
+    capacity: usize,
+    entries: HashMap<Fingerprint, CacheEntry>,
+    /// Fingerprints of the entries in the order of insertion.
+    order: VecDeque<Fingerprint>,
+    backend: Option<Box<dyn CacheBackend + Send>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl std::fmt::Debug for SolveCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SolveCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.entries.len())
+            .field("has_backend", &self.backend.is_some())
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish()
+    }
+}
+
+impl SolveCache {
+    /// Create the cache holding at most `capacity` results of solving problems with
+    /// the `options`.
+    pub fn new(options: SolverOptions, capacity: usize) -> Self {
+        SolveCache {
+            options,
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            backend: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Save optimal solutions to the persistent `backend` and use the saved solutions
+    /// as starting points.
+    pub fn with_backend(mut self, backend: impl CacheBackend + Send + 'static) -> Self {
+        self.backend = Some(Box::new(backend));
+        self
+    }
+
+    /// Return the cached result for the `problem` or solve it and cache the result.
+    ///
+    /// Errors of the persistent backend are logged and otherwise ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error, if the problem is infeasible (constraints can't be satisfied)
+    /// or if the objective value is unbounded.
+    pub fn solve(&mut self, problem: &Problem) -> Result<Solution, Error> {
+        let (fingerprint, layout) = fingerprint::fingerprint(problem);
+        if let Some(entry) = self.entries.get(&fingerprint) {
+            if entry.layout == layout {
+                self.hits += 1;
+                return entry.result.clone();
+            }
+        }
+        self.misses += 1;
+
+        let num_vars = problem.obj_coeffs.len();
+        let start = match self
+            .backend
+            .as_mut()
+            .map(|backend| backend.load(fingerprint))
+        {
+            Some(Ok(values)) => values.filter(|values| values.len() == num_vars),
+            Some(Err(err)) => {
+                warn!("failed to load the solution {}: {}", fingerprint, err);
+                None
+            }
+            None => None,
+        };
+        let result = problem.solve_from(&self.options, start.as_deref());
+
+        if let (Some(backend), Ok(solution)) = (&mut self.backend, &result) {
+            let values: Vec<f64> = solution.iter().map(|(_, &val)| val).collect();
+            if start.as_ref() != Some(&values) {
+                if let Err(err) = backend.store(fingerprint, &values) {
+                    warn!("failed to store the solution {}: {}", fingerprint, err);
+                }
+            }
+        }
+
+        if self.capacity > 0 {
+            let entry = CacheEntry {
+                layout,
+                result: result.clone(),
+            };
+            if self.entries.insert(fingerprint, entry).is_none() {
+                self.order.push_back(fingerprint);
+            }
+            if self.order.len() > self.capacity {
+                let oldest = self.order.pop_front().unwrap();
+                self.entries.remove(&oldest);
+            }
+        }
+        result
+    }
+
+    /// Number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no cached results.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all cached results (the persistent backend is not affected).
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Number of calls to [`solve`](#method.solve) that returned a cached result.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of calls to [`solve`](#method.solve) that solved the problem.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn problem(rhs: f64, reversed: bool) -> (Problem, Variable) {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let mut names = ["x", "y"];
+        if reversed {
+            names.reverse();
+        }
+        let vars: Vec<_> = names
+            .iter()
+            .map(|&name| {
+                let obj_coeff = if name == "x" { 1.0 } else { 2.0 };
+                let var = problem.add_var(obj_coeff, (0.0, 3.0));
+                problem.set_var_name(var, name);
+                var
+            })
+            .collect();
+        let (x, y) = if reversed {
+            (vars[1], vars[0])
+        } else {
+            (vars[0], vars[1])
+        };
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, rhs);
+        (problem, x)
+    }
+
+    #[test]
+    fn in_process() {
+        let mut cache = SolveCache::new(SolverOptions::default(), 2);
+        for &rhs in &[4.0, 5.0, 4.0] {
+            let (problem, x) = problem(rhs, false);
+            let solution = cache.solve(&problem).unwrap();
+            assert_eq!(solution[x], rhs - 3.0);
+        }
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 2));
+
+        // The order of variables differs, the cached solution can't be used.
+        let (problem, x) = problem(4.0, true);
+        assert_eq!(cache.solve(&problem).unwrap()[x], 1.0);
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 3, 2));
+
+        // Errors are cached too.
+        let mut infeasible = problem.clone();
+        infeasible.add_constraint([(x, 1.0)], ComparisonOp::Ge, 5.0);
+        for _ in 0..2 {
+            let err = cache.solve(&infeasible).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Infeasible);
+        }
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (2, 4, 2));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn persistent() {
+        let dir = std::env::temp_dir().join(format!("minilp-cache-{}", std::process::id()));
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let vars: Vec<_> = (0..10)
+            .map(|i| problem.add_var(1.0 + i as f64, (0.0, f64::INFINITY)))
+            .collect();
+        for (i, pair) in vars.windows(2).enumerate() {
+            problem.add_constraint([(pair[0], 1.0), (pair[1], 2.0)], ComparisonOp::Le, i as f64);
+        }
+        let optimal = problem.solve().unwrap();
+
+        let mut cache = SolveCache::new(SolverOptions::default(), 0)
+            .with_backend(DirCacheBackend::new(&dir).unwrap());
+        assert!(cache.solve(&problem).unwrap().approx_eq(&optimal, 1e-9));
+        assert!(cache.is_empty());
+        let path = dir.join(format!("{}.sol", problem.fingerprint()));
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(saved.lines().count(), vars.len());
+
+        // A new cache starts from the saved solution.
+        let mut cache = SolveCache::new(SolverOptions::default(), 10)
+            .with_backend(DirCacheBackend::new(&dir).unwrap());
+        let solution = cache.solve(&problem).unwrap();
+        assert!(solution.approx_eq(&optimal, 1e-9));
+        assert!(solution.stats().iterations <= optimal.stats().iterations);
+
+        // A corrupted file is ignored and overwritten.
+        std::fs::write(&path, "garbage\n").unwrap();
+        let mut cache = SolveCache::new(SolverOptions::default(), 10)
+            .with_backend(DirCacheBackend::new(&dir).unwrap());
+        assert!(cache.solve(&problem).unwrap().approx_eq(&optimal, 1e-9));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
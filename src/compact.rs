@@ -0,0 +1,179 @@
+use crate::{ConstraintRef, CsVec, Problem, Variable};
+
+/// Translation of the variable and constraint references from the generation of the problem
+/// before [`Problem::compact`] to the new generation.
+///
+/// [`Problem::compact`]: struct.Problem.html#method.compact
+#[derive(Clone, Debug)]
+pub struct Remapping {
+    old_generation: u32,
+    new_generation: u32,
+    /// New index of the variable for each old index, `None` if the variable was removed.
+    vars: Vec<Option<usize>>,
+    /// New index of the constraint for each old index, `None` if the constraint was removed.
+    constraints: Vec<Option<usize>>,
+}
+
+impl Remapping {
+    /// The new reference of the variable. Returns `None` if the variable was removed or
+    /// the reference is not from the generation of the problem before compaction.
+    pub fn var(&self, var: Variable) -> Option<Variable> {
+        if var.1 != self.old_generation {
+            return None;
+        }
+        let new_idx = (*self.vars.get(var.0)?)?;
+        Some(Variable(new_idx, self.new_generation))
+    }
+
+    /// The new reference of the constraint, see [`var`](#method.var).
+    pub fn constraint(&self, constr: ConstraintRef) -> Option<ConstraintRef> {
+        if constr.1 != self.old_generation {
+            return None;
+        }
+        let new_idx = (*self.constraints.get(constr.0)?)?;
+        Some(ConstraintRef(new_idx, self.new_generation))
+    }
+
+    /// Number of the variables that were removed by compaction.
+    pub fn num_removed_vars(&self) -> usize {
+        self.vars.iter().filter(|v| v.is_none()).count()
+    }
+
+    /// Number of the constraints that were removed by compaction.
+    pub fn num_removed_constraints(&self) -> usize {
+        self.constraints.iter().filter(|c| c.is_none()).count()
+    }
+}
+
+/// New indices of the kept entities in the same order.
+fn new_indices(is_removed: &[bool]) -> Vec<Option<usize>> {
+    let mut num_kept = 0;
+    is_removed
+        .iter()
+        .map(|&is_removed| {
+            if is_removed {
+                None
+            } else {
+                num_kept += 1;
+                Some(num_kept - 1)
+            }
+        })
+        .collect()
+}
+
+/// Keep the elements of `vec` that are not removed.
+fn retain<T>(vec: &mut Vec<T>, is_removed: &[bool]) {
+    let mut removed = is_removed.iter();
+    vec.retain(|_| !removed.next().unwrap());
+}
+
+pub(crate) fn compact(problem: &mut Problem) -> Remapping {
+    let old_generation = problem.generation;
+    let new_generation = old_generation + 1;
+    let vars = new_indices(&problem.is_var_removed);
+    let constraints = new_indices(&problem.is_constraint_removed);
+
+    let is_var_removed = std::mem::take(&mut problem.is_var_removed);
+    retain(&mut problem.obj_coeffs, &is_var_removed);
+    retain(&mut problem.var_mins, &is_var_removed);
+    retain(&mut problem.var_maxs, &is_var_removed);
+    retain(&mut problem.var_names, &is_var_removed);
+    retain(&mut problem.var_transforms, &is_var_removed);
+    let num_vars = problem.obj_coeffs.len();
+    problem.is_var_removed = vec![false; num_vars];
+
+    let is_constraint_removed = std::mem::take(&mut problem.is_constraint_removed);
+    retain(&mut problem.constraints, &is_constraint_removed);
+    for (coeffs, _, _) in &mut problem.constraints {
+        // Removed variables have no coefficients and the order of the kept ones is preserved,
+        // so the indices stay sorted.
+        let (indices, data) = coeffs
+            .iter()
+            .map(|(v, &coeff)| (vars[v].unwrap(), coeff))
+            .unzip();
+        *coeffs = CsVec::new(num_vars, indices, data);
+    }
+    problem.is_constraint_removed = vec![false; problem.constraints.len()];
+
+    let remapping = Remapping {
+        old_generation,
+        new_generation,
+        vars,
+        constraints,
+    };
+    problem.var_tags = problem.var_tags.remap(|var| remapping.var(var));
+    problem.constraint_tags = problem
+        .constraint_tags
+        .remap(|constr| remapping.constraint(constr));
+    problem.generation = new_generation;
+    remapping
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn compact() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 4.0));
+        let y = problem.add_var(2.0, (0.0, 3.0));
+        let z = problem.add_var(1.0, (0.0, 1.0));
+        problem.set_var_name(z, "z");
+        problem.tag_var(z, "t");
+        problem.tag_var(x, "t");
+        let c1 = problem.add_constraint([(x, 1.0), (y, 1.0), (z, 1.0)], ComparisonOp::Le, 4.0);
+        let c2 = problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+        let c3 = problem.add_constraint([(y, 1.0), (z, 1.0)], ComparisonOp::Le, 3.0);
+        problem.tag_constraint(c3, 0);
+
+        problem.remove_var(x).unwrap();
+        problem.remove_constraint(c2).unwrap();
+        assert_eq!(
+            problem.try_add_constraint([(x, 1.0)], ComparisonOp::Le, 1.0),
+            Err(ModelError::InvalidVar { var: x })
+        );
+        assert_eq!(
+            problem.remove_constraint(c2),
+            Err(ModelError::InvalidConstraint { constr: c2 })
+        );
+        assert_eq!(problem.vars_with_tag("t").collect::<Vec<_>>(), [z]);
+        assert_eq!(problem.solve().unwrap().objective(), 6.0);
+
+        let remapping = problem.compact();
+        assert_eq!(remapping.num_removed_vars(), 1);
+        assert_eq!(remapping.num_removed_constraints(), 1);
+        assert_eq!(remapping.var(x), None);
+        assert_eq!(remapping.constraint(c2), None);
+        let (y, z) = (remapping.var(y).unwrap(), remapping.var(z).unwrap());
+        let (c1, c3) = (
+            remapping.constraint(c1).unwrap(),
+            remapping.constraint(c3).unwrap(),
+        );
+        assert_eq!((y.idx(), z.idx(), c1.idx(), c3.idx()), (0, 1, 0, 1));
+        // The references of the old generation can't be translated again.
+        assert_eq!(remapping.var(y), None);
+
+        assert_eq!(problem.stats().num_vars, 2);
+        assert_eq!(problem.stats().num_constraints, 2);
+        assert_eq!(problem.var_name(z), "z");
+        assert_eq!(problem.vars_with_tag("t").collect::<Vec<_>>(), [z]);
+        assert_eq!(problem.constraints_with_tag(0).collect::<Vec<_>>(), [c3]);
+        assert_eq!(
+            problem.constraint_terms(c1).collect::<Vec<_>>(),
+            [(y, 1.0), (z, 1.0)]
+        );
+        let solution = problem.solve().unwrap();
+        assert_eq!(solution.objective(), 6.0);
+        assert_eq!((solution[y], solution[z]), (3.0, 0.0));
+
+        let mut stale = LinearExpr::empty();
+        stale.add(Variable(0, 0), 1.0);
+        assert_eq!(
+            problem.try_add_constraint(stale, ComparisonOp::Le, 1.0),
+            Err(ModelError::StaleExpr {
+                constr: ConstraintRef(2, 1)
+            })
+        );
+    }
+}
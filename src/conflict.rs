@@ -0,0 +1,92 @@
+use crate::{ComparisonOp, Cut, ErrorKind, Solution, Variable};
+
+/// Solve the problem with the vars fixed and return true if it is infeasible.
+fn is_infeasible(solution: &Solution, fixings: &[(Variable, f64)]) -> bool {
+    let mut cur = solution.clone();
+    for &(var, val) in fixings {
+        cur = match cur.fix_var(var, val) {
+            Ok(cur) => cur,
+            Err(err) => return err.kind() == ErrorKind::Infeasible,
+        };
+    }
+    false
+}
+
+pub(crate) fn conflict_cut(solution: &Solution, fixings: &[(Variable, f64)]) -> Option<Cut> {
+    if !is_infeasible(solution, fixings) {
+        return None;
+    }
+
+    // Deletion filter: drop the fixings that are not needed for the infeasibility. The
+    // rest is a minimal conflict (removing any single fixing makes the problem feasible).
+    let mut conflict = fixings.to_vec();
+    let mut i = 0;
+    while i < conflict.len() {
+        let mut reduced = conflict.clone();
+        reduced.remove(i);
+        if is_infeasible(solution, &reduced) {
+            conflict = reduced;
+        } else {
+            i += 1;
+        }
+    }
+    trace!(
+        "conflict analysis: reduced {} fixings to {}",
+        fixings.len(),
+        conflict.len(),
+    );
+
+    // At least one of the binary vars in the conflict must take the other value:
+    // sum(x fixed to 0) + sum(1 - x fixed to 1) >= 1.
+    let mut terms = vec![];
+    let mut rhs = 1.0;
+    for &(var, val) in &conflict {
+        if solution.solver.var_bounds(var.0) != (0.0, 1.0) {
+            return None;
+        }
+        if val == 0.0 {
+            terms.push((var, 1.0));
+        } else if val == 1.0 {
+            terms.push((var, -1.0));
+            rhs -= 1.0;
+        } else {
+            return None;
+        }
+    }
+    Some(Cut::new(terms, ComparisonOp::Ge, rhs))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn conflict() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let vars: Vec<_> = (0..4).map(|_| problem.add_var(1.0, (0.0, 1.0))).collect();
+        let y = problem.add_var(0.0, (0.0, 2.0));
+        // vars[0] and vars[2] can't both be 1, vars[1] doesn't matter.
+        problem.add_constraint([(vars[0], 1.0), (vars[2], 1.0)], ComparisonOp::Le, 1.5);
+        problem.add_constraint([(vars[3], 1.0), (y, 1.0)], ComparisonOp::Ge, 0.5);
+        let sol = problem.solve().unwrap();
+
+        let fixings = [
+            (vars[0], 1.0),
+            (vars[1], 0.0),
+            (vars[3], 0.0),
+            (vars[2], 1.0),
+        ];
+        let cut = sol.conflict_cut(&fixings).unwrap();
+        assert_eq!(cut.rhs(), 1.0);
+        let lhs = cut.lhs();
+        assert_eq!(lhs.vars, vec![vars[0].0, vars[2].0]);
+        assert_eq!(lhs.coeffs, vec![1.0, 1.0]);
+
+        // Feasible fixings.
+        assert!(sol.conflict_cut(&fixings[..3]).is_none());
+
+        // The conflict includes a non-binary var.
+        let fixings = [(vars[3], 0.0), (y, 0.0)];
+        assert!(sol.conflict_cut(&fixings).is_none());
+    }
+}
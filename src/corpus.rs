@@ -0,0 +1,465 @@
+use crate::{
+    is_approx_eq, Error, LpFile, MpsFile, OptimizationDirection, Problem, Solution, SolveReport,
+    SolveStatus, Variable,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead},
+    path::Path,
+};
+
+/// An instance of a [`Corpus`] together with its known solution.
+///
+/// [`Corpus`]: struct.Corpus.html
+#[derive(Clone, Debug)]
+pub struct CorpusInstance {
+    /// Name of the instance.
+    pub name: String,
+    /// The problem.
+    pub problem: Problem,
+    /// A mapping of a variable name to the corresponding [`Variable`].
+    pub variables: HashMap<String, Variable>,
+    /// Variables that must take integer values (empty for linear programs).
+    pub integer_vars: Vec<Variable>,
+    /// Status of solving the problem (the linear relaxation for mixed-integer programs).
+    pub status: SolveStatus,
+    /// Optimal objective value of the problem (the linear relaxation for mixed-integer
+    /// programs) if `status` is optimal.
+    pub objective: Option<f64>,
+    /// Optimal objective value of the mixed-integer program (if any).
+    pub integer_objective: Option<f64>,
+}
+
+impl CorpusInstance {
+    /// Check that `result` of solving the problem matches the known solution: the status
+    /// and the objective value must be the same (up to `tol` relative to the magnitude of
+    /// the objective) and the solution must satisfy the constraints and the variable bounds
+    /// with the same tolerance.
+    ///
+    /// # Panics
+    ///
+    /// Will panic with the description of the mismatch if `result` doesn't match the known
+    /// solution.
+    pub fn assert_result(&self, result: &Result<Solution, Error>, tol: f64) {
+        let report = SolveReport::new(&self.problem, result);
+        assert_eq!(
+            report.status, self.status,
+            "instance {}: unexpected status (error: {:?})",
+            self.name, report.error
+        );
+        if let (Some(objective), Some(expected)) = (report.objective, self.objective) {
+            assert!(
+                is_approx_eq(objective, expected, tol),
+                "instance {}: objective {} differs from the optimal objective {}",
+                self.name,
+                objective,
+                expected
+            );
+        }
+        if let Some(violation) = report.max_primal_violation {
+            assert!(
+                violation <= tol,
+                "instance {}: constraint violation {}",
+                self.name,
+                violation
+            );
+        }
+    }
+}
+
+/// A set of test instances with known solutions for integration tests of models and
+/// of the solver.
+///
+/// [`embedded`] returns a small curated set of instances shipped with the crate
+/// (linear programs covering degenerate, infeasible and unbounded cases and mixed-integer
+/// programs with fractional relaxations). [`load_dir`] reads user-provided instances.
+///
+/// [`embedded`]: #method.embedded
+/// [`load_dir`]: #method.load_dir
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let corpus = Corpus::embedded();
+/// for instance in &corpus.instances {
+///     instance.assert_result(&instance.problem.solve(), 1e-9);
+/// }
+///
+/// let knapsack = corpus.get("knapsack").unwrap();
+/// assert_eq!(knapsack.objective, Some(22.0));
+/// assert_eq!(knapsack.integer_objective, Some(21.0));
+/// assert_eq!(knapsack.integer_vars.len(), 4);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Corpus {
+    /// The instances in the order of their names.
+    pub instances: Vec<CorpusInstance>,
+}
+
+impl Corpus {
+    /// The instances shipped with the crate.
+    pub fn embedded() -> Self {
+        let instances = EMBEDDED
+            .iter()
+            .map(|embedded| {
+                let file = LpFile::parse(embedded.lp.as_bytes()).unwrap();
+                let integer_vars = (embedded.integer_vars.iter())
+                    .map(|name| file.variables[*name])
+                    .collect();
+                CorpusInstance {
+                    name: embedded.name.to_owned(),
+                    problem: file.problem,
+                    variables: file.variables,
+                    integer_vars,
+                    status: embedded.status,
+                    objective: embedded.objective,
+                    integer_objective: embedded.integer_objective,
+                }
+            })
+            .collect();
+        Corpus { instances }
+    }
+
+    /// Load the instances from the files in the directory `path`.
+    ///
+    /// Files with the `.lp` extension are parsed with [`LpFile::parse`] and files with
+    /// the `.mps` extension with [`MpsFile::parse`] (minimizing unless the file has the
+    /// OBJSENSE section). The name of an instance is the file name without the extension.
+    /// The parsers don't support integer variables, so all instances are linear programs.
+    ///
+    /// Known solutions are read from the files with the `.solu` extension in the format
+    /// used by MIPLIB: each line is either `=opt= <name> <objective>` for an instance with
+    /// the known optimal objective value, `=inf= <name>` for an infeasible instance or
+    /// `=unbd= <name>` for an unbounded one. Other lines are ignored.
+    ///
+    /// [`LpFile::parse`]: struct.LpFile.html#method.parse
+    /// [`MpsFile::parse`]: struct.MpsFile.html#method.parse
+    ///
+    /// # Errors
+    ///
+    /// Apart from I/O errors and parsing errors of the files (with the file name added
+    /// to the message), this function will signal an instance without a known solution
+    /// as [`std::io::Error`] with the kind set to [`InvalidData`](std::io::ErrorKind::InvalidData).
+    pub fn load_dir(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut problem_files = vec![];
+        let mut solutions = HashMap::new();
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            let (name, extension) = match (path.file_stem(), path.extension()) {
+                (Some(name), Some(extension)) => (name.to_string_lossy(), extension),
+                _ => continue,
+            };
+            if extension == "lp" || extension == "mps" {
+                problem_files.push((name.into_owned(), path.clone()));
+            } else if extension == "solu" {
+                let file = io::BufReader::new(fs::File::open(&path)?);
+                parse_solu(file, &mut solutions).map_err(|err| in_file(&path, err))?;
+            }
+        }
+        problem_files.sort();
+
+        let mut instances = vec![];
+        for (name, path) in problem_files {
+            let file = io::BufReader::new(fs::File::open(&path)?);
+            let parsed = if path.extension().unwrap() == "lp" {
+                LpFile::parse(file).map(|file| (file.problem, file.variables))
+            } else {
+                MpsFile::parse(file, OptimizationDirection::Minimize)
+                    .map(|file| (file.problem, file.variables))
+            };
+            let (problem, variables) = parsed.map_err(|err| in_file(&path, err))?;
+
+            let (status, objective) = match solutions.get(&name) {
+                Some(&solution) => solution,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("no known solution for instance {}", name),
+                    ));
+                }
+            };
+            instances.push(CorpusInstance {
+                name,
+                problem,
+                variables,
+                integer_vars: vec![],
+                status,
+                objective,
+                integer_objective: None,
+            });
+        }
+        Ok(Corpus { instances })
+    }
+
+    /// The instance with the `name` (if any).
+    pub fn get(&self, name: &str) -> Option<&CorpusInstance> {
+        self.instances.iter().find(|instance| instance.name == name)
+    }
+}
+
+/// Parse the known solutions in the MIPLIB `.solu` format into `solutions`.
+fn parse_solu(
+    input: impl BufRead,
+    solutions: &mut HashMap<String, (SolveStatus, Option<f64>)>,
+) -> io::Result<()> {
+    for (idx, line) in input.lines().enumerate() {
+        let line = line?;
+        let err = |msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", idx + 1, msg),
+            )
+        };
+        let mut tokens = line.split_whitespace();
+        let tag = tokens.next();
+        if !matches!(tag, Some("=opt=") | Some("=inf=") | Some("=unbd=")) {
+            continue;
+        }
+        let name = tokens
+            .next()
+            .ok_or_else(|| err("expected the instance name"))?;
+        let solution = match tag {
+            Some("=opt=") => match tokens.next().map(str::parse::<f64>) {
+                Some(Ok(objective)) if objective.is_finite() => {
+                    (SolveStatus::Optimal, Some(objective))
+                }
+                _ => return Err(err("expected the objective value")),
+            },
+            Some("=inf=") => (SolveStatus::Infeasible, None),
+            _ => (SolveStatus::Unbounded, None),
+        };
+        solutions.insert(name.to_owned(), solution);
+    }
+    Ok(())
+}
+
+fn in_file(path: &Path, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+}
+
+struct EmbeddedInstance {
+    name: &'static str,
+    lp: &'static str,
+    integer_vars: &'static [&'static str],
+    status: SolveStatus,
+    objective: Option<f64>,
+    integer_objective: Option<f64>,
+}
+
+/// Embedded instances in the order of their names.
+const EMBEDDED: &[EmbeddedInstance] = &[
+    EmbeddedInstance {
+        name: "degenerate",
+        lp: "\
+Minimize
+ obj: -x - y
+Subject To
+ c0: x <= 1
+ c1: y <= 1
+ c2: x + y <= 2
+ c3: x - y <= 0
+End
+",
+        integer_vars: &[],
+        status: SolveStatus::Optimal,
+        objective: Some(-2.0),
+        integer_objective: None,
+    },
+    EmbeddedInstance {
+        name: "free_vars",
+        lp: "\
+Minimize
+ obj: x + y
+Subject To
+ c0: x - y = 1
+ c1: x + 2 y >= 4
+Bounds
+ y free
+End
+",
+        integer_vars: &[],
+        status: SolveStatus::Optimal,
+        objective: Some(3.0),
+        integer_objective: None,
+    },
+    EmbeddedInstance {
+        name: "infeasible",
+        lp: "\
+Minimize
+ obj: x + y
+Subject To
+ c0: x + y <= 1
+ c1: x + y >= 2
+End
+",
+        integer_vars: &[],
+        status: SolveStatus::Infeasible,
+        objective: None,
+        integer_objective: None,
+    },
+    EmbeddedInstance {
+        name: "integer_rounding",
+        lp: "\
+Maximize
+ obj: x + y
+Subject To
+ c0: -x + y <= 1
+ c1: 3 x + 2 y <= 12
+ c2: 2 x + 3 y <= 12
+End
+",
+        integer_vars: &["x", "y"],
+        status: SolveStatus::Optimal,
+        objective: Some(4.8),
+        integer_objective: Some(4.0),
+    },
+    EmbeddedInstance {
+        name: "knapsack",
+        lp: "\
+Maximize
+ obj: 8 x1 + 11 x2 + 6 x3 + 4 x4
+Subject To
+ c0: 5 x1 + 7 x2 + 4 x3 + 3 x4 <= 14
+Bounds
+ x1 <= 1
+ x2 <= 1
+ x3 <= 1
+ x4 <= 1
+End
+",
+        integer_vars: &["x1", "x2", "x3", "x4"],
+        status: SolveStatus::Optimal,
+        objective: Some(22.0),
+        integer_objective: Some(21.0),
+    },
+    EmbeddedInstance {
+        name: "small",
+        lp: "\
+Maximize
+ obj: x + 2 y
+Subject To
+ c0: x + y <= 4
+ c1: 2 x + y >= 2
+Bounds
+ y <= 3
+End
+",
+        integer_vars: &[],
+        status: SolveStatus::Optimal,
+        objective: Some(7.0),
+        integer_objective: None,
+    },
+    EmbeddedInstance {
+        name: "transportation",
+        lp: "\
+Minimize
+ obj: 8 x11 + 6 x12 + 10 x13 + 9 x21 + 12 x22 + 13 x23
+Subject To
+ s1: x11 + x12 + x13 <= 20
+ s2: x21 + x22 + x23 <= 30
+ d1: x11 + x21 >= 10
+ d2: x12 + x22 >= 25
+ d3: x13 + x23 >= 15
+End
+",
+        integer_vars: &[],
+        status: SolveStatus::Optimal,
+        objective: Some(465.0),
+        integer_objective: None,
+    },
+    EmbeddedInstance {
+        name: "unbounded",
+        lp: "\
+Maximize
+ obj: x + y
+Subject To
+ c0: x - y <= 1
+End
+",
+        integer_vars: &[],
+        status: SolveStatus::Unbounded,
+        objective: None,
+        integer_objective: None,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn embedded() {
+        let corpus = Corpus::embedded();
+        let names: Vec<_> = corpus.instances.iter().map(|i| i.name.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+
+        for instance in &corpus.instances {
+            for &scaling in &[ScalingMode::None, ScalingMode::GeometricMeanEquilibration] {
+                let options = SolverOptions {
+                    scaling,
+                    ..SolverOptions::default()
+                };
+                instance.assert_result(&instance.problem.solve_with(&options), 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn load_dir() {
+        let dir = std::env::temp_dir().join(format!("minilp-corpus-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let small = Corpus::embedded().get("small").unwrap().problem.to_string();
+        std::fs::write(dir.join("small.lp"), small).unwrap();
+        let mps = "\
+NAME example
+ROWS
+ N obj
+ G c0
+COLUMNS
+ x obj 1 c0 1
+RHS
+ rhs c0 2
+ENDATA
+";
+        std::fs::write(dir.join("example.mps"), mps).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not an instance").unwrap();
+        std::fs::write(
+            dir.join("corpus.solu"),
+            "=opt= small 7\n=opt= example 2.0\n=best= other 1\n",
+        )
+        .unwrap();
+
+        let corpus = Corpus::load_dir(&dir).unwrap();
+        let names: Vec<_> = corpus.instances.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["example", "small"]);
+        for instance in &corpus.instances {
+            instance.assert_result(&instance.problem.solve(), 1e-9);
+        }
+        assert_eq!(corpus.get("example").unwrap().objective, Some(2.0));
+
+        // An instance without a known solution.
+        std::fs::write(dir.join("corpus.solu"), "=opt= small 7\n").unwrap();
+        let err = Corpus::load_dir(&dir).unwrap_err();
+        assert_eq!(err.to_string(), "no known solution for instance example");
+
+        std::fs::write(dir.join("corpus.solu"), "=opt= small\n").unwrap();
+        let err = Corpus::load_dir(&dir).unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("corpus.solu: line 1: expected the objective value"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "instance small: objective 7 differs from the optimal objective 8")]
+    fn wrong_objective() {
+        let mut instance = Corpus::embedded().get("small").unwrap().clone();
+        instance.objective = Some(8.0);
+        instance.assert_result(&instance.problem.solve(), 1e-9);
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{solve_log::LogValue, ComparisonOp, Error, LinearExpr, Solution};
+use std::collections::HashMap;
+
+/// Cuts violated by less than this are considered satisfied.
+const VIOLATION_TOL: f64 = 1e-6;
+/// Relative tolerance for considering two cuts parallel.
+const PARALLEL_TOL: f64 = 1e-9;
+/// Default number of consecutive separation rounds in which a cut can be non-violated
+/// before it is discarded.
+const DEFAULT_MAX_AGE: usize = 10;
+
+/// A cutting plane: a linear inequality that is valid for the integer solutions of
+/// the problem but (usually) violated by a fractional solution of the LP relaxation.
+#[derive(Clone, Debug)]
+pub struct Cut {
+    // The cut is stored as sum(coeffs[i] * vars[i]) <= rhs with vars sorted and unique.
+    vars: Vec<usize>,
+    coeffs: Vec<f64>,
+    rhs: f64,
+}
+
+impl Cut {
+    /// Create a cut from the inequality `expr cmp_op rhs`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `cmp_op` is [`ComparisonOp::Eq`].
+    pub fn new(expr: impl Into<LinearExpr>, cmp_op: ComparisonOp, rhs: f64) -> Self {
+        let sign = match cmp_op {
+            ComparisonOp::Le => 1.0,
+            ComparisonOp::Ge => -1.0,
+            ComparisonOp::Eq => panic!("cut must be an inequality"),
+        };
+
+        let (vars, coeffs) = expr.into().into_merged();
+        Cut {
+            vars,
+            coeffs: coeffs.into_iter().map(|coeff| sign * coeff).collect(),
+            rhs: sign * rhs,
+        }
+    }
+
+    /// Left-hand side of the cut in the `lhs <= rhs` form.
+    pub fn lhs(&self) -> LinearExpr {
+        LinearExpr {
+            vars: self.vars.clone(),
+            coeffs: self.coeffs.clone(),
+            generation: None,
+        }
+    }
+
+    /// Right-hand side of the cut in the `lhs <= rhs` form.
+    pub fn rhs(&self) -> f64 {
+        self.rhs
+    }
+
+    /// Amount by which the solution violates the cut (negative if the cut is satisfied).
+    pub fn violation(&self, solution: &Solution) -> f64 {
+        let lhs: f64 = self
+            .vars
+            .iter()
+            .zip(&self.coeffs)
+            .map(|(&var, &coeff)| coeff * solution[solution.var_ref(var)])
+            .sum();
+        lhs - self.rhs
+    }
+
+    /// Violation divided by the euclidean norm of the coefficients, i.e. the distance
+    /// from the solution to the cut hyperplane.
+    pub fn efficacy(&self, solution: &Solution) -> f64 {
+        let norm = self.coeffs.iter().map(|c| c * c).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return 0.0;
+        }
+        self.violation(solution) / norm
+    }
+
+    /// Coefficients and rhs divided by the max abs. coefficient.
+    fn normalized(&self) -> (Vec<f64>, f64) {
+        let max = self.coeffs.iter().fold(0.0, |max: f64, c| max.max(c.abs()));
+        if max == 0.0 {
+            return (self.coeffs.clone(), self.rhs);
+        }
+        (
+            self.coeffs.iter().map(|c| c / max).collect(),
+            self.rhs / max,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PoolEntry {
+    cut: Cut,
+    /// Number of consecutive separation rounds in which the cut was not violated.
+    age: usize,
+}
+
+/// A storage for the generated cuts.
+///
+/// Cuts from different sources are added to the pool, which discards duplicates (cuts that
+/// are parallel to already stored ones), and later separated against the solutions of the LP
+/// relaxation (e.g. at different nodes of a branch-and-bound search). Violated cuts are ranked
+/// by efficacy and cuts that were not violated for a number of rounds are discarded.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Maximize);
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// problem.add_constraint(&[(x, 2.0), (y, 2.0)], ComparisonOp::Le, 3.0);
+///
+/// let mut pool = CutPool::new();
+/// assert!(pool.add(Cut::new(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 1.0)));
+/// // The same cut multiplied by 2 is a duplicate.
+/// assert!(!pool.add(Cut::new(&[(x, 2.0), (y, 2.0)], ComparisonOp::Le, 2.0)));
+///
+/// let solution = problem.solve().unwrap();
+/// assert_eq!(solution.objective(), 1.5);
+/// let (solution, num_added) = pool.apply(solution, 10).unwrap();
+/// assert_eq!(num_added, 1);
+/// assert_eq!(solution.objective(), 1.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CutPool {
+    entries: Vec<PoolEntry>,
+    /// Indices of the entries with the given set of vars.
+    by_support: HashMap<Vec<usize>, Vec<usize>>,
+    max_age: usize,
+}
+
+impl Default for CutPool {
+    fn default() -> Self {
+        CutPool::new()
+    }
+}
+
+impl CutPool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        CutPool::with_max_age(DEFAULT_MAX_AGE)
+    }
+
+    /// Create an empty pool that discards cuts after `max_age` consecutive separation
+    /// rounds in which they were not violated.
+    pub fn with_max_age(max_age: usize) -> Self {
+        CutPool {
+            entries: vec![],
+            by_support: HashMap::new(),
+            max_age,
+        }
+    }
+
+    /// Number of cuts in the pool.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the pool contains no cuts.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add the cut to the pool. Returns `false` if the pool already contains a parallel cut
+    /// (if the new cut is tighter, it replaces the stored one).
+    pub fn add(&mut self, cut: Cut) -> bool {
+        let (coeffs, rhs) = cut.normalized();
+        let same_support = self.by_support.entry(cut.vars.clone()).or_default();
+        for &idx in same_support.iter() {
+            let entry = &mut self.entries[idx];
+            let (other_coeffs, other_rhs) = entry.cut.normalized();
+            let is_parallel = coeffs
+                .iter()
+                .zip(&other_coeffs)
+                .all(|(a, b)| f64::abs(a - b) <= PARALLEL_TOL);
+            if is_parallel {
+                if rhs < other_rhs {
+                    entry.cut = cut;
+                    entry.age = 0;
+                }
+                return false;
+            }
+        }
+
+        same_support.push(self.entries.len());
+        self.entries.push(PoolEntry { cut, age: 0 });
+        true
+    }
+
+    /// Find up to `max_cuts` cuts violated by the solution, in order of decreasing efficacy.
+    ///
+    /// This counts as a separation round: cuts that are not violated get older and cuts older
+    /// than the max age are discarded.
+    pub fn separate(&mut self, solution: &Solution, max_cuts: usize) -> Vec<Cut> {
+        let mut violated = vec![];
+        for (idx, entry) in self.entries.iter_mut().enumerate() {
+            if entry.cut.violation(solution) > VIOLATION_TOL {
+                violated.push((entry.cut.efficacy(solution), idx));
+            } else {
+                entry.age += 1;
+            }
+        }
+        violated.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        violated.truncate(max_cuts);
+
+        let mut res = vec![];
+        for &(_, idx) in &violated {
+            self.entries[idx].age = 0;
+            res.push(self.entries[idx].cut.clone());
+        }
+
+        let max_age = self.max_age;
+        let num_entries = self.entries.len();
+        self.entries.retain(|e| e.age <= max_age);
+        if self.entries.len() != num_entries {
+            self.by_support.clear();
+            for (idx, entry) in self.entries.iter().enumerate() {
+                let support = entry.cut.vars.clone();
+                self.by_support.entry(support).or_default().push(idx);
+            }
+        }
+
+        res
+    }
+
+    /// Separate up to `max_cuts` violated cuts (see [`separate`](#method.separate)), add them
+    /// to the solution and return the updated solution along with the number of added cuts.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the problem becomes infeasible with the added cuts.
+    pub fn apply(
+        &mut self,
+        solution: Solution,
+        max_cuts: usize,
+    ) -> Result<(Solution, usize), Error> {
+        let cuts = self.separate(&solution, max_cuts);
+        let mut solution = solution;
+        for cut in &cuts {
+            solution = solution.add_constraint(cut.lhs(), ComparisonOp::Le, cut.rhs)?;
+        }
+        if !cuts.is_empty() {
+            solution
+                .solver
+                .log_event("cuts", &[("count", LogValue::Int(cuts.len()))]);
+        }
+        Ok((solution, cuts.len()))
+    }
+}
+
+/// Add the violated cuts returned by the separator to the solution and reoptimize until
+/// the separator returns no violated cuts. Returns the final solution and the number of
+/// added cuts.
+pub(crate) fn separation_loop(
+    mut solution: Solution,
+    mut separator: impl FnMut(&Solution) -> Vec<Cut>,
+) -> Result<(Solution, usize), Error> {
+    let mut num_added = 0;
+    loop {
+        let cuts = separator(&solution);
+        let mut num_violated = 0;
+        for cut in cuts {
+            // Cuts are checked against the solution they were separated from, reoptimizing
+            // after each one would be too costly.
+            if cut.violation(&solution) > VIOLATION_TOL {
+                solution = solution.add_constraint(cut.lhs(), ComparisonOp::Le, cut.rhs)?;
+                num_violated += 1;
+            }
+        }
+        if num_violated == 0 {
+            return Ok((solution, num_added));
+        }
+        solution
+            .solver
+            .log_event("cuts", &[("count", LogValue::Int(num_violated))]);
+        num_added += num_violated;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn duplicates() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+
+        let mut pool = CutPool::new();
+        assert!(pool.add(Cut::new([(x, 1.0), (y, 2.0)], ComparisonOp::Ge, 2.0)));
+        // Terms are merged and sorted.
+        assert!(!pool.add(Cut::new(
+            [(y, 3.0), (x, 2.0), (y, 1.0)],
+            ComparisonOp::Ge,
+            4.0
+        )));
+        // Different direction.
+        assert!(pool.add(Cut::new([(x, 1.0), (y, 2.0)], ComparisonOp::Le, 2.0)));
+        // Tighter rhs replaces the stored cut.
+        assert!(!pool.add(Cut::new([(x, -1.0), (y, -2.0)], ComparisonOp::Le, -3.0)));
+        assert_eq!(pool.len(), 2);
+
+        let sol = problem.solve().unwrap();
+        let cuts = pool.separate(&sol, 10);
+        assert_eq!(cuts.len(), 1);
+        assert_eq!(cuts[0].rhs(), -3.0);
+        assert!(f64::abs(cuts[0].efficacy(&sol) - 3.0 / f64::sqrt(5.0)) < 1e-8);
+    }
+
+    #[test]
+    fn aging() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 4.0));
+        let y = problem.add_var(2.0, (0.0, 4.0));
+
+        let mut pool = CutPool::with_max_age(1);
+        pool.add(Cut::new([(x, 1.0)], ComparisonOp::Le, 3.0));
+        pool.add(Cut::new([(y, 1.0)], ComparisonOp::Le, 2.0));
+        pool.add(Cut::new([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 5.0));
+        pool.add(Cut::new([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 0.0));
+
+        let sol = problem.solve().unwrap();
+        let (sol, num_added) = pool.apply(sol, 2).unwrap();
+        assert_eq!(num_added, 2);
+        assert_eq!((sol[x], sol[y]), (3.0, 2.0));
+        assert_eq!(pool.len(), 4);
+
+        // The last cut wasn't violated for two rounds.
+        assert!(pool.separate(&sol, 10).is_empty());
+        assert_eq!(pool.len(), 3);
+        assert!(pool.separate(&sol, 10).is_empty());
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn separation_loop() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+
+        // Lazily enforce x + y <= 4 - k for rounds k = 0, 1, 2 and return a satisfied cut
+        // on every round.
+        let mut rounds = 0;
+        let (sol, num_added) = problem
+            .solve()
+            .unwrap()
+            .separate(|sol| {
+                rounds += 1;
+                let mut cuts = vec![Cut::new([(x, 1.0)], ComparisonOp::Le, 10.0)];
+                let rhs = f64::max(4.0 - (rounds - 1) as f64, 2.0);
+                if sol[x] + sol[y] > rhs {
+                    cuts.push(Cut::new([(x, 1.0), (y, 1.0)], ComparisonOp::Le, rhs));
+                }
+                cuts
+            })
+            .unwrap();
+        assert_eq!(num_added, 3);
+        assert_eq!(rounds, 4);
+        assert_eq!(sol.objective(), 2.0);
+
+        let res =
+            problem.solve_with_separation(|_| vec![Cut::new([(x, 1.0)], ComparisonOp::Ge, 11.0)]);
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Infeasible);
+    }
+}
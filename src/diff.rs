@@ -0,0 +1,455 @@
+use crate::{ComparisonOp, ConstraintRef, OptimizationDirection, Problem};
+use std::collections::HashMap;
+
+/// A single difference between two problems found by [`Problem::diff`].
+///
+/// Variables are identified by their names (see [`Problem::var_name`]) and constraints by
+/// their indices. Objective coefficients are given as they were specified when adding the
+/// variables, i.e. in the direction of the respective problem.
+///
+/// [`Problem::diff`]: struct.Problem.html#method.diff
+/// [`Problem::var_name`]: struct.Problem.html#method.var_name
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProblemDifference {
+    /// The optimization direction differs.
+    Direction {
+        /// Direction of the old problem.
+        old: OptimizationDirection,
+        /// Direction of the new problem.
+        new: OptimizationDirection,
+    },
+    /// The constant term of the objective differs (see [`Problem::objective_offset`]).
+    ///
+    /// [`Problem::objective_offset`]: struct.Problem.html#method.objective_offset
+    ObjOffset {
+        /// Offset in the old problem.
+        old: f64,
+        /// Offset in the new problem.
+        new: f64,
+    },
+    /// The variable is present only in the new problem.
+    VarAdded {
+        /// Name of the variable.
+        var: String,
+    },
+    /// The variable is present only in the old problem.
+    VarRemoved {
+        /// Name of the variable.
+        var: String,
+    },
+    /// The objective coefficient of the variable differs.
+    ObjCoeff {
+        /// Name of the variable.
+        var: String,
+        /// Coefficient in the old problem.
+        old: f64,
+        /// Coefficient in the new problem.
+        new: f64,
+    },
+    /// The bounds of the variable differ.
+    Bounds {
+        /// Name of the variable.
+        var: String,
+        /// Bounds in the old problem.
+        old: (f64, f64),
+        /// Bounds in the new problem.
+        new: (f64, f64),
+    },
+    /// The constraint is present only in the new problem.
+    ConstraintAdded {
+        /// The constraint.
+        constr: ConstraintRef,
+    },
+    /// The constraint is present only in the old problem.
+    ConstraintRemoved {
+        /// The constraint.
+        constr: ConstraintRef,
+    },
+    /// The comparison operator of the constraint differs.
+    ComparisonOp {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// Operator in the old problem.
+        old: ComparisonOp,
+        /// Operator in the new problem.
+        new: ComparisonOp,
+    },
+    /// The right-hand side of the constraint differs.
+    Rhs {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// Right-hand side in the old problem.
+        old: f64,
+        /// Right-hand side in the new problem.
+        new: f64,
+    },
+    /// The coefficient of the variable in the constraint differs (missing coefficients
+    /// are zero).
+    Coeff {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// Name of the variable.
+        var: String,
+        /// Coefficient in the old problem.
+        old: f64,
+        /// Coefficient in the new problem.
+        new: f64,
+    },
+}
+
+impl std::fmt::Display for ProblemDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProblemDifference::Direction { old, new } => {
+                write!(f, "direction changed from {:?} to {:?}", old, new)
+            }
+            ProblemDifference::ObjOffset { old, new } => {
+                write!(f, "objective offset changed from {} to {}", old, new)
+            }
+            ProblemDifference::VarAdded { var } => write!(f, "variable {} added", var),
+            ProblemDifference::VarRemoved { var } => write!(f, "variable {} removed", var),
+            ProblemDifference::ObjCoeff { var, old, new } => write!(
+                f,
+                "objective coefficient of {} changed from {} to {}",
+                var, old, new
+            ),
+            ProblemDifference::Bounds { var, old, new } => write!(
+                f,
+                "bounds of {} changed from [{}, {}] to [{}, {}]",
+                var, old.0, old.1, new.0, new.1
+            ),
+            ProblemDifference::ConstraintAdded { constr } => {
+                write!(f, "constraint {} added", constr.0)
+            }
+            ProblemDifference::ConstraintRemoved { constr } => {
+                write!(f, "constraint {} removed", constr.0)
+            }
+            ProblemDifference::ComparisonOp { constr, old, new } => write!(
+                f,
+                "operator of constraint {} changed from {:?} to {:?}",
+                constr.0, old, new
+            ),
+            ProblemDifference::Rhs { constr, old, new } => write!(
+                f,
+                "rhs of constraint {} changed from {} to {}",
+                constr.0, old, new
+            ),
+            ProblemDifference::Coeff {
+                constr,
+                var,
+                old,
+                new,
+            } => write!(
+                f,
+                "coefficient of {} in constraint {} changed from {} to {}",
+                var, constr.0, old, new
+            ),
+        }
+    }
+}
+
+/// A list of differences between two problems, see [`Problem::diff`].
+///
+/// [`Problem::diff`]: struct.Problem.html#method.diff
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProblemDiff {
+    /// Differences found: the direction first, then the variables (in the order of the old
+    /// problem, then the added ones) and then the constraints.
+    pub differences: Vec<ProblemDifference>,
+}
+
+impl ProblemDiff {
+    /// Returns `true` if the problems are the same.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl std::fmt::Display for ProblemDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for difference in &self.differences {
+            writeln!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+fn obj_coeff(problem: &Problem, var: usize) -> f64 {
+    match problem.direction {
+        OptimizationDirection::Minimize => problem.obj_coeffs[var],
+        OptimizationDirection::Maximize => -problem.obj_coeffs[var],
+    }
+}
+
+pub(crate) fn diff(old: &Problem, new: &Problem) -> ProblemDiff {
+    let mut res = vec![];
+    if old.direction != new.direction {
+        res.push(ProblemDifference::Direction {
+            old: old.direction,
+            new: new.direction,
+        });
+    }
+    let (old_offset, new_offset) = (old.objective_offset(), new.objective_offset());
+    if old_offset != new_offset {
+        res.push(ProblemDifference::ObjOffset {
+            old: old_offset,
+            new: new_offset,
+        });
+    }
+
+    // Match the variables by name. If names are repeated, the first one wins.
+    let names = |problem: &Problem| -> Vec<String> {
+        (0..problem.obj_coeffs.len())
+            .map(|v| problem.var_name_at(v).into_owned())
+            .collect()
+    };
+    let old_names = names(old);
+    let new_names = names(new);
+    let mut new_name2idx = HashMap::new();
+    for (v, name) in new_names.iter().enumerate() {
+        if !new.is_var_removed[v] {
+            new_name2idx.entry(name.as_str()).or_insert(v);
+        }
+    }
+    // Indices of the matching vars in the other problem.
+    let mut old2new = vec![None; old_names.len()];
+    let mut new2old = vec![None; new_names.len()];
+    for (old_var, name) in old_names.iter().enumerate() {
+        if old.is_var_removed[old_var] {
+            continue;
+        }
+        if let Some(&new_var) = new_name2idx.get(name.as_str()) {
+            if new2old[new_var].is_none() {
+                old2new[old_var] = Some(new_var);
+                new2old[new_var] = Some(old_var);
+            }
+        }
+    }
+
+    // Removed vars (not yet compacted away) are treated as absent.
+    for (old_var, name) in old_names.iter().enumerate() {
+        let new_var = match old2new[old_var] {
+            Some(new_var) => new_var,
+            None if old.is_var_removed[old_var] => continue,
+            None => {
+                res.push(ProblemDifference::VarRemoved { var: name.clone() });
+                continue;
+            }
+        };
+        let (old_coeff, new_coeff) = (obj_coeff(old, old_var), obj_coeff(new, new_var));
+        if old_coeff != new_coeff {
+            res.push(ProblemDifference::ObjCoeff {
+                var: name.clone(),
+                old: old_coeff,
+                new: new_coeff,
+            });
+        }
+        let old_bounds = (old.var_mins[old_var], old.var_maxs[old_var]);
+        let new_bounds = (new.var_mins[new_var], new.var_maxs[new_var]);
+        if old_bounds != new_bounds {
+            res.push(ProblemDifference::Bounds {
+                var: name.clone(),
+                old: old_bounds,
+                new: new_bounds,
+            });
+        }
+    }
+    for (new_var, name) in new_names.iter().enumerate() {
+        if new2old[new_var].is_none() && !new.is_var_removed[new_var] {
+            res.push(ProblemDifference::VarAdded { var: name.clone() });
+        }
+    }
+
+    let mut new_coeffs = vec![0.0; new_names.len()];
+    for (i, (old_constr, new_constr)) in old.constraints.iter().zip(&new.constraints).enumerate() {
+        let constr = new.constraint_ref(i);
+        match (old.is_constraint_removed[i], new.is_constraint_removed[i]) {
+            (false, false) => {}
+            (true, true) => continue,
+            (false, true) => {
+                res.push(ProblemDifference::ConstraintRemoved {
+                    constr: old.constraint_ref(i),
+                });
+                continue;
+            }
+            (true, false) => {
+                res.push(ProblemDifference::ConstraintAdded { constr });
+                continue;
+            }
+        }
+        let (old_row, old_op, old_rhs) = old_constr;
+        let (new_row, new_op, new_rhs) = new_constr;
+        if old_op != new_op {
+            res.push(ProblemDifference::ComparisonOp {
+                constr,
+                old: *old_op,
+                new: *new_op,
+            });
+        }
+        if old_rhs != new_rhs {
+            res.push(ProblemDifference::Rhs {
+                constr,
+                old: *old_rhs,
+                new: *new_rhs,
+            });
+        }
+
+        // Coefficients of the added and removed vars are not reported.
+        for (new_var, &coeff) in new_row.iter() {
+            new_coeffs[new_var] += coeff;
+        }
+        let mut coeff_diffs = vec![];
+        for (old_var, &old_coeff) in old_row.iter() {
+            if let Some(new_var) = old2new[old_var] {
+                let new_coeff = std::mem::replace(&mut new_coeffs[new_var], 0.0);
+                if old_coeff != new_coeff {
+                    coeff_diffs.push((old_var, old_coeff, new_coeff));
+                }
+            }
+        }
+        // Coefficients remaining in new_coeffs are missing in the old row.
+        for (new_var, _) in new_row.iter() {
+            let new_coeff = std::mem::replace(&mut new_coeffs[new_var], 0.0);
+            if let (Some(old_var), true) = (new2old[new_var], new_coeff != 0.0) {
+                coeff_diffs.push((old_var, 0.0, new_coeff));
+            }
+        }
+
+        coeff_diffs.sort_by_key(|&(old_var, _, _)| old_var);
+        for (old_var, old, new) in coeff_diffs {
+            res.push(ProblemDifference::Coeff {
+                constr,
+                var: old_names[old_var].clone(),
+                old,
+                new,
+            });
+        }
+    }
+    for i in new.constraints.len()..old.constraints.len() {
+        if !old.is_constraint_removed[i] {
+            res.push(ProblemDifference::ConstraintRemoved {
+                constr: old.constraint_ref(i),
+            });
+        }
+    }
+    for i in old.constraints.len()..new.constraints.len() {
+        if !new.is_constraint_removed[i] {
+            res.push(ProblemDifference::ConstraintAdded {
+                constr: new.constraint_ref(i),
+            });
+        }
+    }
+
+    ProblemDiff { differences: res }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn diff() {
+        let mut old = Problem::new(OptimizationDirection::Maximize);
+        let x = old.add_var(1.0, (0.0, f64::INFINITY));
+        let y = old.add_var(2.0, (0.0, 3.0));
+        let z = old.add_var(1.0, (0.0, 1.0));
+        old.set_var_name(z, "z");
+        old.add_constraint([(x, 1.0), (y, 1.0), (z, 1.0)], ComparisonOp::Le, 4.0);
+        old.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+        old.add_constraint([(y, 1.0)], ComparisonOp::Le, 10.0);
+        assert!(old.diff(&old.clone()).is_empty());
+
+        let mut new = Problem::new(OptimizationDirection::Maximize);
+        let x = new.add_var(1.0, (0.0, f64::INFINITY));
+        let y = new.add_var(3.0, (-1.0, 3.0));
+        let w = new.add_var(0.0, (0.0, 1.0));
+        new.set_var_name(w, "w");
+        new.set_objective_offset(1.5);
+        new.add_constraint([(x, 1.0), (y, 1.0), (w, 1.0)], ComparisonOp::Le, 4.0);
+        new.add_constraint([(y, 1.0), (x, 2.5)], ComparisonOp::Eq, 3.0);
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.to_string(),
+            "objective offset changed from 0 to 1.5
+objective coefficient of x1 changed from 2 to 3
+bounds of x1 changed from [0, 3] to [-1, 3]
+variable z removed
+variable w added
+operator of constraint 1 changed from Ge to Eq
+rhs of constraint 1 changed from 2 to 3
+coefficient of x0 in constraint 1 changed from 2 to 2.5
+constraint 2 removed
+"
+        );
+
+        let mut new = old.clone();
+        new.direction = OptimizationDirection::Minimize;
+        new.constraints[2].0 = sprs::CsVec::new(3, vec![0], vec![1.0]);
+        assert_eq!(
+            new.diff(&old).differences,
+            vec![
+                ProblemDifference::Direction {
+                    old: OptimizationDirection::Minimize,
+                    new: OptimizationDirection::Maximize,
+                },
+                ProblemDifference::ObjCoeff {
+                    var: "x0".to_string(),
+                    old: -1.0,
+                    new: 1.0,
+                },
+                ProblemDifference::ObjCoeff {
+                    var: "x1".to_string(),
+                    old: -2.0,
+                    new: 2.0,
+                },
+                ProblemDifference::ObjCoeff {
+                    var: "z".to_string(),
+                    old: -1.0,
+                    new: 1.0,
+                },
+                ProblemDifference::Coeff {
+                    constr: ConstraintRef(2, 0),
+                    var: "x0".to_string(),
+                    old: 1.0,
+                    new: 0.0,
+                },
+                ProblemDifference::Coeff {
+                    constr: ConstraintRef(2, 0),
+                    var: "x1".to_string(),
+                    old: 0.0,
+                    new: 1.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn removed() {
+        let mut old = Problem::new(OptimizationDirection::Maximize);
+        let x = old.add_var(1.0, (0.0, f64::INFINITY));
+        let y = old.add_var(2.0, (0.0, 3.0));
+        old.set_var_name(y, "y");
+        old.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        let c1 = old.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+        old.add_constraint([(x, 1.0)], ComparisonOp::Le, 3.0);
+
+        let mut new = old.clone();
+        new.remove_constraint(c1).unwrap();
+        new.remove_var(y).unwrap();
+        assert_eq!(
+            old.diff(&new).differences,
+            vec![
+                ProblemDifference::VarRemoved {
+                    var: "y".to_string()
+                },
+                ProblemDifference::ConstraintRemoved { constr: c1 },
+            ]
+        );
+        assert_eq!(
+            new.diff(&old).to_string(),
+            "variable y added\nconstraint 1 added\n"
+        );
+        assert!(new.diff(&new.clone()).is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{Problem, Solution};
+use std::fmt;
+
+/// Default abs. value below which variable values and duals are not printed.
+const DEFAULT_THRESHOLD: f64 = 1e-9;
+
+/// A helper for printing a [`Solution`] with variable names, see [`Solution::display`].
+///
+/// [`Solution`]: struct.Solution.html
+/// [`Solution::display`]: struct.Solution.html#method.display
+#[derive(Clone, Copy, Debug)]
+pub struct SolutionDisplay<'a> {
+    solution: &'a Solution,
+    problem: &'a Problem,
+    precision: Option<usize>,
+    threshold: f64,
+    show_duals: bool,
+}
+
+impl<'a> SolutionDisplay<'a> {
+    pub(crate) fn new(solution: &'a Solution, problem: &'a Problem) -> Self {
+        SolutionDisplay {
+            solution,
+            problem,
+            precision: None,
+            threshold: DEFAULT_THRESHOLD,
+            show_duals: false,
+        }
+    }
+
+    /// Print the values with the given number of digits after the decimal point
+    /// (by default the shortest representation that round-trips is used).
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Don't print the values with abs. value less than or equal to `threshold`
+    /// (1e-9 by default).
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Print also the dual values of the constraints (not printed by default).
+    pub fn with_duals(mut self) -> Self {
+        self.show_duals = true;
+        self
+    }
+
+    fn write_value(&self, f: &mut fmt::Formatter, val: f64) -> fmt::Result {
+        match self.precision {
+            Some(precision) => write!(f, "{:.*}", precision, val),
+            None => write!(f, "{}", val),
+        }
+    }
+}
+
+impl fmt::Display for SolutionDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "objective: ")?;
+        self.write_value(f, self.solution.objective())?;
+        writeln!(f)?;
+
+        for (var, &val) in self.solution.iter() {
+            if val.abs() <= self.threshold {
+                continue;
+            }
+            write!(f, "{} = ", self.problem.var_name(var))?;
+            self.write_value(f, val)?;
+            writeln!(f)?;
+        }
+
+        if self.show_duals {
+            writeln!(f, "duals:")?;
+            for constr in 0..self.solution.solver.num_constraints() {
+                let dual = self
+                    .solution
+                    .dual_value(self.solution.constraint_ref(constr));
+                if dual.abs() <= self.threshold {
+                    continue;
+                }
+                write!(f, "c{} = ", constr)?;
+                self.write_value(f, dual)?;
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn display() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let z = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.set_var_name(z, "z");
+        problem.add_constraint([(x, 3.0), (z, 1.0)], ComparisonOp::Ge, 1.0);
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 5.0);
+        let sol = problem.solve().unwrap();
+
+        assert_eq!(
+            sol.display(&problem).to_string(),
+            "objective: 0.3333333333333333\nx0 = 0.3333333333333333\n"
+        );
+        assert_eq!(
+            sol.display(&problem).precision(2).with_duals().to_string(),
+            "objective: 0.33\nx0 = 0.33\nduals:\nc0 = 0.33\n"
+        );
+        assert_eq!(
+            sol.display(&problem).threshold(0.5).to_string(),
+            "objective: 0.3333333333333333\n"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{
+    helpers::{is_integral, INTEGRALITY_TOL},
+    solve_log::LogValue,
+    Solution, Variable,
+};
+
+/// A rule for choosing the variable to round on each step of [`Solution::dive`].
+///
+/// [`Solution::dive`]: struct.Solution.html#method.dive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DivingRule {
+    /// Round the variable closest to an integer value to that value.
+    Fractional,
+    /// Round the variable in the direction in which it can violate the fewest constraints
+    /// (has the fewest *locks*), preferring variables with fewer locks. Such roundings
+    /// are less likely to make the problem infeasible.
+    Coefficient,
+    /// Consider the line from the starting solution to the current one and round the
+    /// variable whose value becomes integral first when moving along that line. Variables
+    /// that changed since the start are thus rounded in the direction of their change.
+    LineSearch,
+}
+
+pub(crate) fn dive(
+    start: &Solution,
+    integer_vars: &[Variable],
+    rule: DivingRule,
+) -> Option<Solution> {
+    let mut cur = start.clone();
+    loop {
+        let fractional: Vec<(Variable, f64)> = integer_vars
+            .iter()
+            .map(|&var| (var, cur[var]))
+            .filter(|&(_, val)| !is_integral(val))
+            .collect();
+        if fractional.is_empty() {
+            let objective = cur.objective();
+            cur.solver
+                .log_event("incumbent", &[("objective", LogValue::Float(objective))]);
+            return Some(cur);
+        }
+
+        let (var, round_up) = match rule {
+            DivingRule::Fractional => choose_fractional(&fractional),
+            DivingRule::Coefficient => choose_coefficient(&cur, &fractional),
+            DivingRule::LineSearch => choose_line_search(start, &fractional)
+                .unwrap_or_else(|| choose_fractional(&fractional)),
+        };
+        trace!(
+            "diving: rounding var {} with value {} {}",
+            var.0,
+            cur[var],
+            if round_up { "up" } else { "down" },
+        );
+
+        // If the rounding makes the LP infeasible, try the opposite direction once.
+        let val = cur[var];
+        cur = match cur.clone().fix_var(var, rounded(val, round_up)) {
+            Ok(next) => next,
+            Err(_) => cur.fix_var(var, rounded(val, !round_up)).ok()?,
+        };
+    }
+}
+
+pub(crate) fn fractional_vars(
+    solution: &Solution,
+    integer_vars: &[Variable],
+) -> Vec<(Variable, f64)> {
+    integer_vars
+        .iter()
+        .map(|&var| (var, solution[var]))
+        .filter(|&(_, val)| !is_integral(val))
+        .map(|(var, val)| (var, val - val.floor()))
+        .collect()
+}
+
+pub(crate) fn repair_start(
+    solution: &Solution,
+    integer_vars: &[Variable],
+    start: &[(Variable, f64)],
+) -> Option<Solution> {
+    let mut is_integer = vec![false; solution.num_vars];
+    for v in integer_vars {
+        is_integer[v.0] = true;
+    }
+
+    // Fix the integer vars one by one, skipping the values that make the problem infeasible.
+    let mut cur = solution.clone();
+    let mut num_skipped = 0;
+    for &(var, val) in start {
+        if !is_integer[var.0] {
+            continue;
+        }
+        cur = match cur.clone().fix_var(var, val.round()) {
+            Ok(next) => next,
+            Err(_) => {
+                num_skipped += 1;
+                cur
+            }
+        };
+    }
+    if num_skipped > 0 {
+        debug!("MIP start: skipped {} infeasible values", num_skipped);
+    }
+
+    // Vars that are not in the start or were skipped get integral values by diving.
+    dive(&cur, integer_vars, DivingRule::Fractional)
+}
+
+fn rounded(val: f64, up: bool) -> f64 {
+    if up {
+        val.ceil()
+    } else {
+        val.floor()
+    }
+}
+
+/// Fractional part of the value in the rounding direction.
+fn rounding_dist(val: f64, up: bool) -> f64 {
+    f64::abs(rounded(val, up) - val)
+}
+
+fn choose_fractional(fractional: &[(Variable, f64)]) -> (Variable, bool) {
+    let mut best = None;
+    let mut best_dist = f64::INFINITY;
+    for &(var, val) in fractional {
+        let round_up = val - val.floor() > 0.5;
+        let dist = rounding_dist(val, round_up);
+        if dist < best_dist {
+            best = Some((var, round_up));
+            best_dist = dist;
+        }
+    }
+    best.unwrap()
+}
+
+fn choose_coefficient(cur: &Solution, fractional: &[(Variable, f64)]) -> (Variable, bool) {
+    let mut best = None;
+    let mut best_score = (usize::MAX, f64::INFINITY);
+    for &(var, val) in fractional {
+        let (down_locks, up_locks) = cur.solver.var_locks(var.0);
+        let round_up = if up_locks != down_locks {
+            up_locks < down_locks
+        } else {
+            val - val.floor() > 0.5
+        };
+        let locks = usize::min(down_locks, up_locks);
+        let score = (locks, rounding_dist(val, round_up));
+        if score < best_score {
+            best = Some((var, round_up));
+            best_score = score;
+        }
+    }
+    best.unwrap()
+}
+
+fn choose_line_search(
+    start: &Solution,
+    fractional: &[(Variable, f64)],
+) -> Option<(Variable, bool)> {
+    let mut best = None;
+    let mut best_dist = f64::INFINITY;
+    for &(var, val) in fractional {
+        let start_val = start[var];
+        if f64::abs(val - start_val) <= INTEGRALITY_TOL {
+            continue;
+        }
+        let round_up = val > start_val;
+        let dist = rounding_dist(val, round_up) / f64::abs(val - start_val);
+        if dist < best_dist {
+            best = Some((var, round_up));
+            best_dist = dist;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn knapsack() -> (Problem, Vec<Variable>) {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let weights = [5.0, 4.0, 3.0, 7.0, 2.0];
+        let values = [10.0, 7.0, 5.0, 12.0, 3.0];
+        let vars: Vec<_> = values
+            .iter()
+            .map(|&v| problem.add_var(v, (0.0, 1.0)))
+            .collect();
+        problem.add_constraint(
+            vars.iter().zip(&weights).map(|(&v, &w)| (v, w)),
+            ComparisonOp::Le,
+            10.5,
+        );
+        problem.add_constraint([(vars[0], 1.0), (vars[3], 1.0)], ComparisonOp::Le, 1.5);
+        (problem, vars)
+    }
+
+    #[test]
+    fn dive() {
+        let (problem, vars) = knapsack();
+        let relaxation = problem.solve().unwrap();
+        let fractional = relaxation.fractional_vars(&vars);
+        assert!(!fractional.is_empty());
+        for &v in &vars {
+            match fractional.iter().find(|&&(var, _)| var == v) {
+                Some(&(_, fract)) => {
+                    assert!(fract > 0.0 && f64::abs(relaxation[v].fract() - fract) < 1e-8)
+                }
+                None => assert_eq!(relaxation[v].fract(), 0.0),
+            }
+        }
+
+        for &rule in &[
+            DivingRule::Fractional,
+            DivingRule::Coefficient,
+            DivingRule::LineSearch,
+        ] {
+            let sol = relaxation.dive(&vars, rule).unwrap();
+            assert!(sol.fractional_vars(&vars).is_empty());
+            assert!(sol.objective() <= relaxation.objective() + 1e-8);
+            assert!(sol.objective() >= 15.0 - 1e-8);
+        }
+    }
+
+    #[test]
+    fn dive_infeasible() {
+        // 2 * x == 1 doesn't have integer solutions.
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        problem.add_constraint([(x, 2.0)], ComparisonOp::Eq, 1.0);
+        let relaxation = problem.solve().unwrap();
+        assert!(relaxation.dive(&[x], DivingRule::Fractional).is_none());
+    }
+
+    #[test]
+    fn repair_start() {
+        let (problem, vars) = knapsack();
+        let relaxation = problem.solve().unwrap();
+
+        // Complete feasible start.
+        let start: Vec<_> = vars
+            .iter()
+            .zip(&[1.0, 0.0, 1.0, 0.0, 1.0])
+            .map(|(&v, &x)| (v, x))
+            .collect();
+        let sol = relaxation.repair_mip_start(&vars, &start).unwrap();
+        assert!(f64::abs(sol.objective() - 18.0) < 1e-8);
+
+        // vars[0] and vars[3] can't be both 1, and vars[1] isn't in the start.
+        let start = [(vars[0], 1.0), (vars[3], 0.9), (vars[2], 0.0)];
+        let sol = relaxation.repair_mip_start(&vars, &start).unwrap();
+        assert_eq!((sol[vars[0]], sol[vars[3]], sol[vars[2]]), (1.0, 0.0, 0.0));
+        for &v in &vars {
+            assert_eq!(sol[v].fract(), 0.0);
+        }
+    }
+}
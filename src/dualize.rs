@@ -0,0 +1,310 @@
+use crate::{
+    solver::Solver, BasisOrdering, ComparisonOp, CsVec, Dualization, Error, LinearExpr,
+    OptimizationDirection, Problem, SolverOptions,
+};
+use std::time::Instant;
+
+/// Minimal number of constraints for which the dual problem is considered in the Auto mode.
+const AUTO_MIN_CONSTRAINTS: usize = 100;
+/// In the Auto mode the dual problem is solved if the number of constraints is at least this
+/// many times bigger than the number of variables.
+const AUTO_RATIO: usize = 4;
+
+pub(crate) fn should_dualize(problem: &Problem, mode: Dualization) -> bool {
+    let num_constraints = problem.constraints.len();
+    match mode {
+        Dualization::Always => true,
+        Dualization::Never => false,
+        Dualization::Auto => {
+            num_constraints >= AUTO_MIN_CONSTRAINTS
+                && num_constraints >= AUTO_RATIO * problem.obj_coeffs.len()
+        }
+    }
+}
+
+/// Form the dual problem. Its constraints correspond to the variables of the original problem
+/// and dual values of these constraints are the values of the original variables.
+///
+/// The original problem (in the internal minimization form) is
+/// min c^T x s.t. A x + s = b, l <= x <= u, and bounds of the slacks s depend on the
+/// constraint type. The dual problem is max b^T y + l^T p - u^T q s.t. A^T y + p - q = c,
+/// where p, q >= 0 are present only for finite bounds and the sign of y depends
+/// on the constraint type.
+///
+/// Empty constraints don't get a dual var and only the vars for which `is_var_used` is true
+/// get a dual constraint (in the order of the vars).
+fn dual_problem(problem: &Problem, is_var_used: &[bool]) -> Problem {
+    let mut dual = Problem::new(OptimizationDirection::Maximize);
+    let mut rows = vec![LinearExpr::empty(); problem.obj_coeffs.len()];
+
+    for (coeffs, cmp_op, rhs) in &problem.constraints {
+        if is_empty(coeffs) {
+            continue;
+        }
+        let bounds = match cmp_op {
+            ComparisonOp::Le => (f64::NEG_INFINITY, 0.0),
+            ComparisonOp::Ge => (0.0, f64::INFINITY),
+            ComparisonOp::Eq => (f64::NEG_INFINITY, f64::INFINITY),
+        };
+        let y = dual.add_var(*rhs, bounds);
+        for (var, &coeff) in coeffs.iter() {
+            rows[var].add(y, coeff);
+        }
+    }
+
+    for (v, row) in rows.iter_mut().enumerate() {
+        if !is_var_used[v] {
+            continue;
+        }
+        let min = problem.var_mins[v];
+        let max = problem.var_maxs[v];
+        if min.is_finite() {
+            row.add(dual.add_var(min, (0.0, f64::INFINITY)), 1.0);
+        }
+        if max.is_finite() {
+            row.add(dual.add_var(-max, (0.0, f64::INFINITY)), -1.0);
+        }
+    }
+
+    for (v, (row, &obj_coeff)) in rows.into_iter().zip(&problem.obj_coeffs).enumerate() {
+        if is_var_used[v] {
+            dual.add_constraint(row, ComparisonOp::Eq, obj_coeff);
+        }
+    }
+
+    dual
+}
+
+fn is_empty(coeffs: &CsVec) -> bool {
+    coeffs.iter().all(|(_, &coeff)| coeff == 0.0)
+}
+
+/// Optimal value of a var that doesn't appear in any constraint. None if the objective
+/// is unbounded in the direction of its infinite bound.
+fn unused_var_value(obj_coeff: f64, min: f64, max: f64) -> Option<f64> {
+    let val = if obj_coeff > 0.0 {
+        min
+    } else if obj_coeff < 0.0 {
+        max
+    } else if min.is_finite() {
+        min
+    } else if max.is_finite() {
+        max
+    } else {
+        0.0
+    };
+    Some(val).filter(|val| val.is_finite())
+}
+
+/// Solve the original problem by solving the dual. Returns None if the problem should be
+/// solved directly: if it has a violated empty constraint, if a var that doesn't appear
+/// in any constraint makes it unbounded, or if solving the dual problem fails (in that case
+/// the original problem is either unbounded or infeasible and the direct solve finds out
+/// which one and reports the error in terms of the original problem).
+pub(crate) fn solve_dualized(
+    problem: &Problem,
+    options: &SolverOptions,
+) -> Option<Result<Solver, Error>> {
+    let start = Instant::now();
+    let num_vars = problem.obj_coeffs.len();
+    let mut is_var_used = vec![false; num_vars];
+    for (coeffs, cmp_op, rhs) in &problem.constraints {
+        if is_empty(coeffs) {
+            let is_satisfied = match cmp_op {
+                ComparisonOp::Le => 0.0 <= *rhs,
+                ComparisonOp::Ge => 0.0 >= *rhs,
+                ComparisonOp::Eq => *rhs == 0.0,
+            };
+            if !is_satisfied {
+                return None;
+            }
+        }
+        for (var, &coeff) in coeffs.iter() {
+            if coeff != 0.0 {
+                is_var_used[var] = true;
+            }
+        }
+    }
+
+    let mut start_vals = vec![0.0; num_vars];
+    for v in 0..num_vars {
+        if !is_var_used[v] {
+            let (min, max) = (problem.var_mins[v], problem.var_maxs[v]);
+            start_vals[v] = unused_var_value(problem.obj_coeffs[v], min, max)?;
+        }
+    }
+
+    let dual = dual_problem(problem, &is_var_used);
+    debug!(
+        "solving the dual problem: vars: {}, constraints: {}",
+        dual.obj_coeffs.len(),
+        dual.constraints.len(),
+    );
+
+    let dual_options = SolverOptions {
+        dualization: Dualization::Never,
+        // The order refers to the variables of the original problem.
+        basis_ordering: BasisOrdering::Auto,
+        // Reporting both solves would make the progress estimate go back.
+        progress: None,
+        solve_log: None,
+        // The objective of the dual problem bounds the original one from the other side.
+        objective_cutoff: None,
+        // Only the vertex of the final primal solve matters.
+        lexicographic_optimum: false,
+        ..options.clone()
+    };
+    let dual_solution = dual.solve_with(&dual_options).ok()?;
+
+    let mut dual_rows = 0..dual.constraints.len();
+    for v in 0..num_vars {
+        if is_var_used[v] {
+            let row = dual_rows.next().unwrap();
+            start_vals[v] = dual_solution.dual_value(dual_solution.constraint_ref(row));
+        }
+    }
+
+    debug!(
+        "solved the dual problem, obj.: {}, starting primal simplex",
+        dual_solution.objective()
+    );
+
+    let dualization_time = start.elapsed();
+
+    let res = Solver::try_new_with_options(
+        &problem.obj_coeffs,
+        &problem.var_mins,
+        &problem.var_maxs,
+        &problem.constraints,
+        Some(&start_vals),
+        options,
+    )
+    .and_then(|mut solver| {
+        solver.timings.dualization = dualization_time;
+        solver.initial_solve()?;
+        Ok(solver)
+    });
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::time::Duration;
+
+    fn solve_both(problem: &Problem) -> (Result<Solution, Error>, Result<Solution, Error>) {
+        let solve = |dualization| {
+            problem.solve_with(&SolverOptions {
+                dualization,
+                ..Default::default()
+            })
+        };
+        (solve(Dualization::Never), solve(Dualization::Always))
+    }
+
+    #[test]
+    fn tall_problem() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(2.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(3.0, (-1.0, 5.0));
+        let z = problem.add_var(-1.0, (f64::NEG_INFINITY, f64::INFINITY));
+        for i in 0..200 {
+            let t = i as f64 / 10.0;
+            problem.add_constraint([(x, t.cos()), (y, t.sin())], ComparisonOp::Le, 10.0);
+            problem.add_constraint([(x, 1.0), (z, 1.0 + t)], ComparisonOp::Ge, -t);
+        }
+        problem.add_constraint([(x, 1.0), (y, -1.0), (z, 1.0)], ComparisonOp::Eq, 1.0);
+        assert!(dualize::should_dualize(&problem, Dualization::Auto));
+
+        let (primal, dual) = solve_both(&problem);
+        let (primal, mut dual) = (primal.unwrap(), dual.unwrap());
+        assert!(f64::abs(primal.objective() - dual.objective()) < 1e-8);
+        for &v in &[x, y, z] {
+            assert!(f64::abs(primal[v] - dual[v]) < 1e-8);
+        }
+        assert_eq!(primal.timings().dualization, Duration::ZERO);
+        assert!(dual.timings().dualization > Duration::ZERO);
+        assert!(dual.timings().factorization > Duration::ZERO);
+
+        // The solution is usable for incremental solving.
+        dual = dual
+            .add_constraint([(y, 1.0)], ComparisonOp::Le, 2.0)
+            .unwrap();
+        assert!(f64::abs(dual[y] - 2.0) < 1e-8);
+    }
+
+    #[test]
+    fn infeasible_and_unbounded() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0)], ComparisonOp::Le, -1.0);
+        match solve_both(&problem) {
+            (Err(e1), Err(e2))
+                if e1.kind() == ErrorKind::Infeasible && e2.kind() == ErrorKind::Infeasible => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, 1.0));
+        problem.add_constraint([(x, 1.0), (y, -1.0)], ComparisonOp::Ge, 0.0);
+        match solve_both(&problem) {
+            (Err(e1), Err(e2))
+                if e1.kind() == ErrorKind::Unbounded && e2.kind() == ErrorKind::Unbounded => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn empty_rows_and_cols() {
+        let check = |problem: &Problem, objective: f64| {
+            let (primal, dual) = solve_both(problem);
+            assert_eq!(primal.unwrap().objective(), objective);
+            assert_eq!(dual.unwrap().objective(), objective);
+        };
+
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(2.0, (1.0, 4.0));
+        problem.add_var(-5.0, (1.0, f64::INFINITY));
+        problem.add_constraint([(x, 0.0)], ComparisonOp::Eq, 0.0);
+        check(&problem, 3.0);
+
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(4.0, (-4.0, 3.0));
+        problem.add_constraint([(x, -2.0)], ComparisonOp::Eq, 8.0);
+        problem.add_constraint(LinearExpr::empty(), ComparisonOp::Eq, 0.0);
+        check(&problem, -16.0);
+
+        // Free and one-sided vars that don't appear in any constraint.
+        for &bounds in &[
+            (f64::NEG_INFINITY, f64::INFINITY),
+            (0.0, f64::INFINITY),
+            (f64::NEG_INFINITY, 0.0),
+        ] {
+            let mut problem = Problem::new(OptimizationDirection::Minimize);
+            let x = problem.add_var(1.0, (0.0, 3.0));
+            problem.add_var(0.0, bounds);
+            problem.add_var(1.0, (-2.0, f64::INFINITY));
+            problem.add_constraint([(x, 1.0)], ComparisonOp::Ge, 1.0);
+            check(&problem, -1.0);
+        }
+
+        // A var that doesn't appear in any constraint makes the problem unbounded
+        // and a violated empty constraint makes it infeasible.
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, 3.0));
+        problem.add_var(-1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0)], ComparisonOp::Ge, 1.0);
+        match solve_both(&problem) {
+            (Err(e1), Err(e2))
+                if e1.kind() == ErrorKind::Unbounded && e2.kind() == ErrorKind::Unbounded => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        problem.add_constraint(LinearExpr::empty(), ComparisonOp::Ge, 1.0);
+        match solve_both(&problem) {
+            (Err(e1), Err(e2))
+                if e1.kind() == ErrorKind::Infeasible && e2.kind() == ErrorKind::Infeasible => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use crate::{ConstraintRef, Variable};
+use std::fmt;
+
+/// The kind of an [`Error`](struct.Error.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Constrains can't simultaneously be satisfied.
+    Infeasible,
+    /// The objective function is unbounded.
+    Unbounded,
+    /// The objective value can't be better than the
+    /// [cutoff](struct.SolverOptions.html#structfield.objective_cutoff).
+    Cutoff,
+}
+
+/// The stage of the solution process in which an [`Error`](struct.Error.html) was detected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Checking the problem data: variable bounds and constraints without variables.
+    Setup,
+    /// Simplifying the problem before solving it.
+    Presolve,
+    /// Primal simplex iterations.
+    PrimalSimplex,
+    /// Dual simplex iterations.
+    DualSimplex,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Phase::Setup => "setup",
+            Phase::Presolve => "presolve",
+            Phase::PrimalSimplex => "primal simplex",
+            Phase::DualSimplex => "dual simplex",
+        };
+        name.fmt(f)
+    }
+}
+
+/// An error encountered while solving a problem.
+///
+/// Apart from the [kind](#method.kind) of the error, it carries the context in which
+/// the error was detected: the phase of the solution process, the variable or constraint
+/// involved (if known) and the magnitude of the violation. Note that for an infeasible
+/// problem the involved constraint is just the place where the infeasibility was detected,
+/// it is not necessarily the cause of it.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Minimize);
+/// let x = problem.add_var(1.0, (2.0, 1.0));
+///
+/// let err = problem.solve().unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Infeasible);
+/// assert_eq!(err.phase(), Phase::Setup);
+/// assert_eq!(err.variable(), Some(x));
+/// assert_eq!(err.violation(), Some(1.0));
+/// assert_eq!(
+///     err.to_string(),
+///     "problem is infeasible (detected in setup at variable 0, violation 1): \
+///      the bounds of the variable are contradictory",
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    kind: ErrorKind,
+    phase: Phase,
+    variable: Option<Variable>,
+    constraint: Option<ConstraintRef>,
+    violation: Option<f64>,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, phase: Phase) -> Self {
+        Error {
+            kind,
+            phase,
+            variable: None,
+            constraint: None,
+            violation: None,
+        }
+    }
+
+    pub(crate) fn infeasible(phase: Phase) -> Self {
+        Error::new(ErrorKind::Infeasible, phase)
+    }
+
+    pub(crate) fn unbounded(phase: Phase) -> Self {
+        Error::new(ErrorKind::Unbounded, phase)
+    }
+
+    pub(crate) fn cutoff(phase: Phase) -> Self {
+        Error::new(ErrorKind::Cutoff, phase)
+    }
+
+    pub(crate) fn with_variable(mut self, var: usize) -> Self {
+        self.variable = Some(Variable(var, 0));
+        self
+    }
+
+    pub(crate) fn with_constraint(mut self, constr: usize) -> Self {
+        self.constraint = Some(ConstraintRef(constr, 0));
+        self
+    }
+
+    /// Set the generation of the variable and constraint references (the solver knows
+    /// only their indices).
+    pub(crate) fn in_generation(mut self, generation: u32) -> Self {
+        if let Some(var) = &mut self.variable {
+            var.1 = generation;
+        }
+        if let Some(constr) = &mut self.constraint {
+            constr.1 = generation;
+        }
+        self
+    }
+
+    pub(crate) fn with_violation(mut self, violation: f64) -> Self {
+        self.violation = Some(violation);
+        self
+    }
+
+    /// The kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The phase in which the error was detected.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The variable involved in the error, if known.
+    pub fn variable(&self) -> Option<Variable> {
+        self.variable
+    }
+
+    /// The constraint involved in the error, if known.
+    pub fn constraint(&self) -> Option<ConstraintRef> {
+        self.constraint
+    }
+
+    /// By how much the involved variable bound or constraint is violated, if known.
+    pub fn violation(&self) -> Option<f64> {
+        self.violation
+    }
+
+    /// A hint on what to do about the error.
+    pub fn suggestion(&self) -> &'static str {
+        match (self.kind, self.phase) {
+            (ErrorKind::Infeasible, Phase::Setup) if self.variable.is_some() => {
+                "the bounds of the variable are contradictory"
+            }
+            (ErrorKind::Infeasible, Phase::Setup) => {
+                "the constraint has no variables and its right-hand side can't be satisfied"
+            }
+            (ErrorKind::Infeasible, _) => {
+                "check the constraints and variable bounds for conflicting requirements"
+            }
+            (ErrorKind::Unbounded, _) => {
+                "check the optimization direction and add bounds to the variables \
+                 that can grow indefinitely"
+            }
+            (ErrorKind::Cutoff, _) => {
+                "there is no solution better than the objective cutoff, \
+                 remove the cutoff to find the optimal solution"
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self.kind {
+            ErrorKind::Infeasible => "problem is infeasible",
+            ErrorKind::Unbounded => "problem is unbounded",
+            ErrorKind::Cutoff => "objective cutoff reached",
+        };
+        write!(f, "{} (detected in {}", msg, self.phase)?;
+        if let Some(var) = self.variable {
+            write!(f, " at variable {}", var.0)?;
+        }
+        if let Some(constr) = self.constraint {
+            write!(f, " at constraint {}", constr.0)?;
+        }
+        if let Some(violation) = self.violation {
+            write!(f, ", violation {}", violation)?;
+        }
+        write!(f, "): {}", self.suggestion())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An invalid value passed when building a [`Problem`](struct.Problem.html).
+///
+/// NaN and infinite values (except for the infinite bounds of unbounded variables) don't
+/// have a meaning in a linear program and would lead the solver to nonsensical results,
+/// so they are rejected when added to the problem.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Minimize);
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+///
+/// let err = problem
+///     .try_add_constraint(&[(x, f64::INFINITY)], ComparisonOp::Le, 1.0)
+///     .unwrap_err();
+/// assert!(matches!(err, ModelError::InvalidCoeff { var, .. } if var == x));
+/// assert_eq!(err.to_string(), "coefficient of variable 0 in constraint 0 is inf");
+/// // The problem is unchanged.
+/// assert_eq!(problem.stats().num_constraints, 0);
+///
+/// let err = problem.try_add_var(f64::NAN, (0.0, 1.0)).unwrap_err();
+/// assert_eq!(err.to_string(), "objective coefficient of variable 1 is NaN");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModelError {
+    /// The objective coefficient of the variable is NaN or infinite.
+    InvalidObjCoeff {
+        /// The variable.
+        var: Variable,
+        /// Value of the coefficient.
+        coeff: f64,
+    },
+    /// A bound of the variable is NaN, or the lower bound is positive infinity,
+    /// or the upper bound is negative infinity.
+    InvalidBounds {
+        /// The variable.
+        var: Variable,
+        /// The lower bound.
+        min: f64,
+        /// The upper bound.
+        max: f64,
+    },
+    /// A coefficient of the variable in the constraint is NaN or infinite.
+    InvalidCoeff {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// The variable.
+        var: Variable,
+        /// Value of the coefficient.
+        coeff: f64,
+    },
+    /// The right-hand side of the constraint is NaN or infinite.
+    InvalidRhs {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// Value of the right-hand side.
+        rhs: f64,
+    },
+    /// The constraint has no terms. Reported only in the [strict](enum.Strictness.html) mode.
+    EmptyConstraint {
+        /// The constraint.
+        constr: ConstraintRef,
+    },
+    /// A term of the constraint has a zero coefficient. Reported only in the
+    /// [strict](enum.Strictness.html) mode.
+    ZeroCoeff {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// The variable of the term.
+        var: Variable,
+        /// Position of the term in the expression.
+        position: usize,
+    },
+    /// The variable appears in several terms of the constraint. Reported only in the
+    /// [strict](enum.Strictness.html) mode.
+    DuplicateVar {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// The variable.
+        var: Variable,
+        /// Position of the first term with the variable in the expression.
+        first_position: usize,
+        /// Position of the repeated term.
+        position: usize,
+    },
+    /// The variable was removed from the problem or the reference is from an older
+    /// generation of the problem (before it was [compacted]).
+    ///
+    /// [compacted]: struct.Problem.html#method.compact
+    InvalidVar {
+        /// The variable.
+        var: Variable,
+    },
+    /// The constraint was removed from the problem or the reference is from an older
+    /// generation of the problem (before it was [compacted]).
+    ///
+    /// [compacted]: struct.Problem.html#method.compact
+    InvalidConstraint {
+        /// The constraint.
+        constr: ConstraintRef,
+    },
+    /// The expression of the constraint refers to variables from an older generation of
+    /// the problem (before it was [compacted]).
+    ///
+    /// [compacted]: struct.Problem.html#method.compact
+    StaleExpr {
+        /// The constraint.
+        constr: ConstraintRef,
+    },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelError::InvalidObjCoeff { var, coeff } => write!(
+                f,
+                "objective coefficient of variable {} is {}",
+                var.0, coeff
+            ),
+            ModelError::InvalidBounds { var, min, max } => write!(
+                f,
+                "bounds of variable {} are invalid: [{}, {}]",
+                var.0, min, max
+            ),
+            ModelError::InvalidCoeff { constr, var, coeff } => write!(
+                f,
+                "coefficient of variable {} in constraint {} is {}",
+                var.0, constr.0, coeff
+            ),
+            ModelError::InvalidRhs { constr, rhs } => {
+                write!(f, "right-hand side of constraint {} is {}", constr.0, rhs)
+            }
+            ModelError::EmptyConstraint { constr } => {
+                write!(f, "constraint {} has no terms", constr.0)
+            }
+            ModelError::ZeroCoeff {
+                constr,
+                var,
+                position,
+            } => write!(
+                f,
+                "term {} of constraint {} (variable {}) has zero coefficient",
+                position, constr.0, var.0
+            ),
+            ModelError::DuplicateVar {
+                constr,
+                var,
+                first_position,
+                position,
+            } => write!(
+                f,
+                "variable {} appears in terms {} and {} of constraint {}",
+                var.0, first_position, position, constr.0
+            ),
+            ModelError::InvalidVar { var } => write!(
+                f,
+                "variable {} (generation {}) is not in the problem",
+                var.0, var.1
+            ),
+            ModelError::InvalidConstraint { constr } => write!(
+                f,
+                "constraint {} (generation {}) is not in the problem",
+                constr.0, constr.1
+            ),
+            ModelError::StaleExpr { constr } => write!(
+                f,
+                "expression of constraint {} refers to variables of an older generation",
+                constr.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// Invalid values read from a file are reported as
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) errors.
+impl From<ModelError> for std::io::Error {
+    fn from(err: ModelError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn context() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 4.0));
+        let y = problem.add_var(1.0, (0.0, 4.0));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 5.0);
+
+        for &scaling in &[ScalingMode::None, ScalingMode::GeometricMean] {
+            let options = SolverOptions {
+                scaling,
+                ..Default::default()
+            };
+            let sol = problem.solve_with(&options).unwrap();
+
+            let err = sol.clone().fix_var(x, 6.0).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Infeasible);
+            assert_eq!(err.variable(), Some(x));
+            assert!(f64::abs(err.violation().unwrap() - 2.0) < 1e-8);
+
+            // Detected by the dual simplex when x + y >= 9 is added.
+            let err = sol
+                .add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 9.0)
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Infeasible);
+            assert_eq!(err.phase(), Phase::DualSimplex);
+            assert!(err.constraint().is_some() || err.variable().is_some());
+            assert!(err.violation().unwrap() > 0.0);
+        }
+    }
+}
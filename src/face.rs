@@ -0,0 +1,122 @@
+use crate::{ConstraintRef, Variable};
+
+/// Description of the set of all optimal solutions of a problem (the *optimal face*),
+/// see [`Solution::optimal_face`](struct.Solution.html#method.optimal_face).
+///
+/// The face is described by the ranges of the variable values and of the left-hand sides
+/// of the constraints over it. Each range is attained by some optimal solution, but the
+/// face is generally not a box: not all combinations of values from the ranges are optimal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptimalFace {
+    var_ranges: Vec<(f64, f64)>,
+    constraint_ranges: Vec<(f64, f64)>,
+    generation: u32,
+}
+
+impl OptimalFace {
+    pub(crate) fn new(mut ranges: Vec<(f64, f64)>, num_vars: usize, generation: u32) -> Self {
+        let constraint_ranges = ranges.split_off(num_vars);
+        OptimalFace {
+            var_ranges: ranges,
+            constraint_ranges,
+            generation,
+        }
+    }
+
+    /// Minimal and maximal values of the variable over the optimal solutions. Bounds are
+    /// infinite if there is a ray of optimal solutions in which the variable is unbounded.
+    pub fn var_range(&self, var: Variable) -> (f64, f64) {
+        assert!(
+            var.0 < self.var_ranges.len() && var.1 == self.generation,
+            "variable {:?} is not in the optimal face",
+            var
+        );
+        self.var_ranges[var.0]
+    }
+
+    /// Minimal and maximal values of the left-hand side of the constraint over the optimal
+    /// solutions, see [`var_range`](#method.var_range).
+    pub fn constraint_range(&self, constr: ConstraintRef) -> (f64, f64) {
+        assert!(
+            constr.0 < self.constraint_ranges.len() && constr.1 == self.generation,
+            "constraint {:?} is not in the optimal face",
+            constr
+        );
+        self.constraint_ranges[constr.0]
+    }
+
+    /// Variables that take different values in different optimal solutions.
+    pub fn varying_vars(&self) -> impl Iterator<Item = Variable> + '_ {
+        (self.var_ranges.iter().enumerate())
+            .filter(|(_, (min, max))| min < max)
+            .map(move |(v, _)| Variable(v, self.generation))
+    }
+
+    /// Constraints whose left-hand sides take different values in different optimal
+    /// solutions (and which are thus not tight in some of them).
+    pub fn varying_constraints(&self) -> impl Iterator<Item = ConstraintRef> + '_ {
+        (self.constraint_ranges.iter().enumerate())
+            .filter(|(_, (min, max))| min < max)
+            .map(move |(c, _)| ConstraintRef(c, self.generation))
+    }
+
+    /// Whether the optimal solution is unique, i.e. no variable can change its value.
+    pub fn is_unique(&self) -> bool {
+        self.varying_vars().next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn optimal_face() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 3.0));
+        let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let z = problem.add_var(-1.0, (0.0, 1.0));
+        let w = problem.add_var(0.0, (0.0, f64::INFINITY));
+        let c1 = problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        let c2 = problem.add_constraint([(x, 1.0), (y, -1.0), (z, 1.0)], ComparisonOp::Ge, -2.0);
+        let c3 = problem.add_constraint([(y, 1.0), (w, -1.0)], ComparisonOp::Le, 1.0);
+
+        for scaling in [ScalingMode::None, ScalingMode::GeometricMeanEquilibration] {
+            let options = SolverOptions {
+                scaling,
+                ..Default::default()
+            };
+            let solution = problem.solve_with(&options).unwrap();
+            assert_eq!(solution.objective(), 4.0);
+            let face = solution.optimal_face().unwrap();
+
+            // The optimal solutions are x + y == 4 with x in [1, 3] (y <= x + 2),
+            // z == 0 and w >= y - 1 (unbounded from above).
+            let close = |lhs: (f64, f64), rhs: (f64, f64)| {
+                for (lhs, rhs) in [(lhs.0, rhs.0), (lhs.1, rhs.1)] {
+                    assert!(lhs == rhs || f64::abs(lhs - rhs) < 1e-8, "{} {}", lhs, rhs);
+                }
+            };
+            close(face.var_range(x), (1.0, 3.0));
+            close(face.var_range(y), (1.0, 3.0));
+            assert_eq!(face.var_range(z), (0.0, 0.0));
+            close(face.var_range(w), (0.0, f64::INFINITY));
+            assert_eq!(face.constraint_range(c1), (4.0, 4.0));
+            close(face.constraint_range(c2), (-2.0, 2.0));
+            close(face.constraint_range(c3), (f64::NEG_INFINITY, 1.0));
+            assert_eq!(face.varying_vars().collect::<Vec<_>>(), [x, y, w]);
+            assert_eq!(face.varying_constraints().collect::<Vec<_>>(), [c2, c3]);
+            assert!(!face.is_unique());
+        }
+
+        // Unique optimum.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 3.0));
+        let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+        let c = problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        let face = problem.solve().unwrap().optimal_face().unwrap();
+        assert!(face.is_unique());
+        assert_eq!(face.var_range(y), (4.0, 4.0));
+        assert_eq!(face.constraint_range(c), (4.0, 4.0));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{solver::Solver, Problem, SolverOptions};
+use std::any::Any;
+use std::fs;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// Run `f`, and if it panics (which means an internal error of the solver), write
+/// a reproducer to the failure dump directory (if it is set) and panic with its path.
+pub(crate) fn guard<T>(problem: &Problem, options: &SolverOptions, f: impl FnOnce() -> T) -> T {
+    let dir = match &options.failure_dump_dir {
+        Some(dir) => dir,
+        None => return f(),
+    };
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| fail(problem, options, None, dir, payload))
+}
+
+/// Same as [`guard`], but the reproducer also contains the basis of the solver at the moment
+/// of the failure.
+pub(crate) fn guard_solver<T>(
+    problem: &Problem,
+    options: &SolverOptions,
+    solver: &mut Solver,
+    f: impl FnOnce(&mut Solver) -> T,
+) -> T {
+    let dir = match &options.failure_dump_dir {
+        Some(dir) => dir,
+        None => return f(solver),
+    };
+    match panic::catch_unwind(AssertUnwindSafe(|| f(&mut *solver))) {
+        Ok(res) => res,
+        Err(payload) => fail(problem, options, Some(solver), dir, payload),
+    }
+}
+
+fn fail(
+    problem: &Problem,
+    options: &SolverOptions,
+    solver: Option<&Solver>,
+    dir: &Path,
+    payload: Box<dyn Any + Send>,
+) -> ! {
+    let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown internal error".to_string()
+    };
+
+    match write_reproducer(problem, options, solver, dir, &msg) {
+        Ok(path) => panic!("{} (failure reproducer written to {})", msg, path.display()),
+        Err(err) => panic!("{} (failed to write the failure reproducer: {})", msg, err),
+    }
+}
+
+/// Write the problem in the LP format with the error message, the options and the basis
+/// in the comments to a new file in `dir`.
+fn write_reproducer(
+    problem: &Problem,
+    options: &SolverOptions,
+    solver: Option<&Solver>,
+    dir: &Path,
+    msg: &str,
+) -> io::Result<PathBuf> {
+    let (path, mut file) = create_new_file(dir)?;
+    writeln!(
+        file,
+        "\\ minilp {} failure reproducer",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    for line in msg.lines() {
+        writeln!(file, "\\ error: {}", line)?;
+    }
+    writeln!(file, "\\ options: {:?}", options)?;
+    if let Some(solver) = solver {
+        let num_vars = problem.obj_coeffs.len();
+        let basic_vars = (0..num_vars + problem.constraints.len())
+            .filter(|&v| solver.is_basic(v))
+            .map(|v| {
+                if v < num_vars {
+                    problem.var_name_at(v).into_owned()
+                } else {
+                    format!("slack(c{})", v - num_vars)
+                }
+            })
+            .collect::<Vec<_>>();
+        writeln!(file, "\\ basic variables: {}", basic_vars.join(" "))?;
+    }
+    write!(file, "{}", problem)?;
+    Ok(path)
+}
+
+fn create_new_file(dir: &Path) -> io::Result<(PathBuf, fs::File)> {
+    fs::create_dir_all(dir)?;
+    for n in 0.. {
+        let path = dir.join(format!("minilp-failure-{}-{}.lp", std::process::id(), n));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn panic_msg(payload: Box<dyn Any + Send>) -> String {
+        payload.downcast_ref::<String>().unwrap().clone()
+    }
+
+    #[test]
+    fn reproducer() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (0.0, 3.0));
+        problem.set_var_name(y, "y");
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+
+        let dir = std::env::temp_dir().join(format!("minilp-failure-test-{}", std::process::id()));
+        let options = SolverOptions {
+            failure_dump_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            guard::<()>(&problem, &options, || panic!("singular basis matrix"))
+        }))
+        .unwrap_err();
+        let msg = panic_msg(payload);
+        let path = msg
+            .strip_prefix("singular basis matrix (failure reproducer written to ")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap();
+        let contents = fs::read_to_string(path).unwrap();
+        assert!(contents.contains("\\ error: singular basis matrix\n"));
+        assert!(contents.contains("\\ options: SolverOptions {"));
+        assert!(!contents.contains("basic variables"));
+        // The reproducer is a valid LP file.
+        let file = LpFile::parse(contents.as_bytes()).unwrap();
+        assert_eq!(file.problem.solve().unwrap().objective(), 7.0);
+
+        // The basis of the solver is included.
+        let solution = problem.solve().unwrap();
+        let mut solver = solution.solver;
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            guard_solver::<()>(&problem, &options, &mut solver, |_| {
+                panic!("cycling\ndetected")
+            })
+        }))
+        .unwrap_err();
+        let msg = panic_msg(payload);
+        let path = msg
+            .rsplit("written to ")
+            .next()
+            .unwrap()
+            .trim_end_matches(')');
+        let contents = fs::read_to_string(path).unwrap();
+        assert!(contents.contains("\\ error: cycling\n\\ error: detected\n"));
+        assert!(contents.contains("\\ basic variables: x0\n"));
+
+        // Without the directory the panic is not caught.
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            guard::<()>(&problem, &SolverOptions::default(), || {
+                panic!("{}", "internal error")
+            })
+        }))
+        .unwrap_err();
+        assert_eq!(panic_msg(payload), "internal error");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
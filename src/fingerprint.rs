@@ -0,0 +1,233 @@
+use crate::{ComparisonOp, OptimizationDirection, Problem};
+use std::fmt;
+
+/// A canonical hash of a [`Problem`], see [`Problem::fingerprint`].
+///
+/// The value is stable: it doesn't depend on the platform, the process or the version
+/// of the Rust compiler, so it can be stored and compared across runs. It is displayed
+/// as 32 hexadecimal digits.
+///
+/// [`Problem`]: struct.Problem.html
+/// [`Problem::fingerprint`]: struct.Problem.html#method.fingerprint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(u128);
+
+impl Fingerprint {
+    /// The fingerprint as a number.
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// The 128-bit FNV-1a hash.
+struct StableHasher(u128);
+
+impl StableHasher {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        StableHasher(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u128::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, val: u64) {
+        self.write(&val.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, val: u128) {
+        self.write(&val.to_le_bytes());
+    }
+
+    fn write_f64(&mut self, val: f64) {
+        // 0.0 and -0.0 are the same value.
+        let val = if val == 0.0 { 0.0 } else { val };
+        self.write_u64(val.to_bits());
+    }
+
+    fn write_str(&mut self, val: &str) {
+        // The length separates consecutive strings.
+        self.write_u64(val.len() as u64);
+        self.write(val.as_bytes());
+    }
+
+    /// Hash the digests of the unordered collection (sorting them first).
+    fn write_unordered(&mut self, mut digests: Vec<u128>) {
+        digests.sort_unstable();
+        self.write_u64(digests.len() as u64);
+        for digest in digests {
+            self.write_u128(digest);
+        }
+    }
+
+    fn finish(&self) -> u128 {
+        self.0
+    }
+}
+
+/// The fingerprint of the problem and the hash of its layout: the order of the variables
+/// and constraints and the generation. Solutions of problems with the same fingerprint
+/// and layout are interchangeable.
+pub(crate) fn fingerprint(problem: &Problem) -> (Fingerprint, u128) {
+    let mut layout = StableHasher::new();
+    layout.write_u64(u64::from(problem.generation));
+    layout.write_u64(problem.obj_coeffs.len() as u64);
+
+    // Named variables are identified by their names and unnamed ones by their positions
+    // among the unnamed variables that were not removed.
+    let mut var_ids = vec![0; problem.obj_coeffs.len()];
+    let mut var_digests = vec![];
+    let mut num_unnamed = 0;
+    for (v, var_id) in var_ids.iter_mut().enumerate() {
+        if problem.is_var_removed[v] {
+            layout.write(&[0]);
+            continue;
+        }
+        let mut hasher = StableHasher::new();
+        match &problem.var_names[v] {
+            Some(name) => hasher.write_str(name),
+            None => {
+                hasher.write_u64(u64::MAX);
+                hasher.write_u64(num_unnamed);
+                num_unnamed += 1;
+            }
+        }
+        *var_id = hasher.finish();
+
+        let mut hasher = StableHasher::new();
+        hasher.write_u128(*var_id);
+        hasher.write_f64(problem.obj_coeffs[v]);
+        hasher.write_f64(problem.var_mins[v]);
+        hasher.write_f64(problem.var_maxs[v]);
+        layout.write_u128(hasher.finish());
+        var_digests.push(hasher.finish());
+    }
+
+    let mut constraint_digests = vec![];
+    for (c, (coeffs, cmp_op, rhs)) in problem.constraints.iter().enumerate() {
+        if problem.is_constraint_removed[c] {
+            layout.write(&[0]);
+            continue;
+        }
+        let mut terms: Vec<(u128, f64)> = coeffs
+            .iter()
+            .map(|(v, &coeff)| (var_ids[v], coeff))
+            .collect();
+        terms.sort_unstable_by_key(|&(var_id, _)| var_id);
+
+        let mut hasher = StableHasher::new();
+        hasher.write(&[match cmp_op {
+            ComparisonOp::Eq => 0,
+            ComparisonOp::Le => 1,
+            ComparisonOp::Ge => 2,
+        }]);
+        hasher.write_f64(*rhs);
+        hasher.write_u64(terms.len() as u64);
+        for (var_id, coeff) in terms {
+            hasher.write_u128(var_id);
+            hasher.write_f64(coeff);
+        }
+        layout.write_u128(hasher.finish());
+        constraint_digests.push(hasher.finish());
+    }
+
+    let mut hasher = StableHasher::new();
+    hasher.write(&[match problem.direction {
+        OptimizationDirection::Minimize => 0,
+        OptimizationDirection::Maximize => 1,
+    }]);
+    hasher.write_f64(problem.obj_offset);
+    hasher.write_unordered(var_digests);
+    hasher.write_unordered(constraint_digests);
+    (Fingerprint(hasher.finish()), layout.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn fingerprint() {
+        let build = |reversed: bool| {
+            let mut problem = Problem::new(OptimizationDirection::Maximize);
+            let mut names = [("x", 1.0), ("y", 2.0)];
+            if reversed {
+                names.reverse();
+            }
+            let vars: Vec<_> = names
+                .iter()
+                .map(|&(name, obj_coeff)| {
+                    let var = problem.add_var(obj_coeff, (0.0, 3.0));
+                    problem.set_var_name(var, name);
+                    (name, var)
+                })
+                .collect();
+            let var = |name: &str| vars.iter().find(|v| v.0 == name).unwrap().1;
+            let mut constraints = vec![
+                (
+                    vec![(var("x"), 1.0), (var("y"), 1.0)],
+                    ComparisonOp::Le,
+                    4.0,
+                ),
+                (
+                    vec![(var("y"), -0.0), (var("x"), 2.0)],
+                    ComparisonOp::Ge,
+                    1.0,
+                ),
+            ];
+            if reversed {
+                constraints.reverse();
+            }
+            for (lhs, cmp_op, rhs) in constraints {
+                problem.add_constraint(lhs, cmp_op, rhs);
+            }
+            (problem, var("x"))
+        };
+
+        let (problem, x) = build(false);
+        let fingerprint = problem.fingerprint();
+        assert_eq!(fingerprint.to_string().len(), 32);
+        assert_eq!(build(true).0.fingerprint(), fingerprint);
+        assert_eq!(problem.clone().fingerprint(), fingerprint);
+
+        let mut changed = problem.clone();
+        changed.set_objective_offset(1.0);
+        assert_ne!(changed.fingerprint(), fingerprint);
+
+        let mut changed = problem.clone();
+        changed.set_var_name(x, "z");
+        assert_ne!(changed.fingerprint(), fingerprint);
+
+        // Removed data doesn't matter.
+        let mut changed = problem.clone();
+        let z = changed.add_var(1.0, (0.0, 1.0));
+        let constr = changed.add_constraint([(z, 1.0)], ComparisonOp::Le, 1.0);
+        assert_ne!(changed.fingerprint(), fingerprint);
+        changed.remove_constraint(constr).unwrap();
+        changed.remove_var(z).unwrap();
+        assert_eq!(changed.fingerprint(), fingerprint);
+        changed.compact();
+        assert_eq!(changed.fingerprint(), fingerprint);
+
+        // The value must not change between versions.
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0)], ComparisonOp::Ge, 1.0);
+        assert_eq!(
+            problem.fingerprint().to_string(),
+            "f6c7eba8556f8cf5fe86d2437eee4c59"
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use crate::{helpers::INTEGRALITY_TOL, ComparisonOp, Cut, CutPool, LinearExpr, Solution, Variable};
+
+/// Fractional parts of the MIR rhs closer than this to 0 or 1 are rejected.
+const MIN_FRACTIONALITY: f64 = 1e-3;
+
+/// A row of the problem in the `coeffs * x <= rhs` form.
+struct Row {
+    coeffs: Vec<(usize, f64)>,
+    rhs: f64,
+}
+
+/// Upper bound of a flow: either a constant or a capacity times a binary var.
+#[derive(Clone, Copy)]
+enum FlowBound {
+    Constant(f64),
+    Variable { capacity: f64, binary: usize },
+}
+
+/// A term of the single-node flow row: `sign * coeff * var` (with coeff > 0) where the flow
+/// `coeff * var` is between 0 and `coeff * bound`.
+#[derive(Clone, Copy)]
+struct Flow {
+    var: usize,
+    coeff: f64,
+    is_inflow: bool,
+    bound: Option<FlowBound>,
+}
+
+impl Flow {
+    fn capacity(&self) -> f64 {
+        match self.bound {
+            Some(FlowBound::Constant(max)) => self.coeff * max,
+            Some(FlowBound::Variable { capacity, .. }) => self.coeff * capacity,
+            None => f64::INFINITY,
+        }
+    }
+
+    /// Value of the binary var controlling the flow (1 if the bound is constant).
+    fn binary_val(&self, solution: &Solution) -> f64 {
+        match self.bound {
+            Some(FlowBound::Variable { binary, .. }) => solution[solution.var_ref(binary)],
+            _ => 1.0,
+        }
+    }
+}
+
+/// A linear expression over the problem vars with a constant term.
+struct Affine {
+    coeffs: Vec<f64>,
+    constant: f64,
+}
+
+impl Affine {
+    fn new(num_vars: usize) -> Affine {
+        Affine {
+            coeffs: vec![0.0; num_vars],
+            constant: 0.0,
+        }
+    }
+
+    /// Add mult * (binary value), the value is constant 1 if there is no binary var.
+    fn add_binary(&mut self, flow: &Flow, mult: f64) {
+        match flow.bound {
+            Some(FlowBound::Variable { binary, .. }) => self.coeffs[binary] += mult,
+            _ => self.constant += mult,
+        }
+    }
+
+    /// Add mult * (capacity * binary - flow) (the slack of the flow bound).
+    fn add_bound_slack(&mut self, flow: &Flow, mult: f64) {
+        self.add_binary(flow, mult * flow.capacity());
+        self.coeffs[flow.var] -= mult * flow.coeff;
+    }
+}
+
+fn is_binary(solution: &Solution, is_integer: &[bool], var: usize) -> bool {
+    is_integer[var] && solution.solver.var_bounds(var) == (0.0, 1.0)
+}
+
+/// Collect the problem rows in the `<=` form.
+fn collect_rows(solution: &Solution) -> Vec<Row> {
+    let mut rows = vec![];
+    for constr in 0..solution.solver.num_constraints() {
+        let (coeffs, min, max) = solution.solver.constraint_row(constr);
+        if max.is_finite() {
+            rows.push(Row {
+                coeffs: coeffs.clone(),
+                rhs: max,
+            });
+        }
+        if min.is_finite() {
+            rows.push(Row {
+                coeffs: coeffs.into_iter().map(|(v, c)| (v, -c)).collect(),
+                rhs: -min,
+            });
+        }
+    }
+    rows
+}
+
+/// Find the variable upper bounds y <= capacity * x for continuous vars y and binary vars x.
+/// If there are several bounds for a var, the tightest (with the smallest capacity) is used.
+fn find_variable_bounds(
+    solution: &Solution,
+    is_integer: &[bool],
+    rows: &[Row],
+) -> Vec<Option<FlowBound>> {
+    let mut res = vec![None; solution.num_vars];
+    for row in rows {
+        if row.coeffs.len() != 2 || row.rhs != 0.0 {
+            continue;
+        }
+        for &((y, y_coeff), (x, x_coeff)) in &[
+            (row.coeffs[0], row.coeffs[1]),
+            (row.coeffs[1], row.coeffs[0]),
+        ] {
+            if is_integer[y] || !is_binary(solution, is_integer, x) {
+                continue;
+            }
+            if y_coeff > 0.0 && x_coeff < 0.0 {
+                let capacity = -x_coeff / y_coeff;
+                let is_tighter = match res[y] {
+                    Some(FlowBound::Variable { capacity: cur, .. }) => capacity < cur,
+                    _ => true,
+                };
+                if is_tighter {
+                    res[y] = Some(FlowBound::Variable {
+                        capacity,
+                        binary: x,
+                    });
+                }
+            }
+        }
+    }
+    res
+}
+
+/// Represent the row as a single-node flow set. Returns None if some var doesn't fit.
+fn as_flow_row(
+    solution: &Solution,
+    is_integer: &[bool],
+    var_bounds: &[Option<FlowBound>],
+    row: &Row,
+) -> Option<Vec<Flow>> {
+    let mut flows = vec![];
+    for &(var, coeff) in &row.coeffs {
+        if coeff == 0.0 {
+            continue;
+        }
+        let is_inflow = coeff > 0.0;
+        let bound = if is_binary(solution, is_integer, var) {
+            // A binary var is a flow equal to its bound.
+            Some(FlowBound::Variable {
+                capacity: 1.0,
+                binary: var,
+            })
+        } else if is_integer[var] {
+            return None;
+        } else {
+            let (min, max) = solution.solver.var_bounds(var);
+            if min != 0.0 {
+                return None;
+            }
+            match var_bounds[var] {
+                Some(bound) => Some(bound),
+                None if max.is_finite() => Some(FlowBound::Constant(max)),
+                None if !is_inflow => None,
+                None => return None,
+            }
+        };
+        flows.push(Flow {
+            var,
+            coeff: coeff.abs(),
+            is_inflow,
+            bound,
+        });
+    }
+    Some(flows)
+}
+
+/// MIR rounding function.
+fn mir_coeff(a: f64, f: f64) -> f64 {
+    let frac = a - a.floor();
+    a.floor() + f64::max(frac - f, 0.0) / (1.0 - f)
+}
+
+/// Derive the lifted flow cover inequality for the cover as a MIR inequality with
+/// the divisor delta. Returns the cut as (coeffs * x <= rhs).
+fn cover_cut(
+    solution: &Solution,
+    flows: &[Flow],
+    rhs: f64,
+    in_cover: &[bool],
+    lambda: f64,
+    delta: f64,
+) -> Option<(Vec<f64>, f64)> {
+    // Substituting flow = capacity * binary - slack for bounded flows gives the relaxation
+    // sum(a_j * z_j) <= beta + s where z_j are the binary vars (complemented in the cover)
+    // and s >= 0 is the sum of bound slacks and unbounded outflows.
+    let num_vars = solution.num_vars;
+    let mut int_part = Affine::new(num_vars);
+    let mut beta = rhs;
+    let mut cont_part = Affine::new(num_vars);
+    let mut mir_terms = vec![];
+    for (flow, &in_cover) in flows.iter().zip(in_cover) {
+        if flow.bound.is_none() {
+            // Unbounded outflow goes to s.
+            cont_part.coeffs[flow.var] += flow.coeff;
+            continue;
+        }
+        let capacity = flow.capacity();
+        if flow.is_inflow {
+            cont_part.add_bound_slack(flow, 1.0);
+            if in_cover {
+                // capacity * binary = capacity - capacity * (1 - binary)
+                beta -= capacity;
+                mir_terms.push((flow, -capacity, true));
+            } else {
+                mir_terms.push((flow, capacity, false));
+            }
+        } else if flow.binary_val(solution) * lambda
+            < solution[solution.var_ref(flow.var)] * flow.coeff
+        {
+            // -flow = -capacity * binary + slack, the slack can be dropped.
+            mir_terms.push((flow, -capacity, false));
+        } else {
+            cont_part.coeffs[flow.var] += flow.coeff;
+        }
+    }
+
+    let beta_scaled = beta / delta;
+    let f = beta_scaled - beta_scaled.floor();
+    if f.min(1.0 - f) < MIN_FRACTIONALITY {
+        return None;
+    }
+
+    // MIR: sum(F(a_j / delta) * z_j) <= floor(beta / delta) + s / (delta * (1 - f)),
+    // multiplied by delta * (1 - f).
+    let mult = delta * (1.0 - f);
+    for (flow, a, complemented) in mir_terms {
+        let coeff = mult * mir_coeff(a / delta, f);
+        if complemented {
+            int_part.add_binary(flow, -coeff);
+            int_part.constant += coeff;
+        } else {
+            int_part.add_binary(flow, coeff);
+        }
+    }
+    int_part.constant -= mult * beta_scaled.floor();
+
+    // int_part - cont_part <= 0
+    let coeffs: Vec<f64> = int_part
+        .coeffs
+        .iter()
+        .zip(&cont_part.coeffs)
+        .map(|(a, b)| a - b)
+        .collect();
+    let rhs = cont_part.constant - int_part.constant;
+    Some((coeffs, rhs))
+}
+
+fn separate_row(solution: &Solution, flows: &[Flow], rhs: f64) -> Option<(Vec<f64>, f64)> {
+    // Choose the cover greedily: inflows with the biggest values of the binary vars first.
+    let mut order: Vec<usize> = (0..flows.len())
+        .filter(|&i| flows[i].is_inflow && flows[i].bound.is_some())
+        .collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&flows[a], &flows[b]);
+        let key = |f: &Flow| (f.binary_val(solution), f.capacity());
+        key(b).partial_cmp(&key(a)).unwrap()
+    });
+
+    let mut in_cover = vec![false; flows.len()];
+    let mut cover_capacity = 0.0;
+    for &i in &order {
+        if cover_capacity > rhs {
+            break;
+        }
+        in_cover[i] = true;
+        cover_capacity += flows[i].capacity();
+    }
+    let lambda = cover_capacity - rhs;
+    if lambda <= INTEGRALITY_TOL {
+        return None;
+    }
+
+    // Try the capacities of the cover items as MIR divisors and choose the most violated cut.
+    let mut best: Option<(Vec<f64>, f64)> = None;
+    let mut best_violation = 0.0;
+    for (i, flow) in flows.iter().enumerate() {
+        if !in_cover[i] || flow.capacity() <= lambda {
+            continue;
+        }
+        if let Some((coeffs, cut_rhs)) =
+            cover_cut(solution, flows, rhs, &in_cover, lambda, flow.capacity())
+        {
+            let lhs: f64 = coeffs
+                .iter()
+                .enumerate()
+                .map(|(v, c)| c * solution[solution.var_ref(v)])
+                .sum();
+            let violation = lhs - cut_rhs;
+            if violation > best_violation {
+                best_violation = violation;
+                best = Some((coeffs, cut_rhs));
+            }
+        }
+    }
+    best
+}
+
+pub(crate) fn flow_cover_cuts(
+    solution: &Solution,
+    integer_vars: &[Variable],
+    max_cuts: usize,
+) -> Vec<Cut> {
+    let mut is_integer = vec![false; solution.num_vars];
+    for v in integer_vars {
+        is_integer[v.0] = true;
+    }
+
+    let rows = collect_rows(solution);
+    let var_bounds = find_variable_bounds(solution, &is_integer, &rows);
+
+    let mut pool = CutPool::new();
+    for row in &rows {
+        let flows = match as_flow_row(solution, &is_integer, &var_bounds, row) {
+            Some(flows) => flows,
+            None => continue,
+        };
+        // Variable bound rows themselves are not interesting.
+        if flows.len() < 2 || flows.iter().all(|f| f.bound.is_none()) {
+            continue;
+        }
+        if let Some((coeffs, rhs)) = separate_row(solution, &flows, row.rhs) {
+            let expr: LinearExpr = coeffs
+                .iter()
+                .enumerate()
+                .filter(|&(_, &c)| c.abs() > INTEGRALITY_TOL)
+                .map(|(v, &c)| (solution.var_ref(v), c))
+                .collect();
+            pool.add(Cut::new(expr, ComparisonOp::Le, rhs));
+        }
+    }
+    pool.separate(solution, max_cuts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// Check that the cut is satisfied by all points of the problem with the binary vars
+    /// fixed to each possible assignment.
+    fn assert_valid(problem: &Problem, binaries: &[Variable], cut: &Cut) {
+        for mask in 0..(1 << binaries.len()) {
+            let mut fixed = problem.clone();
+            for (i, &b) in binaries.iter().enumerate() {
+                let val = ((mask >> i) & 1) as f64;
+                fixed.add_constraint([(b, 1.0)], ComparisonOp::Eq, val);
+            }
+            // Maximize the cut lhs over the fixed problem.
+            let mut obj = Problem::new(OptimizationDirection::Maximize);
+            let lhs = cut.lhs();
+            let mut coeffs = vec![0.0; fixed.obj_coeffs.len()];
+            for (&v, &c) in lhs.vars.iter().zip(&lhs.coeffs) {
+                coeffs[v] = c;
+            }
+            for (v, &c) in coeffs.iter().enumerate() {
+                obj.add_var(c, (fixed.var_mins[v], fixed.var_maxs[v]));
+            }
+            for (coeffs, cmp_op, rhs) in &fixed.constraints {
+                let expr: LinearExpr = coeffs.iter().map(|(v, &c)| (obj.var_ref(v), c)).collect();
+                obj.add_constraint(expr, *cmp_op, *rhs);
+            }
+            if let Ok(sol) = obj.solve() {
+                assert!(
+                    sol.objective() <= cut.rhs() + 1e-6,
+                    "cut {:?} is violated for binaries {:b}",
+                    cut,
+                    mask
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_node_flow() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let capacities = [6.0, 7.0, 4.0, 3.0];
+        let fixed_costs = [10.0, 13.0, 9.0, 5.0];
+        let mut flows = vec![];
+        let mut binaries = vec![];
+        for (&capacity, &cost) in capacities.iter().zip(&fixed_costs) {
+            let y = problem.add_var(3.0, (0.0, f64::INFINITY));
+            let x = problem.add_var(-cost, (0.0, 1.0));
+            problem.add_constraint([(y, 1.0), (x, -capacity)], ComparisonOp::Le, 0.0);
+            flows.push(y);
+            binaries.push(x);
+        }
+        // Outflow with a constant bound.
+        let out = problem.add_var(-1.0, (0.0, 2.0));
+        let mut balance: Vec<_> = flows.iter().map(|&y| (y, 1.0)).collect();
+        balance.push((out, -1.0));
+        problem.add_constraint(balance, ComparisonOp::Le, 10.0);
+
+        let mut sol = problem.solve().unwrap();
+        let mut num_cuts = 0;
+        for _ in 0..10 {
+            let cuts = sol.flow_cover_cuts(&binaries, 10);
+            if cuts.is_empty() {
+                break;
+            }
+            for cut in cuts {
+                assert!(cut.violation(&sol) > 1e-6);
+                assert_valid(&problem, &binaries, &cut);
+                problem.add_constraint(cut.lhs(), ComparisonOp::Le, cut.rhs());
+                num_cuts += 1;
+            }
+            sol = problem.solve().unwrap();
+        }
+        assert!(num_cuts > 0);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::{
+    failure, solver::Solver, Error, OptimizationDirection, Problem, Solution, SolverOptions,
+};
+use std::task::Poll;
+
+/// A solve performed incrementally, a bounded amount of work at a time.
+///
+/// Created by [`Problem::start_solve`]. Each call to [`step`](#method.step) performs
+/// at most the given number of simplex iterations and returns [`Poll::Pending`] if the solve
+/// is not finished yet. This allows to run the solver cooperatively, e.g. from an async
+/// task that yields to the executor between the steps or from the main loop of a game or
+/// a UI, without dedicating a thread to it. Unlike [`Problem::solve_with`], the problem is
+/// never dualized, so the result is the same as solving it with [`Dualization::Never`].
+/// A solve that takes too long can be abandoned with [`stop`](#method.stop),
+/// which returns what is known about the solution so far.
+///
+/// [`Problem::start_solve`]: struct.Problem.html#method.start_solve
+/// [`Problem::solve_with`]: struct.Problem.html#method.solve_with
+/// [`Dualization::Never`]: enum.Dualization.html#variant.Never
+/// [`Poll::Pending`]: https://doc.rust-lang.org/std/task/enum.Poll.html#variant.Pending
+pub struct SolveHandle {
+    state: State,
+    direction: OptimizationDirection,
+    num_vars: usize,
+    obj_offset: f64,
+    generation: u32,
+    /// The problem and the options to write the failure reproducer, if the dump directory
+    /// is set.
+    failure_dump: Option<Box<(Problem, SolverOptions)>>,
+}
+
+enum State {
+    Running(Box<Solver>),
+    Failed(Error),
+    Finished,
+}
+
+impl std::fmt::Debug for SolveHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SolveHandle")
+            .field("direction", &self.direction)
+            .field("num_vars", &self.num_vars)
+            .field("iterations", &self.iterations())
+            .field("is_finished", &matches!(self.state, State::Finished))
+            .finish()
+    }
+}
+
+impl SolveHandle {
+    pub(crate) fn new(problem: &Problem, options: &SolverOptions) -> Self {
+        problem.warn_if_badly_scaled(options);
+        let options = problem.solver_options(options);
+        let solver = failure::guard(problem, &options, || {
+            Solver::try_new_with_options(
+                &problem.obj_coeffs,
+                &problem.var_mins,
+                &problem.var_maxs,
+                &problem.constraints,
+                None,
+                &options,
+            )
+        });
+        let failure_dump = if options.failure_dump_dir.is_some() {
+            Some(Box::new((problem.clone(), options.into_owned())))
+        } else {
+            None
+        };
+        SolveHandle {
+            state: match solver {
+                Ok(solver) => State::Running(Box::new(solver)),
+                Err(err) => State::Failed(err.in_generation(problem.generation)),
+            },
+            direction: problem.direction,
+            num_vars: problem.obj_coeffs.len(),
+            obj_offset: problem.obj_offset,
+            generation: problem.generation,
+            failure_dump,
+        }
+    }
+
+    /// Continue the solve for at most `max_iterations` simplex iterations (a few more are
+    /// possible with the [multiple pricing](enum.Pricing.html#variant.Multiple)). Returns
+    /// the result of the solve when it is finished.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if called again after the result was returned.
+    pub fn step(&mut self, max_iterations: usize) -> Poll<Result<Solution, Error>> {
+        let res = match &mut self.state {
+            State::Running(solver) => {
+                let res = run(self.failure_dump.as_deref(), solver, |solver| {
+                    solver.initial_solve_step(max_iterations)
+                });
+                match res {
+                    Ok(false) => return Poll::Pending,
+                    Ok(true) => Ok(()),
+                    Err(err) => Err(err.in_generation(self.generation)),
+                }
+            }
+            State::Failed(_) => Ok(()),
+            State::Finished => panic!("the solve is already finished"),
+        };
+
+        Poll::Ready(match std::mem::replace(&mut self.state, State::Finished) {
+            State::Running(solver) => res.map(|()| Solution {
+                direction: self.direction,
+                num_vars: self.num_vars,
+                obj_offset: self.obj_offset,
+                generation: self.generation,
+                solver: *solver,
+            }),
+            State::Failed(err) => Err(err),
+            State::Finished => unreachable!(),
+        })
+    }
+
+    /// Stop the solve before it is finished (e.g. when a time limit is reached or the user
+    /// cancels it) and return what is known about the solution so far.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the result of the solve was already returned by [`step`](#method.step).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let vars = (0..50)
+    ///     .map(|i| problem.add_var(1.0 + (i % 7) as f64, (0.0, f64::INFINITY)))
+    ///     .collect::<Vec<_>>();
+    /// for i in 0..20 {
+    ///     let expr = vars.iter().enumerate().map(|(j, &v)| (v, 1.0 + ((i + j) % 4) as f64));
+    ///     problem.add_constraint(expr, ComparisonOp::Le, 100.0);
+    /// }
+    ///
+    /// let time_limit = Duration::from_millis(1);
+    /// let start = Instant::now();
+    /// let mut handle = problem.start_solve(&SolverOptions::default());
+    /// while start.elapsed() < time_limit {
+    ///     if handle.step(1).is_ready() {
+    ///         return;
+    ///     }
+    /// }
+    /// let partial = handle.stop();
+    /// if let (Some(solution), Some(bound)) = (&partial.solution, partial.bound) {
+    ///     assert!(solution.objective() <= bound + 1e-8);
+    /// }
+    /// ```
+    pub fn stop(self) -> PartialResult {
+        let mut solver = match self.state {
+            State::Running(solver) => solver,
+            State::Failed(_) => {
+                return PartialResult {
+                    iterations: 0,
+                    solution: None,
+                    bound: None,
+                }
+            }
+            State::Finished => panic!("the solve is already finished"),
+        };
+
+        let (direction, obj_offset) = (self.direction, self.obj_offset);
+        let bound = run(
+            self.failure_dump.as_deref(),
+            &mut solver,
+            Solver::stop_initial_solve,
+        );
+        PartialResult {
+            iterations: solver.stats.iterations,
+            bound: bound.map(|bound| match direction {
+                OptimizationDirection::Minimize => bound + obj_offset,
+                OptimizationDirection::Maximize => -(bound + obj_offset),
+            }),
+            solution: if solver.is_primal_feasible() {
+                Some(Solution {
+                    direction: self.direction,
+                    num_vars: self.num_vars,
+                    obj_offset: self.obj_offset,
+                    generation: self.generation,
+                    solver: *solver,
+                })
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Number of simplex iterations performed so far.
+    pub fn iterations(&self) -> usize {
+        match &self.state {
+            State::Running(solver) => solver.stats.iterations,
+            _ => 0,
+        }
+    }
+}
+
+/// Run `f` on the solver, writing the failure reproducer if it panics and the dump is set.
+fn run<T>(
+    failure_dump: Option<&(Problem, SolverOptions)>,
+    solver: &mut Solver,
+    f: impl FnOnce(&mut Solver) -> T,
+) -> T {
+    match failure_dump {
+        Some((problem, options)) => failure::guard_solver(problem, options, solver, f),
+        None => f(solver),
+    }
+}
+
+/// What is known about the solution of a problem when the solve is stopped before it is
+/// finished, see [`SolveHandle::stop`](struct.SolveHandle.html#method.stop).
+#[derive(Clone, Debug)]
+pub struct PartialResult {
+    /// Number of simplex iterations performed before the solve was stopped.
+    pub iterations: usize,
+    /// The last feasible solution, if the solve got that far. It is not
+    /// [optimal](struct.Solution.html#method.is_optimal), but it contains the basis
+    /// and can be reoptimized like any other solution, e.g. by fixing a variable.
+    pub solution: Option<Solution>,
+    /// The best known bound on the optimal objective value (lower bound when minimizing,
+    /// upper bound when maximizing), if it is finite.
+    pub bound: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn solve_steps() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let vars: Vec<_> = (0..20)
+            .map(|i| problem.add_var(1.0 + (i % 3) as f64, (0.0, f64::INFINITY)))
+            .collect();
+        for i in 0..10 {
+            let expr = (vars.iter().enumerate())
+                .map(|(j, &v)| (v, 1.0 + ((i * j) % 5) as f64))
+                .collect::<LinearExpr>();
+            problem.add_constraint(expr, ComparisonOp::Le, 10.0 + i as f64);
+        }
+        problem.add_constraint([(vars[0], 1.0), (vars[1], 1.0)], ComparisonOp::Ge, 1.0);
+
+        for algorithm in [Algorithm::Primal, Algorithm::Dual] {
+            for pricing in [Pricing::Full, Pricing::Multiple] {
+                let options = SolverOptions {
+                    algorithm,
+                    pricing,
+                    dualization: Dualization::Never,
+                    ..Default::default()
+                };
+                let expected = problem.solve_with(&options).unwrap();
+
+                let mut handle = problem.start_solve(&options);
+                let mut num_steps = 0;
+                let solution = loop {
+                    num_steps += 1;
+                    let iterations = handle.iterations();
+                    match handle.step(1) {
+                        std::task::Poll::Ready(res) => break res.unwrap(),
+                        std::task::Poll::Pending => assert!(handle.iterations() > iterations),
+                    }
+                };
+                assert!(num_steps > 2);
+                assert_eq!(solution.objective(), expected.objective());
+                assert_eq!(solution.stats().iterations, expected.stats().iterations);
+                for &v in &vars {
+                    assert_eq!(solution[v], expected[v]);
+                }
+            }
+        }
+
+        // Errors are returned as results of the steps.
+        problem.add_constraint([(vars[0], 1.0)], ComparisonOp::Ge, 100.0);
+        let mut handle = problem.start_solve(&SolverOptions::default());
+        let res = loop {
+            if let std::task::Poll::Ready(res) = handle.step(2) {
+                break res;
+            }
+        };
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Infeasible);
+    }
+
+    #[test]
+    fn stop() {
+        // With finite upper bounds the dual simplex is used, otherwise the primal one.
+        for var_max in [10.0, f64::INFINITY] {
+            let mut problem = Problem::new(OptimizationDirection::Maximize);
+            let vars: Vec<_> = (0..30)
+                .map(|i| problem.add_var(1.0 + (i % 7) as f64, (0.0, var_max)))
+                .collect();
+            for i in 0..15 {
+                let expr = (vars.iter().enumerate())
+                    .map(|(j, &v)| (v, 1.0 + ((i + j) % 4) as f64))
+                    .collect::<LinearExpr>();
+                problem.add_constraint(expr, ComparisonOp::Le, 100.0);
+            }
+            let solution = problem.solve().unwrap();
+            let (optimum, total_iterations) = (solution.objective(), solution.stats().iterations);
+
+            let (mut num_bounds, mut num_feasible) = (0, 0);
+            for num_steps in 0..total_iterations {
+                let mut handle = problem.start_solve(&SolverOptions::default());
+                for _ in 0..num_steps {
+                    assert!(handle.step(1).is_pending());
+                }
+                let partial = handle.stop();
+                assert_eq!(partial.iterations, num_steps);
+                if let Some(bound) = partial.bound {
+                    num_bounds += 1;
+                    assert!(bound >= optimum - 1e-8);
+                }
+                if let Some(solution) = partial.solution {
+                    num_feasible += 1;
+                    assert!(solution.objective() <= optimum + 1e-8);
+                    assert!(!solution.is_optimal());
+                    let report = SolveReport::new(&problem, &Ok(solution.clone()));
+                    assert!(report.max_primal_violation.unwrap() < 1e-8);
+                    // It can be reoptimized.
+                    let solution = solution.fix_var(vars[0], 1.0).unwrap();
+                    assert!(solution.is_optimal());
+                }
+            }
+            assert_eq!(num_bounds, total_iterations);
+            assert_eq!(num_feasible > 0, var_max == f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn options() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+        for i in 0..10 {
+            problem.add_constraint([(x, 1.0), (y, i as f64)], ComparisonOp::Ge, 10.0);
+        }
+        let expected = problem
+            .solve_with(&SolverOptions {
+                dualization: Dualization::Never,
+                ..Default::default()
+            })
+            .unwrap();
+
+        // The problem is not dualized and the solve is guarded by the failure dump.
+        let dir = std::env::temp_dir().join(format!("minilp-handle-test-{}", std::process::id()));
+        let options = SolverOptions {
+            dualization: Dualization::Always,
+            failure_dump_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        let mut handle = problem.start_solve(&options);
+        let solution = loop {
+            if let std::task::Poll::Ready(res) = handle.step(1) {
+                break res.unwrap();
+            }
+        };
+        assert_eq!(solution.objective(), expected.objective());
+        assert_eq!(solution.stats().iterations, expected.stats().iterations);
+        assert!(!dir.exists());
+    }
+}
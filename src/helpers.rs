@@ -1,10 +1,18 @@
 use sprs::{CsVecBase, CsVecView};
 use std::ops::Deref;
 
+/// Values closer than this to an integer are considered integral, both in the problem data
+/// (coefficients, rhs and bounds) and in the solutions.
+pub(crate) const INTEGRALITY_TOL: f64 = 1e-9;
+
+pub(crate) fn is_integral(val: f64) -> bool {
+    f64::abs(val - val.round()) <= INTEGRALITY_TOL
+}
+
 pub(crate) fn resized_view<IStorage, DStorage>(
     vec: &CsVecBase<IStorage, DStorage>,
     len: usize,
-) -> CsVecView<f64>
+) -> CsVecView<'_, f64>
 where
     IStorage: Deref<Target = [usize]>,
     DStorage: Deref<Target = [f64]>,
@@ -36,6 +44,27 @@ where
     dense
 }
 
+/// A small pseudo-random number generator (SplitMix64). Not suitable for cryptography, but
+/// fast, deterministic for a given seed and good enough for breaking ties.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
 #[cfg(test)]
 use sprs::{CsMat, CsVec};
 
@@ -0,0 +1,534 @@
+use crate::{
+    solve_log::write_json_str, ComparisonOp, LinearExpr, OptimizationDirection, Problem, Variable,
+};
+use std::{collections::HashMap, fmt::Write as _, io};
+
+/// Write the problem as a JSON document, see [`JsonFile`](struct.JsonFile.html) for
+/// the format.
+pub(crate) fn write_json(problem: &Problem, mut out: impl io::Write) -> io::Result<()> {
+    let num = |val: f64| {
+        if val.is_finite() {
+            format!("{:?}", val)
+        } else {
+            "null".to_owned()
+        }
+    };
+
+    let (direction, obj_sign) = match problem.direction {
+        OptimizationDirection::Minimize => ("minimize", 1.0),
+        OptimizationDirection::Maximize => ("maximize", -1.0),
+    };
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"direction\": \"{}\",", direction)?;
+    writeln!(
+        out,
+        "  \"objective_offset\": {},",
+        num(problem.objective_offset())
+    )?;
+
+    writeln!(out, "  \"variables\": [")?;
+    let num_vars = problem.obj_coeffs.len();
+    for v in 0..num_vars {
+        let mut line = String::from("    {");
+        if let Some(name) = &problem.var_names[v] {
+            line.push_str("\"name\": ");
+            write_json_str(&mut line, name);
+            line.push_str(", ");
+        }
+        write!(
+            line,
+            "\"objective\": {}, \"min\": {}, \"max\": {}}}",
+            num(obj_sign * problem.obj_coeffs[v]),
+            num(problem.var_mins[v]),
+            num(problem.var_maxs[v]),
+        )
+        .unwrap();
+        let sep = if v + 1 < num_vars { "," } else { "" };
+        writeln!(out, "{}{}", line, sep)?;
+    }
+    writeln!(out, "  ],")?;
+
+    writeln!(out, "  \"constraints\": [")?;
+    let num_constraints = problem.constraints.len();
+    for (c, (coeffs, cmp_op, rhs)) in problem.constraints.iter().enumerate() {
+        let terms: Vec<String> = coeffs
+            .iter()
+            .map(|(v, &coeff)| format!("[{}, {}]", v, num(coeff)))
+            .collect();
+        let op = match cmp_op {
+            ComparisonOp::Eq => "=",
+            ComparisonOp::Le => "<=",
+            ComparisonOp::Ge => ">=",
+        };
+        let sep = if c + 1 < num_constraints { "," } else { "" };
+        writeln!(
+            out,
+            "    {{\"terms\": [{}], \"op\": \"{}\", \"rhs\": {}}}{}",
+            terms.join(", "),
+            op,
+            num(*rhs),
+            sep
+        )?;
+    }
+    writeln!(out, "  ]")?;
+    writeln!(out, "}}")
+}
+
+/// A linear programming problem parsed from a JSON document.
+///
+/// The document is an object with the following fields (unknown fields are ignored):
+///
+/// * `direction`: `"minimize"` or `"maximize"`.
+/// * `objective_offset` (optional, default 0): the constant term of the objective.
+/// * `variables`: an array of objects with the fields `name` (optional), `objective`
+///   (the objective coefficient, optional, default 0), `min` and `max` (the bounds, optional,
+///   default 0 and infinity). A `null` bound means that the variable is unbounded in
+///   that direction.
+/// * `constraints`: an array of objects with the fields `terms` (an array of pairs of
+///   the index of the variable in the `variables` array and its coefficient), `op`
+///   (`"<="`, `">="` or `"="`) and `rhs` (the right-hand side).
+///
+/// For example:
+///
+/// ```json
+/// {
+///   "direction": "maximize",
+///   "variables": [
+///     {"name": "x", "objective": 1.0},
+///     {"name": "y", "objective": 2.0, "min": 0.0, "max": 3.0}
+///   ],
+///   "constraints": [
+///     {"terms": [[0, 1.0], [1, 1.0]], "op": "<=", "rhs": 4.0}
+///   ]
+/// }
+/// ```
+///
+/// The output of [`Problem::write_json`] is in this format.
+///
+/// [`Problem::write_json`]: struct.Problem.html#method.write_json
+#[derive(Clone)]
+pub struct JsonFile {
+    /// A mapping of a variable name to the corresponding [`Variable`] (for the variables
+    /// with names).
+    pub variables: HashMap<String, Variable>,
+    /// A parsed problem.
+    pub problem: Problem,
+}
+
+impl std::fmt::Debug for JsonFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonFile")
+            .field("problem", &self.problem)
+            .finish()
+    }
+}
+
+impl JsonFile {
+    /// Parses a linear programming problem from a JSON document in the format described
+    /// in the [type documentation](struct.JsonFile.html).
+    ///
+    /// # Errors
+    ///
+    /// Apart from I/O errors coming from `input`, this function will signal any syntax error
+    /// or a document not matching the format as [`std::io::Error`] with the kind set to
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData). Documents with values nested more
+    /// than 128 levels deep are rejected, so untrusted input can't exhaust the stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let input = r#"{
+    ///   "direction": "maximize",
+    ///   "variables": [
+    ///     {"name": "x", "objective": 1.0},
+    ///     {"name": "y", "objective": 2.0, "max": 3.0}
+    ///   ],
+    ///   "constraints": [
+    ///     {"terms": [[0, 1.0], [1, 1.0]], "op": "<=", "rhs": 4.0},
+    ///     {"terms": [[0, 2.0], [1, 1.0]], "op": ">=", "rhs": 2.0}
+    ///   ]
+    /// }"#;
+    /// let file = JsonFile::parse(input.as_bytes()).unwrap();
+    /// let solution = file.problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 7.0);
+    /// assert_eq!(solution[file.variables["y"]], 3.0);
+    /// ```
+    pub fn parse<R: io::BufRead>(mut input: R) -> io::Result<Self> {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        let mut parser = Parser {
+            text: &text,
+            pos: 0,
+            depth: 0,
+        };
+        parser.skip_whitespace();
+        let doc = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.err("unexpected characters after the document"));
+        }
+
+        let direction = match doc.field("direction")? {
+            Some(Value::Str(dir)) if dir == "minimize" => OptimizationDirection::Minimize,
+            Some(Value::Str(dir)) if dir == "maximize" => OptimizationDirection::Maximize,
+            _ => return Err(invalid("direction must be \"minimize\" or \"maximize\"")),
+        };
+        let mut problem = Problem::new(direction);
+        problem.set_objective_offset(doc.num_field("objective_offset", Some(0.0))?);
+
+        let mut variables = HashMap::new();
+        for var_def in doc.array_field("variables")? {
+            let obj_coeff = var_def.num_field("objective", Some(0.0))?;
+            let min = var_def.bound_field("min", 0.0, f64::NEG_INFINITY)?;
+            let max = var_def.bound_field("max", f64::INFINITY, f64::INFINITY)?;
+            let var = problem.try_add_var(obj_coeff, (min, max))?;
+            match var_def.field("name")? {
+                Some(Value::Str(name)) => {
+                    problem.set_var_name(var, name.clone());
+                    variables.insert(name.clone(), var);
+                }
+                None => {}
+                Some(_) => return Err(invalid("variable name must be a string")),
+            }
+        }
+
+        let num_vars = problem.obj_coeffs.len();
+        for constr_def in doc.array_field("constraints")? {
+            let mut terms = vec![];
+            for term in constr_def.array_field("terms")? {
+                match term {
+                    Value::Array(pair) => match pair.as_slice() {
+                        [Value::Num(idx), Value::Num(coeff)]
+                            if idx.fract() == 0.0 && *idx >= 0.0 && *idx < num_vars as f64 =>
+                        {
+                            terms.push((problem.var_ref(*idx as usize), *coeff));
+                        }
+                        _ => return Err(invalid("invalid constraint term")),
+                    },
+                    _ => return Err(invalid("constraint term must be an array")),
+                }
+            }
+            let cmp_op = match constr_def.field("op")? {
+                Some(Value::Str(op)) if op == "<=" => ComparisonOp::Le,
+                Some(Value::Str(op)) if op == ">=" => ComparisonOp::Ge,
+                Some(Value::Str(op)) if op == "=" => ComparisonOp::Eq,
+                _ => return Err(invalid("constraint op must be \"<=\", \">=\" or \"=\"")),
+            };
+            let rhs = constr_def.num_field("rhs", None)?;
+            problem.try_add_constraint(terms.into_iter().collect::<LinearExpr>(), cmp_op, rhs)?;
+        }
+
+        Ok(JsonFile { variables, problem })
+    }
+}
+
+/// Max nesting depth of values in the document (valid problems need at most 6 levels).
+const MAX_DEPTH: usize = 128;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+enum Value {
+    Null,
+    /// `true` or `false`, no field of the format is boolean.
+    Bool,
+    Num(f64),
+    Str(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn field(&self, name: &str) -> io::Result<Option<&Value>> {
+        match self {
+            Value::Object(fields) => Ok(fields.iter().find(|f| f.0 == name).map(|f| &f.1)),
+            _ => Err(invalid("expected an object")),
+        }
+    }
+
+    fn num_field(&self, name: &str, default: Option<f64>) -> io::Result<f64> {
+        match (self.field(name)?, default) {
+            (Some(Value::Num(val)), _) => Ok(*val),
+            (None, Some(default)) => Ok(default),
+            _ => Err(invalid(&format!(
+                "expected a number in the `{}` field",
+                name
+            ))),
+        }
+    }
+
+    /// A bound of a variable, `null` means `unbounded`.
+    fn bound_field(&self, name: &str, default: f64, unbounded: f64) -> io::Result<f64> {
+        match self.field(name)? {
+            Some(Value::Null) => Ok(unbounded),
+            _ => self.num_field(name, Some(default)),
+        }
+    }
+
+    fn array_field(&self, name: &str) -> io::Result<&[Value]> {
+        match self.field(name)? {
+            Some(Value::Array(vals)) => Ok(vals),
+            _ => Err(invalid(&format!(
+                "expected an array in the `{}` field",
+                name
+            ))),
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// Number of values being parsed (enclosing the current position).
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn err(&self, msg: &str) -> io::Error {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        invalid(&format!("line {}: {}", line, msg))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> io::Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.err(&format!("expected `{}`", c as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, val: Value) -> io::Result<Value> {
+        if !self.text[self.pos..].starts_with(keyword) {
+            return Err(self.err("invalid value"));
+        }
+        self.pos += keyword.len();
+        Ok(val)
+    }
+
+    fn value(&mut self) -> io::Result<Value> {
+        // Values are parsed recursively, so the nesting depth of untrusted input is limited
+        // to keep the stack from overflowing.
+        if self.depth == MAX_DEPTH {
+            return Err(self.err(&format!("nesting depth exceeds {}", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let res = self.nested_value();
+        self.depth -= 1;
+        res
+    }
+
+    fn nested_value(&mut self) -> io::Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let name = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((name, self.value()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(fields));
+                        }
+                        _ => return Err(self.err("expected `,` or `}`")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut vals = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(vals));
+                }
+                loop {
+                    vals.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(vals));
+                        }
+                        _ => return Err(self.err("expected `,` or `]`")),
+                    }
+                }
+            }
+            Some(b'"') => Ok(Value::Str(self.string()?)),
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool),
+            Some(b'f') => self.keyword("false", Value::Bool),
+            Some(b'-') | Some(b'0'..=b'9') => {
+                let start = self.pos;
+                while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E')
+                | Some(b'0'..=b'9') = self.peek()
+                {
+                    self.pos += 1;
+                }
+                match self.text[start..self.pos].parse() {
+                    Ok(val) => Ok(Value::Num(val)),
+                    Err(_) => Err(self.err("invalid number")),
+                }
+            }
+            _ => Err(self.err("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        if self.peek() != Some(b'"') {
+            return Err(self.err("expected a string"));
+        }
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = match self.text[self.pos..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.err("unterminated string")),
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let hex = self.text.get(self.pos + 1..self.pos + 5);
+                            let code = hex.and_then(|hex| u32::from_str_radix(hex, 16).ok());
+                            // Surrogate pairs are not supported.
+                            match code.and_then(std::char::from_u32) {
+                                Some(c) => {
+                                    self.pos += 4;
+                                    c
+                                }
+                                None => return Err(self.err("invalid unicode escape")),
+                            }
+                        }
+                        _ => return Err(self.err("invalid escape")),
+                    };
+                    self.pos += 1;
+                    out.push(escaped);
+                }
+                c => out.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::io;
+
+    #[test]
+    fn round_trip() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (f64::NEG_INFINITY, 5.0));
+        let y = problem.add_var(2.0, (-1.0, 3.0));
+        problem.set_var_name(y, "y \"quoted\"");
+        problem.set_objective_offset(0.5);
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, -0.25)], ComparisonOp::Eq, -1.0);
+
+        let mut out = vec![];
+        problem.write_json(&mut out).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert_eq!(
+            json,
+            r#"{
+  "direction": "maximize",
+  "objective_offset": 0.5,
+  "variables": [
+    {"objective": 1.0, "min": null, "max": 5.0},
+    {"name": "y \"quoted\"", "objective": 2.0, "min": -1.0, "max": 3.0}
+  ],
+  "constraints": [
+    {"terms": [[0, 1.0], [1, 1.0]], "op": "<=", "rhs": 4.0},
+    {"terms": [[0, -0.25]], "op": "=", "rhs": -1.0}
+  ]
+}
+"#
+        );
+
+        let file = JsonFile::parse(json.as_bytes()).unwrap();
+        assert!(problem.diff(&file.problem).is_empty());
+        assert_eq!(file.variables.len(), 1);
+        assert_eq!(file.variables["y \"quoted\""], y);
+        assert_eq!(
+            file.problem.solve().unwrap().objective(),
+            problem.solve().unwrap().objective()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |input: &str| JsonFile::parse(input.as_bytes()).unwrap_err().to_string();
+        assert_eq!(
+            err("{\"direction\": \"max\"}"),
+            "direction must be \"minimize\" or \"maximize\""
+        );
+        assert_eq!(err("{\n\"direction\" \"max\"}"), "line 2: expected `:`");
+        assert_eq!(
+            err("[1, 2] x"),
+            "line 1: unexpected characters after the document"
+        );
+        assert_eq!(
+            err(r#"{"direction": "minimize", "variables": [{}]}"#),
+            "expected an array in the `constraints` field"
+        );
+        assert_eq!(
+            err(
+                r#"{"direction": "minimize", "variables": [], "constraints": [
+                {"terms": [[0, 1.0]], "op": "<=", "rhs": 1.0}]}"#
+            ),
+            "invalid constraint term"
+        );
+        assert_eq!(
+            err(
+                r#"{"direction": "minimize", "variables": [{}], "constraints": [
+                {"terms": [[0, 1.0]], "op": "<", "rhs": 1.0}]}"#
+            ),
+            "constraint op must be \"<=\", \">=\" or \"=\""
+        );
+
+        let deep = "[".repeat(200_000);
+        let err = JsonFile::parse(deep.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 1: nesting depth exceeds 128");
+        let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert_eq!(
+            JsonFile::parse(nested.as_bytes()).unwrap_err().to_string(),
+            "expected an object"
+        );
+    }
+}
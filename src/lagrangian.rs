@@ -0,0 +1,498 @@
+use crate::{
+    ComparisonOp, ConstraintRef, CsVec, Error, OptimizationDirection, Problem, Solution,
+    SolverOptions,
+};
+
+/// Subgradients with a smaller norm (after projection) are considered zero.
+const ZERO_SUBGRADIENT_TOL: f64 = 1e-9;
+
+/// A [Lagrangian relaxation] of a problem: a subset of its constraints is removed and
+/// penalized in the objective function instead.
+///
+/// For multipliers `y` the relaxed problem optimizes `c^T x + sum(y[i] * (b[i] - a[i]^T x))`
+/// (summing over the relaxed constraints `a[i]^T x op b[i]`) subject to the remaining
+/// constraints. Multipliers have the same meaning and signs as
+/// [dual values](struct.Solution.html#method.dual_value): for a minimization problem they are
+/// nonpositive for `<=` constraints and nonnegative for `>=` constraints (the other way round
+/// for maximization) and free for equalities. With such multipliers the optimal value of the
+/// relaxed problem is a bound on the optimal value of the original problem (a lower bound
+/// for minimization, an upper bound for maximization).
+///
+/// Relaxing complicating constraints is useful when the remaining problem decomposes or has
+/// a special structure. Relaxed problems and multipliers list the relaxed constraints in
+/// the order in which they were passed to [`Problem::lagrangian_relaxation`].
+///
+/// [Lagrangian relaxation]: https://en.wikipedia.org/wiki/Lagrangian_relaxation
+/// [`Problem::lagrangian_relaxation`]: struct.Problem.html#method.lagrangian_relaxation
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Maximize);
+/// let x = problem.add_var(3.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+/// let labor = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+/// problem.add_constraint(&[(x, 1.0), (y, 3.0)], ComparisonOp::Le, 9.0);
+/// problem.add_constraint(&[(x, 1.0)], ComparisonOp::Le, 3.0);
+/// let solution = problem.solve().unwrap();
+/// assert_eq!(solution.objective(), 11.0);
+///
+/// let relaxation = problem.lagrangian_relaxation(&[labor]);
+/// assert_eq!(relaxation.relaxed_problem(&[0.0]).stats().num_constraints, 2);
+///
+/// // Without the penalty, x = 3 and y = 2 violate the labor constraint by 1.
+/// let bound = relaxation.evaluate(&[0.0]).unwrap();
+/// assert_eq!(bound.bound, 13.0);
+/// assert_eq!(bound.subgradient, vec![-1.0]);
+///
+/// // The optimal dual values of the LP give the best bound.
+/// let multipliers = relaxation.multipliers_from(&solution);
+/// assert_eq!(multipliers, vec![2.0]);
+/// assert_eq!(relaxation.evaluate(&multipliers).unwrap().bound, 11.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LagrangianRelaxation {
+    /// The problem without the relaxed constraints and with the original objective.
+    base: Problem,
+    relaxed: Vec<ConstraintRef>,
+    relaxed_rows: Vec<(CsVec, ComparisonOp, f64)>,
+    /// Original refs of the constraints of the base problem.
+    kept: Vec<ConstraintRef>,
+}
+
+/// The value of the Lagrangian function for some multipliers, see
+/// [`LagrangianRelaxation::evaluate`](struct.LagrangianRelaxation.html#method.evaluate).
+#[derive(Clone, Debug)]
+pub struct LagrangianBound {
+    /// Optimal objective value of the relaxed problem, a bound on the optimal objective value
+    /// of the original problem if the multipliers have the right signs.
+    pub bound: f64,
+    /// A subgradient of the Lagrangian function: the slacks `b[i] - a[i]^T x` of the relaxed
+    /// constraints in the solution of the relaxed problem.
+    pub subgradient: Vec<f64>,
+    /// The solution of the relaxed problem.
+    pub solution: Solution,
+}
+
+/// Options of the subgradient method, see
+/// [`LagrangianRelaxation::subgradient`](struct.LagrangianRelaxation.html#method.subgradient).
+#[derive(Clone, Debug)]
+pub struct SubgradientOptions {
+    /// Max number of evaluations of the Lagrangian function (solves of the relaxed problem).
+    pub max_iterations: usize,
+    /// Objective value of a known feasible solution of the original problem. If present,
+    /// the Polyak step `step_factor * |target - bound| / |subgradient|^2` is taken and the
+    /// method stops when the bound reaches the target. Otherwise the step is
+    /// `step_factor / (iteration * |subgradient|)`.
+    pub target: Option<f64>,
+    /// Initial value of the step factor.
+    pub step_factor: f64,
+    /// The step factor is halved after this many iterations without improvement of the
+    /// best bound.
+    pub patience: usize,
+    /// The method stops when the step factor becomes smaller than this.
+    pub min_step_factor: f64,
+    /// Options used to solve the relaxed problems.
+    pub solver_options: SolverOptions,
+}
+
+impl Default for SubgradientOptions {
+    fn default() -> Self {
+        SubgradientOptions {
+            max_iterations: 100,
+            target: None,
+            step_factor: 2.0,
+            patience: 5,
+            min_step_factor: 1e-4,
+            solver_options: SolverOptions::default(),
+        }
+    }
+}
+
+/// Result of the subgradient method, see
+/// [`LagrangianRelaxation::subgradient`](struct.LagrangianRelaxation.html#method.subgradient).
+#[derive(Clone, Debug)]
+pub struct SubgradientResult {
+    /// The best bound found and the corresponding solution of the relaxed problem.
+    pub best: LagrangianBound,
+    /// Multipliers giving the best bound.
+    pub multipliers: Vec<f64>,
+    /// Number of evaluations of the Lagrangian function.
+    pub iterations: usize,
+    /// True if the projected subgradient at the best multipliers is zero, which means that
+    /// the best bound is the optimal value of the Lagrangian dual problem.
+    pub is_optimal: bool,
+}
+
+impl LagrangianRelaxation {
+    pub(crate) fn new(problem: &Problem, relaxed: &[ConstraintRef]) -> Self {
+        let num_constraints = problem.constraints.len();
+        let mut is_relaxed = vec![false; num_constraints];
+        for &constr in relaxed {
+            assert!(
+                !std::mem::replace(&mut is_relaxed[problem.constraint_idx(constr)], true),
+                "constraint {:?} is relaxed twice",
+                constr
+            );
+        }
+
+        let mut base = problem.clone();
+        let mut kept = vec![];
+        let mut new_refs = vec![None; num_constraints];
+        let mut relaxed_rows = vec![None; num_constraints];
+        let constraints = std::mem::take(&mut base.constraints);
+        for (c, constraint) in constraints.into_iter().enumerate() {
+            if is_relaxed[c] {
+                relaxed_rows[c] = Some(constraint);
+            } else {
+                new_refs[c] = Some(problem.constraint_ref(kept.len()));
+                kept.push(problem.constraint_ref(c));
+                base.constraints.push(constraint);
+            }
+        }
+        base.is_constraint_removed = kept
+            .iter()
+            .map(|constr| problem.is_constraint_removed[constr.0])
+            .collect();
+        base.constraint_tags = problem.constraint_tags.remap(|constr| new_refs[constr.0]);
+
+        LagrangianRelaxation {
+            base,
+            relaxed: relaxed.to_vec(),
+            relaxed_rows: relaxed
+                .iter()
+                .map(|constr| relaxed_rows[constr.0].take().unwrap())
+                .collect(),
+            kept,
+        }
+    }
+
+    /// The relaxed constraints of the original problem.
+    pub fn relaxed_constraints(&self) -> &[ConstraintRef] {
+        &self.relaxed
+    }
+
+    /// The constraint of the original problem corresponding to the constraint of
+    /// the relaxed problem.
+    pub fn original_constraint(&self, constr: ConstraintRef) -> ConstraintRef {
+        self.kept[constr.0]
+    }
+
+    /// Clamp the multipliers to the signs for which they give a valid bound.
+    pub fn project_multipliers(&self, multipliers: &mut [f64]) {
+        self.check_len(multipliers);
+        let is_min = self.base.direction == OptimizationDirection::Minimize;
+        for ((_, cmp_op, _), y) in self.relaxed_rows.iter().zip(multipliers) {
+            match cmp_op {
+                ComparisonOp::Le if is_min => *y = y.min(0.0),
+                ComparisonOp::Ge if !is_min => *y = y.min(0.0),
+                ComparisonOp::Le | ComparisonOp::Ge => *y = y.max(0.0),
+                ComparisonOp::Eq => {}
+            }
+        }
+    }
+
+    /// Multipliers equal to the dual values of the relaxed constraints in the solution of
+    /// the original problem. These give the same bound as the LP relaxation.
+    pub fn multipliers_from(&self, solution: &Solution) -> Vec<f64> {
+        self.relaxed
+            .iter()
+            .map(|&constr| solution.dual_value(constr))
+            .collect()
+    }
+
+    /// Build the relaxed problem for the multipliers.
+    ///
+    /// Its variables are the variables of the original problem and its constraints are the
+    /// constraints that were not relaxed in the original order (see
+    /// [`original_constraint`](#method.original_constraint)).
+    pub fn relaxed_problem(&self, multipliers: &[f64]) -> Problem {
+        self.check_len(multipliers);
+        // The problem is stored in the minimization form.
+        let sign = match self.base.direction {
+            OptimizationDirection::Minimize => 1.0,
+            OptimizationDirection::Maximize => -1.0,
+        };
+        let mut problem = self.base.clone();
+        for ((coeffs, _, rhs), &y) in self.relaxed_rows.iter().zip(multipliers) {
+            problem.obj_offset += sign * y * rhs;
+            for (v, &coeff) in coeffs.iter() {
+                problem.obj_coeffs[v] -= sign * y * coeff;
+            }
+        }
+        problem
+    }
+
+    /// Solve the relaxed problem for the multipliers and return the resulting bound and
+    /// subgradient.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the relaxed problem is infeasible (then the original problem
+    /// is infeasible as well) or unbounded.
+    pub fn evaluate(&self, multipliers: &[f64]) -> Result<LagrangianBound, Error> {
+        self.evaluate_with(multipliers, &SolverOptions::default())
+    }
+
+    /// Same as [`evaluate`](#method.evaluate), solving the relaxed problem with the options.
+    pub fn evaluate_with(
+        &self,
+        multipliers: &[f64],
+        options: &SolverOptions,
+    ) -> Result<LagrangianBound, Error> {
+        let solution = self.relaxed_problem(multipliers).solve_with(options)?;
+        let subgradient = self
+            .relaxed_rows
+            .iter()
+            .map(|(coeffs, _, rhs)| {
+                let lhs: f64 = coeffs
+                    .iter()
+                    .map(|(v, &coeff)| coeff * solution[solution.var_ref(v)])
+                    .sum();
+                rhs - lhs
+            })
+            .collect();
+        Ok(LagrangianBound {
+            bound: solution.objective(),
+            subgradient,
+            solution,
+        })
+    }
+
+    /// Optimize the multipliers with the projected [subgradient method] starting from
+    /// `initial` to find the best bound (the optimal value of the Lagrangian dual problem).
+    ///
+    /// This is cheap per iteration and gives good bounds fast, but converges slowly to
+    /// the optimal bound. If the relaxed problem is an LP, the optimal bound is the optimal
+    /// value of the original problem, so the method is mostly useful when the relaxed problems
+    /// are easier to solve or when only an approximate bound is needed.
+    ///
+    /// [subgradient method]: https://en.wikipedia.org/wiki/Subgradient_method
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a relaxed problem is infeasible or unbounded (for relaxed
+    /// problems with unbounded variables some multipliers can make the objective unbounded).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(3.0, (0.0, 5.0));
+    /// let y = problem.add_var(2.0, (0.0, 5.0));
+    /// let labor = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint(&[(x, 1.0), (y, 3.0)], ComparisonOp::Le, 9.0);
+    /// problem.add_constraint(&[(x, 1.0)], ComparisonOp::Le, 3.0);
+    ///
+    /// let relaxation = problem.lagrangian_relaxation(&[labor]);
+    /// let options = SubgradientOptions {
+    ///     target: Some(11.0),
+    ///     ..SubgradientOptions::default()
+    /// };
+    /// let res = relaxation.subgradient(&[0.0], &options).unwrap();
+    /// assert!(res.best.bound >= 11.0 && res.best.bound < 11.0 + 1e-3);
+    /// ```
+    pub fn subgradient(
+        &self,
+        initial: &[f64],
+        options: &SubgradientOptions,
+    ) -> Result<SubgradientResult, Error> {
+        // The Lagrangian function is maximized for minimization problems and minimized
+        // for maximization problems.
+        let dir = match self.base.direction {
+            OptimizationDirection::Minimize => 1.0,
+            OptimizationDirection::Maximize => -1.0,
+        };
+        let improves = |bound: f64, best: f64| dir * (bound - best) > 0.0;
+
+        let mut multipliers = initial.to_vec();
+        self.project_multipliers(&mut multipliers);
+        let mut cur = self.evaluate_with(&multipliers, &options.solver_options)?;
+        let mut best_multipliers = multipliers.clone();
+        let mut best = cur.clone();
+        let mut iterations = 1;
+        let mut is_optimal = false;
+        let mut step_factor = options.step_factor;
+        let mut num_not_improved = 0;
+
+        while iterations < options.max_iterations && step_factor >= options.min_step_factor {
+            if let Some(target) = options.target {
+                if !improves(target, best.bound) {
+                    break;
+                }
+            }
+
+            // Components of the subgradient that would move the multipliers out of
+            // the feasible region are dropped.
+            let mut direction = multipliers
+                .iter()
+                .zip(&cur.subgradient)
+                .map(|(y, g)| y + dir * g)
+                .collect::<Vec<_>>();
+            self.project_multipliers(&mut direction);
+            for (d, y) in direction.iter_mut().zip(&multipliers) {
+                *d -= y;
+            }
+            let norm_sq: f64 = direction.iter().map(|d| d * d).sum();
+            if norm_sq <= ZERO_SUBGRADIENT_TOL * ZERO_SUBGRADIENT_TOL {
+                // The current multipliers are optimal (up to rounding errors).
+                best = cur;
+                best_multipliers = multipliers;
+                is_optimal = true;
+                break;
+            }
+
+            let step = match options.target {
+                Some(target) => step_factor * f64::abs(target - cur.bound) / norm_sq,
+                None => step_factor / (iterations as f64 * norm_sq.sqrt()),
+            };
+            for (y, d) in multipliers.iter_mut().zip(&direction) {
+                *y += step * d;
+            }
+            self.project_multipliers(&mut multipliers);
+
+            cur = self.evaluate_with(&multipliers, &options.solver_options)?;
+            iterations += 1;
+            debug!(
+                "subgradient iteration {}: bound {}, step {}",
+                iterations, cur.bound, step
+            );
+            if improves(cur.bound, best.bound) {
+                best = cur.clone();
+                best_multipliers.clone_from(&multipliers);
+                num_not_improved = 0;
+            } else {
+                num_not_improved += 1;
+                if num_not_improved >= options.patience {
+                    step_factor /= 2.0;
+                    num_not_improved = 0;
+                }
+            }
+        }
+
+        Ok(SubgradientResult {
+            best,
+            multipliers: best_multipliers,
+            iterations,
+            is_optimal,
+        })
+    }
+
+    fn check_len(&self, multipliers: &[f64]) {
+        assert_eq!(
+            multipliers.len(),
+            self.relaxed.len(),
+            "expected a multiplier for each relaxed constraint"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn relax() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(2.0, (0.0, 10.0));
+        let y = problem.add_var(3.0, (0.0, 10.0));
+        let z = problem.add_var(1.0, (0.0, 10.0));
+        let c0 = problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 4.0);
+        let c1 = problem.add_constraint([(y, 1.0), (z, -1.0)], ComparisonOp::Le, 1.0);
+        let c2 = problem.add_constraint([(x, 1.0), (z, 1.0)], ComparisonOp::Eq, 5.0);
+        problem.tag_constraint(c1, "tagged");
+        let solution = problem.solve().unwrap();
+
+        let relaxation = problem.lagrangian_relaxation(&[c2, c0]);
+        assert_eq!(relaxation.relaxed_constraints(), [c2, c0]);
+        assert_eq!(relaxation.original_constraint(ConstraintRef(0, 0)), c1);
+        let relaxed = relaxation.relaxed_problem(&[0.0, 0.0]);
+        assert_eq!(relaxed.stats().num_constraints, 1);
+        assert_eq!(
+            relaxed.constraints_with_tag("tagged").collect::<Vec<_>>(),
+            [ConstraintRef(0, 0)]
+        );
+
+        // Weak duality for any valid multipliers, strong duality for the LP duals.
+        let multipliers = relaxation.multipliers_from(&solution);
+        let best = relaxation.evaluate(&multipliers).unwrap();
+        assert!(f64::abs(best.bound - solution.objective()) < 1e-8);
+        for mut multipliers in [[1.0, 1.0], [-1.0, 2.5], [3.0, -1.0]] {
+            relaxation.project_multipliers(&mut multipliers);
+            assert!(multipliers[1] >= 0.0);
+            let bound = relaxation.evaluate(&multipliers).unwrap();
+            assert!(bound.bound <= solution.objective() + 1e-8);
+            let sol = &bound.solution;
+            assert_eq!(
+                bound.subgradient,
+                [5.0 - sol[x] - sol[z], 4.0 - sol[x] - sol[y]]
+            );
+        }
+
+        // Maximization flips the signs of the multipliers for inequalities.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let c0 = problem.add_constraint([(x, 1.0)], ComparisonOp::Le, 4.0);
+        let c1 = problem.add_constraint([(x, 1.0)], ComparisonOp::Ge, 1.0);
+        let relaxation = problem.lagrangian_relaxation(&[c0, c1]);
+        let mut multipliers = [-1.0, 1.0];
+        relaxation.project_multipliers(&mut multipliers);
+        assert_eq!(multipliers, [0.0, 0.0]);
+        let bound = relaxation.evaluate(&[0.5, 0.0]).unwrap();
+        assert_eq!(bound.bound, 7.0);
+    }
+
+    #[test]
+    fn subgradient() {
+        // Assignment of 3 jobs to 2 machines with relaxed capacity constraints.
+        let assignment = |capacity: f64| {
+            let mut problem = Problem::new(OptimizationDirection::Minimize);
+            let costs = [[4.0, 1.0], [2.0, 3.0], [5.0, 2.0]];
+            let vars = costs
+                .iter()
+                .map(|c| [0, 1].map(|m| problem.add_var(c[m], (0.0, 1.0))))
+                .collect::<Vec<_>>();
+            for job in &vars {
+                problem.add_constraint([(job[0], 1.0), (job[1], 1.0)], ComparisonOp::Eq, 1.0);
+            }
+            let capacities = [(0, 2.0), (1, capacity)].map(|(m, capacity)| {
+                let lhs = vars.iter().map(|j| (j[m], 1.0));
+                problem.add_constraint(lhs, ComparisonOp::Le, capacity)
+            });
+            let lp_obj = problem.solve().unwrap().objective();
+            (problem.lagrangian_relaxation(&capacities), lp_obj)
+        };
+
+        let (relaxation, lp_obj) = assignment(1.0);
+        assert_eq!(lp_obj, 8.0);
+        let res = relaxation
+            .subgradient(&[0.0, 0.0], &SubgradientOptions::default())
+            .unwrap();
+        assert!(res.best.bound <= lp_obj + 1e-8);
+        assert!(res.best.bound >= lp_obj - 1e-3);
+        assert!(res.multipliers.iter().all(|&y| y <= 0.0));
+        assert!(res.iterations <= 100);
+
+        let options = SubgradientOptions {
+            target: Some(lp_obj),
+            ..SubgradientOptions::default()
+        };
+        let res = relaxation.subgradient(&[0.0, 0.0], &options).unwrap();
+        assert!(res.best.bound >= lp_obj - 1e-3);
+
+        // The target is reached by the initial multipliers.
+        let res = relaxation.subgradient(&[0.0, -3.0], &options).unwrap();
+        assert_eq!((res.best.bound, res.iterations), (8.0, 1));
+
+        // With the capacity of 2 the relaxed solution is feasible and the projected
+        // subgradient is zero.
+        let (relaxation, lp_obj) = assignment(2.0);
+        let res = relaxation
+            .subgradient(&[0.0, 0.0], &SubgradientOptions::default())
+            .unwrap();
+        assert!(res.is_optimal);
+        assert_eq!((res.best.bound, res.iterations), (lp_obj, 1));
+    }
+}
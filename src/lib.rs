@@ -11,7 +11,7 @@ subject to linear equality and inequality constraints.
 * Able to solve problems with hundreds of thousands of variables and constraints.
 * Incremental: add constraints to an existing solution without solving it from scratch.
 * Problems can be defined via an API or parsed from an
-  [MPS](https://en.wikipedia.org/wiki/MPS_(format)) file.
+  [MPS](https://en.wikipedia.org/wiki/MPS_(format)), an LP or a JSON file.
 
 # Entry points
 
@@ -20,7 +20,56 @@ constraints. Solving it will produce a [`Solution`](struct.Solution.html) that c
 get the optimal objective value, corresponding variable values and to add more constraints
 to the problem.
 
-Alternatively, create an [`MpsFile`](mps/struct.MpsFile.html) by parsing a file in the MPS format.
+Alternatively, create an [`MpsFile`](struct.MpsFile.html), an [`LpFile`](struct.LpFile.html)
+or a [`JsonFile`](struct.JsonFile.html) by parsing a file in the MPS, the CPLEX LP or
+the documented JSON format. Problems can be written back in these formats with
+[`Problem::write_mps`](struct.Problem.html#method.write_mps), the
+[`Display`](struct.Problem.html#impl-Display-for-Problem) implementation and
+[`Problem::write_json`](struct.Problem.html#method.write_json). Two-stage stochastic programs
+in the SMPS format are parsed by [`SmpsFile`](struct.SmpsFile.html), which builds their
+deterministic equivalent.
+
+# Reproducibility
+
+Solving is deterministic and bit-reproducible across platforms: the same problem solved with
+the same options gives bit-identical pivots and results on any target with IEEE 754 `f64`
+arithmetic. The solver uses only basic arithmetic operations and `sqrt`, which are exactly
+rounded. It has no fused multiply-add, no math library calls, and no parallel or
+hash-order-dependent summation. Random tie-breaking in pricing uses a built-in generator
+seeded with [`SolverOptions::random_seed`](struct.SolverOptions.html#structfield.random_seed).
+
+# Cargo features
+
+* `matrixmultiply`: factorize dense blocks of the basis matrix using the optimized matrix
+  multiplication kernels of the [matrixmultiply](https://crates.io/crates/matrixmultiply)
+  crate instead of plain loops. These kernels may use fused multiply-add and
+  CPU-dependent operation order, so results are no longer bit-reproducible across platforms.
+* `cli`: build the `minilp` command-line tool that solves problems in the MPS or LP format
+  (printing the optimal objective value and writing the solution and the optimal basis
+  to files), converts them between the formats and prints their statistics
+  (run `minilp --help` for the options).
+* `nested-dissection`: enable [`BasisOrdering::NestedDissection`], a graph partitioning
+  based ordering of the basis matrix columns for large problems where the default ordering
+  produces too much fill-in.
+* `serde`: implement `serde::Serialize` for [`SolveReport`] and the statistics and timings
+  it contains, so that services can return the report in any serde format.
+* `tracing`: emit [tracing](https://crates.io/crates/tracing) spans (at the debug level)
+  around solves, reoptimizations after the problem is changed (e.g. at the nodes of
+  a branch-and-bound search) and refactorizations of the basis matrix, so that the solver
+  activity is correlated with the spans of the application.
+* `u32-indices`: store the row indices of the basis factors as `u32` instead of `usize`,
+  roughly halving the memory footprint of the factorization on 64-bit targets and
+  improving cache behavior. Solving a problem with more than `u32::MAX` constraints
+  will panic.
+* `u16-indices`: store the row indices of the basis factors as `u16`, for small problems
+  on memory-constrained targets. Solving a problem with more than `u16::MAX` constraints
+  will panic. If both this and the `u32-indices` feature are enabled, `u32` is used.
+* `mmap` (Unix only): enable [`MappedProblem`], a problem with the constraint matrix stored
+  in a memory-mapped file for models with more nonzeros than fit in memory.
+
+[`BasisOrdering::NestedDissection`]: enum.BasisOrdering.html
+[`MappedProblem`]: struct.MappedProblem.html
+[`SolveReport`]: struct.SolveReport.html
 
 # Example
 
@@ -49,17 +98,70 @@ assert_eq!(solution[y], 3.0);
 #[macro_use]
 extern crate log;
 
+/// Enter a `tracing` span with the given name and fields for the rest of the scope.
+/// Does nothing if the `tracing` feature is disabled.
+macro_rules! trace_span {
+    ($name:expr $(, $field:ident = $value:expr)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $field = $value)*).entered();
+    };
+}
+
+mod basis_file;
+mod batch;
+mod cache;
+mod compact;
+mod conflict;
+mod corpus;
+mod cuts;
+mod diff;
+mod display;
+mod diving;
+mod dualize;
+mod error;
+mod face;
+mod failure;
+mod fingerprint;
+mod flow_cover;
+mod handle;
 mod helpers;
+mod json_format;
+mod lagrangian;
+mod lp_format;
 mod lu;
+#[cfg(all(feature = "mmap", unix))]
+mod mapped;
+mod matrix_free;
+mod matrix_market;
 mod mps;
+#[cfg(feature = "nested-dissection")]
+mod nested_dissection;
+mod obbt;
+mod options;
 mod ordering;
+mod parallel;
+mod pdlp;
+mod presolve;
+mod probing;
+mod progress;
+mod report;
+mod robust;
+mod scaling;
+mod smps;
+mod solve_log;
 mod solver;
 mod sparse;
+mod stats;
+mod summation;
+mod tags;
+mod validate;
+mod zero_half;
 
 use solver::Solver;
+use tags::TagIndex;
 
 /// An enum indicating whether to minimize or maximize objective function.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OptimizationDirection {
     /// Minimize the objective function.
     Minimize,
@@ -68,14 +170,37 @@ pub enum OptimizationDirection {
 }
 
 /// A reference to a variable in a linear programming problem.
+///
+/// Besides the index, the reference records the generation of the problem it was created
+/// in. The generation changes when the problem is [compacted](struct.Problem.html#method.compact)
+/// and references from older generations are rejected afterwards.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Variable(pub(crate) usize);
+pub struct Variable(pub(crate) usize, pub(crate) u32);
 
 impl Variable {
     /// Sequence number of the variable.
     ///
     /// Variables are referenced by their number in the addition sequence. The method returns
-    /// this number.
+    /// this number. Removed variables keep their numbers until the problem is
+    /// [compacted](struct.Problem.html#method.compact).
+    pub fn idx(&self) -> usize {
+        self.0
+    }
+}
+
+/// A reference to a constraint in a linear programming problem.
+///
+/// Like [`Variable`](struct.Variable.html), the reference is checked against the generation
+/// of the problem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConstraintRef(pub(crate) usize, pub(crate) u32);
+
+impl ConstraintRef {
+    /// Sequence number of the constraint.
+    ///
+    /// Constraints are referenced by their number in the addition sequence. The method returns
+    /// this number. Removed constraints keep their numbers until the problem is
+    /// [compacted](struct.Problem.html#method.compact).
     pub fn idx(&self) -> usize {
         self.0
     }
@@ -87,6 +212,8 @@ impl Variable {
 pub struct LinearExpr {
     vars: Vec<usize>,
     coeffs: Vec<f64>,
+    /// The oldest generation of the added variables (if any).
+    generation: Option<u32>,
 }
 
 impl LinearExpr {
@@ -95,18 +222,110 @@ impl LinearExpr {
         Self {
             vars: vec![],
             coeffs: vec![],
+            generation: None,
         }
     }
 
+    /// Creates the sum of the variables, that is, an expression with all coefficients
+    /// equal to 1.
+    ///
+    /// Together with the `FromIterator` and `Extend` implementations this allows building
+    /// expressions with iterator adaptors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let vars = (0..4)
+    ///     .map(|i| problem.add_var(i as f64, (0.0, 1.0)))
+    ///     .collect::<Vec<_>>();
+    /// // At most two variables can be selected...
+    /// problem.add_constraint(LinearExpr::sum(vars.iter().copied()), ComparisonOp::Le, 2.0);
+    /// // ...and their weighted sum is limited.
+    /// let mut weighted: LinearExpr = vars.iter().map(|&v| (v, 2.0)).collect();
+    /// weighted.extend(vars.iter().skip(2).map(|&v| (v, 1.0)));
+    /// problem.add_constraint(weighted, ComparisonOp::Le, 6.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 5.0);
+    /// ```
+    pub fn sum(vars: impl IntoIterator<Item = Variable>) -> Self {
+        vars.into_iter().map(|var| (var, 1.0)).collect()
+    }
+
     /// Add a single term to the linear expression.
     ///
-    /// Variables can be added to an expression in any order, but adding the same variable
-    /// several times is forbidden (the [`Problem::add_constraint`] method will panic).
+    /// Variables can be added to an expression in any order. The same variable can be
+    /// added several times, in which case its coefficients are summed up when the expression
+    /// is used (but see the [strict](enum.Strictness.html) mode of [`Problem`]).
     ///
-    /// [`Problem::add_constraint`]: struct.Problem.html#method.add_constraint
+    /// [`Problem`]: struct.Problem.html
     pub fn add(&mut self, var: Variable, coeff: f64) {
         self.vars.push(var.0);
         self.coeffs.push(coeff);
+        self.generation = Some(self.generation.map_or(var.1, |gen| gen.min(var.1)));
+    }
+
+    /// Positions of the first two terms with the same variable, choosing the variable
+    /// whose second term comes first.
+    fn first_duplicate(&self) -> Option<(usize, usize)> {
+        let mut order = (0..self.vars.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| (self.vars[i], i));
+        order
+            .windows(2)
+            .filter(|w| self.vars[w[0]] == self.vars[w[1]])
+            .map(|w| (w[0], w[1]))
+            .min_by_key(|&(_, second)| second)
+    }
+
+    /// Combine the terms with the same variable into one, summing up their coefficients.
+    /// After this call the terms are sorted by variable.
+    ///
+    /// There is no need to call this method before adding the expression to a problem as
+    /// repeated terms are combined automatically, but it can be useful for inspecting the
+    /// expression or reducing its size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    ///
+    /// let mut expr = LinearExpr::from(&[(y, 1.0), (x, 2.0), (y, 3.0)]);
+    /// expr.merge_duplicates();
+    /// let merged = SparseVec::from(&expr);
+    /// assert_eq!(merged.indices(), [x.idx(), y.idx()]);
+    /// assert_eq!(merged.values(), [2.0, 4.0]);
+    /// ```
+    pub fn merge_duplicates(&mut self) {
+        let generation = self.generation;
+        let merged = std::mem::replace(self, LinearExpr::empty()).into_merged();
+        self.vars = merged.0;
+        self.coeffs = merged.1;
+        self.generation = generation;
+    }
+
+    /// Terms sorted by variable with the coefficients of repeated variables summed up.
+    pub(crate) fn into_merged(self) -> (Vec<usize>, Vec<f64>) {
+        if self.vars.windows(2).all(|w| w[0] < w[1]) {
+            return (self.vars, self.coeffs);
+        }
+        let mut terms = self.vars.into_iter().zip(self.coeffs).collect::<Vec<_>>();
+        terms.sort_by_key(|&(var, _)| var);
+        let mut vars: Vec<usize> = Vec::with_capacity(terms.len());
+        let mut coeffs: Vec<f64> = Vec::with_capacity(terms.len());
+        for (var, coeff) in terms {
+            if vars.last() == Some(&var) {
+                *coeffs.last_mut().unwrap() += coeff;
+            } else {
+                vars.push(var);
+                coeffs.push(coeff);
+            }
+        }
+        (vars, coeffs)
     }
 }
 
@@ -157,8 +376,16 @@ impl std::iter::Extend<(Variable, f64)> for LinearExpr {
     }
 }
 
+/// Coefficients of the expression indexed by variable number.
+impl From<&LinearExpr> for SparseVec {
+    fn from(expr: &LinearExpr) -> Self {
+        let (vars, coeffs) = expr.clone().into_merged();
+        vars.into_iter().zip(coeffs).collect()
+    }
+}
+
 /// An operator specifying the relation between left-hand and right-hand sides of the constraint.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ComparisonOp {
     /// The == operator (equal to)
     Eq,
@@ -168,27 +395,19 @@ pub enum ComparisonOp {
     Ge,
 }
 
-/// An error encountered while solving a problem.
-#[derive(Clone, Debug, PartialEq)]
-pub enum Error {
-    /// Constrains can't simultaneously be satisfied.
-    Infeasible,
-    /// The objective function is unbounded.
-    Unbounded,
-}
-
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let msg = match self {
-            Error::Infeasible => "problem is infeasible",
-            Error::Unbounded => "problem is unbounded",
-        };
-        msg.fmt(f)
-    }
+/// How the problem reacts to suspicious constraint expressions, see
+/// [`Problem::set_strictness`](struct.Problem.html#method.set_strictness).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Coefficients of repeated variables are summed up, zero coefficients and
+    /// empty expressions are accepted silently.
+    #[default]
+    Permissive,
+    /// Repeated variables, zero coefficients and empty expressions are errors. This helps
+    /// to catch bugs in the code generating the model.
+    Strict,
 }
 
-impl std::error::Error for Error {}
-
 /// A specification of a linear programming problem.
 #[derive(Clone)]
 pub struct Problem {
@@ -196,7 +415,17 @@ pub struct Problem {
     obj_coeffs: Vec<f64>,
     var_mins: Vec<f64>,
     var_maxs: Vec<f64>,
+    var_names: Vec<Option<String>>,
+    var_transforms: Vec<(f64, f64)>,
+    obj_offset: f64,
     constraints: Vec<(CsVec, ComparisonOp, f64)>,
+    var_tags: TagIndex<Variable>,
+    constraint_tags: TagIndex<ConstraintRef>,
+    strictness: Strictness,
+    /// Incremented by compaction, references from older generations are rejected.
+    generation: u32,
+    is_var_removed: Vec<bool>,
+    is_constraint_removed: Vec<bool>,
 }
 
 impl std::fmt::Debug for Problem {
@@ -210,6 +439,41 @@ impl std::fmt::Debug for Problem {
     }
 }
 
+/// Prints the problem in the [CPLEX LP format][lp], useful for small problems in tests
+/// and bug reports.
+///
+/// [lp]: https://www.ibm.com/docs/en/icos/latest?topic=cplex-lp-file-format-algebraic-representation
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Maximize);
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(2.0, (0.0, 3.0));
+/// problem.set_var_name(y, "y");
+/// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+/// problem.add_constraint(&[(x, 2.0), (y, -1.0)], ComparisonOp::Ge, 2.0);
+///
+/// assert_eq!(
+///     problem.to_string(),
+///     "Maximize
+///  obj: x0 + 2 y
+/// Subject To
+///  c0: x0 + y <= 4
+///  c1: 2 x0 - y >= 2
+/// Bounds
+///  0 <= y <= 3
+/// End
+/// "
+/// );
+/// ```
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        lp_format::write_lp(self, f)
+    }
+}
+
 type CsVec = sprs::CsVecI<f64, usize>;
 
 impl Problem {
@@ -220,18 +484,85 @@ impl Problem {
             obj_coeffs: vec![],
             var_mins: vec![],
             var_maxs: vec![],
+            var_names: vec![],
+            var_transforms: vec![],
+            obj_offset: 0.0,
             constraints: vec![],
+            var_tags: TagIndex::new(),
+            constraint_tags: TagIndex::new(),
+            strictness: Strictness::default(),
+            generation: 0,
+            is_var_removed: vec![],
+            is_constraint_removed: vec![],
         }
     }
 
+    /// Set how the constraints added from now on are checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    ///
+    /// // In the default permissive mode, coefficients of x are summed up.
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0), (x, 1.0)], ComparisonOp::Ge, 4.0);
+    /// assert_eq!(problem.solve().unwrap().objective(), 2.0);
+    ///
+    /// problem.set_strictness(Strictness::Strict);
+    /// let err = problem
+    ///     .try_add_constraint(&[(x, 1.0), (y, 1.0), (x, 1.0)], ComparisonOp::Ge, 4.0)
+    ///     .unwrap_err();
+    /// assert_eq!(err.to_string(), "variable 0 appears in terms 0 and 2 of constraint 1");
+    /// let err = problem
+    ///     .try_add_constraint(&[(x, 1.0), (y, 0.0)], ComparisonOp::Ge, 4.0)
+    ///     .unwrap_err();
+    /// assert_eq!(err.to_string(), "term 1 of constraint 1 (variable 1) has zero coefficient");
+    /// ```
+    pub fn set_strictness(&mut self, strictness: Strictness) {
+        self.strictness = strictness;
+    }
+
+    /// How the constraints are checked, see [`set_strictness`](#method.set_strictness).
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
     /// Add a new variable to the problem.
     ///
     /// `obj_coeff` is a coefficient of the term in the objective function corresponding to this
     /// variable, `min` and `max` are the minimum and maximum (inclusive) bounds of this
     /// variable. If one of the bounds is absent, use `f64::NEG_INFINITY` for minimum and
     /// `f64::INFINITY` for maximum.
-    pub fn add_var(&mut self, obj_coeff: f64, (min, max): (f64, f64)) -> Variable {
-        let var = Variable(self.obj_coeffs.len());
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the objective coefficient or a bound is invalid (NaN or infinite
+    /// in the wrong direction), see [`try_add_var`](#method.try_add_var).
+    pub fn add_var(&mut self, obj_coeff: f64, bounds: (f64, f64)) -> Variable {
+        self.try_add_var(obj_coeff, bounds)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Add a new variable to the problem like [`add_var`](#method.add_var), but return
+    /// an error instead of panicking if the objective coefficient or a bound is invalid.
+    pub fn try_add_var(
+        &mut self,
+        obj_coeff: f64,
+        (min, max): (f64, f64),
+    ) -> Result<Variable, ModelError> {
+        let var = Variable(self.obj_coeffs.len(), self.generation);
+        if !obj_coeff.is_finite() {
+            return Err(ModelError::InvalidObjCoeff {
+                var,
+                coeff: obj_coeff,
+            });
+        }
+        if min.is_nan() || max.is_nan() || min == f64::INFINITY || max == f64::NEG_INFINITY {
+            return Err(ModelError::InvalidBounds { var, min, max });
+        }
         let obj_coeff = match self.direction {
             OptimizationDirection::Minimize => obj_coeff,
             OptimizationDirection::Maximize => -obj_coeff,
@@ -239,119 +570,2163 @@ impl Problem {
         self.obj_coeffs.push(obj_coeff);
         self.var_mins.push(min);
         self.var_maxs.push(max);
-        var
+        self.var_names.push(None);
+        self.var_transforms.push((1.0, 0.0));
+        self.is_var_removed.push(false);
+        Ok(var)
+    }
+
+    /// Set the name of the variable used when printing the problem.
+    ///
+    /// Names are not checked for uniqueness or validity. Variables without a name are
+    /// printed as `x` followed by the variable index.
+    pub fn set_var_name(&mut self, var: Variable, name: impl Into<String>) {
+        let v = self.var_idx(var);
+        self.var_names[v] = Some(name.into());
+    }
+
+    /// Name of the variable (see [`set_var_name`](#method.set_var_name)).
+    pub fn var_name(&self, var: Variable) -> std::borrow::Cow<'_, str> {
+        self.var_name_at(self.var_idx(var))
+    }
+
+    pub(crate) fn var_name_at(&self, v: usize) -> std::borrow::Cow<'_, str> {
+        match &self.var_names[v] {
+            Some(name) => name.into(),
+            None => format!("x{}", v).into(),
+        }
+    }
+
+    /// Rewrite the problem in terms of a new variable `y` such that the variable `var` is
+    /// equal to `scale * y + offset`, e.g. to change the units or the origin of a variable.
+    ///
+    /// The coefficients, bounds, right-hand sides and the objective function are updated
+    /// accordingly and `var` refers to `y` afterwards: its value in the solution is the value
+    /// of `y`. Use [`Solution::original_var_value`] to recover the value of the original
+    /// variable. Repeated substitutions are composed. The constant term of the objective
+    /// function that the substitution can introduce is included in the
+    /// [objective value](struct.Solution.html#method.objective) of the solution.
+    ///
+    /// [`Solution::original_var_value`]: struct.Solution.html#method.original_var_value
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `scale` is zero or if `scale` or `offset` is not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// // Mass in grams.
+    /// let x = problem.add_var(1.0, (1500.0, 4000.0));
+    /// let y = problem.add_var(2000.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1000.0)], ComparisonOp::Ge, 2000.0);
+    ///
+    /// // Measure x in kilograms.
+    /// problem.substitute_var(x, 1000.0, 0.0);
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution[x], 2.0);
+    /// assert_eq!(solution.original_var_value(&problem, x), 2000.0);
+    /// assert_eq!(solution.objective(), 2000.0);
+    /// ```
+    pub fn substitute_var(&mut self, var: Variable, scale: f64, offset: f64) {
+        assert!(
+            scale != 0.0 && scale.is_finite(),
+            "invalid scale: {}",
+            scale
+        );
+        assert!(offset.is_finite(), "invalid offset: {}", offset);
+        let v = self.var_idx(var);
+
+        self.obj_offset += self.obj_coeffs[v] * offset;
+        self.obj_coeffs[v] *= scale;
+        for (coeffs, _, rhs) in &mut self.constraints {
+            if let Some(coeff) = coeffs.get_mut(v) {
+                *rhs -= *coeff * offset;
+                *coeff *= scale;
+            }
+        }
+
+        let min = (self.var_mins[v] - offset) / scale;
+        let max = (self.var_maxs[v] - offset) / scale;
+        if scale > 0.0 {
+            self.var_mins[v] = min;
+            self.var_maxs[v] = max;
+        } else {
+            self.var_mins[v] = max;
+            self.var_maxs[v] = min;
+        }
+
+        let (prev_scale, prev_offset) = self.var_transforms[v];
+        self.var_transforms[v] = (prev_scale * scale, prev_scale * offset + prev_offset);
+    }
+
+    /// Accumulated substitution of the variable as a `(scale, offset)` pair: the original
+    /// variable is equal to `scale * var + offset` (see
+    /// [`substitute_var`](#method.substitute_var)).
+    pub fn var_transform(&self, var: Variable) -> (f64, f64) {
+        self.var_transforms[self.var_idx(var)]
+    }
+
+    /// Substitute the variables fixed by equal bounds with their values: their terms are
+    /// moved to the right-hand sides of the constraints and to the
+    /// [objective offset](#method.objective_offset). Returns the number of the eliminated
+    /// variables.
+    ///
+    /// The eliminated variables stay in the problem with their bounds, but without
+    /// the coefficients, so the solver doesn't have to deal with them and the solution still
+    /// reports their values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (1.0, 1.0));
+    /// let c = problem.add_constraint(&[(x, 1.0), (y, 3.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// assert_eq!(problem.eliminate_fixed(), 1);
+    /// assert_eq!(problem.constraint_terms(c).collect::<Vec<_>>(), [(x, 1.0)]);
+    /// assert_eq!(problem.objective_offset(), 2.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!((solution[x], solution[y]), (1.0, 1.0));
+    /// assert_eq!(solution.objective(), 3.0);
+    /// ```
+    pub fn eliminate_fixed(&mut self) -> usize {
+        let num_vars = self.obj_coeffs.len();
+        let fixed_vals: Vec<Option<f64>> = (0..num_vars)
+            .map(|v| {
+                let min = self.var_mins[v];
+                let is_fixed = min == self.var_maxs[v] && min.is_finite();
+                Some(min).filter(|_| is_fixed && !self.is_var_removed[v])
+            })
+            .collect();
+
+        let mut is_eliminated = vec![false; num_vars];
+        for (v, val) in fixed_vals.iter().enumerate() {
+            if let Some(val) = val {
+                if self.obj_coeffs[v] != 0.0 {
+                    self.obj_offset += self.obj_coeffs[v] * val;
+                    self.obj_coeffs[v] = 0.0;
+                    is_eliminated[v] = true;
+                }
+            }
+        }
+        for (coeffs, _, rhs) in &mut self.constraints {
+            if coeffs.iter().all(|(v, _)| fixed_vals[v].is_none()) {
+                continue;
+            }
+            let mut indices = vec![];
+            let mut data = vec![];
+            for (v, &coeff) in coeffs.iter() {
+                if let Some(val) = fixed_vals[v] {
+                    *rhs -= coeff * val;
+                    is_eliminated[v] = true;
+                } else {
+                    indices.push(v);
+                    data.push(coeff);
+                }
+            }
+            *coeffs = CsVec::new(num_vars, indices, data);
+        }
+
+        is_eliminated.iter().filter(|&&e| e).count()
+    }
+
+    /// Set the constant term of the objective function. It doesn't affect the optimal
+    /// point, but is added to the reported [objective value](struct.Solution.html#method.objective).
+    ///
+    /// Note that [`substitute_var`](#method.substitute_var) changes the constant term too
+    /// and this method overwrites the accumulated value.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `offset` is NaN or infinite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.set_objective_offset(10.0);
+    /// assert_eq!(problem.objective_offset(), 10.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution[x], 3.0);
+    /// assert_eq!(solution.objective(), 16.0);
+    /// ```
+    pub fn set_objective_offset(&mut self, offset: f64) {
+        assert!(offset.is_finite(), "invalid objective offset: {}", offset);
+        self.obj_offset = match self.direction {
+            OptimizationDirection::Minimize => offset,
+            OptimizationDirection::Maximize => -offset,
+        };
+    }
+
+    /// Constant term of the objective function, see
+    /// [`set_objective_offset`](#method.set_objective_offset).
+    pub fn objective_offset(&self) -> f64 {
+        match self.direction {
+            OptimizationDirection::Minimize => self.obj_offset,
+            // Avoid reporting negative zero.
+            OptimizationDirection::Maximize => 0.0 - self.obj_offset,
+        }
+    }
+
+    /// Add a linear constraint to the problem.
+    ///
+    /// # Examples
+    ///
+    /// Left-hand side of the constraint can be specified in several ways:
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    ///
+    /// // Add an x + y >= 2 constraint, specifying the left-hand side expression:
+    ///
+    /// // * by passing a slice of pairs (useful when explicitly enumerating variables)
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    ///
+    /// // * by passing an iterator of variable-coefficient pairs.
+    /// let vars = [x, y];
+    /// problem.add_constraint(vars.iter().map(|&v| (v, 1.0)), ComparisonOp::Ge, 2.0);
+    ///
+    /// // * by manually constructing a LinearExpr.
+    /// let mut lhs = LinearExpr::empty();
+    /// for &v in &vars {
+    ///     lhs.add(v, 1.0);
+    /// }
+    /// problem.add_constraint(lhs, ComparisonOp::Ge, 2.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a coefficient or the right-hand side is NaN or infinite, or if
+    /// the expression is rejected in the [strict](enum.Strictness.html) mode, see
+    /// [`try_add_constraint`](#method.try_add_constraint).
+    pub fn add_constraint(
+        &mut self,
+        expr: impl Into<LinearExpr>,
+        cmp_op: ComparisonOp,
+        rhs: f64,
+    ) -> ConstraintRef {
+        self.try_add_constraint(expr, cmp_op, rhs)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Add a linear constraint like [`add_constraint`](#method.add_constraint), but return
+    /// an error instead of panicking if a coefficient or the right-hand side is NaN
+    /// or infinite, or if the expression is rejected in the [strict](enum.Strictness.html)
+    /// mode. The problem is not changed in case of error.
+    pub fn try_add_constraint(
+        &mut self,
+        expr: impl Into<LinearExpr>,
+        cmp_op: ComparisonOp,
+        rhs: f64,
+    ) -> Result<ConstraintRef, ModelError> {
+        let constr = ConstraintRef(self.constraints.len(), self.generation);
+        let expr = expr.into();
+        if expr.generation.is_some_and(|gen| gen != self.generation) {
+            return Err(ModelError::StaleExpr { constr });
+        }
+        for (&var, &coeff) in expr.vars.iter().zip(&expr.coeffs) {
+            let var = Variable(var, self.generation);
+            self.check_var(var)?;
+            if !coeff.is_finite() {
+                return Err(ModelError::InvalidCoeff { constr, var, coeff });
+            }
+        }
+        if !rhs.is_finite() {
+            return Err(ModelError::InvalidRhs { constr, rhs });
+        }
+        if self.strictness == Strictness::Strict {
+            if expr.vars.is_empty() {
+                return Err(ModelError::EmptyConstraint { constr });
+            }
+            if let Some(position) = expr.coeffs.iter().position(|&coeff| coeff == 0.0) {
+                return Err(ModelError::ZeroCoeff {
+                    constr,
+                    var: Variable(expr.vars[position], self.generation),
+                    position,
+                });
+            }
+            if let Some((first_position, position)) = expr.first_duplicate() {
+                return Err(ModelError::DuplicateVar {
+                    constr,
+                    var: Variable(expr.vars[position], self.generation),
+                    first_position,
+                    position,
+                });
+            }
+        }
+        let (vars, coeffs) = expr.into_merged();
+        self.constraints
+            .push((CsVec::new(self.obj_coeffs.len(), vars, coeffs), cmp_op, rhs));
+        self.is_constraint_removed.push(false);
+        Ok(constr)
+    }
+
+    /// Add a linear constraint that must hold for all values of its coefficients from
+    /// the uncertainty set (the *robust counterpart* of the nominal constraint).
+    ///
+    /// The counterpart is expressed by introducing auxiliary variables and constraints so that
+    /// the problem remains a linear program. Note that the auxiliary variables are added
+    /// to the problem and thus will be present in the solution.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `cmp_op` is [`ComparisonOp::Eq`] (robust equality constraints are
+    /// infeasible unless there is no uncertainty) or if some deviation is negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    ///
+    /// // Nominal constraint is 2 * x <= 4, but the coefficient can be as big as 3.
+    /// let uncertainty = UncertaintySet::boxed(&[(x, 1.0)]);
+    /// problem.add_robust_constraint(&[(x, 2.0)], ComparisonOp::Le, 4.0, &uncertainty);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert!(f64::abs(solution[x] - 4.0 / 3.0) < 1e-8);
+    /// ```
+    pub fn add_robust_constraint(
+        &mut self,
+        expr: impl Into<LinearExpr>,
+        cmp_op: ComparisonOp,
+        rhs: f64,
+        uncertainty: &UncertaintySet,
+    ) -> ConstraintRef {
+        robust::add_robust_constraint(self, expr.into(), cmp_op, rhs, uncertainty)
+    }
+
+    /// Coefficient of the variable in the constraint (zero if the variable is not present).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let z = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let c1 = problem.add_constraint(&[(z, 3.0), (x, 1.0)], ComparisonOp::Ge, 2.0);
+    /// let c2 = problem.add_constraint(&[(x, 2.0), (y, -1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// assert_eq!(problem.coeff(c1, z), 3.0);
+    /// assert_eq!(problem.coeff(c1, y), 0.0);
+    /// assert_eq!(problem.constraint_terms(c1).collect::<Vec<_>>(), [(x, 1.0), (z, 3.0)]);
+    /// assert_eq!(problem.var_occurrences(x).collect::<Vec<_>>(), [(c1, 1.0), (c2, 2.0)]);
+    /// ```
+    pub fn coeff(&self, constr: ConstraintRef, var: Variable) -> f64 {
+        let (coeffs, _, _) = &self.constraints[self.constraint_idx(constr)];
+        coeffs.get(self.var_idx(var)).copied().unwrap_or(0.0)
+    }
+
+    /// Iterate over the terms of the left-hand side of the constraint in the order of
+    /// increasing variable index. See [`coeff`](#method.coeff).
+    pub fn constraint_terms(
+        &self,
+        constr: ConstraintRef,
+    ) -> impl Iterator<Item = (Variable, f64)> + '_ {
+        let (coeffs, _, _) = &self.constraints[self.constraint_idx(constr)];
+        coeffs
+            .iter()
+            .map(move |(var, &coeff)| (Variable(var, self.generation), coeff))
+    }
+
+    /// Iterate over the constraints in which the variable is present
+    /// together with that coefficient, in the order of constraint addition.
+    /// See [`coeff`](#method.coeff).
+    ///
+    /// The constraints are stored row-wise so this takes time proportional to the number
+    /// of constraints.
+    pub fn var_occurrences(
+        &self,
+        var: Variable,
+    ) -> impl Iterator<Item = (ConstraintRef, f64)> + '_ {
+        let v = self.var_idx(var);
+        self.constraints
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, (coeffs, _, _))| {
+                coeffs
+                    .get(v)
+                    .map(|&coeff| (ConstraintRef(i, self.generation), coeff))
+            })
+    }
+
+    /// Remove the variable from the problem. Its coefficients in the constraints are dropped
+    /// and the variable itself is fixed at zero and excluded from the objective, so that
+    /// the references to other variables and constraints stay valid. The slot of the variable
+    /// is reclaimed by [`compact`](#method.compact).
+    ///
+    /// Using the reference of a removed variable is an error: `try_*` methods report
+    /// [`ModelError::InvalidVar`](enum.ModelError.html#variant.InvalidVar) and other methods
+    /// panic.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the variable was already removed or the reference is from
+    /// an older generation of the problem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 2.0));
+    /// let y = problem.add_var(3.0, (0.0, 2.0));
+    /// let z = problem.add_var(2.0, (0.0, 2.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0), (z, 1.0)], ComparisonOp::Le, 3.0);
+    ///
+    /// problem.remove_var(y).unwrap();
+    /// assert!(!problem.has_var(y));
+    /// assert_eq!(problem.remove_var(y), Err(ModelError::InvalidVar { var: y }));
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 5.0);
+    /// ```
+    pub fn remove_var(&mut self, var: Variable) -> Result<(), ModelError> {
+        let v = self.check_var(var)?;
+        self.is_var_removed[v] = true;
+        self.obj_coeffs[v] = 0.0;
+        self.var_mins[v] = 0.0;
+        self.var_maxs[v] = 0.0;
+        for (coeffs, _, _) in &mut self.constraints {
+            if coeffs.get(v).is_some() {
+                let (indices, data) = coeffs.iter().filter(|&(i, _)| i != v).unzip();
+                *coeffs = CsVec::new(coeffs.dim(), indices, data);
+            }
+        }
+        self.var_tags = self
+            .var_tags
+            .remap(|other| Some(other).filter(|&o| o != var));
+        Ok(())
+    }
+
+    /// Remove the constraint from the problem. It is replaced by an empty constraint
+    /// until the problem is [compacted](#method.compact), see [`remove_var`](#method.remove_var).
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the constraint was already removed or the reference is from
+    /// an older generation of the problem.
+    pub fn remove_constraint(&mut self, constr: ConstraintRef) -> Result<(), ModelError> {
+        let c = self.check_constraint(constr)?;
+        self.is_constraint_removed[c] = true;
+        self.constraints[c] = (CsVec::empty(self.obj_coeffs.len()), ComparisonOp::Le, 0.0);
+        self.constraint_tags = self
+            .constraint_tags
+            .remap(|other| Some(other).filter(|&o| o != constr));
+        Ok(())
+    }
+
+    /// Returns `true` if the variable is in the problem: it was not removed and the reference
+    /// is from the current generation of the problem.
+    pub fn has_var(&self, var: Variable) -> bool {
+        self.check_var(var).is_ok()
+    }
+
+    /// Returns `true` if the constraint is in the problem, see [`has_var`](#method.has_var).
+    pub fn has_constraint(&self, constr: ConstraintRef) -> bool {
+        self.check_constraint(constr).is_ok()
+    }
+
+    /// Reclaim the slots of the removed variables and constraints, renumbering the remaining
+    /// ones densely in the same order.
+    ///
+    /// This starts a new generation of the problem: the references obtained before are
+    /// rejected afterwards and must be translated with the returned [`Remapping`].
+    ///
+    /// [`Remapping`]: struct.Remapping.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (1.0, 2.0));
+    /// let y = problem.add_var(1.0, (0.0, 2.0));
+    /// let z = problem.add_var(1.0, (0.0, 2.0));
+    /// let c1 = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    /// let c2 = problem.add_constraint(&[(y, 1.0), (z, 1.0)], ComparisonOp::Ge, 1.0);
+    /// problem.remove_var(x).unwrap();
+    /// problem.remove_constraint(c1).unwrap();
+    ///
+    /// let remapping = problem.compact();
+    /// assert_eq!(problem.stats().num_vars, 2);
+    /// assert_eq!(remapping.var(x), None);
+    /// let y = remapping.var(y).unwrap();
+    /// assert_eq!(y.idx(), 0);
+    /// let c2 = remapping.constraint(c2).unwrap();
+    /// assert_eq!(problem.coeff(c2, y), 1.0);
+    ///
+    /// // The old reference to z is stale now.
+    /// assert!(!problem.has_var(z));
+    /// ```
+    pub fn compact(&mut self) -> Remapping {
+        compact::compact(self)
+    }
+
+    /// Options passed to the solver: the objective cutoff is converted to the internal form.
+    pub(crate) fn solver_options<'a>(
+        &self,
+        options: &'a SolverOptions,
+    ) -> std::borrow::Cow<'a, SolverOptions> {
+        match options.objective_cutoff {
+            Some(cutoff) => std::borrow::Cow::Owned(SolverOptions {
+                objective_cutoff: Some(internal_cutoff(self.direction, self.obj_offset, cutoff)),
+                ..options.clone()
+            }),
+            None => std::borrow::Cow::Borrowed(options),
+        }
+    }
+
+    /// Index of the variable if the reference is valid.
+    pub(crate) fn check_var(&self, var: Variable) -> Result<usize, ModelError> {
+        match self.is_var_removed.get(var.0) {
+            Some(false) if var.1 == self.generation => Ok(var.0),
+            _ => Err(ModelError::InvalidVar { var }),
+        }
+    }
+
+    /// Index of the constraint if the reference is valid.
+    pub(crate) fn check_constraint(&self, constr: ConstraintRef) -> Result<usize, ModelError> {
+        match self.is_constraint_removed.get(constr.0) {
+            Some(false) if constr.1 == self.generation => Ok(constr.0),
+            _ => Err(ModelError::InvalidConstraint { constr }),
+        }
+    }
+
+    pub(crate) fn var_ref(&self, var: usize) -> Variable {
+        Variable(var, self.generation)
+    }
+
+    pub(crate) fn constraint_ref(&self, constr: usize) -> ConstraintRef {
+        ConstraintRef(constr, self.generation)
+    }
+
+    /// Index of the variable, panics if the reference is invalid.
+    pub(crate) fn var_idx(&self, var: Variable) -> usize {
+        self.check_var(var).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Index of the constraint, panics if the reference is invalid.
+    pub(crate) fn constraint_idx(&self, constr: ConstraintRef) -> usize {
+        self.check_constraint(constr)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Attach the tag to the variable. Returns `false` if the variable already had this tag.
+    ///
+    /// Tags group variables (and constraints, see
+    /// [`tag_constraint`](#method.tag_constraint)) semantically so that the values of
+    /// a group can be retrieved in one call. A variable can have several tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 2.0));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// let z = problem.add_var(3.0, (0.0, 4.0));
+    /// problem.tag_var(x, "production");
+    /// problem.tag_var(z, "production");
+    /// problem.tag_var(y, 7);
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0), (z, 1.0)], ComparisonOp::Le, 5.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// let production: f64 = problem.vars_with_tag("production").map(|v| solution[v]).sum();
+    /// assert_eq!(production, 4.0);
+    /// ```
+    pub fn tag_var(&mut self, var: Variable, tag: impl Into<Tag>) -> bool {
+        self.var_idx(var);
+        self.var_tags.insert(tag.into(), var)
+    }
+
+    /// Attach the tag to the constraint. Returns `false` if the constraint already had this
+    /// tag. See [`tag_var`](#method.tag_var).
+    pub fn tag_constraint(&mut self, constr: ConstraintRef, tag: impl Into<Tag>) -> bool {
+        self.constraint_idx(constr);
+        self.constraint_tags.insert(tag.into(), constr)
+    }
+
+    /// Iterate over the variables with the tag in the order of their addition.
+    pub fn vars_with_tag(&self, tag: impl Into<Tag>) -> impl Iterator<Item = Variable> + '_ {
+        self.var_tags.get(&tag.into())
+    }
+
+    /// Iterate over the constraints with the tag in the order of their addition.
+    pub fn constraints_with_tag(
+        &self,
+        tag: impl Into<Tag>,
+    ) -> impl Iterator<Item = ConstraintRef> + '_ {
+        self.constraint_tags.get(&tag.into())
+    }
+
+    /// Tags of the variable in sorted order (integer tags first).
+    pub fn var_tags(&self, var: Variable) -> Vec<&Tag> {
+        self.var_tags.tags_of(var)
+    }
+
+    /// Tags of the constraint in sorted order (integer tags first).
+    pub fn constraint_tags(&self, constr: ConstraintRef) -> Vec<&Tag> {
+        self.constraint_tags.tags_of(constr)
+    }
+
+    /// All tags of the variables and the constraints in sorted order (integer tags first).
+    pub fn tags(&self) -> Vec<&Tag> {
+        let mut tags = self.var_tags.tags();
+        tags.extend(self.constraint_tags.tags());
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Check the problem for suspicious modeling and potential issues that can lead
+    /// to numerical difficulties.
+    ///
+    /// The report lists the constraints and variables whose nonzero coefficients differ in
+    /// magnitude by more than a factor of 10^6. Such problems should be solved with
+    /// [scaling](struct.SolverOptions.html#structfield.scaling) enabled. If scaling
+    /// is disabled, `solve` logs a warning for badly scaled problems. The report also lists
+    /// empty constraints, zero and extreme coefficients, near-duplicate constraints, free
+    /// variables in equalities and variables along which the objective is unbounded, see
+    /// [`ValidationWarning`](enum.ValidationWarning.html).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let constr = problem.add_constraint(&[(x, 1e-4), (y, 1e4)], ComparisonOp::Le, 1.0);
+    ///
+    /// let report = problem.validate();
+    /// assert!(report.is_badly_scaled());
+    /// assert_eq!(
+    ///     report.warnings,
+    ///     vec![ValidationWarning::BadlyScaledConstraint {
+    ///         constr,
+    ///         min_abs_coeff: 1e-4,
+    ///         max_abs_coeff: 1e4,
+    ///     }],
+    /// );
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        validate::validate(self)
+    }
+
+    /// Write the problem to `out` in the MPS format with the problem `name`.
+    ///
+    /// The objective row is named `obj` and constraints are named `c0`, `c1`, ...
+    /// The direction of maximization problems is written in the OBJSENSE section, which is
+    /// understood by [`MpsFile::parse`] and most other solvers.
+    ///
+    /// # Errors
+    ///
+    /// Apart from I/O errors coming from `out`, the error of kind
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput) is returned if a variable name
+    /// can't be written in the `format` (e.g. because it contains whitespace).
+    ///
+    /// [`MpsFile::parse`]: struct.MpsFile.html#method.parse
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let mut out = vec![];
+    /// problem.write_mps("example", MpsFormat::Free, &mut out).unwrap();
+    /// let file = MpsFile::parse(&out[..], OptimizationDirection::Minimize).unwrap();
+    /// assert_eq!(file.problem.solve().unwrap().objective(), 7.0);
+    /// ```
+    pub fn write_mps(
+        &self,
+        name: &str,
+        format: MpsFormat,
+        out: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        mps::write_mps(self, name, format, out)
+    }
+
+    /// Write the constraint matrix to `out` in the [Matrix Market][mm] coordinate format, to
+    /// inspect its sparsity pattern and numerics in external tools (e.g. with `mmread` in
+    /// SciPy or MATLAB). Rows correspond to the constraints and columns to the variables,
+    /// in the order of their addition.
+    ///
+    /// [mm]: https://math.nist.gov/MatrixMarket/formats.html
+    ///
+    /// # Errors
+    ///
+    /// I/O errors coming from `out` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let mut out = vec![];
+    /// problem.write_matrix_market(&mut out).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "%%MatrixMarket matrix coordinate real general\n1 2 2\n1 1 1.0\n1 2 1.0\n",
+    /// );
+    /// ```
+    pub fn write_matrix_market(&self, out: impl std::io::Write) -> std::io::Result<()> {
+        matrix_market::write_constraint_matrix(self, out)
+    }
+
+    /// Write the problem to `out` as a JSON document in the format described in
+    /// the documentation of [`JsonFile`].
+    ///
+    /// Variables are referenced by their indices, so the names are written only for
+    /// the variables that have them.
+    ///
+    /// [`JsonFile`]: struct.JsonFile.html
+    ///
+    /// # Errors
+    ///
+    /// I/O errors coming from `out` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let mut out = vec![];
+    /// problem.write_json(&mut out).unwrap();
+    /// let file = JsonFile::parse(&out[..]).unwrap();
+    /// assert_eq!(file.problem.solve().unwrap().objective(), 7.0);
+    /// ```
+    pub fn write_json(&self, out: impl std::io::Write) -> std::io::Result<()> {
+        json_format::write_json(self, out)
+    }
+
+    /// Compare the problem with the `new` version of it and list the differences.
+    ///
+    /// Variables are matched by their names (see [`var_name`](#method.var_name)) and
+    /// constraints by their indices. Values are compared exactly. Coefficients of
+    /// the added and removed variables are not reported separately. Variables and
+    /// constraints [removed](#method.remove_var) from a problem that was not compacted yet
+    /// are reported as removed (or added, if they are present only in the new problem).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let mut new = problem.clone();
+    /// let constr = new.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    /// let diff = problem.diff(&new);
+    /// assert_eq!(diff.differences, vec![ProblemDifference::ConstraintAdded { constr }]);
+    /// assert_eq!(diff.to_string(), "constraint 1 added\n");
+    /// ```
+    pub fn diff(&self, new: &Problem) -> ProblemDiff {
+        diff::diff(self, new)
+    }
+
+    /// Size and sparsity statistics of the problem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 1.0), (y, 2.0)], ComparisonOp::Ge, 1.0);
+    /// problem.add_constraint(&[(y, -0.5)], ComparisonOp::Le, 3.0);
+    ///
+    /// let stats = problem.stats();
+    /// assert_eq!((stats.num_vars, stats.num_constraints), (2, 2));
+    /// assert_eq!((stats.num_le_constraints, stats.num_ge_constraints), (1, 1));
+    /// assert_eq!(stats.num_nonzeros, 3);
+    /// assert_eq!(stats.density(), 0.75);
+    /// assert_eq!((stats.min_abs_coeff, stats.max_abs_coeff), (0.5, 2.0));
+    /// ```
+    pub fn stats(&self) -> ProblemStats {
+        stats::problem_stats(self)
+    }
+
+    /// A canonical hash of the problem, e.g. for caching solves, deduplicating models
+    /// or detecting that a model changed unexpectedly.
+    ///
+    /// Equivalent problems have the same fingerprint regardless of the order in which
+    /// the constraints were added. Named variables are identified by their names, so
+    /// the order of adding them doesn't matter either, while unnamed variables are
+    /// identified by their positions. Removed variables and constraints and tags are ignored.
+    /// The fingerprint is stable across runs, platforms and versions of the crate (but note
+    /// that it depends on the exact floating-point values of the data).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let build = |constraints: &[(f64, f64)]| {
+    ///     let mut problem = Problem::new(OptimizationDirection::Minimize);
+    ///     let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    ///     for &(coeff, rhs) in constraints {
+    ///         problem.add_constraint(&[(x, coeff)], ComparisonOp::Ge, rhs);
+    ///     }
+    ///     problem
+    /// };
+    ///
+    /// let fingerprint = build(&[(1.0, 1.0), (2.0, 3.0)]).fingerprint();
+    /// assert_eq!(build(&[(2.0, 3.0), (1.0, 1.0)]).fingerprint(), fingerprint);
+    /// assert_ne!(build(&[(2.0, 3.0), (1.0, 2.0)]).fingerprint(), fingerprint);
+    /// ```
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint::fingerprint(self).0
+    }
+
+    /// Compute the Dulmage–Mendelsohn decomposition of the constraint matrix (rows are
+    /// constraints and columns are variables).
+    ///
+    /// Blocks of the decomposition show the structure of the problem: e.g. if the matrix
+    /// splits into several independent blocks, the problem consists of independent
+    /// subproblems. See [`DmDecomposition`] for details.
+    ///
+    /// [`DmDecomposition`]: struct.DmDecomposition.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let z = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 1.0);
+    /// problem.add_constraint(&[(z, 1.0)], ComparisonOp::Ge, 2.0);
+    ///
+    /// let dm = problem.dm_decomposition();
+    /// let blocks: Vec<_> = dm.blocks().collect();
+    /// assert_eq!(blocks.len(), 2);
+    /// assert_eq!((&blocks[0].rows[..], &blocks[0].cols[..]), (&[0][..], &[0, 1][..]));
+    /// assert_eq!((&blocks[1].rows[..], &blocks[1].cols[..]), (&[1][..], &[2][..]));
+    /// ```
+    pub fn dm_decomposition(&self) -> DmDecomposition {
+        let mut cols = vec![vec![]; self.obj_coeffs.len()];
+        for (r, (coeffs, _, _)) in self.constraints.iter().enumerate() {
+            for (c, &coeff) in coeffs.iter() {
+                if coeff != 0.0 {
+                    cols[c].push(r);
+                }
+            }
+        }
+        DmDecomposition::compute(self.constraints.len(), cols.len(), |c| &cols[c])
+    }
+
+    /// Strengthen the constraints using the integrality of the specified variables
+    /// and return the number of changed constraints.
+    ///
+    /// This is a presolve step that improves the bound given by the LP relaxation without
+    /// removing any integer solutions. For each inequality constraint:
+    /// * *Coefficient tightening:* if the constraint can't be violated when a binary
+    ///   variable (a variable from `integer_vars` with bounds 0 and 1) takes one of its
+    ///   values, the coefficient of that variable is decreased in abs. value.
+    /// * *Chvátal-Gomory strengthening:* if all variables of the constraint are integer and
+    ///   have integral coefficients, the constraint is divided by the GCD of the coefficients
+    ///   and the right-hand side is rounded. Equality constraints are divided too.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`ErrorKind::Infeasible`] error if some constraint can't be satisfied
+    /// within the variable bounds or if the right-hand side of an equality constraint is not
+    /// a multiple of the GCD.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 10.0));
+    /// let y = problem.add_var(1.0, (0.0, 10.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 3.5);
+    /// assert_eq!(problem.solve().unwrap().objective(), 3.5);
+    ///
+    /// assert_eq!(problem.tighten_integer_rows(&[x, y]), Ok(1));
+    /// assert_eq!(problem.solve().unwrap().objective(), 3.0);
+    /// ```
+    pub fn tighten_integer_rows(&mut self, integer_vars: &[Variable]) -> Result<usize, Error> {
+        self.tighten_integer_rows_with(integer_vars, &SolverOptions::default())
+    }
+
+    /// Same as [`tighten_integer_rows`](#method.tighten_integer_rows), but the rows are
+    /// tightened in parallel using up to [`threads`](struct.SolverOptions.html#structfield.threads)
+    /// threads. Other options are ignored.
+    pub fn tighten_integer_rows_with(
+        &mut self,
+        integer_vars: &[Variable],
+        options: &SolverOptions,
+    ) -> Result<usize, Error> {
+        presolve::tighten_integer_rows(self, integer_vars, options.threads)
+    }
+
+    /// Tighten the bounds of the variables `vars` by minimizing and maximizing each of them
+    /// subject to the constraints (optimization-based bound tightening) and return
+    /// the number of changed bounds.
+    ///
+    /// The feasible region of the problem is not changed, but tighter bounds strengthen
+    /// the LP relaxations of MIPs and the domain-based reasoning of MIP and NLP solvers.
+    /// The bounds are the optima of the LP relaxation relaxed outward by the feasibility
+    /// tolerance of the solver (1e-8, relative to the magnitude of values above 1), so that
+    /// rounding errors don't cut off feasible points. They are not rounded for integer
+    /// variables. Each solve is warm-started from the basis of the previous one and
+    /// is skipped if a bound is already attained by the current solution. If a variable is
+    /// unbounded in some direction, its bound stays infinite. The effort is limited by
+    /// the budget in `options`; the variables are processed in the given order, so list
+    /// the most important ones first.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`ErrorKind::Infeasible`] error if the problem is infeasible and
+    /// propagate other errors of the solver.
+    ///
+    /// [`ErrorKind::Infeasible`]: enum.ErrorKind.html#variant.Infeasible
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint(&[(x, 2.0), (y, -1.0)], ComparisonOp::Ge, 2.0);
+    ///
+    /// let options = BoundTighteningOptions::default();
+    /// assert_eq!(problem.tighten_bounds(&[x, y], &options), Ok(3));
+    /// assert!(problem.to_string().contains(" 0.99999999 <= x0 <= 4.00000004\n"));
+    /// ```
+    pub fn tighten_bounds(
+        &mut self,
+        vars: &[Variable],
+        options: &BoundTighteningOptions,
+    ) -> Result<usize, Error> {
+        obbt::tighten_bounds(self, vars, options)
+    }
+
+    /// Find the pairs of inequality constraints with opposite directions that together form
+    /// an equality (e.g. `x + 2 y <= 4` and `-2 x - 4 y <= -8`), turn the first constraint
+    /// of each pair into the equality and remove the second one. Returns the pairs of
+    /// the kept and the removed constraints.
+    ///
+    /// This is a presolve step that doesn't change the feasible region: each merged pair
+    /// is replaced by a single row, reducing the size of the basis once the problem is
+    /// [compacted](#method.compact). Rows are compared exactly after dividing them by their first
+    /// nonzero coefficient.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// let c1 = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// let c2 = problem.add_constraint(&[(x, 2.0), (y, 2.0)], ComparisonOp::Ge, 8.0);
+    ///
+    /// assert_eq!(problem.merge_inequality_pairs(), vec![(c1, c2)]);
+    /// assert!(!problem.has_constraint(c2));
+    /// assert_eq!(problem.solve().unwrap().objective(), 7.0);
+    /// ```
+    pub fn merge_inequality_pairs(&mut self) -> Vec<(ConstraintRef, ConstraintRef)> {
+        self.merge_inequality_pairs_with(&SolverOptions::default())
+    }
+
+    /// Same as [`merge_inequality_pairs`](#method.merge_inequality_pairs), but the rows are
+    /// normalized in parallel using up to
+    /// [`threads`](struct.SolverOptions.html#structfield.threads) threads. Other options
+    /// are ignored.
+    pub fn merge_inequality_pairs_with(
+        &mut self,
+        options: &SolverOptions,
+    ) -> Vec<(ConstraintRef, ConstraintRef)> {
+        presolve::merge_inequality_pairs(self, options.threads)
+    }
+
+    /// Find the continuous variables that are guaranteed to be integral in every basic
+    /// solution in which the `integer_vars` are integral.
+    ///
+    /// Integrality constraints on these variables can be dropped: branching on the integer
+    /// variables is enough. If `integer_vars` is empty, the variables are integral in every
+    /// basic solution, in particular in the solution found by [`solve`](#method.solve).
+    /// If all variables are returned, the problem has the integrality property (as e.g.
+    /// network flow problems with integral capacities and demands).
+    ///
+    /// The detection is based on a sufficient condition: the constraint matrix restricted to
+    /// the returned variables is totally unimodular because it has at most two nonzeros
+    /// (equal to 1 or -1) per column and its rows can be split in two sets as required by
+    /// the Heller-Tompkins theorem. Also, their bounds, the right-hand sides of their
+    /// constraints and the coefficients of the integer variables in these constraints are
+    /// integral and the constraints don't contain other continuous variables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// // Flow conservation: inflow x equals the sum of outflows y and z.
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 10.0));
+    /// let y = problem.add_var(0.0, (0.0, 3.0));
+    /// let z = problem.add_var(0.0, (0.0, 4.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, -1.0), (z, -1.0)], ComparisonOp::Eq, 0.0);
+    /// assert_eq!(problem.implied_integer_vars(&[]), vec![x, y, z]);
+    ///
+    /// // Because of the fractional bound of w, z can be fractional. But if z is integer,
+    /// // so are x and y.
+    /// let w = problem.add_var(0.0, (0.0, 0.5));
+    /// problem.add_constraint(&[(z, 1.0), (w, 1.0)], ComparisonOp::Le, 4.0);
+    /// assert_eq!(problem.implied_integer_vars(&[]), vec![]);
+    /// assert_eq!(problem.implied_integer_vars(&[z]), vec![x, y]);
+    /// ```
+    pub fn implied_integer_vars(&self, integer_vars: &[Variable]) -> Vec<Variable> {
+        presolve::implied_integer_vars(self, integer_vars)
+    }
+
+    /// Create a [Lagrangian relaxation](struct.LagrangianRelaxation.html) of the problem
+    /// in which the constraints `relaxed` are moved to the objective function.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a constraint is listed more than once.
+    pub fn lagrangian_relaxation(&self, relaxed: &[ConstraintRef]) -> LagrangianRelaxation {
+        LagrangianRelaxation::new(self, relaxed)
+    }
+
+    /// Solve the problem, finding the optimal objective function value and variable values.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error, if the problem is infeasible (constraints can't be satisfied)
+    /// or if the objective value is unbounded.
+    pub fn solve(&self) -> Result<Solution, Error> {
+        self.solve_with(&SolverOptions::default())
+    }
+
+    /// Solve the problem using the specified options.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error, if the problem is infeasible (constraints can't be satisfied)
+    /// or if the objective value is unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// for i in 0..10 {
+    ///     problem.add_constraint(&[(x, 1.0), (y, i as f64)], ComparisonOp::Le, 10.0);
+    /// }
+    ///
+    /// let options = SolverOptions {
+    ///     dualization: Dualization::Always,
+    ///     ..Default::default()
+    /// };
+    /// let solution = problem.solve_with(&options).unwrap();
+    /// assert!(f64::abs(solution.objective() - 10.0) < 1e-8);
+    /// ```
+    pub fn solve_with(&self, options: &SolverOptions) -> Result<Solution, Error> {
+        self.solve_from(options, None)
+    }
+
+    /// Solve the problem starting from the values of the variables in `start` (if any).
+    /// Problems with a starting point are not dualized.
+    pub(crate) fn solve_from(
+        &self,
+        options: &SolverOptions,
+        start: Option<&[f64]>,
+    ) -> Result<Solution, Error> {
+        self.warn_if_badly_scaled(options);
+        trace_span!(
+            "solve",
+            num_vars = self.obj_coeffs.len(),
+            num_constraints = self.constraints.len()
+        );
+        let options = &self.solver_options(options);
+        let dualized = if start.is_none() && dualize::should_dualize(self, options.dualization) {
+            failure::guard(self, options, || dualize::solve_dualized(self, options))
+        } else {
+            None
+        };
+
+        let solver = match dualized {
+            Some(res) => res,
+            None => Solver::try_new_with_options(
+                &self.obj_coeffs,
+                &self.var_mins,
+                &self.var_maxs,
+                &self.constraints,
+                start,
+                options,
+            )
+            .and_then(|mut solver| {
+                failure::guard_solver(self, options, &mut solver, Solver::initial_solve)?;
+                Ok(solver)
+            }),
+        }
+        .map_err(|err| err.in_generation(self.generation))?;
+
+        Ok(Solution {
+            num_vars: self.obj_coeffs.len(),
+            direction: self.direction,
+            obj_offset: self.obj_offset,
+            generation: self.generation,
+            solver,
+        })
+    }
+
+    /// Log a warning if scaling is disabled and the problem needs it.
+    pub(crate) fn warn_if_badly_scaled(&self, options: &SolverOptions) {
+        if options.scaling == ScalingMode::None && log_enabled!(log::Level::Warn) {
+            let warnings = validate::scaling_warnings(
+                self.obj_coeffs.len(),
+                &self.constraints,
+                self.generation,
+            );
+            if let Some(first) = warnings.first() {
+                warn!(
+                    "problem is badly scaled ({} warnings, first: {}), consider enabling scaling",
+                    warnings.len(),
+                    first
+                );
+            }
+        }
+    }
+
+    /// Start solving the problem incrementally: the returned [`SolveHandle`] performs
+    /// a bounded number of simplex iterations on each [step](struct.SolveHandle.html#method.step).
+    /// The problem is not [dualized](struct.SolverOptions.html#structfield.dualization) even
+    /// if the options say so, other options (including the
+    /// [failure dump](struct.SolverOptions.html#structfield.failure_dump_dir)) are applied
+    /// as in [`solve_with`].
+    ///
+    /// [`SolveHandle`]: struct.SolveHandle.html
+    /// [`solve_with`]: #method.solve_with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// use std::task::Poll;
+    ///
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    ///
+    /// let mut handle = problem.start_solve(&SolverOptions::default());
+    /// let solution = loop {
+    ///     // Do something else between the steps.
+    ///     if let Poll::Ready(res) = handle.step(1) {
+    ///         break res.unwrap();
+    ///     }
+    /// };
+    /// assert_eq!(solution.objective(), 7.0);
+    /// ```
+    pub fn start_solve(&self, options: &SolverOptions) -> SolveHandle {
+        SolveHandle::new(self, options)
+    }
+
+    /// Solve the problem for each of the right-hand side vectors in `rhs_batch`. Each vector
+    /// contains one value per constraint in the order of their [indices] (values of the
+    /// removed constraints are ignored).
+    ///
+    /// The problem is solved once and the solves for the vectors are hot-started from
+    /// the previous solution with the dual simplex, reusing its basis factorization. With
+    /// [`SolverOptions::threads`] bigger than 1 the batch is split into contiguous chunks
+    /// solved in parallel. The results are returned in the order of the vectors.
+    ///
+    /// [indices]: struct.ConstraintRef.html#method.idx
+    /// [`SolverOptions::threads`]: struct.SolverOptions.html#structfield.threads
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the length of a vector is not equal to the number of constraints or
+    /// if a value is NaN or infinite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    ///
+    /// let rhs_batch = vec![vec![4.0, 2.0], vec![6.0, 1.0], vec![1.0, 5.0]];
+    /// let results = problem.solve_rhs_batch(&rhs_batch, &SolverOptions::default());
+    /// assert_eq!(results[0].as_ref().unwrap().objective(), 7.0);
+    /// assert_eq!(results[1].as_ref().unwrap().objective(), 9.0);
+    /// assert_eq!(results[2].as_ref().unwrap_err().kind(), ErrorKind::Infeasible);
+    /// ```
+    pub fn solve_rhs_batch(
+        &self,
+        rhs_batch: &[Vec<f64>],
+        options: &SolverOptions,
+    ) -> Vec<Result<Solution, Error>> {
+        batch::solve_rhs_batch(self, rhs_batch, options)
+    }
+
+    /// Optimize each of the `objectives` in turn over the feasible region of the problem,
+    /// for example to sample the Pareto front of several criteria with their weighted sums.
+    ///
+    /// The objectives replace the objective of the problem (including its constant term)
+    /// and are optimized in the direction of the problem. The feasible region is found once
+    /// and each objective is optimized with the primal simplex starting from the basis
+    /// of the previous solution, which is usually much faster than solving from scratch.
+    /// The results are returned in the order of the objectives. The problem is solved
+    /// with the default [`SolverOptions`] without the objective cutoff.
+    ///
+    /// [`SolverOptions`]: struct.SolverOptions.html
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an objective contains a coefficient that is NaN or infinite or
+    /// a variable that is not in the problem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(0.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(0.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint([(x, 1.0), (y, 2.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let objectives = [
+    ///     LinearExpr::from([(x, 1.0)]),
+    ///     LinearExpr::from([(x, 1.0), (y, 1.0)]),
+    ///     LinearExpr::from([(y, 1.0)]),
+    /// ];
+    /// let results = problem.solve_multi_objective_sequence(&objectives);
+    /// let points = results
+    ///     .iter()
+    ///     .map(|res| {
+    ///         let solution = res.as_ref().unwrap();
+    ///         (solution[x], solution[y])
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(points[0].0, 2.0);
+    /// assert!(f64::abs(points[1].0 - 4.0 / 3.0) < 1e-8);
+    /// assert!(f64::abs(points[1].1 - 4.0 / 3.0) < 1e-8);
+    /// assert_eq!(points[2].1, 2.0);
+    /// ```
+    pub fn solve_multi_objective_sequence(
+        &self,
+        objectives: &[LinearExpr],
+    ) -> Vec<Result<Solution, Error>> {
+        batch::solve_objective_sequence(self, objectives)
+    }
+
+    /// Solve the problem approximately with a first-order method that needs only
+    /// multiplications by the constraint matrix and its transpose.
+    ///
+    /// The method is the primal-dual hybrid gradient with restarts and primal weight
+    /// updates, as in [PDLP]. It doesn't factorize matrices, so its memory use is linear
+    /// in the problem size, which makes it an alternative for huge problems where
+    /// the factorization of the basis is too costly. The price is the accuracy: the solution
+    /// satisfies the constraints only up to the tolerance and the convergence can be slow.
+    /// The approximate solution can be polished into an exact one with
+    /// [`FirstOrderSolution::crossover`](struct.FirstOrderSolution.html#method.crossover).
+    ///
+    /// Infeasible and unbounded problems are not detected: the method stops at the iteration
+    /// limit with a solution that is not [converged]. To solve a problem without forming
+    /// its constraint matrix, use a [`MatrixFreeProblem`](struct.MatrixFreeProblem.html).
+    ///
+    /// [PDLP]: https://arxiv.org/abs/2106.04756
+    /// [converged]: struct.FirstOrderSolution.html#method.is_converged
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    ///
+    /// let approx = problem.solve_first_order(&FirstOrderOptions::default());
+    /// assert!(approx.is_converged());
+    /// assert!(f64::abs(approx.objective() - 7.0) < 1e-5);
+    ///
+    /// let solution = approx.crossover(&problem, &SolverOptions::default()).unwrap();
+    /// assert_eq!(solution.objective(), 7.0);
+    /// ```
+    pub fn solve_first_order(&self, options: &FirstOrderOptions) -> FirstOrderSolution {
+        pdlp::solve(self, options)
+    }
+
+    /// Solve the problem with a cutting-plane loop: the `separator` is called with each
+    /// solution and the violated cuts it returns are added to the problem, after which the
+    /// solution is reoptimized from the current basis. The loop ends when the separator
+    /// returns no violated cuts.
+    ///
+    /// This is also the way to handle lazy constraints: constraints that are too numerous
+    /// to add upfront, but are easy to check. To start from a solution obtained with
+    /// specific options, use [`Solution::separate`](struct.Solution.html#method.separate).
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the problem or the problem with the added cuts is
+    /// infeasible, or if the objective value is unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let vars = [
+    ///     problem.add_var(1.0, (0.0, 1.0)),
+    ///     problem.add_var(1.0, (0.0, 1.0)),
+    ///     problem.add_var(1.0, (0.0, 1.0)),
+    /// ];
+    ///
+    /// // At most one of each pair of variables can be nonzero.
+    /// let solution = problem
+    ///     .solve_with_separation(|solution| {
+    ///         let mut cuts = vec![];
+    ///         for i in 0..3 {
+    ///             let (a, b) = (vars[i], vars[(i + 1) % 3]);
+    ///             if solution[a] + solution[b] > 1.0 {
+    ///                 cuts.push(Cut::new(&[(a, 1.0), (b, 1.0)], ComparisonOp::Le, 1.0));
+    ///             }
+    ///         }
+    ///         cuts
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(solution.objective(), 1.5);
+    /// ```
+    pub fn solve_with_separation(
+        &self,
+        separator: impl FnMut(&Solution) -> Vec<Cut>,
+    ) -> Result<Solution, Error> {
+        let (solution, _) = cuts::separation_loop(self.solve()?, separator)?;
+        Ok(solution)
+    }
+}
+
+/// Convert the objective cutoff to the form used by the solver: minimization without
+/// the constant term of the objective.
+fn internal_cutoff(direction: OptimizationDirection, obj_offset: f64, cutoff: f64) -> f64 {
+    match direction {
+        OptimizationDirection::Minimize => cutoff - obj_offset,
+        OptimizationDirection::Maximize => -cutoff - obj_offset,
+    }
+}
+
+/// A solution of a problem: optimal objective function value and variable values.
+///
+/// Note that a `Solution` instance contains the whole solver machinery which can require
+/// a lot of memory for larger problems. Thus saving the `Solution` instance (as opposed
+/// to getting the values of interest and discarding the solution) is mainly useful if you
+/// want to add more constraints to it later.
+#[derive(Clone)]
+pub struct Solution {
+    direction: OptimizationDirection,
+    num_vars: usize,
+    /// Constant term of the objective function in the minimization form.
+    obj_offset: f64,
+    /// Generation of the problem, see [`Problem::compact`].
+    generation: u32,
+    solver: solver::Solver,
+}
+
+impl std::fmt::Debug for Solution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Only printing lengths here because actual data is probably huge.
+        f.debug_struct("Solution")
+            .field("direction", &self.direction)
+            .field("num_vars", &self.num_vars)
+            .field("num_constraints", &self.solver.num_constraints())
+            .field("objective", &self.objective())
+            .finish()
+    }
+}
+
+impl Solution {
+    /// Optimal value of the objective function.
+    pub fn objective(&self) -> f64 {
+        match self.direction {
+            OptimizationDirection::Minimize => self.solver.cur_obj_val + self.obj_offset,
+            OptimizationDirection::Maximize => -(self.solver.cur_obj_val + self.obj_offset),
+        }
+    }
+
+    /// Whether the solution is proven optimal. It is not optimal only if the solve was
+    /// stopped early because the estimated [relative gap](#method.relative_gap) reached
+    /// the [tolerance](struct.SolverOptions.html#structfield.relative_gap).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let vars: Vec<_> = (0..50)
+    ///     .map(|i| problem.add_var((1 + i % 7) as f64, (0.0, f64::INFINITY)))
+    ///     .collect();
+    /// for i in 0..50 {
+    ///     let expr: LinearExpr = (0..3)
+    ///         .map(|j| (vars[(i + 3 * j) % 50], (1 + (i + j) % 4) as f64))
+    ///         .collect();
+    ///     problem.add_constraint(expr, ComparisonOp::Le, (10 + i % 3) as f64);
+    /// }
+    ///
+    /// let options = SolverOptions {
+    ///     relative_gap: Some(0.1),
+    ///     ..Default::default()
+    /// };
+    /// let solution = problem.solve_with(&options).unwrap();
+    /// assert!(!solution.is_optimal());
+    /// assert!(solution.relative_gap() <= 0.1);
+    ///
+    /// let optimal = problem.solve().unwrap();
+    /// assert!(optimal.is_optimal());
+    /// assert_eq!(optimal.relative_gap(), 0.0);
+    /// assert!(solution.objective() >= 0.9 * optimal.objective());
+    /// ```
+    pub fn is_optimal(&self) -> bool {
+        self.solver.is_optimal()
+    }
+
+    /// Estimated relative gap between the objective value and the optimal one: the difference
+    /// between the objective value and a bound on the optimal value divided by the
+    /// abs. objective value (or by 1 if it is smaller). Zero for optimal solutions.
+    pub fn relative_gap(&self) -> f64 {
+        self.solver.relative_gap()
+    }
+
+    /// Set the [objective cutoff](struct.SolverOptions.html#structfield.objective_cutoff)
+    /// applied when the solution is reoptimized after it is modified, e.g. by fixing
+    /// variables or adding constraints. `None` removes the cutoff.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(3.0, (0.0, 1.0));
+    /// let y = problem.add_var(2.0, (0.0, 1.0));
+    /// problem.add_constraint(&[(x, 2.0), (y, 2.0)], ComparisonOp::Le, 3.0);
+    ///
+    /// let mut solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 4.0);
+    /// // A solution with the objective value 3 is known, so only better ones are of interest.
+    /// solution.set_objective_cutoff(Some(3.0));
+    ///
+    /// let branch = solution.clone().fix_var(x, 0.0);
+    /// assert_eq!(branch.unwrap_err().kind(), ErrorKind::Cutoff);
+    /// let branch = solution.fix_var(x, 1.0).unwrap();
+    /// assert_eq!(branch.objective(), 4.0);
+    /// ```
+    pub fn set_objective_cutoff(&mut self, cutoff: Option<f64>) {
+        let cutoff = cutoff.map(|cutoff| internal_cutoff(self.direction, self.obj_offset, cutoff));
+        self.solver.set_objective_cutoff(cutoff);
+    }
+
+    /// Value of the variable at optimum.
+    ///
+    /// Note that you can use indexing operations to get variable values.
+    pub fn var_value(&self, var: Variable) -> &f64 {
+        self.solver.get_value(self.var_idx(var))
+    }
+
+    /// Value of the variable of the `problem` before its
+    /// [substitution](struct.Problem.html#method.substitute_var) at optimum.
+    pub fn original_var_value(&self, problem: &Problem, var: Variable) -> f64 {
+        let (scale, offset) = problem.var_transform(var);
+        scale * self.var_value(var) + offset
+    }
+
+    /// Dual value (shadow price) of the constraint at optimum.
+    ///
+    /// This is the rate of change of the optimal objective value when the right-hand side
+    /// of the constraint is increased. It is zero for constraints that are not binding.
+    pub fn dual_value(&self, constr: ConstraintRef) -> f64 {
+        let c = self.constraint_idx(constr);
+        match self.direction {
+            OptimizationDirection::Minimize => self.solver.dual_value(c),
+            OptimizationDirection::Maximize => -self.solver.dual_value(c),
+        }
+    }
+
+    /// Dual values of the constraints of the `problem` with the tag (see
+    /// [`Problem::tag_constraint`](struct.Problem.html#method.tag_constraint)) in the order
+    /// of their addition.
+    ///
+    /// `problem` must be the problem this solution was obtained from; tags are not stored
+    /// in the solution.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(3.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+    /// let labor = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// let machine = problem.add_constraint(&[(x, 1.0), (y, 3.0)], ComparisonOp::Le, 9.0);
+    /// let demand = problem.add_constraint(&[(x, 1.0)], ComparisonOp::Le, 3.0);
+    /// problem.tag_constraint(labor, "capacity");
+    /// problem.tag_constraint(machine, "capacity");
+    /// problem.tag_constraint(demand, "market");
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(
+    ///     solution.duals_for_tag(&problem, "capacity"),
+    ///     vec![(labor, 2.0), (machine, 0.0)],
+    /// );
+    /// assert_eq!(solution.duals_for_tag(&problem, "market"), vec![(demand, 1.0)]);
+    /// ```
+    pub fn duals_for_tag(
+        &self,
+        problem: &Problem,
+        tag: impl Into<Tag>,
+    ) -> Vec<(ConstraintRef, f64)> {
+        assert!(problem.constraints.len() <= self.solver.num_constraints());
+        problem
+            .constraints_with_tag(tag)
+            .map(|constr| (constr, self.dual_value(constr)))
+            .collect()
+    }
+
+    /// Sensitivity analysis of the right-hand side of the constraint.
+    ///
+    /// Returns the interval over which the right-hand side can vary while the current basis
+    /// remains optimal (and thus the [dual value](#method.dual_value) remains the same).
+    /// Note that this method needs to clone the factorization of the basis, so it is not cheap
+    /// for larger problems.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 3.0));
+    /// let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+    /// let constr = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// problem.add_constraint(&[(y, 1.0)], ComparisonOp::Le, 3.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.dual_value(constr), 1.0);
+    ///
+    /// // The dual value stays the same until the rhs drops to 3 (x becomes 0)
+    /// // or rises to 6 (x hits its upper bound).
+    /// let range = solution.rhs_range(constr);
+    /// assert_eq!((range.min, range.max), (3.0, 6.0));
+    /// assert_eq!(range.leaving_at_min, Some(BasisVar::Var(x)));
+    /// assert_eq!(range.leaving_at_max, Some(BasisVar::Var(x)));
+    /// ```
+    pub fn rhs_range(&self, constr: ConstraintRef) -> RhsRange {
+        let range = self.solver.rhs_range(self.constraint_idx(constr));
+        let to_basis_var = |var: usize| {
+            if var < self.num_vars {
+                BasisVar::Var(self.var_ref(var))
+            } else {
+                BasisVar::Slack(self.constraint_ref(var - self.num_vars))
+            }
+        };
+        RhsRange {
+            dual_value: self.dual_value(constr),
+            min: range.min,
+            max: range.max,
+            leaving_at_min: range.leaving_at_min.map(to_basis_var),
+            leaving_at_max: range.leaving_at_max.map(to_basis_var),
+        }
+    }
+
+    /// Whether the variable is basic in the optimal basis.
+    ///
+    /// Non-basic variables are at one of their bounds. Together with the values this
+    /// determines the optimal basis, e.g. for writing it to a basis file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// let constr = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert!(solution.is_basic(BasisVar::Var(x)));
+    /// assert!(!solution.is_basic(BasisVar::Var(y)));
+    /// assert!(!solution.is_basic(BasisVar::Slack(constr)));
+    /// ```
+    pub fn is_basic(&self, var: BasisVar) -> bool {
+        match var {
+            BasisVar::Var(var) => self.solver.is_basic(self.var_idx(var)),
+            BasisVar::Slack(constr) => self
+                .solver
+                .is_basic(self.num_vars + self.constraint_idx(constr)),
+        }
+    }
+
+    /// Returns `true` if the optimum is (possibly) not unique.
+    ///
+    /// This is detected by checking whether some non-basic variable has zero reduced cost,
+    /// meaning that it can change its value without changing the objective value.
+    /// Note that in case of degeneracy such change may not lead to a different optimal point.
+    pub fn has_alternative_optima(&self) -> bool {
+        self.solver.has_alternative_optima()
+    }
+
+    /// Enumerate up to `max_count` optimal vertices that differ from this solution
+    /// in the variable values.
+    ///
+    /// Vertices are found by a breadth-first search over the bases obtained by pivoting
+    /// on variables with zero reduced costs, so vertices reachable only through degenerate
+    /// pivots can be missed. As each returned `Solution` contains a copy of the solver state,
+    /// `max_count` should be kept small for larger problems.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// // Both (4, 0) and (0, 4) are optimal.
+    /// let solution = problem.solve().unwrap();
+    /// assert!(solution.has_alternative_optima());
+    /// let others = solution.alternative_optima(10);
+    /// assert_eq!(others.len(), 1);
+    /// assert_eq!(others[0].objective(), 4.0);
+    /// assert_eq!(others[0][x], solution[y]);
+    /// ```
+    pub fn alternative_optima(&self, max_count: usize) -> Vec<Solution> {
+        self.solver
+            .find_alternative_optima(max_count)
+            .into_iter()
+            .map(|solver| Solution {
+                direction: self.direction,
+                num_vars: self.num_vars,
+                obj_offset: self.obj_offset,
+                generation: self.generation,
+                solver,
+            })
+            .collect()
+    }
+
+    /// Describe the set of all optimal solutions (the *optimal face*) by the ranges of the
+    /// variable values and of the constraint left-hand sides over it. Returns `None` if the
+    /// solution is not [optimal](#method.is_optimal).
+    ///
+    /// Unlike [`alternative_optima`](#method.alternative_optima) this covers the whole face,
+    /// including its unbounded directions, but it is expensive: two problems restricted
+    /// to the optimal face are solved for each variable and constraint that is not
+    /// trivially fixed on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 3.0));
+    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let z = problem.add_var(-1.0, (0.0, 1.0));
+    /// let constr = problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// // x + y == 4 for all optimal solutions, with x between 0 and 3, and z == 0.
+    /// let solution = problem.solve().unwrap();
+    /// let face = solution.optimal_face().unwrap();
+    /// assert_eq!(face.varying_vars().collect::<Vec<_>>(), vec![x, y]);
+    /// assert_eq!(face.var_range(x), (0.0, 3.0));
+    /// assert_eq!(face.var_range(y), (1.0, 4.0));
+    /// assert_eq!(face.var_range(z), (0.0, 0.0));
+    /// assert_eq!(face.constraint_range(constr), (4.0, 4.0));
+    /// ```
+    pub fn optimal_face(&self) -> Option<OptimalFace> {
+        if !self.is_optimal() {
+            return None;
+        }
+        let ranges = self.solver.optimal_face_ranges();
+        Some(OptimalFace::new(ranges, self.num_vars, self.generation))
+    }
+
+    /// Statistics of the simplex iterations performed to obtain this solution.
+    pub fn stats(&self) -> &SolverStats {
+        &self.solver.stats
+    }
+
+    /// Wall-clock time spent in the phases of the solution process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    /// let solution = problem.solve().unwrap();
+    ///
+    /// let timings = solution.timings();
+    /// assert!(timings.factorization <= timings.sum());
+    /// println!("time spent in pricing: {:?}", timings.pricing);
+    /// ```
+    pub fn timings(&self) -> SolverTimings {
+        self.solver.timings()
+    }
+
+    /// Variables from `integer_vars` that have fractional values in the solution, together
+    /// with the fractional parts of their values (between 0 and 1, exclusive). Values closer
+    /// than 1e-6 to an integer are considered integral.
+    ///
+    /// When the solution is the optimum of the LP relaxation of a MIP (the problem solved
+    /// without the integrality requirements), this together with the values and the dual
+    /// values gives all that is needed for custom rounding schemes, without running any
+    /// of the heuristics. If no variables are returned, the solution is integer-feasible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(3.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(3.0, (0.0, f64::INFINITY));
+    /// let c = problem.add_constraint(&[(x, 2.0), (y, 3.0)], ComparisonOp::Le, 11.0);
+    /// problem.add_constraint(&[(x, 1.0), (y, -1.0)], ComparisonOp::Le, 0.5);
+    ///
+    /// let relaxation = problem.solve().unwrap();
+    /// assert_eq!((relaxation[x], relaxation[y]), (2.5, 2.0));
+    /// assert!(f64::abs(relaxation.dual_value(c) - 1.2) < 1e-8);
+    /// assert_eq!(relaxation.fractional_vars(&[x, y]), vec![(x, 0.5)]);
+    /// ```
+    pub fn fractional_vars(&self, integer_vars: &[Variable]) -> Vec<(Variable, f64)> {
+        diving::fractional_vars(self, integer_vars)
+    }
+
+    /// Search for a solution in which the specified variables have integer values by
+    /// repeatedly rounding one of the fractional variables and re-solving the problem
+    /// (a *diving* heuristic).
+    ///
+    /// The variable to round is chosen according to `rule`. If the rounding makes the
+    /// problem infeasible, the variable is rounded in the opposite direction, and if that
+    /// fails too, the dive is abandoned and `None` is returned. Diving is a cheap way of
+    /// finding good integer-feasible solutions, but it gives no optimality guarantees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(2.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(3.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 2.0), (y, 3.0)], ComparisonOp::Le, 11.0);
+    /// problem.add_constraint(&[(x, 1.0), (y, -1.0)], ComparisonOp::Le, 0.5);
+    ///
+    /// let relaxation = problem.solve().unwrap();
+    /// let sol = relaxation.dive(&[x, y], DivingRule::Fractional).unwrap();
+    /// assert_eq!((sol[x].fract(), sol[y].fract()), (0.0, 0.0));
+    /// assert!(sol.objective() <= relaxation.objective());
+    /// ```
+    pub fn dive(&self, integer_vars: &[Variable], rule: DivingRule) -> Option<Solution> {
+        diving::dive(self, integer_vars, rule)
+    }
+
+    /// Turn a (possibly partial) assignment of integer values into an integer-feasible
+    /// solution that can be used as the initial incumbent of a MIP search (a *MIP start*).
+    ///
+    /// The variables from `integer_vars` that appear in `start` are fixed to their
+    /// (rounded) values, and the problem is re-solved to find the values of the continuous
+    /// variables. Values of the continuous variables in `start` are ignored. The start is
+    /// repaired if necessary: values that make the problem infeasible are skipped, and the
+    /// integer variables that didn't get a value are rounded by [diving](#method.dive).
+    /// Returns `None` if the repair fails.
+    ///
+    /// In the returned solution the integer variables are fixed with
+    /// [`fix_var`](#method.fix_var).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(2.0, (0.0, 3.0));
+    /// let y = problem.add_var(1.0, (0.0, 3.0));
+    /// let z = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0), (z, 1.0)], ComparisonOp::Le, 4.5);
+    ///
+    /// let relaxation = problem.solve().unwrap();
+    /// let start = relaxation.repair_mip_start(&[x, y], &[(x, 1.0)]).unwrap();
+    /// assert_eq!(start[x], 1.0);
+    /// assert_eq!(start[y].fract(), 0.0);
+    /// assert_eq!(start.objective(), 5.5);
+    /// ```
+    pub fn repair_mip_start(
+        &self,
+        integer_vars: &[Variable],
+        start: &[(Variable, f64)],
+    ) -> Option<Solution> {
+        diving::repair_start(self, integer_vars, start)
+    }
+
+    /// Find up to `max_cuts` {0, 1/2}-Chvátal-Gomory (*zero-half*) cuts violated by the
+    /// solution, in order of decreasing efficacy.
+    ///
+    /// A zero-half cut is obtained by summing a subset of the constraints and variable bounds
+    /// (each with the multiplier 1/2) and rounding the coefficients and the right-hand side
+    /// down. Only constraints with integral coefficients and right-hand sides that contain
+    /// only variables from `integer_vars` are used. These cuts are particularly effective
+    /// on models with binary variables such as set partitioning and matching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// // Odd cycle: x + y <= 1, y + z <= 1, x + z <= 1 imply x + y + z <= 1 for integer vars.
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 1.0));
+    /// let y = problem.add_var(1.0, (0.0, 1.0));
+    /// let z = problem.add_var(1.0, (0.0, 1.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 1.0);
+    /// problem.add_constraint(&[(y, 1.0), (z, 1.0)], ComparisonOp::Le, 1.0);
+    /// problem.add_constraint(&[(x, 1.0), (z, 1.0)], ComparisonOp::Le, 1.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 1.5);
+    /// let cuts = solution.zero_half_cuts(&[x, y, z], 10);
+    /// assert_eq!(cuts.len(), 1);
+    /// assert_eq!(cuts[0].rhs(), 1.0);
+    /// ```
+    pub fn zero_half_cuts(&self, integer_vars: &[Variable], max_cuts: usize) -> Vec<Cut> {
+        zero_half::zero_half_cuts(self, integer_vars, max_cuts)
+    }
+
+    /// Find up to `max_cuts` lifted flow cover cuts violated by the solution, in order of
+    /// decreasing efficacy.
+    ///
+    /// Flow cover cuts are derived from *single-node flow* structures: constraints
+    /// bounding the total flow through a node where each flow `y` is limited by a variable
+    /// upper bound `y <= u * x` with a binary `x` (a variable from `integer_vars` with bounds
+    /// 0 and 1). Variable upper bounds are recognized among the constraints of the form
+    /// `a * y - b * x <= 0`. The cuts are obtained as mixed-integer rounding (MIR)
+    /// inequalities, which lifts the flows not in the cover. They are effective on fixed
+    /// charge network design and lot-sizing models.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let y1 = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y2 = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let x1 = problem.add_var(-1.0, (0.0, 1.0));
+    /// let x2 = problem.add_var(-1.0, (0.0, 1.0));
+    /// // y1 <= 4 * x1, y2 <= 4 * x2, y1 + y2 <= 5
+    /// problem.add_constraint(&[(y1, 1.0), (x1, -4.0)], ComparisonOp::Le, 0.0);
+    /// problem.add_constraint(&[(y2, 1.0), (x2, -4.0)], ComparisonOp::Le, 0.0);
+    /// problem.add_constraint(&[(y1, 1.0), (y2, 1.0)], ComparisonOp::Le, 5.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 3.75);
+    /// let cuts = solution.flow_cover_cuts(&[x1, x2], 10);
+    /// assert!(!cuts.is_empty());
+    /// let solution = solution.add_constraint(cuts[0].lhs(), ComparisonOp::Le, cuts[0].rhs())
+    ///     .unwrap();
+    /// assert!(solution.objective() < 3.75);
+    /// ```
+    pub fn flow_cover_cuts(&self, integer_vars: &[Variable], max_cuts: usize) -> Vec<Cut> {
+        flow_cover::flow_cover_cuts(self, integer_vars, max_cuts)
+    }
+
+    /// Find up to `max_cuts` implied bound cuts violated by the solution, in order of
+    /// decreasing efficacy.
+    ///
+    /// The cuts are found by *probing*: each fractional binary variable `x` (a variable from
+    /// `integer_vars` with bounds 0 and 1) is tentatively fixed to 0 and to 1 and the bounds
+    /// of the other variables are propagated through the constraints. If `x == 0` implies
+    /// `y <= u0` and `x == 1` implies `y <= u1`, then `y <= u0 + (u1 - u0) * x` is valid
+    /// (and similarly for lower bounds). If one of the fixings is infeasible, the cut
+    /// fixes `x` to the other value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(-1.0, (0.0, 1.0));
+    /// let y = problem.add_var(1.0, (0.0, 5.0));
+    /// // y <= 10 * x
+    /// problem.add_constraint(&[(y, 1.0), (x, -10.0)], ComparisonOp::Le, 0.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 4.5);
+    /// // y <= 5 * x
+    /// let cuts = solution.implied_bound_cuts(&[x], 10);
+    /// assert_eq!(cuts.len(), 1);
+    /// let solution = solution.add_constraint(cuts[0].lhs(), ComparisonOp::Le, cuts[0].rhs())
+    ///     .unwrap();
+    /// assert_eq!(solution.objective(), 4.0);
+    /// ```
+    pub fn implied_bound_cuts(&self, integer_vars: &[Variable], max_cuts: usize) -> Vec<Cut> {
+        probing::implied_bound_cuts(self, integer_vars, max_cuts)
+    }
+
+    /// Analyze the infeasibility caused by fixing the variables to the given values and
+    /// return a conflict constraint that excludes this combination of values everywhere.
+    ///
+    /// This is useful in a branch-and-bound search or a dive: when the problem with a set
+    /// of fixings is infeasible, the fixings that are not needed for the infeasibility are
+    /// dropped (each one is tentatively removed and the problem is re-solved) and the cut
+    /// requires at least one of the remaining binary variables to take the other value.
+    /// The cut is valid globally, so adding it prunes the same failure in other parts of
+    /// the search.
+    ///
+    /// Returns `None` if the problem with the fixings is feasible or if the conflict
+    /// contains a variable that is not binary (a variable with bounds 0 and 1 fixed to
+    /// either of them).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 1.0));
+    /// let y = problem.add_var(1.0, (0.0, 1.0));
+    /// let z = problem.add_var(1.0, (0.0, 1.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 1.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// // z doesn't take part in the conflict.
+    /// let cut = solution.conflict_cut(&[(x, 1.0), (z, 1.0), (y, 1.0)]).unwrap();
+    /// let solution = solution.add_constraint(cut.lhs(), ComparisonOp::Le, cut.rhs()).unwrap();
+    /// assert!(solution.fix_var(x, 1.0).unwrap().fix_var(y, 1.0).is_err());
+    /// ```
+    pub fn conflict_cut(&self, fixings: &[(Variable, f64)]) -> Option<Cut> {
+        conflict::conflict_cut(self, fixings)
     }
 
-    /// Add a linear constraint to the problem.
+    /// Returns an object that implements [`Display`](std::fmt::Display) and prints the
+    /// objective value and the nonzero variable values using the variable names from
+    /// `problem`. Precision, the threshold for hiding values and printing of the dual values
+    /// can be configured.
     ///
     /// # Panics
     ///
-    /// Will panic if a variable was added more than once to the left-hand side expression.
+    /// Will panic if `problem` has a different number of variables than the problem that
+    /// was solved.
     ///
     /// # Examples
     ///
-    /// Left-hand side of the constraint can be specified in several ways:
     /// ```
     /// # use minilp::*;
-    /// let mut problem = Problem::new(OptimizationDirection::Minimize);
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
     /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
-    /// let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.set_var_name(x, "x");
+    /// problem.set_var_name(y, "y");
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
     ///
-    /// // Add an x + y >= 2 constraint, specifying the left-hand side expression:
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(
+    ///     solution.display(&problem).with_duals().to_string(),
+    ///     "objective: 7\nx = 1\ny = 3\nduals:\nc0 = 1\n",
+    /// );
+    /// ```
+    pub fn display<'a>(&'a self, problem: &'a Problem) -> SolutionDisplay<'a> {
+        assert_eq!(
+            self.num_vars,
+            problem.obj_coeffs.len(),
+            "problem doesn't match the solution"
+        );
+        SolutionDisplay::new(self, problem)
+    }
+
+    /// Write the optimal basis to `out` in the MPS basis format with the problem `name`.
     ///
-    /// // * by passing a slice of pairs (useful when explicitly enumerating variables)
-    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+    /// Variables are named as in `problem` and constraints are named `c0`, `c1`, ...
+    /// (as in the [LP format](struct.Problem.html#impl-Display-for-Problem) output).
     ///
-    /// // * by passing an iterator of variable-coefficient pairs.
-    /// let vars = [x, y];
-    /// problem.add_constraint(vars.iter().map(|&v| (v, 1.0)), ComparisonOp::Ge, 2.0);
+    /// # Panics
+    ///
+    /// Will panic if `problem` has a different number of variables or constraints than
+    /// the problem that was solved.
+    ///
+    /// # Examples
     ///
-    /// // * by manually constructing a LinearExpr.
-    /// let mut lhs = LinearExpr::empty();
-    /// for &v in &vars {
-    ///     lhs.add(v, 1.0);
-    /// }
-    /// problem.add_constraint(lhs, ComparisonOp::Ge, 2.0);
     /// ```
-    pub fn add_constraint(&mut self, expr: impl Into<LinearExpr>, cmp_op: ComparisonOp, rhs: f64) {
-        let expr = expr.into();
-        self.constraints.push((
-            CsVec::new(self.obj_coeffs.len(), expr.vars, expr.coeffs),
-            cmp_op,
-            rhs,
-        ));
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.set_var_name(x, "x");
+    /// problem.set_var_name(y, "y");
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// let mut out = vec![];
+    /// solution.write_basis(&problem, "example", &mut out).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "NAME          example\n XU x c0\n UL y\nENDATA\n",
+    /// );
+    /// ```
+    pub fn write_basis(
+        &self,
+        problem: &Problem,
+        name: &str,
+        out: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        assert!(
+            self.num_vars == problem.obj_coeffs.len()
+                && self.solver.num_constraints() == problem.constraints.len(),
+            "problem doesn't match the solution"
+        );
+        basis_file::write_basis(self, problem, name, out)
     }
 
-    /// Solve the problem, finding the optimal objective function value and variable values.
+    /// Write the LU factors of the basis matrix to `lower` and `upper` in the Matrix Market
+    /// coordinate format (see [`Problem::write_matrix_market`]), to inspect their fill-in
+    /// and numerics.
+    ///
+    /// The factors are of the basis matrix with the rows and columns permuted by the pivoting
+    /// order, as computed at the last refactorization. The updates of the basis since
+    /// then are not included. The diagonal of `lower` is written even when it consists
+    /// of implicit ones.
+    ///
+    /// [`Problem::write_matrix_market`]: struct.Problem.html#method.write_matrix_market
     ///
     /// # Errors
     ///
-    /// Will return an error, if the problem is infeasible (constraints can't be satisfied)
-    /// or if the objective value is unbounded.
-    pub fn solve(&self) -> Result<Solution, Error> {
-        let mut solver = Solver::try_new(
-            &self.obj_coeffs,
-            &self.var_mins,
-            &self.var_maxs,
-            &self.constraints,
-        )?;
-        solver.initial_solve()?;
-        Ok(Solution {
-            num_vars: self.obj_coeffs.len(),
-            direction: self.direction,
-            solver,
-        })
-    }
-}
-
-/// A solution of a problem: optimal objective function value and variable values.
-///
-/// Note that a `Solution` instance contains the whole solver machinery which can require
-/// a lot of memory for larger problems. Thus saving the `Solution` instance (as opposed
-/// to getting the values of interest and discarding the solution) is mainly useful if you
-/// want to add more constraints to it later.
-#[derive(Clone)]
-pub struct Solution {
-    direction: OptimizationDirection,
-    num_vars: usize,
-    solver: solver::Solver,
-}
-
-impl std::fmt::Debug for Solution {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Only printing lengths here because actual data is probably huge.
-        f.debug_struct("Solution")
-            .field("direction", &self.direction)
-            .field("num_vars", &self.num_vars)
-            .field("num_constraints", &self.solver.num_constraints())
-            .field("objective", &self.objective())
-            .finish()
+    /// I/O errors coming from `lower` and `upper` are returned.
+    pub fn write_basis_factors(
+        &self,
+        lower: impl std::io::Write,
+        upper: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        let lu_factors = self.solver.lu_factors();
+        matrix_market::write_triangle(lu_factors.lower(), lower)?;
+        matrix_market::write_triangle(lu_factors.upper(), upper)
     }
-}
 
-impl Solution {
-    /// Optimal value of the objective function.
-    pub fn objective(&self) -> f64 {
-        match self.direction {
-            OptimizationDirection::Minimize => self.solver.cur_obj_val,
-            OptimizationDirection::Maximize => -self.solver.cur_obj_val,
+    /// Iterate over the variable-value pairs of the solution.
+    pub fn iter(&self) -> SolutionIter<'_> {
+        SolutionIter {
+            solution: self,
+            var_idx: 0,
         }
     }
 
-    /// Value of the variable at optimum.
+    /// Check that `other` is approximately the same solution, e.g. a solution of the same
+    /// problem obtained with a different version of the crate or with different options.
     ///
-    /// Note that you can use indexing operations to get variable values.
-    pub fn var_value(&self, var: Variable) -> &f64 {
-        assert!(var.0 < self.num_vars);
-        self.solver.get_value(var.0)
+    /// Objective values and values of the variables must differ by at most `tol` times
+    /// the magnitude of the values (or by `tol` if the magnitude is less than 1). Returns
+    /// false if the solutions have different numbers of variables. See [`var_diffs`]
+    /// to find out which variables differ.
+    ///
+    /// Note that a problem with alternative optima can legitimately have several different
+    /// optimal solutions, compare only objective values in this case.
+    ///
+    /// [`var_diffs`]: #method.var_diffs
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// let options = SolverOptions { pricing: Pricing::Partial, ..SolverOptions::default() };
+    /// let other = problem.solve_with(&options).unwrap();
+    /// assert!(solution.approx_eq(&other, 1e-9));
+    ///
+    /// let other = other.fix_var(x, 0.5).unwrap();
+    /// assert!(!solution.approx_eq(&other, 1e-9));
+    /// ```
+    pub fn approx_eq(&self, other: &Solution, tol: f64) -> bool {
+        self.num_vars == other.num_vars
+            && is_approx_eq(self.objective(), other.objective(), tol)
+            && self.var_diffs(other, tol).next().is_none()
     }
 
-    /// Iterate over the variable-value pairs of the solution.
-    pub fn iter(&self) -> SolutionIter {
-        SolutionIter {
-            solution: self,
-            var_idx: 0,
-        }
+    /// Iterate over the variables with values that differ in this solution and `other` by
+    /// more than `tol` (relative to the magnitude of the values as in [`approx_eq`]).
+    ///
+    /// Items are triples of the variable (a reference to a variable of this solution),
+    /// its value in this solution and its value in `other`.
+    ///
+    /// [`approx_eq`]: #method.approx_eq
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the solutions have different numbers of variables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// let other = solution.clone().fix_var(x, 0.5).unwrap();
+    /// let diffs: Vec<_> = solution.var_diffs(&other, 1e-9).collect();
+    /// assert_eq!(diffs, [(x, 1.0, 0.5)]);
+    /// ```
+    pub fn var_diffs<'a>(
+        &'a self,
+        other: &'a Solution,
+        tol: f64,
+    ) -> impl Iterator<Item = (Variable, f64, f64)> + 'a {
+        assert_eq!(
+            self.num_vars, other.num_vars,
+            "solutions have different numbers of variables"
+        );
+        (0..self.num_vars).filter_map(move |v| {
+            let val = *self.solver.get_value(v);
+            let other_val = *other.solver.get_value(v);
+            if is_approx_eq(val, other_val, tol) {
+                None
+            } else {
+                Some((self.var_ref(v), val, other_val))
+            }
+        })
     }
 
     /// Add another constraint and return the solution to the updated problem.
@@ -371,12 +2746,10 @@ impl Solution {
         cmp_op: ComparisonOp,
         rhs: f64,
     ) -> Result<Self, Error> {
-        let expr = expr.into();
-        self.solver.add_constraint(
-            CsVec::new(self.num_vars, expr.vars, expr.coeffs),
-            cmp_op,
-            rhs,
-        )?;
+        let (vars, coeffs) = expr.into().into_merged();
+        self.solver
+            .add_constraint(CsVec::new(self.num_vars, vars, coeffs), cmp_op, rhs)
+            .map_err(|err| err.in_generation(self.generation))?;
         Ok(self)
     }
 
@@ -388,8 +2761,10 @@ impl Solution {
     ///
     /// Will return an error if the problem becomes infeasible with the additional constraint.
     pub fn fix_var(mut self, var: Variable, val: f64) -> Result<Self, Error> {
-        assert!(var.0 < self.num_vars);
-        self.solver.fix_var(var.0, val)?;
+        let v = self.var_idx(var);
+        self.solver
+            .fix_var(v, val)
+            .map_err(|err| err.in_generation(self.generation))?;
         Ok(self)
     }
 
@@ -397,11 +2772,83 @@ impl Solution {
     /// and return the solution to the updated problem and a boolean indicating if the variable was
     /// really fixed before.
     pub fn unfix_var(mut self, var: Variable) -> (Self, bool) {
-        assert!(var.0 < self.num_vars);
-        let res = self.solver.unfix_var(var.0);
+        let res = self.solver.unfix_var(self.var_idx(var));
         (self, res)
     }
 
+    /// Run the cutting-plane loop starting from this solution (see
+    /// [`Problem::solve_with_separation`]) and return the final solution along with
+    /// the number of added cuts.
+    ///
+    /// This method will consume the solution and not return it in case of error.
+    ///
+    /// [`Problem::solve_with_separation`]: struct.Problem.html#method.solve_with_separation
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the problem becomes infeasible with the added cuts.
+    pub fn separate(
+        self,
+        separator: impl FnMut(&Solution) -> Vec<Cut>,
+    ) -> Result<(Self, usize), Error> {
+        cuts::separation_loop(self, separator)
+    }
+
+    /// Fix several variables to the specified values at once and return the solution to
+    /// the updated problem.
+    ///
+    /// This is equivalent to calling [`fix_var`](#method.fix_var) for each pair, but the
+    /// feasibility is restored by the dual simplex method only once, after all variables are
+    /// fixed. This method will consume the solution and not return it in case of error.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the problem becomes infeasible with the additional constraints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, 2.0));
+    /// let y = problem.add_var(2.0, (0.0, 3.0));
+    /// let z = problem.add_var(3.0, (0.0, 4.0));
+    /// problem.add_constraint(&[(x, 1.0), (y, 1.0), (z, 1.0)], ComparisonOp::Le, 5.0);
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 14.0);
+    ///
+    /// let solution = solution.fix_vars(&[(z, 1.0), (x, 2.0)]).unwrap();
+    /// assert_eq!(solution.objective(), 9.0);
+    ///
+    /// let (solution, num_unfixed) = solution.unfix_vars(&[x, y, z]);
+    /// assert_eq!(num_unfixed, 2);
+    /// assert_eq!(solution.objective(), 14.0);
+    /// ```
+    pub fn fix_vars(mut self, fixings: &[(Variable, f64)]) -> Result<Self, Error> {
+        let fixings = fixings
+            .iter()
+            .map(|&(var, val)| (self.var_idx(var), val))
+            .collect::<Vec<_>>();
+        self.solver
+            .fix_vars(&fixings)
+            .map_err(|err| err.in_generation(self.generation))?;
+        Ok(self)
+    }
+
+    /// Remove the fixings of several variables at once (see
+    /// [`unfix_var`](#method.unfix_var)) and return the solution to the updated problem and
+    /// the number of variables that were really fixed before.
+    ///
+    /// The solution is reoptimized only once, after all variables are unfixed.
+    pub fn unfix_vars(mut self, vars: &[Variable]) -> (Self, usize) {
+        let vars = vars
+            .iter()
+            .map(|&var| self.var_idx(var))
+            .collect::<Vec<_>>();
+        let num_unfixed = self.solver.unfix_vars(&vars);
+        (self, num_unfixed)
+    }
+
     // TODO: remove_constraint
 
     /// Add a [Gomory cut] constraint to the problem and return the solution.
@@ -417,10 +2864,92 @@ impl Solution {
     /// Will panic if the variable is not basic (variable is basic if it has value other than
     /// its bounds).
     pub fn add_gomory_cut(mut self, var: Variable) -> Result<Self, Error> {
-        assert!(var.0 < self.num_vars);
-        self.solver.add_gomory_cut(var.0)?;
+        let v = self.var_idx(var);
+        self.solver
+            .add_gomory_cut(v)
+            .map_err(|err| err.in_generation(self.generation))?;
         Ok(self)
     }
+
+    /// Generate a Gomory mixed-integer (GMI) cut from the row of the simplex tableau
+    /// corresponding to the variable.
+    ///
+    /// Unlike [`add_gomory_cut`](#method.add_gomory_cut), this cut is valid for problems in
+    /// which only some variables (`integer_vars`) must be integer. The cut is valid with
+    /// respect to the current variable bounds (including the variables fixed with
+    /// [`fix_var`](#method.fix_var)) and can be added to the solution directly or via a
+    /// [`CutPool`](struct.CutPool.html).
+    ///
+    /// Returns `None` if the variable is not basic, its value is almost integer or if the cut
+    /// is numerically unsafe (its coefficients differ too much in magnitude).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let mut problem = Problem::new(OptimizationDirection::Maximize);
+    /// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+    /// let y = problem.add_var(1.0, (0.0, 0.5));
+    /// problem.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+    ///
+    /// let solution = problem.solve().unwrap();
+    /// assert_eq!(solution[x], 1.75);
+    ///
+    /// // Only x must be integer.
+    /// // The cut is x + 2 * y <= 2, it makes the optimum integral.
+    /// let cut = solution.gmi_cut(x, &[x]).unwrap();
+    /// assert!(cut.violation(&solution) > 0.0);
+    /// let solution = solution.add_constraint(cut.lhs(), ComparisonOp::Le, cut.rhs()).unwrap();
+    /// assert!(f64::abs(solution[x] - 2.0) < 1e-8);
+    /// ```
+    pub fn gmi_cut(&self, var: Variable, integer_vars: &[Variable]) -> Option<Cut> {
+        let var = self.var_idx(var);
+        let mut is_integer = vec![false; self.num_vars];
+        for &v in integer_vars {
+            is_integer[self.var_idx(v)] = true;
+        }
+        let (coeffs, rhs) = self.solver.gmi_cut(var, |v| is_integer[v])?;
+        let expr: LinearExpr = coeffs
+            .into_iter()
+            .map(|(v, coeff)| (self.var_ref(v), coeff))
+            .collect();
+        Some(Cut::new(expr, ComparisonOp::Ge, rhs))
+    }
+}
+
+impl Solution {
+    /// Index of the variable, panics if the reference is not from this solution.
+    pub(crate) fn var_idx(&self, var: Variable) -> usize {
+        assert!(
+            var.0 < self.num_vars && var.1 == self.generation,
+            "variable {:?} is not in the solution",
+            var
+        );
+        var.0
+    }
+
+    /// Index of the constraint, panics if the reference is not from this solution.
+    pub(crate) fn constraint_idx(&self, constr: ConstraintRef) -> usize {
+        assert!(
+            constr.0 < self.solver.num_constraints() && constr.1 == self.generation,
+            "constraint {:?} is not in the solution",
+            constr
+        );
+        constr.0
+    }
+
+    pub(crate) fn var_ref(&self, var: usize) -> Variable {
+        Variable(var, self.generation)
+    }
+
+    pub(crate) fn constraint_ref(&self, constr: usize) -> ConstraintRef {
+        ConstraintRef(constr, self.generation)
+    }
+}
+
+/// Values differ by at most `tol` relative to their magnitude (absolute for small values).
+pub(crate) fn is_approx_eq(val: f64, other: f64, tol: f64) -> bool {
+    val == other || f64::abs(val - other) <= tol * f64::max(1.0, f64::max(val.abs(), other.abs()))
 }
 
 impl std::ops::Index<Variable> for Solution {
@@ -431,6 +2960,34 @@ impl std::ops::Index<Variable> for Solution {
     }
 }
 
+/// A variable of the basis: either a problem variable or a slack variable of a constraint
+/// (its value is the difference between the right-hand and the left-hand sides).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BasisVar {
+    /// A problem variable.
+    Var(Variable),
+    /// A slack variable of the constraint.
+    Slack(ConstraintRef),
+}
+
+/// A result of the right-hand side sensitivity analysis for a single constraint.
+/// See [`Solution::rhs_range`](struct.Solution.html#method.rhs_range).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RhsRange {
+    /// Dual value of the constraint which remains constant inside the range.
+    pub dual_value: f64,
+    /// Minimal rhs value for which the current basis remains optimal.
+    pub min: f64,
+    /// Maximal rhs value for which the current basis remains optimal.
+    pub max: f64,
+    /// Basic variable that reaches its bound and must leave the basis when the rhs decreases
+    /// past `min` (`None` if `min` is infinite).
+    pub leaving_at_min: Option<BasisVar>,
+    /// Basic variable that reaches its bound and must leave the basis when the rhs increases
+    /// past `max` (`None` if `max` is infinite).
+    pub leaving_at_max: Option<BasisVar>,
+}
+
 /// An iterator over the variable-value pairs of a [`Solution`].
 #[derive(Debug, Clone)]
 pub struct SolutionIter<'a> {
@@ -445,7 +3002,10 @@ impl<'a> Iterator for SolutionIter<'a> {
         if self.var_idx < self.solution.num_vars {
             let var_idx = self.var_idx;
             self.var_idx += 1;
-            Some((Variable(var_idx), self.solution.solver.get_value(var_idx)))
+            Some((
+                self.solution.var_ref(var_idx),
+                self.solution.solver.get_value(var_idx),
+            ))
         } else {
             None
         }
@@ -461,9 +3021,45 @@ impl<'a> IntoIterator for &'a Solution {
     }
 }
 
-pub use mps::MpsFile;
+pub use cache::{CacheBackend, DirCacheBackend, SolveCache};
+pub use compact::Remapping;
+pub use corpus::{Corpus, CorpusInstance};
+pub use cuts::{Cut, CutPool};
+pub use diff::{ProblemDiff, ProblemDifference};
+pub use display::SolutionDisplay;
+pub use diving::DivingRule;
+pub use error::{Error, ErrorKind, ModelError, Phase};
+pub use face::OptimalFace;
+pub use fingerprint::Fingerprint;
+pub use handle::{PartialResult, SolveHandle};
+pub use json_format::JsonFile;
+pub use lagrangian::{
+    LagrangianBound, LagrangianRelaxation, SubgradientOptions, SubgradientResult,
+};
+pub use lp_format::LpFile;
+#[cfg(all(feature = "mmap", unix))]
+pub use mapped::MappedProblem;
+pub use matrix_free::MatrixFreeProblem;
+pub use mps::{MpsFile, MpsFormat};
+pub use obbt::BoundTighteningOptions;
+pub use options::{
+    Accuracy, Algorithm, AntiDegeneracy, BasisOrdering, Dualization, Pricing, ScalingMode,
+    SolverOptions,
+};
+pub use ordering::{DmBlock, DmDecomposition};
+pub use pdlp::{FirstOrderOptions, FirstOrderSolution};
+pub use progress::{Progress, ProgressCallback};
+pub use report::{SolveReport, SolveStatus};
+pub use robust::UncertaintySet;
+pub use smps::{DeterministicEquivalent, Scenario, ScenarioChange, SmpsFile};
+pub use solve_log::SolveLog;
+pub use sparse::{IndexType, Perm, ScatteredVec, SparseVec};
+pub use stats::{ProblemStats, SolverStats, SolverTimings};
+pub use tags::Tag;
+pub use validate::{ValidationReport, ValidationWarning};
 
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args)]
 mod tests {
     use super::*;
 
@@ -512,17 +3108,25 @@ mod tests {
 
         for (expr, op, b) in infeasible.iter().cloned() {
             let mut cloned = problem.clone();
-            cloned.add_constraint(expr, op, b);
-            assert_eq!(cloned.solve().map(|_| "solved"), Err(Error::Infeasible));
+            let constr = cloned.add_constraint(expr, op, b);
+            let err = cloned.solve().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Infeasible);
+            assert_eq!(err.phase(), Phase::Setup);
+            assert_eq!(err.constraint(), Some(constr));
+            assert_eq!(err.violation(), Some(b.abs()));
         }
 
         for (expr, op, b) in infeasible.iter().cloned() {
             let sol = problem.solve().unwrap().add_constraint(expr, op, b);
-            assert_eq!(sol.map(|_| "solved"), Err(Error::Infeasible));
+            let err = sol.map(|_| "solved").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Infeasible);
+            assert_eq!(err.violation(), Some(b.abs()));
         }
 
-        let _ = problem.add_var(-1.0, (0.0, f64::INFINITY));
-        assert_eq!(problem.solve().map(|_| "solved"), Err(Error::Unbounded));
+        let v = problem.add_var(-1.0, (0.0, f64::INFINITY));
+        let err = problem.solve().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unbounded);
+        assert_eq!(err.variable(), Some(v));
     }
 
     #[test]
@@ -575,6 +3179,195 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fix_unfix_vars() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let vars = (0..6)
+            .map(|i| problem.add_var(1.0 + i as f64, (0.0, 4.0)))
+            .collect::<Vec<_>>();
+        for i in 0..5 {
+            let terms = [(vars[i], 1.0), (vars[i + 1], 2.0)];
+            problem.add_constraint(&terms, ComparisonOp::Ge, 3.0 + i as f64);
+        }
+        let orig_sol = problem.solve().unwrap();
+
+        let fixings = [(vars[1], 0.0), (vars[4], 3.0), (vars[2], 2.0)];
+        let batch_sol = orig_sol.clone().fix_vars(&fixings).unwrap();
+        let mut seq_sol = orig_sol.clone();
+        for &(var, val) in &fixings {
+            seq_sol = seq_sol.fix_var(var, val).unwrap();
+        }
+        assert!(f64::abs(batch_sol.objective() - seq_sol.objective()) < 1e-8);
+        for &(var, val) in &fixings {
+            assert_eq!(batch_sol[var], val);
+        }
+
+        let (sol, num_unfixed) = batch_sol.unfix_vars(&[vars[0], vars[1], vars[2], vars[4]]);
+        assert_eq!(num_unfixed, 3);
+        assert!(f64::abs(sol.objective() - orig_sol.objective()) < 1e-8);
+
+        let infeasible = [(vars[0], 0.0), (vars[1], 1.0)];
+        assert_eq!(
+            orig_sol
+                .fix_vars(&infeasible)
+                .map_err(|err| err.kind())
+                .err(),
+            Some(ErrorKind::Infeasible)
+        );
+    }
+
+    #[test]
+    fn substitute_var() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(3.0, (12.0, f64::INFINITY));
+        let y = problem.add_var(4.0, (5.0, f64::INFINITY));
+        problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 20.0);
+        problem.add_constraint(&[(y, -4.0), (x, 1.0)], ComparisonOp::Ge, -20.0);
+
+        // y = -2 * z + 8 and then z = 2 * w + 1, so y = -4 * w + 6.
+        problem.substitute_var(y, -2.0, 8.0);
+        problem.substitute_var(y, 2.0, 1.0);
+        assert_eq!(problem.var_transform(y), (-4.0, 6.0));
+        assert_eq!(problem.var_transform(x), (1.0, 0.0));
+        assert_eq!(problem.var_mins[y.0], f64::NEG_INFINITY);
+        assert_eq!(problem.var_maxs[y.0], 0.25);
+
+        let sol = problem.solve().unwrap();
+        assert_eq!(sol[x], 12.0);
+        assert_eq!(sol[y], -0.5);
+        assert_eq!(sol.original_var_value(&problem, y), 8.0);
+        assert_eq!(sol.objective(), 68.0);
+    }
+
+    #[test]
+    fn eliminate_fixed() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(-1.0, (2.0, 2.0));
+        let z = problem.add_var(0.0, (-1.0, -1.0));
+        let w = problem.add_var(5.0, (3.0, 3.0));
+        let c1 = problem.add_constraint(&[(x, 1.0), (y, 1.0), (z, 2.0)], ComparisonOp::Ge, 3.0);
+        let c2 = problem.add_constraint(&[(y, 1.0), (z, 1.0)], ComparisonOp::Le, 1.0);
+        problem.remove_var(w).unwrap();
+
+        assert_eq!(problem.eliminate_fixed(), 2);
+        assert_eq!(problem.eliminate_fixed(), 0);
+        assert_eq!(problem.constraint_terms(c1).collect::<Vec<_>>(), [(x, 1.0)]);
+        assert_eq!(problem.constraints[c1.0].2, 3.0);
+        assert_eq!(problem.constraint_terms(c2).count(), 0);
+        assert_eq!(problem.constraints[c2.0].2, 0.0);
+        assert_eq!(problem.objective_offset(), -2.0);
+
+        let sol = problem.solve().unwrap();
+        assert_eq!((sol[x], sol[y], sol[z]), (3.0, 2.0, -1.0));
+        assert_eq!(sol.objective(), 1.0);
+
+        // Constraints left without terms are still checked.
+        let c3 = problem.add_constraint(&[(y, 1.0)], ComparisonOp::Ge, 3.0);
+        problem.eliminate_fixed();
+        assert_eq!(problem.constraints[c3.0].2, 1.0);
+        assert_eq!(problem.solve().unwrap_err().kind(), ErrorKind::Infeasible);
+    }
+
+    #[test]
+    fn objective_cutoff() {
+        let solve = |problem: &Problem, cutoff| {
+            problem.solve_with(&SolverOptions {
+                objective_cutoff: Some(cutoff),
+                ..Default::default()
+            })
+        };
+
+        // Solved by the dual simplex.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(3.0, (0.0, 2.0));
+        let y = problem.add_var(2.0, (0.0, 2.0));
+        problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 3.0);
+        problem.set_objective_offset(1.0);
+        assert_eq!(problem.solve().unwrap().objective(), 9.0);
+        assert_eq!(solve(&problem, 8.5).unwrap().objective(), 9.0);
+        let err = solve(&problem, 9.0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Cutoff);
+        assert_eq!(
+            err.to_string(),
+            "objective cutoff reached (detected in dual simplex): there is no solution better \
+             than the objective cutoff, remove the cutoff to find the optimal solution"
+        );
+
+        // Solved by the primal simplex.
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let vars: Vec<_> = (0..30)
+            .map(|i| problem.add_var(-((1 + i % 5) as f64), (0.0, f64::INFINITY)))
+            .collect();
+        for i in 0..30 {
+            let expr: LinearExpr = (0..3)
+                .map(|j| (vars[(i + 4 * j) % 30], (1 + (i + j) % 3) as f64))
+                .collect();
+            problem.add_constraint(expr, ComparisonOp::Le, 10.0);
+        }
+        let optimal = problem.solve().unwrap();
+        let cutoff = optimal.objective() + 1.0;
+        let sol = solve(&problem, cutoff).unwrap();
+        assert!(f64::abs(sol.objective() - optimal.objective()) < 1e-8);
+        let err = solve(&problem, optimal.objective() - 1.0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Cutoff);
+        assert_eq!(err.phase(), Phase::PrimalSimplex);
+
+        // Reoptimization of a solution.
+        let mut sol = optimal.clone();
+        sol.set_objective_cutoff(Some(optimal.objective() + 10.0));
+        let constr = LinearExpr::sum(vars.iter().copied());
+        let err = sol
+            .add_constraint(constr, ComparisonOp::Le, 1.0)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Cutoff);
+        let mut sol = optimal;
+        sol.set_objective_cutoff(None);
+        let sol = sol.fix_var(vars[0], 0.0).unwrap();
+        let (sol, num_unfixed) = sol.unfix_vars(&[vars[0]]);
+        assert_eq!(num_unfixed, 1);
+        assert!(sol.objective() < cutoff);
+    }
+
+    #[test]
+    fn strictness() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint(&[(y, 1.0), (x, 2.0), (y, -3.0)], ComparisonOp::Ge, 1.0);
+        problem.add_constraint(&[(x, 0.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint(LinearExpr::empty(), ComparisonOp::Le, 1.0);
+        let (coeffs, _, _) = &problem.constraints[0];
+        assert_eq!(coeffs.indices(), &[0, 1]);
+        assert_eq!(coeffs.data(), &[2.0, -2.0]);
+
+        problem.set_strictness(Strictness::Strict);
+        assert_eq!(problem.strictness(), Strictness::Strict);
+        let constr = ConstraintRef(3, 0);
+        assert_eq!(
+            problem.try_add_constraint(LinearExpr::empty(), ComparisonOp::Le, 1.0),
+            Err(ModelError::EmptyConstraint { constr })
+        );
+        assert_eq!(
+            problem.try_add_constraint(
+                &[(y, 1.0), (x, 1.0), (y, 2.0), (x, 0.5)],
+                ComparisonOp::Le,
+                1.0
+            ),
+            Err(ModelError::DuplicateVar {
+                constr,
+                var: y,
+                first_position: 0,
+                position: 2,
+            })
+        );
+        assert_eq!(problem.stats().num_constraints, 3);
+        assert_eq!(
+            problem.try_add_constraint(&[(y, 1.0), (x, 1.0)], ComparisonOp::Le, 1.0),
+            Ok(constr)
+        );
+    }
+
     #[test]
     fn add_constraint() {
         let mut problem = Problem::new(OptimizationDirection::Minimize);
@@ -618,6 +3411,118 @@ mod tests {
             assert_eq!(sol[v2], 3.0);
             assert_eq!(sol.objective(), 3.0);
         }
+
+        {
+            // Repeated terms are summed up: v2 - v1 <= 0
+            let sol = orig_sol
+                .clone()
+                .add_constraint(
+                    &[(v1, -0.5), (v2, 2.0), (v1, -0.5), (v2, -1.0)],
+                    ComparisonOp::Le,
+                    0.0,
+                )
+                .unwrap();
+
+            assert_eq!(sol[v1], 1.0);
+            assert_eq!(sol[v2], 1.0);
+            assert_eq!(sol.objective(), 3.0);
+        }
+    }
+
+    #[test]
+    fn rhs_ranging() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let v1 = problem.add_var(2.0, (0.0, f64::INFINITY));
+        let v2 = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let c1 = problem.add_constraint(&[(v1, 1.0), (v2, 1.0)], ComparisonOp::Ge, 2.0);
+        let c2 = problem.add_constraint(&[(v1, 1.0), (v2, -1.0)], ComparisonOp::Ge, -1.0);
+        let c3 = problem.add_constraint(&[(v1, 1.0), (v2, 1.0)], ComparisonOp::Le, 10.0);
+
+        let sol = problem.solve().unwrap();
+        assert_eq!(sol[v1], 0.5);
+        assert_eq!(sol[v2], 1.5);
+        assert_eq!(sol.dual_value(c1), 1.5);
+        assert_eq!(sol.dual_value(c2), 0.5);
+        assert_eq!(sol.dual_value(c3), 0.0);
+
+        let range = sol.rhs_range(c1);
+        assert_eq!((range.min, range.max), (1.0, 10.0));
+        assert_eq!(range.leaving_at_min, Some(BasisVar::Var(v1)));
+        assert_eq!(range.leaving_at_max, Some(BasisVar::Slack(c3)));
+
+        let range = sol.rhs_range(c3);
+        assert_eq!(range.dual_value, 0.0);
+        assert_eq!((range.min, range.max), (2.0, f64::INFINITY));
+        assert_eq!(range.leaving_at_min, Some(BasisVar::Slack(c3)));
+        assert_eq!(range.leaving_at_max, None);
+    }
+
+    #[test]
+    fn scaling() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let v1 = problem.add_var(2e3, (0.0, f64::INFINITY));
+        let v2 = problem.add_var(1e-3, (0.0, f64::INFINITY));
+        let c1 = problem.add_constraint(&[(v1, 1e3), (v2, 1e-3)], ComparisonOp::Ge, 2.0);
+        let c2 = problem.add_constraint(&[(v1, 1e3), (v2, -1e-3)], ComparisonOp::Ge, -1.0);
+        let c3 = problem.add_constraint(&[(v1, 1e3), (v2, 1e-3)], ComparisonOp::Le, 10.0);
+
+        let reference = problem.solve().unwrap();
+        for &mode in &[
+            ScalingMode::Equilibration,
+            ScalingMode::GeometricMean,
+            ScalingMode::GeometricMeanEquilibration,
+        ] {
+            let options = SolverOptions {
+                scaling: mode,
+                ..SolverOptions::default()
+            };
+            let sol = problem.solve_with(&options).unwrap();
+            assert!(f64::abs(sol.objective() - reference.objective()) < 1e-8);
+            assert!(f64::abs(sol[v1] - 0.5e-3) < 1e-12);
+            assert!(f64::abs(sol[v2] - 1.5e3) < 1e-8);
+            for &c in &[c1, c2, c3] {
+                assert!(f64::abs(sol.dual_value(c) - reference.dual_value(c)) < 1e-8);
+            }
+            let range = sol.rhs_range(c1);
+            assert!(f64::abs(range.min - 1.0) < 1e-8);
+            assert!(f64::abs(range.max - 10.0) < 1e-8);
+
+            let sol = sol
+                .add_constraint(&[(v2, 1.0)], ComparisonOp::Le, 1e3)
+                .unwrap();
+            assert!(f64::abs(sol[v1] - 1e-3) < 1e-12);
+            assert!(f64::abs(sol[v2] - 1e3) < 1e-8);
+
+            let sol = sol.fix_var(v2, 500.0).unwrap();
+            assert!(f64::abs(sol[v1] - 1.5e-3) < 1e-12);
+            assert_eq!(sol[v2], 500.0);
+        }
+    }
+
+    #[test]
+    fn alternative_optima() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let v1 = problem.add_var(1.0, (0.0, 3.0));
+        let v2 = problem.add_var(1.0, (0.0, 3.0));
+        let v3 = problem.add_var(0.0, (0.0, 1.0));
+        problem.add_constraint(&[(v1, 1.0), (v2, 1.0), (v3, 1.0)], ComparisonOp::Le, 4.0);
+
+        let sol = problem.solve().unwrap();
+        assert!(sol.has_alternative_optima());
+
+        let mut vertices = vec![(sol[v1], sol[v2], sol[v3])];
+        for other in sol.alternative_optima(10) {
+            assert_eq!(other.objective(), 4.0);
+            vertices.push((other[v1], other[v2], other[v3]));
+        }
+        vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(vertices, vec![(1.0, 3.0, 0.0), (3.0, 1.0, 0.0)]);
+
+        assert_eq!(sol.alternative_optima(0).len(), 0);
+
+        let unique = sol.fix_var(v2, 3.0).unwrap();
+        assert!(!unique.has_alternative_optima());
+        assert!(unique.alternative_optima(10).is_empty());
     }
 
     #[test]
@@ -643,4 +3548,80 @@ mod tests {
         assert_eq!(sol[v2], 1.0);
         assert_eq!(sol.objective(), -1.0);
     }
+
+    #[test]
+    fn gmi_cut() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x1 = problem.add_var(2.0, (0.0, 5.0));
+        let x2 = problem.add_var(3.0, (0.0, 5.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+        problem.add_constraint(&[(x1, 3.0), (x2, 4.0), (y, 1.0)], ComparisonOp::Le, 13.5);
+        problem.add_constraint(&[(x1, 1.0), (x2, -1.0), (y, 0.5)], ComparisonOp::Le, 2.2);
+        problem.add_constraint(&[(x1, 1.0), (x2, 1.0)], ComparisonOp::Ge, 1.5);
+        let int_vars = [x1, x2];
+
+        // Best objective for each assignment of integer vars.
+        let best_objectives = |problem: &Problem| {
+            let mut res = vec![];
+            for v1 in 0..=5 {
+                for v2 in 0..=5 {
+                    let mut fixed = problem.clone();
+                    fixed.add_constraint(&[(x1, 1.0)], ComparisonOp::Eq, v1 as f64);
+                    fixed.add_constraint(&[(x2, 1.0)], ComparisonOp::Eq, v2 as f64);
+                    res.push(fixed.solve().map(|sol| sol.objective()).ok());
+                }
+            }
+            res
+        };
+        let reference = best_objectives(&problem);
+
+        for &scaling in &[ScalingMode::None, ScalingMode::GeometricMeanEquilibration] {
+            let options = SolverOptions {
+                scaling,
+                ..SolverOptions::default()
+            };
+            let mut problem = problem.clone();
+            let mut num_cuts = 0;
+            for _ in 0..5 {
+                let sol = problem.solve_with(&options).unwrap();
+                let cut = int_vars.iter().find_map(|&v| sol.gmi_cut(v, &int_vars));
+                let cut = if let Some(cut) = cut { cut } else { break };
+                assert!(cut.violation(&sol) > 1e-6);
+                problem.add_constraint(cut.lhs(), ComparisonOp::Le, cut.rhs());
+                num_cuts += 1;
+
+                // Cuts don't remove mixed-integer solutions.
+                for (with_cut, orig) in best_objectives(&problem).iter().zip(&reference) {
+                    match (with_cut, orig) {
+                        (Some(a), Some(b)) => assert!(f64::abs(a - b) < 1e-6),
+                        (None, None) => {}
+                        _ => panic!("cut changed feasibility"),
+                    }
+                }
+            }
+            assert!(num_cuts > 0);
+        }
+    }
+
+    #[test]
+    fn approx_eq() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (1e6, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, 1.0));
+        let solution = problem.solve().unwrap();
+        assert!(solution.approx_eq(&solution, 0.0));
+
+        // The tolerance is relative for big values and absolute for small ones.
+        let shifted = solution.clone().fix_var(x, 1e6 + 0.5).unwrap();
+        assert!(shifted.approx_eq(&solution, 1e-6));
+        assert!(!shifted.approx_eq(&solution, 1e-7));
+        let shifted = solution.clone().fix_var(y, 1e-7).unwrap();
+        assert!(shifted.approx_eq(&solution, 1e-6));
+        let diffs: Vec<_> = shifted.var_diffs(&solution, 1e-8).collect();
+        assert_eq!(diffs, [(y, 1e-7, 0.0)]);
+
+        problem.add_var(0.0, (0.0, 1.0));
+        let bigger = problem.solve().unwrap();
+        assert!(!bigger.approx_eq(&solution, 1.0));
+    }
 }
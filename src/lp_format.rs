@@ -0,0 +1,640 @@
+use crate::{ComparisonOp, LinearExpr, OptimizationDirection, Problem, Variable};
+use std::{collections::HashMap, fmt, io};
+
+/// Write the terms of a linear expression, e.g. `x0 - 2 x1 + 0.5 x2`.
+fn write_expr(
+    f: &mut fmt::Formatter,
+    problem: &Problem,
+    terms: impl Iterator<Item = (usize, f64)>,
+) -> fmt::Result {
+    let mut is_first = true;
+    for (var, coeff) in terms {
+        if coeff == 0.0 {
+            continue;
+        }
+        let sign = if coeff < 0.0 { "-" } else { "+" };
+        match (is_first, sign) {
+            (true, "+") => {}
+            (true, _) => write!(f, "-")?,
+            (false, _) => write!(f, " {} ", sign)?,
+        }
+        if coeff.abs() != 1.0 {
+            write!(f, "{} ", coeff.abs())?;
+        }
+        write!(f, "{}", problem.var_name_at(var))?;
+        is_first = false;
+    }
+    if is_first {
+        write!(f, "0")?;
+    }
+    Ok(())
+}
+
+/// Write the problem in the CPLEX LP format.
+pub(crate) fn write_lp(problem: &Problem, f: &mut fmt::Formatter) -> fmt::Result {
+    let obj_sign = match problem.direction {
+        OptimizationDirection::Minimize => {
+            writeln!(f, "Minimize")?;
+            1.0
+        }
+        OptimizationDirection::Maximize => {
+            writeln!(f, "Maximize")?;
+            -1.0
+        }
+    };
+    write!(f, " obj: ")?;
+    let obj_terms = problem.obj_coeffs.iter().map(|&c| obj_sign * c).enumerate();
+    write_expr(f, problem, obj_terms)?;
+    let obj_offset = problem.objective_offset();
+    if obj_offset != 0.0 {
+        let sign = if obj_offset < 0.0 { "-" } else { "+" };
+        write!(f, " {} {}", sign, obj_offset.abs())?;
+    }
+    writeln!(f)?;
+
+    writeln!(f, "Subject To")?;
+    for (i, (coeffs, cmp_op, rhs)) in problem.constraints.iter().enumerate() {
+        write!(f, " c{}: ", i)?;
+        write_expr(f, problem, coeffs.iter().map(|(v, &c)| (v, c)))?;
+        let op = match cmp_op {
+            ComparisonOp::Eq => "=",
+            ComparisonOp::Le => "<=",
+            ComparisonOp::Ge => ">=",
+        };
+        writeln!(f, " {} {}", op, rhs)?;
+    }
+
+    let mut has_bounds = false;
+    for (v, (&min, &max)) in problem.var_mins.iter().zip(&problem.var_maxs).enumerate() {
+        if min == 0.0 && max == f64::INFINITY {
+            continue;
+        }
+        if !has_bounds {
+            writeln!(f, "Bounds")?;
+            has_bounds = true;
+        }
+        let name = problem.var_name_at(v);
+        match (min.is_finite(), max.is_finite()) {
+            _ if min == max => writeln!(f, " {} = {}", name, min)?,
+            (false, false) => writeln!(f, " {} free", name)?,
+            (true, false) => writeln!(f, " {} >= {}", name, min)?,
+            (false, true) => writeln!(f, " -inf <= {} <= {}", name, max)?,
+            (true, true) => writeln!(f, " {} <= {} <= {}", min, name, max)?,
+        }
+    }
+    writeln!(f, "End")
+}
+
+/// A linear programming problem parsed from a file in the CPLEX LP format.
+#[derive(Clone)]
+pub struct LpFile {
+    /// A mapping of a variable name to the corresponding [`Variable`].
+    pub variables: HashMap<String, Variable>,
+    /// A parsed problem.
+    pub problem: Problem,
+}
+
+impl std::fmt::Debug for LpFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LpFile")
+            .field("problem", &self.problem)
+            .finish()
+    }
+}
+
+impl LpFile {
+    /// Parses a linear programming problem from a file in the CPLEX LP format.
+    ///
+    /// The sections of the objective (`Minimize` or `Maximize`), the constraints
+    /// (`Subject To`) and the variable bounds (`Bounds`) are supported, which is enough to
+    /// read the output of the [`Display`](struct.Problem.html#impl-Display-for-Problem)
+    /// implementation of `Problem`. Names of the objective and the constraints are ignored.
+    /// Variables are added to the problem in the order of their first appearance. A constant
+    /// term in the objective becomes the
+    /// [objective offset](struct.Problem.html#method.set_objective_offset).
+    ///
+    /// # Errors
+    ///
+    /// Apart from I/O errors coming from `input`, this function will signal any syntax error
+    /// as [`std::io::Error`] with the kind set to [`InvalidData`](std::io::ErrorKind::InvalidData).
+    /// Unsupported features such as integer variables are reported similarly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let input = "\
+    /// Maximize
+    ///  obj: x + 2 y
+    /// Subject To
+    ///  c0: x + y <= 4
+    ///  c1: 2 x + y >= 2
+    /// Bounds
+    ///  y <= 3
+    /// End
+    /// ";
+    /// let file = LpFile::parse(input.as_bytes()).unwrap();
+    /// let solution = file.problem.solve().unwrap();
+    /// assert_eq!(solution.objective(), 7.0);
+    /// assert_eq!(solution[file.variables["y"]], 3.0);
+    /// ```
+    pub fn parse<R: io::BufRead>(input: R) -> io::Result<Self> {
+        // Format description:
+        // https://www.ibm.com/docs/en/icos/latest?topic=cplex-lp-file-format-algebraic-representation
+
+        let mut tokens = vec![];
+        for (idx, line) in input.lines().enumerate() {
+            tokenize(&line?, idx + 1, &mut tokens)?;
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            var_name2idx: HashMap::new(),
+            var_names: vec![],
+        };
+
+        let direction = match parser.section() {
+            Some(Section::Objective(direction)) => direction,
+            _ => return Err(parser.err_at(0, "expected objective section")),
+        };
+        parser.skip_label();
+        let (obj_terms, obj_offset) = parser.expr()?;
+        if !obj_offset.is_finite() {
+            return Err(parser.err("invalid constant term in the objective"));
+        }
+
+        let mut constraints = vec![];
+        let mut bounds = vec![];
+        loop {
+            let start = parser.pos;
+            match parser.section() {
+                Some(Section::Constraints) => {
+                    while parser.pos < parser.tokens.len() && !parser.at_section() {
+                        parser.skip_label();
+                        let (terms, constant) = parser.expr()?;
+                        let cmp_op = parser.cmp_op()?;
+                        let rhs = parser.value()?;
+                        constraints.push((terms, cmp_op, rhs - constant));
+                    }
+                }
+                Some(Section::Bounds) => {
+                    while parser.pos < parser.tokens.len() && !parser.at_section() {
+                        parser.bound(&mut bounds)?;
+                    }
+                }
+                Some(Section::Integers) => {
+                    return Err(parser.err_at(start, "integer variables are not supported"));
+                }
+                Some(Section::Objective(_)) => {
+                    return Err(parser.err_at(start, "objective section already declared"));
+                }
+                Some(Section::End) => break,
+                None if parser.pos == parser.tokens.len() => break,
+                None => return Err(parser.err("unexpected token")),
+            }
+        }
+
+        let mut var_defs = vec![(0.0, (0.0, f64::INFINITY)); parser.var_names.len()];
+        for (var, coeff) in obj_terms {
+            var_defs[var].0 = coeff;
+        }
+        for (var, bound, val) in bounds {
+            let (min, max) = &mut var_defs[var].1;
+            match bound {
+                ComparisonOp::Ge => *min = val,
+                ComparisonOp::Le => *max = val,
+                ComparisonOp::Eq => {
+                    *min = val;
+                    *max = val;
+                }
+            }
+        }
+
+        let mut problem = Problem::new(direction);
+        problem.set_objective_offset(obj_offset);
+        for (name, (obj_coeff, bounds)) in parser.var_names.into_iter().zip(var_defs) {
+            let var = problem.try_add_var(obj_coeff, bounds)?;
+            problem.set_var_name(var, name);
+        }
+        for (terms, cmp_op, rhs) in constraints {
+            let terms: LinearExpr = terms
+                .into_iter()
+                .map(|(var, coeff)| (problem.var_ref(var), coeff))
+                .collect();
+            problem.try_add_constraint(terms, cmp_op, rhs)?;
+        }
+
+        let variables = parser
+            .var_name2idx
+            .into_iter()
+            .map(|(name, var)| (name, problem.var_ref(var)))
+            .collect();
+        Ok(LpFile { variables, problem })
+    }
+}
+
+enum Token {
+    Num(f64),
+    Name(String),
+    Op(ComparisonOp),
+    Colon,
+    Plus,
+    Minus,
+}
+
+struct TokenPos {
+    token: Token,
+    line_idx: usize,
+    is_line_start: bool,
+}
+
+fn tokenize(line: &str, line_idx: usize, tokens: &mut Vec<TokenPos>) -> io::Result<()> {
+    // Backslash starts a comment.
+    let line = line.split('\\').next().unwrap();
+    let mut is_line_start = true;
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let mut chars = rest.chars();
+        let first = chars.next().unwrap();
+        let second = chars.next();
+        let (token, len) = match (first, second) {
+            ('+', _) => (Token::Plus, 1),
+            ('-', _) => (Token::Minus, 1),
+            (':', _) => (Token::Colon, 1),
+            ('<', Some('=')) | ('=', Some('<')) => (Token::Op(ComparisonOp::Le), 2),
+            ('>', Some('=')) | ('=', Some('>')) => (Token::Op(ComparisonOp::Ge), 2),
+            ('<', _) => (Token::Op(ComparisonOp::Le), 1),
+            ('>', _) => (Token::Op(ComparisonOp::Ge), 1),
+            ('=', _) => (Token::Op(ComparisonOp::Eq), 1),
+            (c, _) if c.is_ascii_digit() || c == '.' => {
+                let bytes = rest.as_bytes();
+                let mut len = 0;
+                while len < bytes.len() && (bytes[len].is_ascii_digit() || bytes[len] == b'.') {
+                    len += 1;
+                }
+                // Exponent (but not a variable name starting with e, as in "2 e").
+                if len < bytes.len() && (bytes[len] == b'e' || bytes[len] == b'E') {
+                    let mut exp_len = len + 1;
+                    if exp_len < bytes.len() && (bytes[exp_len] == b'+' || bytes[exp_len] == b'-') {
+                        exp_len += 1;
+                    }
+                    if exp_len < bytes.len() && bytes[exp_len].is_ascii_digit() {
+                        len = exp_len;
+                        while len < bytes.len() && bytes[len].is_ascii_digit() {
+                            len += 1;
+                        }
+                    }
+                }
+                let val = rest[..len].parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "line {}: couldn't parse float from string: `{}`",
+                            line_idx,
+                            &rest[..len]
+                        ),
+                    )
+                })?;
+                (Token::Num(val), len)
+            }
+            _ => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || "+-<>=:".contains(c))
+                    .unwrap_or(rest.len());
+                (Token::Name(rest[..len].to_owned()), len)
+            }
+        };
+        tokens.push(TokenPos {
+            token,
+            line_idx,
+            is_line_start,
+        });
+        is_line_start = false;
+        rest = rest[len..].trim_start();
+    }
+    Ok(())
+}
+
+enum Section {
+    Objective(OptimizationDirection),
+    Constraints,
+    Bounds,
+    Integers,
+    End,
+}
+
+struct Parser {
+    tokens: Vec<TokenPos>,
+    pos: usize,
+    var_name2idx: HashMap<String, usize>,
+    var_names: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|t| &t.token)
+    }
+
+    fn err(&self, msg: &str) -> io::Error {
+        self.err_at(self.pos, msg)
+    }
+
+    fn err_at(&self, pos: usize, msg: &str) -> io::Error {
+        let msg = match self.tokens.get(pos) {
+            Some(token) => format!("line {}: {}", token.line_idx, msg),
+            None => format!("end of file: {}", msg),
+        };
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    /// Section that starts at the current position (if any) and the number of its tokens.
+    fn section_at(&self) -> Option<(Section, usize)> {
+        let token = self.tokens.get(self.pos)?;
+        let keyword = match &token.token {
+            Token::Name(name) if token.is_line_start => name.to_lowercase(),
+            _ => return None,
+        };
+        // A keyword followed by a colon is a label.
+        if let Some(Token::Colon) = self.peek(1) {
+            return None;
+        }
+        let next = match self.peek(1) {
+            Some(Token::Name(name)) => name.to_lowercase(),
+            _ => String::new(),
+        };
+        let section = match (keyword.as_str(), next.as_str()) {
+            ("minimize" | "minimise" | "minimum" | "min", _) => {
+                (Section::Objective(OptimizationDirection::Minimize), 1)
+            }
+            ("maximize" | "maximise" | "maximum" | "max", _) => {
+                (Section::Objective(OptimizationDirection::Maximize), 1)
+            }
+            ("subject", "to") | ("such", "that") => (Section::Constraints, 2),
+            ("st" | "s.t.", _) => (Section::Constraints, 1),
+            ("bounds" | "bound", _) => (Section::Bounds, 1),
+            ("general" | "generals" | "gen" | "integer" | "integers", _)
+            | ("binary" | "binaries" | "bin", _)
+            | ("semi-continuous" | "semis" | "semi", _) => (Section::Integers, 1),
+            ("end", _) => (Section::End, 1),
+            _ => return None,
+        };
+        Some(section)
+    }
+
+    fn at_section(&self) -> bool {
+        self.section_at().is_some()
+    }
+
+    /// Consume the header of the section starting at the current position.
+    fn section(&mut self) -> Option<Section> {
+        let (section, len) = self.section_at()?;
+        self.pos += len;
+        Some(section)
+    }
+
+    /// Skip the `name:` label of the objective or a constraint.
+    fn skip_label(&mut self) {
+        if let (Some(Token::Name(_)), Some(Token::Colon)) = (self.peek(0), self.peek(1)) {
+            self.pos += 2;
+        }
+    }
+
+    fn var(&mut self, name: &str) -> usize {
+        if let Some(&var) = self.var_name2idx.get(name) {
+            return var;
+        }
+        let var = self.var_names.len();
+        self.var_name2idx.insert(name.to_owned(), var);
+        self.var_names.push(name.to_owned());
+        var
+    }
+
+    /// Parse a sum of terms up to a comparison operator or a section header. Returns
+    /// the coefficients of the variables (repeated variables are merged) and the sum
+    /// of the constant terms.
+    fn expr(&mut self) -> io::Result<(Vec<(usize, f64)>, f64)> {
+        let mut terms: Vec<(usize, f64)> = vec![];
+        let mut constant = 0.0;
+        loop {
+            match self.peek(0) {
+                Some(Token::Plus) | Some(Token::Minus) | Some(Token::Num(_)) => {}
+                Some(Token::Name(_)) if !self.at_section() => {}
+                _ => break,
+            }
+
+            let mut sign = 1.0;
+            loop {
+                match self.peek(0) {
+                    Some(Token::Plus) => {}
+                    Some(Token::Minus) => sign = -sign,
+                    _ => break,
+                }
+                self.pos += 1;
+            }
+            let coeff = match self.peek(0) {
+                Some(&Token::Num(val)) => {
+                    self.pos += 1;
+                    Some(val)
+                }
+                _ => None,
+            };
+            let name = match self.peek(0) {
+                Some(Token::Name(name)) if !self.at_section() => Some(name.clone()),
+                _ => None,
+            };
+            match (coeff, name) {
+                (coeff, Some(name)) => {
+                    self.pos += 1;
+                    let var = self.var(&name);
+                    terms.push((var, sign * coeff.unwrap_or(1.0)));
+                }
+                (Some(coeff), None) => constant += sign * coeff,
+                (None, None) => return Err(self.err("expected a term")),
+            }
+        }
+
+        terms.sort_by_key(|&(var, _)| var);
+        let mut merged: Vec<(usize, f64)> = vec![];
+        for (var, coeff) in terms {
+            match merged.last_mut() {
+                Some(last) if last.0 == var => last.1 += coeff,
+                _ => merged.push((var, coeff)),
+            }
+        }
+        Ok((merged, constant))
+    }
+
+    fn cmp_op(&mut self) -> io::Result<ComparisonOp> {
+        match self.peek(0) {
+            Some(&Token::Op(cmp_op)) => {
+                self.pos += 1;
+                Ok(cmp_op)
+            }
+            _ => Err(self.err("expected a comparison operator")),
+        }
+    }
+
+    /// Parse a signed number (possibly infinite).
+    fn value(&mut self) -> io::Result<f64> {
+        let mut sign = 1.0;
+        loop {
+            match self.peek(0) {
+                Some(Token::Plus) => {}
+                Some(Token::Minus) => sign = -sign,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        let val = match self.peek(0) {
+            Some(&Token::Num(val)) => val,
+            Some(Token::Name(name)) if is_infinity(name) => f64::INFINITY,
+            _ => return Err(self.err("expected a number")),
+        };
+        self.pos += 1;
+        Ok(sign * val)
+    }
+
+    /// Parse a bound statement: `x free`, `x op value` or `value op x [op value]`.
+    fn bound(&mut self, bounds: &mut Vec<(usize, ComparisonOp, f64)>) -> io::Result<()> {
+        let reverse = |cmp_op| match cmp_op {
+            ComparisonOp::Le => ComparisonOp::Ge,
+            ComparisonOp::Ge => ComparisonOp::Le,
+            ComparisonOp::Eq => ComparisonOp::Eq,
+        };
+
+        let var = match self.peek(0) {
+            Some(Token::Name(name)) if !is_infinity(name) => {
+                let name = name.clone();
+                self.pos += 1;
+                if let Some(Token::Name(free)) = self.peek(0) {
+                    if free.eq_ignore_ascii_case("free") {
+                        self.pos += 1;
+                        let var = self.var(&name);
+                        bounds.push((var, ComparisonOp::Ge, f64::NEG_INFINITY));
+                        bounds.push((var, ComparisonOp::Le, f64::INFINITY));
+                        return Ok(());
+                    }
+                }
+                self.var(&name)
+            }
+            _ => {
+                let val = self.value()?;
+                let cmp_op = self.cmp_op()?;
+                let var = match self.peek(0) {
+                    Some(Token::Name(name)) => name.clone(),
+                    _ => return Err(self.err("expected a variable")),
+                };
+                self.pos += 1;
+                let var = self.var(&var);
+                bounds.push((var, reverse(cmp_op), val));
+                if !matches!(self.peek(0), Some(Token::Op(_))) {
+                    return Ok(());
+                }
+                var
+            }
+        };
+        let cmp_op = self.cmp_op()?;
+        let val = self.value()?;
+        bounds.push((var, cmp_op, val));
+        Ok(())
+    }
+}
+
+fn is_infinity(name: &str) -> bool {
+    name.eq_ignore_ascii_case("inf") || name.eq_ignore_ascii_case("infinity")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const LP_FILE: &str = "\\ test file
+Minimize
+ obj: 2x + 3 y - z
+   + 0 w + 3 - 1
+Subject To
+ c1: x + y >= 2 \\ comment
+ -x + 2 y - x <= 4
+ y + z - 1 = 0
+Bounds
+ -inf <= z <= 10
+ w free
+ 1 <= x <= 1e1
+ y >= -1
+End
+";
+
+    #[test]
+    fn lp_format() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(-1.0, (f64::NEG_INFINITY, f64::INFINITY));
+        let y = problem.add_var(0.0, (1.0, f64::INFINITY));
+        let z = problem.add_var(2.5, (f64::NEG_INFINITY, 4.0));
+        let w = problem.add_var(0.0, (3.0, 3.0));
+        problem.set_var_name(y, "y");
+        problem.add_constraint([(x, -1.0), (y, 1.0), (z, -0.5)], ComparisonOp::Eq, 1.5);
+        problem.add_constraint([(w, 0.0)], ComparisonOp::Le, 0.0);
+        problem.set_objective_offset(-4.5);
+        assert_eq!(
+            problem.to_string(),
+            "Minimize
+ obj: -x0 + 2.5 x2 - 4.5
+Subject To
+ c0: -x0 + y - 0.5 x2 = 1.5
+ c1: 0 <= 0
+Bounds
+ x0 free
+ y >= 1
+ -inf <= x2 <= 4
+ x3 = 3
+End
+"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let file = LpFile::parse(LP_FILE.as_bytes()).unwrap();
+        let problem = &file.problem;
+        assert_eq!(file.variables.len(), 4);
+        assert_eq!(problem.var_name(file.variables["z"]), "z");
+        assert_eq!(
+            problem.to_string(),
+            "Minimize
+ obj: 2 x + 3 y - z + 2
+Subject To
+ c0: x + y >= 2
+ c1: -2 x + 2 y <= 4
+ c2: y + z = 1
+Bounds
+ 1 <= x <= 10
+ y >= -1
+ -inf <= z <= 10
+ w free
+End
+"
+        );
+
+        // Round trip.
+        let file = LpFile::parse(problem.to_string().as_bytes()).unwrap();
+        assert_eq!(file.problem.to_string(), problem.to_string());
+        assert_eq!(file.problem.solve().unwrap().objective(), 3.0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |input: &str| LpFile::parse(input.as_bytes()).unwrap_err().to_string();
+        assert_eq!(
+            err("Subject To\n x >= 1\nEnd"),
+            "line 1: expected objective section"
+        );
+        assert_eq!(
+            err("Maximize\n x\nSubject To\n x >= \nEnd"),
+            "line 5: expected a number"
+        );
+        assert_eq!(
+            err("Maximize\n x\nGeneral\n x\nEnd"),
+            "line 3: integer variables are not supported"
+        );
+    }
+}
@@ -1,9 +1,21 @@
-use crate::sparse::{Error, Perm, ScatteredVec, SparseMat, TriangleMat};
+use crate::sparse::{Error, Index, IndexType, Perm, ScatteredVec, SparseMat, TriangleMat};
+
+/// The rest of the matrix is factorized as a dense one if it has at least this many columns
+/// (smaller dense blocks are handled efficiently by the sparse algorithm).
+const DENSE_MIN_SIZE: usize = 64;
+/// Dense factorization is not used for blocks with more columns than this to limit the
+/// memory usage.
+const DENSE_MAX_SIZE: usize = 4096;
+/// Switch to the dense factorization when at least this fraction of the
+/// not yet factorized part of the column is nonzero.
+const DENSE_MIN_DENSITY: f64 = 0.5;
+/// Number of columns in a block of the dense factorization.
+const DENSE_BLOCK_SIZE: usize = 32;
 
 #[derive(Clone)]
-pub struct LUFactors {
-    lower: TriangleMat,
-    upper: TriangleMat,
+pub struct LUFactors<I = Index> {
+    lower: TriangleMat<I>,
+    upper: TriangleMat<I>,
     row_perm: Option<Perm>,
     col_perm: Option<Perm>,
 }
@@ -30,7 +42,7 @@ impl ScratchSpace {
     }
 }
 
-impl std::fmt::Debug for LUFactors {
+impl<I: IndexType> std::fmt::Debug for LUFactors<I> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "L:\n{:?}", self.lower)?;
         writeln!(f, "U:\n{:?}", self.upper)?;
@@ -48,17 +60,35 @@ impl std::fmt::Debug for LUFactors {
     }
 }
 
-impl LUFactors {
+impl<I: IndexType> LUFactors<I> {
+    pub(crate) fn lower(&self) -> &TriangleMat<I> {
+        &self.lower
+    }
+
+    pub(crate) fn upper(&self) -> &TriangleMat<I> {
+        &self.upper
+    }
+
     pub fn nnz(&self) -> usize {
         self.lower.nondiag.nnz() + self.upper.nondiag.nnz() + self.lower.cols()
     }
 
+    /// The biggest absolute value in the U factor.
+    pub fn max_abs_upper(&self) -> f64 {
+        let upper = &self.upper;
+        let nondiag = (0..upper.cols()).flat_map(|c| upper.nondiag.col_data(c));
+        let diag = upper.diag.iter().flatten();
+        nondiag
+            .chain(diag)
+            .fold(0.0, |max: f64, &val| max.max(val.abs()))
+    }
+
     pub fn solve_dense(&self, rhs: &mut [f64], scratch: &mut ScratchSpace) {
         scratch.dense_rhs.resize(rhs.len(), 0.0);
 
         if let Some(row_perm) = &self.row_perm {
-            for i in 0..rhs.len() {
-                scratch.dense_rhs[row_perm.orig2new[i]] = rhs[i];
+            for (i, &val) in rhs.iter().enumerate() {
+                scratch.dense_rhs[row_perm.orig2new[i]] = val;
             }
         } else {
             scratch.dense_rhs.copy_from_slice(rhs);
@@ -72,7 +102,7 @@ impl LUFactors {
                 rhs[col_perm.new2orig[i]] = scratch.dense_rhs[i];
             }
         } else {
-            rhs.copy_from_slice(&mut scratch.dense_rhs);
+            rhs.copy_from_slice(&scratch.dense_rhs);
         }
     }
 
@@ -105,7 +135,7 @@ impl LUFactors {
         }
     }
 
-    pub fn transpose(&self) -> LUFactors {
+    pub fn transpose(&self) -> LUFactors<I> {
         LUFactors {
             lower: self.upper.transpose(),
             upper: self.lower.transpose(),
@@ -115,12 +145,13 @@ impl LUFactors {
     }
 }
 
-pub fn lu_factorize<'a>(
+pub fn lu_factorize<'a, I: IndexType, J: IndexType>(
     size: usize,
-    get_col: impl Fn(usize) -> (&'a [usize], &'a [f64]),
+    get_col: impl Fn(usize) -> (&'a [J], &'a [f64]),
+    col_perm: Option<Perm>,
     stability_coeff: f64,
     scratch: &mut ScratchSpace,
-) -> Result<LUFactors, Error> {
+) -> Result<LUFactors<I>, Error> {
     // Implementation of the Gilbert-Peierls algorithm:
     //
     // Gilbert, John R., and Tim Peierls. "Sparse partial pivoting in time
@@ -137,19 +168,20 @@ pub fn lu_factorize<'a>(
         mat_nnz - size,
     );
 
-    let col_perm = super::ordering::order_simple(size, |c| get_col(c).0);
+    let col_perm =
+        col_perm.unwrap_or_else(|| super::ordering::order_simple(size, |c| get_col(c).0));
 
     let mut orig_row2elt_count = vec![0; size];
     for col_rows in (0..size).map(|c| get_col(c).0) {
-        for &orig_r in col_rows {
-            orig_row2elt_count[orig_r] += 1;
+        for orig_r in col_rows {
+            orig_row2elt_count[orig_r.index()] += 1;
         }
     }
 
     scratch.clear_sparse(size);
 
-    let mut lower = SparseMat::new(size);
-    let mut upper = SparseMat::new(size);
+    let mut lower = SparseMat::<I>::new(size);
+    let mut upper = SparseMat::<I>::new(size);
     let mut upper_diag = Vec::with_capacity(size);
 
     let mut new2orig_row = (0..size).collect::<Vec<_>>();
@@ -158,32 +190,37 @@ pub fn lu_factorize<'a>(
     for i_col in 0..size {
         let mat_col = get_col(col_perm.new2orig[i_col]);
 
-        // Solve the equation L'_j * x = a_j (x will be in scratch.rhs).
-        // L'_j is a sq. matrix with the first j columns of L
-        // and columns of identity matrix after j.
+        // Solve the equation L'_j * x = a_j with j = i_col.
         // Part of x above the diagonal (in the new row indices) is the column of U
         // and part of x below the diagonal divided by the pivot value is the column of L
 
-        scratch.rhs.set(mat_col.0.iter().copied().zip(mat_col.1));
-
-        scratch.mark_nonzero.run(
-            &mut scratch.rhs,
-            |new_i| &lower.col_rows(new_i),
-            |new_i| new_i < i_col,
-            |orig_r| orig2new_row[orig_r],
-        );
-
-        // At this point all future nonzero positions of scratch.rhs are marked
-        // and the order in which variables depend on each other is determined.
-        // rev() because DFS returns vertices in reverse topological order.
-        for &orig_i in scratch.mark_nonzero.visited.iter().rev() {
-            // values[orig_i] is already fully calculated, diag coeff = 1.0.
-            let new_i = orig2new_row[orig_i];
-            if new_i < i_col {
-                let x_val = scratch.rhs.values[orig_i];
-                for (orig_r, coeff) in lower.col_iter(new_i) {
-                    scratch.rhs.values[orig_r] -= x_val * coeff;
-                }
+        solve_lower_prefix(mat_col, &lower, i_col, &orig2new_row, scratch);
+
+        // If the rest of the matrix is dense, switch to the dense factorization.
+        let num_remaining = size - i_col;
+        if (DENSE_MIN_SIZE..=DENSE_MAX_SIZE).contains(&num_remaining) {
+            let num_below = scratch
+                .rhs
+                .nonzero
+                .iter()
+                .filter(|&&orig_r| orig2new_row[orig_r] >= i_col)
+                .count();
+            if num_below as f64 >= DENSE_MIN_DENSITY * num_remaining as f64 {
+                trace!(
+                    "lu_factorize: switching to dense factorization of the last {} columns",
+                    num_remaining
+                );
+                factorize_dense_tail(
+                    i_col,
+                    |c| get_col(col_perm.new2orig[c]),
+                    &mut lower,
+                    &mut upper,
+                    &mut upper_diag,
+                    &mut new2orig_row,
+                    &mut orig2new_row,
+                    scratch,
+                )?;
+                break;
             }
         }
 
@@ -269,7 +306,7 @@ pub fn lu_factorize<'a>(
     // permute rows of lower to "new" indices.
     for i_col in 0..lower.cols() {
         for r in lower.col_rows_mut(i_col) {
-            *r = orig2new_row[*r];
+            *r = I::from_usize(orig2new_row[r.index()]);
         }
     }
 
@@ -303,6 +340,211 @@ pub fn lu_factorize<'a>(
     Ok(res)
 }
 
+/// Solve the equation L'_j * x = a_j (x will be in scratch.rhs), where L'_j is a sq. matrix
+/// with the first j columns of L and columns of identity matrix after j.
+fn solve_lower_prefix<I: IndexType, J: IndexType>(
+    mat_col: (&[J], &[f64]),
+    lower: &SparseMat<I>,
+    j: usize,
+    orig2new_row: &[usize],
+    scratch: &mut ScratchSpace,
+) {
+    scratch
+        .rhs
+        .set(mat_col.0.iter().map(|r| r.index()).zip(mat_col.1));
+
+    scratch.mark_nonzero.run(
+        &mut scratch.rhs,
+        |new_i| lower.col_rows(new_i),
+        |new_i| new_i < j,
+        |orig_r| orig2new_row[orig_r],
+    );
+
+    // At this point all future nonzero positions of scratch.rhs are marked
+    // and the order in which variables depend on each other is determined.
+    // rev() because DFS returns vertices in reverse topological order.
+    for &orig_i in scratch.mark_nonzero.visited.iter().rev() {
+        // values[orig_i] is already fully calculated, diag coeff = 1.0.
+        let new_i = orig2new_row[orig_i];
+        if new_i < j {
+            let x_val = scratch.rhs.values[orig_i];
+            for (orig_r, coeff) in lower.col_iter(new_i) {
+                scratch.rhs.values[orig_r] -= x_val * coeff;
+            }
+        }
+    }
+}
+
+/// Factorize the columns starting from `start` as a dense matrix. Their parts below the
+/// diagonal form the Schur complement of the already factorized block, which is gathered
+/// into a dense matrix and factorized with partial pivoting.
+#[allow(clippy::too_many_arguments)]
+fn factorize_dense_tail<'a, I: IndexType, J: IndexType>(
+    start: usize,
+    get_col: impl Fn(usize) -> (&'a [J], &'a [f64]),
+    lower: &mut SparseMat<I>,
+    upper: &mut SparseMat<I>,
+    upper_diag: &mut Vec<f64>,
+    new2orig_row: &mut [usize],
+    orig2new_row: &mut [usize],
+    scratch: &mut ScratchSpace,
+) -> Result<(), Error> {
+    let size = new2orig_row.len();
+    let n = size - start;
+
+    // Column-major, element (r, c) is at c * n + r.
+    let mut dense = vec![0.0; n * n];
+    let mut upper_parts = vec![vec![]; n];
+    for c in 0..n {
+        solve_lower_prefix(get_col(start + c), lower, start, orig2new_row, scratch);
+        for &orig_r in &scratch.rhs.nonzero {
+            let val = scratch.rhs.values[orig_r];
+            if val == 0.0 {
+                continue;
+            }
+            let new_r = orig2new_row[orig_r];
+            if new_r < start {
+                upper_parts[c].push((new_r, val));
+            } else {
+                dense[c * n + new_r - start] = val;
+            }
+        }
+    }
+
+    let perm = dense_lu(&mut dense, n, DENSE_BLOCK_SIZE)?;
+    let tail_new2orig = new2orig_row[start..].to_vec();
+    for (i, &p) in perm.iter().enumerate() {
+        let orig_r = tail_new2orig[p];
+        new2orig_row[start + i] = orig_r;
+        orig2new_row[orig_r] = start + i;
+    }
+
+    for (c, upper_part) in upper_parts.into_iter().enumerate() {
+        let col = &dense[c * n..(c + 1) * n];
+        for (new_r, val) in upper_part {
+            upper.push(new_r, val);
+        }
+        for (r, &val) in col[..c].iter().enumerate() {
+            if val != 0.0 {
+                upper.push(start + r, val);
+            }
+        }
+        upper_diag.push(col[c]);
+        for (r, &val) in col.iter().enumerate().skip(c + 1) {
+            if val != 0.0 {
+                lower.push(new2orig_row[start + r], val);
+            }
+        }
+        upper.seal_column();
+        lower.seal_column();
+    }
+    Ok(())
+}
+
+/// Factorize a dense n x n column-major matrix in place into L (unit lower triangular,
+/// below the diagonal) and U using partial pivoting. Columns are processed in blocks and
+/// the trailing submatrix is updated with a matrix-matrix product. Returns the row
+/// permutation: row i of the factorized matrix is row perm[i] of the original one.
+fn dense_lu(a: &mut [f64], n: usize, block_size: usize) -> Result<Vec<usize>, Error> {
+    let mut perm: Vec<usize> = (0..n).collect();
+    for k0 in (0..n).step_by(block_size) {
+        let k1 = usize::min(k0 + block_size, n);
+
+        // Factorize the panel of columns k0..k1.
+        for k in k0..k1 {
+            let mut pivot_r = k;
+            for r in k + 1..n {
+                if a[k * n + r].abs() > a[k * n + pivot_r].abs() {
+                    pivot_r = r;
+                }
+            }
+            if a[k * n + pivot_r].abs() < 1e-8 {
+                return Err(Error::SingularMatrix);
+            }
+            if pivot_r != k {
+                perm.swap(k, pivot_r);
+                for c in 0..n {
+                    a.swap(c * n + k, c * n + pivot_r);
+                }
+            }
+
+            let pivot_val = a[k * n + k];
+            for r in k + 1..n {
+                a[k * n + r] /= pivot_val;
+            }
+            for c in k + 1..k1 {
+                let u_val = a[c * n + k];
+                if u_val != 0.0 {
+                    for r in k + 1..n {
+                        a[c * n + r] -= a[k * n + r] * u_val;
+                    }
+                }
+            }
+        }
+
+        if k1 == n {
+            break;
+        }
+
+        // Rows k0..k1 of the rest of the columns become rows of U.
+        for c in k1..n {
+            for k in k0..k1 {
+                let u_val = a[c * n + k];
+                if u_val != 0.0 {
+                    for r in k + 1..k1 {
+                        a[c * n + r] -= a[k * n + r] * u_val;
+                    }
+                }
+            }
+        }
+
+        schur_update(a, n, k0, k1);
+    }
+    Ok(perm)
+}
+
+/// Update the trailing submatrix: A[k1.., k1..] -= A[k1.., k0..k1] * A[k0..k1, k1..].
+#[cfg(not(feature = "matrixmultiply"))]
+fn schur_update(a: &mut [f64], n: usize, k0: usize, k1: usize) {
+    for c in k1..n {
+        for k in k0..k1 {
+            let u_val = a[c * n + k];
+            if u_val != 0.0 {
+                for r in k1..n {
+                    a[c * n + r] -= a[k * n + r] * u_val;
+                }
+            }
+        }
+    }
+}
+
+/// Update the trailing submatrix: A[k1.., k1..] -= A[k1.., k0..k1] * A[k0..k1, k1..].
+#[cfg(feature = "matrixmultiply")]
+fn schur_update(a: &mut [f64], n: usize, k0: usize, k1: usize) {
+    let m = n - k1;
+    let ptr = a.as_mut_ptr();
+    // SAFETY: the three blocks are disjoint parts of `a` (they lie in different columns
+    // or rows) and all pointers are derived from the same mutable pointer.
+    unsafe {
+        matrixmultiply::dgemm(
+            m,
+            k1 - k0,
+            m,
+            -1.0,
+            ptr.add(k0 * n + k1),
+            1,
+            n as isize,
+            ptr.add(k1 * n + k0),
+            1,
+            n as isize,
+            1.0,
+            ptr.add(k1 * n + k1),
+            1,
+            n as isize,
+        );
+    }
+}
+
 #[derive(Clone, Debug)]
 struct MarkNonzero {
     dfs_stack: Vec<DfsStep>,
@@ -340,10 +582,10 @@ impl MarkNonzero {
     }
 
     // compute the non-zero elements of the result by dfs traversal
-    fn run<'a>(
+    fn run<'a, I: IndexType>(
         &mut self,
         rhs: &mut ScatteredVec,
-        get_children: impl Fn(usize) -> &'a [usize] + 'a,
+        get_children: impl Fn(usize) -> &'a [I] + 'a,
         filter: impl Fn(usize) -> bool,
         orig2new_row: impl Fn(usize) -> usize,
     ) {
@@ -377,7 +619,7 @@ impl MarkNonzero {
                 }
 
                 while cur_step.cur_child < children.len() {
-                    let child_orig_r = children[cur_step.cur_child];
+                    let child_orig_r = children[cur_step.cur_child].index();
                     if !self.is_visited[child_orig_r] {
                         break;
                     }
@@ -387,7 +629,7 @@ impl MarkNonzero {
                 if cur_step.cur_child < children.len() {
                     let i_child = cur_step.cur_child;
                     self.dfs_stack.push(DfsStep {
-                        orig_i: children[i_child],
+                        orig_i: children[i_child].index(),
                         cur_child: 0,
                     });
                 } else {
@@ -411,7 +653,7 @@ enum Triangle {
     Upper,
 }
 
-fn tri_solve_dense(tri_mat: &TriangleMat, triangle: Triangle, rhs: &mut [f64]) {
+fn tri_solve_dense<I: IndexType>(tri_mat: &TriangleMat<I>, triangle: Triangle, rhs: &mut [f64]) {
     assert_eq!(tri_mat.rows(), rhs.len());
     match triangle {
         Triangle::Lower => {
@@ -429,7 +671,7 @@ fn tri_solve_dense(tri_mat: &TriangleMat, triangle: Triangle, rhs: &mut [f64]) {
 }
 
 /// rhs is passed via scratch.visited, scratch.values.
-fn tri_solve_sparse(tri_mat: &TriangleMat, scratch: &mut ScratchSpace) {
+fn tri_solve_sparse<I: IndexType>(tri_mat: &TriangleMat<I>, scratch: &mut ScratchSpace) {
     assert_eq!(tri_mat.rows(), scratch.rhs.len());
 
     // compute the non-zero elements of the result by dfs traversal
@@ -447,7 +689,7 @@ fn tri_solve_sparse(tri_mat: &TriangleMat, scratch: &mut ScratchSpace) {
     }
 }
 
-fn tri_solve_process_col(tri_mat: &TriangleMat, col: usize, rhs: &mut [f64]) {
+fn tri_solve_process_col<I: IndexType>(tri_mat: &TriangleMat<I>, col: usize, rhs: &mut [f64]) {
     // all other variables in this row (multiplied by their coeffs)
     // are already subtracted from rhs[col].
     let x_val = if let Some(diag) = tri_mat.diag.as_ref() {
@@ -495,9 +737,10 @@ mod tests {
         );
 
         let mut scratch = ScratchSpace::with_capacity(mat.rows());
-        let lu = lu_factorize(
+        let lu = lu_factorize::<usize, _>(
             mat.rows(),
             |c| mat.outer_view([1, 0, 3][c]).unwrap().into_raw_storage(),
+            None,
             0.9,
             &mut scratch,
         )
@@ -563,7 +806,7 @@ mod tests {
             );
 
             let mut scratch = ScratchSpace::with_capacity(size);
-            let err = lu_factorize(
+            let err = lu_factorize::<usize, _>(
                 size,
                 |c| {
                     symbolically_singular
@@ -571,6 +814,7 @@ mod tests {
                         .unwrap()
                         .into_raw_storage()
                 },
+                None,
                 0.9,
                 &mut scratch,
             );
@@ -593,7 +837,7 @@ mod tests {
             );
 
             let mut scratch = ScratchSpace::with_capacity(size);
-            let err = lu_factorize(
+            let err = lu_factorize::<usize, _>(
                 size,
                 |c| {
                     numerically_singular
@@ -601,6 +845,7 @@ mod tests {
                         .unwrap()
                         .into_raw_storage()
                 },
+                None,
                 0.9,
                 &mut scratch,
             );
@@ -630,9 +875,10 @@ mod tests {
         // TODO: random permutation?
         let cols: Vec<_> = (0..size).collect();
 
-        let lu = lu_factorize(
+        let lu = lu_factorize::<usize, _>(
             size,
             |c| mat.outer_view(cols[c]).unwrap().into_raw_storage(),
+            None,
             0.1,
             &mut scratch,
         )
@@ -702,4 +948,82 @@ mod tests {
             assert!(diff.norm(1.0) < 1e-5);
         }
     }
+
+    #[test]
+    fn dense_blocks() {
+        let n = 10;
+        let mut rng = rand_pcg::Pcg64::seed_from_u64(42);
+        use rand::prelude::*;
+        let orig: Vec<f64> = (0..n * n).map(|_| rng.gen_range(-1.0, 1.0)).collect();
+
+        for &block_size in &[1, 3, n] {
+            let mut a = orig.clone();
+            let perm = dense_lu(&mut a, n, block_size).unwrap();
+            for r in 0..n {
+                for c in 0..n {
+                    // Row r of L * U is row perm[r] of the original matrix.
+                    let mut prod = 0.0;
+                    for k in 0..=usize::min(r, c) {
+                        let l_val = if k == r { 1.0 } else { a[k * n + r] };
+                        prod += l_val * a[c * n + k];
+                    }
+                    assert!(f64::abs(prod - orig[c * n + perm[r]]) < 1e-10);
+                }
+            }
+        }
+
+        let mut singular = orig;
+        for r in 0..n {
+            singular[3 * n + r] = 2.0 * singular[5 * n + r];
+        }
+        assert_eq!(dense_lu(&mut singular, n, 3), Err(Error::SingularMatrix));
+    }
+
+    #[test]
+    fn lu_dense_tail() {
+        // A sparse diagonal part followed by a dense block.
+        let size = 100;
+        let sparse_size = 20;
+        let mut rng = rand_pcg::Pcg64::seed_from_u64(7);
+        use rand::prelude::*;
+
+        let mut mat = TriMat::new((size, size));
+        for c in 0..size {
+            if c < sparse_size {
+                mat.add_triplet(c, c, rng.gen_range(1.0, 2.0));
+                mat.add_triplet(sparse_size + c, c, rng.gen_range(-1.0, 1.0));
+            } else {
+                mat.add_triplet(c % sparse_size, c, rng.gen_range(-1.0, 1.0));
+                for r in sparse_size..size {
+                    mat.add_triplet(r, c, rng.gen_range(-1.0, 1.0));
+                }
+            }
+        }
+        let mat: CsMat<f64> = mat.to_csc();
+
+        let mut scratch = ScratchSpace::with_capacity(size);
+        let lu = lu_factorize::<u16, _>(
+            size,
+            |c| mat.outer_view(c).unwrap().into_raw_storage(),
+            None,
+            0.1,
+            &mut scratch,
+        )
+        .unwrap();
+
+        let rhs: Vec<f64> = (0..size).map(|_| rng.gen_range(-1.0, 1.0)).collect();
+        let mut sol = rhs.clone();
+        lu.solve_dense(&mut sol, &mut scratch);
+        let prod = &mat * &ndarray::Array1::from(sol);
+        for (r, &val) in rhs.iter().enumerate() {
+            assert!(f64::abs(prod[r] - val) < 1e-8);
+        }
+
+        let mut sol_t = rhs.clone();
+        lu.transpose().solve_dense(&mut sol_t, &mut scratch);
+        let prod = &mat.transpose_view() * &ndarray::Array1::from(sol_t);
+        for (r, &val) in rhs.iter().enumerate() {
+            assert!(f64::abs(prod[r] - val) < 1e-8);
+        }
+    }
 }
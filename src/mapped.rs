@@ -0,0 +1,428 @@
+use crate::{
+    pdlp::{self, LinearOperator, ScaledLp},
+    ComparisonOp, ConstraintRef, FirstOrderOptions, FirstOrderSolution, LinearExpr,
+    OptimizationDirection, Variable,
+};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// A nonzero of the constraint matrix as it is stored in the file.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Entry {
+    col: u64,
+    val: f64,
+}
+
+const ENTRY_SIZE: usize = std::mem::size_of::<Entry>();
+
+/// A linear programming problem with the constraint matrix stored in a file that is
+/// memory-mapped when solving.
+///
+/// Only the objective, the variable bounds and the constraint right-hand sides are kept
+/// in memory, so problems with more nonzeros than fit in RAM can be loaded and solved
+/// with the [first-order method](struct.Problem.html#method.solve_first_order): the matrix
+/// is accessed by sequential passes over the mapped file and the operating system pages
+/// it in and out as needed.
+///
+/// Variables and constraints are added in the same way as for a
+/// [`Problem`](struct.Problem.html), constraint coefficients are written to the file
+/// immediately. The file is a scratch file in the native byte order, it is not meant to be
+/// read by anything else and must not be modified while the problem exists.
+///
+/// Before solving, a simple presolve removes the rows that are empty or have a single
+/// nonzero (turning them into variable bounds), their dual values are then recovered
+/// from the reduced costs.
+///
+/// Available only on Unix with the `mmap` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let path = std::env::temp_dir().join(format!("minilp-doc-{}.bin", std::process::id()));
+/// let mut problem = MappedProblem::create(OptimizationDirection::Maximize, &path).unwrap();
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(2.0, (0.0, 3.0));
+/// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0).unwrap();
+/// problem.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0).unwrap();
+///
+/// let solution = problem.solve(&FirstOrderOptions::default()).unwrap();
+/// assert!(solution.is_converged());
+/// assert!(f64::abs(solution.objective() - 7.0) < 1e-4);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MappedProblem {
+    direction: OptimizationDirection,
+    obj_coeffs: Vec<f64>,
+    var_mins: Vec<f64>,
+    var_maxs: Vec<f64>,
+    obj_offset: f64,
+    constraints: Vec<(ComparisonOp, f64)>,
+    /// Entries of the constraint `r` are `indptr[r]..indptr[r + 1]`.
+    indptr: Vec<usize>,
+    writer: BufWriter<File>,
+}
+
+impl MappedProblem {
+    /// Create a new problem storing its constraint matrix in the file at `path`.
+    /// The file is created or truncated if it exists.
+    pub fn create(direction: OptimizationDirection, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(MappedProblem {
+            direction,
+            obj_coeffs: vec![],
+            var_mins: vec![],
+            var_maxs: vec![],
+            obj_offset: 0.0,
+            constraints: vec![],
+            indptr: vec![0],
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Add a new variable to the problem, see
+    /// [`Problem::add_var`](struct.Problem.html#method.add_var).
+    pub fn add_var(&mut self, obj_coeff: f64, (min, max): (f64, f64)) -> Variable {
+        let var = Variable(self.obj_coeffs.len(), 0);
+        let obj_coeff = match self.direction {
+            OptimizationDirection::Minimize => obj_coeff,
+            OptimizationDirection::Maximize => -obj_coeff,
+        };
+        self.obj_coeffs.push(obj_coeff);
+        self.var_mins.push(min);
+        self.var_maxs.push(max);
+        var
+    }
+
+    /// Set the constant term of the objective function, see
+    /// [`Problem::set_objective_offset`](struct.Problem.html#method.set_objective_offset).
+    pub fn set_objective_offset(&mut self, offset: f64) {
+        assert!(offset.is_finite(), "invalid objective offset: {}", offset);
+        self.obj_offset = match self.direction {
+            OptimizationDirection::Minimize => offset,
+            OptimizationDirection::Maximize => -offset,
+        };
+    }
+
+    /// Add a linear constraint to the problem writing its coefficients to the file, see
+    /// [`Problem::add_constraint`](struct.Problem.html#method.add_constraint).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a variable was not added to the problem.
+    pub fn add_constraint(
+        &mut self,
+        expr: impl Into<LinearExpr>,
+        cmp_op: ComparisonOp,
+        rhs: f64,
+    ) -> io::Result<ConstraintRef> {
+        let (vars, coeffs) = expr.into().into_merged();
+        for (&col, &val) in vars.iter().zip(&coeffs) {
+            assert!(col < self.obj_coeffs.len(), "unknown variable {}", col);
+            self.writer.write_all(&(col as u64).to_ne_bytes())?;
+            self.writer.write_all(&val.to_ne_bytes())?;
+        }
+        let constr = ConstraintRef(self.constraints.len(), 0);
+        self.constraints.push((cmp_op, rhs));
+        self.indptr.push(self.indptr.last().unwrap() + vars.len());
+        Ok(constr)
+    }
+
+    /// Number of the constraint matrix nonzeros stored in the file.
+    pub fn num_nonzeros(&self) -> usize {
+        *self.indptr.last().unwrap()
+    }
+
+    /// Solve the problem approximately with the first-order method, see
+    /// [`Problem::solve_first_order`](struct.Problem.html#method.solve_first_order).
+    ///
+    /// Returns an error if the file could not be mapped into memory.
+    pub fn solve(&mut self, options: &FirstOrderOptions) -> io::Result<FirstOrderSolution> {
+        self.writer.flush()?;
+        let mapping = Mapping::new(self.writer.get_ref(), self.num_nonzeros() * ENTRY_SIZE)?;
+        let entries = mapping.entries();
+
+        let row = |r: usize| entries[self.indptr[r]..self.indptr[r + 1]].iter();
+        let row_signs = (self.constraints.iter())
+            .map(|(cmp_op, _)| match cmp_op {
+                ComparisonOp::Le => -1.0,
+                ComparisonOp::Ge | ComparisonOp::Eq => 1.0,
+            })
+            .collect::<Vec<_>>();
+
+        // Presolve: a row a * x_j >= b becomes a bound on x_j if the bounds stay consistent.
+        let mut var_mins = self.var_mins.clone();
+        let mut var_maxs = self.var_maxs.clone();
+        let mut min_rows = vec![None; self.obj_coeffs.len()];
+        let mut max_rows = vec![None; self.obj_coeffs.len()];
+        let mut kept = vec![];
+        for (r, (cmp_op, rhs)) in self.constraints.iter().enumerate() {
+            let rhs = row_signs[r] * rhs;
+            let is_eq = *cmp_op == ComparisonOp::Eq;
+            let mut nonzeros = row(r).filter(|e| e.val != 0.0);
+            let is_removed = match (nonzeros.next(), nonzeros.next()) {
+                (None, _) => rhs <= 0.0 && (!is_eq || rhs == 0.0),
+                (Some(e), None) => {
+                    let col = e.col as usize;
+                    let coeff = row_signs[r] * e.val;
+                    let bound = rhs / coeff;
+                    let (is_min, is_max) = (is_eq || coeff > 0.0, is_eq || coeff < 0.0);
+                    let new_min = if is_min {
+                        bound.max(var_mins[col])
+                    } else {
+                        var_mins[col]
+                    };
+                    let new_max = if is_max {
+                        bound.min(var_maxs[col])
+                    } else {
+                        var_maxs[col]
+                    };
+                    if new_min <= new_max {
+                        if is_min && bound >= var_mins[col] {
+                            var_mins[col] = bound;
+                            min_rows[col] = Some((r, coeff));
+                        }
+                        if is_max && bound <= var_maxs[col] {
+                            var_maxs[col] = bound;
+                            max_rows[col] = Some((r, coeff));
+                        }
+                    }
+                    new_min <= new_max
+                }
+                _ => false,
+            };
+            if !is_removed {
+                kept.push(r);
+            }
+        }
+        debug!(
+            "presolve removed {} of {} rows",
+            self.constraints.len() - kept.len(),
+            self.constraints.len(),
+        );
+
+        let (row_scales, col_scales) = pdlp::ruiz_scales(kept.len(), self.obj_coeffs.len(), |k| {
+            row(kept[k]).map(|e| (e.col as usize, e.val))
+        });
+        let matrix = MappedMatrix {
+            entries,
+            indptr: &self.indptr,
+            rows: kept.clone(),
+            row_factors: (kept.iter().zip(&row_scales))
+                .map(|(&r, scale)| row_signs[r] * scale)
+                .collect(),
+            col_scales: col_scales.clone(),
+        };
+        let lp = ScaledLp::new(
+            matrix,
+            &self.obj_coeffs,
+            &var_mins,
+            &var_maxs,
+            kept.iter()
+                .map(|&r| row_signs[r] * self.constraints[r].1)
+                .collect(),
+            (kept.iter())
+                .map(|&r| self.constraints[r].0 == ComparisonOp::Eq)
+                .collect(),
+            row_scales,
+            col_scales,
+        );
+        let mut output = lp.solve(options);
+
+        // Postsolve: the dual value of a removed row is the reduced cost of its variable
+        // if the row bound is active.
+        let mut y = vec![0.0; self.constraints.len()];
+        for (&r, &val) in kept.iter().zip(&output.y) {
+            y[r] = val;
+        }
+        let mut reduced_costs = self.obj_coeffs.clone();
+        for &r in &kept {
+            for e in row(r) {
+                reduced_costs[e.col as usize] -= row_signs[r] * e.val * y[r];
+            }
+        }
+        for (col, &cost) in reduced_costs.iter().enumerate() {
+            let row = if cost > 0.0 {
+                min_rows[col]
+            } else {
+                max_rows[col]
+            };
+            if let Some((r, coeff)) = row {
+                y[r] = cost / coeff;
+            }
+        }
+        output.y = y;
+
+        let cmp_ops = self.constraints.iter().map(|(cmp_op, _)| *cmp_op);
+        Ok(pdlp::solution(
+            output,
+            self.direction,
+            cmp_ops,
+            self.obj_offset,
+            0,
+        ))
+    }
+}
+
+/// A read-only memory mapping of the beginning of a file.
+struct Mapping {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mapping {
+    fn new(file: &File, len: usize) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        if len == 0 {
+            // Empty mappings are not allowed.
+            return Ok(Mapping {
+                ptr: std::ptr::null_mut(),
+                len,
+            });
+        }
+        // Safety: the arguments are valid and the result is checked.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping { ptr, len })
+    }
+
+    fn entries(&self) -> &[Entry] {
+        if self.len == 0 {
+            return &[];
+        }
+        // Safety: the mapping is page-aligned, covers `len` bytes written as entries and
+        // lives as long as the returned slice.
+        unsafe { std::slice::from_raw_parts(self.ptr as *const Entry, self.len / ENTRY_SIZE) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            // Safety: the pointer was returned by mmap with this length.
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+/// The mapped matrix restricted to the kept rows with `Le` rows negated and scaling
+/// applied on the fly.
+struct MappedMatrix<'a> {
+    entries: &'a [Entry],
+    indptr: &'a [usize],
+    rows: Vec<usize>,
+    row_factors: Vec<f64>,
+    col_scales: Vec<f64>,
+}
+
+impl MappedMatrix<'_> {
+    fn row(&self, k: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let r = self.rows[k];
+        let factor = self.row_factors[k];
+        self.entries[self.indptr[r]..self.indptr[r + 1]]
+            .iter()
+            .map(move |e| {
+                let col = e.col as usize;
+                (col, factor * e.val * self.col_scales[col])
+            })
+    }
+}
+
+impl LinearOperator for MappedMatrix<'_> {
+    fn apply(&self, x: &[f64], out: &mut [f64]) {
+        for (k, out) in out.iter_mut().enumerate() {
+            *out = self.row(k).map(|(c, val)| val * x[c]).sum();
+        }
+    }
+
+    fn apply_transpose(&self, y: &[f64], out: &mut [f64]) {
+        out.iter_mut().for_each(|out| *out = 0.0);
+        for (k, &y) in y.iter().enumerate() {
+            for (c, val) in self.row(k) {
+                out[c] += val * y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn same_as_explicit() {
+        let path = std::env::temp_dir().join(format!("minilp-test-{}.bin", std::process::id()));
+        let mut explicit = Problem::new(OptimizationDirection::Maximize);
+        let mut mapped = MappedProblem::create(OptimizationDirection::Maximize, &path).unwrap();
+        let vars = [
+            (3.0, (0.0, f64::INFINITY)),
+            (2.5, (0.0, f64::INFINITY)),
+            (-1.0, (f64::NEG_INFINITY, 2.0)),
+        ]
+        .iter()
+        .map(|&(obj_coeff, bounds)| {
+            let var = explicit.add_var(obj_coeff, bounds);
+            assert_eq!(mapped.add_var(obj_coeff, bounds), var);
+            var
+        })
+        .collect::<Vec<_>>();
+        let (x, y, z) = (vars[0], vars[1], vars[2]);
+        explicit.set_objective_offset(-3.0);
+        mapped.set_objective_offset(-3.0);
+        let rows = [
+            (vec![(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0),
+            (vec![(x, 1.0), (y, 3.0), (z, 1.0)], ComparisonOp::Le, 9.0),
+            (vec![(x, 1.0)], ComparisonOp::Le, 3.0),
+            (vec![(z, 2.0), (y, 1.0)], ComparisonOp::Ge, 1.0),
+            (vec![(x, 1.0), (z, -1.0)], ComparisonOp::Eq, 2.5),
+            (vec![], ComparisonOp::Ge, -1.0),
+            (vec![(y, -2.0)], ComparisonOp::Ge, -6.0),
+        ];
+        let constraints = rows
+            .iter()
+            .map(|(terms, cmp_op, rhs)| {
+                let constr = explicit.add_constraint(&terms[..], *cmp_op, *rhs);
+                let mapped_constr = mapped.add_constraint(&terms[..], *cmp_op, *rhs);
+                assert_eq!(mapped_constr.unwrap(), constr);
+                constr
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(mapped.num_nonzeros(), 11);
+
+        let exact = explicit.solve().unwrap();
+        let sol = mapped.solve(&FirstOrderOptions::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(sol.is_converged());
+        assert!(f64::abs(sol.objective() - exact.objective()) < 1e-4);
+        for &var in &vars {
+            assert!(f64::abs(sol[var] - exact[var]) < 1e-4);
+        }
+        for &constr in &constraints {
+            assert!(f64::abs(sol.dual_value(constr) - exact.dual_value(constr)) < 1e-4);
+        }
+    }
+}
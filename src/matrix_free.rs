@@ -0,0 +1,240 @@
+use crate::{
+    pdlp::{self, LinearOperator, ScaledLp},
+    ComparisonOp, ConstraintRef, FirstOrderOptions, FirstOrderSolution, OptimizationDirection,
+    Variable,
+};
+
+type MatVec<'a> = Box<dyn Fn(&[f64], &mut [f64]) + 'a>;
+
+/// A linear programming problem with the constraint matrix given implicitly by
+/// the functions computing its products with vectors.
+///
+/// This allows to solve problems with structured matrices (convolutions, Kronecker products,
+/// discretized differential operators etc.) that are too big to be stored explicitly with
+/// the [first-order method](struct.Problem.html#method.solve_first_order), which needs
+/// nothing else.
+///
+/// Variables and constraints (rows of the matrix) are added in the same way as for
+/// a [`Problem`](struct.Problem.html), except that constraints don't have coefficients.
+/// The functions passed to [`new`](#method.new) compute `out = A * x` (`out` has an element for
+/// each constraint) and `out = A^T * y` (`out` has an element for each variable); `out`
+/// contains garbage on entry and must be overwritten.
+///
+/// As the matrix entries are unknown, the problem is not scaled before solving, so
+/// the convergence depends on the scaling of the matrix more than for a `Problem`.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// // Minimize the sum of x[i] subject to x[i] + x[i + 1] >= 1 for a chain of 100 variables.
+/// let n = 100;
+/// let mut problem = MatrixFreeProblem::new(
+///     OptimizationDirection::Minimize,
+///     move |x, out| {
+///         for i in 0..n - 1 {
+///             out[i] = x[i] + x[i + 1];
+///         }
+///     },
+///     move |y, out| {
+///         for i in 0..n {
+///             out[i] = if i > 0 { y[i - 1] } else { 0.0 } + if i < n - 1 { y[i] } else { 0.0 };
+///         }
+///     },
+/// );
+/// for _ in 0..n {
+///     problem.add_var(1.0, (0.0, f64::INFINITY));
+/// }
+/// for _ in 0..n - 1 {
+///     problem.add_constraint(ComparisonOp::Ge, 1.0);
+/// }
+///
+/// let solution = problem.solve(&FirstOrderOptions::default());
+/// assert!(solution.is_converged());
+/// assert!(f64::abs(solution.objective() - 50.0) < 1e-4);
+/// ```
+pub struct MatrixFreeProblem<'a> {
+    direction: OptimizationDirection,
+    obj_coeffs: Vec<f64>,
+    var_mins: Vec<f64>,
+    var_maxs: Vec<f64>,
+    constraints: Vec<(ComparisonOp, f64)>,
+    apply: MatVec<'a>,
+    apply_transpose: MatVec<'a>,
+}
+
+impl std::fmt::Debug for MatrixFreeProblem<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MatrixFreeProblem")
+            .field("direction", &self.direction)
+            .field("num_vars", &self.obj_coeffs.len())
+            .field("num_constraints", &self.constraints.len())
+            .finish()
+    }
+}
+
+impl<'a> MatrixFreeProblem<'a> {
+    /// Create a new problem with the constraint matrix `A` given by the functions
+    /// `apply(x, out)` computing `out = A * x` and `apply_transpose(y, out)` computing
+    /// `out = A^T * y`.
+    pub fn new(
+        direction: OptimizationDirection,
+        apply: impl Fn(&[f64], &mut [f64]) + 'a,
+        apply_transpose: impl Fn(&[f64], &mut [f64]) + 'a,
+    ) -> Self {
+        MatrixFreeProblem {
+            direction,
+            obj_coeffs: vec![],
+            var_mins: vec![],
+            var_maxs: vec![],
+            constraints: vec![],
+            apply: Box::new(apply),
+            apply_transpose: Box::new(apply_transpose),
+        }
+    }
+
+    /// Add a new variable (a column of the matrix) to the problem, see
+    /// [`Problem::add_var`](struct.Problem.html#method.add_var).
+    pub fn add_var(&mut self, obj_coeff: f64, (min, max): (f64, f64)) -> Variable {
+        let var = Variable(self.obj_coeffs.len(), 0);
+        let obj_coeff = match self.direction {
+            OptimizationDirection::Minimize => obj_coeff,
+            OptimizationDirection::Maximize => -obj_coeff,
+        };
+        self.obj_coeffs.push(obj_coeff);
+        self.var_mins.push(min);
+        self.var_maxs.push(max);
+        var
+    }
+
+    /// Add a constraint `a * x cmp_op rhs` where `a` is the next row of the matrix.
+    pub fn add_constraint(&mut self, cmp_op: ComparisonOp, rhs: f64) -> ConstraintRef {
+        let constr = ConstraintRef(self.constraints.len(), 0);
+        self.constraints.push((cmp_op, rhs));
+        constr
+    }
+
+    /// Solve the problem approximately with the first-order method, see
+    /// [`Problem::solve_first_order`](struct.Problem.html#method.solve_first_order).
+    pub fn solve(&self, options: &FirstOrderOptions) -> FirstOrderSolution {
+        let row_signs = self
+            .constraints
+            .iter()
+            .map(|(cmp_op, _)| match cmp_op {
+                ComparisonOp::Le => -1.0,
+                ComparisonOp::Ge | ComparisonOp::Eq => 1.0,
+            })
+            .collect::<Vec<_>>();
+        let lp = ScaledLp {
+            obj_coeffs: self.obj_coeffs.clone(),
+            var_mins: self.var_mins.clone(),
+            var_maxs: self.var_maxs.clone(),
+            rhs: (self.constraints.iter().zip(&row_signs))
+                .map(|((_, rhs), sign)| sign * rhs)
+                .collect(),
+            is_eq: (self.constraints.iter())
+                .map(|(cmp_op, _)| *cmp_op == ComparisonOp::Eq)
+                .collect(),
+            row_scales: vec![1.0; self.constraints.len()],
+            col_scales: vec![1.0; self.obj_coeffs.len()],
+            matrix: SignedRows {
+                problem: self,
+                row_signs,
+                buf: std::cell::RefCell::new(vec![0.0; self.constraints.len()]),
+            },
+        };
+        let output = lp.solve(options);
+        let cmp_ops = self.constraints.iter().map(|(cmp_op, _)| *cmp_op);
+        pdlp::solution(output, self.direction, cmp_ops, 0.0, 0)
+    }
+}
+
+/// The user-supplied operator with `Le` rows negated.
+struct SignedRows<'a, 'b> {
+    problem: &'b MatrixFreeProblem<'a>,
+    row_signs: Vec<f64>,
+    buf: std::cell::RefCell<Vec<f64>>,
+}
+
+impl LinearOperator for SignedRows<'_, '_> {
+    fn apply(&self, x: &[f64], out: &mut [f64]) {
+        (self.problem.apply)(x, out);
+        out.iter_mut()
+            .zip(&self.row_signs)
+            .for_each(|(out, sign)| *out *= sign);
+    }
+
+    fn apply_transpose(&self, y: &[f64], out: &mut [f64]) {
+        let mut buf = self.buf.borrow_mut();
+        buf.iter_mut()
+            .zip(y.iter().zip(&self.row_signs))
+            .for_each(|(buf, (y, sign))| *buf = y * sign);
+        (self.problem.apply_transpose)(&buf, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn same_as_explicit() {
+        let rows = [
+            (vec![1.0, 1.0, 0.0], ComparisonOp::Le, 4.0),
+            (vec![1.0, 3.0, 1.0], ComparisonOp::Le, 9.0),
+            (vec![1.0, 0.0, 0.0], ComparisonOp::Le, 3.0),
+            (vec![0.0, 1.0, 2.0], ComparisonOp::Ge, 1.0),
+            (vec![1.0, 0.0, -1.0], ComparisonOp::Eq, 2.5),
+        ];
+        let vars = [
+            (3.0, (0.0, f64::INFINITY)),
+            (2.5, (0.0, f64::INFINITY)),
+            (-1.0, (f64::NEG_INFINITY, 2.0)),
+        ];
+
+        let mut explicit = Problem::new(OptimizationDirection::Maximize);
+        let mut implicit = MatrixFreeProblem::new(
+            OptimizationDirection::Maximize,
+            |x, out| {
+                for (out, (row, _, _)) in out.iter_mut().zip(&rows) {
+                    *out = row.iter().zip(x).map(|(a, x)| a * x).sum();
+                }
+            },
+            |y, out| {
+                for (c, out) in out.iter_mut().enumerate() {
+                    *out = rows.iter().zip(y).map(|((row, _, _), y)| row[c] * y).sum();
+                }
+            },
+        );
+        let vars = vars
+            .iter()
+            .map(|&(obj_coeff, bounds)| {
+                let var = explicit.add_var(obj_coeff, bounds);
+                assert_eq!(implicit.add_var(obj_coeff, bounds), var);
+                var
+            })
+            .collect::<Vec<_>>();
+        let constraints = rows
+            .iter()
+            .map(|(row, cmp_op, rhs)| {
+                let terms = vars.iter().zip(row).map(|(&var, &coeff)| (var, coeff));
+                let constr = explicit.add_constraint(terms, *cmp_op, *rhs);
+                assert_eq!(implicit.add_constraint(*cmp_op, *rhs), constr);
+                constr
+            })
+            .collect::<Vec<_>>();
+
+        let exact = explicit.solve().unwrap();
+        let sol = implicit.solve(&FirstOrderOptions::default());
+        assert!(sol.is_converged());
+        assert!(f64::abs(sol.objective() - exact.objective()) < 1e-4);
+        for &var in &vars {
+            assert!(f64::abs(sol[var] - exact[var]) < 1e-4);
+        }
+        for &constr in &constraints {
+            assert!(f64::abs(sol.dual_value(constr) - exact.dual_value(constr)) < 1e-4);
+        }
+        let polished = sol.crossover(&explicit, &SolverOptions::default()).unwrap();
+        assert_eq!(polished.objective(), exact.objective());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    sparse::{IndexType, TriangleMat},
+    Problem,
+};
+use std::io;
+
+/// Write the constraint matrix of the problem in the Matrix Market coordinate format.
+/// Rows are constraints and columns are variables, both numbered from 1.
+pub(crate) fn write_constraint_matrix(
+    problem: &Problem,
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let nnz: usize = problem.constraints.iter().map(|(c, _, _)| c.nnz()).sum();
+    writeln!(out, "%%MatrixMarket matrix coordinate real general")?;
+    writeln!(
+        out,
+        "{} {} {}",
+        problem.constraints.len(),
+        problem.obj_coeffs.len(),
+        nnz
+    )?;
+    for (constr, (coeffs, _, _)) in problem.constraints.iter().enumerate() {
+        for (var, &coeff) in coeffs.iter() {
+            writeln!(out, "{} {} {:?}", constr + 1, var + 1, coeff)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the triangular factor in the Matrix Market coordinate format, including the unit
+/// diagonal if it is not stored explicitly.
+pub(crate) fn write_triangle<I: IndexType>(
+    mat: &TriangleMat<I>,
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    writeln!(out, "%%MatrixMarket matrix coordinate real general")?;
+    writeln!(
+        out,
+        "{} {} {}",
+        mat.rows(),
+        mat.cols(),
+        mat.nondiag.nnz() + mat.cols()
+    )?;
+    for c in 0..mat.cols() {
+        let diag = mat.diag.as_ref().map_or(1.0, |diag| diag[c]);
+        writeln!(out, "{} {} {:?}", c + 1, c + 1, diag)?;
+        for (r, &val) in mat.nondiag.col_iter(c) {
+            writeln!(out, "{} {} {:?}", r + 1, c + 1, val)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn constraint_matrix() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0), (y, -2.5)], ComparisonOp::Ge, 1.0);
+        problem.add_constraint([(y, 1e-10)], ComparisonOp::Le, 1.0);
+
+        let mut out = vec![];
+        problem.write_matrix_market(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "%%MatrixMarket matrix coordinate real general\n2 3 3\n1 1 1.0\n1 2 -2.5\n2 2 1e-10\n"
+        );
+    }
+
+    #[test]
+    fn basis_factors() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Le, 6.0);
+        let solution = problem.solve().unwrap();
+
+        let (mut lower, mut upper) = (vec![], vec![]);
+        solution
+            .write_basis_factors(&mut lower, &mut upper)
+            .unwrap();
+        for factor in [lower, upper] {
+            let text = String::from_utf8(factor).unwrap();
+            let mut lines = text.lines();
+            assert_eq!(
+                lines.next(),
+                Some("%%MatrixMarket matrix coordinate real general")
+            );
+            let size: Vec<usize> = lines
+                .next()
+                .unwrap()
+                .split(' ')
+                .map(|s| s.parse().unwrap())
+                .collect();
+            assert_eq!(size[..2], [2, 2]);
+            assert_eq!(lines.count(), size[2]);
+        }
+    }
+}
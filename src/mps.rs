@@ -1,4 +1,4 @@
-use crate::{ComparisonOp, LinearExpr, OptimizationDirection, Problem, Variable};
+use crate::{ComparisonOp, ConstraintRef, LinearExpr, OptimizationDirection, Problem, Variable};
 use std::{
     collections::{HashMap, HashSet},
     io,
@@ -28,8 +28,9 @@ impl MpsFile {
     /// Parses a linear programming problem from an MPS file.
     ///
     /// This function supports the "free" MPS format, meaning that lines are tokenized based on
-    /// whitespace, not based on position. Also, because MPS lacks any way to indicate
-    /// the optimization direction, you have to supply it manually.
+    /// whitespace, not based on position. Also, because the optimization direction is
+    /// indicated only by the optional (non-standard) OBJSENSE section, you have to supply it
+    /// manually. It is overridden by the OBJSENSE section if present.
     ///
     /// # Errors
     ///
@@ -37,305 +38,536 @@ impl MpsFile {
     /// as [`std::io::Error`] with the kind set to [`InvalidData`](std::io::ErrorKind::InvalidData).
     /// Unsupported features such as integer variables are reported similarly.
     pub fn parse<R: io::BufRead>(input: R, direction: OptimizationDirection) -> io::Result<Self> {
-        // Format descriptions:
-        // Introduction: http://lpsolve.sourceforge.net/5.5/mps-format.htm
-        // More in-depth: http://cgm.cs.mcgill.ca/~avis/courses/567/cplex/reffileformatscplex.pdf
-
-        let mut lines = Lines {
-            input,
-            cur: String::new(),
-            idx: 0,
-        };
+        parse_core(input, direction).map(|(file, _)| file)
+    }
+}
 
-        let problem_name = {
-            lines.to_next()?;
-            let mut tokens = Tokens::new(&lines);
-            if tokens.next()? != "NAME" {
-                return Err(lines.err("expected NAME section"));
+/// Names of the rows of an MPS file, needed to resolve references from the SMPS files.
+pub(crate) struct MpsRows {
+    /// Name of the objective row.
+    pub(crate) obj_name: String,
+    /// Name of the RHS vector, if the RHS section is not empty.
+    pub(crate) rhs_name: Option<String>,
+    /// Rows in the file order with the constraints they became (two for ranged rows).
+    pub(crate) rows: Vec<(String, Vec<ConstraintRef>)>,
+}
+
+/// Parse the MPS file like [`MpsFile::parse`], also returning the names of the rows.
+pub(crate) fn parse_core<R: io::BufRead>(
+    input: R,
+    direction: OptimizationDirection,
+) -> io::Result<(MpsFile, MpsRows)> {
+    // Format descriptions:
+    // Introduction: http://lpsolve.sourceforge.net/5.5/mps-format.htm
+    // More in-depth: http://cgm.cs.mcgill.ca/~avis/courses/567/cplex/reffileformatscplex.pdf
+
+    let mut lines = Lines {
+        input,
+        cur: String::new(),
+        idx: 0,
+    };
+
+    let problem_name = {
+        lines.advance()?;
+        let mut tokens = Tokens::new(&lines);
+        if tokens.next()? != "NAME" {
+            return Err(lines.err("expected NAME section"));
+        }
+        tokens.iter.next().unwrap_or("").to_owned()
+    };
+
+    struct ConstraintDef {
+        name: String,
+        lhs: LinearExpr,
+        cmp_op: ComparisonOp,
+        rhs: f64,
+        range: f64,
+    }
+
+    let mut direction = direction;
+    lines.advance()?;
+    if lines.cur.starts_with("OBJSENSE") {
+        let mut tokens = Tokens::new(&lines);
+        tokens.next()?;
+        let sense = match tokens.iter.next() {
+            Some(sense) => sense.to_owned(),
+            None => {
+                lines.advance()?;
+                Tokens::new(&lines).next()?.to_owned()
             }
-            tokens.iter.next().unwrap_or("").to_owned()
         };
+        direction = match sense.as_str() {
+            "MIN" | "MINIMIZE" => OptimizationDirection::Minimize,
+            "MAX" | "MAXIMIZE" => OptimizationDirection::Maximize,
+            _ => return Err(lines.err(&format!("unknown objective sense {}", sense))),
+        };
+        lines.advance()?;
+    }
 
-        struct ConstraintDef {
-            lhs: LinearExpr,
-            cmp_op: ComparisonOp,
-            rhs: f64,
-            range: f64,
+    let mut obj_func_name = None;
+    let mut free_rows = HashSet::new();
+    let mut constraints = vec![];
+    let mut constr_name2idx = HashMap::new();
+    {
+        if lines.cur != "ROWS" {
+            return Err(lines.err("expected ROWS section"));
         }
 
-        let mut obj_func_name = None;
-        let mut free_rows = HashSet::new();
-        let mut constraints = vec![];
-        let mut constr_name2idx = HashMap::new();
-        {
-            lines.to_next()?;
-            if lines.cur != "ROWS" {
-                return Err(lines.err("expected ROWS section"));
+        loop {
+            lines.advance()?;
+            if !lines.cur.starts_with(" ") {
+                break;
             }
 
-            loop {
-                lines.to_next()?;
-                if !lines.cur.starts_with(" ") {
-                    break;
-                }
-
-                let mut tokens = Tokens::new(&lines);
-                let row_type = tokens.next()?;
-                let name = tokens.next()?;
-                let cmp_op = match row_type {
-                    "N" => {
-                        if obj_func_name.is_none() {
-                            obj_func_name = Some(name.to_owned());
-                        } else {
-                            free_rows.insert(name.to_owned());
-                        }
-                        continue;
+            let mut tokens = Tokens::new(&lines);
+            let row_type = tokens.next()?;
+            let name = tokens.next()?;
+            let cmp_op = match row_type {
+                "N" => {
+                    if obj_func_name.is_none() {
+                        obj_func_name = Some(name.to_owned());
+                    } else {
+                        free_rows.insert(name.to_owned());
                     }
-                    "L" => ComparisonOp::Le,
-                    "G" => ComparisonOp::Ge,
-                    "E" => ComparisonOp::Eq,
-                    _ => return Err(lines.err(&format!("unexpected row type {}", row_type))),
-                };
-
-                if constr_name2idx
-                    .insert(name.to_owned(), constraints.len())
-                    .is_some()
-                {
-                    return Err(lines.err(&format!("row {} already declared", name)));
+                    continue;
                 }
+                "L" => ComparisonOp::Le,
+                "G" => ComparisonOp::Ge,
+                "E" => ComparisonOp::Eq,
+                _ => return Err(lines.err(&format!("unexpected row type {}", row_type))),
+            };
 
-                constraints.push(ConstraintDef {
-                    lhs: LinearExpr::empty(),
-                    cmp_op,
-                    rhs: 0.0,
-                    range: 0.0,
-                });
+            if constr_name2idx
+                .insert(name.to_owned(), constraints.len())
+                .is_some()
+            {
+                return Err(lines.err(&format!("row {} already declared", name)));
             }
+
+            constraints.push(ConstraintDef {
+                name: name.to_owned(),
+                lhs: LinearExpr::empty(),
+                cmp_op,
+                rhs: 0.0,
+                range: 0.0,
+            });
         }
+    }
 
-        let obj_func_name = if let Some(name) = obj_func_name {
-            name
-        } else {
-            return Err(lines.err("objective function name not declared"));
-        };
+    let mut obj_offset = 0.0;
+    let obj_func_name = if let Some(name) = obj_func_name {
+        name
+    } else {
+        return Err(lines.err("objective function name not declared"));
+    };
+
+    #[derive(Default)]
+    struct VariableDef {
+        min: Option<f64>,
+        max: Option<f64>,
+        obj_coeff: f64,
+    }
 
-        #[derive(Default)]
-        struct VariableDef {
-            min: Option<f64>,
-            max: Option<f64>,
-            obj_coeff: f64,
+    let mut var_defs = vec![];
+    let mut var_name2idx = HashMap::new();
+    {
+        if lines.cur != "COLUMNS" {
+            return Err(lines.err("expected COLUMNS section"));
         }
 
-        let mut var_defs = vec![];
-        let mut var_name2idx = HashMap::new();
-        {
-            if lines.cur != "COLUMNS" {
-                return Err(lines.err("expected COLUMNS section"));
+        let mut cur_var = Variable(0, 0);
+        let mut cur_name = String::new();
+        let mut cur_def = VariableDef::default();
+        loop {
+            lines.advance()?;
+            if !lines.cur.starts_with(" ") {
+                break;
             }
 
-            let mut cur_var = Variable(0);
-            let mut cur_name = String::new();
-            let mut cur_def = VariableDef::default();
-            loop {
-                lines.to_next()?;
-                if !lines.cur.starts_with(" ") {
-                    break;
-                }
-
-                let mut tokens = Tokens::new(&lines);
-                let name = tokens.next()?;
-
-                if name != cur_name {
-                    if var_name2idx.get(name).is_some() {
-                        return Err(lines.err(&format!("variable {} already declared", name)));
-                    }
+            let mut tokens = Tokens::new(&lines);
+            let name = tokens.next()?;
 
-                    if !cur_name.is_empty() {
-                        var_name2idx.insert(std::mem::take(&mut cur_name), cur_var);
-                        var_defs.push(std::mem::take(&mut cur_def));
-                        cur_var.0 += 1;
-                    }
-                    cur_name = name.to_owned();
+            if name != cur_name {
+                if var_name2idx.contains_key(name) {
+                    return Err(lines.err(&format!("variable {} already declared", name)));
                 }
 
-                for (key, val) in KVPairs::parse(&mut tokens)?.iter() {
-                    if key == obj_func_name {
-                        cur_def.obj_coeff = val;
-                    } else if let Some(idx) = constr_name2idx.get(key) {
-                        constraints[*idx].lhs.add(cur_var, val);
-                    } else if free_rows.get(key).is_none() {
-                        return Err(lines.err(&format!("unknown constraint: {}", key)));
-                    }
+                if !cur_name.is_empty() {
+                    var_name2idx.insert(std::mem::take(&mut cur_name), cur_var);
+                    var_defs.push(std::mem::take(&mut cur_def));
+                    cur_var.0 += 1;
                 }
+                cur_name = name.to_owned();
             }
 
-            if !cur_name.is_empty() {
-                var_name2idx.insert(std::mem::take(&mut cur_name), cur_var);
-                var_defs.push(std::mem::take(&mut cur_def));
+            for (key, val) in KVPairs::parse(&mut tokens)?.iter() {
+                if key == obj_func_name {
+                    cur_def.obj_coeff = val;
+                } else if let Some(idx) = constr_name2idx.get(key) {
+                    constraints[*idx].lhs.add(cur_var, val);
+                } else if !free_rows.contains(key) {
+                    return Err(lines.err(&format!("unknown constraint: {}", key)));
+                }
             }
         }
 
-        {
-            if lines.cur != "RHS" {
-                return Err(lines.err("expected RHS section"));
-            }
+        if !cur_name.is_empty() {
+            var_name2idx.insert(std::mem::take(&mut cur_name), cur_var);
+            var_defs.push(std::mem::take(&mut cur_def));
+        }
+    }
 
-            let mut cur_vec_name = None;
-            loop {
-                lines.to_next()?;
-                if !lines.cur.starts_with(" ") {
-                    break;
-                }
+    let rhs_name;
+    {
+        if lines.cur != "RHS" {
+            return Err(lines.err("expected RHS section"));
+        }
 
-                let mut tokens = Tokens::new(&lines);
-                let vec_name = tokens.next()?;
+        let mut cur_vec_name = None;
+        loop {
+            lines.advance()?;
+            if !lines.cur.starts_with(" ") {
+                break;
+            }
 
-                if cur_vec_name.is_none() {
-                    cur_vec_name = Some(vec_name.to_owned());
-                } else if cur_vec_name.as_deref() != Some(vec_name) {
-                    // use only the first RHS vector
-                    continue;
-                }
+            let mut tokens = Tokens::new(&lines);
+            let vec_name = tokens.next()?;
 
-                for (key, val) in KVPairs::parse(&mut tokens)?.iter() {
-                    if key == obj_func_name {
-                        return Err(lines.err("setting objective in RHS section is not supported"));
-                    } else if let Some(idx) = constr_name2idx.get(key) {
-                        constraints[*idx].rhs = val;
-                    } else {
-                        return Err(lines.err(&format!("unknown constraint: {}", key)));
+            if cur_vec_name.is_none() {
+                cur_vec_name = Some(vec_name.to_owned());
+            } else if cur_vec_name.as_deref() != Some(vec_name) {
+                // use only the first RHS vector
+                continue;
+            }
+
+            for (key, val) in KVPairs::parse(&mut tokens)?.iter() {
+                if key == obj_func_name {
+                    // By convention, the right-hand side of the objective row is minus
+                    // the constant term of the objective.
+                    if !val.is_finite() {
+                        return Err(lines.err(&format!("invalid objective offset: {}", -val)));
                     }
+                    obj_offset = -val;
+                } else if let Some(idx) = constr_name2idx.get(key) {
+                    constraints[*idx].rhs = val;
+                } else {
+                    return Err(lines.err(&format!("unknown constraint: {}", key)));
                 }
             }
         }
+        rhs_name = cur_vec_name;
+    }
 
-        if lines.cur == "RANGES" {
-            let mut cur_vec_name = None;
-            loop {
-                lines.to_next()?;
-                if !lines.cur.starts_with(" ") {
-                    break;
-                }
+    if lines.cur == "RANGES" {
+        let mut cur_vec_name = None;
+        loop {
+            lines.advance()?;
+            if !lines.cur.starts_with(" ") {
+                break;
+            }
 
-                let mut tokens = Tokens::new(&lines);
+            let mut tokens = Tokens::new(&lines);
 
-                let vec_name = tokens.next()?;
-                if cur_vec_name.is_none() {
-                    cur_vec_name = Some(vec_name.to_owned());
-                } else if cur_vec_name.as_deref() != Some(vec_name) {
-                    // use only the first RANGES vector
-                    continue;
+            let vec_name = tokens.next()?;
+            if cur_vec_name.is_none() {
+                cur_vec_name = Some(vec_name.to_owned());
+            } else if cur_vec_name.as_deref() != Some(vec_name) {
+                // use only the first RANGES vector
+                continue;
+            }
+
+            for (key, val) in KVPairs::parse(&mut tokens)?.iter() {
+                if let Some(idx) = constr_name2idx.get(key) {
+                    constraints[*idx].range = val;
+                } else {
+                    return Err(lines.err(&format!("unknown constraint: {}", key)));
                 }
+            }
+        }
+    }
 
-                for (key, val) in KVPairs::parse(&mut tokens)?.iter() {
-                    if let Some(idx) = constr_name2idx.get(key) {
-                        constraints[*idx].range = val;
-                    } else {
-                        return Err(lines.err(&format!("unknown constraint: {}", key)));
+    if lines.cur == "BOUNDS" {
+        let mut cur_vec_name = None;
+        loop {
+            lines.advance()?;
+            if !lines.cur.starts_with(" ") {
+                break;
+            }
+
+            let mut tokens = Tokens::new(&lines);
+
+            let bound_type = tokens.next()?;
+
+            let vec_name = tokens.next()?;
+            if cur_vec_name.is_none() {
+                cur_vec_name = Some(vec_name.to_owned());
+            } else if cur_vec_name.as_deref() != Some(vec_name) {
+                // use only the first BOUNDS vector
+                continue;
+            }
+
+            let var_name = tokens.next()?;
+            let var_idx = if let Some(idx) = var_name2idx.get(var_name) {
+                idx
+            } else {
+                return Err(lines.err(&format!("unknown variable: {}", var_name)));
+            };
+            let var_def = &mut var_defs[var_idx.0];
+
+            if bound_type == "FR" {
+                var_def.min = Some(f64::NEG_INFINITY);
+                var_def.max = Some(f64::INFINITY);
+            } else if bound_type == "MI" {
+                var_def.min = Some(f64::NEG_INFINITY);
+            } else if bound_type == "PL" {
+                var_def.max = Some(f64::INFINITY);
+            } else {
+                let val = parse_f64(tokens.next()?, lines.idx)?;
+                match bound_type {
+                    "LO" => var_def.min = Some(val),
+                    "UP" => var_def.max = Some(val),
+                    "FX" => {
+                        var_def.min = Some(val);
+                        var_def.max = Some(val);
+                    }
+                    _ => {
+                        return Err(
+                            lines.err(&format!("bound type {} is not supported", bound_type))
+                        );
                     }
                 }
             }
         }
+    }
 
-        if lines.cur == "BOUNDS" {
-            let mut cur_vec_name = None;
-            loop {
-                lines.to_next()?;
-                if !lines.cur.starts_with(" ") {
-                    break;
-                }
+    if lines.cur != "ENDATA" {
+        return Err(lines.err("expected ENDATA section"));
+    }
 
-                let mut tokens = Tokens::new(&lines);
+    let mut problem = Problem::new(direction);
+    problem.set_objective_offset(obj_offset);
 
-                let bound_type = tokens.next()?;
+    for var_def in &var_defs {
+        let (min, max) = match (var_def.min, var_def.max) {
+            (Some(min), Some(max)) => (min, max),
+            (Some(min), None) => (min, f64::INFINITY),
+            (None, Some(max)) if max < 0.0 => (f64::NEG_INFINITY, max),
+            (None, Some(max)) => (0.0, max),
+            (None, None) => (0.0, f64::INFINITY),
+        };
+        problem.try_add_var(var_def.obj_coeff, (min, max))?;
+    }
+    for (name, &var) in &var_name2idx {
+        problem.set_var_name(var, name.as_str());
+    }
 
-                let vec_name = tokens.next()?;
-                if cur_vec_name.is_none() {
-                    cur_vec_name = Some(vec_name.to_owned());
-                } else if cur_vec_name.as_deref() != Some(vec_name) {
-                    // use only the first BOUNDS vector
-                    continue;
-                }
+    let mut rows = vec![];
+    for constr in constraints {
+        let refs = if constr.range == 0.0 {
+            vec![problem.try_add_constraint(constr.lhs, constr.cmp_op, constr.rhs)?]
+        } else {
+            let (min, max) = match constr.cmp_op {
+                ComparisonOp::Ge => (constr.rhs, constr.rhs + constr.range.abs()),
+                ComparisonOp::Le => (constr.rhs - constr.range.abs(), constr.rhs),
+                ComparisonOp::Eq if constr.range > 0.0 => (constr.rhs, constr.rhs + constr.range),
+                ComparisonOp::Eq => (constr.rhs + constr.range, constr.rhs),
+            };
+            vec![
+                problem.try_add_constraint(constr.lhs.clone(), ComparisonOp::Ge, min)?,
+                problem.try_add_constraint(constr.lhs, ComparisonOp::Le, max)?,
+            ]
+        };
+        rows.push((constr.name, refs));
+    }
 
-                let var_name = tokens.next()?;
-                let var_idx = if let Some(idx) = var_name2idx.get(var_name) {
-                    idx
-                } else {
-                    return Err(lines.err(&format!("unknown variable: {}", var_name)));
-                };
-                let var_def = &mut var_defs[var_idx.0];
+    let file = MpsFile {
+        problem_name,
+        variables: var_name2idx,
+        problem,
+    };
+    let rows = MpsRows {
+        obj_name: obj_func_name,
+        rhs_name,
+        rows,
+    };
+    Ok((file, rows))
+}
 
-                if bound_type == "FR" {
-                    var_def.min = Some(f64::NEG_INFINITY);
-                    var_def.max = Some(f64::INFINITY);
-                    continue;
-                } else {
-                    let val = parse_f64(tokens.next()?, lines.idx)?;
-                    match bound_type {
-                        "LO" => var_def.min = Some(val),
-                        "UP" => var_def.max = Some(val),
-                        "FX" => {
-                            var_def.min = Some(val);
-                            var_def.max = Some(val);
-                        }
-                        _ => {
-                            return Err(lines.err(&format!("bound type {} is not supported", bound_type)));
-                        }
+/// Layout of the lines of an MPS file, see [`Problem::write_mps`].
+///
+/// [`Problem::write_mps`]: struct.Problem.html#method.write_mps
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MpsFormat {
+    /// Fields start at the fixed positions. Names are at most 8 characters long.
+    Fixed,
+    /// Fields are separated by whitespace and names can be of any length.
+    Free,
+}
+
+/// Write the problem in the MPS format.
+pub(crate) fn write_mps(
+    problem: &Problem,
+    name: &str,
+    format: MpsFormat,
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let var_names = (0..problem.obj_coeffs.len())
+        .map(|v| problem.var_name_at(v))
+        .collect::<Vec<_>>();
+    let constr_names = (0..problem.constraints.len())
+        .map(|constr| format!("c{}", constr))
+        .collect::<Vec<_>>();
+    for name in var_names
+        .iter()
+        .map(|n| n.as_ref())
+        .chain(constr_names.iter().map(|n| n.as_str()))
+    {
+        let max_len = match format {
+            MpsFormat::Fixed => 8,
+            MpsFormat::Free => usize::MAX,
+        };
+        if name.is_empty() || name.len() > max_len || name.contains(char::is_whitespace) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "name `{}` can't be written in the {:?} MPS format",
+                    name, format
+                ),
+            ));
+        }
+    }
+
+    // Fields 1-6 of a line start at these positions in the fixed format.
+    let write_line = |out: &mut dyn io::Write, fields: &[&str]| -> io::Result<()> {
+        let mut line = String::new();
+        for (i, field) in fields.iter().enumerate() {
+            match format {
+                MpsFormat::Fixed => {
+                    let start = [1, 4, 14, 24, 39, 49][i];
+                    while line.len() < start {
+                        line.push(' ');
                     }
                 }
+                MpsFormat::Free => line.push(' '),
             }
+            line.push_str(field);
+        }
+        writeln!(out, "{}", line)
+    };
+
+    writeln!(out, "NAME          {}", name)?;
+    let obj_sign = match problem.direction {
+        OptimizationDirection::Minimize => 1.0,
+        OptimizationDirection::Maximize => {
+            writeln!(out, "OBJSENSE")?;
+            writeln!(out, "    MAX")?;
+            -1.0
         }
+    };
+
+    writeln!(out, "ROWS")?;
+    write_line(&mut out, &["N", "obj"])?;
+    for (name, (_, cmp_op, _)) in constr_names.iter().zip(&problem.constraints) {
+        let row_type = match cmp_op {
+            ComparisonOp::Le => "L",
+            ComparisonOp::Ge => "G",
+            ComparisonOp::Eq => "E",
+        };
+        write_line(&mut out, &[row_type, name])?;
+    }
 
-        if lines.cur != "ENDATA" {
-            return Err(lines.err("expected ENDATA section"));
+    writeln!(out, "COLUMNS")?;
+    let mut cols = vec![vec![]; var_names.len()];
+    for (constr, (coeffs, _, _)) in problem.constraints.iter().enumerate() {
+        for (v, &coeff) in coeffs.iter() {
+            cols[v].push((constr, coeff));
         }
+    }
+    for (v, col) in cols.iter().enumerate() {
+        let obj_coeff = obj_sign * problem.obj_coeffs[v];
+        // Every variable must be declared, even if it doesn't appear anywhere.
+        if obj_coeff != 0.0 || col.is_empty() {
+            write_line(
+                &mut out,
+                &["", &var_names[v], "obj", &format_f64(obj_coeff)],
+            )?;
+        }
+        for &(constr, coeff) in col {
+            let coeff = format_f64(coeff);
+            write_line(
+                &mut out,
+                &["", &var_names[v], &constr_names[constr], &coeff],
+            )?;
+        }
+    }
 
-        let mut problem = Problem::new(direction);
+    writeln!(out, "RHS")?;
+    let obj_offset = problem.objective_offset();
+    if obj_offset != 0.0 {
+        write_line(&mut out, &["", "RHS", "obj", &format_f64(-obj_offset)])?;
+    }
+    for (name, (_, _, rhs)) in constr_names.iter().zip(&problem.constraints) {
+        if *rhs != 0.0 {
+            write_line(&mut out, &["", "RHS", name, &format_f64(*rhs)])?;
+        }
+    }
 
-        for var_def in &var_defs {
-            let (min, max) = match (var_def.min, var_def.max) {
-                (Some(min), Some(max)) => (min, max),
-                (Some(min), None) => (min, f64::INFINITY),
-                (None, Some(max)) if max < 0.0 => (f64::NEG_INFINITY, max),
-                (None, Some(max)) => (0.0, max),
-                (None, None) => (0.0, f64::INFINITY),
-            };
-            problem.add_var(var_def.obj_coeff, (min, max));
+    let mut has_bounds = false;
+    for (v, (&min, &max)) in problem.var_mins.iter().zip(&problem.var_maxs).enumerate() {
+        let mut bounds = vec![];
+        if min == max {
+            bounds.push(("FX", Some(min)));
+        } else if min == f64::NEG_INFINITY && max == f64::INFINITY {
+            bounds.push(("FR", None));
+        } else {
+            if min == f64::NEG_INFINITY {
+                bounds.push(("MI", None));
+            } else if min != 0.0 || max < 0.0 {
+                // An upper bound alone that is negative would make the lower bound infinite.
+                bounds.push(("LO", Some(min)));
+            }
+            if max != f64::INFINITY {
+                bounds.push(("UP", Some(max)));
+            }
         }
 
-        for constr in constraints {
-            if constr.range == 0.0 {
-                problem.add_constraint(constr.lhs, constr.cmp_op, constr.rhs);
-            } else {
-                let (min, max) = match constr.cmp_op {
-                    ComparisonOp::Ge => (constr.rhs, constr.rhs + constr.range.abs()),
-                    ComparisonOp::Le => (constr.rhs - constr.range.abs(), constr.rhs),
-                    ComparisonOp::Eq if constr.range > 0.0 => {
-                        (constr.rhs, constr.rhs + constr.range)
-                    }
-                    ComparisonOp::Eq => (constr.rhs + constr.range, constr.rhs),
-                };
-                problem.add_constraint(constr.lhs.clone(), ComparisonOp::Ge, min);
-                problem.add_constraint(constr.lhs, ComparisonOp::Le, max);
+        for (bound_type, val) in bounds {
+            if !has_bounds {
+                writeln!(out, "BOUNDS")?;
+                has_bounds = true;
+            }
+            match val {
+                Some(val) => write_line(
+                    &mut out,
+                    &[bound_type, "BND", &var_names[v], &format_f64(val)],
+                )?,
+                None => write_line(&mut out, &[bound_type, "BND", &var_names[v]])?,
             }
         }
+    }
+    writeln!(out, "ENDATA")
+}
 
-        Ok(Self {
-            problem_name,
-            variables: var_name2idx,
-            problem,
-        })
+/// Format the number in the shortest representation that round-trips, preferring the
+/// scientific notation for very big or small numbers.
+fn format_f64(val: f64) -> String {
+    let plain = val.to_string();
+    let exp = format!("{:e}", val);
+    if exp.len() < plain.len() {
+        exp
+    } else {
+        plain
     }
 }
 
-struct Lines<R: io::BufRead> {
-    input: R,
-    cur: String,
-    idx: usize,
+pub(crate) struct Lines<R: io::BufRead> {
+    pub(crate) input: R,
+    pub(crate) cur: String,
+    pub(crate) idx: usize,
 }
 
 impl<R: io::BufRead> Lines<R> {
-    fn to_next(&mut self) -> io::Result<()> {
+    pub(crate) fn advance(&mut self) -> io::Result<()> {
         loop {
             self.idx += 1;
             self.cur.clear();
@@ -356,7 +588,7 @@ impl<R: io::BufRead> Lines<R> {
         }
     }
 
-    fn err(&self, msg: &str) -> io::Error {
+    pub(crate) fn err(&self, msg: &str) -> io::Error {
         io::Error::new(
             io::ErrorKind::InvalidData,
             format!("line {}: {}", self.idx, msg),
@@ -364,20 +596,20 @@ impl<R: io::BufRead> Lines<R> {
     }
 }
 
-struct Tokens<'a> {
-    line_idx: usize,
-    iter: std::str::SplitWhitespace<'a>,
+pub(crate) struct Tokens<'a> {
+    pub(crate) line_idx: usize,
+    pub(crate) iter: std::str::SplitWhitespace<'a>,
 }
 
 impl<'a> Tokens<'a> {
-    fn new<R: io::BufRead>(lines: &'a Lines<R>) -> Self {
+    pub(crate) fn new<R: io::BufRead>(lines: &'a Lines<R>) -> Self {
         Self {
             line_idx: lines.idx,
             iter: lines.cur.split_whitespace(),
         }
     }
 
-    fn next(&mut self) -> io::Result<&'a str> {
+    pub(crate) fn next(&mut self) -> io::Result<&'a str> {
         self.iter.next().ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -387,15 +619,15 @@ impl<'a> Tokens<'a> {
     }
 }
 
-fn parse_f64(input: &str, line_idx: usize) -> io::Result<f64> {
-    input.parse().or_else(|_| {
-        Err(io::Error::new(
+pub(crate) fn parse_f64(input: &str, line_idx: usize) -> io::Result<f64> {
+    input.parse().map_err(|_| {
+        io::Error::new(
             io::ErrorKind::InvalidData,
             format!(
                 "line {}: couldn't parse float from string: `{}`",
                 line_idx, input
             ),
-        ))
+        )
     })
 }
 
@@ -467,6 +699,7 @@ ENDATA
         let file = MpsFile::parse(&mut input, OptimizationDirection::Minimize).unwrap();
         assert_eq!(file.problem_name, "TESTPROB");
         assert_eq!(file.variables.len(), 3);
+        assert_eq!(file.problem.var_name(file.variables["YTWO"]), "YTWO");
 
         let sol = file.problem.solve().unwrap();
         assert_eq!(sol[file.variables["XONE"]], 4.0);
@@ -474,4 +707,107 @@ ENDATA
         assert_eq!(sol[file.variables["ZTHREE"]], 6.0);
         assert_eq!(sol.objective(), 54.0);
     }
+
+    #[test]
+    fn invalid_values() {
+        let file = TEST_FILE.replace(
+            "RHS1      MYEQN                7",
+            "RHS1      MYEQN              NaN",
+        );
+        let err =
+            MpsFile::parse(io::Cursor::new(file), OptimizationDirection::Minimize).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "right-hand side of constraint 2 is NaN");
+
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, 1.0));
+        assert_eq!(
+            problem.try_add_var(1.0, (f64::INFINITY, f64::INFINITY)),
+            Err(crate::ModelError::InvalidBounds {
+                var: Variable(1, 0),
+                min: f64::INFINITY,
+                max: f64::INFINITY,
+            })
+        );
+        assert!(problem.try_add_var(1.0, (f64::NEG_INFINITY, 0.0)).is_ok());
+        assert_eq!(
+            problem.try_add_constraint([(x, 1.0)], ComparisonOp::Ge, f64::NEG_INFINITY),
+            Err(crate::ModelError::InvalidRhs {
+                constr: crate::ConstraintRef(0, 0),
+                rhs: f64::NEG_INFINITY,
+            })
+        );
+        assert_eq!(problem.stats().num_constraints, 0);
+    }
+
+    #[test]
+    fn write_mps_file() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (f64::NEG_INFINITY, 4.0));
+        let y = problem.add_var(2.0, (-1.0, 3.0));
+        let z = problem.add_var(0.0, (2.0, 2.0));
+        let w = problem.add_var(0.0, (f64::NEG_INFINITY, f64::INFINITY));
+        problem.set_var_name(y, "YTWO");
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, 2.0), (y, 1.0), (z, -0.5)], ComparisonOp::Ge, 1e-7);
+        problem.add_constraint([(w, 1.0)], ComparisonOp::Eq, 0.0);
+        problem.set_objective_offset(2.5);
+
+        let mut out = vec![];
+        problem
+            .write_mps("TEST", MpsFormat::Fixed, &mut out)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text,
+            "\
+NAME          TEST
+OBJSENSE
+    MAX
+ROWS
+ N  obj
+ L  c0
+ G  c1
+ E  c2
+COLUMNS
+    x0        obj       1
+    x0        c0        1
+    x0        c1        2
+    YTWO      obj       2
+    YTWO      c0        1
+    YTWO      c1        1
+    x2        c1        -0.5
+    x3        c2        1
+RHS
+    RHS       obj       -2.5
+    RHS       c0        4
+    RHS       c1        1e-7
+BOUNDS
+ MI BND       x0
+ UP BND       x0        4
+ LO BND       YTWO      -1
+ UP BND       YTWO      3
+ FX BND       x2        2
+ FR BND       x3
+ENDATA
+"
+        );
+
+        for format in [MpsFormat::Fixed, MpsFormat::Free] {
+            let mut out = vec![];
+            problem.write_mps("TEST", format, &mut out).unwrap();
+            let file = MpsFile::parse(&out[..], OptimizationDirection::Minimize).unwrap();
+            assert_eq!(file.problem_name, "TEST");
+            assert_eq!(file.problem.to_string(), problem.to_string());
+        }
+
+        problem.set_var_name(x, "LONG_NAME");
+        let err = problem
+            .write_mps("TEST", MpsFormat::Fixed, io::sink())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        problem
+            .write_mps("TEST", MpsFormat::Free, io::sink())
+            .unwrap();
+    }
 }
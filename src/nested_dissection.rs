@@ -0,0 +1,395 @@
+use crate::sparse::{IndexType, Perm};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Subsets of columns smaller than this are not split further.
+const LEAF_SIZE: usize = 64;
+/// Rows with more than `DENSE_ROW_COEFF * sqrt(size)` entries are ignored when building
+/// the column graph: they connect almost everything and would make the separators huge.
+const DENSE_ROW_COEFF: f64 = 10.0;
+/// Max number of Fiduccia-Mattheyses refinement passes per bisection.
+const MAX_FM_PASSES: usize = 4;
+/// Each part of a bisection contains at least this fraction of the subset.
+const MIN_PART_FRACTION: f64 = 0.4;
+
+/// Order columns by nested dissection of the column intersection graph (two columns are
+/// adjacent if they share a row). The graph is recursively bisected and the columns on
+/// the boundary of the bisection (the separator) are ordered after both parts, so that
+/// the fill-in of the LU factors is confined to the diagonal blocks and the separators.
+///
+/// Each bisection starts from the breadth-first order of the subset and is refined by
+/// the Fiduccia-Mattheyses heuristic. This is slower than the default ordering, but
+/// for large matrices with a mesh-like structure the fill-in can be much smaller.
+pub fn order_nested_dissection<'a, I: IndexType>(
+    size: usize,
+    get_col: impl Fn(usize) -> &'a [I],
+) -> Perm {
+    let graph = ColGraph::new(size, get_col);
+    let mut dissection = Dissection {
+        label: vec![0; size],
+        side: vec![0; size],
+        gain: vec![0; size],
+        is_locked: vec![false; size],
+        next_label: 1,
+        new2orig: Vec::with_capacity(size),
+        graph,
+    };
+    dissection.dissect((0..size).collect());
+    Perm::from_new2orig(dissection.new2orig).unwrap()
+}
+
+/// Column intersection graph of a square matrix stored as the column and row patterns.
+struct ColGraph {
+    col_begins: Vec<usize>,
+    col_rows: Vec<usize>,
+    row_begins: Vec<usize>,
+    row_cols: Vec<usize>,
+}
+
+impl ColGraph {
+    fn new<'a, I: IndexType>(size: usize, get_col: impl Fn(usize) -> &'a [I]) -> ColGraph {
+        let mut row_begins = vec![0; size + 1];
+        for c in 0..size {
+            for r in get_col(c).iter().map(|r| r.index()) {
+                row_begins[r + 1] += 1;
+            }
+        }
+
+        let max_row_len = usize::max(16, (DENSE_ROW_COEFF * (size as f64).sqrt()) as usize);
+        let is_dense_row: Vec<bool> = (0..size).map(|r| row_begins[r + 1] > max_row_len).collect();
+        for r in 0..size {
+            row_begins[r + 1] = row_begins[r]
+                + if is_dense_row[r] {
+                    0
+                } else {
+                    row_begins[r + 1]
+                };
+        }
+
+        let mut col_begins = Vec::with_capacity(size + 1);
+        col_begins.push(0);
+        let mut col_rows = vec![];
+        let mut row_cols = vec![0; row_begins[size]];
+        let mut row_ends = row_begins.clone();
+        for c in 0..size {
+            for r in get_col(c).iter().map(|r| r.index()) {
+                if !is_dense_row[r] {
+                    col_rows.push(r);
+                    row_cols[row_ends[r]] = c;
+                    row_ends[r] += 1;
+                }
+            }
+            col_begins.push(col_rows.len());
+        }
+
+        ColGraph {
+            col_begins,
+            col_rows,
+            row_begins,
+            row_cols,
+        }
+    }
+
+    fn degree(&self, c: usize) -> usize {
+        self.col_begins[c + 1] - self.col_begins[c]
+    }
+
+    /// Neighbors of the column `c`, one for each shared row (so a column sharing
+    /// several rows with `c` is repeated).
+    fn neighbors(&self, c: usize) -> impl Iterator<Item = usize> + '_ {
+        self.col_rows[self.col_begins[c]..self.col_begins[c + 1]]
+            .iter()
+            .flat_map(move |&r| &self.row_cols[self.row_begins[r]..self.row_begins[r + 1]])
+            .copied()
+            .filter(move |&n| n != c)
+    }
+}
+
+/// Label of the columns that are already ordered.
+const ORDERED: usize = usize::MAX;
+
+struct Dissection {
+    graph: ColGraph,
+    /// Columns of the subset that is currently processed share the label.
+    label: Vec<usize>,
+    /// Side of the bisection (0 or 1) of the columns of the current subset.
+    side: Vec<u8>,
+    /// Decrease of the cut if the column is moved to the other side.
+    gain: Vec<i64>,
+    is_locked: Vec<bool>,
+    next_label: usize,
+    new2orig: Vec<usize>,
+}
+
+impl Dissection {
+    fn dissect(&mut self, cols: Vec<usize>) {
+        if cols.len() <= LEAF_SIZE {
+            self.push_ordered(cols);
+            return;
+        }
+
+        self.bisect(&cols);
+
+        // Columns of the smaller boundary form the separator.
+        let label = self.label[cols[0]];
+        let mut boundaries = [vec![], vec![]];
+        for &c in &cols {
+            let side = self.side[c];
+            let graph = &self.graph;
+            let is_boundary = graph
+                .neighbors(c)
+                .any(|n| self.label[n] == label && self.side[n] != side);
+            if is_boundary {
+                boundaries[side as usize].push(c);
+            }
+        }
+        let sep_side = if boundaries[0].len() <= boundaries[1].len() {
+            0
+        } else {
+            1
+        };
+        let separator = std::mem::take(&mut boundaries[sep_side]);
+        for &c in &separator {
+            self.label[c] = ORDERED;
+        }
+
+        let mut parts = [vec![], vec![]];
+        for &c in &cols {
+            if self.label[c] != ORDERED {
+                parts[self.side[c] as usize].push(c);
+            }
+        }
+        if parts.iter().any(|part| part.is_empty()) {
+            // The subset can't be split (e.g. it is a clique).
+            for &c in &cols {
+                self.label[c] = label;
+            }
+            self.push_ordered(cols);
+            return;
+        }
+
+        for part in &parts {
+            for &c in part {
+                self.label[c] = self.next_label;
+            }
+            self.next_label += 1;
+        }
+        let [part0, part1] = parts;
+        self.dissect(part0);
+        self.dissect(part1);
+        self.push_ordered(separator);
+    }
+
+    fn push_ordered(&mut self, mut cols: Vec<usize>) {
+        let graph = &self.graph;
+        cols.sort_unstable_by_key(|&c| (graph.degree(c), c));
+        for &c in &cols {
+            self.label[c] = ORDERED;
+        }
+        self.new2orig.extend(cols);
+    }
+
+    /// Breadth-first order of the subset starting from `start`. Components not reachable
+    /// from `start` follow in the order of their first columns in `cols`.
+    fn bfs_order(&mut self, cols: &[usize], start: usize) -> Vec<usize> {
+        let label = self.label[start];
+        for &c in cols {
+            self.is_locked[c] = false;
+        }
+
+        let mut order = Vec::with_capacity(cols.len());
+        for root in std::iter::once(start).chain(cols.iter().copied()) {
+            if self.is_locked[root] {
+                continue;
+            }
+            self.is_locked[root] = true;
+            order.push(root);
+
+            let mut i = order.len() - 1;
+            while i < order.len() {
+                let c = order[i];
+                i += 1;
+                for n in self.graph.neighbors(c) {
+                    if self.label[n] == label && !self.is_locked[n] {
+                        self.is_locked[n] = true;
+                        order.push(n);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// Split the subset into two parts with a small number of edges between them.
+    fn bisect(&mut self, cols: &[usize]) {
+        // Start from a pseudo-peripheral column so that the BFS levels are narrow.
+        let first_order = self.bfs_order(cols, cols[0]);
+        let order = self.bfs_order(cols, *first_order.last().unwrap());
+        let half = cols.len() / 2;
+        for (i, &c) in order.iter().enumerate() {
+            self.side[c] = if i < half { 0 } else { 1 };
+        }
+
+        let min_part_len = (MIN_PART_FRACTION * cols.len() as f64) as usize;
+        for _ in 0..MAX_FM_PASSES {
+            if !self.refine_pass(cols, min_part_len) {
+                break;
+            }
+        }
+    }
+
+    /// One pass of the Fiduccia-Mattheyses heuristic: greedily move the columns with
+    /// the biggest gain to the other side (each at most once, even if the gain is negative)
+    /// and keep the best prefix of the moves. Returns true if the cut was decreased.
+    fn refine_pass(&mut self, cols: &[usize], min_part_len: usize) -> bool {
+        let label = self.label[cols[0]];
+        let mut part_lens = [0, 0];
+        let mut heaps = [BinaryHeap::new(), BinaryHeap::new()];
+        for &c in cols {
+            let side = self.side[c];
+            let mut gain = 0;
+            for n in self.graph.neighbors(c) {
+                if self.label[n] == label {
+                    gain += if self.side[n] == side { -1 } else { 1 };
+                }
+            }
+            self.gain[c] = gain;
+            self.is_locked[c] = false;
+            part_lens[side as usize] += 1;
+            heaps[side as usize].push((gain, Reverse(c)));
+        }
+
+        let mut moves = vec![];
+        let mut cut_change = 0;
+        let mut best_cut_change = 0;
+        let mut best_num_moves = 0;
+        loop {
+            // Find the best allowed move from each side.
+            let mut best: Option<(i64, usize)> = None;
+            for side in 0..2 {
+                if part_lens[side] <= min_part_len {
+                    continue;
+                }
+                let heap = &mut heaps[side];
+                while let Some(&(gain, Reverse(c))) = heap.peek() {
+                    if self.is_locked[c] || self.gain[c] != gain || self.side[c] as usize != side {
+                        heap.pop();
+                        continue;
+                    }
+                    if best.is_none_or(|(best_gain, _)| gain > best_gain) {
+                        best = Some((gain, c));
+                    }
+                    break;
+                }
+            }
+            let (gain, c) = match best {
+                Some(best) => best,
+                None => break,
+            };
+
+            let from = self.side[c];
+            let to = 1 - from;
+            self.side[c] = to;
+            self.is_locked[c] = true;
+            part_lens[from as usize] -= 1;
+            part_lens[to as usize] += 1;
+            cut_change -= gain;
+            moves.push(c);
+            if cut_change < best_cut_change {
+                best_cut_change = cut_change;
+                best_num_moves = moves.len();
+            }
+
+            for n in self.graph.neighbors(c) {
+                if self.label[n] != label || self.is_locked[n] {
+                    continue;
+                }
+                self.gain[n] += if self.side[n] == to { -2 } else { 2 };
+                heaps[self.side[n] as usize].push((self.gain[n], Reverse(n)));
+            }
+        }
+
+        for &c in &moves[best_num_moves..] {
+            self.side[c] = 1 - self.side[c];
+        }
+        best_num_moves > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lu::{lu_factorize, ScratchSpace};
+    use rand::prelude::*;
+    use sprs::{CsMat, TriMat};
+
+    /// Matrix of the 5-point stencil on a square grid.
+    fn grid_matrix(side: usize) -> CsMat<f64> {
+        let size = side * side;
+        let mut mat = TriMat::new((size, size));
+        for i in 0..side {
+            for j in 0..side {
+                let c = i * side + j;
+                mat.add_triplet(c, c, 4.0);
+                if i > 0 {
+                    mat.add_triplet(c - side, c, -1.0);
+                }
+                if i + 1 < side {
+                    mat.add_triplet(c + side, c, -1.0);
+                }
+                if j > 0 {
+                    mat.add_triplet(c - 1, c, -1.0);
+                }
+                if j + 1 < side {
+                    mat.add_triplet(c + 1, c, -1.0);
+                }
+            }
+        }
+        mat.to_csc()
+    }
+
+    #[test]
+    fn path() {
+        // Columns of a tridiagonal matrix form a path, so the separators are short.
+        let size: usize = 200;
+        let rows: Vec<Vec<usize>> = (0..size)
+            .map(|c| (c.saturating_sub(1)..usize::min(c + 2, size)).collect())
+            .collect();
+        let perm = order_nested_dissection(size, |c| &rows[c]);
+        assert!(perm.is_valid());
+        assert_eq!(perm.len(), size);
+
+        // The top-level separator is ordered last and splits the path in the middle.
+        let last = *perm.new2orig().last().unwrap();
+        assert!((50..150).contains(&last));
+
+        assert!(order_nested_dissection(0, |c| &rows[c]).is_empty());
+    }
+
+    #[test]
+    fn grid_fill_in() {
+        let side = 30;
+        let size = side * side;
+        let mat = grid_matrix(side);
+        let get_col = |c: usize| mat.outer_view(c).unwrap().into_raw_storage();
+
+        let perm = order_nested_dissection(size, |c| get_col(c).0);
+        assert!(perm.is_valid());
+
+        let mut scratch = ScratchSpace::with_capacity(size);
+        let lu = lu_factorize::<usize, _>(size, get_col, Some(perm), 0.1, &mut scratch).unwrap();
+        let lu_natural =
+            lu_factorize::<u32, _>(size, get_col, Some(Perm::identity(size)), 0.1, &mut scratch)
+                .unwrap();
+        assert!(lu.nnz() < lu_natural.nnz());
+
+        let mut rng = rand_pcg::Pcg64::seed_from_u64(3);
+        let rhs: Vec<f64> = (0..size).map(|_| rng.gen_range(-1.0, 1.0)).collect();
+        let mut sol = rhs.clone();
+        lu.solve_dense(&mut sol, &mut scratch);
+        let prod = &mat * &ndarray::Array1::from(sol);
+        for (r, &val) in rhs.iter().enumerate() {
+            assert!(f64::abs(prod[r] - val) < 1e-8);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{solver::EPS, Error, ErrorKind, Problem, Solver, SolverOptions, Variable};
+
+/// Options of the optimization-based bound tightening, see
+/// [`Problem::tighten_bounds`](struct.Problem.html#method.tighten_bounds).
+#[derive(Clone, Debug)]
+pub struct BoundTighteningOptions {
+    /// Max number of LP solves, at most two per variable. Bounds that are proven tight by
+    /// a solution found earlier don't need a solve.
+    pub max_solves: usize,
+    /// Max total number of simplex iterations, including the initial solve. The budget is
+    /// checked between the solves, so the last solve can exceed it.
+    pub max_iterations: usize,
+    /// A bound is changed only if it is tightened by more than this.
+    pub min_improvement: f64,
+    /// Options used to solve the problems. The objective cutoff and the relative gap
+    /// tolerance are ignored, as every solve must reach the optimum.
+    pub solver_options: SolverOptions,
+}
+
+impl Default for BoundTighteningOptions {
+    fn default() -> Self {
+        BoundTighteningOptions {
+            max_solves: usize::MAX,
+            max_iterations: usize::MAX,
+            min_improvement: 1e-6,
+            solver_options: SolverOptions::default(),
+        }
+    }
+}
+
+pub(crate) fn tighten_bounds(
+    problem: &mut Problem,
+    vars: &[Variable],
+    options: &BoundTighteningOptions,
+) -> Result<usize, Error> {
+    let vars: Vec<usize> = vars.iter().map(|&var| problem.var_idx(var)).collect();
+    let num_vars = problem.obj_coeffs.len();
+    let solver_options = SolverOptions {
+        objective_cutoff: None,
+        relative_gap: None,
+        ..options.solver_options.clone()
+    };
+
+    // Any feasible point will do for the start.
+    let mut solver = Solver::try_new_with_options(
+        &vec![0.0; num_vars],
+        &problem.var_mins,
+        &problem.var_maxs,
+        &problem.constraints,
+        None,
+        &solver_options,
+    )
+    .map_err(|err| err.in_generation(problem.generation))?;
+    solver
+        .initial_solve()
+        .map_err(|err| err.in_generation(problem.generation))?;
+
+    let mut num_solves = 0;
+    let mut num_tightened = 0;
+    'vars: for &v in &vars {
+        for &is_max in &[false, true] {
+            let bound = if is_max {
+                problem.var_maxs[v]
+            } else {
+                problem.var_mins[v]
+            };
+            // The bound is attained by the current solution and can't be tightened.
+            if *solver.get_value(v) == bound {
+                continue;
+            }
+            if num_solves >= options.max_solves || solver.stats.iterations >= options.max_iterations
+            {
+                break 'vars;
+            }
+            num_solves += 1;
+
+            // Each solve is warm-started from the optimal basis of the previous one.
+            let mut obj_coeffs = vec![0.0; num_vars];
+            obj_coeffs[v] = if is_max { -1.0 } else { 1.0 };
+            let mut next = solver.clone();
+            match next.set_obj_coeffs(&obj_coeffs) {
+                Ok(()) => solver = next,
+                Err(err) if err.kind() == ErrorKind::Unbounded => continue,
+                Err(err) => return Err(err.in_generation(problem.generation)),
+            }
+
+            // The optimum is only feasible up to the tolerance and has a rounding error, so
+            // the new bound is relaxed by the tolerance to not cut off feasible points.
+            let val = *solver.get_value(v);
+            let tol = EPS * val.abs().max(1.0);
+            if is_max && val < problem.var_maxs[v] - options.min_improvement {
+                problem.var_maxs[v] = val + tol;
+                num_tightened += 1;
+            } else if !is_max && val > problem.var_mins[v] + options.min_improvement {
+                problem.var_mins[v] = val - tol;
+                num_tightened += 1;
+            }
+        }
+    }
+    Ok(num_tightened)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn tighten() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+        let z = problem.add_var(-1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, 1.0), (y, -1.0)], ComparisonOp::Ge, -2.0);
+        problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+        problem.add_constraint([(x, 1.0), (z, -1.0)], ComparisonOp::Le, 0.0);
+
+        // The budget runs out before any bound is tightened.
+        let mut budgeted = problem.clone();
+        let options = BoundTighteningOptions {
+            max_solves: 0,
+            ..Default::default()
+        };
+        assert_eq!(budgeted.tighten_bounds(&[x, y, z], &options), Ok(0));
+        assert_eq!(budgeted.to_string(), problem.to_string());
+
+        // x + y <= 4 gives x <= 4 and together with y <= x + 2 gives y <= 3.
+        // The lower bounds are attained and z is unbounded above.
+        let options = BoundTighteningOptions::default();
+        // The new bounds are relaxed by the feasibility tolerance.
+        assert_eq!(problem.tighten_bounds(&[x, y, z], &options), Ok(2));
+        assert_eq!(problem.var_mins, [0.0, 0.0, 0.0]);
+        assert!(problem.var_maxs[0] > 4.0 && problem.var_maxs[0] < 4.0 + 1e-7);
+        assert!(problem.var_maxs[1] > 3.0 && problem.var_maxs[1] < 3.0 + 1e-7);
+        assert_eq!(problem.var_maxs[2], f64::INFINITY);
+        assert!(f64::abs(problem.solve().unwrap().objective() - 3.0) < 1e-7);
+
+        problem.add_constraint([(z, 1.0)], ComparisonOp::Le, -1.0);
+        let err = problem.tighten_bounds(&[x], &options).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Infeasible);
+    }
+
+    #[test]
+    fn inexact_optimum() {
+        // The bounds of x are 1/3 and 2/3, which are not exactly representable.
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (-10.0, 10.0));
+        let y = problem.add_var(0.0, (0.0, 1.0));
+        problem.add_constraint([(x, 3.0), (y, -1.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint([(x, 3.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+
+        let options = BoundTighteningOptions::default();
+        assert_eq!(problem.tighten_bounds(&[x], &options), Ok(2));
+        let (min, max) = (problem.var_mins[0], problem.var_maxs[0]);
+        assert!(min < 1.0 / 3.0 && min > 1.0 / 3.0 - 1e-7);
+        assert!(max > 2.0 / 3.0 && max < 2.0 / 3.0 + 1e-7);
+
+        // The extreme point is still feasible.
+        let solution = problem.solve().unwrap();
+        assert!(f64::abs(solution[x] - 1.0 / 3.0) < 1e-8);
+        assert!(f64::abs(solution[y] - 1.0) < 1e-8);
+    }
+}
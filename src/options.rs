@@ -0,0 +1,220 @@
+use crate::{Perm, ProgressCallback, SolveLog};
+
+/// Options controlling the solution process.
+///
+/// Use with [`Problem::solve_with`]. Default options are used by [`Problem::solve`].
+///
+/// [`Problem::solve_with`]: struct.Problem.html#method.solve_with
+/// [`Problem::solve`]: struct.Problem.html#method.solve
+#[derive(Clone, Debug, Default)]
+pub struct SolverOptions {
+    /// Whether to form and solve the dual problem instead of the original one.
+    pub dualization: Dualization,
+    /// Simplex algorithm used to find the first feasible solution.
+    pub algorithm: Algorithm,
+    /// Procedure used to avoid stalling and cycling on degenerate problems.
+    pub anti_degeneracy: AntiDegeneracy,
+    /// Strategy for choosing the entering variable in the primal simplex.
+    pub pricing: Pricing,
+    /// If present, candidates with equal pricing scores are chosen pseudo-randomly using
+    /// a generator with this seed. Otherwise ties are broken by index. Random tie-breaking
+    /// avoids pathological pivot sequences on some structured problems, and results are
+    /// still reproducible for a fixed seed.
+    pub random_seed: Option<u64>,
+    /// Scaling of the constraint matrix applied before solving.
+    pub scaling: ScalingMode,
+    /// Floating-point accuracy of the critical computations.
+    pub accuracy: Accuracy,
+    /// Max number of threads used for the parallelizable passes over the constraint matrix
+    /// (currently scaling, the pricing scan of the full pricing and the presolve row passes
+    /// of the `_with` presolve methods of [`Problem`](struct.Problem.html)) and for the batch
+    /// solves (see [`Problem::solve_rhs_batch`](struct.Problem.html#method.solve_rhs_batch)).
+    /// Values 0 and 1 mean that everything runs in the calling thread.
+    /// The results don't depend on the number of threads.
+    pub threads: usize,
+    /// Order in which the columns of the basis matrix are factorized.
+    pub basis_ordering: BasisOrdering,
+    /// If present, the primal simplex stops as soon as the estimated relative gap between
+    /// the objective value and the optimal one is at most this value. The solution is then
+    /// feasible, but not necessarily optimal, see
+    /// [`Solution::is_optimal`](struct.Solution.html#method.is_optimal). This is useful
+    /// when the optimal value is needed only as a bound, e.g. inside a larger algorithm.
+    ///
+    /// The gap is estimated with the Lagrangian bound given by the current dual values and
+    /// the variable bounds implied by the constraints, and is checked every few iterations.
+    /// Solves that are finished by the dual simplex (e.g. if all variables are bounded)
+    /// always end at the optimum.
+    pub relative_gap: Option<f64>,
+    /// If present, the solve is aborted with an [`ErrorKind::Cutoff`] error as soon as it is
+    /// proven that the optimal objective value is not better than this value (greater or
+    /// equal when minimizing, less or equal when maximizing). This is the pruning mechanism
+    /// of branch-and-bound: pass the objective value of the best known solution. To apply
+    /// the cutoff to the reoptimization of a [`Solution`], use
+    /// [`Solution::set_objective_cutoff`].
+    ///
+    /// [`ErrorKind::Cutoff`]: enum.ErrorKind.html#variant.Cutoff
+    /// [`Solution`]: struct.Solution.html
+    /// [`Solution::set_objective_cutoff`]: struct.Solution.html#method.set_objective_cutoff
+    pub objective_cutoff: Option<f64>,
+    /// If true, after the optimum is found the solution is moved to the lexicographically
+    /// smallest optimal vertex: among all optimal solutions the one with the smallest value
+    /// of the first variable (in the order in which variables were added) is chosen, then
+    /// of the second one and so on. The returned solution then doesn't depend on the pivot
+    /// sequence (and thus on the other options) even if the problem has alternative optima,
+    /// at the price of additional simplex iterations. Has no effect if the solve was stopped
+    /// early because of the [`relative_gap`](#structfield.relative_gap) tolerance.
+    pub lexicographic_optimum: bool,
+    /// If present, internal errors of the solver (which are reported by panicking, e.g. when
+    /// the basis matrix becomes singular) are caught by
+    /// [`Problem::solve_with`](struct.Problem.html#method.solve_with) and a reproducer
+    /// is written to a new file in this directory before the panic is resumed. The file
+    /// contains the problem in the LP format, with the error message, these options and
+    /// the basis at the moment of the failure in the comments. Its path is appended to
+    /// the panic message, so that it can be attached to a bug report.
+    pub failure_dump_dir: Option<std::path::PathBuf>,
+    /// If present, called periodically during the solve with an estimate of its progress.
+    pub progress: Option<ProgressCallback>,
+    /// If present, solver events are written to this machine-readable log.
+    pub solve_log: Option<SolveLog>,
+}
+
+/// Specifies when the solver should solve the dual problem instead of the original one.
+///
+/// The cost of simplex iterations depends mostly on the number of constraints, so solving
+/// the dual problem is beneficial for problems with many more constraints than variables.
+/// The solution of the original problem is recovered from the dual values and then polished
+/// by a (usually short) run of the primal simplex method.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dualization {
+    /// Solve the dual problem if the problem is large and the dual problem has
+    /// far fewer constraints.
+    Auto,
+    /// Always solve the dual problem.
+    Always,
+    /// Never solve the dual problem.
+    #[default]
+    Never,
+}
+
+/// Specifies the simplex algorithm used to find the first feasible solution.
+///
+/// The choice matters only when the starting basis (all variables at their bounds or at
+/// the starting point) is neither primal nor dual feasible. After a feasible solution is found,
+/// it is improved with the primal simplex method.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Choose the algorithm based on the starting point: the primal simplex is used if the
+    /// solver was started from a point (e.g. when solving the dual problem) or if only a few
+    /// constraints are violated, the dual simplex otherwise.
+    #[default]
+    Auto,
+    /// Primal simplex minimizing the sum of infeasibilities. This method keeps the starting
+    /// point close and is preferable when it is almost feasible.
+    Primal,
+    /// Dual simplex with an artificial objective function.
+    Dual,
+}
+
+/// Specifies how the solver deals with degeneracy, i.e. iterations that don't change
+/// the objective value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AntiDegeneracy {
+    /// Switch to the Bland's pivoting rule that is guaranteed to terminate after a long
+    /// sequence of degenerate iterations is detected.
+    #[default]
+    Bland,
+    /// The EXPAND procedure of Gill et al.: the feasibility tolerance of the primal
+    /// ratio test is slowly increased, which allows to make a small positive step on each
+    /// iteration. Periodically and at the end of the solve the tolerance is reset
+    /// and the solution is cleaned up. The dual simplex still uses the Bland's rule.
+    Expand,
+}
+
+/// Specifies which columns are examined when choosing the entering variable in the primal
+/// simplex.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pricing {
+    /// Examine all columns and choose the best one.
+    #[default]
+    Full,
+    /// Examine only a segment of columns, keeping the attractive ones in a candidate list.
+    /// The entering variable is chosen from the list until it runs dry, after which the next
+    /// segment is scanned. This reduces the cost of an iteration on problems with a lot of
+    /// variables at the price of a (usually moderately) bigger number of iterations.
+    Partial,
+    /// Choose several attractive candidates at once and perform a sequence of cheap minor
+    /// iterations among them, updating only their columns. Obj. coeffs of all variables
+    /// are recalculated only after the minor iterations.
+    Multiple,
+}
+
+/// Specifies how the rows and columns of the constraint matrix are scaled before solving.
+///
+/// Scaling decreases the spread of the coefficient magnitudes, which improves numerical
+/// stability on badly scaled problems. The solution is reported in the original units.
+/// Scale factors are powers of 2, so scaling itself doesn't introduce rounding errors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// Don't scale the problem.
+    #[default]
+    None,
+    /// Scale rows and then columns so that the biggest abs. value in each of them is 1.
+    Equilibration,
+    /// Repeatedly scale rows and columns so that the geometric mean of the smallest and
+    /// the biggest abs. values in each of them is 1.
+    GeometricMean,
+    /// Geometric mean scaling followed by equilibration.
+    GeometricMeanEquilibration,
+    /// Use `GeometricMeanEquilibration` if the problem is badly scaled (see
+    /// [`ValidationReport::is_badly_scaled`]) and don't scale otherwise.
+    ///
+    /// [`ValidationReport::is_badly_scaled`]: struct.ValidationReport.html#method.is_badly_scaled
+    Auto,
+}
+
+/// Specifies how accurately the critical sums are computed.
+///
+/// Reduced costs (used for pricing), values of the basic variables (computed from the
+/// residuals of the constraints) and the objective value are periodically recomputed
+/// from scratch. Rounding errors accumulated in these sums can lead to false optimality
+/// or infeasibility declarations on ill-conditioned problems.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Accuracy {
+    /// Plain floating-point summation.
+    #[default]
+    Standard,
+    /// Compensated (Kahan) summation that tracks the rounding errors of the sums. It is
+    /// slower, but the result is usually as accurate as if it were computed with twice
+    /// the working precision and then rounded.
+    Compensated,
+    /// Double-double arithmetic: in addition to compensated summation, the columns used
+    /// in the ratio test are refined with one step of iterative refinement whose residual
+    /// is computed in double-double precision (products included). This is a middle ground
+    /// between `f64` and arbitrary precision arithmetic for numerically difficult problems,
+    /// at the cost of an extra solve with the basis matrix per iteration.
+    DoubleDouble,
+}
+
+/// Specifies the column order used when factorizing the basis matrix.
+///
+/// The order affects the fill-in of the LU factors and thus the cost of the simplex
+/// iterations. Computing it is a part of each refactorization, so in pipelines that
+/// repeatedly solve problems with the same sparsity pattern it can pay off to compute
+/// a good order once (e.g. offline) and skip the ordering phase.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum BasisOrdering {
+    /// Order the columns of each basis by a cheap fill-reducing heuristic.
+    #[default]
+    Auto,
+    /// Use a fixed order of the problem variables: `perm.new2orig()` lists the variables in
+    /// the order in which they are factorized. Slack columns of the constraints come first.
+    /// The permutation must have one entry per problem variable. It is ignored when
+    /// the dual problem is solved instead of the original one.
+    Fixed(Perm),
+    /// Order the columns of each basis by nested dissection of the column intersection
+    /// graph. The graph is recursively partitioned by the Fiduccia-Mattheyses heuristic.
+    /// It is more expensive than `Auto` but can greatly reduce the fill-in for large
+    /// problems with a mesh-like structure. Requires the `nested-dissection` feature.
+    #[cfg(feature = "nested-dissection")]
+    NestedDissection,
+}
@@ -1,7 +1,7 @@
-use super::sparse::{Error, Perm};
+use super::sparse::{Error, IndexType, Perm};
 
 /// Simplest preordering: order columns based on their size
-pub fn order_simple<'a>(size: usize, get_col: impl Fn(usize) -> &'a [usize]) -> Perm {
+pub fn order_simple<'a, I: IndexType>(size: usize, get_col: impl Fn(usize) -> &'a [I]) -> Perm {
     let mut cols_queue = ColsQueue::new(size);
     for c in 0..size {
         cols_queue.add(c, get_col(c).len() - 1);
@@ -21,9 +21,9 @@ pub fn order_simple<'a>(size: usize, get_col: impl Fn(usize) -> &'a [usize]) ->
 }
 
 #[allow(dead_code)]
-pub fn order_colamd<'a>(
+pub fn order_colamd<'a, I: IndexType>(
     size: usize,
-    get_col: impl Fn(usize) -> &'a [usize],
+    get_col: impl Fn(usize) -> &'a [I],
 ) -> Result<Perm, Error> {
     // Implementation of (a part of) the COLAMD algorithm:
     //
@@ -44,8 +44,10 @@ pub fn order_colamd<'a>(
     // * order empty columns/rows
     // * supercolumns
 
+    // All adjacency lists are stored in one buffer: first the rows of each column, then
+    // the columns of each row, then the pivot rows formed during the elimination.
     let mut cols = vec![Slice { begin: 0, end: 0 }; size];
-    let mut row_storage = vec![];
+    let mut storage = vec![];
 
     let mut new2orig = vec![0; size];
     let mut cur_ordered_col = 0;
@@ -59,20 +61,20 @@ pub fn order_colamd<'a>(
     {
         // Gather columns and in the process cheaply order columns of size 1.
         for c in 0..size {
-            let rows_begin = row_storage.len();
-            for &r in get_col(c) {
+            let rows_begin = storage.len();
+            for r in get_col(c).iter().map(|r| r.index()) {
                 if !is_absorbed_row[r] {
-                    row_storage.push(r);
+                    storage.push(r);
                 }
             }
 
-            let rows_end = row_storage.len();
+            let rows_end = storage.len();
             if rows_end - rows_begin == 0 {
                 return Err(Error::SingularMatrix);
             } else if rows_end - rows_begin > 1 {
                 cols[c].begin = rows_begin;
                 cols[c].end = rows_end;
-                for &r in &row_storage[rows_begin..rows_end] {
+                for &r in &storage[rows_begin..rows_end] {
                     rows[r].end += 1;
                 }
             } else {
@@ -81,8 +83,8 @@ pub fn order_colamd<'a>(
                 new2orig[cur_ordered_col] = c;
                 cur_ordered_col += 1;
 
-                is_absorbed_row[row_storage[rows_begin]] = true;
-                row_storage.pop();
+                is_absorbed_row[storage[rows_begin]] = true;
+                storage.pop();
             }
         }
     }
@@ -93,27 +95,30 @@ pub fn order_colamd<'a>(
         }
     }
 
-    let mut col_storage = vec![0; row_storage.len()];
+    let cols_nnz = storage.len();
+    storage.resize(2 * cols_nnz, 0);
 
     {
         // Gather rows.
-        for i in 1..size {
-            let prev_end = rows[i - 1].end;
-            rows[i].begin = prev_end;
-            rows[i].end += prev_end;
+        let mut prev_end = cols_nnz;
+        for row in &mut rows {
+            row.begin = prev_end;
+            row.end += prev_end;
+            prev_end = row.end;
         }
 
-        for c in 0..size {
-            for &r in cols[c].elems(&row_storage) {
-                let row = &mut rows[r];
-                col_storage[row.begin] = c;
+        for (c, col) in cols.iter().enumerate() {
+            for i in col.begin..col.end {
+                let row = &mut rows[storage[i]];
+                storage[row.begin] = c;
                 row.begin += 1;
             }
         }
 
-        rows[0].begin = 0;
-        for i in 1..size {
-            rows[i].begin = rows[i - 1].end;
+        let mut prev_end = cols_nnz;
+        for row in &mut rows {
+            row.begin = prev_end;
+            prev_end = row.end;
         }
     }
 
@@ -132,14 +137,13 @@ pub fn order_colamd<'a>(
             // all columns on the stack are of size 1
             stack.clear();
             stack.push(c);
-            while !stack.is_empty() {
-                let c = stack.pop().unwrap();
+            while let Some(c) = stack.pop() {
                 let r = *cols[c]
-                    .elems(&row_storage)
+                    .elems(&storage)
                     .iter()
                     .find(|&&r| !is_absorbed_row[r])
                     .unwrap();
-                for &other_c in rows[r].elems(&col_storage) {
+                for &other_c in rows[r].elems(&storage) {
                     col_rows_len[other_c] -= 1;
                     if col_rows_len[other_c] == 1 {
                         stack.push(other_c);
@@ -186,9 +190,9 @@ pub fn order_colamd<'a>(
             let col = &mut cols[c];
             let mut cur_end = col.begin;
             for i in col.begin..col.end {
-                let r = row_storage[i];
+                let r = storage[i];
                 if !is_absorbed_row[r] {
-                    row_storage[cur_end] = r;
+                    storage[cur_end] = r;
                     cur_end += 1;
                 }
             }
@@ -232,7 +236,7 @@ pub fn order_colamd<'a>(
             let col = &mut cols[c];
 
             let mut score = 0;
-            for &r in col.elems(&row_storage) {
+            for &r in col.elems(&storage) {
                 let row = &rows[r];
                 score += row.end - row.begin - 1;
             }
@@ -254,21 +258,22 @@ pub fn order_colamd<'a>(
 
     while cols_queue.len() > 0 {
         let pivot_c = cols_queue.pop_min().unwrap();
-        let pivot_row_begin = col_storage.len();
+        let pivot_row_begin = storage.len();
 
         new2orig[cur_ordered_col] = pivot_c;
         cur_ordered_col += 1;
         is_ordered_col[pivot_c] = true;
 
         let mut pivot_r = None;
-        for &r in cols[pivot_c].elems(&row_storage) {
+        for i_r in cols[pivot_c].begin..cols[pivot_c].end {
+            let r = storage[i_r];
             if !std::mem::replace(&mut is_absorbed_row[r], true) {
                 pivot_r = Some(r); // choose any absorbed row index to represent pivot row.
                 let row = &rows[r];
                 for i in row.begin..row.end {
-                    let c = col_storage[i];
+                    let c = storage[i];
                     if !is_ordered_col[c] && !std::mem::replace(&mut is_in_pivot_row[c], true) {
-                        col_storage.push(c);
+                        storage.push(c);
                     }
                 }
             }
@@ -276,13 +281,13 @@ pub fn order_colamd<'a>(
         let pivot_r = pivot_r.unwrap();
 
         // clear for next iteration.
-        for &c in &col_storage[pivot_row_begin..] {
+        for &c in &storage[pivot_row_begin..] {
             is_in_pivot_row[c] = false;
         }
 
         // find row set differences
-        for &c in &col_storage[pivot_row_begin..] {
-            for &r in cols[c].elems(&row_storage) {
+        for &c in &storage[pivot_row_begin..] {
+            for &r in cols[c].elems(&storage) {
                 if is_absorbed_row[r] {
                     continue;
                 }
@@ -306,8 +311,8 @@ pub fn order_colamd<'a>(
         // calculate row set differences with the pivot row
         {
             let mut cur_pivot_i = pivot_row_begin;
-            for pivot_i in pivot_row_begin..col_storage.len() {
-                let c = col_storage[pivot_i];
+            for pivot_i in pivot_row_begin..storage.len() {
+                let c = storage[pivot_i];
                 cols_queue.remove(c, col_scores[c]);
 
                 let col = &mut cols[c];
@@ -316,9 +321,9 @@ pub fn order_colamd<'a>(
                 let mut diff = 0;
                 let mut cur_end = col.begin;
                 for i in col.begin..col.end {
-                    let r = row_storage[i];
+                    let r = storage[i];
                     if !is_absorbed_row[r] {
-                        row_storage[cur_end] = r;
+                        storage[cur_end] = r;
                         cur_end += 1;
                         diff += row_set_diffs[r];
                     }
@@ -334,11 +339,11 @@ pub fn order_colamd<'a>(
                     is_ordered_col[c] = true;
                 } else {
                     col_scores[c] = diff; // NOTE: not the final score, will be updated later.
-                    col_storage[cur_pivot_i] = c;
+                    storage[cur_pivot_i] = c;
                     cur_pivot_i += 1;
                 }
             }
-            col_storage.truncate(cur_pivot_i);
+            storage.truncate(cur_pivot_i);
         }
 
         // clear diffs for next iteration
@@ -351,13 +356,13 @@ pub fn order_colamd<'a>(
         // TODO: detect supercolumns
 
         // calculate final column scores
-        let pivot_row_len = col_storage.len() - pivot_row_begin;
+        let pivot_row_len = storage.len() - pivot_row_begin;
         if pivot_row_len > 0 {
             let row = &mut rows[pivot_r];
             row.begin = pivot_row_begin;
-            row.end = col_storage.len();
+            row.end = storage.len();
             is_absorbed_row[pivot_r] = false;
-            for &c in row.elems(&col_storage) {
+            for &c in row.elems(&storage) {
                 let score = &mut col_scores[c];
                 *score += pivot_row_len - 1; // TODO: supercolumns
                 *score = std::cmp::min(*score, size - 1);
@@ -461,15 +466,17 @@ impl ColsQueue {
 
 const SENTINEL: usize = 0usize.wrapping_sub(1);
 
-#[allow(dead_code)]
-pub fn find_diag_matching<'a>(
-    size: usize,
-    get_col: impl Fn(usize) -> &'a [usize],
-) -> Option<Vec<usize>> {
-    let mut col2visited_on_iter = vec![SENTINEL; size];
-    let mut row2matched_col = vec![SENTINEL; size];
+/// Find a matching of maximum size between rows and columns of a (possibly rectangular)
+/// matrix with `num_rows` rows. Returns for each row its matched column or SENTINEL.
+pub fn find_max_matching<'a, I: IndexType>(
+    num_rows: usize,
+    num_cols: usize,
+    get_col: impl Fn(usize) -> &'a [I],
+) -> Vec<usize> {
+    let mut col2visited_on_iter = vec![SENTINEL; num_cols];
+    let mut row2matched_col = vec![SENTINEL; num_rows];
     // for each col a pointer to the position in its adjacency lists where we last looked for neighbors.
-    let mut cheap = vec![0; size];
+    let mut cheap = vec![0; num_cols];
 
     struct Step {
         col: usize,
@@ -477,7 +484,7 @@ pub fn find_diag_matching<'a>(
     }
 
     let mut dfs_stack = vec![];
-    for start_c in 0..size {
+    for start_c in 0..num_cols {
         let mut found = false; // whether the dfs iteration found the match
 
         dfs_stack.clear();
@@ -487,7 +494,7 @@ pub fn find_diag_matching<'a>(
         });
 
         'dfs_loop: while !dfs_stack.is_empty() {
-            let mut cur_step = dfs_stack.last_mut().unwrap();
+            let cur_step = dfs_stack.last_mut().unwrap();
             let c = cur_step.col;
             let col_rows = get_col(c);
 
@@ -496,7 +503,7 @@ pub fn find_diag_matching<'a>(
 
                 let cur_cheap = &mut cheap[c];
                 while *cur_cheap < col_rows.len() {
-                    let r = col_rows[*cur_cheap];
+                    let r = col_rows[*cur_cheap].index();
                     if row2matched_col[r] == SENTINEL {
                         row2matched_col[r] = c;
                         found = true;
@@ -507,7 +514,7 @@ pub fn find_diag_matching<'a>(
                 }
             } else {
                 if found {
-                    let r = col_rows[cur_step.cur_i];
+                    let r = col_rows[cur_step.cur_i].index();
                     row2matched_col[r] = c;
                     dfs_stack.pop();
                     continue 'dfs_loop;
@@ -517,7 +524,7 @@ pub fn find_diag_matching<'a>(
             }
 
             while cur_step.cur_i < col_rows.len() {
-                let r = col_rows[cur_step.cur_i];
+                let r = col_rows[cur_step.cur_i].index();
                 if col2visited_on_iter[row2matched_col[r]] != start_c {
                     break;
                 }
@@ -527,20 +534,63 @@ pub fn find_diag_matching<'a>(
             if cur_step.cur_i == col_rows.len() {
                 dfs_stack.pop();
             } else {
-                let col = row2matched_col[col_rows[cur_step.cur_i]];
+                let col = row2matched_col[col_rows[cur_step.cur_i].index()];
                 dfs_stack.push(Step { col, cur_i: 0 });
             }
         }
+    }
 
-        if !found {
-            return None;
-        }
+    row2matched_col
+}
+
+/// A maximum matching between rows and columns of a square matrix.
+#[derive(Clone, Debug)]
+pub struct DiagMatching {
+    /// For each row its matched column (SENTINEL if the row is unmatched).
+    pub row2col: Vec<usize>,
+    /// Rows left unmatched.
+    pub deficient_rows: Vec<usize>,
+    /// Columns left unmatched. There are as many of them as there are deficient rows.
+    pub deficient_cols: Vec<usize>,
+}
+
+impl DiagMatching {
+    /// Returns true if all rows and columns are matched, i.e. the matrix can be permuted
+    /// to have a zero-free diagonal. Otherwise the matrix is structurally singular.
+    pub fn is_perfect(&self) -> bool {
+        self.deficient_rows.is_empty()
     }
+}
 
-    Some(row2matched_col)
+/// Find a matching of rows and columns so that the matrix with rows permuted according
+/// to it has a zero-free diagonal. If there is no such matching, the matrix is structurally
+/// singular, and the maximum matching is returned together with the deficient rows and
+/// columns. Replacing each deficient column by a unit column of a deficient row makes
+/// the matrix structurally nonsingular.
+pub fn find_diag_matching<'a, I: IndexType>(
+    size: usize,
+    get_col: impl Fn(usize) -> &'a [I],
+) -> DiagMatching {
+    let row2col = find_max_matching(size, size, get_col);
+    let mut is_matched_col = vec![false; size];
+    let mut deficient_rows = vec![];
+    for (r, &c) in row2col.iter().enumerate() {
+        if c == SENTINEL {
+            deficient_rows.push(r);
+        } else {
+            is_matched_col[c] = true;
+        }
+    }
+    let deficient_cols = (0..size).filter(|&c| !is_matched_col[c]).collect();
+    DiagMatching {
+        row2col,
+        deficient_rows,
+        deficient_cols,
+    }
 }
 
 /// Lower block triangular form of a matrix.
+#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct BlockDiagForm {
     /// Row permutation: for each original row its new row number so that diag is nonzero.
@@ -549,12 +599,36 @@ pub struct BlockDiagForm {
     pub block_cols: Vec<Vec<usize>>,
 }
 
+/// Find the lower block triangular form of a matrix. If the matrix is structurally
+/// singular, returns the maximum matching with the deficient rows and columns instead.
 #[allow(dead_code)]
-pub fn find_block_diag_form<'a>(
+pub fn find_block_diag_form<'a, I: IndexType>(
     size: usize,
-    get_col: impl Fn(usize) -> &'a [usize],
-) -> BlockDiagForm {
-    let row2col = find_diag_matching(size, &get_col).unwrap();
+    get_col: impl Fn(usize) -> &'a [I],
+) -> Result<BlockDiagForm, DiagMatching> {
+    let matching = find_diag_matching(size, &get_col);
+    if !matching.is_perfect() {
+        return Err(matching);
+    }
+    let row2col = matching.row2col;
+    let block_cols = find_strong_components(&vec![true; size], get_col, &row2col);
+    Ok(BlockDiagForm {
+        row2col,
+        block_cols,
+    })
+}
+
+/// Find the strongly connected components of the graph on the included columns, in which
+/// column c is connected to the column matched to each row of c. Returns for each component
+/// its set of columns. Components are ordered so that the matrix with rows ordered by their
+/// matched columns is lower block triangular.
+fn find_strong_components<'a, I: IndexType>(
+    is_included: &[bool],
+    get_col: impl Fn(usize) -> &'a [I],
+    row2col: &[usize],
+) -> Vec<Vec<usize>> {
+    let num_cols = is_included.len();
+    let next_col = |r: usize| Some(row2col[r]).filter(|&c| c != SENTINEL && is_included[c]);
 
     struct Step {
         col: usize,
@@ -563,9 +637,9 @@ pub fn find_block_diag_form<'a>(
 
     let mut dfs_stack = vec![];
     let mut visited = vec![];
-    let mut is_visited = vec![false; size];
-    for start_c in 0..size {
-        if is_visited[start_c] {
+    let mut is_visited = vec![false; num_cols];
+    for start_c in 0..num_cols {
+        if is_visited[start_c] || !is_included[start_c] {
             continue;
         }
 
@@ -585,15 +659,14 @@ pub fn find_block_diag_form<'a>(
 
             let col_rows = get_col(c);
             while cur_step.cur_i < col_rows.len() {
-                let next_c = row2col[col_rows[cur_step.cur_i]];
-                if !is_visited[next_c] {
-                    break;
+                match next_col(col_rows[cur_step.cur_i].index()) {
+                    Some(next_c) if !is_visited[next_c] => break,
+                    _ => cur_step.cur_i += 1,
                 }
-                cur_step.cur_i += 1;
             }
 
             if cur_step.cur_i < col_rows.len() {
-                let col = row2col[col_rows[cur_step.cur_i]];
+                let col = next_col(col_rows[cur_step.cur_i].index()).unwrap();
                 dfs_stack.push(Step { col, cur_i: 0 });
             } else {
                 visited.push(c);
@@ -602,17 +675,31 @@ pub fn find_block_diag_form<'a>(
         }
     }
 
-    // Prepare transposed graph
-    // TODO: more efficient transpose without allocating each row.
-    let mut rows = vec![vec![]; size];
-    for c in 0..size {
-        for &r in get_col(c) {
-            rows[row2col[r]].push(c);
+    // Prepare transposed graph: for each column the columns with edges into it.
+    let mut in_begins = vec![0; num_cols + 1];
+    for c in (0..num_cols).filter(|&c| is_included[c]) {
+        for r in get_col(c).iter().map(|r| r.index()) {
+            if let Some(next_c) = next_col(r) {
+                in_begins[next_c + 1] += 1;
+            }
+        }
+    }
+    for c in 0..num_cols {
+        in_begins[c + 1] += in_begins[c];
+    }
+    let mut in_storage = vec![0; in_begins[num_cols]];
+    let mut in_ends = in_begins.clone();
+    for c in (0..num_cols).filter(|&c| is_included[c]) {
+        for r in get_col(c).iter().map(|r| r.index()) {
+            if let Some(next_c) = next_col(r) {
+                in_storage[in_ends[next_c]] = c;
+                in_ends[next_c] += 1;
+            }
         }
     }
 
     is_visited.clear();
-    is_visited.resize(size, false);
+    is_visited.resize(num_cols, false);
 
     let mut block_cols = vec![];
 
@@ -639,7 +726,7 @@ pub fn find_block_diag_form<'a>(
                 cur_step.cur_i += 1;
             }
 
-            let next = &rows[c];
+            let next = &in_storage[in_begins[c]..in_begins[c + 1]];
             while cur_step.cur_i < next.len() {
                 if !is_visited[next[cur_step.cur_i]] {
                     break;
@@ -656,9 +743,284 @@ pub fn find_block_diag_form<'a>(
         }
     }
 
-    BlockDiagForm {
-        row2col,
-        block_cols,
+    block_cols
+}
+
+/// Find the connected components of a part of the bipartite graph of a matrix. The part
+/// consists of `nodes` of the first kind (e.g. columns) and `others` of the second kind
+/// (e.g. rows). Nodes are joined through the others, and each other belongs to the component
+/// of its `matched` node (or to none if it is SENTINEL). Returns the nodes and the others of
+/// each component.
+fn connected_components<'a, I: IndexType>(
+    others: &[usize],
+    nodes: &[usize],
+    num_nodes: usize,
+    get_neighbors: impl Fn(usize) -> &'a [I],
+    is_in_part: impl Fn(usize) -> bool,
+    matched: impl Fn(usize) -> usize,
+) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut parent: Vec<usize> = (0..num_nodes).collect();
+    fn find_root(parent: &mut [usize], mut node: usize) -> usize {
+        while parent[node] != node {
+            parent[node] = parent[parent[node]];
+            node = parent[node];
+        }
+        node
+    }
+
+    for &other in others {
+        let mut root = None;
+        for node in get_neighbors(other).iter().map(|node| node.index()) {
+            if !is_in_part(node) {
+                continue;
+            }
+            let node_root = find_root(&mut parent, node);
+            match root {
+                None => root = Some(node_root),
+                Some(root) => parent[node_root] = find_root(&mut parent, root),
+            }
+        }
+    }
+
+    let mut root2component = vec![SENTINEL; num_nodes];
+    let mut components: Vec<(Vec<usize>, Vec<usize>)> = vec![];
+    for &node in nodes {
+        let root = find_root(&mut parent, node);
+        if root2component[root] == SENTINEL {
+            root2component[root] = components.len();
+            components.push((vec![], vec![]));
+        }
+        components[root2component[root]].0.push(node);
+    }
+    for &other in others {
+        let node = matched(other);
+        if node == SENTINEL {
+            continue;
+        }
+        let root = find_root(&mut parent, node);
+        components[root2component[root]].1.push(other);
+    }
+    components
+}
+
+/// A set of rows and columns of a matrix.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DmBlock {
+    /// Row indices.
+    pub rows: Vec<usize>,
+    /// Column indices.
+    pub cols: Vec<usize>,
+}
+
+/// The Dulmage–Mendelsohn decomposition of a sparse matrix.
+///
+/// The decomposition is based on a maximum matching between rows and columns (a maximum
+/// set of nonzero positions no two of which share a row or a column). Its size is the
+/// *structural rank* of the matrix: the maximal rank over all values of the nonzero entries.
+///
+/// The coarse decomposition splits the rows and columns into three parts:
+/// * **horizontal** (underdetermined) part, with more columns than rows: columns reachable
+///   from unmatched columns by alternating paths and their matched rows.
+/// * **vertical** (overdetermined) part, with more rows than columns: rows reachable from
+///   unmatched rows by alternating paths and their matched columns.
+/// * **square** part: the rest, with a perfect matching.
+///
+/// With rows and columns ordered as horizontal, square, vertical the matrix is block upper
+/// triangular. The fine decomposition splits the square part into irreducible blocks
+/// (strongly connected components) ordered so that the square part is block upper
+/// triangular as well. Thus the matrix is nonsingular iff the horizontal and vertical parts
+/// are empty, and a system with this matrix can be solved block by block, starting from
+/// the last fine block.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::DmDecomposition;
+/// // Nonzeros of the columns of the 3x3 matrix
+/// // | x x x |
+/// // | . . x |
+/// // | . . x |
+/// let cols: [&[usize]; 3] = [&[0], &[0], &[0, 1, 2]];
+/// let dm = DmDecomposition::compute(3, 3, |c| cols[c]);
+/// assert_eq!(dm.structural_rank(), 2);
+/// assert_eq!(dm.horizontal.cols.len(), 2);
+/// assert_eq!(dm.horizontal.rows.len(), 1);
+/// assert_eq!(dm.vertical.rows.len(), 2);
+/// assert_eq!(dm.vertical.cols.len(), 1);
+/// assert!(dm.square.rows.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DmDecomposition {
+    /// For each row its matched column in the maximum matching.
+    pub row2col: Vec<Option<usize>>,
+    /// For each column its matched row in the maximum matching.
+    pub col2row: Vec<Option<usize>>,
+    /// The horizontal (underdetermined) part.
+    pub horizontal: DmBlock,
+    /// The square part.
+    pub square: DmBlock,
+    /// The vertical (overdetermined) part.
+    pub vertical: DmBlock,
+    /// The fine decomposition of the square part in block upper triangular order. Rows of
+    /// each block are listed in the order of their matched columns.
+    pub fine_blocks: Vec<DmBlock>,
+    /// Connected components of the horizontal part.
+    pub horizontal_blocks: Vec<DmBlock>,
+    /// Connected components of the vertical part.
+    pub vertical_blocks: Vec<DmBlock>,
+}
+
+impl DmDecomposition {
+    /// Compute the decomposition of a matrix given by the row indices of the nonzeros
+    /// of each of its columns.
+    pub fn compute<'a, I: IndexType>(
+        num_rows: usize,
+        num_cols: usize,
+        get_col: impl Fn(usize) -> &'a [I],
+    ) -> DmDecomposition {
+        let row2col = find_max_matching(num_rows, num_cols, &get_col);
+        let mut col2row = vec![SENTINEL; num_cols];
+        for (r, &c) in row2col.iter().enumerate() {
+            if c != SENTINEL {
+                col2row[c] = r;
+            }
+        }
+
+        // Alternating paths from unmatched columns go through any nonzero of the column to
+        // its row and then to the column matched to that row.
+        let mut is_horizontal_col = vec![false; num_cols];
+        let mut is_horizontal_row = vec![false; num_rows];
+        let mut stack: Vec<usize> = (0..num_cols).filter(|&c| col2row[c] == SENTINEL).collect();
+        for &c in &stack {
+            is_horizontal_col[c] = true;
+        }
+        while let Some(c) = stack.pop() {
+            for r in get_col(c).iter().map(|r| r.index()) {
+                if !is_horizontal_row[r] {
+                    is_horizontal_row[r] = true;
+                    let next_c = row2col[r];
+                    if !is_horizontal_col[next_c] {
+                        is_horizontal_col[next_c] = true;
+                        stack.push(next_c);
+                    }
+                }
+            }
+        }
+
+        // Alternating paths from unmatched rows need the row-wise structure.
+        let mut row_begins = vec![0; num_rows + 1];
+        for c in 0..num_cols {
+            for r in get_col(c).iter().map(|r| r.index()) {
+                row_begins[r + 1] += 1;
+            }
+        }
+        for r in 0..num_rows {
+            row_begins[r + 1] += row_begins[r];
+        }
+        let mut row_cols = vec![0; row_begins[num_rows]];
+        let mut row_ends = row_begins.clone();
+        for c in 0..num_cols {
+            for r in get_col(c).iter().map(|r| r.index()) {
+                row_cols[row_ends[r]] = c;
+                row_ends[r] += 1;
+            }
+        }
+
+        let mut is_vertical_col = vec![false; num_cols];
+        let mut is_vertical_row = vec![false; num_rows];
+        let mut stack: Vec<usize> = (0..num_rows).filter(|&r| row2col[r] == SENTINEL).collect();
+        for &r in &stack {
+            is_vertical_row[r] = true;
+        }
+        while let Some(r) = stack.pop() {
+            for &c in &row_cols[row_begins[r]..row_begins[r + 1]] {
+                if !is_vertical_col[c] {
+                    is_vertical_col[c] = true;
+                    let next_r = col2row[c];
+                    if !is_vertical_row[next_r] {
+                        is_vertical_row[next_r] = true;
+                        stack.push(next_r);
+                    }
+                }
+            }
+        }
+
+        let is_square_col: Vec<bool> = (0..num_cols)
+            .map(|c| !is_horizontal_col[c] && !is_vertical_col[c])
+            .collect();
+        let block = |is_row: &dyn Fn(usize) -> bool, is_col: &dyn Fn(usize) -> bool| DmBlock {
+            rows: (0..num_rows).filter(|&r| is_row(r)).collect(),
+            cols: (0..num_cols).filter(|&c| is_col(c)).collect(),
+        };
+        let horizontal = block(&|r| is_horizontal_row[r], &|c| is_horizontal_col[c]);
+        let vertical = block(&|r| is_vertical_row[r], &|c| is_vertical_col[c]);
+        let square = block(&|r| !is_horizontal_row[r] && !is_vertical_row[r], &|c| {
+            is_square_col[c]
+        });
+
+        let mut fine_blocks: Vec<DmBlock> =
+            find_strong_components(&is_square_col, &get_col, &row2col)
+                .into_iter()
+                .map(|cols| DmBlock {
+                    rows: cols.iter().map(|&c| col2row[c]).collect(),
+                    cols,
+                })
+                .collect();
+        fine_blocks.reverse();
+
+        // Components of the horizontal part are found by joining the columns of each row
+        // (all rows are matched), and of the vertical part by joining the rows of each column.
+        let horizontal_blocks = connected_components(
+            &horizontal.rows,
+            &horizontal.cols,
+            num_cols,
+            |r| &row_cols[row_begins[r]..row_begins[r + 1]],
+            |c| is_horizontal_col[c],
+            |r| row2col[r],
+        )
+        .into_iter()
+        .map(|(cols, rows)| DmBlock { rows, cols })
+        .collect();
+        let vertical_blocks = connected_components(
+            &vertical.cols,
+            &vertical.rows,
+            num_rows,
+            &get_col,
+            |r| is_vertical_row[r],
+            |c| col2row[c],
+        )
+        .into_iter()
+        .map(|(rows, cols)| DmBlock { rows, cols })
+        .collect();
+
+        let to_option = |idx: usize| Some(idx).filter(|&i| i != SENTINEL);
+        DmDecomposition {
+            row2col: row2col.into_iter().map(to_option).collect(),
+            col2row: col2row.into_iter().map(to_option).collect(),
+            horizontal,
+            square,
+            vertical,
+            fine_blocks,
+            horizontal_blocks,
+            vertical_blocks,
+        }
+    }
+
+    /// All blocks of the decomposition: horizontal components, fine blocks of the square part
+    /// and vertical components. Each row and column of the matrix belongs to exactly one
+    /// block, and with rows and columns ordered by blocks the matrix is block upper triangular.
+    /// Note that blocks of the horizontal part have more columns than rows and blocks of
+    /// the vertical part have more rows than columns.
+    pub fn blocks(&self) -> impl Iterator<Item = &DmBlock> {
+        self.horizontal_blocks
+            .iter()
+            .chain(&self.fine_blocks)
+            .chain(&self.vertical_blocks)
+    }
+
+    /// Size of the maximum matching, i.e. the structural rank of the matrix.
+    pub fn structural_rank(&self) -> usize {
+        self.row2col.iter().filter(|c| c.is_some()).count()
     }
 }
 
@@ -735,7 +1097,25 @@ mod tests {
 
         let matching =
             find_diag_matching(size, |c| mat.outer_view(c).unwrap().into_raw_storage().0);
-        assert_eq!(matching, Some(vec![1, 2, 0]));
+        assert!(matching.is_perfect());
+        assert_eq!(matching.row2col, vec![1, 2, 0]);
+
+        // Cols 1 and 2 have nonzeros only in row 0.
+        let singular = mat_from_triplets(size, size, &[(0, 0), (1, 0), (2, 0), (0, 1), (0, 2)]);
+        let get_col = |c| singular.outer_view(c).unwrap().into_raw_storage().0;
+        let matching = find_diag_matching(size, get_col);
+        assert!(!matching.is_perfect());
+        assert_eq!(matching.deficient_rows.len(), 1);
+        assert_eq!(matching.deficient_cols.len(), 1);
+        assert_ne!(matching.deficient_rows, vec![0]);
+        assert_ne!(matching.deficient_cols, vec![0]);
+        assert_eq!(
+            find_block_diag_form(size, get_col)
+                .unwrap_err()
+                .row2col
+                .len(),
+            3
+        );
     }
 
     #[test]
@@ -748,8 +1128,102 @@ mod tests {
         );
 
         let bd_form =
-            find_block_diag_form(size, |c| mat.outer_view(c).unwrap().into_raw_storage().0);
+            find_block_diag_form(size, |c| mat.outer_view(c).unwrap().into_raw_storage().0)
+                .unwrap();
         assert_eq!(bd_form.row2col, &[2, 0, 1]);
         assert_eq!(bd_form.block_cols, vec![vec![0, 1], vec![2]]);
     }
+
+    #[test]
+    fn dulmage_mendelsohn() {
+        // Rows 0, 1 and cols 0, 1, 2 are horizontal, rows 4, 5 and col 5 are vertical,
+        // rows 2, 3 and cols 3, 4 are square, each forming a fine block.
+        let mat = mat_from_triplets(
+            6,
+            6,
+            &[
+                (0, 0),
+                (0, 1),
+                (1, 1),
+                (1, 2),
+                (0, 3),
+                (2, 3),
+                (2, 4),
+                (3, 4),
+                (3, 5),
+                (4, 5),
+                (5, 5),
+            ],
+        );
+        let dm =
+            DmDecomposition::compute(6, 6, |c| mat.outer_view(c).unwrap().into_raw_storage().0);
+        assert_eq!(dm.structural_rank(), 5);
+        assert_eq!(dm.horizontal.rows, vec![0, 1]);
+        assert_eq!(dm.horizontal.cols, vec![0, 1, 2]);
+        assert_eq!(dm.square.rows, vec![2, 3]);
+        assert_eq!(dm.square.cols, vec![3, 4]);
+        assert_eq!(dm.vertical.rows, vec![4, 5]);
+        assert_eq!(dm.vertical.cols, vec![5]);
+        assert_eq!(
+            dm.fine_blocks,
+            vec![
+                DmBlock {
+                    rows: vec![2],
+                    cols: vec![3],
+                },
+                DmBlock {
+                    rows: vec![3],
+                    cols: vec![4],
+                },
+            ]
+        );
+
+        // Nonsingular matrix: everything is in the square part.
+        let mat = mat_from_triplets(3, 3, &[(1, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2)]);
+        let dm =
+            DmDecomposition::compute(3, 3, |c| mat.outer_view(c).unwrap().into_raw_storage().0);
+        assert_eq!(dm.structural_rank(), 3);
+        assert!(dm.horizontal.cols.is_empty() && dm.vertical.rows.is_empty());
+        assert_eq!(dm.fine_blocks.len(), 2);
+        assert_eq!(dm.fine_blocks[0].cols, vec![2]);
+        assert_eq!(dm.fine_blocks[1].cols, vec![0, 1]);
+    }
+
+    #[test]
+    fn rectangular_blocks() {
+        // Two independent underdetermined blocks (rows 0 and 1), a square block (row 2)
+        // and an overdetermined block (rows 3, 4 with col 5).
+        let mat = mat_from_triplets(
+            5,
+            6,
+            &[
+                (0, 0),
+                (0, 1),
+                (1, 2),
+                (1, 3),
+                (2, 4),
+                (1, 4),
+                (2, 5),
+                (3, 5),
+                (4, 5),
+            ],
+        );
+        let dm =
+            DmDecomposition::compute(5, 6, |c| mat.outer_view(c).unwrap().into_raw_storage().0);
+        assert_eq!(dm.structural_rank(), 4);
+        let blocks: Vec<_> = dm.blocks().cloned().collect();
+        let block = |rows: &[usize], cols: &[usize]| DmBlock {
+            rows: rows.to_vec(),
+            cols: cols.to_vec(),
+        };
+        assert_eq!(
+            blocks,
+            vec![
+                block(&[0], &[0, 1]),
+                block(&[1], &[2, 3]),
+                block(&[2], &[4]),
+                block(&[3, 4], &[5]),
+            ]
+        );
+    }
 }
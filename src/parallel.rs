@@ -0,0 +1,73 @@
+use std::ops::Range;
+
+/// Splitting the work into chunks shorter than this is not worth the cost of spawning threads.
+const MIN_CHUNK_LEN: usize = 1000;
+
+/// Split `0..len` into contiguous chunks, call `f` on each chunk in a separate thread
+/// and return the results in the chunk order. At most `threads` threads are used,
+/// and if there is not enough work `f` is simply called on the whole range.
+///
+/// Chunk boundaries depend only on `threads` and `len`, so combining the results in
+/// the returned order is deterministic.
+pub(crate) fn map_chunks<T, F>(threads: usize, len: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Range<usize>) -> T + Sync,
+{
+    map_chunks_with_min_len(threads, len, MIN_CHUNK_LEN, f)
+}
+
+/// Same as [`map_chunks`], but for items that are expensive to process, so that chunks
+/// as short as `min_chunk_len` are worth a separate thread.
+pub(crate) fn map_chunks_with_min_len<T, F>(
+    threads: usize,
+    len: usize,
+    min_chunk_len: usize,
+    f: F,
+) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Range<usize>) -> T + Sync,
+{
+    let num_chunks = usize::min(threads, len / min_chunk_len).max(1);
+    if num_chunks == 1 {
+        return vec![f(0..len)];
+    }
+
+    let chunk_len = len.div_ceil(num_chunks);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..num_chunks)
+            .map(|i| {
+                let range = i * chunk_len..usize::min((i + 1) * chunk_len, len);
+                scope.spawn(move || f(range))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks() {
+        assert_eq!(map_chunks(4, 10, |range| range), vec![0..10]);
+        assert_eq!(map_chunks(0, 5000, |range| range), vec![0..5000]);
+
+        let ranges = map_chunks(3, 5000, |range| range);
+        assert_eq!(ranges, vec![0..1667, 1667..3334, 3334..5000]);
+
+        let sums = map_chunks(8, 5000, |range| range.sum::<usize>());
+        assert_eq!(sums.len(), 5);
+        assert_eq!(sums.iter().sum::<usize>(), (0..5000).sum());
+
+        let ranges = map_chunks_with_min_len(2, 5, 1, |range| range);
+        assert_eq!(ranges, vec![0..3, 3..5]);
+        assert_eq!(map_chunks_with_min_len(8, 3, 2, |range| range), vec![0..3]);
+    }
+}
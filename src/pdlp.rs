@@ -0,0 +1,714 @@
+use crate::{
+    solver::Solver, ComparisonOp, ConstraintRef, Error, OptimizationDirection, Problem, Solution,
+    SolverOptions, Variable,
+};
+
+/// Number of iterations between the checks of the termination and restart criteria.
+const CHECK_INTERVAL: usize = 64;
+/// Number of iterations of the Ruiz equilibration of the constraint matrix.
+const RUIZ_ITERATIONS: usize = 10;
+/// Number of iterations of the power method estimating the norm of the constraint matrix.
+const POWER_ITERATIONS: usize = 64;
+/// Fraction of the max stable step size (1 / norm of the matrix) that is used.
+const STEP_SIZE_FACTOR: f64 = 0.9;
+/// Restart if the KKT error decreased by this factor since the last restart.
+const SUFFICIENT_RESTART_DECAY: f64 = 0.2;
+/// Restart if the KKT error decreased by this factor since the last restart and started
+/// to increase.
+const NECESSARY_RESTART_DECAY: f64 = 0.8;
+/// Restart if the iterations since the last restart are this fraction of all iterations.
+const ARTIFICIAL_RESTART_FRACTION: f64 = 0.36;
+/// Smoothing of the primal weight updates on restarts.
+const PRIMAL_WEIGHT_SMOOTHING: f64 = 0.5;
+
+/// Options of the first-order method, see
+/// [`Problem::solve_first_order`](struct.Problem.html#method.solve_first_order).
+#[derive(Clone, Debug)]
+pub struct FirstOrderOptions {
+    /// Max number of iterations (each costs two multiplications by the constraint matrix).
+    pub max_iterations: usize,
+    /// Relative tolerance of the termination criteria: the primal residual must be at most
+    /// `tolerance * (1 + |b|)`, the dual residual at most `tolerance * (1 + |c|)` and
+    /// the duality gap at most `tolerance * (1 + |primal obj.| + |dual obj.|)` (norms are
+    /// euclidean).
+    pub tolerance: f64,
+}
+
+impl Default for FirstOrderOptions {
+    fn default() -> Self {
+        FirstOrderOptions {
+            max_iterations: 100_000,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// An approximate solution found by the first-order method, see
+/// [`Problem::solve_first_order`](struct.Problem.html#method.solve_first_order).
+///
+/// Variable values satisfy the bounds exactly, but the constraints only approximately.
+#[derive(Clone, Debug)]
+pub struct FirstOrderSolution {
+    objective: f64,
+    var_values: Vec<f64>,
+    dual_values: Vec<f64>,
+    iterations: usize,
+    is_converged: bool,
+    residuals: Residuals,
+    generation: u32,
+}
+
+impl FirstOrderSolution {
+    /// Objective value of the solution.
+    pub fn objective(&self) -> f64 {
+        self.objective
+    }
+
+    /// Value of the variable.
+    pub fn var_value(&self, var: Variable) -> f64 {
+        self.var_values[self.var_idx(var)]
+    }
+
+    /// Dual value of the constraint, with the same meaning and sign as
+    /// [`Solution::dual_value`](struct.Solution.html#method.dual_value).
+    pub fn dual_value(&self, constr: ConstraintRef) -> f64 {
+        self.dual_values[self.constraint_idx(constr)]
+    }
+
+    /// Index of the variable, panics if the reference is not from this solution.
+    fn var_idx(&self, var: Variable) -> usize {
+        assert!(
+            var.0 < self.var_values.len() && var.1 == self.generation,
+            "variable {:?} is not in the solution",
+            var
+        );
+        var.0
+    }
+
+    /// Index of the constraint, panics if the reference is not from this solution.
+    fn constraint_idx(&self, constr: ConstraintRef) -> usize {
+        assert!(
+            constr.0 < self.dual_values.len() && constr.1 == self.generation,
+            "constraint {:?} is not in the solution",
+            constr
+        );
+        constr.0
+    }
+
+    /// Number of iterations performed.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// True if the termination criteria were met, false if the iteration limit was reached.
+    pub fn is_converged(&self) -> bool {
+        self.is_converged
+    }
+
+    /// Euclidean norm of the constraint violations.
+    pub fn primal_residual(&self) -> f64 {
+        self.residuals.primal
+    }
+
+    /// Euclidean norm of the violations of the dual constraints: reduced costs that can't
+    /// be balanced by the bounds of the variables.
+    pub fn dual_residual(&self) -> f64 {
+        self.residuals.dual
+    }
+
+    /// Absolute difference between the primal and the dual objective values.
+    pub fn duality_gap(&self) -> f64 {
+        self.residuals.gap()
+    }
+
+    /// Polish the approximate solution with the simplex method started from it (crossover)
+    /// and return the exact optimal solution.
+    ///
+    /// `problem` must be the problem this solution was obtained from. The number of simplex
+    /// iterations is usually small if the approximate solution is accurate.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the problem is infeasible or unbounded.
+    pub fn crossover(&self, problem: &Problem, options: &SolverOptions) -> Result<Solution, Error> {
+        assert_eq!(self.var_values.len(), problem.obj_coeffs.len());
+        assert_eq!(self.dual_values.len(), problem.constraints.len());
+        let solver = Solver::try_new_with_options(
+            &problem.obj_coeffs,
+            &problem.var_mins,
+            &problem.var_maxs,
+            &problem.constraints,
+            Some(&self.var_values),
+            &problem.solver_options(options),
+        )
+        .and_then(|mut solver| {
+            solver.initial_solve()?;
+            Ok(solver)
+        })
+        .map_err(|err| err.in_generation(problem.generation))?;
+        Ok(Solution {
+            direction: problem.direction,
+            num_vars: problem.obj_coeffs.len(),
+            obj_offset: problem.obj_offset,
+            generation: problem.generation,
+            solver,
+        })
+    }
+}
+
+impl std::ops::Index<Variable> for FirstOrderSolution {
+    type Output = f64;
+
+    fn index(&self, var: Variable) -> &Self::Output {
+        &self.var_values[self.var_idx(var)]
+    }
+}
+
+/// A linear map given by the products with the matrix and its transpose.
+pub(crate) trait LinearOperator {
+    /// out = A x
+    fn apply(&self, x: &[f64], out: &mut [f64]);
+    /// out = A^T y
+    fn apply_transpose(&self, y: &[f64], out: &mut [f64]);
+}
+
+/// A sparse matrix in the compressed row format.
+struct CsrMatrix {
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<f64>,
+}
+
+impl CsrMatrix {
+    fn row(&self, r: usize) -> impl Iterator<Item = (usize, &f64)> {
+        let range = self.indptr[r]..self.indptr[r + 1];
+        self.indices[range.clone()]
+            .iter()
+            .copied()
+            .zip(&self.data[range])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.indptr.len() - 1
+    }
+}
+
+impl LinearOperator for CsrMatrix {
+    fn apply(&self, x: &[f64], out: &mut [f64]) {
+        for (r, out) in out.iter_mut().enumerate() {
+            *out = self.row(r).map(|(c, &val)| val * x[c]).sum();
+        }
+    }
+
+    fn apply_transpose(&self, y: &[f64], out: &mut [f64]) {
+        out.iter_mut().for_each(|out| *out = 0.0);
+        for (r, &y) in y.iter().enumerate() {
+            for (c, &val) in self.row(r) {
+                out[c] += val * y;
+            }
+        }
+    }
+}
+
+/// A linear program min c^T x s.t. A x >= b (A x = b for equality rows), l <= x <= u
+/// in the scaled form: the original variables are `col_scales * x`, the original matrix
+/// is `A / (row_scales * col_scales)` and the original dual values are `row_scales * y`.
+pub(crate) struct ScaledLp<Op> {
+    pub(crate) matrix: Op,
+    pub(crate) obj_coeffs: Vec<f64>,
+    pub(crate) var_mins: Vec<f64>,
+    pub(crate) var_maxs: Vec<f64>,
+    pub(crate) rhs: Vec<f64>,
+    pub(crate) is_eq: Vec<bool>,
+    pub(crate) row_scales: Vec<f64>,
+    pub(crate) col_scales: Vec<f64>,
+}
+
+/// Errors of the optimality conditions in the original units.
+#[derive(Clone, Copy, Debug, Default)]
+struct Residuals {
+    primal: f64,
+    dual: f64,
+    primal_obj: f64,
+    dual_obj: f64,
+}
+
+impl Residuals {
+    fn gap(&self) -> f64 {
+        f64::abs(self.primal_obj - self.dual_obj)
+    }
+
+    fn kkt_error(&self) -> f64 {
+        f64::sqrt(self.primal * self.primal + self.dual * self.dual + self.gap() * self.gap())
+    }
+}
+
+/// A primal-dual iterate along with the matrix products.
+#[derive(Clone)]
+struct Iterate {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    ax: Vec<f64>,
+    aty: Vec<f64>,
+}
+
+pub(crate) struct PdhgOutput {
+    /// Variable values in the original units.
+    pub(crate) x: Vec<f64>,
+    /// Dual values of the A x >= b rows in the original units.
+    pub(crate) y: Vec<f64>,
+    pub(crate) primal_obj: f64,
+    pub(crate) iterations: usize,
+    pub(crate) is_converged: bool,
+    residuals: Residuals,
+}
+
+fn norm(vals: &[f64]) -> f64 {
+    vals.iter().map(|v| v * v).sum::<f64>().sqrt()
+}
+
+impl<Op> ScaledLp<Op> {
+    /// Scale the problem in the A x >= b form given the already scaled `matrix`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        matrix: Op,
+        obj_coeffs: &[f64],
+        var_mins: &[f64],
+        var_maxs: &[f64],
+        rhs: Vec<f64>,
+        is_eq: Vec<bool>,
+        row_scales: Vec<f64>,
+        col_scales: Vec<f64>,
+    ) -> Self {
+        let scale_cols = |vals: &[f64], mul: bool| {
+            vals.iter()
+                .zip(&col_scales)
+                .map(|(v, s)| if mul { v * s } else { v / s })
+                .collect::<Vec<_>>()
+        };
+        ScaledLp {
+            obj_coeffs: scale_cols(obj_coeffs, true),
+            var_mins: scale_cols(var_mins, false),
+            var_maxs: scale_cols(var_maxs, false),
+            rhs: rhs.iter().zip(&row_scales).map(|(b, s)| b * s).collect(),
+            is_eq,
+            matrix,
+            row_scales,
+            col_scales,
+        }
+    }
+}
+
+impl<Op: LinearOperator> ScaledLp<Op> {
+    fn num_rows(&self) -> usize {
+        self.rhs.len()
+    }
+
+    fn num_cols(&self) -> usize {
+        self.obj_coeffs.len()
+    }
+
+    fn residuals(&self, it: &Iterate) -> Residuals {
+        let mut res = Residuals::default();
+
+        let mut primal_sq = 0.0;
+        for r in 0..self.num_rows() {
+            let y = it.y[r] * self.row_scales[r];
+            let diff = (self.rhs[r] - it.ax[r]) / self.row_scales[r];
+            let violation = if self.is_eq[r] { diff } else { diff.max(0.0) };
+            primal_sq += violation * violation;
+            res.dual_obj += self.rhs[r] / self.row_scales[r] * y;
+        }
+        res.primal = primal_sq.sqrt();
+
+        let mut dual_sq = 0.0;
+        for c in 0..self.num_cols() {
+            let scale = self.col_scales[c];
+            let x = it.x[c] * scale;
+            res.primal_obj += self.obj_coeffs[c] / scale * x;
+
+            let reduced_cost = (self.obj_coeffs[c] - it.aty[c]) / scale;
+            let bound = if reduced_cost > 0.0 {
+                self.var_mins[c]
+            } else {
+                self.var_maxs[c]
+            } * scale;
+            if bound.is_finite() {
+                res.dual_obj += reduced_cost * bound;
+            } else {
+                dual_sq += reduced_cost * reduced_cost;
+            }
+        }
+        res.dual = dual_sq.sqrt();
+        res
+    }
+
+    fn is_optimal(&self, res: &Residuals, tolerance: f64) -> bool {
+        let rhs_norm = norm(
+            &(self.rhs.iter().zip(&self.row_scales))
+                .map(|(b, s)| b / s)
+                .collect::<Vec<_>>(),
+        );
+        let obj_norm = norm(
+            &(self.obj_coeffs.iter().zip(&self.col_scales))
+                .map(|(c, s)| c / s)
+                .collect::<Vec<_>>(),
+        );
+        res.primal <= tolerance * (1.0 + rhs_norm)
+            && res.dual <= tolerance * (1.0 + obj_norm)
+            && res.gap() <= tolerance * (1.0 + res.primal_obj.abs() + res.dual_obj.abs())
+    }
+
+    fn iterate(&self, x: Vec<f64>, y: Vec<f64>) -> Iterate {
+        let mut ax = vec![0.0; self.num_rows()];
+        let mut aty = vec![0.0; self.num_cols()];
+        self.matrix.apply(&x, &mut ax);
+        self.matrix.apply_transpose(&y, &mut aty);
+        Iterate { x, y, ax, aty }
+    }
+
+    /// Estimate the spectral norm of the matrix with the power method.
+    fn matrix_norm(&self) -> f64 {
+        let mut v = vec![1.0 / (self.num_cols().max(1) as f64).sqrt(); self.num_cols()];
+        let mut av = vec![0.0; self.num_rows()];
+        let mut norm_sq = 0.0;
+        for _ in 0..POWER_ITERATIONS {
+            self.matrix.apply(&v, &mut av);
+            self.matrix.apply_transpose(&av, &mut v);
+            norm_sq = norm(&v);
+            if norm_sq == 0.0 {
+                break;
+            }
+            v.iter_mut().for_each(|v| *v /= norm_sq);
+        }
+        norm_sq.sqrt()
+    }
+
+    /// Solve the problem with the primal-dual hybrid gradient method with restarts
+    /// to the average iterate and primal weight updates (as in PDLP). See
+    /// Applegate, D., Díaz, M., Hinder, O., Lu, H., Lubin, M., O'Donoghue, B., & Schudy, W.
+    /// (2021). Practical large-scale linear programming using primal-dual hybrid gradient.
+    /// Advances in Neural Information Processing Systems, 34, 20243-20257.
+    pub(crate) fn solve(&self, options: &FirstOrderOptions) -> PdhgOutput {
+        let (num_rows, num_cols) = (self.num_rows(), self.num_cols());
+        let x = (0..num_cols)
+            .map(|c| f64::min(f64::max(0.0, self.var_mins[c]), self.var_maxs[c]))
+            .collect();
+        let mut cur = self.iterate(x, vec![0.0; num_rows]);
+
+        let matrix_norm = self.matrix_norm();
+        let step_size = if matrix_norm > 0.0 {
+            STEP_SIZE_FACTOR / matrix_norm
+        } else {
+            1.0
+        };
+        let (obj_norm, rhs_norm) = (norm(&self.obj_coeffs), norm(&self.rhs));
+        let mut primal_weight = if obj_norm > 0.0 && rhs_norm > 0.0 {
+            obj_norm / rhs_norm
+        } else {
+            1.0
+        };
+
+        let mut sum_x = vec![0.0; num_cols];
+        let mut sum_y = vec![0.0; num_rows];
+        let mut num_summed = 0;
+        let mut last_restart = cur.clone();
+        let mut last_restart_iter = 0;
+        let mut last_restart_error = self.residuals(&cur).kkt_error();
+        let mut last_candidate_error = f64::INFINITY;
+        let mut best = (cur.clone(), self.residuals(&cur));
+
+        let mut next = cur.clone();
+        for iter in 1..=options.max_iterations {
+            let primal_step = step_size / primal_weight;
+            let dual_step = step_size * primal_weight;
+
+            for c in 0..num_cols {
+                let val = cur.x[c] - primal_step * (self.obj_coeffs[c] - cur.aty[c]);
+                next.x[c] = f64::min(f64::max(val, self.var_mins[c]), self.var_maxs[c]);
+            }
+            self.matrix.apply(&next.x, &mut next.ax);
+            for r in 0..num_rows {
+                let extrapolated_ax = 2.0 * next.ax[r] - cur.ax[r];
+                let val = cur.y[r] + dual_step * (self.rhs[r] - extrapolated_ax);
+                next.y[r] = if self.is_eq[r] { val } else { val.max(0.0) };
+            }
+            self.matrix.apply_transpose(&next.y, &mut next.aty);
+            std::mem::swap(&mut cur, &mut next);
+
+            sum_x.iter_mut().zip(&cur.x).for_each(|(s, x)| *s += x);
+            sum_y.iter_mut().zip(&cur.y).for_each(|(s, y)| *s += y);
+            num_summed += 1;
+
+            if iter % CHECK_INTERVAL != 0 && iter != options.max_iterations {
+                continue;
+            }
+
+            let average = self.iterate(
+                sum_x.iter().map(|s| s / num_summed as f64).collect(),
+                sum_y.iter().map(|s| s / num_summed as f64).collect(),
+            );
+            let (cur_res, average_res) = (self.residuals(&cur), self.residuals(&average));
+            let (candidate, candidate_res) = if average_res.kkt_error() < cur_res.kkt_error() {
+                (average, average_res)
+            } else {
+                (cur.clone(), cur_res)
+            };
+            trace!(
+                "PDHG iteration {}: primal {}, dual {}, gap {}",
+                iter,
+                candidate_res.primal,
+                candidate_res.dual,
+                candidate_res.gap(),
+            );
+
+            if self.is_optimal(&candidate_res, options.tolerance) {
+                return self.output(candidate, candidate_res, iter, true);
+            }
+            let candidate_error = candidate_res.kkt_error();
+            if candidate_error < best.1.kkt_error() {
+                best = (candidate.clone(), candidate_res);
+            }
+
+            let should_restart = candidate_error <= SUFFICIENT_RESTART_DECAY * last_restart_error
+                || (candidate_error <= NECESSARY_RESTART_DECAY * last_restart_error
+                    && candidate_error > last_candidate_error)
+                || (iter - last_restart_iter) as f64 >= ARTIFICIAL_RESTART_FRACTION * iter as f64;
+            last_candidate_error = candidate_error;
+            if should_restart {
+                let diff = |a: &[f64], b: &[f64]| {
+                    let diff = a.iter().zip(b).map(|(a, b)| a - b).collect::<Vec<_>>();
+                    norm(&diff)
+                };
+                let primal_diff = diff(&candidate.x, &last_restart.x);
+                let dual_diff = diff(&candidate.y, &last_restart.y);
+                if primal_diff > 0.0 && dual_diff > 0.0 {
+                    primal_weight = f64::exp(
+                        PRIMAL_WEIGHT_SMOOTHING * f64::ln(dual_diff / primal_diff)
+                            + (1.0 - PRIMAL_WEIGHT_SMOOTHING) * f64::ln(primal_weight),
+                    );
+                }
+
+                cur = candidate;
+                last_restart = cur.clone();
+                last_restart_iter = iter;
+                last_restart_error = candidate_error;
+                last_candidate_error = f64::INFINITY;
+                sum_x.iter_mut().for_each(|s| *s = 0.0);
+                sum_y.iter_mut().for_each(|s| *s = 0.0);
+                num_summed = 0;
+            }
+        }
+
+        let (best, best_res) = best;
+        self.output(best, best_res, options.max_iterations, false)
+    }
+
+    fn output(
+        &self,
+        it: Iterate,
+        residuals: Residuals,
+        iterations: usize,
+        is_converged: bool,
+    ) -> PdhgOutput {
+        let x = it.x.iter().zip(&self.col_scales).map(|(x, s)| x * s);
+        let y = it.y.iter().zip(&self.row_scales).map(|(y, s)| y * s);
+        PdhgOutput {
+            x: x.collect(),
+            y: y.collect(),
+            primal_obj: residuals.primal_obj,
+            iterations,
+            is_converged,
+            residuals,
+        }
+    }
+}
+
+/// Transform the problem into the A x >= b form and scale it with the Ruiz equilibration.
+fn scaled_lp(problem: &Problem) -> ScaledLp<CsrMatrix> {
+    let num_cols = problem.obj_coeffs.len();
+    let mut matrix = CsrMatrix {
+        indptr: vec![0],
+        indices: vec![],
+        data: vec![],
+    };
+    let mut rhs = vec![];
+    let mut is_eq = vec![];
+    for (coeffs, cmp_op, b) in &problem.constraints {
+        let sign = match cmp_op {
+            ComparisonOp::Le => -1.0,
+            ComparisonOp::Ge | ComparisonOp::Eq => 1.0,
+        };
+        for (c, &val) in coeffs.iter() {
+            matrix.indices.push(c);
+            matrix.data.push(sign * val);
+        }
+        matrix.indptr.push(matrix.indices.len());
+        rhs.push(sign * b);
+        is_eq.push(*cmp_op == ComparisonOp::Eq);
+    }
+
+    let (row_scales, col_scales) = ruiz_scales(matrix.num_rows(), num_cols, |r| {
+        matrix.row(r).map(|(c, &val)| (c, val))
+    });
+    for (r, &row_scale) in row_scales.iter().enumerate() {
+        for i in matrix.indptr[r]..matrix.indptr[r + 1] {
+            matrix.data[i] *= row_scale * col_scales[matrix.indices[i]];
+        }
+    }
+    ScaledLp::new(
+        matrix,
+        &problem.obj_coeffs,
+        &problem.var_mins,
+        &problem.var_maxs,
+        rhs,
+        is_eq,
+        row_scales,
+        col_scales,
+    )
+}
+
+/// Row and column scales equilibrating the matrix with rows given by `row` (the Ruiz method).
+/// The matrix is only read row by row so it can be stored out of core.
+pub(crate) fn ruiz_scales<I: Iterator<Item = (usize, f64)>>(
+    num_rows: usize,
+    num_cols: usize,
+    row: impl Fn(usize) -> I,
+) -> (Vec<f64>, Vec<f64>) {
+    let mut row_scales = vec![1.0; num_rows];
+    let mut col_scales = vec![1.0; num_cols];
+    for _ in 0..RUIZ_ITERATIONS {
+        let mut row_max = vec![0.0f64; num_rows];
+        let mut col_max = vec![0.0f64; num_cols];
+        for (r, row_max) in row_max.iter_mut().enumerate() {
+            for (c, val) in row(r) {
+                let val = f64::abs(val * row_scales[r] * col_scales[c]);
+                *row_max = row_max.max(val);
+                col_max[c] = col_max[c].max(val);
+            }
+        }
+        for (scale, max) in row_scales.iter_mut().zip(row_max) {
+            if max > 0.0 {
+                *scale /= max.sqrt();
+            }
+        }
+        for (scale, max) in col_scales.iter_mut().zip(col_max) {
+            if max > 0.0 {
+                *scale /= max.sqrt();
+            }
+        }
+    }
+    (row_scales, col_scales)
+}
+
+pub(crate) fn solve(problem: &Problem, options: &FirstOrderOptions) -> FirstOrderSolution {
+    let output = scaled_lp(problem).solve(options);
+    let cmp_ops = problem.constraints.iter().map(|(_, cmp_op, _)| *cmp_op);
+    solution(
+        output,
+        problem.direction,
+        cmp_ops,
+        problem.obj_offset,
+        problem.generation,
+    )
+}
+
+/// Convert the output for the min c^T x s.t. A x >= b form (`Le` rows negated) to
+/// the solution of the problem.
+pub(crate) fn solution(
+    output: PdhgOutput,
+    direction: OptimizationDirection,
+    cmp_ops: impl Iterator<Item = ComparisonOp>,
+    obj_offset: f64,
+    generation: u32,
+) -> FirstOrderSolution {
+    debug!(
+        "first-order method finished after {} iterations (converged: {})",
+        output.iterations, output.is_converged,
+    );
+
+    let dir_sign = match direction {
+        OptimizationDirection::Minimize => 1.0,
+        OptimizationDirection::Maximize => -1.0,
+    };
+    let dual_values = output
+        .y
+        .iter()
+        .zip(cmp_ops)
+        .map(|(y, cmp_op)| match cmp_op {
+            ComparisonOp::Le => -dir_sign * y,
+            ComparisonOp::Ge | ComparisonOp::Eq => dir_sign * y,
+        })
+        .collect();
+
+    FirstOrderSolution {
+        objective: dir_sign * (output.primal_obj + obj_offset),
+        var_values: output.x,
+        dual_values,
+        iterations: output.iterations,
+        is_converged: output.is_converged,
+        residuals: output.residuals,
+        generation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn first_order() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(3.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.5, (0.0, f64::INFINITY));
+        let z = problem.add_var(-1.0, (f64::NEG_INFINITY, 2.0));
+        let c0 = problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        let c1 = problem.add_constraint([(x, 1.0), (y, 3.0), (z, 1.0)], ComparisonOp::Le, 9.0);
+        let c2 = problem.add_constraint([(x, 1.0)], ComparisonOp::Le, 3.0);
+        let c3 = problem.add_constraint([(z, 2.0), (y, 1.0)], ComparisonOp::Ge, 1.0);
+        let c4 = problem.add_constraint([(x, 1.0), (z, -1.0)], ComparisonOp::Eq, 2.5);
+        let exact = problem.solve().unwrap();
+        assert_eq!((exact[x], exact[y], exact.objective()), (2.0, 2.0, 11.5));
+
+        let sol = problem.solve_first_order(&FirstOrderOptions::default());
+        assert!(sol.is_converged());
+        assert!(sol.iterations() > 0);
+        assert!(f64::abs(sol.objective() - exact.objective()) < 1e-4);
+        for var in [x, y, z] {
+            assert!(f64::abs(sol[var] - exact[var]) < 1e-4);
+        }
+        for constr in [c0, c1, c2, c3, c4] {
+            assert!(f64::abs(sol.dual_value(constr) - exact.dual_value(constr)) < 1e-4);
+        }
+        assert!(sol.primal_residual() < 1e-4);
+        assert!(sol.dual_residual() < 1e-4);
+        assert!(sol.duality_gap() < 1e-4);
+
+        let polished = sol.crossover(&problem, &SolverOptions::default()).unwrap();
+        assert_eq!(polished.objective(), exact.objective());
+
+        let options = FirstOrderOptions {
+            max_iterations: 10,
+            ..FirstOrderOptions::default()
+        };
+        let sol = problem.solve_first_order(&options);
+        assert!(!sol.is_converged());
+        assert_eq!(sol.iterations(), 10);
+        let polished = sol.crossover(&problem, &SolverOptions::default()).unwrap();
+        assert!(f64::abs(polished.objective() - exact.objective()) < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not in the solution")]
+    fn old_generation() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 1.0));
+        let y = problem.add_var(1.0, (0.0, 2.0));
+        problem.add_var(1.0, (0.0, 3.0));
+        problem.remove_var(x).unwrap();
+        problem.compact();
+        let sol = problem.solve_first_order(&FirstOrderOptions::default());
+        // The old handle of y has the index of the third var after the compaction.
+        let _ = sol.var_value(y);
+    }
+}
@@ -0,0 +1,588 @@
+use crate::{
+    helpers::{is_integral, INTEGRALITY_TOL},
+    parallel, ComparisonOp, ConstraintRef, CsVec, Error, Phase, Problem, Variable,
+};
+use std::collections::HashMap;
+
+/// Coefficient and rhs changes smaller than this are ignored.
+const MIN_CHANGE: f64 = 1e-9;
+/// Integral coeffs bigger than this are not exactly representable as integers.
+const MAX_INTEGER: f64 = 1e15;
+
+/// Tighten a row `coeffs * x <= rhs`. Returns true if the row was changed.
+fn tighten_row(
+    coeffs: &mut [(usize, f64)],
+    rhs: &mut f64,
+    is_integer: &[bool],
+    var_mins: &[f64],
+    var_maxs: &[f64],
+) -> Result<bool, Error> {
+    let mut changed = false;
+
+    let mut max_act = 0.0;
+    let mut min_act = 0.0;
+    for &(v, coeff) in coeffs.iter() {
+        let (lo, hi) = if coeff > 0.0 {
+            (var_mins[v], var_maxs[v])
+        } else {
+            (var_maxs[v], var_mins[v])
+        };
+        min_act += coeff * lo;
+        max_act += coeff * hi;
+    }
+    if min_act > *rhs + 1e-6 * f64::max(1.0, rhs.abs()) {
+        return Err(Error::infeasible(Phase::Presolve).with_violation(min_act - *rhs));
+    }
+
+    // Coefficient tightening: if the row can't be violated for one of the values of a binary
+    // var, its coefficient can be decreased in abs. value without losing integer solutions.
+    if max_act.is_finite() {
+        for (v, coeff) in coeffs.iter_mut() {
+            let v = *v;
+            if !is_integer[v] || var_mins[v] != 0.0 || var_maxs[v] != 1.0 {
+                continue;
+            }
+            if *coeff > 0.0 && max_act - *coeff < *rhs - MIN_CHANGE {
+                // Redundant for x = 0.
+                let delta = *rhs - (max_act - *coeff);
+                *coeff -= delta;
+                *rhs -= delta;
+                max_act -= delta;
+                changed = true;
+            } else if *coeff < 0.0 && max_act + *coeff < *rhs - MIN_CHANGE {
+                // Redundant for x = 1.
+                *coeff = *rhs - max_act;
+                changed = true;
+            }
+        }
+    }
+
+    if is_pure_integer(coeffs, is_integer) {
+        changed |= reduce_pure_integer(coeffs, rhs, false)?;
+    }
+
+    Ok(changed)
+}
+
+fn is_pure_integer(coeffs: &[(usize, f64)], is_integer: &[bool]) -> bool {
+    coeffs
+        .iter()
+        .all(|&(v, coeff)| is_integer[v] && is_integral(coeff) && coeff.abs() < MAX_INTEGER)
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// In a row with integral coeffs over integer vars the lhs is a multiple of the GCD of the
+/// coeffs. Divide the row `coeffs * x <= rhs` (or `== rhs` if `is_eq`) by the GCD and round
+/// the rhs (Chvátal-Gomory strengthening). Returns true if the row was changed.
+fn reduce_pure_integer(
+    coeffs: &mut [(usize, f64)],
+    rhs: &mut f64,
+    is_eq: bool,
+) -> Result<bool, Error> {
+    let divisor = coeffs
+        .iter()
+        .fold(0, |acc, &(_, coeff)| gcd(acc, coeff.abs().round() as u64));
+    if divisor == 0 {
+        return Ok(false);
+    }
+    let divisor = divisor as f64;
+    let reduced_rhs = *rhs / divisor;
+    if is_integral(reduced_rhs) {
+        if divisor == 1.0 {
+            return Ok(false);
+        }
+    } else if is_eq {
+        // The rhs is not attainable.
+        let violation = f64::abs(reduced_rhs - reduced_rhs.round()) * divisor;
+        return Err(Error::infeasible(Phase::Presolve).with_violation(violation));
+    }
+
+    for (_, coeff) in coeffs.iter_mut() {
+        *coeff = (*coeff / divisor).round();
+    }
+    *rhs = if is_integral(reduced_rhs) {
+        reduced_rhs.round()
+    } else {
+        reduced_rhs.floor()
+    };
+    Ok(true)
+}
+
+/// Tightened row and rhs of the constraint (in its original direction) or None if it can't
+/// be tightened.
+fn tighten_constraint(
+    (coeffs, cmp_op, rhs): &(CsVec, ComparisonOp, f64),
+    is_integer: &[bool],
+    var_mins: &[f64],
+    var_maxs: &[f64],
+) -> Result<Option<(CsVec, f64)>, Error> {
+    let sign = match cmp_op {
+        ComparisonOp::Ge => -1.0,
+        ComparisonOp::Le | ComparisonOp::Eq => 1.0,
+    };
+    let mut row: Vec<(usize, f64)> = coeffs.iter().map(|(v, &c)| (v, sign * c)).collect();
+    let mut row_rhs = sign * *rhs;
+    let changed = match cmp_op {
+        ComparisonOp::Eq => {
+            is_pure_integer(&row, is_integer) && reduce_pure_integer(&mut row, &mut row_rhs, true)?
+        }
+        _ => tighten_row(&mut row, &mut row_rhs, is_integer, var_mins, var_maxs)?,
+    };
+    if !changed {
+        return Ok(None);
+    }
+    row.retain(|&(_, coeff)| coeff != 0.0);
+    let (vars, vals) = row.into_iter().map(|(v, c)| (v, sign * c)).unzip();
+    Ok(Some((CsVec::new(coeffs.dim(), vars, vals), sign * row_rhs)))
+}
+
+pub(crate) fn tighten_integer_rows(
+    problem: &mut Problem,
+    integer_vars: &[Variable],
+    threads: usize,
+) -> Result<usize, Error> {
+    let mut is_integer = vec![false; problem.obj_coeffs.len()];
+    for &var in integer_vars {
+        is_integer[problem.var_idx(var)] = true;
+    }
+
+    // Rows are tightened independently, so chunks of them are tightened in parallel
+    // and the results are applied in the row order.
+    let constraints = &problem.constraints;
+    let (var_mins, var_maxs) = (&problem.var_mins, &problem.var_maxs);
+    let chunks = parallel::map_chunks(threads, constraints.len(), |rows| {
+        rows.map(|constr| {
+            tighten_constraint(&constraints[constr], &is_integer, var_mins, var_maxs)
+                .map_err(|err| err.with_constraint(constr))
+        })
+        .collect::<Vec<_>>()
+    });
+
+    let mut num_changed = 0;
+    for (constr, res) in chunks.into_iter().flatten().enumerate() {
+        if let Some((coeffs, rhs)) = res? {
+            problem.constraints[constr].0 = coeffs;
+            problem.constraints[constr].2 = rhs;
+            num_changed += 1;
+        }
+    }
+    debug!("presolve: tightened {} integer rows", num_changed);
+    Ok(num_changed)
+}
+
+/// Rows are grouped by their nonzero coefficients divided by the first one (as bits).
+type RowKey = Vec<(usize, u64)>;
+
+/// Key of the inequality row, whether it is `<=` after the division and the divided rhs.
+/// None for equalities and rows with no nonzero coefficients.
+fn inequality_key(
+    (coeffs, cmp_op, rhs): &(CsVec, ComparisonOp, f64),
+) -> Option<(RowKey, bool, f64)> {
+    let is_le = match cmp_op {
+        ComparisonOp::Le => true,
+        ComparisonOp::Ge => false,
+        ComparisonOp::Eq => return None,
+    };
+    // Explicit zeros (kept in the permissive mode) are ignored.
+    let nonzeros = || coeffs.iter().filter(|&(_, &coeff)| coeff != 0.0);
+    let (_, &scale) = nonzeros().next()?;
+    let key = nonzeros()
+        .map(|(v, &coeff)| (v, (coeff / scale).to_bits()))
+        .collect();
+    Some((key, is_le == (scale > 0.0), *rhs / scale))
+}
+
+pub(crate) fn merge_inequality_pairs(
+    problem: &mut Problem,
+    threads: usize,
+) -> Vec<(ConstraintRef, ConstraintRef)> {
+    // Keys are computed in parallel, rows are then matched in the row order.
+    let constraints = &problem.constraints;
+    let keys = parallel::map_chunks(threads, constraints.len(), |rows| {
+        rows.map(|constr| inequality_key(&constraints[constr]))
+            .collect::<Vec<_>>()
+    });
+
+    // Unmatched rows of each group are kept as (index, is `<=`, divided rhs).
+    type Unmatched = Vec<(usize, bool, f64)>;
+    let mut groups: HashMap<RowKey, Unmatched> = HashMap::new();
+    let mut pairs = vec![];
+    for (constr, key) in keys.into_iter().flatten().enumerate() {
+        let (key, is_le, scaled_rhs) = match key {
+            Some(key) => key,
+            None => continue,
+        };
+        let unmatched = groups.entry(key).or_default();
+        let opposite = unmatched.iter().position(|&(_, other_is_le, other_rhs)| {
+            other_is_le != is_le && other_rhs == scaled_rhs
+        });
+        match opposite {
+            Some(pos) => {
+                let (first, _, _) = unmatched.swap_remove(pos);
+                pairs.push((first, constr));
+            }
+            None => unmatched.push((constr, is_le, scaled_rhs)),
+        }
+    }
+
+    for &(first, second) in &pairs {
+        problem.constraints[first].1 = ComparisonOp::Eq;
+        problem
+            .remove_constraint(problem.constraint_ref(second))
+            .unwrap();
+    }
+    debug!("presolve: merged {} inequality pairs", pairs.len());
+    pairs
+        .into_iter()
+        .map(|(first, second)| {
+            (
+                problem.constraint_ref(first),
+                problem.constraint_ref(second),
+            )
+        })
+        .collect()
+}
+
+/// Union-find over rows that tracks the parity of the path to the root, used to check
+/// whether rows can be split into two sets with the prescribed relations.
+struct ParityUnionFind {
+    parent: Vec<usize>,
+    /// Whether the row is in a different set than its parent.
+    parity: Vec<bool>,
+}
+
+impl ParityUnionFind {
+    fn new(size: usize) -> Self {
+        ParityUnionFind {
+            parent: (0..size).collect(),
+            parity: vec![false; size],
+        }
+    }
+
+    fn find(&mut self, node: usize) -> (usize, bool) {
+        let parent = self.parent[node];
+        if parent == node {
+            return (node, false);
+        }
+        let (root, parent_parity) = self.find(parent);
+        self.parent[node] = root;
+        self.parity[node] ^= parent_parity;
+        (root, self.parity[node])
+    }
+
+    /// Require the rows to be in different sets (if `parity` is true) or in the same set.
+    /// Returns false if this contradicts the previous requirements.
+    fn union(&mut self, node1: usize, node2: usize, parity: bool) -> bool {
+        let (root1, parity1) = self.find(node1);
+        let (root2, parity2) = self.find(node2);
+        if root1 == root2 {
+            return parity1 ^ parity2 == parity;
+        }
+        self.parent[root1] = root2;
+        self.parity[root1] = parity1 ^ parity2 ^ parity;
+        true
+    }
+}
+
+pub(crate) fn implied_integer_vars(problem: &Problem, integer_vars: &[Variable]) -> Vec<Variable> {
+    let num_vars = problem.obj_coeffs.len();
+    let num_rows = problem.constraints.len();
+    let mut is_integer = vec![false; num_vars];
+    for &var in integer_vars {
+        is_integer[problem.var_idx(var)] = true;
+    }
+
+    let mut cols = vec![vec![]; num_vars];
+    for (r, (coeffs, _, _)) in problem.constraints.iter().enumerate() {
+        for (v, &coeff) in coeffs.iter() {
+            if coeff != 0.0 {
+                cols[v].push((r, coeff));
+            }
+        }
+    }
+
+    // Candidates are continuous vars with integral bounds and at most two +-1 coeffs.
+    let is_integral_bound = |val: f64| val.is_infinite() || is_integral(val);
+    let mut is_candidate = (0..num_vars)
+        .map(|v| {
+            !is_integer[v]
+                && is_integral_bound(problem.var_mins[v])
+                && is_integral_bound(problem.var_maxs[v])
+                && cols[v].len() <= 2
+                && cols[v]
+                    .iter()
+                    .all(|&(_, coeff)| f64::abs(coeff.abs() - 1.0) <= INTEGRALITY_TOL)
+        })
+        .collect::<Vec<_>>();
+
+    // Rows with integral rhs and integral coeffs of the integer vars.
+    let is_good_row = problem
+        .constraints
+        .iter()
+        .map(|(coeffs, _, rhs)| {
+            is_integral(*rhs)
+                && coeffs
+                    .iter()
+                    .all(|(v, &coeff)| !is_integer[v] || is_integral(coeff))
+        })
+        .collect::<Vec<_>>();
+
+    loop {
+        let mut changed = false;
+
+        // All continuous vars of a row must be candidates and the row must be good.
+        for (r, (coeffs, _, _)) in problem.constraints.iter().enumerate() {
+            let is_ok = is_good_row[r]
+                && coeffs
+                    .iter()
+                    .all(|(v, &coeff)| coeff == 0.0 || is_integer[v] || is_candidate[v]);
+            if !is_ok {
+                for (v, &coeff) in coeffs.iter() {
+                    if coeff != 0.0 && is_candidate[v] {
+                        is_candidate[v] = false;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        // Heller-Tompkins condition for total unimodularity of the candidate columns:
+        // rows can be split into two sets so that two nonzeros of the same sign in a column
+        // are in different sets and nonzeros of opposite signs are in the same set.
+        let mut union_find = ParityUnionFind::new(num_rows);
+        for v in 0..num_vars {
+            if let (true, [(r1, coeff1), (r2, coeff2)]) = (is_candidate[v], cols[v].as_slice()) {
+                let is_same_sign = (*coeff1 > 0.0) == (*coeff2 > 0.0);
+                if !union_find.union(*r1, *r2, is_same_sign) {
+                    is_candidate[v] = false;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    (0..num_vars)
+        .filter(|&v| is_candidate[v])
+        .map(|v| problem.var_ref(v))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn coefficient_tightening() {
+        // x + 3 * y <= 2.5 with binary y and 0 <= x <= 1 becomes x + 1.5 * y <= 1.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(2.0, (0.0, 1.0));
+        let y = problem.add_var(1.0, (0.0, 1.0));
+        problem.add_constraint([(x, 1.0), (y, 3.0)], ComparisonOp::Le, 2.5);
+        let relaxation = problem.solve().unwrap();
+        assert_eq!(relaxation.objective(), 2.5);
+
+        let mut tightened = problem.clone();
+        assert_eq!(tightened.tighten_integer_rows(&[y]).unwrap(), 1);
+        let (coeffs, _, rhs) = &tightened.constraints[0];
+        assert_eq!(coeffs.data(), &[1.0, 1.5]);
+        assert_eq!(*rhs, 1.0);
+        assert_eq!(tightened.solve().unwrap().objective(), 2.0);
+    }
+
+    #[test]
+    fn rounding() {
+        // In -2 * x - 4 * y >= -7.5 the lhs is an even integer, so the row becomes
+        // -x - 2 * y >= -3.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+        problem.add_constraint([(x, -2.0), (y, -4.0)], ComparisonOp::Ge, -7.5);
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 20.0);
+        assert_eq!(problem.tighten_integer_rows(&[x, y]).unwrap(), 1);
+        let (coeffs, cmp_op, rhs) = &problem.constraints[0];
+        assert_eq!(coeffs.data(), &[-1.0, -2.0]);
+        assert!(matches!(cmp_op, ComparisonOp::Ge));
+        assert_eq!(*rhs, -3.0);
+
+        // Not all vars are integer.
+        assert_eq!(problem.tighten_integer_rows(&[x]).unwrap(), 0);
+
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, -1.0);
+        let err = problem.tighten_integer_rows(&[x]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Infeasible);
+        assert_eq!(err.constraint(), Some(ConstraintRef(2, 0)));
+        assert_eq!(err.violation(), Some(1.0));
+    }
+
+    #[test]
+    fn gcd_reduction() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+        let z = problem.add_var(1.0, (0.0, 10.0));
+        problem.add_constraint([(x, 4.0), (y, 6.0)], ComparisonOp::Le, 15.0);
+        problem.add_constraint([(y, -3.0), (z, 6.0)], ComparisonOp::Eq, 9.0);
+        assert_eq!(problem.tighten_integer_rows(&[x, y, z]).unwrap(), 2);
+        let (coeffs, _, rhs) = &problem.constraints[0];
+        assert_eq!((coeffs.data(), *rhs), (&[2.0, 3.0][..], 7.0));
+        let (coeffs, _, rhs) = &problem.constraints[1];
+        assert_eq!((coeffs.data(), *rhs), (&[-1.0, 2.0][..], 3.0));
+        assert_eq!(problem.tighten_integer_rows(&[x, y, z]).unwrap(), 0);
+
+        // 2 * x - 4 * z == 5 has no integer solutions.
+        problem.add_constraint([(x, 2.0), (z, -4.0)], ComparisonOp::Eq, 5.0);
+        let err = problem.tighten_integer_rows(&[x, z]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Infeasible);
+        assert_eq!(err.phase(), Phase::Presolve);
+    }
+
+    #[test]
+    #[should_panic(expected = "variable 1 (generation 0) is not in the problem")]
+    fn removed_integer_var() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+        problem.add_constraint([(x, 1.0)], ComparisonOp::Le, 3.5);
+        problem.remove_var(y).unwrap();
+        let _ = problem.tighten_integer_rows(&[x, y]);
+    }
+
+    #[test]
+    fn inequality_pairs() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, 10.0));
+        let y = problem.add_var(2.0, (0.0, 10.0));
+        let c0 = problem.add_constraint([(x, 1.0), (y, 2.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 3.0);
+        // Same as c0 with the opposite sign.
+        let c2 = problem.add_constraint([(x, -2.0), (y, -4.0)], ComparisonOp::Le, -8.0);
+        // Different rhs.
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+        let c4 = problem.add_constraint([(x, 3.0), (y, 3.0)], ComparisonOp::Ge, 9.0);
+        let expected = problem.solve().unwrap().objective();
+
+        assert_eq!(
+            problem.merge_inequality_pairs(),
+            vec![(c0, c2), (ConstraintRef(1, 0), c4)]
+        );
+        assert_eq!(problem.constraints[0].1, ComparisonOp::Eq);
+        assert!(!problem.has_constraint(c2));
+        assert_eq!(problem.solve().unwrap().objective(), expected);
+        assert_eq!(problem.merge_inequality_pairs(), vec![]);
+
+        // Explicit zero leading coefficients don't count as the first coefficient.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(0.0, (0.0, 10.0));
+        let y = problem.add_var(1.0, (0.0, 10.0));
+        problem.add_constraint([(x, 0.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, 0.0), (y, 3.0)], ComparisonOp::Ge, 8.0);
+        problem.add_constraint([(x, 0.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint([(x, 0.0)], ComparisonOp::Ge, -1.0);
+        let c4 = problem.add_constraint([(y, 1.0)], ComparisonOp::Le, 3.0);
+        let c5 = problem.add_constraint([(x, 0.0), (y, 2.0)], ComparisonOp::Ge, 6.0);
+        assert_eq!(problem.merge_inequality_pairs(), vec![(c4, c5)]);
+        assert_eq!(problem.solve().unwrap().objective(), 3.0);
+    }
+
+    #[test]
+    fn parallel_row_passes() {
+        // Rows i and i + 1500 are the same with the opposite directions.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let vars: Vec<_> = (0..10).map(|_| problem.add_var(1.0, (0.0, 5.0))).collect();
+        for i in 0..3000 {
+            let (x, y) = (vars[i % 10], vars[(i / 10) % 10]);
+            let coeff = (i % 3) as f64 + 2.0;
+            let cmp_op = if i < 1500 {
+                ComparisonOp::Le
+            } else {
+                ComparisonOp::Ge
+            };
+            let rhs = 0.5 * (i % 20) as f64;
+            problem.add_constraint([(x, coeff), (y, 2.0 * coeff)], cmp_op, rhs);
+        }
+        let options = SolverOptions {
+            threads: 4,
+            ..Default::default()
+        };
+
+        let mut expected = problem.clone();
+        let pairs = expected.merge_inequality_pairs();
+        assert!(!pairs.is_empty());
+        let mut merged = problem.clone();
+        assert_eq!(merged.merge_inequality_pairs_with(&options), pairs);
+        assert_eq!(merged.to_string(), expected.to_string());
+
+        let mut expected = problem.clone();
+        let num_tightened = expected.tighten_integer_rows(&vars).unwrap();
+        assert!(num_tightened > 0);
+        assert_eq!(
+            problem.tighten_integer_rows_with(&vars, &options),
+            Ok(num_tightened)
+        );
+        assert_eq!(problem.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn implied_integers() {
+        // Transportation problem from 2 sources to 3 sinks.
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let supply = [5.0, 7.0];
+        let demand = [3.0, 4.0, 5.0];
+        let mut vars = vec![];
+        for i in 0..2 {
+            for j in 0..3 {
+                vars.push(problem.add_var((1 + i + 2 * j) as f64, (0.0, f64::INFINITY)));
+            }
+        }
+        for i in 0..2 {
+            let terms = (0..3).map(|j| (vars[3 * i + j], 1.0));
+            problem.add_constraint(terms.collect::<Vec<_>>(), ComparisonOp::Le, supply[i]);
+        }
+        for j in 0..3 {
+            let terms = (0..2).map(|i| (vars[3 * i + j], 1.0));
+            problem.add_constraint(terms.collect::<Vec<_>>(), ComparisonOp::Ge, demand[j]);
+        }
+        assert_eq!(problem.implied_integer_vars(&[]), vars);
+        let solution = problem.solve().unwrap();
+        assert!(solution.iter().all(|(_, &val)| val == val.round()));
+
+        // A fractional supply breaks the integrality of the vars of its row and then
+        // of the vars of the demand rows.
+        let mut fractional = problem.clone();
+        fractional.constraints[0].2 = 5.5;
+        assert_eq!(fractional.implied_integer_vars(&[]), vec![]);
+
+        // Odd cycle: x + y <= 1, y + z <= 1, x + z <= 1 is not TU.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let z = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint([(y, 1.0), (z, 1.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint([(x, 1.0), (z, 1.0)], ComparisonOp::Le, 1.0);
+        assert_eq!(problem.implied_integer_vars(&[]), vec![]);
+        assert_eq!(problem.solve().unwrap().objective(), 1.5);
+        // But if z is integer, x and y are integral.
+        assert_eq!(problem.implied_integer_vars(&[z]), vec![x, y]);
+
+        // Continuous var with an integer var: w = 2 * v + 3.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let v = problem.add_var(1.0, (0.0, 2.5));
+        let w = problem.add_var(1.0, (f64::NEG_INFINITY, f64::INFINITY));
+        problem.add_constraint([(w, 1.0), (v, -2.0)], ComparisonOp::Eq, 3.0);
+        assert_eq!(problem.implied_integer_vars(&[v]), vec![w]);
+        assert_eq!(problem.implied_integer_vars(&[]), vec![]);
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{ComparisonOp, Cut, CutPool, Solution, Variable};
+
+/// Bound changes smaller than this are ignored by the propagation.
+const BOUND_TOL: f64 = 1e-7;
+/// Bounds crossing by more than this mean that the problem is infeasible.
+const FEASIBILITY_TOL: f64 = 1e-6;
+/// Max number of propagation passes over all rows.
+const MAX_PROPAGATION_PASSES: usize = 10;
+/// Max number of binary vars probed in one call.
+const MAX_PROBED_VARS: usize = 100;
+/// Cuts violated by less than this are not reported.
+const MIN_VIOLATION: f64 = 1e-6;
+
+/// A constraint `min <= coeffs * x <= max` (bounds can be infinite).
+pub(crate) struct Row {
+    coeffs: Vec<(usize, f64)>,
+    min: f64,
+    max: f64,
+}
+
+impl Row {
+    pub(crate) fn new(coeffs: Vec<(usize, f64)>, min: f64, max: f64) -> Row {
+        Row { coeffs, min, max }
+    }
+}
+
+pub(crate) fn collect_rows(solution: &Solution) -> Vec<Row> {
+    (0..solution.solver.num_constraints())
+        .map(|constr| {
+            let (coeffs, min, max) = solution.solver.constraint_row(constr);
+            Row { coeffs, min, max }
+        })
+        .collect()
+}
+
+/// Min or max activity of the row: the finite part and the number of infinite terms.
+fn activity(row: &Row, bounds: &[(f64, f64)], is_max: bool) -> (f64, usize, Vec<f64>) {
+    let mut sum = 0.0;
+    let mut num_inf = 0;
+    let mut terms = vec![];
+    for &(v, coeff) in &row.coeffs {
+        let (min, max) = bounds[v];
+        let bound = if (coeff > 0.0) == is_max { max } else { min };
+        let term = coeff * bound;
+        if term.is_infinite() {
+            num_inf += 1;
+        } else {
+            sum += term;
+        }
+        terms.push(term);
+    }
+    (sum, num_inf, terms)
+}
+
+/// Tighten the var bounds using the rows (activity-based bound propagation).
+/// Returns false if the bounds are found to be infeasible.
+pub(crate) fn propagate(rows: &[Row], is_integer: &[bool], bounds: &mut [(f64, f64)]) -> bool {
+    for _ in 0..MAX_PROPAGATION_PASSES {
+        let mut changed = false;
+        for row in rows {
+            // Implied bounds of a var are the row bounds minus the activity of the other vars.
+            for &(is_max_row_bound, row_bound) in &[(true, row.max), (false, row.min)] {
+                if row_bound.is_infinite() {
+                    continue;
+                }
+                let (act, num_inf, terms) = activity(row, bounds, !is_max_row_bound);
+                if num_inf > 1 {
+                    continue;
+                }
+
+                for (&(v, coeff), &term) in row.coeffs.iter().zip(&terms) {
+                    let residual = if term.is_infinite() {
+                        act
+                    } else if num_inf == 0 {
+                        act - term
+                    } else {
+                        continue;
+                    };
+                    let implied = (row_bound - residual) / coeff;
+                    // coeff * x <= max - residual gives an upper bound if coeff > 0.
+                    let is_upper = (coeff > 0.0) == is_max_row_bound;
+                    let (min, max) = &mut bounds[v];
+                    if is_upper {
+                        let implied = if is_integer[v] {
+                            (implied + FEASIBILITY_TOL).floor()
+                        } else {
+                            implied
+                        };
+                        if implied < *max - BOUND_TOL {
+                            *max = implied;
+                            changed = true;
+                        }
+                    } else {
+                        let implied = if is_integer[v] {
+                            (implied - FEASIBILITY_TOL).ceil()
+                        } else {
+                            implied
+                        };
+                        if implied > *min + BOUND_TOL {
+                            *min = implied;
+                            changed = true;
+                        }
+                    }
+                    if *min > *max + FEASIBILITY_TOL {
+                        return false;
+                    }
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    true
+}
+
+pub(crate) fn implied_bound_cuts(
+    solution: &Solution,
+    integer_vars: &[Variable],
+    max_cuts: usize,
+) -> Vec<Cut> {
+    let num_vars = solution.num_vars;
+    let mut is_integer = vec![false; num_vars];
+    for v in integer_vars {
+        is_integer[v.0] = true;
+    }
+
+    let rows = collect_rows(solution);
+    let mut global_bounds: Vec<_> = (0..num_vars)
+        .map(|v| solution.solver.var_bounds(v))
+        .collect();
+    if !propagate(&rows, &is_integer, &mut global_bounds) {
+        return vec![];
+    }
+
+    // Only fractional binaries are probed, because the implied bounds hold for the LP
+    // solutions with integral values of the probed var.
+    let mut probed: Vec<usize> = integer_vars
+        .iter()
+        .map(|v| v.0)
+        .filter(|&v| global_bounds[v] == (0.0, 1.0))
+        .filter(|&v| {
+            let val = solution[solution.var_ref(v)];
+            val > MIN_VIOLATION && val < 1.0 - MIN_VIOLATION
+        })
+        .collect();
+    let fractionality = |v: usize| {
+        let val = solution[solution.var_ref(v)];
+        f64::min(val, 1.0 - val)
+    };
+    probed.sort_by(|&a, &b| fractionality(b).partial_cmp(&fractionality(a)).unwrap());
+    probed.truncate(MAX_PROBED_VARS);
+
+    let mut pool = CutPool::new();
+    for x in probed {
+        let probe = |val: f64| {
+            let mut bounds = global_bounds.clone();
+            bounds[x] = (val, val);
+            if propagate(&rows, &is_integer, &mut bounds) {
+                Some(bounds)
+            } else {
+                None
+            }
+        };
+        let (bounds0, bounds1) = match (probe(0.0), probe(1.0)) {
+            (Some(b0), Some(b1)) => (b0, b1),
+            (None, None) => return vec![],
+            (None, Some(_)) => {
+                // x == 0 is infeasible, thus x >= 1.
+                pool.add(Cut::new(
+                    [(solution.var_ref(x), 1.0)],
+                    ComparisonOp::Ge,
+                    1.0,
+                ));
+                continue;
+            }
+            (Some(_), None) => {
+                pool.add(Cut::new(
+                    [(solution.var_ref(x), 1.0)],
+                    ComparisonOp::Le,
+                    0.0,
+                ));
+                continue;
+            }
+        };
+
+        let x_val = solution[solution.var_ref(x)];
+        for y in 0..num_vars {
+            if y == x {
+                continue;
+            }
+            let y_val = solution[solution.var_ref(y)];
+            let (min0, max0) = bounds0[y];
+            let (min1, max1) = bounds1[y];
+            // As x is binary, y <= max0 + (max1 - max0) * x.
+            if max0.is_finite() && max1.is_finite() {
+                let violation = y_val - (max0 + (max1 - max0) * x_val);
+                if violation > MIN_VIOLATION {
+                    let expr = [
+                        (solution.var_ref(y), 1.0),
+                        (solution.var_ref(x), max0 - max1),
+                    ];
+                    pool.add(Cut::new(expr, ComparisonOp::Le, max0));
+                }
+            }
+            // y >= min0 + (min1 - min0) * x.
+            if min0.is_finite() && min1.is_finite() {
+                let violation = (min0 + (min1 - min0) * x_val) - y_val;
+                if violation > MIN_VIOLATION {
+                    let expr = [
+                        (solution.var_ref(y), 1.0),
+                        (solution.var_ref(x), min0 - min1),
+                    ];
+                    pool.add(Cut::new(expr, ComparisonOp::Ge, min0));
+                }
+            }
+        }
+    }
+    pool.separate(solution, max_cuts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn propagation() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(0.0, (0.0, 10.0));
+        let y = problem.add_var(0.0, (0.0, f64::INFINITY));
+        let z = problem.add_var(0.0, (0.0, 3.0));
+        problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Le, 7.0);
+        problem.add_constraint([(x, -1.0), (z, 1.0)], ComparisonOp::Ge, 0.5);
+        let sol = problem.solve().unwrap();
+
+        let rows = collect_rows(&sol);
+        let mut bounds = vec![(0.0, 10.0), (0.0, f64::INFINITY), (0.0, 3.0)];
+        assert!(propagate(&rows, &[false; 3], &mut bounds));
+        assert_eq!(bounds, vec![(0.0, 2.5), (0.0, 7.0), (0.5, 3.0)]);
+
+        let mut bounds = vec![(0.0, 10.0), (0.0, f64::INFINITY), (0.0, 3.0)];
+        assert!(propagate(&rows, &[true; 3], &mut bounds));
+        assert_eq!(bounds, vec![(0.0, 2.0), (0.0, 7.0), (1.0, 3.0)]);
+
+        let mut bounds = vec![(3.0, 10.0), (0.0, f64::INFINITY), (0.0, 3.0)];
+        assert!(!propagate(&rows, &[false; 3], &mut bounds));
+    }
+
+    #[test]
+    fn implied_bounds() {
+        // y and z are limited by 10 * x through an aggregated constraint.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(-1.0, (0.0, 1.0));
+        let y = problem.add_var(1.0, (0.0, 8.0));
+        let z = problem.add_var(0.0, (0.0, 2.0));
+        problem.add_constraint([(y, 1.0), (z, 1.0), (x, -10.0)], ComparisonOp::Le, 0.0);
+
+        let sol = problem.solve().unwrap();
+        assert!(f64::abs(sol[x] - 0.8) < 1e-8);
+        assert_eq!(sol[y], 8.0);
+
+        // Probing x = 0 gives y <= 0, thus y <= 8 * x. z <= 2 * x is not violated.
+        let cuts = sol.implied_bound_cuts(&[x], 10);
+        assert_eq!(cuts.len(), 1);
+        assert_eq!(cuts[0].rhs(), 0.0);
+        let sol = sol
+            .add_constraint(cuts[0].lhs(), ComparisonOp::Le, cuts[0].rhs())
+            .unwrap();
+        assert_eq!(sol[x], 1.0);
+        assert!(sol.implied_bound_cuts(&[x], 10).is_empty());
+
+        // x = 0 is infeasible because then both y and z are 0.
+        problem.add_constraint([(z, 1.0), (x, -10.0)], ComparisonOp::Le, 0.0);
+        problem.add_constraint([(y, 1.0), (z, 1.0)], ComparisonOp::Ge, 1.0);
+        let sol = problem.solve().unwrap();
+        let cuts = sol.implied_bound_cuts(&[x], 10);
+        assert_eq!(cuts.len(), 1);
+        assert_eq!(cuts[0].rhs(), -1.0);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::Phase;
+use std::fmt;
+use std::sync::Arc;
+
+/// Progress of a solve reported to a [`ProgressCallback`](struct.ProgressCallback.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    /// The simplex phase that is currently running.
+    pub phase: Phase,
+    /// Number of simplex iterations performed so far.
+    pub iterations: usize,
+    /// Sum of primal infeasibilities while searching for a feasible solution and sum of
+    /// dual infeasibilities (non-optimal reduced costs) while optimizing it.
+    pub infeasibility: f64,
+    /// Estimate of the completed fraction of the work, from 0 to 1. It never decreases
+    /// during a solve, so it is suitable for progress bars.
+    ///
+    /// The first half of the range corresponds to the search for a feasible solution, the
+    /// second half to the optimization. In each half the estimate is based on the decay of
+    /// the infeasibility (on a logarithmic scale) relative to its value when the half
+    /// started. It is only a heuristic: the simplex method doesn't reduce infeasibilities
+    /// uniformly, so don't expect the estimate to be proportional to the elapsed time.
+    pub estimate: f64,
+}
+
+/// A function called periodically during a solve to report its [`Progress`].
+///
+/// Set it in [`SolverOptions::progress`]. The callback is called every hundred iterations
+/// of the initial solve and once more when the solve is finished (with the estimate equal
+/// to 1). If the dual problem is solved instead of the original one, only the final run of
+/// the primal simplex is reported. Operations on the [`Solution`] don't report progress.
+///
+/// [`Progress`]: struct.Progress.html
+/// [`SolverOptions::progress`]: struct.SolverOptions.html#structfield.progress
+/// [`Solution`]: struct.Solution.html
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// use std::sync::{Arc, Mutex};
+///
+/// let mut problem = Problem::new(OptimizationDirection::Maximize);
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(2.0, (0.0, 3.0));
+/// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+/// problem.add_constraint(&[(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+///
+/// let estimates = Arc::new(Mutex::new(vec![]));
+/// let options = SolverOptions {
+///     progress: Some(ProgressCallback::new({
+///         let estimates = estimates.clone();
+///         move |progress: &Progress| estimates.lock().unwrap().push(progress.estimate)
+///     })),
+///     ..Default::default()
+/// };
+/// problem.solve_with(&options).unwrap();
+/// assert_eq!(estimates.lock().unwrap().last(), Some(&1.0));
+/// ```
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Wrap a function into a callback.
+    pub fn new(f: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        ProgressCallback(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Keeps track of the progress estimate during a solve.
+#[derive(Clone, Debug)]
+pub(crate) struct ProgressTracker {
+    callback: ProgressCallback,
+    /// Index of the current half of the progress range and the infeasibility at its start.
+    stage: Option<(usize, f64)>,
+    estimate: f64,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(callback: ProgressCallback) -> Self {
+        ProgressTracker {
+            callback,
+            stage: None,
+            estimate: 0.0,
+        }
+    }
+
+    /// Report the `infeasibility` in the `stage` (0 while searching for a feasible solution,
+    /// 1 while optimizing).
+    pub(crate) fn report(
+        &mut self,
+        phase: Phase,
+        stage: usize,
+        iterations: usize,
+        infeasibility: f64,
+    ) {
+        let initial = match self.stage {
+            Some((cur_stage, initial)) if cur_stage == stage => initial,
+            _ => {
+                self.stage = Some((stage, infeasibility));
+                infeasibility
+            }
+        };
+        let decay = if initial > 0.0 {
+            1.0 - f64::ln_1p(infeasibility) / f64::ln_1p(initial)
+        } else {
+            1.0
+        };
+        let estimate = 0.5 * (stage as f64 + decay.clamp(0.0, 1.0));
+        self.estimate = f64::max(self.estimate, estimate);
+        self.call(phase, iterations, infeasibility);
+    }
+
+    pub(crate) fn finish(&mut self, phase: Phase, iterations: usize) {
+        self.estimate = 1.0;
+        self.call(phase, iterations, 0.0);
+    }
+
+    fn call(&self, phase: Phase, iterations: usize, infeasibility: f64) {
+        (self.callback.0)(&Progress {
+            phase,
+            iterations,
+            infeasibility,
+            estimate: self.estimate,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn estimates() {
+        let reported = Arc::new(Mutex::new(vec![]));
+        let callback = ProgressCallback::new({
+            let reported = reported.clone();
+            move |progress: &Progress| reported.lock().unwrap().push(progress.estimate)
+        });
+        let mut tracker = ProgressTracker::new(callback);
+        tracker.report(Phase::DualSimplex, 0, 0, 99.0);
+        tracker.report(Phase::DualSimplex, 0, 100, 9.0);
+        tracker.report(Phase::DualSimplex, 0, 200, 20.0);
+        tracker.report(Phase::PrimalSimplex, 1, 300, 5.0);
+        tracker.report(Phase::PrimalSimplex, 1, 400, 0.0);
+        tracker.finish(Phase::PrimalSimplex, 400);
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![0.0, 0.25, 0.25, 0.5, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn solve() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let vars: Vec<_> = (0..400)
+            .map(|i| problem.add_var(1.0 + (i % 7) as f64, (0.0, f64::INFINITY)))
+            .collect();
+        for (i, w) in vars.windows(2).enumerate() {
+            problem.add_constraint([(w[0], 1.0), (w[1], 1.0)], ComparisonOp::Le, (i % 3) as f64);
+        }
+
+        let reported = Arc::new(Mutex::new(vec![]));
+        let options = SolverOptions {
+            progress: Some(ProgressCallback::new({
+                let reported = reported.clone();
+                move |progress: &Progress| reported.lock().unwrap().push(progress.clone())
+            })),
+            ..Default::default()
+        };
+        let solution = problem.solve_with(&options).unwrap();
+        assert!(solution.stats().iterations > 100);
+
+        let reported = reported.lock().unwrap();
+        assert!(reported.len() > 2);
+        assert!(reported.windows(2).all(|w| w[0].estimate <= w[1].estimate));
+        let last = reported.last().unwrap();
+        assert_eq!(last.estimate, 1.0);
+        assert_eq!(last.iterations, solution.stats().iterations);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    solve_log::{json_object, LogValue},
+    ComparisonOp, Error, ErrorKind, Problem, Solution, SolverStats, SolverTimings,
+};
+use std::time::Duration;
+
+/// Values closer than this (relative to the bound magnitude) to a bound are considered
+/// to be at the bound when checking the signs of reduced costs.
+const AT_BOUND_TOL: f64 = 1e-9;
+
+/// Outcome of a solve, see [`SolveReport`](struct.SolveReport.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SolveStatus {
+    /// An optimal solution was found.
+    Optimal,
+    /// The solve was stopped early at a feasible solution within the
+    /// [relative gap](struct.SolverOptions.html#structfield.relative_gap) tolerance.
+    NearOptimal,
+    /// The problem is infeasible.
+    Infeasible,
+    /// The objective function is unbounded.
+    Unbounded,
+    /// The solve was aborted because the objective value can't be better than the
+    /// [cutoff](struct.SolverOptions.html#structfield.objective_cutoff).
+    Cutoff,
+}
+
+impl SolveStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            SolveStatus::Optimal => "optimal",
+            SolveStatus::NearOptimal => "near_optimal",
+            SolveStatus::Infeasible => "infeasible",
+            SolveStatus::Unbounded => "unbounded",
+            SolveStatus::Cutoff => "cutoff",
+        }
+    }
+}
+
+/// A summary of a solve: status, objective, statistics, quality of the solution and timings.
+///
+/// It is meant to be returned as is by services wrapping the solver. With the `serde`
+/// feature the report and its parts implement `serde::Serialize` (the status is serialized
+/// in snake case, e.g. `"near_optimal"`). Without it, [`to_json`] formats the report as
+/// a JSON object with the same field names (durations are in seconds, missing and
+/// non-finite values are `null`).
+///
+/// The quality measures are computed from the original problem data, so they reflect
+/// rounding errors and tolerances of the solver in the units of the problem.
+///
+/// [`to_json`]: #method.to_json
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Maximize);
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(2.0, (0.0, 3.0));
+/// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+///
+/// let report = SolveReport::new(&problem, &problem.solve());
+/// assert_eq!(report.status, SolveStatus::Optimal);
+/// assert_eq!(report.objective, Some(7.0));
+/// assert_eq!(report.max_primal_violation, Some(0.0));
+/// assert!(report.to_json().starts_with("{\"status\":\"optimal\",\"objective\":7.0,"));
+///
+/// problem.add_constraint(&[(x, 1.0)], ComparisonOp::Ge, 5.0);
+/// let report = SolveReport::new(&problem, &problem.solve());
+/// assert_eq!(report.status, SolveStatus::Infeasible);
+/// assert_eq!(report.objective, None);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SolveReport {
+    /// Outcome of the solve.
+    pub status: SolveStatus,
+    /// Optimal objective value (if optimal).
+    pub objective: Option<f64>,
+    /// Description of the error (if not optimal).
+    pub error: Option<String>,
+    /// Statistics of the simplex iterations (default if not optimal).
+    pub stats: SolverStats,
+    /// The biggest violation of a constraint or a variable bound by the solution
+    /// (if optimal).
+    pub max_primal_violation: Option<f64>,
+    /// The biggest violation of the optimality conditions by the dual values: reduced costs
+    /// with the wrong sign for the bound the variable is at and dual values with the wrong
+    /// sign for the constraint type (if optimal).
+    pub max_dual_violation: Option<f64>,
+    /// Time spent in the phases of the solution process (default if not optimal).
+    pub timings: SolverTimings,
+}
+
+impl SolveReport {
+    /// Create the report for the `result` of solving the `problem`.
+    pub fn new(problem: &Problem, result: &Result<Solution, Error>) -> Self {
+        match result {
+            Ok(solution) => SolveReport {
+                status: if solution.is_optimal() {
+                    SolveStatus::Optimal
+                } else {
+                    SolveStatus::NearOptimal
+                },
+                objective: Some(solution.objective()),
+                error: None,
+                stats: solution.stats().clone(),
+                max_primal_violation: Some(max_primal_violation(problem, solution)),
+                max_dual_violation: Some(max_dual_violation(problem, solution)),
+                timings: solution.timings(),
+            },
+            Err(err) => SolveReport {
+                status: match err.kind() {
+                    ErrorKind::Infeasible => SolveStatus::Infeasible,
+                    ErrorKind::Unbounded => SolveStatus::Unbounded,
+                    ErrorKind::Cutoff => SolveStatus::Cutoff,
+                },
+                objective: None,
+                error: Some(err.to_string()),
+                stats: SolverStats::default(),
+                max_primal_violation: None,
+                max_dual_violation: None,
+                timings: SolverTimings::default(),
+            },
+        }
+    }
+
+    /// Format the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let stats = &self.stats;
+        let stats = json_object(&[
+            ("iterations", LogValue::Int(stats.iterations)),
+            ("primal_iterations", LogValue::Int(stats.primal_iterations)),
+            ("dual_iterations", LogValue::Int(stats.dual_iterations)),
+            ("bound_flips", LogValue::Int(stats.bound_flips)),
+            (
+                "degenerate_iterations",
+                LogValue::Int(stats.degenerate_iterations),
+            ),
+            (
+                "max_degenerate_streak",
+                LogValue::Int(stats.max_degenerate_streak),
+            ),
+            ("stalls", LogValue::Int(stats.stalls)),
+            (
+                "update_refactorizations",
+                LogValue::Int(stats.update_refactorizations),
+            ),
+            (
+                "recalc_refactorizations",
+                LogValue::Int(stats.recalc_refactorizations),
+            ),
+            (
+                "constraint_refactorizations",
+                LogValue::Int(stats.constraint_refactorizations),
+            ),
+            ("max_pivot_growth", LogValue::Float(stats.max_pivot_growth)),
+            (
+                "min_pivot",
+                LogValue::Float(stats.min_pivot.unwrap_or(f64::NAN)),
+            ),
+            ("rejected_pivots", LogValue::Int(stats.rejected_pivots)),
+        ]);
+
+        let secs = |duration: Duration| LogValue::Float(duration.as_secs_f64());
+        let timings = &self.timings;
+        let timings = json_object(&[
+            ("scaling", secs(timings.scaling)),
+            ("dualization", secs(timings.dualization)),
+            ("ordering", secs(timings.ordering)),
+            ("factorization", secs(timings.factorization)),
+            ("ftran", secs(timings.ftran)),
+            ("btran", secs(timings.btran)),
+            ("pricing", secs(timings.pricing)),
+            ("ratio_test", secs(timings.ratio_test)),
+        ]);
+
+        let optional = |val: Option<f64>| LogValue::Float(val.unwrap_or(f64::NAN));
+        json_object(&[
+            ("status", LogValue::Str(self.status.as_str())),
+            ("objective", optional(self.objective)),
+            (
+                "error",
+                match &self.error {
+                    Some(error) => LogValue::Str(error),
+                    None => LogValue::Json("null"),
+                },
+            ),
+            ("stats", LogValue::Json(&stats)),
+            ("max_primal_violation", optional(self.max_primal_violation)),
+            ("max_dual_violation", optional(self.max_dual_violation)),
+            ("timings", LogValue::Json(&timings)),
+        ])
+    }
+}
+
+fn max_primal_violation(problem: &Problem, solution: &Solution) -> f64 {
+    let mut max_violation: f64 = 0.0;
+    for (v, (&min, &max)) in problem.var_mins.iter().zip(&problem.var_maxs).enumerate() {
+        let val = solution[solution.var_ref(v)];
+        max_violation = max_violation.max(min - val).max(val - max);
+    }
+    for (coeffs, cmp_op, rhs) in &problem.constraints {
+        let lhs: f64 = coeffs
+            .iter()
+            .map(|(v, &coeff)| coeff * solution[solution.var_ref(v)])
+            .sum();
+        let violation = match cmp_op {
+            ComparisonOp::Le => lhs - rhs,
+            ComparisonOp::Ge => rhs - lhs,
+            ComparisonOp::Eq => f64::abs(lhs - rhs),
+        };
+        max_violation = max_violation.max(violation);
+    }
+    max_violation
+}
+
+fn max_dual_violation(problem: &Problem, solution: &Solution) -> f64 {
+    // Everything is in the minimization form, in which the problem is stored.
+    let mut max_violation: f64 = 0.0;
+    let mut reduced_costs = problem.obj_coeffs.clone();
+    for (constr, (coeffs, cmp_op, _)) in problem.constraints.iter().enumerate() {
+        let dual = solution.solver.dual_value(constr);
+        let violation = match cmp_op {
+            ComparisonOp::Le => dual,
+            ComparisonOp::Ge => -dual,
+            ComparisonOp::Eq => 0.0,
+        };
+        max_violation = max_violation.max(violation);
+        for (v, &coeff) in coeffs.iter() {
+            reduced_costs[v] -= dual * coeff;
+        }
+    }
+
+    for (v, &cost) in reduced_costs.iter().enumerate() {
+        let (min, max) = (problem.var_mins[v], problem.var_maxs[v]);
+        let val = solution[solution.var_ref(v)];
+        let is_at = |bound: f64| f64::abs(val - bound) <= AT_BOUND_TOL * f64::max(1.0, bound.abs());
+        let violation = match (is_at(min), is_at(max)) {
+            (true, true) => 0.0,
+            (true, false) => -cost,
+            (false, true) => cost,
+            (false, false) => cost.abs(),
+        };
+        max_violation = max_violation.max(violation);
+    }
+    max_violation
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn quality() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(-1.0, (f64::NEG_INFINITY, 2.0));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 1.0);
+        problem.add_constraint([(x, 1.0), (y, -1.0)], ComparisonOp::Le, 3.0);
+        problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Eq, 3.0);
+
+        let report = SolveReport::new(&problem, &problem.solve());
+        assert_eq!(report.status, SolveStatus::Optimal);
+        assert!(report.max_primal_violation.unwrap() < 1e-9);
+        assert!(report.max_dual_violation.unwrap() < 1e-9);
+        assert!(report.stats.iterations > 0);
+
+        let json = report.to_json();
+        assert!(json.contains("\"error\":null,\"stats\":{\"iterations\":"));
+        assert!(json.contains("\"timings\":{\"scaling\":"));
+        assert!(json.contains("\"max_pivot_growth\":"));
+
+        // Unbounded.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0)], ComparisonOp::Ge, 1.0);
+        let report = SolveReport::new(&problem, &problem.solve());
+        assert_eq!(report.status, SolveStatus::Unbounded);
+        assert!(report.error.as_ref().unwrap().contains("unbounded"));
+        let json = report.to_json();
+        assert!(json.starts_with("{\"status\":\"unbounded\",\"objective\":null,\"error\":\""));
+        assert!(json.contains("\"max_primal_violation\":null"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializable() {
+        fn is_serializable<T: serde::Serialize>() {}
+        is_serializable::<SolveReport>();
+    }
+}
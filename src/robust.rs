@@ -0,0 +1,169 @@
+use crate::{ComparisonOp, ConstraintRef, LinearExpr, Problem, Variable};
+
+/// A set of possible deviations of constraint coefficients from their nominal values.
+///
+/// Used with [`Problem::add_robust_constraint`] to add a constraint that must hold for every
+/// realization of the coefficients from the set.
+///
+/// [`Problem::add_robust_constraint`]: struct.Problem.html#method.add_robust_constraint
+#[derive(Clone, Debug)]
+pub struct UncertaintySet {
+    deviations: LinearExpr,
+    budget: Option<f64>,
+}
+
+impl UncertaintySet {
+    /// Box uncertainty: each coefficient can simultaneously deviate from its nominal value
+    /// by at most the specified (nonnegative) amount.
+    ///
+    /// This is the most conservative choice, the counterpart protects against the worst case
+    /// in which all coefficients take their extreme values.
+    pub fn boxed(deviations: impl Into<LinearExpr>) -> Self {
+        UncertaintySet {
+            deviations: deviations.into(),
+            budget: None,
+        }
+    }
+
+    /// Budgeted uncertainty of Bertsimas and Sim: each coefficient can deviate by at most
+    /// the specified (nonnegative) amount, but the total scaled deviation is limited by
+    /// `budget` (roughly, at most `budget` coefficients deviate at the same time).
+    ///
+    /// See Bertsimas, D., & Sim, M. (2004). The price of robustness.
+    /// Operations research, 52(1), 35-53.
+    pub fn budget(deviations: impl Into<LinearExpr>, budget: f64) -> Self {
+        assert!(budget >= 0.0, "uncertainty budget must be nonnegative");
+        UncertaintySet {
+            deviations: deviations.into(),
+            budget: Some(budget),
+        }
+    }
+}
+
+pub(crate) fn add_robust_constraint(
+    problem: &mut Problem,
+    expr: LinearExpr,
+    cmp_op: ComparisonOp,
+    rhs: f64,
+    uncertainty: &UncertaintySet,
+) -> ConstraintRef {
+    // The worst case of sum(a_j * x_j) over the uncertainty set is the nominal value plus
+    // the max over deviations of sum(d_j * |x_j| * t_j) where 0 <= t_j <= 1 and
+    // sum(t_j) <= budget. By LP duality this max equals the min of budget * z + sum(p_j)
+    // subject to z + p_j >= d_j * |x_j|, z >= 0, p_j >= 0, so it can be expressed with
+    // additional variables and constraints. For the box set the max is simply
+    // sum(d_j * |x_j|). For the >= constraint we protect against the deviation downwards.
+    let sign = match cmp_op {
+        ComparisonOp::Le => 1.0,
+        ComparisonOp::Ge => -1.0,
+        ComparisonOp::Eq => panic!("robust counterpart of an equality constraint is not supported"),
+    };
+
+    // Nonnegative expressions equal to d_j * |x_j|
+    let mut abs_deviations = vec![];
+    let generation = uncertainty.deviations.generation;
+    for (&var, &deviation) in uncertainty
+        .deviations
+        .vars
+        .iter()
+        .zip(&uncertainty.deviations.coeffs)
+    {
+        assert!(
+            deviation >= 0.0,
+            "coefficient deviations must be nonnegative"
+        );
+        if deviation == 0.0 {
+            continue;
+        }
+
+        let var = Variable(var, generation.unwrap_or(problem.generation));
+        let v = problem.var_idx(var);
+        let abs_expr = if problem.var_mins[v] >= 0.0 {
+            LinearExpr::from(&[(var, deviation)])
+        } else if problem.var_maxs[v] <= 0.0 {
+            LinearExpr::from(&[(var, -deviation)])
+        } else {
+            // u >= x and u >= -x, thus u >= |x|.
+            let abs_var = problem.add_var(0.0, (0.0, f64::INFINITY));
+            problem.add_constraint([(abs_var, 1.0), (var, -1.0)], ComparisonOp::Ge, 0.0);
+            problem.add_constraint([(abs_var, 1.0), (var, 1.0)], ComparisonOp::Ge, 0.0);
+            LinearExpr::from(&[(abs_var, deviation)])
+        };
+        abs_deviations.push(abs_expr);
+    }
+
+    // Deviation terms can refer to the variables already present in the nominal expression,
+    // so the terms are merged before adding the constraint (as the problem can be strict).
+    let mut lhs = expr;
+    match uncertainty.budget {
+        None => {
+            for abs_expr in abs_deviations {
+                for (&var, &coeff) in abs_expr.vars.iter().zip(&abs_expr.coeffs) {
+                    lhs.add(problem.var_ref(var), sign * coeff);
+                }
+            }
+        }
+
+        Some(budget) => {
+            let budget_var = problem.add_var(0.0, (0.0, f64::INFINITY));
+            lhs.add(budget_var, sign * budget);
+            for mut abs_expr in abs_deviations {
+                let excess_var = problem.add_var(0.0, (0.0, f64::INFINITY));
+                lhs.add(excess_var, sign);
+
+                // z + p_j - d_j * |x_j| >= 0
+                for coeff in &mut abs_expr.coeffs {
+                    *coeff = -*coeff;
+                }
+                abs_expr.add(budget_var, 1.0);
+                abs_expr.add(excess_var, 1.0);
+                problem.add_constraint(abs_expr, ComparisonOp::Ge, 0.0);
+            }
+        }
+    }
+
+    lhs.merge_duplicates();
+    problem.add_constraint(lhs, cmp_op, rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn box_uncertainty() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(0.0, (-1.0, f64::INFINITY));
+        problem.add_robust_constraint(
+            [(x, 1.0), (y, 1.0)],
+            ComparisonOp::Le,
+            4.0,
+            &UncertaintySet::boxed([(x, 1.0), (y, 2.0)]),
+        );
+
+        // Worst case: 2 * x + y + 2 * |y| <= 4, optimum at y = 0.
+        let sol = problem.solve().unwrap();
+        assert!(f64::abs(sol[x] - 2.0) < 1e-8);
+        assert!(f64::abs(sol[y]) < 1e-8);
+        assert!(f64::abs(sol.objective() - 2.0) < 1e-8);
+    }
+
+    #[test]
+    fn budget_uncertainty() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(-1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(-1.0, (0.0, f64::INFINITY));
+        problem.add_robust_constraint(
+            [(x, -1.0), (y, -1.0)],
+            ComparisonOp::Ge,
+            -4.0,
+            &UncertaintySet::budget([(x, 1.0), (y, 1.0)], 1.0),
+        );
+
+        // Only one of the coefficients can deviate: x + y + max(x, y) <= 4.
+        let sol = problem.solve().unwrap();
+        assert!(f64::abs(sol[x] + sol[y] - 8.0 / 3.0) < 1e-8);
+        assert!(f64::abs(sol.objective() + 8.0 / 3.0) < 1e-8);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{parallel, validate::scaling_warnings, ComparisonOp, CsVec, ScalingMode};
+
+/// Max number of geometric mean scaling passes.
+const GEOMETRIC_MEAN_MAX_PASSES: usize = 10;
+/// Geometric mean scaling stops when a pass doesn't decrease the spread of
+/// coefficient magnitudes at least by this factor.
+const GEOMETRIC_MEAN_MIN_IMPROVEMENT: f64 = 0.9;
+
+/// Row and column scale factors. The scaled problem has the constraint matrix R * A * C,
+/// where R and C are the diagonal matrices of row and column scales. Thus the scaled
+/// variables are x' = x / C and the scaled constraint rhs are b' = R * b.
+#[derive(Clone, Debug)]
+pub(crate) struct Scaling {
+    pub(crate) row_scales: Vec<f64>,
+    pub(crate) col_scales: Vec<f64>,
+}
+
+/// Min and max abs. values of the scaled nonzero coeffs in each row and column.
+struct Extremes {
+    row_min: Vec<f64>,
+    row_max: Vec<f64>,
+    col_min: Vec<f64>,
+    col_max: Vec<f64>,
+}
+
+impl Extremes {
+    /// Ratio of the biggest to the smallest abs. value of the scaled coeffs.
+    fn max_ratio(&self) -> f64 {
+        let min = self.row_min.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self.row_max.iter().copied().fold(0.0, f64::max);
+        if max == 0.0 {
+            1.0
+        } else {
+            max / min
+        }
+    }
+}
+
+impl Scaling {
+    /// Compute the scale factors. Returns None if the problem doesn't need scaling.
+    /// Passes over the constraint matrix are split between `threads` threads.
+    pub(crate) fn compute(
+        mode: ScalingMode,
+        num_vars: usize,
+        constraints: &[(CsVec, ComparisonOp, f64)],
+        threads: usize,
+    ) -> Option<Scaling> {
+        let mut scaling = Scaling::trivial(num_vars, constraints.len());
+
+        match mode {
+            ScalingMode::None => return None,
+            ScalingMode::Equilibration => scaling.equilibrate(constraints, threads),
+            ScalingMode::GeometricMean => scaling.geometric_mean(constraints, threads),
+            ScalingMode::GeometricMeanEquilibration => {
+                scaling.geometric_mean(constraints, threads);
+                scaling.equilibrate(constraints, threads);
+            }
+            ScalingMode::Auto => {
+                if scaling_warnings(num_vars, constraints, 0).is_empty() {
+                    return None;
+                }
+                scaling.geometric_mean(constraints, threads);
+                scaling.equilibrate(constraints, threads);
+            }
+        }
+
+        // Scale factors are rounded to powers of 2 so that scaling doesn't introduce
+        // rounding errors.
+        for scale in scaling.row_scales.iter_mut().chain(&mut scaling.col_scales) {
+            *scale = round_to_power_of_2(*scale);
+        }
+
+        let is_trivial = |scales: &[f64]| scales.iter().all(|&s| s == 1.0);
+        if is_trivial(&scaling.row_scales) && is_trivial(&scaling.col_scales) {
+            return None;
+        }
+
+        debug!(
+            "scaled the problem, max abs. coeff. ratio: {} -> {}",
+            Scaling::trivial(num_vars, constraints.len()).max_ratio(constraints),
+            scaling.max_ratio(constraints),
+        );
+        Some(scaling)
+    }
+
+    fn trivial(num_vars: usize, num_constraints: usize) -> Scaling {
+        Scaling {
+            row_scales: vec![1.0; num_constraints],
+            col_scales: vec![1.0; num_vars],
+        }
+    }
+
+    /// Compute the extremes of the scaled coeffs. Rows are split into chunks processed
+    /// in parallel, column extremes of the chunks are then merged (min and max don't depend
+    /// on the order of the operations, so the result is the same for any number of threads).
+    fn extremes(&self, constraints: &[(CsVec, ComparisonOp, f64)], threads: usize) -> Extremes {
+        let num_cols = self.col_scales.len();
+        let chunks = parallel::map_chunks(threads, constraints.len(), |rows| {
+            let mut chunk = Extremes {
+                row_min: vec![f64::INFINITY; rows.len()],
+                row_max: vec![0.0; rows.len()],
+                col_min: vec![f64::INFINITY; num_cols],
+                col_max: vec![0.0; num_cols],
+            };
+            for (i, r) in rows.enumerate() {
+                for (c, &coeff) in constraints[r].0.iter() {
+                    if coeff == 0.0 {
+                        continue;
+                    }
+                    let val = (coeff * self.row_scales[r] * self.col_scales[c]).abs();
+                    chunk.row_min[i] = f64::min(chunk.row_min[i], val);
+                    chunk.row_max[i] = f64::max(chunk.row_max[i], val);
+                    chunk.col_min[c] = f64::min(chunk.col_min[c], val);
+                    chunk.col_max[c] = f64::max(chunk.col_max[c], val);
+                }
+            }
+            chunk
+        });
+
+        let mut chunks = chunks.into_iter();
+        let mut res = chunks.next().unwrap();
+        for chunk in chunks {
+            res.row_min.extend(chunk.row_min);
+            res.row_max.extend(chunk.row_max);
+            for c in 0..num_cols {
+                res.col_min[c] = f64::min(res.col_min[c], chunk.col_min[c]);
+                res.col_max[c] = f64::max(res.col_max[c], chunk.col_max[c]);
+            }
+        }
+        res
+    }
+
+    /// Ratio of the biggest to the smallest abs. value of the scaled coeffs.
+    pub(crate) fn max_ratio(&self, constraints: &[(CsVec, ComparisonOp, f64)]) -> f64 {
+        self.extremes(constraints, 1).max_ratio()
+    }
+
+    /// Alternately scale rows and columns so that the geometric mean of the min and max
+    /// abs. values of the coeffs in each row and column is 1.
+    fn geometric_mean(&mut self, constraints: &[(CsVec, ComparisonOp, f64)], threads: usize) {
+        let mut ratio = self.extremes(constraints, threads).max_ratio();
+        for pass in 0..GEOMETRIC_MEAN_MAX_PASSES {
+            let prev = (self.row_scales.clone(), self.col_scales.clone());
+
+            let extremes = self.extremes(constraints, threads);
+            for (r, scale) in self.row_scales.iter_mut().enumerate() {
+                if extremes.row_max[r] > 0.0 {
+                    *scale /= f64::sqrt(extremes.row_min[r] * extremes.row_max[r]);
+                }
+            }
+
+            let extremes = self.extremes(constraints, threads);
+            for (c, scale) in self.col_scales.iter_mut().enumerate() {
+                if extremes.col_max[c] > 0.0 {
+                    *scale /= f64::sqrt(extremes.col_min[c] * extremes.col_max[c]);
+                }
+            }
+
+            let new_ratio = self.extremes(constraints, threads).max_ratio();
+            trace!(
+                "geometric mean scaling pass {}: max ratio {} -> {}",
+                pass,
+                ratio,
+                new_ratio
+            );
+            if new_ratio > ratio {
+                // The pass made things worse, revert it.
+                self.row_scales = prev.0;
+                self.col_scales = prev.1;
+                break;
+            }
+            if new_ratio > GEOMETRIC_MEAN_MIN_IMPROVEMENT * ratio {
+                break;
+            }
+            ratio = new_ratio;
+        }
+    }
+
+    /// Scale rows and then columns so that the max abs. value in each of them is 1.
+    fn equilibrate(&mut self, constraints: &[(CsVec, ComparisonOp, f64)], threads: usize) {
+        let extremes = self.extremes(constraints, threads);
+        for (scale, &max) in self.row_scales.iter_mut().zip(&extremes.row_max) {
+            if max > 0.0 {
+                *scale /= max;
+            }
+        }
+
+        let extremes = self.extremes(constraints, threads);
+        for (scale, &max) in self.col_scales.iter_mut().zip(&extremes.col_max) {
+            if max > 0.0 {
+                *scale /= max;
+            }
+        }
+    }
+
+    /// Scale factor of the row (constraints added after scaling are not scaled).
+    pub(crate) fn row_scale(&self, constr: usize) -> f64 {
+        self.row_scales.get(constr).copied().unwrap_or(1.0)
+    }
+
+    /// Scale the constraint coeffs of the problem vars.
+    pub(crate) fn scale_coeffs(&self, coeffs: &CsVec, row_scale: f64) -> CsVec {
+        let data = coeffs
+            .iter()
+            .map(|(c, &coeff)| coeff * row_scale * self.col_scales[c])
+            .collect();
+        CsVec::new(coeffs.dim(), coeffs.indices().to_vec(), data)
+    }
+}
+
+/// Round a positive number to the nearest power of 2 in the log scale. Unlike
+/// `exp2(log2(x).round())` this doesn't depend on the libm implementation, so the
+/// results are the same on all platforms.
+fn round_to_power_of_2(x: f64) -> f64 {
+    if !x.is_normal() || x < 0.0 {
+        return x;
+    }
+    // Clearing the mantissa bits gives the biggest power of 2 not greater than x.
+    let floor = f64::from_bits(x.to_bits() & (0x7ff << 52));
+    if x / floor >= std::f64::consts::SQRT_2 {
+        2.0 * floor
+    } else {
+        floor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::to_sparse;
+    use rand::prelude::*;
+
+    #[test]
+    fn badly_scaled() {
+        let constraints = vec![
+            (to_sparse(&[1e4, 2e-2]), ComparisonOp::Le, 1.0),
+            (to_sparse(&[3e2, 1e-4]), ComparisonOp::Ge, 1.0),
+        ];
+        let orig_ratio = Scaling::trivial(2, 2).max_ratio(&constraints);
+        assert!(Scaling::compute(ScalingMode::None, 2, &constraints, 1).is_none());
+
+        for &mode in &[
+            ScalingMode::Equilibration,
+            ScalingMode::GeometricMean,
+            ScalingMode::GeometricMeanEquilibration,
+            ScalingMode::Auto,
+        ] {
+            let scaling = Scaling::compute(mode, 2, &constraints, 1).unwrap();
+            assert!(scaling.max_ratio(&constraints) < orig_ratio / 100.0);
+            for &scale in scaling.row_scales.iter().chain(&scaling.col_scales) {
+                assert_eq!(scale, f64::exp2(scale.log2().round()));
+            }
+        }
+
+        let well_scaled = vec![(to_sparse(&[1.0, 1.0]), ComparisonOp::Le, 1.0)];
+        assert!(Scaling::compute(ScalingMode::GeometricMean, 2, &well_scaled, 1).is_none());
+
+        // Moderately scaled problems are left alone in the auto mode.
+        let moderate = vec![(to_sparse(&[1e2, 1e-2]), ComparisonOp::Le, 1.0)];
+        assert!(Scaling::compute(ScalingMode::Equilibration, 2, &moderate, 1).is_some());
+        assert!(Scaling::compute(ScalingMode::Auto, 2, &moderate, 1).is_none());
+    }
+
+    #[test]
+    fn parallel_scaling() {
+        let mut rng = rand_pcg::Pcg64::seed_from_u64(5);
+        let num_vars = 50;
+        let constraints: Vec<_> = (0..5000)
+            .map(|r| {
+                let mut coeffs = vec![0.0; num_vars];
+                for _ in 0..3 {
+                    let exp: i32 = rng.gen_range(-6, 6);
+                    coeffs[rng.gen_range(0, num_vars)] = rng.gen_range(1.0, 9.0) * 10f64.powi(exp);
+                }
+                coeffs[r % num_vars] = 1.0;
+                (to_sparse(&coeffs), ComparisonOp::Le, 1.0)
+            })
+            .collect();
+
+        let single = Scaling::compute(ScalingMode::GeometricMean, num_vars, &constraints, 1);
+        let single = single.unwrap();
+        for &threads in &[2, 3, 8] {
+            let parallel =
+                Scaling::compute(ScalingMode::GeometricMean, num_vars, &constraints, threads);
+            let parallel = parallel.unwrap();
+            assert_eq!(parallel.row_scales, single.row_scales);
+            assert_eq!(parallel.col_scales, single.col_scales);
+        }
+    }
+
+    #[test]
+    fn power_of_2_rounding() {
+        assert_eq!(round_to_power_of_2(1.0), 1.0);
+        assert_eq!(round_to_power_of_2(1.4), 1.0);
+        assert_eq!(round_to_power_of_2(1.5), 2.0);
+        assert_eq!(round_to_power_of_2(0.7), 0.5);
+        assert_eq!(round_to_power_of_2(0.75), 1.0);
+        assert_eq!(round_to_power_of_2(3e10), f64::exp2(35.0));
+        for i in 0..1000 {
+            let x = 1.037f64.powi(i - 500);
+            assert_eq!(round_to_power_of_2(x), f64::exp2(x.log2().round()));
+        }
+    }
+}
@@ -0,0 +1,853 @@
+use crate::{
+    mps::{parse_core, parse_f64, Lines, MpsRows, Tokens},
+    ConstraintRef, LinearExpr, MpsFile, OptimizationDirection, Problem, Variable,
+};
+use std::{collections::HashMap, io};
+
+/// Probabilities of the realizations of a random element must sum to one up to this.
+const PROBABILITY_TOL: f64 = 1e-6;
+
+/// A two-stage stochastic linear program parsed from the SMPS core, time and stoch files.
+///
+/// The core file is an MPS file with the deterministic problem. The time file splits its
+/// variables and constraints into the first stage (decided before the uncertainty is
+/// revealed) and the second stage (decided for each scenario). The stoch file describes
+/// the scenarios as changes of the right-hand sides, the constraint coefficients and
+/// the objective coefficients of the second stage.
+///
+/// The problem can be solved by building its [deterministic
+/// equivalent](#method.deterministic_equivalent), or the scenario structure in
+/// [`scenarios`](#structfield.scenarios) can be used to set up a decomposition.
+#[derive(Clone)]
+pub struct SmpsFile {
+    /// Core problem with the variables and the constraints of both stages.
+    pub core: MpsFile,
+    /// Variables of the core problem that belong to the first stage.
+    pub first_stage_vars: Vec<Variable>,
+    /// Constraints of the core problem that belong to the first stage.
+    pub first_stage_constraints: Vec<ConstraintRef>,
+    /// Scenarios in the order they were declared (or enumerated, for independent
+    /// random elements).
+    pub scenarios: Vec<Scenario>,
+}
+
+/// A scenario of a two-stage stochastic program, see [`SmpsFile`].
+///
+/// [`SmpsFile`]: struct.SmpsFile.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    /// Name of the scenario.
+    pub name: String,
+    /// Probability of the scenario.
+    pub probability: f64,
+    /// Changes of the second stage of the core problem in this scenario.
+    pub changes: Vec<ScenarioChange>,
+}
+
+/// A change of the core problem in a [`Scenario`].
+///
+/// All references are to the core problem of the [`SmpsFile`].
+///
+/// [`Scenario`]: struct.Scenario.html
+/// [`SmpsFile`]: struct.SmpsFile.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScenarioChange {
+    /// The right-hand side of a constraint is replaced.
+    Rhs {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// The new right-hand side.
+        val: f64,
+    },
+    /// A coefficient of a constraint is replaced.
+    Coeff {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// The variable.
+        var: Variable,
+        /// The new coefficient.
+        val: f64,
+    },
+    /// An objective coefficient of a variable is replaced.
+    ObjCoeff {
+        /// The variable.
+        var: Variable,
+        /// The new objective coefficient.
+        val: f64,
+    },
+}
+
+/// The deterministic equivalent of a two-stage stochastic program, see
+/// [`SmpsFile::deterministic_equivalent`].
+///
+/// [`SmpsFile::deterministic_equivalent`]: struct.SmpsFile.html#method.deterministic_equivalent
+#[derive(Clone, Debug)]
+pub struct DeterministicEquivalent {
+    /// The problem with the first stage and a copy of the second stage for each scenario.
+    pub problem: Problem,
+    is_first_stage: Vec<bool>,
+    num_core_vars: usize,
+    /// Index of the copy of each second-stage core variable among the copies of a scenario.
+    second_stage_idx: Vec<usize>,
+    num_second_stage_vars: usize,
+}
+
+impl DeterministicEquivalent {
+    /// The variable of the deterministic equivalent that corresponds to the `var` of
+    /// the core problem in the scenario with index `scenario`. First-stage variables
+    /// are the same for all scenarios.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the scenario index is out of range or the variable is not from the core
+    /// problem.
+    pub fn scenario_var(&self, scenario: usize, var: Variable) -> Variable {
+        let v = var.idx();
+        assert!(v < self.num_core_vars, "unknown variable {}", v);
+        if self.is_first_stage[v] {
+            return self.problem.var_ref(v);
+        }
+        let num_first_stage = self.num_core_vars - self.num_second_stage_vars;
+        let idx =
+            num_first_stage + scenario * self.num_second_stage_vars + self.second_stage_idx[v];
+        assert!(
+            idx < self.problem.obj_coeffs.len(),
+            "scenario {} out of range",
+            scenario
+        );
+        self.problem.var_ref(idx)
+    }
+}
+
+impl std::fmt::Debug for SmpsFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmpsFile")
+            .field("core", &self.core)
+            .field("num_first_stage_vars", &self.first_stage_vars.len())
+            .field("num_scenarios", &self.scenarios.len())
+            .finish()
+    }
+}
+
+impl SmpsFile {
+    /// Parses a two-stage stochastic program from the SMPS `core`, `time` and `stoch` files.
+    ///
+    /// The core file is parsed like [`MpsFile::parse`] with the `direction`. The time file
+    /// must be in the implicit format with two periods: the first-stage variables and
+    /// constraints are those preceding the column and the row that start the second period
+    /// in the core file. The stoch file can have either an `INDEP DISCRETE` section
+    /// (independent random elements, the scenarios are all combinations of their
+    /// realizations) or a `SCENARIOS DISCRETE` section (scenarios branching from `ROOT`).
+    /// An element is referred to by the variable (or the name of the RHS vector) and the
+    /// row (or the objective).
+    ///
+    /// [`MpsFile::parse`]: struct.MpsFile.html#method.parse
+    ///
+    /// # Errors
+    ///
+    /// Apart from I/O errors coming from the inputs, this function will signal any syntax
+    /// error as [`std::io::Error`] with the kind set to
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData). Unsupported features, such as more
+    /// than two periods, `BLOCKS` sections or random data of ranged rows and of the first
+    /// stage, are reported similarly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use minilp::*;
+    /// let core = "\
+    /// NAME          FARM
+    /// ROWS
+    ///  N  COST
+    ///  G  DEMAND
+    /// COLUMNS
+    ///     BUILD     COST      1         DEMAND    1
+    ///     BUY       COST      3         DEMAND    1
+    /// RHS
+    ///     RHS       DEMAND    0
+    /// ENDATA
+    /// ";
+    /// let time = "\
+    /// TIME          FARM
+    /// PERIODS
+    ///     BUILD     DEMAND    STAGE1
+    ///     BUY       DEMAND    STAGE2
+    /// ENDATA
+    /// ";
+    /// let stoch = "\
+    /// STOCH         FARM
+    /// INDEP         DISCRETE
+    ///     RHS       DEMAND    2         STAGE2    0.5
+    ///     RHS       DEMAND    4         STAGE2    0.5
+    /// ENDATA
+    /// ";
+    /// let file = SmpsFile::parse(
+    ///     core.as_bytes(),
+    ///     time.as_bytes(),
+    ///     stoch.as_bytes(),
+    ///     OptimizationDirection::Minimize,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(file.scenarios.len(), 2);
+    ///
+    /// // Building for the high demand is cheaper than buying the shortfall half of the time.
+    /// let det = file.deterministic_equivalent();
+    /// let solution = det.problem.solve().unwrap();
+    /// assert_eq!(solution[file.core.variables["BUILD"]], 4.0);
+    /// assert_eq!(solution.objective(), 4.0);
+    /// ```
+    pub fn parse<C: io::BufRead, T: io::BufRead, S: io::BufRead>(
+        core: C,
+        time: T,
+        stoch: S,
+        direction: OptimizationDirection,
+    ) -> io::Result<Self> {
+        // Format description: https://www.mcs.anl.gov/~jtl/PAPERS/SMPS/smps2.pdf
+        let (core, rows) = parse_core(core, direction).map_err(|err| in_file("core", err))?;
+        let (first_stage_vars, first_stage_constraints, period_names) =
+            parse_time(time, &core, &rows).map_err(|err| in_file("time", err))?;
+
+        let mut file = Self {
+            core,
+            first_stage_vars,
+            first_stage_constraints,
+            scenarios: vec![],
+        };
+        file.scenarios =
+            parse_stoch(stoch, &file, &rows, &period_names).map_err(|err| in_file("stoch", err))?;
+        Ok(file)
+    }
+
+    /// Build the deterministic equivalent of the program: the first-stage variables and
+    /// constraints together with a copy of the second-stage variables and constraints for
+    /// each scenario. The objective coefficients of the copies are weighted by
+    /// the probabilities of the scenarios, so the optimal objective value is the optimal
+    /// expected value of the stochastic program.
+    ///
+    /// Copies of the named variables are named by appending `_` and the name of the scenario.
+    ///
+    /// The size of the deterministic equivalent is proportional to the number of scenarios,
+    /// which grows exponentially with the number of independent random elements.
+    pub fn deterministic_equivalent(&self) -> DeterministicEquivalent {
+        let core = &self.core.problem;
+        let num_core_vars = core.obj_coeffs.len();
+        let obj_sign = match core.direction {
+            OptimizationDirection::Minimize => 1.0,
+            OptimizationDirection::Maximize => -1.0,
+        };
+
+        let mut is_first_stage = vec![false; num_core_vars];
+        for var in &self.first_stage_vars {
+            is_first_stage[var.idx()] = true;
+        }
+        let mut is_first_stage_constr = vec![false; core.constraints.len()];
+        for constr in &self.first_stage_constraints {
+            is_first_stage_constr[constr.idx()] = true;
+        }
+
+        let mut problem = Problem::new(core.direction);
+        problem.set_objective_offset(core.objective_offset());
+        for &var in &self.first_stage_vars {
+            let v = var.idx();
+            let new_var = problem.add_var(
+                obj_sign * core.obj_coeffs[v],
+                (core.var_mins[v], core.var_maxs[v]),
+            );
+            if let Some(name) = &core.var_names[v] {
+                problem.set_var_name(new_var, name.as_str());
+            }
+        }
+        for (c, (coeffs, cmp_op, rhs)) in core.constraints.iter().enumerate() {
+            if is_first_stage_constr[c] {
+                let expr: LinearExpr = coeffs
+                    .iter()
+                    .map(|(v, &coeff)| (problem.var_ref(v), coeff))
+                    .collect();
+                problem.add_constraint(expr, *cmp_op, *rhs);
+            }
+        }
+
+        let mut second_stage_idx = vec![usize::MAX; num_core_vars];
+        let second_stage_vars: Vec<usize> =
+            (0..num_core_vars).filter(|&v| !is_first_stage[v]).collect();
+        for (i, &v) in second_stage_vars.iter().enumerate() {
+            second_stage_idx[v] = i;
+        }
+
+        for scenario in &self.scenarios {
+            let mut obj_coeffs: HashMap<usize, f64> = HashMap::new();
+            let mut rhs_changes: HashMap<usize, f64> = HashMap::new();
+            let mut coeff_changes: HashMap<usize, Vec<(usize, f64)>> = HashMap::new();
+            for change in &scenario.changes {
+                match *change {
+                    ScenarioChange::Rhs { constr, val } => {
+                        rhs_changes.insert(constr.idx(), val);
+                    }
+                    ScenarioChange::Coeff { constr, var, val } => coeff_changes
+                        .entry(constr.idx())
+                        .or_default()
+                        .push((var.idx(), val)),
+                    ScenarioChange::ObjCoeff { var, val } => {
+                        obj_coeffs.insert(var.idx(), val);
+                    }
+                }
+            }
+
+            let first_copy = problem.obj_coeffs.len();
+            for &v in &second_stage_vars {
+                let obj_coeff = obj_coeffs
+                    .get(&v)
+                    .copied()
+                    .unwrap_or(obj_sign * core.obj_coeffs[v]);
+                let new_var = problem.add_var(
+                    scenario.probability * obj_coeff,
+                    (core.var_mins[v], core.var_maxs[v]),
+                );
+                if let Some(name) = &core.var_names[v] {
+                    problem.set_var_name(new_var, format!("{}_{}", name, scenario.name));
+                }
+            }
+            let map_var = |v: usize| {
+                if is_first_stage[v] {
+                    Variable(v, 0)
+                } else {
+                    Variable(first_copy + second_stage_idx[v], 0)
+                }
+            };
+
+            for (c, (coeffs, cmp_op, rhs)) in core.constraints.iter().enumerate() {
+                if is_first_stage_constr[c] {
+                    continue;
+                }
+                let mut terms: Vec<(usize, f64)> =
+                    coeffs.iter().map(|(v, &coeff)| (v, coeff)).collect();
+                for &(var, val) in coeff_changes.get(&c).into_iter().flatten() {
+                    match terms.iter_mut().find(|(v, _)| *v == var) {
+                        Some(term) => term.1 = val,
+                        None => terms.push((var, val)),
+                    }
+                }
+                let expr: LinearExpr = terms
+                    .into_iter()
+                    .map(|(v, coeff)| (map_var(v), coeff))
+                    .collect();
+                let rhs = rhs_changes.get(&c).copied().unwrap_or(*rhs);
+                problem.add_constraint(expr, *cmp_op, rhs);
+            }
+        }
+
+        DeterministicEquivalent {
+            problem,
+            is_first_stage,
+            num_core_vars,
+            second_stage_idx,
+            num_second_stage_vars: second_stage_vars.len(),
+        }
+    }
+}
+
+fn in_file(file: &str, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{} file: {}", file, err))
+}
+
+fn new_lines<R: io::BufRead>(input: R) -> Lines<R> {
+    Lines {
+        input,
+        cur: String::new(),
+        idx: 0,
+    }
+}
+
+/// Parse the header line of a time or stoch file.
+fn parse_header<R: io::BufRead>(lines: &mut Lines<R>, header: &str) -> io::Result<()> {
+    lines.advance()?;
+    if Tokens::new(lines).next()? != header {
+        return Err(lines.err(&format!("expected {} section", header)));
+    }
+    lines.advance()
+}
+
+/// Parse the time file, returning the first-stage variables and constraints and the names
+/// of the two periods.
+fn parse_time<R: io::BufRead>(
+    input: R,
+    core: &MpsFile,
+    rows: &MpsRows,
+) -> io::Result<(Vec<Variable>, Vec<ConstraintRef>, [String; 2])> {
+    let mut lines = new_lines(input);
+    parse_header(&mut lines, "TIME")?;
+
+    {
+        let mut tokens = Tokens::new(&lines);
+        if tokens.next()? != "PERIODS" {
+            return Err(lines.err("expected PERIODS section"));
+        }
+        match tokens.iter.next() {
+            None | Some("IMPLICIT") => {}
+            Some(kind) => {
+                return Err(lines.err(&format!("{} periods are not supported", kind)));
+            }
+        }
+    }
+
+    let mut periods = vec![];
+    loop {
+        lines.advance()?;
+        if !lines.cur.starts_with(' ') {
+            break;
+        }
+        let mut tokens = Tokens::new(&lines);
+        let col = tokens.next()?;
+        let row = tokens.next()?;
+        let period = tokens.next()?;
+
+        let var = match core.variables.get(col) {
+            Some(&var) => var,
+            None => return Err(lines.err(&format!("unknown variable: {}", col))),
+        };
+        // The objective can be named as the first row of a period, meaning that
+        // the period starts with the first constraint.
+        let row_idx = if row == rows.obj_name {
+            0
+        } else if let Some(idx) = rows.rows.iter().position(|(name, _)| name == row) {
+            idx
+        } else {
+            return Err(lines.err(&format!("unknown constraint: {}", row)));
+        };
+        periods.push((var.idx(), row_idx, period.to_owned()));
+    }
+
+    if lines.cur != "ENDATA" {
+        return Err(lines.err("expected ENDATA section"));
+    }
+    if periods.len() != 2 {
+        return Err(lines.err(&format!(
+            "expected two periods, found {} (only two-stage problems are supported)",
+            periods.len()
+        )));
+    }
+
+    let (first_col, first_row, _) = &periods[1];
+    let first_stage_vars = (0..*first_col).map(|v| core.problem.var_ref(v)).collect();
+    let first_stage_constraints: Vec<ConstraintRef> = rows.rows[..*first_row]
+        .iter()
+        .flat_map(|(_, constrs)| constrs.iter().copied())
+        .collect();
+    for &constr in &first_stage_constraints {
+        if let Some((var, _)) = core
+            .problem
+            .constraint_terms(constr)
+            .find(|(var, _)| var.idx() >= *first_col)
+        {
+            return Err(lines.err(&format!(
+                "first-stage constraint {} contains the second-stage variable {}",
+                constr.idx(),
+                core.problem.var_name(var)
+            )));
+        }
+    }
+    let period_names = [periods[0].2.clone(), periods[1].2.clone()];
+    Ok((first_stage_vars, first_stage_constraints, period_names))
+}
+
+/// Parse the stoch file into the list of scenarios.
+fn parse_stoch<R: io::BufRead>(
+    input: R,
+    file: &SmpsFile,
+    rows: &MpsRows,
+    period_names: &[String; 2],
+) -> io::Result<Vec<Scenario>> {
+    let mut lines = new_lines(input);
+    parse_header(&mut lines, "STOCH")?;
+
+    let (section, kind) = {
+        let mut tokens = Tokens::new(&lines);
+        let section = tokens.next()?.to_owned();
+        let kind = tokens.iter.next().unwrap_or("DISCRETE").to_owned();
+        (section, kind)
+    };
+    if kind != "DISCRETE" {
+        return Err(lines.err(&format!("{} distributions are not supported", kind)));
+    }
+    let resolver = Resolver {
+        file,
+        rows,
+        second_period: &period_names[1],
+    };
+
+    let scenarios = match section.as_str() {
+        "INDEP" => parse_indep(&mut lines, &resolver)?,
+        "SCENARIOS" => parse_scenarios(&mut lines, &resolver)?,
+        _ => return Err(lines.err(&format!("section {} is not supported", section))),
+    };
+
+    if lines.cur != "ENDATA" {
+        return Err(lines.err("expected ENDATA section"));
+    }
+    if scenarios.is_empty() {
+        return Err(lines.err("no scenarios declared"));
+    }
+    Ok(scenarios)
+}
+
+/// Parse the INDEP section. Consecutive lines referring to the same element
+/// are its realizations.
+fn parse_indep<R: io::BufRead>(
+    lines: &mut Lines<R>,
+    resolver: &Resolver,
+) -> io::Result<Vec<Scenario>> {
+    // Each element is identified by the column and the row names.
+    type Realizations = Vec<(ScenarioChange, f64)>;
+    let mut elements: Vec<((String, String), Realizations)> = vec![];
+    loop {
+        lines.advance()?;
+        if !lines.cur.starts_with(' ') {
+            break;
+        }
+        let mut tokens = Tokens::new(lines);
+        let col = tokens.next()?;
+        let row = tokens.next()?;
+        let val = parse_f64(tokens.next()?, lines.idx)?;
+        resolver.check_period(lines, tokens.next()?)?;
+        let probability = parse_probability(lines, tokens.next()?)?;
+        let change = resolver.resolve(lines, col, row, val)?;
+
+        let key = (col.to_owned(), row.to_owned());
+        match elements.last_mut() {
+            Some((last, realizations)) if *last == key => {
+                realizations.push((change, probability));
+            }
+            _ => {
+                if elements.iter().any(|(other, _)| *other == key) {
+                    return Err(lines.err(&format!(
+                        "realizations of the element {} {} are not consecutive",
+                        col, row
+                    )));
+                }
+                elements.push((key, vec![(change, probability)]));
+            }
+        }
+    }
+
+    for ((col, row), realizations) in &elements {
+        let total: f64 = realizations.iter().map(|(_, p)| p).sum();
+        if (total - 1.0).abs() > PROBABILITY_TOL {
+            return Err(lines.err(&format!(
+                "probabilities of the element {} {} sum to {}",
+                col, row, total
+            )));
+        }
+    }
+
+    // Enumerate all combinations of the realizations, the last element changing fastest.
+    let mut scenarios = vec![Scenario {
+        name: String::new(),
+        probability: 1.0,
+        changes: vec![],
+    }];
+    for (_, realizations) in &elements {
+        let mut next = Vec::with_capacity(scenarios.len() * realizations.len());
+        for scenario in &scenarios {
+            for &(change, probability) in realizations {
+                let mut changes = scenario.changes.clone();
+                changes.push(change);
+                next.push(Scenario {
+                    name: String::new(),
+                    probability: scenario.probability * probability,
+                    changes,
+                });
+            }
+        }
+        scenarios = next;
+    }
+    for (i, scenario) in scenarios.iter_mut().enumerate() {
+        scenario.name = format!("S{}", i);
+    }
+    Ok(scenarios)
+}
+
+/// Parse the SCENARIOS section.
+fn parse_scenarios<R: io::BufRead>(
+    lines: &mut Lines<R>,
+    resolver: &Resolver,
+) -> io::Result<Vec<Scenario>> {
+    let mut scenarios: Vec<Scenario> = vec![];
+    loop {
+        lines.advance()?;
+        if !lines.cur.starts_with(' ') {
+            break;
+        }
+        let mut tokens = Tokens::new(lines);
+        let first = tokens.next()?;
+        if first == "SC" {
+            let name = tokens.next()?;
+            let parent = tokens.next()?;
+            if parent != "ROOT" {
+                return Err(lines.err(&format!(
+                    "scenario {} branches from {} (only two-stage problems are supported)",
+                    name, parent
+                )));
+            }
+            let probability = parse_probability(lines, tokens.next()?)?;
+            resolver.check_period(lines, tokens.next()?)?;
+            if scenarios.iter().any(|s| s.name == name) {
+                return Err(lines.err(&format!("scenario {} already declared", name)));
+            }
+            scenarios.push(Scenario {
+                name: name.to_owned(),
+                probability,
+                changes: vec![],
+            });
+        } else {
+            let row = tokens.next()?;
+            let val = parse_f64(tokens.next()?, lines.idx)?;
+            let change = resolver.resolve(lines, first, row, val)?;
+            match scenarios.last_mut() {
+                Some(scenario) => scenario.changes.push(change),
+                None => return Err(lines.err("expected SC line")),
+            }
+        }
+    }
+
+    let total: f64 = scenarios.iter().map(|s| s.probability).sum();
+    if !scenarios.is_empty() && (total - 1.0).abs() > PROBABILITY_TOL {
+        return Err(lines.err(&format!("probabilities of scenarios sum to {}", total)));
+    }
+    Ok(scenarios)
+}
+
+fn parse_probability<R: io::BufRead>(lines: &Lines<R>, input: &str) -> io::Result<f64> {
+    let probability = parse_f64(input, lines.idx)?;
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(lines.err(&format!("invalid probability: {}", probability)));
+    }
+    Ok(probability)
+}
+
+/// Resolves the names in the stoch file to the changes of the core problem.
+struct Resolver<'a> {
+    file: &'a SmpsFile,
+    rows: &'a MpsRows,
+    second_period: &'a str,
+}
+
+impl Resolver<'_> {
+    fn check_period<R: io::BufRead>(&self, lines: &Lines<R>, period: &str) -> io::Result<()> {
+        if period != self.second_period {
+            return Err(lines.err(&format!(
+                "random data is supported only in the second period {}, found {}",
+                self.second_period, period
+            )));
+        }
+        Ok(())
+    }
+
+    fn resolve<R: io::BufRead>(
+        &self,
+        lines: &Lines<R>,
+        col: &str,
+        row: &str,
+        val: f64,
+    ) -> io::Result<ScenarioChange> {
+        if !val.is_finite() {
+            return Err(lines.err(&format!("invalid value: {}", val)));
+        }
+
+        let var = self.file.core.variables.get(col).copied();
+        if var.is_none() && Some(col) != self.rows.rhs_name.as_deref().or(Some("RHS")) {
+            return Err(lines.err(&format!("unknown variable: {}", col)));
+        }
+
+        if row == self.rows.obj_name {
+            return match var {
+                Some(var) if !self.file.first_stage_vars.contains(&var) => {
+                    Ok(ScenarioChange::ObjCoeff { var, val })
+                }
+                Some(_) => Err(lines.err(&format!(
+                    "random objective coefficient of the first-stage variable {}",
+                    col
+                ))),
+                None => Err(lines.err("random objective offset is not supported")),
+            };
+        }
+
+        let constrs = match self.rows.rows.iter().find(|(name, _)| name == row) {
+            Some((_, constrs)) => constrs,
+            None => return Err(lines.err(&format!("unknown constraint: {}", row))),
+        };
+        let constr = match constrs[..] {
+            [constr] => constr,
+            _ => {
+                return Err(lines.err(&format!(
+                    "random data of the ranged row {} is not supported",
+                    row
+                )));
+            }
+        };
+        if self.file.first_stage_constraints.contains(&constr) {
+            return Err(lines.err(&format!(
+                "random data of the first-stage constraint {}",
+                row
+            )));
+        }
+
+        Ok(match var {
+            Some(var) => ScenarioChange::Coeff { constr, var, val },
+            None => ScenarioChange::Rhs { constr, val },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORE: &str = "\
+NAME          NEWSVEND
+ROWS
+ N  PROFIT
+ L  SELL
+ L  STOCK
+COLUMNS
+    ORDER     PROFIT    -1        STOCK     -1
+    SALES     PROFIT    2         SELL      1
+    SALES     STOCK     1
+RHS
+    RHS       SELL      0
+ENDATA
+";
+
+    const TIME: &str = "\
+TIME          NEWSVEND
+PERIODS       IMPLICIT
+    ORDER     PROFIT    FIRST
+    SALES     SELL      SECOND
+ENDATA
+";
+
+    fn parse(stoch: &str) -> io::Result<SmpsFile> {
+        SmpsFile::parse(
+            CORE.as_bytes(),
+            TIME.as_bytes(),
+            stoch.as_bytes(),
+            OptimizationDirection::Maximize,
+        )
+    }
+
+    #[test]
+    fn indep() {
+        let file = parse(
+            "\
+STOCH         NEWSVEND
+INDEP         DISCRETE
+    RHS       SELL      10        SECOND    0.5
+    RHS       SELL      30        SECOND    0.5
+    SALES     PROFIT    2         SECOND    0.25
+    SALES     PROFIT    4         SECOND    0.75
+ENDATA
+",
+        )
+        .unwrap();
+        let order = file.core.variables["ORDER"];
+        let sales = file.core.variables["SALES"];
+        assert_eq!(file.first_stage_vars, [order]);
+        assert_eq!(file.first_stage_constraints, []);
+        assert_eq!(file.scenarios.len(), 4);
+        assert_eq!(file.scenarios[1].name, "S1");
+        assert_eq!(file.scenarios[1].probability, 0.375);
+        assert_eq!(
+            file.scenarios[1].changes,
+            [
+                ScenarioChange::Rhs {
+                    constr: ConstraintRef(0, 0),
+                    val: 10.0
+                },
+                ScenarioChange::ObjCoeff {
+                    var: sales,
+                    val: 4.0
+                },
+            ]
+        );
+
+        // The expected price is 3.5, so it pays to order for the high demand:
+        // -30 + 0.5 * 3.5 * 10 + 0.5 * 3.5 * 30.
+        let det = file.deterministic_equivalent();
+        let solution = det.problem.solve().unwrap();
+        assert_eq!(solution[det.scenario_var(3, order)], 30.0);
+        assert_eq!(solution[det.scenario_var(0, sales)], 10.0);
+        assert_eq!(solution[det.scenario_var(3, sales)], 30.0);
+        assert!(f64::abs(solution.objective() - 40.0) < 1e-9);
+        assert_eq!(det.problem.var_name(det.scenario_var(2, sales)), "SALES_S2");
+    }
+
+    #[test]
+    fn scenarios() {
+        let file = parse(
+            "\
+STOCH         NEWSVEND
+SCENARIOS     DISCRETE
+ SC SCEN1     ROOT      0.4       SECOND
+    RHS       SELL      10
+ SC SCEN2     ROOT      0.6       SECOND
+    RHS       SELL      30
+    SALES     STOCK     2
+ENDATA
+",
+        )
+        .unwrap();
+        assert_eq!(file.scenarios.len(), 2);
+        assert_eq!(file.scenarios[1].name, "SCEN2");
+
+        // In the second scenario every sale needs two units of stock.
+        let det = file.deterministic_equivalent();
+        let solution = det.problem.solve().unwrap();
+        let order = file.core.variables["ORDER"];
+        assert_eq!(solution[det.scenario_var(0, order)], 10.0);
+        assert!(f64::abs(solution.objective() - (-10.0 + 0.4 * 20.0 + 0.6 * 10.0)) < 1e-9);
+    }
+
+    #[test]
+    fn errors() {
+        let err = parse(
+            "\
+STOCH         NEWSVEND
+INDEP         DISCRETE
+    RHS       SELL      10        SECOND    0.5
+    RHS       SELL      30        SECOND    0.4
+ENDATA
+",
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "stoch file: line 5: probabilities of the element RHS SELL sum to 0.9"
+        );
+
+        let err = parse(
+            "\
+STOCH         NEWSVEND
+INDEP         DISCRETE
+    ORDER     PROFIT    -2        SECOND    1
+ENDATA
+",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "stoch file: line 3: random objective coefficient of the first-stage variable ORDER"
+        );
+
+        let err = SmpsFile::parse(
+            CORE.as_bytes(),
+            "TIME X\nPERIODS\n    ORDER PROFIT FIRST\nENDATA\n".as_bytes(),
+            "".as_bytes(),
+            OptimizationDirection::Maximize,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "time file: line 4: expected two periods, found 1 (only two-stage problems are supported)"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// A machine-readable log of solver events in the JSON Lines format (one JSON object
+/// per line), see [`SolverOptions::solve_log`].
+///
+/// Each event has the `event` field with its name, the `time` in seconds since the log
+/// was created and the total number of simplex `iterations` so far. The events are:
+///
+/// * `iteration`: sampled every [`sample_interval`](#method.with_sample_interval) iterations
+///   of each simplex run. Fields: `phase`, `objective` (the current objective value of the
+///   phase), `primal_infeasibility` and `dual_infeasibility`.
+/// * `refactorization`: the basis matrix was factorized from scratch. Fields: `trigger`
+///   (`updates`, `recalc` or `constraint`, see [`SolverStats`]), `updates` (number of basis
+///   updates since the previous factorization), `lu_nnz` (number of nonzeros in the factors)
+///   and `pivot_growth`.
+/// * `finish`: the initial solve is finished. Fields: `status` (`optimal`, `infeasible` or
+///   `unbounded`) and `objective` (the optimal objective value if optimal).
+/// * `constraint`: a constraint was added to the solution.
+/// * `cuts`: cuts from a [`CutPool`] were added. Fields: `count`.
+/// * `gomory_cut`: a Gomory cut was added. Fields: `var`.
+/// * `incumbent`: a dive found an integer-feasible solution. Fields: `objective`.
+///
+/// Objective values are in the minimization form, i.e. negated for maximization problems.
+/// Infinite and NaN values are written as `null`. Errors writing to the underlying writer
+/// are reported with the `log` crate and otherwise ignored.
+///
+/// [`SolverOptions::solve_log`]: struct.SolverOptions.html#structfield.solve_log
+/// [`CutPool`]: struct.CutPool.html
+/// [`SolverStats`]: struct.SolverStats.html
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::*;
+/// let mut problem = Problem::new(OptimizationDirection::Maximize);
+/// let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+/// let y = problem.add_var(2.0, (0.0, 3.0));
+/// problem.add_constraint(&[(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+///
+/// let options = SolverOptions {
+///     solve_log: Some(SolveLog::new(std::io::sink()).with_sample_interval(10)),
+///     ..Default::default()
+/// };
+/// let solution = problem.solve_with(&options).unwrap();
+/// assert_eq!(solution.objective(), 7.0);
+/// ```
+#[derive(Clone)]
+pub struct SolveLog {
+    writer: Arc<Mutex<dyn Write + Send>>,
+    start: Instant,
+    sample_interval: usize,
+}
+
+impl fmt::Debug for SolveLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SolveLog")
+            .field("sample_interval", &self.sample_interval)
+            .finish()
+    }
+}
+
+/// A value of an event field.
+pub(crate) enum LogValue<'a> {
+    Int(usize),
+    Float(f64),
+    Str(&'a str),
+    /// Already formatted JSON (e.g. a nested object).
+    Json(&'a str),
+}
+
+impl SolveLog {
+    /// Create a log writing to `writer`. Iteration events are sampled every 100 iterations.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        SolveLog {
+            writer: Arc::new(Mutex::new(writer)),
+            start: Instant::now(),
+            sample_interval: 100,
+        }
+    }
+
+    /// Sample iteration events every `interval` iterations (0 disables them).
+    pub fn with_sample_interval(mut self, interval: usize) -> Self {
+        self.sample_interval = interval;
+        self
+    }
+
+    pub(crate) fn samples_iteration(&self, iter: usize) -> bool {
+        self.sample_interval != 0 && iter.is_multiple_of(self.sample_interval)
+    }
+
+    pub(crate) fn event(&self, event: &str, iterations: usize, fields: &[(&str, LogValue)]) {
+        let header = [
+            ("event", LogValue::Str(event)),
+            ("time", LogValue::Float(self.start.elapsed().as_secs_f64())),
+            ("iterations", LogValue::Int(iterations)),
+        ];
+        let mut line = json_object(header.iter().chain(fields));
+        line.push('\n');
+
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(err) = writer.write_all(line.as_bytes()) {
+            warn!("failed to write the solve log: {}", err);
+        }
+    }
+}
+
+/// Format the fields as a JSON object.
+pub(crate) fn json_object<'a, 'b: 'a>(
+    fields: impl IntoIterator<Item = &'a (&'b str, LogValue<'b>)>,
+) -> String {
+    let mut out = String::from("{");
+    for (i, (name, value)) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_json_str(&mut out, name);
+        out.push(':');
+        match *value {
+            LogValue::Int(val) => write!(out, "{}", val).unwrap(),
+            LogValue::Float(val) if val.is_finite() => write!(out, "{:?}", val).unwrap(),
+            LogValue::Float(_) => out.push_str("null"),
+            LogValue::Str(val) => write_json_str(&mut out, val),
+            LogValue::Json(val) => out.push_str(val),
+        }
+    }
+    out.push('}');
+    out
+}
+
+/// Write the string as a JSON string literal.
+pub(crate) fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn lines(&self) -> Vec<String> {
+            let buf = self.0.lock().unwrap();
+            String::from_utf8(buf.clone())
+                .unwrap()
+                .lines()
+                .map(|line| line.to_string())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn format() {
+        let buf = SharedBuf::default();
+        let log = SolveLog::new(buf.clone());
+        log.event(
+            "test",
+            3,
+            &[
+                ("count", LogValue::Int(2)),
+                ("val", LogValue::Float(0.5)),
+                ("inf", LogValue::Float(f64::INFINITY)),
+                ("name", LogValue::Str("a \"b\"\n")),
+            ],
+        );
+        let lines = buf.lines();
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert!(line.starts_with("{\"event\":\"test\",\"time\":"));
+        assert!(line.ends_with(
+            ",\"iterations\":3,\"count\":2,\"val\":0.5,\"inf\":null,\"name\":\"a \\\"b\\\"\\u000a\"}"
+        ));
+    }
+
+    #[test]
+    fn events() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (0.0, 3.0));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Le, 4.0);
+        problem.add_constraint([(x, 2.0), (y, 1.0)], ComparisonOp::Ge, 2.0);
+
+        let buf = SharedBuf::default();
+        let options = SolverOptions {
+            solve_log: Some(SolveLog::new(buf.clone()).with_sample_interval(1)),
+            ..Default::default()
+        };
+        let solution = problem.solve_with(&options).unwrap();
+        let solution = solution
+            .add_constraint([(x, 1.0)], ComparisonOp::Le, 0.5)
+            .unwrap();
+        assert_eq!(solution.objective(), 6.5);
+
+        let lines = buf.lines();
+        let events: Vec<&str> = lines
+            .iter()
+            .map(|line| line.split('"').nth(3).unwrap())
+            .collect();
+        assert_eq!(events[0], "iteration");
+        let finish = lines
+            .iter()
+            .position(|line| line.contains("\"finish\""))
+            .unwrap();
+        assert!(lines[finish].contains("\"status\":\"optimal\",\"objective\":-7.0"));
+        assert_eq!(events.last(), Some(&"constraint"));
+        assert!(finish < events.len() - 1);
+    }
+}
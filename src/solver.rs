@@ -1,19 +1,71 @@
 use crate::{
-    helpers::{resized_view, to_dense},
+    helpers::{resized_view, to_dense, Rng},
     lu::{lu_factorize, LUFactors, ScratchSpace},
-    sparse::{ScatteredVec, SparseMat, SparseVec},
-    ComparisonOp, CsVec, Error,
+    ordering::{find_diag_matching, order_simple},
+    parallel,
+    probing::{self, Row},
+    progress::ProgressTracker,
+    scaling::Scaling,
+    solve_log::LogValue,
+    sparse::{Perm, ScatteredVec, SparseMat, SparseVec},
+    summation, Accuracy, Algorithm, AntiDegeneracy, BasisOrdering, ComparisonOp, CsVec, Error,
+    ErrorKind, Phase, Pricing, ScalingMode, SolverOptions, SolverStats, SolverTimings,
 };
 
 use sprs::CompressedStorage;
+use std::time::Instant;
 
 type CsMat = sprs::CsMatI<f64, usize>;
 
-const EPS: f64 = 1e-8;
+/// Tolerance of the solver, in particular of the primal and dual feasibility.
+pub(crate) const EPS: f64 = 1e-8;
+
+/// Number of consecutive degenerate iterations after which the solver is considered stalling.
+const STALL_ITERS: usize = 100;
+
+/// Initial value of the primal feasibility tolerance for the EXPAND procedure.
+const EXPAND_TOL_INITIAL: f64 = 0.5 * EPS;
+/// After this many iterations the EXPAND tolerance reaches EPS and is reset.
+const EXPAND_RESET_ITERS: usize = 10000;
+/// Increment of the EXPAND tolerance on each iteration.
+const EXPAND_TOL_INCREMENT: f64 = (EPS - EXPAND_TOL_INITIAL) / EXPAND_RESET_ITERS as f64;
+
+/// Partial pricing splits the columns into this many segments.
+const PARTIAL_PRICING_SEGMENTS: usize = 8;
+/// Minimal number of columns in a partial pricing segment.
+const PARTIAL_PRICING_MIN_SEGMENT: usize = 1000;
+
+/// Number of entering candidates chosen by the multiple pricing.
+const MULTIPLE_PRICING_CANDIDATES: usize = 4;
+
+/// GMI cuts are not generated from rows in which the fractional part of the basic var value
+/// is closer than this to 0 or 1.
+const GMI_MIN_FRACTIONALITY: f64 = 0.01;
+/// GMI cuts with the ratio of the biggest to the smallest abs. coefficient greater than this
+/// are rejected as numerically unsafe.
+const GMI_MAX_DYNAMISM: f64 = 1e6;
+
+/// In the Auto mode the primal simplex is used to find a feasible solution if at most
+/// 1/PRIMAL_MAX_INFEASIBLE_RATIO of the constraints are violated by the starting basis.
+const PRIMAL_MAX_INFEASIBLE_RATIO: usize = 100;
+
+/// Progress is reported to the callback every PROGRESS_INTERVAL iterations.
+const PROGRESS_INTERVAL: usize = 100;
+
+/// If the relative gap tolerance is set, the gap is calculated every GAP_CHECK_INTERVAL
+/// iterations of the primal simplex (it costs about as much as an iteration).
+const GAP_CHECK_INTERVAL: usize = 10;
 
 #[derive(Clone)]
 pub(crate) struct Solver {
     pub(crate) num_vars: usize,
+    options: SolverOptions,
+
+    /// If present, the solver works with the scaled problem.
+    scaling: Option<Scaling>,
+    /// Values of the problem vars in the original (unscaled) units. Maintained
+    /// only if the problem is scaled.
+    unscaled_vals: Vec<f64>,
 
     orig_obj_coeffs: Vec<f64>,
     orig_var_mins: Vec<f64>,
@@ -27,6 +79,8 @@ pub(crate) struct Solver {
 
     is_primal_feasible: bool,
     is_dual_feasible: bool,
+    /// Whether initial values of the variables were supplied by the caller.
+    is_warm_started: bool,
 
     // Updated on each pivot
     /// For each var: whether it is basic/non-basic and the corresponding index.
@@ -50,6 +104,38 @@ pub(crate) struct Solver {
 
     pub(crate) cur_obj_val: f64,
 
+    pub(crate) stats: SolverStats,
+    /// Time spent in scaling, dualization, pricing and ratio tests (the rest is recorded
+    /// by the basis solver).
+    pub(crate) timings: SolverTimings,
+    /// Number of consecutive degenerate iterations.
+    degenerate_streak: usize,
+    /// Current primal feasibility tolerance of the EXPAND procedure.
+    expand_tol: f64,
+    /// Relative gap at which the primal simplex was stopped early.
+    relative_gap: f64,
+    /// Bounds of all vars (slack vars included) implied by the constraints. Calculated when
+    /// the relative gap is first needed.
+    implied_bounds: Vec<(f64, f64)>,
+    /// Candidate list of the partial pricing (columns of non-basic vars).
+    pricing_candidates: Vec<usize>,
+    /// Column from which the next partial pricing segment starts.
+    pricing_start: usize,
+    /// Generator for random tie-breaking in pricing (if enabled).
+    rng: Option<Rng>,
+    /// Present during the initial solve if progress reporting is enabled.
+    progress: Option<ProgressTracker>,
+    /// If present, the simplex loops return early when the number of iterations reaches it.
+    iteration_limit: Option<usize>,
+    /// Whether the last initial solve returned early because of the iteration limit.
+    is_interrupted: bool,
+    /// Whether the primal simplex was chosen to find the first feasible solution (remembered
+    /// so that an interrupted initial solve continues with the same algorithm).
+    is_primal_phase1: Option<bool>,
+    /// Whether the initial solve has started optimizing (so that an interrupted initial
+    /// solve continues without recalculating the objective coeffs).
+    is_optimizing: bool,
+
     // Recomputed on each pivot
     col_coeffs: SparseVec,
     sq_norms_update_helper: Vec<f64>,
@@ -71,46 +157,131 @@ struct NonBasicVarState {
 
 impl std::fmt::Debug for Solver {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Solver\n")?;
-        write!(
+        writeln!(f, "Solver")?;
+        writeln!(
             f,
-            "num_vars: {}, num_constraints: {}, is_primal_feasible: {}, is_dual_feasible: {}\n",
+            "num_vars: {}, num_constraints: {}, is_primal_feasible: {}, is_dual_feasible: {}",
             self.num_vars,
             self.num_constraints(),
             self.is_primal_feasible,
             self.is_dual_feasible,
         )?;
-        write!(f, "orig_obj_coeffs:\n{:?}\n", self.orig_obj_coeffs)?;
-        write!(f, "orig_var_mins:\n{:?}\n", self.orig_var_mins)?;
-        write!(f, "orig_var_maxs:\n{:?}\n", self.orig_var_maxs)?;
-        write!(f, "orig_constraints:\n")?;
+        writeln!(f, "orig_obj_coeffs:\n{:?}", self.orig_obj_coeffs)?;
+        writeln!(f, "orig_var_mins:\n{:?}", self.orig_var_mins)?;
+        writeln!(f, "orig_var_maxs:\n{:?}", self.orig_var_maxs)?;
+        writeln!(f, "orig_constraints:")?;
         for row in self.orig_constraints.outer_iterator() {
-            write!(f, "{:?}\n", to_dense(&row))?;
-        }
-        write!(f, "orig_rhs:\n{:?}\n", self.orig_rhs)?;
-        write!(f, "basic_vars:\n{:?}\n", self.basic_vars)?;
-        write!(f, "basic_var_vals:\n{:?}\n", self.basic_var_vals)?;
-        write!(f, "dual_edge_sq_norms:\n{:?}\n", self.dual_edge_sq_norms)?;
-        write!(f, "nb_vars:\n{:?}\n", self.nb_vars)?;
-        write!(f, "nb_var_vals:\n{:?}\n", self.nb_var_vals)?;
-        write!(f, "nb_var_obj_coeffs:\n{:?}\n", self.nb_var_obj_coeffs)?;
-        write!(
-            f,
-            "primal_edge_sq_norms:\n{:?}\n",
-            self.primal_edge_sq_norms
-        )?;
-        write!(f, "cur_obj_val: {:?}\n", self.cur_obj_val)?;
+            writeln!(f, "{:?}", to_dense(&row))?;
+        }
+        writeln!(f, "orig_rhs:\n{:?}", self.orig_rhs)?;
+        writeln!(f, "basic_vars:\n{:?}", self.basic_vars)?;
+        writeln!(f, "basic_var_vals:\n{:?}", self.basic_var_vals)?;
+        writeln!(f, "dual_edge_sq_norms:\n{:?}", self.dual_edge_sq_norms)?;
+        writeln!(f, "nb_vars:\n{:?}", self.nb_vars)?;
+        writeln!(f, "nb_var_vals:\n{:?}", self.nb_var_vals)?;
+        writeln!(f, "nb_var_obj_coeffs:\n{:?}", self.nb_var_obj_coeffs)?;
+        writeln!(f, "primal_edge_sq_norms:\n{:?}", self.primal_edge_sq_norms)?;
+        writeln!(f, "cur_obj_val: {:?}", self.cur_obj_val)?;
         Ok(())
     }
 }
 
 impl Solver {
+    #[cfg(test)]
     pub(crate) fn try_new(
         obj_coeffs: &[f64],
         var_mins: &[f64],
         var_maxs: &[f64],
         constraints: &[(CsVec, ComparisonOp, f64)],
     ) -> Result<Self, Error> {
+        Self::try_new_with_options(
+            obj_coeffs,
+            var_mins,
+            var_maxs,
+            constraints,
+            None,
+            &SolverOptions::default(),
+        )
+    }
+
+    /// If `start` is present, it contains initial values of the variables (they will be
+    /// clamped to the bounds). Variables with values strictly inside their bounds will be
+    /// moved to the bounds or into the basis during the initial solve.
+    pub(crate) fn try_new_with_options(
+        obj_coeffs: &[f64],
+        var_mins: &[f64],
+        var_maxs: &[f64],
+        constraints: &[(CsVec, ComparisonOp, f64)],
+        start: Option<&[f64]>,
+        options: &SolverOptions,
+    ) -> Result<Self, Error> {
+        let scaling_start = Instant::now();
+        if let Some(scaling) = Scaling::compute(
+            options.scaling,
+            obj_coeffs.len(),
+            constraints,
+            options.threads,
+        ) {
+            let col_scales = &scaling.col_scales;
+            let obj_coeffs: Vec<f64> = obj_coeffs
+                .iter()
+                .zip(col_scales)
+                .map(|(&coeff, &scale)| coeff * scale)
+                .collect();
+            let unscale = |vals: &[f64]| -> Vec<f64> {
+                vals.iter()
+                    .zip(col_scales)
+                    .map(|(&val, &scale)| val / scale)
+                    .collect()
+            };
+            let var_mins = unscale(var_mins);
+            let var_maxs = unscale(var_maxs);
+            let start = start.map(unscale);
+            let constraints: Vec<_> = constraints
+                .iter()
+                .zip(&scaling.row_scales)
+                .map(|((coeffs, cmp_op, rhs), &row_scale)| {
+                    (
+                        scaling.scale_coeffs(coeffs, row_scale),
+                        *cmp_op,
+                        rhs * row_scale,
+                    )
+                })
+                .collect();
+
+            let scaling_time = scaling_start.elapsed();
+
+            let unscaled_options = SolverOptions {
+                scaling: ScalingMode::None,
+                ..options.clone()
+            };
+            let mut solver = Self::try_new_with_options(
+                &obj_coeffs,
+                &var_mins,
+                &var_maxs,
+                &constraints,
+                start.as_deref(),
+                &unscaled_options,
+            )
+            .map_err(|err| {
+                // Report the violation in the original units.
+                let scale = match (err.variable(), err.constraint()) {
+                    (Some(var), _) => col_scales[var.0],
+                    (None, Some(constr)) => 1.0 / scaling.row_scales[constr.0],
+                    (None, None) => 1.0,
+                };
+                match err.violation() {
+                    Some(violation) => err.with_violation(violation * scale),
+                    None => err,
+                }
+            })?;
+            solver.options.scaling = options.scaling;
+            solver.scaling = Some(scaling);
+            solver.timings.scaling = scaling_time;
+            solver.refresh_unscaled_vals();
+            return Ok(solver);
+        }
+
         let enable_steepest_edge = true; // TODO: make user-settable.
 
         let num_vars = obj_coeffs.len();
@@ -136,7 +307,9 @@ impl Solver {
             let min = orig_var_mins[v];
             let max = orig_var_maxs[v];
             if min > max {
-                return Err(Error::Infeasible);
+                return Err(Error::infeasible(Phase::Setup)
+                    .with_variable(v)
+                    .with_violation(min - max));
             }
 
             // initially all user-created variables are non-basic
@@ -144,7 +317,13 @@ impl Solver {
             nb_vars.push(v);
 
             // Try to choose values to achieve dual feasibility.
-            let init_val = if min == max {
+            let init_val = if let Some(start) = start {
+                let val = f64::min(f64::max(start[v], min), max);
+                if (obj_coeffs[v] > 0.0 && val != min) || (obj_coeffs[v] < 0.0 && val != max) {
+                    is_dual_feasible = false;
+                }
+                val
+            } else if min == max {
                 // Fixed variable, the obj. coeff doesn't matter.
                 min
             } else if min.is_infinite() && max.is_infinite() {
@@ -205,10 +384,12 @@ impl Solver {
                     ComparisonOp::Ge => 0.0 >= rhs,
                 };
 
-                if is_tautological {
-                    continue;
-                } else {
-                    return Err(Error::Infeasible);
+                // Tautological constraints are still added so that rows of the solver
+                // correspond to the problem constraints.
+                if !is_tautological {
+                    return Err(Error::infeasible(Phase::Setup)
+                        .with_constraint(constraint_coeffs.len())
+                        .with_violation(rhs.abs()));
                 }
             }
 
@@ -269,7 +450,9 @@ impl Solver {
 
         // If is dual feasible at start, we don't need lengthy primal phase2.
         // Thus we can skip expensive calculations for primal sq. norms.
-        let enable_primal_steepest_edge = enable_steepest_edge && !is_dual_feasible;
+        // Multiple pricing doesn't maintain primal sq. norms.
+        let enable_primal_steepest_edge =
+            enable_steepest_edge && !is_dual_feasible && options.pricing != Pricing::Multiple;
         let sq_norms_update_helper = if enable_primal_steepest_edge {
             vec![0.0; num_total_vars - num_constraints]
         } else {
@@ -301,25 +484,37 @@ impl Solver {
 
         let cur_obj_val = if need_artificial_obj { 0.0 } else { obj_val };
 
+        if let BasisOrdering::Fixed(perm) = &options.basis_ordering {
+            assert_eq!(
+                perm.len(),
+                num_vars,
+                "basis ordering must have an entry for each variable"
+            );
+        }
+
         let mut scratch = ScratchSpace::with_capacity(num_constraints);
-        let lu_factors = lu_factorize(
-            basic_vars.len(),
-            |c| {
-                orig_constraints_csc
-                    .outer_view(basic_vars[c])
-                    .unwrap()
-                    .into_raw_storage()
-            },
-            0.1,
+        let mut basis_timings = SolverTimings::default();
+        let lu_factors = factorize_basis(
+            &orig_constraints_csc,
+            &basic_vars,
+            &options.basis_ordering,
             &mut scratch,
+            &mut basis_timings,
         )
         .unwrap();
         let lu_factors_transp = lu_factors.transpose();
+        let stats = SolverStats {
+            max_pivot_growth: pivot_growth(&orig_constraints_csc, &basic_vars, &lu_factors),
+            ..SolverStats::default()
+        };
 
         let nb_var_is_fixed = vec![false; nb_vars.len()];
 
         let res = Self {
             num_vars,
+            options: options.clone(),
+            scaling: None,
+            unscaled_vals: vec![],
             orig_obj_coeffs,
             orig_var_mins,
             orig_var_maxs,
@@ -330,6 +525,7 @@ impl Solver {
             enable_dual_steepest_edge,
             is_primal_feasible,
             is_dual_feasible,
+            is_warm_started: start.is_some(),
             var_states,
             basis_solver: BasisSolver {
                 lu_factors,
@@ -337,6 +533,8 @@ impl Solver {
                 scratch,
                 eta_matrices: EtaMatrices::new(num_constraints),
                 rhs: ScatteredVec::empty(num_constraints),
+                ordering: options.basis_ordering.clone(),
+                timings: basis_timings,
             },
             basic_vars,
             basic_var_vals,
@@ -350,6 +548,20 @@ impl Solver {
             nb_var_is_fixed,
             primal_edge_sq_norms,
             cur_obj_val,
+            stats,
+            timings: SolverTimings::default(),
+            degenerate_streak: 0,
+            expand_tol: EXPAND_TOL_INITIAL,
+            relative_gap: 0.0,
+            implied_bounds: vec![],
+            pricing_candidates: vec![],
+            pricing_start: 0,
+            rng: options.random_seed.map(Rng::new),
+            progress: None,
+            iteration_limit: None,
+            is_interrupted: false,
+            is_primal_phase1: None,
+            is_optimizing: false,
             col_coeffs: SparseVec::new(),
             sq_norms_update_helper,
             inv_basis_row_coeffs: SparseVec::new(),
@@ -368,24 +580,435 @@ impl Solver {
         Ok(res)
     }
 
+    /// Value of the problem var in the original (unscaled) units.
     pub(crate) fn get_value(&self, var: usize) -> &f64 {
+        if self.scaling.is_some() {
+            &self.unscaled_vals[var]
+        } else {
+            self.get_scaled_value(var)
+        }
+    }
+
+    fn get_scaled_value(&self, var: usize) -> &f64 {
         match self.var_states[var] {
             VarState::Basic(idx) => &self.basic_var_vals[idx],
             VarState::NonBasic(idx) => &self.nb_var_vals[idx],
         }
     }
 
+    /// Must be called after each operation changing the var values.
+    fn refresh_unscaled_vals(&mut self) {
+        if let Some(scaling) = &self.scaling {
+            let vals = (0..self.num_vars)
+                .map(|v| self.get_scaled_value(v) * scaling.col_scales[v])
+                .collect();
+            self.unscaled_vals = vals;
+        }
+    }
+
+    /// Ratio of the original var (problem var or slack) to the corresponding scaled var.
+    fn var_scale(&self, var: usize) -> f64 {
+        match &self.scaling {
+            None => 1.0,
+            Some(scaling) if var < self.num_vars => scaling.col_scales[var],
+            Some(scaling) => 1.0 / scaling.row_scale(var - self.num_vars),
+        }
+    }
+
+    /// Add the var (or the constraint if the var is a slack var) and the violation of its
+    /// bounds in the original units to the error context.
+    fn error_at(&self, err: Error, var: usize, violation: Option<f64>) -> Error {
+        let err = if var < self.num_vars {
+            err.with_variable(var)
+        } else {
+            err.with_constraint(var - self.num_vars)
+        };
+        match violation {
+            Some(violation) => err.with_violation(violation * self.var_scale(var)),
+            None => err,
+        }
+    }
+
+    /// Bounds of the problem var in the original units.
+    pub(crate) fn var_bounds(&self, var: usize) -> (f64, f64) {
+        let scale = self.var_scale(var);
+        (
+            self.orig_var_mins[var] * scale,
+            self.orig_var_maxs[var] * scale,
+        )
+    }
+
+    /// Coeffs of the problem vars in the constraint and the bounds of the constraint lhs
+    /// (possibly infinite) in the original units.
+    pub(crate) fn constraint_row(&self, constr: usize) -> (Vec<(usize, f64)>, f64, f64) {
+        // lhs is rhs - slack, thus its bounds are determined by the slack bounds.
+        let slack_var = self.num_vars + constr;
+        let scale = self.var_scale(slack_var);
+        let rhs = self.orig_rhs[constr];
+        let min = (rhs - self.orig_var_maxs[slack_var]) * scale;
+        let max = (rhs - self.orig_var_mins[slack_var]) * scale;
+
+        let mut coeffs = vec![];
+        for (v, &coeff) in self.orig_constraints.outer_view(constr).unwrap().iter() {
+            if v < self.num_vars {
+                coeffs.push((v, coeff * scale / self.var_scale(v)));
+            }
+        }
+        (coeffs, min, max)
+    }
+
+    /// Whether the var (problem var or slack) is in the current basis.
+    pub(crate) fn is_basic(&self, var: usize) -> bool {
+        matches!(self.var_states[var], VarState::Basic(_))
+    }
+
+    /// Rate of change of the objective with respect to the constraint rhs.
+    pub(crate) fn dual_value(&self, constr: usize) -> f64 {
+        // The slack var column is a unit vector, so its reduced cost is -(dual value).
+        let scaled_dual_value = match self.var_states[self.num_vars + constr] {
+            VarState::Basic(_) => 0.0,
+            VarState::NonBasic(idx) => -self.nb_var_obj_coeffs[idx],
+        };
+        scaled_dual_value / self.var_scale(self.num_vars + constr)
+    }
+
+    /// Find the interval of rhs values of the constraint for which the current basis remains
+    /// optimal along with the basic vars that become infeasible at the interval ends.
+    pub(crate) fn rhs_range(&self, constr: usize) -> RhsRange {
+        let mut res = self.scaled_rhs_range(constr);
+        let scale = self.var_scale(self.num_vars + constr);
+        res.min *= scale;
+        res.max *= scale;
+        res
+    }
+
+    fn scaled_rhs_range(&self, constr: usize) -> RhsRange {
+        let rhs = self.orig_rhs[constr];
+        let slack_var = self.num_vars + constr;
+
+        let mut res = RhsRange {
+            min: f64::NEG_INFINITY,
+            leaving_at_min: None,
+            max: f64::INFINITY,
+            leaving_at_max: None,
+        };
+
+        match self.var_states[slack_var] {
+            VarState::Basic(row) => {
+                // Only the slack var itself changes with the rhs.
+                let val = self.basic_var_vals[row];
+                if self.basic_var_mins[row].is_finite() {
+                    res.min = rhs + self.basic_var_mins[row] - val;
+                    res.leaving_at_min = Some(slack_var);
+                }
+                if self.basic_var_maxs[row].is_finite() {
+                    res.max = rhs + self.basic_var_maxs[row] - val;
+                    res.leaving_at_max = Some(slack_var);
+                }
+            }
+
+            VarState::NonBasic(_) => {
+                // When rhs changes by delta, basic vars change by delta * inv(B) * e_constr.
+                let mut basis_solver = self.basis_solver.clone();
+                let coeffs = basis_solver.solve(std::iter::once((constr, &1.0)));
+                for (r, &coeff) in coeffs.iter() {
+                    if coeff.abs() < EPS {
+                        continue;
+                    }
+
+                    let val = self.basic_var_vals[r];
+                    let to_min = (self.basic_var_mins[r] - val) / coeff;
+                    let to_max = (self.basic_var_maxs[r] - val) / coeff;
+                    let (neg_delta, pos_delta) = if coeff > 0.0 {
+                        (to_min, to_max)
+                    } else {
+                        (to_max, to_min)
+                    };
+
+                    if rhs + neg_delta > res.min {
+                        res.min = rhs + f64::min(neg_delta, 0.0);
+                        res.leaving_at_min = Some(self.basic_vars[r]);
+                    }
+                    if rhs + pos_delta < res.max {
+                        res.max = rhs + f64::max(pos_delta, 0.0);
+                        res.leaving_at_max = Some(self.basic_vars[r]);
+                    }
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Restrict the problem to the optimal face for the current objective. The optimal
+    /// solutions are exactly those in which all non-basic vars with non-zero reduced costs
+    /// stay at their values, so these vars are fixed (and appended to `fixed_vars`).
+    fn fix_optimal_face(&mut self, fixed_vars: &mut Vec<usize>) {
+        for c in 0..self.nb_vars.len() {
+            if !self.nb_var_is_fixed[c] && self.nb_var_obj_coeffs[c].abs() >= EPS {
+                self.nb_var_is_fixed[c] = true;
+                self.nb_var_states[c] = NonBasicVarState {
+                    at_min: true,
+                    at_max: true,
+                };
+                fixed_vars.push(self.nb_vars[c]);
+            }
+        }
+    }
+
+    /// Ranges of values over the optimal face (in the original units) of the problem vars
+    /// followed by the ranges of the left-hand sides of the constraints. Two LPs are solved
+    /// for each var (slack vars included) that is not fixed on the face.
+    pub(crate) fn optimal_face_ranges(&self) -> Vec<(f64, f64)> {
+        assert!(self.is_optimal());
+        let orig_val =
+            |solver: &Solver, var: usize| *solver.get_scaled_value(var) * solver.var_scale(var);
+        let mut ranges: Vec<(f64, f64)> = (0..self.num_total_vars())
+            .map(|var| (orig_val(self, var), orig_val(self, var)))
+            .collect();
+
+        let mut face = self.clone();
+        face.options.objective_cutoff = None;
+        face.options.relative_gap = None;
+        face.fix_optimal_face(&mut vec![]);
+        face.orig_obj_coeffs = vec![0.0; self.num_total_vars()];
+        for (var, range) in ranges.iter_mut().enumerate() {
+            if let VarState::NonBasic(col) = face.var_states[var] {
+                if face.nb_var_is_fixed[col] {
+                    continue;
+                }
+            }
+
+            let mut extreme_val = |coeff: f64| {
+                face.orig_obj_coeffs[var] = coeff;
+                face.recalc_obj_coeffs();
+                face.is_dual_feasible = false;
+                let res = match face.optimize() {
+                    Ok(()) => orig_val(&face, var),
+                    // The face is feasible, so optimization can only fail if the var
+                    // can change its value indefinitely.
+                    Err(_) => -coeff * f64::INFINITY,
+                };
+                face.orig_obj_coeffs[var] = 0.0;
+                res
+            };
+            let (min, max) = (extreme_val(1.0), extreme_val(-1.0));
+            if max - min >= EPS * f64::max(1.0, min.abs()) {
+                *range = (min, max);
+            }
+        }
+
+        // Left-hand side of a constraint is its rhs minus the slack var.
+        for (r, &rhs) in self.orig_rhs.iter().enumerate() {
+            let var = self.num_vars + r;
+            let rhs = rhs * self.var_scale(var);
+            let (min, max) = ranges[var];
+            ranges[var] = (rhs - max, rhs - min);
+        }
+        ranges
+    }
+
+    /// Move to the lexicographically smallest (in the order of the problem vars) vertex
+    /// of the optimal face: among the optimal solutions minimize the first var, then
+    /// the second one and so on. Vars for which the minimum doesn't exist (the face is
+    /// unbounded in their direction) are skipped.
+    fn refine_lexicographic(&mut self) -> Result<(), Error> {
+        // Objective bounds and tolerances refer to the original objective.
+        let cutoff = self.options.objective_cutoff.take();
+        let relative_gap = self.options.relative_gap.take();
+        // The refinement can't be interrupted as it replaces the objective.
+        let iteration_limit = self.iteration_limit.take();
+        let zero_coeffs = vec![0.0; self.num_total_vars()];
+        let obj_coeffs = std::mem::replace(&mut self.orig_obj_coeffs, zero_coeffs);
+        let iterations = self.stats.iterations;
+
+        let mut face_vars = vec![];
+        let mut minimized = None;
+        let mut res = Ok(());
+        for var in 0..self.num_vars {
+            self.fix_optimal_face(&mut face_vars);
+            if self.nb_var_is_fixed.iter().all(|&is_fixed| is_fixed) {
+                // The optimal face is a single vertex.
+                break;
+            }
+            if let VarState::NonBasic(col) = self.var_states[var] {
+                if self.nb_var_is_fixed[col] {
+                    continue;
+                }
+            }
+
+            // Minimizing a var without a lower bound can be unbounded on the optimal face.
+            // In that case the var is skipped and the refinement continues from the state
+            // before the failed solve.
+            let saved = if self.orig_var_mins[var].is_infinite() {
+                Some((self.clone(), minimized))
+            } else {
+                None
+            };
+            if let Some(prev_var) = minimized.replace(var) {
+                self.orig_obj_coeffs[prev_var] = 0.0;
+            }
+            self.orig_obj_coeffs[var] = 1.0;
+            self.recalc_obj_coeffs();
+            self.is_dual_feasible = false;
+            match (self.optimize(), saved) {
+                (Ok(()), _) => {}
+                (Err(err), Some((saved, prev_minimized))) if err.kind() == ErrorKind::Unbounded => {
+                    let stats = self.stats.clone();
+                    let timings = self.timings.clone();
+                    *self = saved;
+                    self.stats = stats;
+                    self.timings = timings;
+                    minimized = prev_minimized;
+                }
+                (Err(err), _) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+        }
+
+        // Release the face and restore the optimality for the original objective (the current
+        // solution is optimal, so only degenerate pivots are possible).
+        for &var in &face_vars {
+            if let VarState::NonBasic(col) = self.var_states[var] {
+                let cur_val = self.nb_var_vals[col];
+                self.nb_var_is_fixed[col] = false;
+                self.nb_var_states[col] = NonBasicVarState {
+                    at_min: cur_val == self.orig_var_mins[var],
+                    at_max: cur_val == self.orig_var_maxs[var],
+                };
+            }
+        }
+        self.orig_obj_coeffs = obj_coeffs;
+        self.recalc_obj_coeffs();
+        self.is_dual_feasible = false;
+        let res = res.and_then(|()| self.optimize());
+        self.options.objective_cutoff = cutoff;
+        self.options.relative_gap = relative_gap;
+        self.iteration_limit = iteration_limit;
+
+        debug!(
+            "lexicographic refinement: {} iterations, vars fixed to the optimal face: {}",
+            self.stats.iterations - iterations,
+            face_vars.len(),
+        );
+        res
+    }
+
+    /// Returns true if some non-basic var has zero reduced cost and can change its value.
+    /// Pivoting on such var will not change the objective value so the optimum is
+    /// (up to degeneracy) not unique.
+    pub(crate) fn has_alternative_optima(&self) -> bool {
+        self.is_optimal()
+            && (0..self.nb_vars.len()).any(|c| !self.zero_cost_directions(c).is_empty())
+    }
+
+    /// Directions (true means increasing) in which the non-basic var can change its value
+    /// without changing the objective value.
+    fn zero_cost_directions(&self, c: usize) -> Vec<bool> {
+        let state = &self.nb_var_states[c];
+        if self.nb_var_is_fixed[c] || self.nb_var_obj_coeffs[c].abs() >= EPS {
+            return vec![];
+        }
+
+        let mut res = vec![];
+        if !state.at_max {
+            res.push(true);
+        }
+        if !state.at_min {
+            res.push(false);
+        }
+        res
+    }
+
+    /// Find up to `max_count` optimal vertices (differing from the current one in the values
+    /// of the problem vars) by pivoting on columns with zero reduced costs.
+    pub(crate) fn find_alternative_optima(&self, max_count: usize) -> Vec<Solver> {
+        if !self.is_optimal() {
+            return vec![];
+        }
+
+        let is_same_vertex = |lhs: &Solver, rhs: &Solver| {
+            (0..self.num_vars).all(|v| (lhs.get_value(v) - rhs.get_value(v)).abs() < EPS)
+        };
+
+        let mut found: Vec<Solver> = vec![];
+        let mut queue = std::collections::VecDeque::new();
+        queue.push_back(self.clone());
+        while let Some(cur) = queue.pop_front() {
+            for c in 0..cur.nb_vars.len() {
+                for entering_diff_sign in cur.zero_cost_directions(c) {
+                    if found.len() >= max_count {
+                        return found;
+                    }
+
+                    let mut next = cur.clone();
+                    let pivot_info = match next.choose_leaving_row(c, entering_diff_sign) {
+                        Ok(pivot_info) => pivot_info,
+                        // Ray of optimal solutions, there are no vertices in this direction.
+                        Err(_) => continue,
+                    };
+                    next.pivot(&pivot_info);
+                    next.refresh_unscaled_vals();
+
+                    if is_same_vertex(&next, self)
+                        || found.iter().any(|other| is_same_vertex(&next, other))
+                    {
+                        continue;
+                    }
+
+                    debug!("found alternative optimal vertex #{}", found.len() + 1);
+                    found.push(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        found
+    }
+
     pub(crate) fn fix_var(&mut self, var: usize, val: f64) -> Result<(), Error> {
-        if val < self.orig_var_mins[var] || val > self.orig_var_maxs[var] {
-            return Err(Error::Infeasible);
+        self.fix_vars(&[(var, val)])
+    }
+
+    /// Fix all vars and only then restore feasibility, so that the basis changes at most
+    /// once per var plus the dual simplex iterations needed for the whole batch.
+    pub(crate) fn fix_vars(&mut self, fixings: &[(usize, f64)]) -> Result<(), Error> {
+        for &(var, val) in fixings {
+            self.fix_var_without_restore(var, val)?;
+        }
+        self.is_primal_feasible = false;
+        self.reoptimize()?;
+        self.refresh_unscaled_vals();
+        Ok(())
+    }
+
+    fn fix_var_without_restore(&mut self, var: usize, val: f64) -> Result<(), Error> {
+        let val = val / self.var_scale(var);
+        let (min, max) = (self.orig_var_mins[var], self.orig_var_maxs[var]);
+        if val < min || val > max {
+            let violation = f64::max(min - val, val - max);
+            let err = Error::infeasible(Phase::Setup);
+            return Err(self.error_at(err, var, Some(violation)));
         }
 
         let col = match self.var_states[var] {
             VarState::Basic(row) => {
                 // if var was basic, remove it.
                 self.calc_row_coeffs(row);
-                let pivot_info = self.choose_entering_col_dual(row, val)?;
+                let pivot_info = match self.choose_entering_col_dual(row, val) {
+                    Ok(pivot_info) => pivot_info,
+                    // The var is already at the value, so it can leave the basis in either
+                    // direction (a fixed non-basic var is dual feasible for any obj. coeff).
+                    Err(_) if f64::abs(val - self.basic_var_vals[row]) < EPS => {
+                        let leaving_diff_sign = val <= self.basic_var_vals[row];
+                        self.choose_entering_col_dual_in_dir(row, val, leaving_diff_sign)?
+                    }
+                    Err(err) => return Err(err),
+                };
                 self.calc_col_coeffs(pivot_info.col);
+                self.stats.dual_iterations += 1;
                 self.pivot(&pivot_info);
                 pivot_info.col
             }
@@ -409,132 +1032,910 @@ impl Solver {
             at_max: true,
         };
         self.nb_var_is_fixed[col] = true;
-
-        self.is_primal_feasible = false;
-        self.restore_feasibility()
+        Ok(())
     }
 
     /// Return true if the var was really unset.
     pub(crate) fn unfix_var(&mut self, var: usize) -> bool {
-        if let VarState::NonBasic(col) = self.var_states[var] {
-            if !std::mem::replace(&mut self.nb_var_is_fixed[col], false) {
-                return false;
-            }
+        self.unfix_vars(&[var]) == 1
+    }
 
-            let cur_val = self.nb_var_vals[col];
-            self.nb_var_states[col] = NonBasicVarState {
-                at_min: cur_val == self.orig_var_mins[var],
-                at_max: cur_val == self.orig_var_maxs[var],
-            };
+    /// Unfix all vars and reoptimize once. Return the number of vars that were really unset.
+    pub(crate) fn unfix_vars(&mut self, vars: &[usize]) -> usize {
+        let mut num_unfixed = 0;
+        for &var in vars {
+            if let VarState::NonBasic(col) = self.var_states[var] {
+                if !std::mem::replace(&mut self.nb_var_is_fixed[col], false) {
+                    continue;
+                }
+
+                let cur_val = self.nb_var_vals[col];
+                self.nb_var_states[col] = NonBasicVarState {
+                    at_min: cur_val == self.orig_var_mins[var],
+                    at_max: cur_val == self.orig_var_maxs[var],
+                };
+                num_unfixed += 1;
+            }
+        }
 
-            // Shouldn't result in error, presumably problem was solvable before this variable
-            // was fixed.
+        if num_unfixed > 0 {
+            // Shouldn't result in error, presumably problem was solvable before these
+            // variables were fixed.
             self.is_dual_feasible = false;
+            // Unfixing only relaxes the problem and can't fail, so the cutoff is not applied.
+            let cutoff = self.options.objective_cutoff.take();
             self.optimize().unwrap();
-            true
-        } else {
-            false
+            self.options.objective_cutoff = cutoff;
+            self.refresh_unscaled_vals();
         }
+        num_unfixed
     }
 
     pub(crate) fn add_gomory_cut(&mut self, var: usize) -> Result<(), Error> {
         if let VarState::Basic(row) = self.var_states[var] {
             self.calc_row_coeffs(row);
 
+            // The cut is derived from the tableau row in the original units (integrality
+            // is not preserved by scaling) and then expressed in the scaled vars.
+            let basic_scale = self.var_scale(var);
             let mut cut_coeffs = SparseVec::new();
             for (col, &coeff) in self.row_coeffs.iter() {
                 let var = self.nb_vars[col];
-                cut_coeffs.push(var, coeff.floor() - coeff);
+                let scale = self.var_scale(var);
+                let coeff = coeff * basic_scale / scale;
+                cut_coeffs.push(var, (coeff.floor() - coeff) * scale);
             }
 
-            let cut_bound = self.basic_var_vals[row].floor() - self.basic_var_vals[row];
+            let val = self.basic_var_vals[row] * basic_scale;
+            let cut_bound = val.floor() - val;
             let num_total_vars = self.num_total_vars();
-            self.add_constraint(
+            self.add_scaled_constraint(
                 cut_coeffs.into_csvec(num_total_vars),
                 ComparisonOp::Le,
                 cut_bound,
-            )
+            )?;
+            self.refresh_unscaled_vals();
+            self.log_event("gomory_cut", &[("var", LogValue::Int(var))]);
+            Ok(())
         } else {
             panic!("var {:?} is not basic!", var);
         }
     }
 
-    pub(crate) fn num_constraints(&self) -> usize {
-        self.orig_constraints.rows()
-    }
-
-    fn num_total_vars(&self) -> usize {
-        self.num_vars + self.num_constraints()
-    }
+    /// Generate a Gomory mixed-integer cut from the tableau row of the basic var.
+    /// Returns the cut as (coeffs of problem vars, rhs) meaning coeffs * x >= rhs or None
+    /// if the cut is numerically unsafe. The cut is valid for the current var bounds.
+    pub(crate) fn gmi_cut(
+        &self,
+        var: usize,
+        is_integer: impl Fn(usize) -> bool,
+    ) -> Option<(Vec<(usize, f64)>, f64)> {
+        let row = match self.var_states[var] {
+            VarState::Basic(row) => row,
+            VarState::NonBasic(_) => return None,
+        };
 
-    pub(crate) fn initial_solve(&mut self) -> Result<(), Error> {
-        if !self.is_primal_feasible {
-            self.restore_feasibility()?;
+        // Integrality is not preserved by scaling, so everything is computed in the
+        // original units.
+        let basic_scale = self.var_scale(var);
+        let val = self.basic_var_vals[row] * basic_scale;
+        let f0 = val - val.floor();
+        if f0.min(1.0 - f0) < GMI_MIN_FRACTIONALITY {
+            return None;
         }
 
-        if !self.is_dual_feasible {
-            self.recalc_obj_coeffs();
-            self.optimize()?;
+        let mut basis_solver = self.basis_solver.clone();
+        let inv_basis_row = basis_solver.solve_transp(std::iter::once((row, &1.0)));
+        let mut row_coeffs = vec![0.0; self.nb_vars.len()];
+        for (r, &coeff) in inv_basis_row.iter() {
+            for (v, &val) in self.orig_constraints.outer_view(r).unwrap().iter() {
+                if let VarState::NonBasic(idx) = self.var_states[v] {
+                    row_coeffs[idx] += val * coeff;
+                }
+            }
         }
 
-        // Disable updates of primal sq. norms, because lengthy primal simplex runs
-        // are unlikely after the initial solve.
-        self.enable_primal_steepest_edge = false;
+        // The tableau row is x_B + sum(a_j * x'_j) = val where x'_j >= 0 are the distances
+        // of the non-basic vars from their current bounds. The GMI cut is
+        // sum(g_j * x'_j) >= 1, it is then expressed in terms of the problem vars.
+        let mut cut_coeffs = vec![0.0; self.num_vars];
+        let mut cut_rhs = 1.0;
+        for (c, &coeff) in row_coeffs.iter().enumerate() {
+            let state = &self.nb_var_states[c];
+            if coeff.abs() < EPS || self.nb_var_is_fixed[c] || (state.at_min && state.at_max) {
+                continue;
+            }
 
-        Ok(())
-    }
+            let nb_var = self.nb_vars[c];
+            let scale = self.var_scale(nb_var);
+            let (sign, bound) = if state.at_min {
+                (1.0, self.orig_var_mins[nb_var] * scale)
+            } else if state.at_max {
+                (-1.0, self.orig_var_maxs[nb_var] * scale)
+            } else {
+                // Free non-basic var, its distance from the bound is not defined.
+                return None;
+            };
+            let a = sign * coeff * basic_scale / scale;
 
-    fn optimize(&mut self) -> Result<(), Error> {
-        for iter in 0.. {
-            if iter % 1000 == 0 {
-                let (num_vars, infeasibility) = self.calc_dual_infeasibility();
-                debug!(
-                    "optimize iter {}: obj.: {}, non-optimal coeffs: {} ({})",
-                    iter, self.cur_obj_val, num_vars, infeasibility,
-                );
-            }
+            let g = if nb_var < self.num_vars && is_integer(nb_var) {
+                let f = a - a.floor();
+                if f <= f0 {
+                    f / f0
+                } else {
+                    (1.0 - f) / (1.0 - f0)
+                }
+            } else if a >= 0.0 {
+                a / f0
+            } else {
+                -a / (1.0 - f0)
+            };
 
-            if let Some(pivot_info) = self.choose_pivot()? {
-                self.pivot(&pivot_info);
+            // g * x' = g * sign * (x_j - bound)
+            let g = g * sign;
+            cut_rhs += g * bound;
+            if nb_var < self.num_vars {
+                cut_coeffs[nb_var] += g;
             } else {
-                debug!(
-                    "found optimum in {} iterations, obj.: {}",
-                    iter + 1,
-                    self.cur_obj_val,
-                );
-                break;
+                // Slack var is b_i - a_i * x.
+                let constr = nb_var - self.num_vars;
+                cut_rhs -= g * self.orig_rhs[constr] * scale;
+                for (v, &val) in self.orig_constraints.outer_view(constr).unwrap().iter() {
+                    if v < self.num_vars {
+                        cut_coeffs[v] -= g * val * scale / self.var_scale(v);
+                    }
+                }
             }
         }
 
-        self.is_dual_feasible = true;
-        Ok(())
-    }
+        // Remove tiny coeffs, relaxing the rhs using the var bounds.
+        let mut res = vec![];
+        for (v, &coeff) in cut_coeffs.iter().enumerate() {
+            if coeff.abs() >= EPS {
+                res.push((v, coeff));
+            } else if coeff != 0.0 {
+                let scale = self.var_scale(v);
+                let bound = if coeff > 0.0 {
+                    self.orig_var_maxs[v]
+                } else {
+                    self.orig_var_mins[v]
+                };
+                if bound.is_infinite() {
+                    return None;
+                }
+                cut_rhs -= coeff * bound * scale;
+            }
+        }
 
-    fn restore_feasibility(&mut self) -> Result<(), Error> {
-        let obj_str = if self.is_dual_feasible {
-            "obj."
-        } else {
-            "artificial obj."
-        };
+        let min = res
+            .iter()
+            .fold(f64::INFINITY, |min, (_, c)| f64::min(min, c.abs()));
+        let max = res.iter().fold(0.0, |max, (_, c)| f64::max(max, c.abs()));
+        if res.is_empty() || max > GMI_MAX_DYNAMISM * min {
+            return None;
+        }
+        Some((res, cut_rhs))
+    }
 
-        for iter in 0.. {
-            if iter % 1000 == 0 {
-                let (num_vars, infeasibility) = self.calc_primal_infeasibility();
-                debug!(
-                    "restore feasibility iter {}: {}: {}, infeas. vars: {} ({})",
-                    iter, obj_str, self.cur_obj_val, num_vars, infeasibility,
-                );
+    /// Number of constraints that can become violated when the var decreases and
+    /// when it increases (the down- and up-locks of the var).
+    pub(crate) fn var_locks(&self, var: usize) -> (usize, usize) {
+        let mut down_locks = 0;
+        let mut up_locks = 0;
+        let col = self.orig_constraints_csc.outer_view(var).unwrap();
+        for (r, &coeff) in col.iter() {
+            let slack_var = self.num_vars + r;
+            if slack_var == var || coeff == 0.0 {
+                continue;
             }
-
-            if let Some((row, leaving_new_val)) = self.choose_pivot_row_dual() {
-                self.calc_row_coeffs(row);
-                let pivot_info = self.choose_entering_col_dual(row, leaving_new_val)?;
-                self.calc_col_coeffs(pivot_info.col);
-                self.pivot(&pivot_info);
+            // Slack var changes in the direction opposite to the sign of coeff * var change.
+            let slack_min_locks = self.orig_var_mins[slack_var].is_finite();
+            let slack_max_locks = self.orig_var_maxs[slack_var].is_finite();
+            let (locks_up, locks_down) = if coeff > 0.0 {
+                (slack_min_locks, slack_max_locks)
             } else {
-                debug!(
-                    "restored feasibility in {} iterations, {}: {}",
-                    iter + 1,
+                (slack_max_locks, slack_min_locks)
+            };
+            down_locks += locks_down as usize;
+            up_locks += locks_up as usize;
+        }
+        (down_locks, up_locks)
+    }
+
+    pub(crate) fn timings(&self) -> SolverTimings {
+        let basis_timings = &self.basis_solver.timings;
+        SolverTimings {
+            ordering: basis_timings.ordering,
+            factorization: basis_timings.factorization,
+            ftran: basis_timings.ftran,
+            btran: basis_timings.btran,
+            ..self.timings.clone()
+        }
+    }
+
+    pub(crate) fn lu_factors(&self) -> &LUFactors {
+        &self.basis_solver.lu_factors
+    }
+
+    pub(crate) fn num_constraints(&self) -> usize {
+        self.orig_constraints.rows()
+    }
+
+    fn num_total_vars(&self) -> usize {
+        self.num_vars + self.num_constraints()
+    }
+
+    pub(crate) fn initial_solve(&mut self) -> Result<(), Error> {
+        trace_span!(
+            "initial_solve",
+            num_vars = self.num_vars,
+            num_constraints = self.num_constraints()
+        );
+        if self.progress.is_none() {
+            self.progress = self.options.progress.clone().map(ProgressTracker::new);
+        }
+        let res = self.initial_solve_impl();
+        if res.is_ok() && self.is_interrupted {
+            // To be continued, the progress is kept.
+            return res;
+        }
+        if let Some(mut progress) = self.progress.take() {
+            if res.is_ok() {
+                progress.finish(Phase::PrimalSimplex, self.stats.iterations);
+            }
+        }
+        match &res {
+            Ok(()) if !self.is_optimal() => self.log_event(
+                "finish",
+                &[
+                    ("status", LogValue::Str("near_optimal")),
+                    ("objective", LogValue::Float(self.cur_obj_val)),
+                    ("relative_gap", LogValue::Float(self.relative_gap())),
+                ],
+            ),
+            Ok(()) => self.log_event(
+                "finish",
+                &[
+                    ("status", LogValue::Str("optimal")),
+                    ("objective", LogValue::Float(self.cur_obj_val)),
+                ],
+            ),
+            Err(err) => {
+                let status = match err.kind() {
+                    ErrorKind::Infeasible => "infeasible",
+                    ErrorKind::Unbounded => "unbounded",
+                    ErrorKind::Cutoff => "cutoff",
+                };
+                self.log_event("finish", &[("status", LogValue::Str(status))]);
+            }
+        }
+        res
+    }
+
+    /// Continue the initial solve for at most `max_iterations` iterations (a few more are
+    /// possible with the multiple pricing). Returns true if the solve is finished.
+    pub(crate) fn initial_solve_step(&mut self, max_iterations: usize) -> Result<bool, Error> {
+        self.iteration_limit = Some(self.stats.iterations + max_iterations);
+        let res = self.initial_solve();
+        self.iteration_limit = None;
+        res.map(|()| !std::mem::take(&mut self.is_interrupted))
+    }
+
+    /// Stop the interrupted initial solve. The current solution is cleaned up (if it is
+    /// feasible) and can be used as an approximate one. Returns the best known lower bound
+    /// on the optimal objective value (if it is finite).
+    pub(crate) fn stop_initial_solve(&mut self) -> Option<f64> {
+        self.progress = None;
+        self.enable_primal_steepest_edge = false;
+        if self.is_primal_feasible && self.reset_expand_tol().is_err() {
+            self.is_primal_feasible = false;
+        }
+
+        // The Lagrangian bound is valid for any basis, the objective value only if
+        // the dual simplex is running on the true objective.
+        let mut bound = self.objective_bound();
+        if self.is_dual_feasible {
+            bound = f64::max(bound, self.cur_obj_val);
+        }
+        if self.is_primal_feasible {
+            self.relative_gap =
+                f64::max(self.cur_obj_val - bound, 0.0) / f64::max(1.0, self.cur_obj_val.abs());
+            self.refresh_unscaled_vals();
+        }
+        self.log_event(
+            "finish",
+            &[
+                ("status", LogValue::Str("stopped")),
+                ("bound", LogValue::Float(bound)),
+            ],
+        );
+        Some(bound).filter(|bound| bound.is_finite())
+    }
+
+    /// Check the iteration limit. Simplex loops return as soon as this returns true
+    /// and are resumed from the same basis when called again.
+    fn is_iteration_limit_reached(&mut self) -> bool {
+        if self
+            .iteration_limit
+            .is_some_and(|limit| self.stats.iterations >= limit)
+        {
+            self.is_interrupted = true;
+        }
+        self.is_interrupted
+    }
+
+    fn initial_solve_impl(&mut self) -> Result<(), Error> {
+        if !self.is_primal_feasible {
+            let is_primal_phase1 = match self.is_primal_phase1 {
+                Some(is_primal_phase1) => is_primal_phase1,
+                None => !self.is_dual_feasible && self.prefer_primal(),
+            };
+            self.is_primal_phase1 = Some(is_primal_phase1);
+            if is_primal_phase1 {
+                self.restore_feasibility_primal()?;
+            } else {
+                self.restore_feasibility()?;
+            }
+            if self.is_interrupted {
+                return Ok(());
+            }
+        }
+
+        if !self.is_dual_feasible {
+            if !std::mem::replace(&mut self.is_optimizing, true) {
+                self.recalc_obj_coeffs();
+            }
+            self.optimize()?;
+            if self.is_interrupted {
+                return Ok(());
+            }
+        } else {
+            // The starting basis can be optimal without any iterations.
+            self.check_cutoff(self.cur_obj_val, Phase::PrimalSimplex)?;
+        }
+
+        self.push_superbasic_vars();
+
+        if self.options.lexicographic_optimum && self.is_optimal() {
+            self.refine_lexicographic()?;
+        }
+
+        // Disable updates of primal sq. norms, because lengthy primal simplex runs
+        // are unlikely after the initial solve.
+        self.enable_primal_steepest_edge = false;
+
+        self.refresh_unscaled_vals();
+
+        Ok(())
+    }
+
+    /// Report progress to the callback (if it is enabled) every PROGRESS_INTERVAL iterations
+    /// and write sampled iterations to the solve log (if it is enabled). `stage` is 0 while
+    /// searching for a feasible solution and 1 while optimizing.
+    fn report_iteration(&mut self, iter: usize, phase: Phase, stage: usize) {
+        if self.progress.is_some() && iter.is_multiple_of(PROGRESS_INTERVAL) {
+            let (_, infeasibility) = if stage == 0 {
+                self.calc_primal_infeasibility()
+            } else {
+                self.calc_dual_infeasibility()
+            };
+            let iterations = self.stats.iterations;
+            if let Some(progress) = &mut self.progress {
+                progress.report(phase, stage, iterations, infeasibility);
+            }
+        }
+
+        if let Some(log) = &self.options.solve_log {
+            if log.samples_iteration(iter) {
+                let (_, primal_infeasibility) = self.calc_primal_infeasibility();
+                let (_, dual_infeasibility) = self.calc_dual_infeasibility();
+                log.event(
+                    "iteration",
+                    self.stats.iterations,
+                    &[
+                        ("phase", LogValue::Str(&phase.to_string())),
+                        ("objective", LogValue::Float(self.cur_obj_val)),
+                        (
+                            "primal_infeasibility",
+                            LogValue::Float(primal_infeasibility),
+                        ),
+                        ("dual_infeasibility", LogValue::Float(dual_infeasibility)),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Write an event to the solve log (if it is enabled).
+    pub(crate) fn log_event(&self, event: &str, fields: &[(&str, LogValue)]) {
+        if let Some(log) = &self.options.solve_log {
+            log.event(event, self.stats.iterations, fields);
+        }
+    }
+
+    /// Factorize the basis matrix from scratch.
+    fn refactorize(&mut self, trigger: RefactorTrigger) {
+        let num_updates = self.basis_solver.eta_matrices.len();
+        trace_span!(
+            "refactorize",
+            trigger = trigger.as_str(),
+            updates = num_updates
+        );
+        self.basis_solver
+            .reset(&self.orig_constraints_csc, &self.basic_vars);
+
+        let count = match trigger {
+            RefactorTrigger::Updates => &mut self.stats.update_refactorizations,
+            RefactorTrigger::Recalc => &mut self.stats.recalc_refactorizations,
+            RefactorTrigger::Constraint => &mut self.stats.constraint_refactorizations,
+        };
+        *count += 1;
+        let growth = pivot_growth(
+            &self.orig_constraints_csc,
+            &self.basic_vars,
+            &self.basis_solver.lu_factors,
+        );
+        self.stats.max_pivot_growth = self.stats.max_pivot_growth.max(growth);
+
+        self.log_event(
+            "refactorization",
+            &[
+                ("trigger", LogValue::Str(trigger.as_str())),
+                ("updates", LogValue::Int(num_updates)),
+                ("lu_nnz", LogValue::Int(self.basis_solver.lu_factors.nnz())),
+                ("pivot_growth", LogValue::Float(growth)),
+            ],
+        );
+    }
+
+    fn optimize(&mut self) -> Result<(), Error> {
+        for iter in 0.. {
+            if self.is_iteration_limit_reached() {
+                return Ok(());
+            }
+            self.report_iteration(iter, Phase::PrimalSimplex, 1);
+            if iter % 1000 == 0 {
+                let (num_vars, infeasibility) = self.calc_dual_infeasibility();
+                debug!(
+                    "optimize iter {}: obj.: {}, non-optimal coeffs: {} ({})",
+                    iter, self.cur_obj_val, num_vars, infeasibility,
+                );
+            }
+
+            if self.expand_tol >= EPS {
+                self.reset_expand_tol()?;
+            }
+
+            let needs_bound =
+                self.options.relative_gap.is_some() || self.options.objective_cutoff.is_some();
+            if needs_bound
+                && iter.is_multiple_of(GAP_CHECK_INTERVAL)
+                && (0..self.nb_vars.len()).any(|c| self.is_entering_candidate(c))
+            {
+                let bound = self.objective_bound();
+                self.check_cutoff(bound, Phase::PrimalSimplex)?;
+                self.relative_gap =
+                    f64::max(self.cur_obj_val - bound, 0.0) / f64::max(1.0, self.cur_obj_val.abs());
+                if self
+                    .options
+                    .relative_gap
+                    .is_some_and(|gap_tol| self.relative_gap <= gap_tol)
+                {
+                    if self.reset_expand_tol()? {
+                        continue;
+                    }
+                    debug!(
+                        "stopped at relative gap {} in {} iterations, obj.: {}",
+                        self.relative_gap, iter, self.cur_obj_val,
+                    );
+                    // The solution is feasible, but not dual feasible.
+                    return Ok(());
+                }
+            }
+
+            let num_pivots = if self.options.pricing == Pricing::Multiple
+                && !(self.is_stalling() && self.options.anti_degeneracy == AntiDegeneracy::Bland)
+            {
+                self.multiple_pricing_iteration()?
+            } else if let Some(pivot_info) = self.choose_pivot()? {
+                self.stats.primal_iterations += 1;
+                self.pivot(&pivot_info);
+                1
+            } else {
+                0
+            };
+
+            if num_pivots > 0 {
+                if self.options.anti_degeneracy == AntiDegeneracy::Expand {
+                    self.expand_tol += EXPAND_TOL_INCREMENT * num_pivots as f64;
+                }
+            } else {
+                if self.reset_expand_tol()? {
+                    continue;
+                }
+
+                debug!(
+                    "found optimum in {} iterations, obj.: {}",
+                    iter + 1,
+                    self.cur_obj_val,
+                );
+                self.check_cutoff(self.cur_obj_val, Phase::PrimalSimplex)?;
+                break;
+            }
+        }
+
+        self.is_dual_feasible = true;
+        Ok(())
+    }
+
+    /// Whether the current solution is optimal. It is not if the primal simplex was stopped
+    /// early because the relative gap tolerance was reached.
+    pub(crate) fn is_optimal(&self) -> bool {
+        self.is_primal_feasible && self.is_dual_feasible
+    }
+
+    pub(crate) fn is_primal_feasible(&self) -> bool {
+        self.is_primal_feasible
+    }
+
+    /// Bounds of the vars tightened by the activity-based bound propagation.
+    fn calc_implied_bounds(&self) -> Vec<(f64, f64)> {
+        let rows: Vec<Row> = self
+            .orig_constraints
+            .outer_iterator()
+            .zip(&self.orig_rhs)
+            .map(|(row, &rhs)| Row::new(row.iter().map(|(v, &c)| (v, c)).collect(), rhs, rhs))
+            .collect();
+        let orig_bounds: Vec<(f64, f64)> = self
+            .orig_var_mins
+            .iter()
+            .copied()
+            .zip(self.orig_var_maxs.iter().copied())
+            .collect();
+        let mut bounds = orig_bounds.clone();
+        let is_integer = vec![false; bounds.len()];
+        if probing::propagate(&rows, &is_integer, &mut bounds) {
+            bounds
+        } else {
+            orig_bounds
+        }
+    }
+
+    /// Lower bound on the optimal objective value given by the Lagrangian relaxation
+    /// of the constraints with the current simplex multipliers over the box of the implied
+    /// var bounds. Multipliers of the wrong sign (the ones that make the bound infinite
+    /// because the slack var is unbounded in that direction) are replaced by zero.
+    fn objective_bound(&mut self) -> f64 {
+        if self.implied_bounds.len() < self.num_total_vars() {
+            self.implied_bounds = self.calc_implied_bounds();
+        }
+
+        let basic_obj_coeffs: Vec<(usize, f64)> = self
+            .basic_vars
+            .iter()
+            .enumerate()
+            .map(|(r, &var)| (r, self.orig_obj_coeffs[var]))
+            .filter(|&(_, coeff)| coeff != 0.0)
+            .collect();
+        let num_constraints = self.num_constraints();
+        let mut multipliers = vec![0.0; num_constraints];
+        for (r, &val) in self
+            .basis_solver
+            .solve_transp(basic_obj_coeffs.iter().map(|(r, coeff)| (*r, coeff)))
+            .iter()
+        {
+            multipliers[r] = val;
+        }
+        let multipliers: Vec<f64> = (0..num_constraints)
+            .map(|r| {
+                let (slack_min, slack_max) = self.implied_bounds[self.num_vars + r];
+                let mut multiplier = multipliers[r];
+                if slack_max == f64::INFINITY {
+                    multiplier = f64::min(multiplier, 0.0);
+                }
+                if slack_min == f64::NEG_INFINITY {
+                    multiplier = f64::max(multiplier, 0.0);
+                }
+                multiplier
+            })
+            .collect();
+
+        let mut terms: Vec<f64> = multipliers
+            .iter()
+            .zip(&self.orig_rhs)
+            .map(|(multiplier, rhs)| multiplier * rhs)
+            .collect();
+        for var in 0..self.num_total_vars() {
+            let col = self.orig_constraints_csc.outer_view(var).unwrap();
+            let dot_prod = summation::sum(
+                self.options.accuracy,
+                col.iter().map(|(r, val)| val * multipliers[r]),
+            );
+            let obj_coeff = self.orig_obj_coeffs[var] - dot_prod;
+            let (min, max) = match self.var_states[var] {
+                VarState::NonBasic(c) if self.nb_var_is_fixed[c] => {
+                    (self.nb_var_vals[c], self.nb_var_vals[c])
+                }
+                _ => self.implied_bounds[var],
+            };
+            if obj_coeff > 0.0 {
+                terms.push(obj_coeff * min);
+            } else if obj_coeff < 0.0 {
+                terms.push(obj_coeff * max);
+            }
+        }
+        summation::sum(self.options.accuracy, terms.into_iter())
+    }
+
+    /// Return the cutoff error if the objective value can't be better than the cutoff
+    /// because `bound` is a lower bound on it.
+    fn check_cutoff(&self, bound: f64, phase: Phase) -> Result<(), Error> {
+        match self.options.objective_cutoff {
+            Some(cutoff) if bound >= cutoff => {
+                debug!("objective bound {} reached the cutoff {}", bound, cutoff);
+                Err(Error::cutoff(phase))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the objective cutoff (in the internal units: minimization form without
+    /// the constant term of the objective).
+    pub(crate) fn set_objective_cutoff(&mut self, cutoff: Option<f64>) {
+        self.options.objective_cutoff = cutoff;
+    }
+
+    /// Relative gap at which the primal simplex was stopped, 0 if the solution is optimal.
+    pub(crate) fn relative_gap(&self) -> f64 {
+        if self.is_optimal() {
+            0.0
+        } else {
+            self.relative_gap
+        }
+    }
+
+    /// Restore feasibility after the problem was changed. If the solution was optimal,
+    /// the dual simplex is used, otherwise the primal simplex is restarted.
+    fn reoptimize(&mut self) -> Result<(), Error> {
+        trace_span!("reoptimize", iteration = self.stats.iterations);
+        if self.is_dual_feasible {
+            self.restore_feasibility()
+        } else {
+            self.restore_feasibility_primal()?;
+            self.optimize()
+        }
+    }
+
+    /// Reset the EXPAND tolerance and clean up the solution: recalculate basic vars
+    /// (they can be infeasible by up to the current tolerance) and restore feasibility
+    /// if necessary. Returns true if the basis has changed.
+    fn reset_expand_tol(&mut self) -> Result<bool, Error> {
+        if self.expand_tol == EXPAND_TOL_INITIAL {
+            return Ok(false);
+        }
+        self.expand_tol = EXPAND_TOL_INITIAL;
+
+        self.recalc_basic_var_vals();
+        let (num_vars, infeasibility) = self.calc_primal_infeasibility();
+        if num_vars == 0 {
+            return Ok(false);
+        }
+
+        debug!(
+            "EXPAND reset: infeas. vars: {} ({}), cleaning up",
+            num_vars, infeasibility
+        );
+        // The clean-up is not resumable, so it can't be interrupted.
+        let iteration_limit = self.iteration_limit.take();
+        let res = self.restore_feasibility_primal();
+        self.iteration_limit = iteration_limit;
+        res?;
+        Ok(true)
+    }
+
+    /// Move non-basic vars that are strictly inside their bounds (this is possible only if
+    /// the solver was started from a user-supplied point) to the bounds or into the basis,
+    /// so that the solution is a vertex. Free vars are left as is.
+    fn push_superbasic_vars(&mut self) {
+        let mut num_pushed = 0;
+        for c in 0..self.nb_vars.len() {
+            let var = self.nb_vars[c];
+            let state = &self.nb_var_states[c];
+            if state.at_min
+                || state.at_max
+                || (self.orig_var_mins[var].is_infinite() && self.orig_var_maxs[var].is_infinite())
+            {
+                continue;
+            }
+
+            // Prefer the direction in which the objective doesn't increase.
+            let preferred_sign = self.nb_var_obj_coeffs[c] < 0.0;
+            for &entering_diff_sign in &[preferred_sign, !preferred_sign] {
+                if let Ok(pivot_info) = self.choose_leaving_row(c, entering_diff_sign) {
+                    self.pivot(&pivot_info);
+                    num_pushed += 1;
+                    break;
+                }
+            }
+        }
+
+        if num_pushed > 0 {
+            debug!(
+                "pushed {} superbasic vars, obj.: {}",
+                num_pushed, self.cur_obj_val
+            );
+        }
+    }
+
+    /// Whether to use the primal simplex to find the first feasible solution when the
+    /// starting basis is neither primal nor dual feasible.
+    fn prefer_primal(&self) -> bool {
+        match self.options.algorithm {
+            Algorithm::Primal => true,
+            Algorithm::Dual => false,
+            Algorithm::Auto => {
+                let (num_infeasible, _) = self.calc_primal_infeasibility();
+                self.is_warm_started
+                    || num_infeasible * PRIMAL_MAX_INFEASIBLE_RATIO <= self.num_constraints()
+            }
+        }
+    }
+
+    /// Primal simplex phase 1: minimize the sum of infeasibilities of the basic vars.
+    fn restore_feasibility_primal(&mut self) -> Result<(), Error> {
+        for iter in 0.. {
+            if self.is_iteration_limit_reached() {
+                return Ok(());
+            }
+            self.report_iteration(iter, Phase::PrimalSimplex, 0);
+            // Gradient of the sum of infeasibilities w.r.t. basic vars.
+            let mut infeasibility_grad = vec![];
+            for (r, ((&val, &min), &max)) in self
+                .basic_var_vals
+                .iter()
+                .zip(&self.basic_var_mins)
+                .zip(&self.basic_var_maxs)
+                .enumerate()
+            {
+                if val < min - EPS {
+                    infeasibility_grad.push((r, -1.0));
+                } else if val > max + EPS {
+                    infeasibility_grad.push((r, 1.0));
+                }
+            }
+
+            if iter % 1000 == 0 || infeasibility_grad.is_empty() {
+                let (num_vars, infeasibility) = self.calc_primal_infeasibility();
+                debug!(
+                    "primal phase 1 iter {}: infeas. vars: {} ({})",
+                    iter, num_vars, infeasibility,
+                );
+            }
+            if infeasibility_grad.is_empty() {
+                break;
+            }
+
+            // Reduced costs of the non-basic vars for the phase 1 objective. They are stored
+            // in place of the objective coeffs and recalculated after the phase is finished.
+            let multipliers = self
+                .basis_solver
+                .solve_transp(infeasibility_grad.iter().map(|(r, coeff)| (*r, coeff)));
+            for (c, &var) in self.nb_vars.iter().enumerate() {
+                let col = self.orig_constraints_csc.outer_view(var).unwrap();
+                self.nb_var_obj_coeffs[c] = -col
+                    .iter()
+                    .map(|(r, val)| val * multipliers.get(r))
+                    .sum::<f64>();
+            }
+
+            let entering_c = if self.is_stalling() {
+                (0..self.nb_vars.len())
+                    .filter(|&c| self.is_entering_candidate(c))
+                    .min_by_key(|&c| self.nb_vars[c])
+            } else {
+                self.choose_best_entering_col(0..self.nb_vars.len())
+            };
+            let entering_c = match entering_c {
+                Some(c) => c,
+                None => {
+                    // Report the most infeasible basic var.
+                    let mut worst = (0, 0.0);
+                    for r in 0..self.basic_vars.len() {
+                        let val = self.basic_var_vals[r];
+                        let violation =
+                            f64::max(self.basic_var_mins[r] - val, val - self.basic_var_maxs[r]);
+                        if violation > worst.1 {
+                            worst = (r, violation);
+                        }
+                    }
+                    let (r, violation) = worst;
+                    let err = Error::infeasible(Phase::PrimalSimplex);
+                    return Err(self.error_at(err, self.basic_vars[r], Some(violation)));
+                }
+            };
+
+            // Infeasible basic vars are allowed to move freely away from the violated bound
+            // and leave the basis when they reach it. This way the sum of infeasibilities
+            // doesn't increase.
+            for &(r, coeff) in &infeasibility_grad {
+                if coeff < 0.0 {
+                    self.basic_var_maxs[r] = self.basic_var_mins[r];
+                    self.basic_var_mins[r] = f64::NEG_INFINITY;
+                } else {
+                    self.basic_var_mins[r] = self.basic_var_maxs[r];
+                    self.basic_var_maxs[r] = f64::INFINITY;
+                }
+            }
+            let entering_diff_sign = self.nb_var_obj_coeffs[entering_c] < 0.0;
+            let pivot_info = self.choose_leaving_row(entering_c, entering_diff_sign);
+            for &(r, _) in &infeasibility_grad {
+                let var = self.basic_vars[r];
+                self.basic_var_mins[r] = self.orig_var_mins[var];
+                self.basic_var_maxs[r] = self.orig_var_maxs[var];
+            }
+
+            // The sum of infeasibilities is bounded below, so an entering var with a nonzero
+            // reduced cost is always limited by some row or by its own bound.
+            let pivot_info = match pivot_info {
+                Ok(pivot_info) => pivot_info,
+                Err(err) if err.kind() == ErrorKind::Unbounded => panic!(
+                    "primal phase 1: no leaving row for entering var {}",
+                    self.nb_vars[entering_c]
+                ),
+                Err(err) => return Err(err),
+            };
+            self.stats.primal_iterations += 1;
+            self.pivot(&pivot_info);
+        }
+
+        self.is_primal_feasible = true;
+        self.is_dual_feasible = false;
+        self.recalc_obj_coeffs();
+        Ok(())
+    }
+
+    fn restore_feasibility(&mut self) -> Result<(), Error> {
+        let obj_str = if self.is_dual_feasible {
+            "obj."
+        } else {
+            "artificial obj."
+        };
+
+        for iter in 0.. {
+            if self.is_iteration_limit_reached() {
+                return Ok(());
+            }
+            self.report_iteration(iter, Phase::DualSimplex, 0);
+            if self.is_dual_feasible {
+                // The objective value of a dual feasible solution is a lower bound
+                // on the optimal one and it only grows during the dual simplex.
+                self.check_cutoff(self.cur_obj_val, Phase::DualSimplex)?;
+            }
+            if iter % 1000 == 0 {
+                let (num_vars, infeasibility) = self.calc_primal_infeasibility();
+                debug!(
+                    "restore feasibility iter {}: {}: {}, infeas. vars: {} ({})",
+                    iter, obj_str, self.cur_obj_val, num_vars, infeasibility,
+                );
+            }
+
+            let start = Instant::now();
+            let pivot_row = self.choose_pivot_row_dual();
+            self.timings.pricing += start.elapsed();
+
+            if let Some((row, leaving_new_val)) = pivot_row {
+                self.calc_row_coeffs(row);
+                let start = Instant::now();
+                let pivot_info = self.choose_entering_col_dual(row, leaving_new_val);
+                self.timings.ratio_test += start.elapsed();
+                let pivot_info = pivot_info?;
+                self.calc_col_coeffs(pivot_info.col);
+                self.stats.dual_iterations += 1;
+                self.pivot(&pivot_info);
+            } else {
+                debug!(
+                    "restored feasibility in {} iterations, {}: {}",
+                    iter + 1,
                     obj_str,
                     self.cur_obj_val,
                 );
@@ -546,14 +1947,63 @@ impl Solver {
         Ok(())
     }
 
+    /// Replace the right-hand sides of all constraints (in the original units). The basis
+    /// stays dual feasible, so optimality is restored with the dual simplex.
+    pub(crate) fn set_rhs(&mut self, rhs: &[f64]) -> Result<(), Error> {
+        assert_eq!(rhs.len(), self.num_constraints());
+        for (r, &val) in rhs.iter().enumerate() {
+            let row_scale = self.scaling.as_ref().map_or(1.0, |s| s.row_scale(r));
+            self.orig_rhs[r] = val * row_scale;
+        }
+        self.recalc_basic_var_vals();
+        self.recalc_obj_coeffs();
+        self.is_primal_feasible = false;
+        self.reoptimize()?;
+        self.refresh_unscaled_vals();
+        self.log_event("rhs", &[]);
+        Ok(())
+    }
+
+    /// Replace the objective coeffs of the problem vars (in the original units). The basis
+    /// stays primal feasible, so optimality is restored with the primal simplex.
+    pub(crate) fn set_obj_coeffs(&mut self, obj_coeffs: &[f64]) -> Result<(), Error> {
+        assert_eq!(obj_coeffs.len(), self.num_vars);
+        for (v, &coeff) in obj_coeffs.iter().enumerate() {
+            self.orig_obj_coeffs[v] = coeff * self.var_scale(v);
+        }
+        trace_span!("reoptimize", iteration = self.stats.iterations);
+        self.recalc_obj_coeffs();
+        self.is_dual_feasible = false;
+        self.optimize()?;
+        self.refresh_unscaled_vals();
+        self.log_event("objective", &[]);
+        Ok(())
+    }
+
+    /// Add a constraint on the problem vars (coeffs are in the original units).
     pub(crate) fn add_constraint(
+        &mut self,
+        coeffs: CsVec,
+        cmp_op: ComparisonOp,
+        rhs: f64,
+    ) -> Result<(), Error> {
+        let coeffs = match &self.scaling {
+            Some(scaling) => scaling.scale_coeffs(&coeffs, 1.0),
+            None => coeffs,
+        };
+        self.add_scaled_constraint(coeffs, cmp_op, rhs)?;
+        self.refresh_unscaled_vals();
+        self.log_event("constraint", &[]);
+        Ok(())
+    }
+
+    fn add_scaled_constraint(
         &mut self,
         mut coeffs: CsVec,
         cmp_op: ComparisonOp,
         rhs: f64,
     ) -> Result<(), Error> {
         assert!(self.is_primal_feasible);
-        assert!(self.is_dual_feasible);
 
         if coeffs.indices().is_empty() {
             let is_tautological = match cmp_op {
@@ -565,7 +2015,9 @@ impl Solver {
             if is_tautological {
                 return Ok(());
             } else {
-                return Err(Error::Infeasible);
+                return Err(Error::infeasible(Phase::Setup)
+                    .with_constraint(self.num_constraints())
+                    .with_violation(rhs.abs()));
             }
         }
 
@@ -609,8 +2061,7 @@ impl Solver {
         self.orig_constraints = new_orig_constraints;
         self.orig_constraints_csc = self.orig_constraints.to_csc();
 
-        self.basis_solver
-            .reset(&self.orig_constraints_csc, &self.basic_vars);
+        self.refactorize(RefactorTrigger::Constraint);
 
         if self.enable_primal_steepest_edge || self.enable_dual_steepest_edge {
             // existing tableau rows didn't change, so we calc the last row
@@ -630,7 +2081,7 @@ impl Solver {
         }
 
         self.is_primal_feasible = false;
-        self.restore_feasibility()
+        self.reoptimize()
     }
 
     /// Number of infeasible basic vars and sum of their infeasibilities.
@@ -659,7 +2110,7 @@ impl Solver {
         let mut num_vars = 0;
         let mut infeasibility = 0.0;
         for (&obj_coeff, var_state) in self.nb_var_obj_coeffs.iter().zip(&self.nb_var_states) {
-            if !(var_state.at_min && obj_coeff > -EPS) && !(var_state.at_max && obj_coeff < EPS) {
+            if (!var_state.at_min || obj_coeff <= -EPS) && (!var_state.at_max || obj_coeff >= EPS) {
                 num_vars += 1;
                 infeasibility += obj_coeff.abs();
             }
@@ -674,6 +2125,53 @@ impl Solver {
         self.basis_solver
             .solve(orig_col.iter())
             .to_sparse_vec(&mut self.col_coeffs);
+        if self.options.accuracy == Accuracy::DoubleDouble {
+            self.refine_col_coeffs(var);
+        }
+    }
+
+    /// One step of iterative refinement of the col coeffs x (the solution of B x = a) with
+    /// the residual a - B x computed in double-double precision.
+    fn refine_col_coeffs(&mut self, var: usize) {
+        let mut residuals = vec![summation::DoubleDouble::default(); self.num_constraints()];
+        for (r, &val) in self.orig_constraints_csc.outer_view(var).unwrap().iter() {
+            residuals[r].add(val);
+        }
+        for (r, &coeff) in self.col_coeffs.iter() {
+            let basic_col = self.orig_constraints_csc.outer_view(self.basic_vars[r]);
+            for (i, &val) in basic_col.unwrap().iter() {
+                residuals[i].add_prod(-val, coeff);
+            }
+        }
+        let residuals: Vec<(usize, f64)> = residuals
+            .iter()
+            .enumerate()
+            .map(|(r, res)| (r, res.value()))
+            .filter(|&(_, res)| res != 0.0)
+            .collect();
+        if residuals.is_empty() {
+            return;
+        }
+
+        let mut is_in_col = vec![false; self.num_constraints()];
+        let correction = self
+            .basis_solver
+            .solve(residuals.iter().map(|(r, res)| (*r, res)));
+        let mut refined = SparseVec::new();
+        for (r, &coeff) in self.col_coeffs.iter() {
+            is_in_col[r] = true;
+            let val = coeff + *correction.get(r);
+            if val != 0.0 {
+                refined.push(r, val);
+            }
+        }
+        // The correction can have nonzeros where the original solution had none.
+        for (r, &val) in correction.iter() {
+            if !is_in_col[r] && val != 0.0 {
+                refined.push(r, val);
+            }
+        }
+        self.col_coeffs = refined;
     }
 
     /// Calculate current coeffs row for a single constraint (permuted according to nb_vars).
@@ -693,61 +2191,292 @@ impl Solver {
     }
 
     fn choose_pivot(&mut self) -> Result<Option<PivotInfo>, Error> {
-        let entering_c = {
-            let filtered_obj_coeffs = self
-                .nb_var_obj_coeffs
+        let start = Instant::now();
+        let entering_c =
+            if self.is_stalling() && self.options.anti_degeneracy == AntiDegeneracy::Bland {
+                // Bland's rule: choose the eligible var with the smallest index.
+                (0..self.nb_vars.len())
+                    .filter(|&c| self.is_entering_candidate(c))
+                    .min_by_key(|&c| self.nb_vars[c])
+            } else {
+                match self.options.pricing {
+                    Pricing::Full | Pricing::Multiple => self.choose_entering_col_full(),
+                    Pricing::Partial => self.choose_entering_col_partial(),
+                }
+            };
+        self.timings.pricing += start.elapsed();
+
+        let entering_c = if let Some(col) = entering_c {
+            col
+        } else {
+            return Ok(None);
+        };
+
+        // If true, entering variable will increase (because the objective function must decrease).
+        let entering_diff_sign = self.nb_var_obj_coeffs[entering_c] < 0.0;
+        self.choose_leaving_row(entering_c, entering_diff_sign)
+            .map(Some)
+    }
+
+    /// Major iteration of the multiple pricing: choose several attractive entering candidates
+    /// and perform a sequence of minor iterations among them. During the minor iterations
+    /// only the columns and obj. coeffs of the candidates are updated (so that BTRAN needed
+    /// to calculate the pivot row is avoided). Obj. coeffs of all non-basic vars are
+    /// recalculated at the end. Returns the number of performed pivots.
+    fn multiple_pricing_iteration(&mut self) -> Result<usize, Error> {
+        // Candidates with the biggest obj. coeffs (primal steepest edge weights are not
+        // maintained during minor iterations).
+        let mut candidates = vec![];
+        for col in 0..self.nb_vars.len() {
+            if !self.is_entering_candidate(col) {
+                continue;
+            }
+            let score = self.nb_var_obj_coeffs[col].abs();
+            let pos = candidates
                 .iter()
-                .zip(&self.nb_var_states)
-                .enumerate()
-                .filter_map(|(col, (&obj_coeff, var_state))| {
-                    // Choose only among non-basic vars that can be changed
-                    // with objective decreasing.
-                    if (var_state.at_min && obj_coeff > -EPS)
-                        || (var_state.at_max && obj_coeff < EPS)
-                    {
-                        None
-                    } else {
-                        Some((col, obj_coeff))
-                    }
-                });
+                .position(|&(_, other_score)| score > other_score)
+                .unwrap_or(candidates.len());
+            if pos < MULTIPLE_PRICING_CANDIDATES {
+                candidates.insert(pos, (col, score));
+                candidates.truncate(MULTIPLE_PRICING_CANDIDATES);
+            }
+        }
+        if candidates.is_empty() {
+            return Ok(0);
+        }
 
-            let mut best_col = None;
-            let mut best_score = f64::NEG_INFINITY;
-            if self.enable_primal_steepest_edge {
-                for (col, obj_coeff) in filtered_obj_coeffs {
-                    let score = obj_coeff * obj_coeff / self.primal_edge_sq_norms[col];
-                    if score > best_score {
-                        best_col = Some(col);
-                        best_score = score;
-                    }
+        // Dense columns of the candidates in terms of the current basis.
+        let mut candidates: Vec<(usize, Vec<f64>)> = candidates
+            .into_iter()
+            .map(|(col, _)| {
+                self.calc_col_coeffs(col);
+                let mut coeffs = vec![0.0; self.num_constraints()];
+                for (r, &coeff) in self.col_coeffs.iter() {
+                    coeffs[r] = coeff;
                 }
+                (col, coeffs)
+            })
+            .collect();
+
+        let mut num_pivots = 0;
+        loop {
+            let best = candidates
+                .iter()
+                .enumerate()
+                .filter(|(_, (col, _))| self.is_entering_candidate(*col))
+                .max_by(|(_, (col1, _)), (_, (col2, _))| {
+                    let score1 = self.nb_var_obj_coeffs[*col1].abs();
+                    let score2 = self.nb_var_obj_coeffs[*col2].abs();
+                    score1.partial_cmp(&score2).unwrap()
+                })
+                .map(|(i, _)| i);
+            let i = if let Some(i) = best {
+                i
             } else {
-                for (col, obj_coeff) in filtered_obj_coeffs {
-                    let score = obj_coeff.abs();
-                    if score > best_score {
-                        best_col = Some(col);
-                        best_score = score;
+                break;
+            };
+
+            let entering_c = candidates[i].0;
+            self.col_coeffs.clear();
+            for (r, &coeff) in candidates[i].1.iter().enumerate() {
+                if coeff != 0.0 {
+                    self.col_coeffs.push(r, coeff);
+                }
+            }
+
+            let entering_obj_coeff = self.nb_var_obj_coeffs[entering_c];
+            let start = Instant::now();
+            let pivot_info = self.primal_ratio_test(entering_c, entering_obj_coeff < 0.0);
+            self.timings.ratio_test += start.elapsed();
+            let pivot_info = pivot_info?;
+
+            let obj_diff = entering_obj_coeff * pivot_info.entering_diff;
+            self.cur_obj_val += obj_diff;
+            self.update_degeneracy_stats(obj_diff);
+
+            if let Some(pivot_elem) = &pivot_info.elem {
+                // Update the remaining candidates to the new basis.
+                let (_, pivot_col) = candidates.swap_remove(i);
+                for (col, coeffs) in &mut candidates {
+                    let ratio = coeffs[pivot_elem.row] / pivot_elem.coeff;
+                    if ratio == 0.0 {
+                        continue;
+                    }
+                    for (coeff, &pivot_col_coeff) in coeffs.iter_mut().zip(&pivot_col) {
+                        *coeff -= ratio * pivot_col_coeff;
                     }
+                    coeffs[pivot_elem.row] = ratio;
+                    self.nb_var_obj_coeffs[*col] -= entering_obj_coeff * ratio;
                 }
+                self.nb_var_obj_coeffs[entering_c] = -entering_obj_coeff / pivot_elem.coeff;
             }
 
-            if let Some(col) = best_col {
-                col
-            } else {
-                return Ok(None);
+            self.update_basis(&pivot_info);
+            self.stats.primal_iterations += 1;
+            num_pivots += 1;
+        }
+
+        self.recalc_nb_obj_coeffs();
+        if self.enable_dual_steepest_edge {
+            // Dual weights were not updated, reset them to the initial reference framework.
+            for sq_norm in &mut self.dual_edge_sq_norms {
+                *sq_norm = 1.0;
             }
-        };
+        }
+
+        Ok(num_pivots)
+    }
+
+    /// Recalculate obj. coeffs of non-basic vars using the current basis factorization
+    /// (unlike recalc_obj_coeffs, doesn't refactorize the basis).
+    fn recalc_nb_obj_coeffs(&mut self) {
+        let basic_obj_coeffs: Vec<(usize, f64)> = self
+            .basic_vars
+            .iter()
+            .enumerate()
+            .map(|(r, &var)| (r, self.orig_obj_coeffs[var]))
+            .filter(|&(_, coeff)| coeff != 0.0)
+            .collect();
+        let multipliers = self
+            .basis_solver
+            .solve_transp(basic_obj_coeffs.iter().map(|(r, coeff)| (*r, coeff)));
+
+        for (c, &var) in self.nb_vars.iter().enumerate() {
+            let col = self.orig_constraints_csc.outer_view(var).unwrap();
+            let dot_prod = summation::sum(
+                self.options.accuracy,
+                col.iter().map(|(r, val)| val * multipliers.get(r)),
+            );
+            self.nb_var_obj_coeffs[c] = self.orig_obj_coeffs[var] - dot_prod;
+        }
+    }
+
+    /// Whether the non-basic var can be changed with objective decreasing.
+    fn is_entering_candidate(&self, c: usize) -> bool {
+        let obj_coeff = self.nb_var_obj_coeffs[c];
+        let var_state = &self.nb_var_states[c];
+        if var_state.at_min || var_state.at_max {
+            !((var_state.at_min && obj_coeff > -EPS) || (var_state.at_max && obj_coeff < EPS))
+        } else {
+            // Free and superbasic vars can move in both directions, but with a zero
+            // reduced cost neither of them decreases the objective.
+            obj_coeff.abs() >= EPS
+        }
+    }
+
+    fn pricing_score(&self, c: usize) -> f64 {
+        let obj_coeff = self.nb_var_obj_coeffs[c];
+        if self.enable_primal_steepest_edge {
+            obj_coeff * obj_coeff / self.primal_edge_sq_norms[c]
+        } else {
+            obj_coeff.abs()
+        }
+    }
+
+    /// Choose the entering var with the best pricing score among the specified columns.
+    fn choose_best_entering_col(&mut self, cols: impl Iterator<Item = usize>) -> Option<usize> {
+        let mut best = MaxScore::new();
+        for col in cols {
+            if self.is_entering_candidate(col) {
+                best.update(col, self.pricing_score(col), self.rng.as_mut());
+            }
+        }
+        best.idx
+    }
+
+    /// Choose the entering var among all columns. The scan is split between threads
+    /// (unless random tie-breaking is enabled). The best candidates of the chunks are
+    /// reduced in the chunk order with ties broken by index, so the result is the same
+    /// as that of the sequential scan regardless of the number of threads.
+    fn choose_entering_col_full(&mut self) -> Option<usize> {
+        let num_cols = self.nb_vars.len();
+        if self.options.threads <= 1 || self.rng.is_some() {
+            return self.choose_best_entering_col(0..num_cols);
+        }
+
+        let solver = &*self;
+        let chunks = parallel::map_chunks(self.options.threads, num_cols, |cols| {
+            let mut best = MaxScore::new();
+            for col in cols {
+                if solver.is_entering_candidate(col) {
+                    best.update(col, solver.pricing_score(col), None);
+                }
+            }
+            best
+        });
+        let mut best = MaxScore::new();
+        for chunk in chunks {
+            if let Some(col) = chunk.idx {
+                best.update(col, chunk.score, None);
+            }
+        }
+        best.idx
+    }
+
+    /// Partial pricing: choose the entering var from the candidate list. When the list runs
+    /// dry, it is refilled by scanning the next segments of columns in a round-robin fashion.
+    fn choose_entering_col_partial(&mut self) -> Option<usize> {
+        let mut candidates = std::mem::take(&mut self.pricing_candidates);
+        candidates.retain(|&c| self.is_entering_candidate(c));
+
+        let num_cols = self.nb_vars.len();
+        let segment_len = usize::max(
+            PARTIAL_PRICING_MIN_SEGMENT,
+            num_cols.div_ceil(PARTIAL_PRICING_SEGMENTS),
+        );
+        let mut num_scanned = 0;
+        while candidates.is_empty() && num_scanned < num_cols {
+            let len = usize::min(segment_len, num_cols - num_scanned);
+            for i in 0..len {
+                let col = (self.pricing_start + i) % num_cols;
+                if self.is_entering_candidate(col) {
+                    candidates.push(col);
+                }
+            }
+            self.pricing_start = (self.pricing_start + len) % num_cols;
+            num_scanned += len;
+        }
+
+        let best_col = self.choose_best_entering_col(candidates.iter().copied());
+        self.pricing_candidates = candidates;
+        best_col
+    }
+
+    /// Find the pivot for the entering column, changing in the direction given by
+    /// `entering_diff_sign` (true means increasing) and calculate the pivot row and column.
+    fn choose_leaving_row(
+        &mut self,
+        entering_c: usize,
+        entering_diff_sign: bool,
+    ) -> Result<PivotInfo, Error> {
+        self.calc_col_coeffs(entering_c);
+        let start = Instant::now();
+        let pivot_info = self.primal_ratio_test(entering_c, entering_diff_sign);
+        self.timings.ratio_test += start.elapsed();
+        let pivot_info = pivot_info?;
+        if let Some(elem) = &pivot_info.elem {
+            self.calc_row_coeffs(elem.row);
+        }
+        Ok(pivot_info)
+    }
 
+    /// Primal ratio test. Column coeffs of the entering var must be already calculated.
+    fn primal_ratio_test(
+        &mut self,
+        entering_c: usize,
+        entering_diff_sign: bool,
+    ) -> Result<PivotInfo, Error> {
         let entering_cur_val = self.nb_var_vals[entering_c];
-        // If true, entering variable will increase (because the objective function must decrease).
-        let entering_diff_sign = self.nb_var_obj_coeffs[entering_c] < 0.0;
         let entering_other_val = if entering_diff_sign {
             self.orig_var_maxs[self.nb_vars[entering_c]]
         } else {
             self.orig_var_mins[self.nb_vars[entering_c]]
         };
 
-        self.calc_col_coeffs(entering_c);
+        let num_rejected = (self.col_coeffs.values().iter())
+            .filter(|&&coeff| coeff != 0.0 && coeff.abs() < EPS)
+            .count();
+        self.stats.rejected_pivots += num_rejected;
 
         let get_leaving_var_step = |r: usize, coeff: f64| -> f64 {
             let val = self.basic_var_vals[r];
@@ -779,6 +2508,10 @@ impl Solver {
 
         // First, we determine the max change in entering variable so that basic variables
         // remain feasible using relaxed bounds.
+        let feasibility_tol = match self.options.anti_degeneracy {
+            AntiDegeneracy::Bland => EPS,
+            AntiDegeneracy::Expand => self.expand_tol,
+        };
         let mut max_step = (entering_other_val - entering_cur_val).abs();
         for (r, &coeff) in self.col_coeffs.iter() {
             let coeff_abs = coeff.abs();
@@ -788,12 +2521,18 @@ impl Solver {
 
             // By which amount can we change the entering variable so that the limit on this
             // basic var is not violated. The var with the minimum such amount becomes leaving.
-            let cur_step = (get_leaving_var_step(r, coeff) + EPS) / coeff_abs;
+            let cur_step = (get_leaving_var_step(r, coeff) + feasibility_tol) / coeff_abs;
             if cur_step < max_step {
                 max_step = cur_step;
             }
         }
 
+        if max_step.is_infinite() {
+            // Neither the entering variable nor any basic variable limits the step.
+            let err = Error::unbounded(Phase::PrimalSimplex);
+            return Err(self.error_at(err, self.nb_vars[entering_c], None));
+        }
+
         // Second, we choose among variables with steps less than max_step a variable with the biggest
         // abs. coefficient as the leaving variable. This means that we get numerically more stable
         // basis at the price of slight infeasibility of some basic variables.
@@ -823,12 +2562,21 @@ impl Solver {
         }
 
         if let Some(row) = leaving_r {
-            self.calc_row_coeffs(row);
-
-            let entering_diff = (self.basic_var_vals[row] - leaving_new_val) / pivot_coeff;
+            let mut entering_diff = (self.basic_var_vals[row] - leaving_new_val) / pivot_coeff;
+            if self.options.anti_degeneracy == AntiDegeneracy::Expand {
+                // EXPAND guarantees a small positive step in the right direction. The leaving
+                // variable is still set exactly to its bound, basic vars are allowed
+                // to become slightly infeasible.
+                let min_step = EXPAND_TOL_INCREMENT / pivot_coeff.abs();
+                if entering_diff_sign {
+                    entering_diff = f64::max(entering_diff, min_step);
+                } else {
+                    entering_diff = f64::min(entering_diff, -min_step);
+                }
+            }
             let entering_new_val = entering_cur_val + entering_diff;
 
-            Ok(Some(PivotInfo {
+            Ok(PivotInfo {
                 col: entering_c,
                 entering_new_val,
                 entering_diff,
@@ -837,22 +2585,23 @@ impl Solver {
                     coeff: pivot_coeff,
                     leaving_new_val,
                 }),
-            }))
+            })
         } else {
             if entering_other_val.is_infinite() {
-                return Err(Error::Unbounded);
+                let err = Error::unbounded(Phase::PrimalSimplex);
+                return Err(self.error_at(err, self.nb_vars[entering_c], None));
             }
 
-            Ok(Some(PivotInfo {
+            Ok(PivotInfo {
                 col: entering_c,
                 entering_new_val: entering_other_val,
                 entering_diff: entering_other_val - entering_cur_val,
                 elem: None,
-            }))
+            })
         }
     }
 
-    fn choose_pivot_row_dual(&self) -> Option<(usize, f64)> {
+    fn choose_pivot_row_dual(&mut self) -> Option<(usize, f64)> {
         let infeasibilities = self
             .basic_var_vals
             .iter()
@@ -869,25 +2618,24 @@ impl Solver {
                 }
             });
 
-        let mut leaving_r = None;
-        let mut max_score = f64::NEG_INFINITY;
-        if self.enable_dual_steepest_edge {
-            for (r, infeasibility) in infeasibilities {
-                let sq_norm = self.dual_edge_sq_norms[r];
-                let score = infeasibility * infeasibility / sq_norm;
-                if score > max_score {
-                    leaving_r = Some(r);
-                    max_score = score;
-                }
-            }
+        let leaving_r = if self.is_stalling() {
+            // Bland's rule: choose the infeasible basic var with the smallest index.
+            infeasibilities
+                .map(|(r, _)| r)
+                .min_by_key(|&r| self.basic_vars[r])
         } else {
+            let infeasibilities: Vec<_> = infeasibilities.collect();
+            let mut best = MaxScore::new();
             for (r, infeasibility) in infeasibilities {
-                if infeasibility > max_score {
-                    leaving_r = Some(r);
-                    max_score = infeasibility;
-                }
+                let score = if self.enable_dual_steepest_edge {
+                    infeasibility * infeasibility / self.dual_edge_sq_norms[r]
+                } else {
+                    infeasibility
+                };
+                best.update(r, score, self.rng.as_mut());
             }
-        }
+            best.idx
+        };
 
         leaving_r.map(|r| {
             let val = self.basic_var_vals[r];
@@ -917,13 +2665,21 @@ impl Solver {
     }
 
     fn choose_entering_col_dual(
-        &self,
+        &mut self,
         row: usize,
         leaving_new_val: f64,
     ) -> Result<PivotInfo, Error> {
         // True if the new obj. coeff. must be nonnegative in a dual-feasible configuration.
         let leaving_diff_sign = leaving_new_val > self.basic_var_vals[row];
+        self.choose_entering_col_dual_in_dir(row, leaving_new_val, leaving_diff_sign)
+    }
 
+    fn choose_entering_col_dual_in_dir(
+        &mut self,
+        row: usize,
+        leaving_new_val: f64,
+        leaving_diff_sign: bool,
+    ) -> Result<PivotInfo, Error> {
         fn clamp_obj_coeff(mut obj_coeff: f64, var_state: &NonBasicVarState) -> f64 {
             if var_state.at_min && obj_coeff < 0.0 {
                 obj_coeff = 0.0;
@@ -950,48 +2706,95 @@ impl Solver {
             }
         };
 
-        // Harris rule. See e.g.
+        // Candidates for entering: (column, pivot coeff, step) where step is the change in the
+        // leaving variable obj. coeff at which the obj. coeff of the candidate crosses zero.
+        let mut candidates = vec![];
+        for (c, &coeff) in self.row_coeffs.iter() {
+            if coeff != 0.0 && coeff.abs() < EPS {
+                self.stats.rejected_pivots += 1;
+                continue;
+            }
+
+            let var_state = &self.nb_var_states[c];
+            if !is_eligible_var(coeff, var_state) {
+                continue;
+            }
+
+            let obj_coeff = clamp_obj_coeff(self.nb_var_obj_coeffs[c], var_state);
+            let step = obj_coeff.abs() / coeff.abs();
+            // A NaN obj. coeff (after a numerical breakdown) can't bound the step.
+            if !step.is_nan() {
+                candidates.push((c, coeff, step));
+            }
+        }
+        candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+        // Bound-flipping (long-step) ratio test. See e.g.
+        // Koberstein, A. (2005). The dual simplex method, techniques for a fast and stable
+        // implementation (PhD thesis), section 3.2.
+        //
+        // The dual objective grows with the slope equal to the primal infeasibility of the
+        // leaving variable. Each time the step passes a candidate, the candidate's obj. coeff
+        // changes sign, so it must be flipped to its other bound and the slope decreases by
+        // |coeff| * (max - min). We pass the candidates while the slope remains positive.
+        let mut slope = (self.basic_var_vals[row] - leaving_new_val).abs();
+        let mut flipped = vec![];
+
+        // Candidates are processed in groups determined by the Harris rule. See e.g.
         // Gill, P. E., Murray, W., Saunders, M. A., & Wright, M. H. (1989).
         // A practical anti-cycling procedure for linearly constrained optimization.
         // Mathematical Programming, 45(1-3), 437-474.
         //
         // https://link.springer.com/content/pdf/10.1007/BF01589114.pdf
-
-        // First, we determine the max step (change in the leaving variable obj. coeff that still
-        // leaves us with a dual-feasible state) using relaxed bounds.
-        let mut max_step = f64::INFINITY;
-        for (c, &coeff) in self.row_coeffs.iter() {
-            let var_state = &self.nb_var_states[c];
-            if !is_eligible_var(coeff, var_state) {
-                continue;
+        let mut entering_c = None;
+        let mut pivot_coeff = 0.0;
+        let mut group_start = 0;
+        while group_start < candidates.len() {
+            // First, we determine the max step (change in the leaving variable obj. coeff
+            // that still leaves us with a dual-feasible state) using relaxed bounds.
+            let mut max_step = f64::INFINITY;
+            for &(c, coeff, _) in &candidates[group_start..] {
+                let obj_coeff = clamp_obj_coeff(self.nb_var_obj_coeffs[c], &self.nb_var_states[c]);
+                let cur_step = (obj_coeff.abs() + EPS) / coeff.abs();
+                if cur_step < max_step {
+                    max_step = cur_step;
+                }
             }
 
-            let obj_coeff = clamp_obj_coeff(self.nb_var_obj_coeffs[c], var_state);
-            let cur_step = (obj_coeff.abs() + EPS) / coeff.abs();
-            if cur_step < max_step {
-                max_step = cur_step;
-            }
-        }
+            let group_end = group_start
+                + candidates[group_start..]
+                    .iter()
+                    .take_while(|&&(_, _, step)| step <= max_step)
+                    .count();
+            let group = &candidates[group_start..group_end];
 
-        // Second, we choose among the variables satisfying the relaxed step bound
-        // the one with the biggest pivot coefficient. This allows for a much more
-        // numerically stable basis at the price of slight infeasibility in dual variables.
-        let mut entering_c = None;
-        let mut pivot_coeff_abs = f64::NEG_INFINITY;
-        let mut pivot_coeff = 0.0;
-        for (c, &coeff) in self.row_coeffs.iter() {
-            let var_state = &self.nb_var_states[c];
-            if !is_eligible_var(coeff, var_state) {
+            let slope_diff: f64 = group
+                .iter()
+                .map(|&(c, coeff, _)| {
+                    let var_state = &self.nb_var_states[c];
+                    if var_state.at_min || var_state.at_max {
+                        let var = self.nb_vars[c];
+                        coeff.abs() * (self.orig_var_maxs[var] - self.orig_var_mins[var])
+                    } else {
+                        f64::INFINITY
+                    }
+                })
+                .sum();
+
+            // Flip only if the leaving variable remains infeasible after that, otherwise
+            // the candidates of the group can enter the basis.
+            if slope - slope_diff > EPS {
+                slope -= slope_diff;
+                flipped.extend(group.iter().map(|&(c, _, _)| c));
+                group_start = group_end;
                 continue;
             }
 
-            let obj_coeff = clamp_obj_coeff(self.nb_var_obj_coeffs[c], var_state);
-
-            // If we change obj. coeff of the leaving variable by this amount,
-            // obj. coeff if the current variable will reach the bound of dual infeasibility.
-            // Variable with the tightest such bound is the entering variable.
-            let cur_step = obj_coeff.abs() / coeff.abs();
-            if cur_step <= max_step {
+            // Second, we choose among the variables satisfying the relaxed step bound
+            // the one with the biggest pivot coefficient. This allows for a much more
+            // numerically stable basis at the price of slight infeasibility in dual variables.
+            let mut pivot_coeff_abs = f64::NEG_INFINITY;
+            for &(c, coeff, _) in group {
                 let coeff_abs = coeff.abs();
                 if coeff_abs > pivot_coeff_abs {
                     entering_c = Some(c);
@@ -999,9 +2802,12 @@ impl Solver {
                     pivot_coeff = coeff;
                 }
             }
+            break;
         }
 
         if let Some(col) = entering_c {
+            self.flip_nb_vars(&flipped);
+
             let entering_diff = (self.basic_var_vals[row] - leaving_new_val) / pivot_coeff;
             let entering_new_val = self.nb_var_vals[col] + entering_diff;
 
@@ -1016,16 +2822,82 @@ impl Solver {
                 }),
             })
         } else {
-            Err(Error::Infeasible)
+            let violation = f64::abs(leaving_new_val - self.basic_var_vals[row]);
+            let err = Error::infeasible(Phase::DualSimplex);
+            Err(self.error_at(err, self.basic_vars[row], Some(violation)))
+        }
+    }
+
+    /// Move non-basic vars to their other bounds.
+    fn flip_nb_vars(&mut self, cols: &[usize]) {
+        if cols.is_empty() {
+            return;
+        }
+
+        let mut rhs_diff = vec![0.0; self.num_constraints()];
+        for &c in cols {
+            let var = self.nb_vars[c];
+            let new_val = if self.nb_var_states[c].at_min {
+                self.orig_var_maxs[var]
+            } else {
+                self.orig_var_mins[var]
+            };
+            let diff = new_val - self.nb_var_vals[c];
+            for (r, &coeff) in self.orig_constraints_csc.outer_view(var).unwrap().iter() {
+                rhs_diff[r] += diff * coeff;
+            }
+            self.cur_obj_val += diff * self.nb_var_obj_coeffs[c];
+            self.nb_var_vals[c] = new_val;
+            self.nb_var_states[c] = NonBasicVarState {
+                at_min: new_val == self.orig_var_mins[var],
+                at_max: new_val == self.orig_var_maxs[var],
+            };
         }
+
+        let basic_vals_diff = self
+            .basis_solver
+            .solve(rhs_diff.iter().enumerate().filter(|(_, &val)| val != 0.0));
+        for (r, &diff) in basic_vals_diff.iter() {
+            self.basic_var_vals[r] -= diff;
+        }
+        self.stats.bound_flips += cols.len();
     }
 
     fn pivot(&mut self, pivot_info: &PivotInfo) {
         // TODO: periodically (say, every 1000 pivots) recalc basic vars and object coeffs
         // from scratch for numerical stability.
 
-        self.cur_obj_val += self.nb_var_obj_coeffs[pivot_info.col] * pivot_info.entering_diff;
+        let obj_diff = self.nb_var_obj_coeffs[pivot_info.col] * pivot_info.entering_diff;
+        self.cur_obj_val += obj_diff;
+        self.update_degeneracy_stats(obj_diff);
+
+        if let Some(pivot_elem) = &pivot_info.elem {
+            let pivot_coeff = pivot_elem.coeff;
+
+            if self.enable_dual_steepest_edge {
+                self.update_dual_sq_norms(pivot_elem.row, pivot_coeff);
+            }
+
+            let pivot_obj = self.nb_var_obj_coeffs[pivot_info.col] / pivot_coeff;
+            for (c, &coeff) in self.row_coeffs.iter() {
+                if c == pivot_info.col {
+                    self.nb_var_obj_coeffs[c] = -pivot_obj;
+                } else {
+                    self.nb_var_obj_coeffs[c] -= pivot_obj * coeff;
+                }
+            }
+
+            if self.enable_primal_steepest_edge {
+                self.update_primal_sq_norms(pivot_info.col, pivot_coeff);
+            }
+        }
+
+        self.update_basis(pivot_info);
+    }
 
+    /// Update variable values and the basis. Column coeffs of the entering var
+    /// must be already calculated.
+    fn update_basis(&mut self, pivot_info: &PivotInfo) {
         let entering_var = self.nb_vars[pivot_info.col];
 
         if pivot_info.elem.is_none() {
@@ -1043,6 +2915,8 @@ impl Solver {
 
         let pivot_elem = pivot_info.elem.as_ref().unwrap();
         let pivot_coeff = pivot_elem.coeff;
+        let pivot_abs = pivot_coeff.abs();
+        self.stats.min_pivot = Some(self.stats.min_pivot.map_or(pivot_abs, |p| p.min(pivot_abs)));
 
         // Update basic vars stuff
 
@@ -1057,10 +2931,6 @@ impl Solver {
         self.basic_var_mins[pivot_elem.row] = self.orig_var_mins[entering_var];
         self.basic_var_maxs[pivot_elem.row] = self.orig_var_maxs[entering_var];
 
-        if self.enable_dual_steepest_edge {
-            self.update_dual_sq_norms(pivot_elem.row, pivot_coeff);
-        }
-
         // Update non-basic vars stuff
 
         let leaving_var = self.basic_vars[pivot_elem.row];
@@ -1070,19 +2940,6 @@ impl Solver {
         leaving_var_state.at_min = pivot_elem.leaving_new_val == self.orig_var_mins[leaving_var];
         leaving_var_state.at_max = pivot_elem.leaving_new_val == self.orig_var_maxs[leaving_var];
 
-        let pivot_obj = self.nb_var_obj_coeffs[pivot_info.col] / pivot_coeff;
-        for (c, &coeff) in self.row_coeffs.iter() {
-            if c == pivot_info.col {
-                self.nb_var_obj_coeffs[c] = -pivot_obj;
-            } else {
-                self.nb_var_obj_coeffs[c] -= pivot_obj * coeff;
-            }
-        }
-
-        if self.enable_primal_steepest_edge {
-            self.update_primal_sq_norms(pivot_info.col, pivot_coeff);
-        }
-
         // Update basis itself
 
         self.basic_vars[pivot_elem.row] = entering_var;
@@ -1098,11 +2955,35 @@ impl Solver {
             self.basis_solver
                 .push_eta_matrix(&self.col_coeffs, pivot_elem.row, pivot_coeff);
         } else {
-            self.basis_solver
-                .reset(&self.orig_constraints_csc, &self.basic_vars);
+            self.refactorize(RefactorTrigger::Updates);
         }
     }
 
+    fn update_degeneracy_stats(&mut self, obj_diff: f64) {
+        self.stats.iterations += 1;
+        if obj_diff.abs() >= EPS {
+            self.degenerate_streak = 0;
+            return;
+        }
+
+        self.stats.degenerate_iterations += 1;
+        self.degenerate_streak += 1;
+        if self.degenerate_streak > self.stats.max_degenerate_streak {
+            self.stats.max_degenerate_streak = self.degenerate_streak;
+        }
+        if self.degenerate_streak == STALL_ITERS {
+            debug!(
+                "stall detected after {} degenerate iterations, switching to Bland's rule",
+                STALL_ITERS
+            );
+            self.stats.stalls += 1;
+        }
+    }
+
+    fn is_stalling(&self) -> bool {
+        self.degenerate_streak >= STALL_ITERS
+    }
+
     fn update_primal_sq_norms(&mut self, entering_col: usize, pivot_coeff: f64) {
         // Computations for the steepest edge pivoting rule. See
         // Forrest, J. J., & Goldfarb, D. (1992).
@@ -1173,21 +3054,33 @@ impl Solver {
         }
     }
 
-    #[allow(dead_code)]
     fn recalc_basic_var_vals(&mut self) {
         let mut cur_vals = self.orig_rhs.clone();
+        // Rounding errors of the residuals, used only with compensated summation.
+        let mut errs = vec![];
+        if self.options.accuracy != Accuracy::Standard {
+            errs.resize(cur_vals.len(), 0.0);
+        }
         for (i, var) in self.nb_vars.iter().enumerate() {
             let val = self.nb_var_vals[i];
             if val != 0.0 {
                 for (r, &coeff) in self.orig_constraints_csc.outer_view(*var).unwrap().iter() {
-                    cur_vals[r] -= val * coeff;
+                    if errs.is_empty() {
+                        cur_vals[r] -= val * coeff;
+                    } else {
+                        let (sum, err) = summation::two_sum(cur_vals[r], -val * coeff);
+                        cur_vals[r] = sum;
+                        errs[r] += err;
+                    }
                 }
             }
         }
+        for (val, err) in cur_vals.iter_mut().zip(&errs) {
+            *val += err;
+        }
 
         if self.basis_solver.eta_matrices.len() > 0 {
-            self.basis_solver
-                .reset(&self.orig_constraints_csc, &self.basic_vars);
+            self.refactorize(RefactorTrigger::Recalc);
         }
 
         self.basis_solver
@@ -1198,11 +3091,11 @@ impl Solver {
 
     fn recalc_obj_coeffs(&mut self) {
         if self.basis_solver.eta_matrices.len() > 0 {
-            self.basis_solver
-                .reset(&self.orig_constraints_csc, &self.basic_vars);
+            self.refactorize(RefactorTrigger::Recalc);
         }
 
         let multipliers = {
+            let start = Instant::now();
             let mut rhs = vec![0.0; self.num_constraints()];
             for (c, &var) in self.basic_vars.iter().enumerate() {
                 rhs[c] = self.orig_obj_coeffs[var];
@@ -1210,24 +3103,27 @@ impl Solver {
             self.basis_solver
                 .lu_factors_transp
                 .solve_dense(&mut rhs, &mut self.basis_solver.scratch);
+            self.basis_solver.timings.btran += start.elapsed();
             rhs
         };
 
+        let accuracy = self.options.accuracy;
         self.nb_var_obj_coeffs.clear();
         for &var in &self.nb_vars {
             let col = self.orig_constraints_csc.outer_view(var).unwrap();
-            let dot_prod: f64 = col.iter().map(|(r, val)| val * multipliers[r]).sum();
+            let dot_prod =
+                summation::sum(accuracy, col.iter().map(|(r, val)| val * multipliers[r]));
             self.nb_var_obj_coeffs
                 .push(self.orig_obj_coeffs[var] - dot_prod);
         }
 
-        self.cur_obj_val = 0.0;
-        for (r, &var) in self.basic_vars.iter().enumerate() {
-            self.cur_obj_val += self.orig_obj_coeffs[var] * self.basic_var_vals[r];
-        }
-        for (c, &var) in self.nb_vars.iter().enumerate() {
-            self.cur_obj_val += self.orig_obj_coeffs[var] * self.nb_var_vals[c];
-        }
+        let basic_terms = (self.basic_vars.iter())
+            .zip(&self.basic_var_vals)
+            .map(|(&var, &val)| self.orig_obj_coeffs[var] * val);
+        let nb_terms = (self.nb_vars.iter())
+            .zip(&self.nb_var_vals)
+            .map(|(&var, &val)| self.orig_obj_coeffs[var] * val);
+        self.cur_obj_val = summation::sum(accuracy, basic_terms.chain(nb_terms));
     }
 
     #[allow(dead_code)]
@@ -1241,6 +3137,50 @@ impl Solver {
     }
 }
 
+/// See `Solver::rhs_range`. Leaving vars are indices in the combined var + slack var space.
+#[derive(Clone, Debug)]
+pub(crate) struct RhsRange {
+    pub(crate) min: f64,
+    pub(crate) leaving_at_min: Option<usize>,
+    pub(crate) max: f64,
+    pub(crate) leaving_at_max: Option<usize>,
+}
+
+/// Tracks the candidate with the maximum score. Ties are broken in favor of the first
+/// candidate or, if the random generator is supplied, uniformly at random.
+struct MaxScore {
+    idx: Option<usize>,
+    score: f64,
+    num_ties: u64,
+}
+
+impl MaxScore {
+    fn new() -> MaxScore {
+        MaxScore {
+            idx: None,
+            score: f64::NEG_INFINITY,
+            num_ties: 0,
+        }
+    }
+
+    fn update(&mut self, idx: usize, score: f64, rng: Option<&mut Rng>) {
+        if score > self.score {
+            self.idx = Some(idx);
+            self.score = score;
+            self.num_ties = 1;
+        } else if score == self.score {
+            if let Some(rng) = rng {
+                // Reservoir sampling: replace the current candidate with probability
+                // 1 / (number of candidates with this score).
+                self.num_ties += 1;
+                if rng.next_u64() % self.num_ties == 0 {
+                    self.idx = Some(idx);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct PivotInfo {
     col: usize,
@@ -1268,6 +3208,98 @@ struct BasisSolver {
     scratch: ScratchSpace,
     eta_matrices: EtaMatrices,
     rhs: ScatteredVec,
+    ordering: BasisOrdering,
+    /// Time spent in ordering, factorization, FTRAN and BTRAN.
+    timings: SolverTimings,
+}
+
+/// Column permutation of the basis matrix requested by `ordering`. For a fixed order of
+/// the structural vars slack vars come first in the order of their constraints.
+fn basis_col_perm<'a>(
+    ordering: &BasisOrdering,
+    basic_vars: &[usize],
+    get_col: impl Fn(usize) -> &'a [usize],
+) -> Perm {
+    match ordering {
+        BasisOrdering::Auto => order_simple(basic_vars.len(), get_col),
+        BasisOrdering::Fixed(perm) => {
+            let mut new2orig: Vec<usize> = (0..basic_vars.len()).collect();
+            new2orig.sort_unstable_by_key(|&c| match perm.orig2new.get(basic_vars[c]) {
+                Some(&rank) => (1, rank),
+                None => (0, basic_vars[c]),
+            });
+            Perm::from_new2orig(new2orig).unwrap()
+        }
+        #[cfg(feature = "nested-dissection")]
+        BasisOrdering::NestedDissection => {
+            crate::nested_dissection::order_nested_dissection(basic_vars.len(), get_col)
+        }
+    }
+}
+
+/// What triggered a refactorization of the basis matrix.
+#[derive(Clone, Copy, Debug)]
+enum RefactorTrigger {
+    /// Basis updates grew bigger than the LU factors.
+    Updates,
+    /// Values are recalculated from scratch.
+    Recalc,
+    /// A constraint was added.
+    Constraint,
+}
+
+impl RefactorTrigger {
+    fn as_str(self) -> &'static str {
+        match self {
+            RefactorTrigger::Updates => "updates",
+            RefactorTrigger::Recalc => "recalc",
+            RefactorTrigger::Constraint => "constraint",
+        }
+    }
+}
+
+/// Ratio of the biggest absolute value in the U factor to the biggest absolute value
+/// in the basis matrix.
+fn pivot_growth(orig_constraints_csc: &CsMat, basic_vars: &[usize], lu_factors: &LUFactors) -> f64 {
+    let max_basis_abs = basic_vars
+        .iter()
+        .flat_map(|&var| {
+            orig_constraints_csc
+                .outer_view(var)
+                .unwrap()
+                .into_raw_storage()
+                .1
+        })
+        .fold(0.0, |max: f64, &val| max.max(val.abs()));
+    if max_basis_abs == 0.0 {
+        return 1.0;
+    }
+    lu_factors.max_abs_upper() / max_basis_abs
+}
+
+/// Order the columns of the basis matrix and factorize it.
+fn factorize_basis(
+    orig_constraints_csc: &CsMat,
+    basic_vars: &[usize],
+    ordering: &BasisOrdering,
+    scratch: &mut ScratchSpace,
+    timings: &mut SolverTimings,
+) -> Result<LUFactors, crate::sparse::Error> {
+    let get_col = |c: usize| {
+        orig_constraints_csc
+            .outer_view(basic_vars[c])
+            .unwrap()
+            .into_raw_storage()
+    };
+
+    let start = Instant::now();
+    let col_perm = basis_col_perm(ordering, basic_vars, |c| get_col(c).0);
+    timings.ordering += start.elapsed();
+
+    let start = Instant::now();
+    let res = lu_factorize(basic_vars.len(), get_col, Some(col_perm), 0.1, scratch);
+    timings.factorization += start.elapsed();
+    res
 }
 
 impl BasisSolver {
@@ -1287,22 +3319,43 @@ impl BasisSolver {
         self.scratch.clear_sparse(basic_vars.len());
         self.eta_matrices.clear_and_resize(basic_vars.len());
         self.rhs.clear_and_resize(basic_vars.len());
-        self.lu_factors = lu_factorize(
-            basic_vars.len(),
-            |c| {
-                orig_constraints_csc
-                    .outer_view(basic_vars[c])
-                    .unwrap()
-                    .into_raw_storage()
-            },
-            0.1,
+        let factorized = factorize_basis(
+            orig_constraints_csc,
+            basic_vars,
+            &self.ordering,
             &mut self.scratch,
-        )
-        .unwrap(); // TODO: When is singular basis matrix possible? Report as a proper error.
+            &mut self.timings,
+        );
+        self.lu_factors = match factorized {
+            Ok(lu_factors) => lu_factors,
+            Err(_) => {
+                // The starting basis consists of slacks and pivots are nonzero, so the basis
+                // stays structurally nonsingular. Report structural deficiency if any to
+                // distinguish a bug from a numerical failure.
+                let get_col = |c: usize| {
+                    orig_constraints_csc
+                        .outer_view(basic_vars[c])
+                        .unwrap()
+                        .into_raw_storage()
+                        .0
+                };
+                let matching = find_diag_matching(basic_vars.len(), get_col);
+                let deficient_vars: Vec<usize> = matching
+                    .deficient_cols
+                    .iter()
+                    .map(|&c| basic_vars[c])
+                    .collect();
+                panic!(
+                    "singular basis matrix, structurally deficient rows: {:?}, vars: {:?}",
+                    matching.deficient_rows, deficient_vars,
+                );
+            }
+        };
         self.lu_factors_transp = self.lu_factors.transpose();
     }
 
     fn solve<'a>(&mut self, rhs: impl Iterator<Item = (usize, &'a f64)>) -> &ScatteredVec {
+        let start = Instant::now();
         self.rhs.set(rhs);
         self.lu_factors.solve(&mut self.rhs, &mut self.scratch);
 
@@ -1315,11 +3368,13 @@ impl BasisSolver {
             }
         }
 
+        self.timings.ftran += start.elapsed();
         &mut self.rhs
     }
 
     /// Pass right-hand side via self.rhs
     fn solve_transp<'a>(&mut self, rhs: impl Iterator<Item = (usize, &'a f64)>) -> &ScatteredVec {
+        let start = Instant::now();
         self.rhs.set(rhs);
         // apply eta matrices in reverse (Vanderbei p.139)
         for idx in (0..self.eta_matrices.len()).rev() {
@@ -1334,6 +3389,7 @@ impl BasisSolver {
 
         self.lu_factors_transp
             .solve(&mut self.rhs, &mut self.scratch);
+        self.timings.btran += start.elapsed();
         &mut self.rhs
     }
 }
@@ -1387,6 +3443,7 @@ fn into_resized(vec: CsVec, len: usize) -> CsVec {
 mod tests {
     use super::*;
     use crate::helpers::{assert_matrix_eq, to_sparse};
+    use crate::ErrorKind;
 
     #[test]
     fn initialize() {
@@ -1475,6 +3532,580 @@ mod tests {
         )
         .unwrap()
         .initial_solve();
-        assert_eq!(infeasible.unwrap_err(), Error::Infeasible);
+        assert_eq!(infeasible.unwrap_err().kind(), ErrorKind::Infeasible);
+    }
+
+    #[test]
+    fn primal_and_dual_phase1() {
+        // Starting basis is neither primal nor dual feasible.
+        let constraints = [
+            (to_sparse(&[1.0, 1.0, 0.0]), ComparisonOp::Ge, 2.0),
+            (to_sparse(&[1.0, -1.0, 1.0]), ComparisonOp::Le, 1.0),
+            (to_sparse(&[0.0, 1.0, 1.0]), ComparisonOp::Eq, 3.0),
+        ];
+        let new_solver = || {
+            Solver::try_new(
+                &[1.0, 2.0, -1.0],
+                &[0.0, 0.0, f64::NEG_INFINITY],
+                &[f64::INFINITY, f64::INFINITY, f64::INFINITY],
+                &constraints,
+            )
+            .unwrap()
+        };
+
+        for &algorithm in &[Algorithm::Primal, Algorithm::Dual] {
+            let mut sol = new_solver();
+            assert!(!sol.is_primal_feasible && !sol.is_dual_feasible);
+            sol.options.algorithm = algorithm;
+            sol.initial_solve().unwrap();
+            assert!(sol.is_primal_feasible && sol.is_dual_feasible);
+            assert!(f64::abs(sol.cur_obj_val - 5.0 / 3.0) < 1e-8);
+        }
+
+        let mut infeasible = Solver::try_new(
+            &[-1.0, 1.0],
+            &[0.0, 0.0],
+            &[f64::INFINITY, f64::INFINITY],
+            &[
+                (to_sparse(&[1.0, 1.0]), ComparisonOp::Ge, 10.0),
+                (to_sparse(&[1.0, 1.0]), ComparisonOp::Le, 5.0),
+            ],
+        )
+        .unwrap();
+        infeasible.options.algorithm = Algorithm::Primal;
+        let infeasible = infeasible.initial_solve();
+        assert_eq!(infeasible.unwrap_err().kind(), ErrorKind::Infeasible);
+    }
+
+    #[test]
+    fn primal_phase1_free_vars() {
+        // Free vars with zero reduced cost must not enter the basis, the last one
+        // doesn't appear in any constraint.
+        let mut sol = Solver::try_new(
+            &[1.0, -1.0, 0.0],
+            &[0.0, f64::NEG_INFINITY, f64::NEG_INFINITY],
+            &[f64::INFINITY; 3],
+            &[
+                (to_sparse(&[1.0, 1.0, 0.0]), ComparisonOp::Ge, 2.0),
+                (to_sparse(&[0.0, 1.0, 0.0]), ComparisonOp::Le, 1.0),
+            ],
+        )
+        .unwrap();
+        assert!(!sol.is_primal_feasible && !sol.is_dual_feasible);
+        sol.options.algorithm = Algorithm::Primal;
+        sol.initial_solve().unwrap();
+        assert_eq!(sol.cur_obj_val, 0.0);
+        assert_eq!(*sol.get_value(0), 1.0);
+        assert_eq!(*sol.get_value(1), 1.0);
+
+        let inf = f64::INFINITY;
+        let mut infeasible = Solver::try_new(
+            &[-4.0, -1.0, 0.0, 4.0, -2.0, -5.0],
+            &[1.0, -inf, 1.0, -3.0, 1.0, -5.0],
+            &[inf, inf, 3.0, inf, inf, -3.0],
+            &[
+                (
+                    to_sparse(&[1.0, 0.0, 0.0, 0.0, -3.0, -2.0]),
+                    ComparisonOp::Eq,
+                    -4.0,
+                ),
+                (
+                    to_sparse(&[0.0, 0.0, 0.0, -4.0, -4.0, 1.0]),
+                    ComparisonOp::Ge,
+                    -4.0,
+                ),
+            ],
+        )
+        .unwrap();
+        infeasible.options.algorithm = Algorithm::Primal;
+        let infeasible = infeasible.initial_solve();
+        assert_eq!(infeasible.unwrap_err().kind(), ErrorKind::Infeasible);
+    }
+
+    #[test]
+    fn bound_flipping() {
+        // Initial basis is dual feasible, the dual simplex passes the breakpoints
+        // of the three cheapest vars flipping them to the upper bound.
+        let mut sol = Solver::try_new(
+            &[4.0, 1.0, 6.0, 2.0, 5.0, 3.0],
+            &[0.0; 6],
+            &[1.0; 6],
+            &[(to_sparse(&[1.0; 6]), ComparisonOp::Ge, 3.5)],
+        )
+        .unwrap();
+        sol.initial_solve().unwrap();
+
+        assert_eq!(sol.stats.dual_iterations, 1);
+        assert_eq!(sol.stats.bound_flips, 3);
+        assert_eq!(sol.cur_obj_val, 8.0);
+        let vals: Vec<f64> = (0..6).map(|v| *sol.get_value(v)).collect();
+        assert_eq!(vals, [0.5, 1.0, 0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn pricing_strategies() {
+        // Enough vars for several pricing segments.
+        let num_vars = 3 * PARTIAL_PRICING_MIN_SEGMENT + 1;
+        let obj_coeffs: Vec<f64> = (0..num_vars).map(|v| -((v * 7 % 13) as f64)).collect();
+        let constraints: Vec<_> = (0..5)
+            .map(|i| {
+                let coeffs: Vec<f64> = (0..num_vars).map(|v| ((v + i) % 3) as f64).collect();
+                (to_sparse(&coeffs), ComparisonOp::Le, 10.0)
+            })
+            .collect();
+        let new_solver = || {
+            Solver::try_new(
+                &obj_coeffs,
+                &vec![0.0; num_vars],
+                &vec![f64::INFINITY; num_vars],
+                &constraints,
+            )
+            .unwrap()
+        };
+
+        let mut full = new_solver();
+        full.initial_solve().unwrap();
+
+        let mut partial = new_solver();
+        partial.options.pricing = Pricing::Partial;
+        partial.initial_solve().unwrap();
+        assert!(f64::abs(full.cur_obj_val - partial.cur_obj_val) < 1e-8);
+
+        // Parallel scan follows the same pivot sequence.
+        let mut parallel = new_solver();
+        parallel.options.threads = 4;
+        parallel.initial_solve().unwrap();
+        assert_eq!(parallel.cur_obj_val, full.cur_obj_val);
+        assert_eq!(parallel.stats, full.stats);
+        assert_ne!(partial.pricing_start, 0);
+
+        let mut multiple = Solver::try_new_with_options(
+            &obj_coeffs,
+            &vec![0.0; num_vars],
+            &vec![f64::INFINITY; num_vars],
+            &constraints,
+            None,
+            &SolverOptions {
+                pricing: Pricing::Multiple,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        multiple.initial_solve().unwrap();
+        assert!(f64::abs(full.cur_obj_val - multiple.cur_obj_val) < 1e-8);
+        assert!(multiple.stats.primal_iterations > 0);
+    }
+
+    #[test]
+    fn fixed_basis_ordering() {
+        let obj_coeffs = [-3.0, -1.0, -2.0, -1.0];
+        let constraints = vec![
+            (to_sparse(&[1.0, 1.0, 3.0, 0.0]), ComparisonOp::Le, 30.0),
+            (to_sparse(&[2.0, 2.0, 5.0, 1.0]), ComparisonOp::Le, 24.0),
+            (to_sparse(&[4.0, 1.0, 2.0, 3.0]), ComparisonOp::Le, 36.0),
+            (to_sparse(&[0.0, 1.0, 0.0, 1.0]), ComparisonOp::Le, 5.0),
+        ];
+        let solve = |basis_ordering| {
+            let mut sol = Solver::try_new_with_options(
+                &obj_coeffs,
+                &[0.0; 4],
+                &[f64::INFINITY; 4],
+                &constraints,
+                None,
+                &SolverOptions {
+                    basis_ordering,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            sol.initial_solve().unwrap();
+            sol.basis_solver
+                .reset(&sol.orig_constraints_csc, &sol.basic_vars);
+            sol
+        };
+
+        let auto = solve(BasisOrdering::Auto);
+        let perm = Perm::from_new2orig(vec![3, 1, 0, 2]).unwrap();
+        let fixed = solve(BasisOrdering::Fixed(perm));
+        assert!(f64::abs(auto.cur_obj_val - fixed.cur_obj_val) < 1e-8);
+
+        assert_eq!(auto.stats.primal_iterations, fixed.stats.primal_iterations);
+        #[cfg(feature = "nested-dissection")]
+        {
+            let nested = solve(BasisOrdering::NestedDissection);
+            assert!(f64::abs(auto.cur_obj_val - nested.cur_obj_val) < 1e-8);
+        }
+
+        // Slacks come first, then the structural vars in the requested order.
+        let ordering = BasisOrdering::Fixed(Perm::from_orig2new(vec![2, 1, 3, 0]).unwrap());
+        let col_perm = basis_col_perm(&ordering, &[2, 5, 0, 3, 4], |_| &[]);
+        assert_eq!(col_perm.new2orig, vec![4, 1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn compensated_summation() {
+        let obj_coeffs = [-3.0, -1.0, -2.0];
+        let constraints = vec![
+            (to_sparse(&[1.0, 1.0, 3.0]), ComparisonOp::Le, 30.0),
+            (to_sparse(&[2.0, 2.0, 5.0]), ComparisonOp::Le, 24.0),
+            (to_sparse(&[4.0, 1.0, 2.0]), ComparisonOp::Le, 36.0),
+        ];
+        let solve = |accuracy| {
+            let options = SolverOptions {
+                accuracy,
+                ..Default::default()
+            };
+            let bounds = ([0.0; 3], [f64::INFINITY; 3]);
+            let mut solver = Solver::try_new_with_options(
+                &obj_coeffs,
+                &bounds.0,
+                &bounds.1,
+                &constraints,
+                None,
+                &options,
+            )
+            .unwrap();
+            solver.initial_solve().unwrap();
+            solver.recalc_basic_var_vals();
+            solver.recalc_obj_coeffs();
+            solver
+        };
+
+        let standard = solve(Accuracy::Standard);
+        let compensated = solve(Accuracy::Compensated);
+        assert_eq!(compensated.cur_obj_val, -28.0);
+        assert_eq!(compensated.cur_obj_val, standard.cur_obj_val);
+        assert_eq!(compensated.basic_var_vals, standard.basic_var_vals);
+        assert_eq!(compensated.nb_var_obj_coeffs, standard.nb_var_obj_coeffs);
+
+        let mut double_double = solve(Accuracy::DoubleDouble);
+        assert_eq!(double_double.cur_obj_val, -28.0);
+        assert_eq!(double_double.basic_var_vals, standard.basic_var_vals);
+
+        // Refined columns still solve B x = a.
+        let mut standard = standard;
+        for c in 0..double_double.nb_vars.len() {
+            standard.calc_col_coeffs(c);
+            double_double.calc_col_coeffs(c);
+            let standard_col = to_dense(&standard.col_coeffs.clone().into_csvec(3));
+            let refined_col = to_dense(&double_double.col_coeffs.clone().into_csvec(3));
+            for (s, d) in standard_col.iter().zip(&refined_col) {
+                assert!(f64::abs(s - d) < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn random_tie_breaking() {
+        let choose = |rng: Option<&mut Rng>| {
+            let mut best = MaxScore::new();
+            let mut rng = rng;
+            for (idx, &score) in [1.0, 3.0, 2.0, 3.0, 3.0].iter().enumerate() {
+                best.update(idx, score, rng.as_deref_mut());
+            }
+            best.idx.unwrap()
+        };
+        assert_eq!(choose(None), 1);
+        let chosen: Vec<usize> = (0..20)
+            .map(|seed| choose(Some(&mut Rng::new(seed))))
+            .collect();
+        assert!(chosen.iter().all(|&idx| idx == 1 || idx == 3 || idx == 4));
+        assert!(chosen.iter().any(|&idx| idx != 1));
+
+        // Symmetric problem with a lot of ties. Results are the same for the same seed.
+        let constraints: Vec<_> = (0..4)
+            .map(|i| {
+                let coeffs: Vec<f64> = (0..8).map(|v| if v % 4 == i { 1.0 } else { 0.5 }).collect();
+                (to_sparse(&coeffs), ComparisonOp::Le, 2.0)
+            })
+            .collect();
+        let solve = |seed| {
+            let mut sol = Solver::try_new_with_options(
+                &[-1.0; 8],
+                &[0.0; 8],
+                &[f64::INFINITY; 8],
+                &constraints,
+                None,
+                &SolverOptions {
+                    random_seed: Some(seed),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            sol.initial_solve().unwrap();
+            assert!(f64::abs(sol.cur_obj_val + 16.0 / 5.0) < 1e-8);
+            sol.basic_vars
+        };
+        for seed in 0..5 {
+            assert_eq!(solve(seed), solve(seed));
+        }
+    }
+
+    #[test]
+    fn lexicographic_optimum() {
+        // The optimal face is the triangle x + y + z == 5 with all vars at most 3.
+        let constraints = [(to_sparse(&[1.0, 1.0, 1.0]), ComparisonOp::Le, 5.0)];
+        let mut sol = Solver::try_new_with_options(
+            &[-1.0; 3],
+            &[0.0; 3],
+            &[3.0; 3],
+            &constraints,
+            None,
+            &SolverOptions {
+                lexicographic_optimum: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        sol.initial_solve().unwrap();
+        assert_eq!(sol.cur_obj_val, -5.0);
+        assert_eq!(
+            (0..3).map(|v| *sol.get_value(v)).collect::<Vec<_>>(),
+            [0.0, 2.0, 3.0]
+        );
+
+        // The vertex doesn't depend on the pivoting rules.
+        let constraints: Vec<_> = (0..4)
+            .map(|i| {
+                let coeffs: Vec<f64> = (0..8).map(|v| if v % 4 == i { 1.0 } else { 0.5 }).collect();
+                (to_sparse(&coeffs), ComparisonOp::Le, 2.0)
+            })
+            .collect();
+        let solve = |options: SolverOptions| {
+            let mut sol = Solver::try_new_with_options(
+                &[-1.0; 8],
+                &[0.0; 8],
+                &[f64::INFINITY; 8],
+                &constraints,
+                None,
+                &SolverOptions {
+                    lexicographic_optimum: true,
+                    ..options
+                },
+            )
+            .unwrap();
+            sol.initial_solve().unwrap();
+            assert!(f64::abs(sol.cur_obj_val + 16.0 / 5.0) < 1e-8);
+            assert!(sol.is_optimal());
+            (0..8).map(|v| *sol.get_value(v)).collect::<Vec<_>>()
+        };
+        let expected = solve(SolverOptions::default());
+        assert_eq!(expected[..4], [0.0; 4]);
+        let other_options = (0..5)
+            .map(|seed| SolverOptions {
+                random_seed: Some(seed),
+                ..Default::default()
+            })
+            .chain([
+                SolverOptions {
+                    pricing: Pricing::Partial,
+                    ..Default::default()
+                },
+                SolverOptions {
+                    pricing: Pricing::Multiple,
+                    anti_degeneracy: AntiDegeneracy::Expand,
+                    ..Default::default()
+                },
+            ]);
+        for options in other_options {
+            let vals = solve(options);
+            for (val, expected) in vals.iter().zip(&expected) {
+                assert!(f64::abs(val - expected) < 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn lexicographic_optimum_free_vars() {
+        let inf = f64::INFINITY;
+        let solve = |obj_coeffs: &[f64], mins: &[f64], maxs: &[f64], constraints: &[_]| {
+            for &algorithm in &[Algorithm::Auto, Algorithm::Primal, Algorithm::Dual] {
+                let mut sol = Solver::try_new_with_options(
+                    obj_coeffs,
+                    mins,
+                    maxs,
+                    constraints,
+                    None,
+                    &SolverOptions {
+                        lexicographic_optimum: true,
+                        algorithm,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+                sol.initial_solve().unwrap();
+                assert!(sol.is_optimal());
+                assert_eq!(sol.cur_obj_val, 0.0);
+            }
+        };
+
+        // Minimizing the free vars is unbounded, so they are skipped.
+        solve(&[0.0, 1.0], &[-inf, 0.0], &[inf; 2], &[]);
+        solve(
+            &[0.0],
+            &[-inf],
+            &[inf],
+            &[(to_sparse(&[0.0]), ComparisonOp::Le, 2.0)],
+        );
+
+        // The var after the skipped one is still minimized.
+        let mut sol = Solver::try_new_with_options(
+            &[0.0, 0.0],
+            &[-inf, 0.0],
+            &[inf, 2.0],
+            &[(to_sparse(&[1.0, -1.0]), ComparisonOp::Le, 0.0)],
+            None,
+            &SolverOptions {
+                lexicographic_optimum: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        sol.initial_solve().unwrap();
+        assert_eq!(*sol.get_value(1), 0.0);
+    }
+
+    #[test]
+    fn degenerate_stats() {
+        let constraints = [
+            (to_sparse(&[1.0, 0.0]), ComparisonOp::Le, 1.0),
+            (to_sparse(&[0.0, 1.0]), ComparisonOp::Le, 1.0),
+            (to_sparse(&[1.0, 1.0]), ComparisonOp::Le, 2.0),
+            (to_sparse(&[1.0, -1.0]), ComparisonOp::Le, 0.0),
+        ];
+        let new_solver = || {
+            Solver::try_new(
+                &[-1.0, -1.0],
+                &[0.0, 0.0],
+                &[f64::INFINITY, f64::INFINITY],
+                &constraints,
+            )
+            .unwrap()
+        };
+
+        let mut sol = new_solver();
+        sol.initial_solve().unwrap();
+        assert_eq!(sol.cur_obj_val, -2.0);
+        let stats = &sol.stats;
+        assert_eq!(
+            stats.iterations,
+            stats.primal_iterations + stats.dual_iterations
+        );
+        assert!(stats.degenerate_iterations > 0);
+        assert!(stats.max_degenerate_streak > 0);
+        assert_eq!(stats.stalls, 0);
+
+        // Pretend that the solver is stalling, it must switch to Bland's rule
+        // and still find the optimum.
+        let mut stalling = new_solver();
+        stalling.degenerate_streak = STALL_ITERS;
+        assert!(stalling.is_stalling());
+        stalling.initial_solve().unwrap();
+        assert_eq!(stalling.cur_obj_val, -2.0);
+
+        // EXPAND makes positive steps on degenerate vertices and cleans up at the end.
+        let mut expand = new_solver();
+        expand.options.anti_degeneracy = AntiDegeneracy::Expand;
+        expand.initial_solve().unwrap();
+        assert!(f64::abs(expand.cur_obj_val + 2.0) < 1e-8);
+        assert_eq!(expand.expand_tol, EXPAND_TOL_INITIAL);
+        assert_eq!(expand.calc_primal_infeasibility().0, 0);
+
+        // The periodic reset with the tolerance at its maximum.
+        let mut expand = new_solver();
+        expand.options.anti_degeneracy = AntiDegeneracy::Expand;
+        expand.expand_tol = EPS;
+        expand.initial_solve().unwrap();
+        assert!(f64::abs(expand.cur_obj_val + 2.0) < 1e-8);
+    }
+
+    #[test]
+    fn numerical_stats() {
+        let mut sol = Solver::try_new(
+            &[-1.0, -1.0],
+            &[0.0, 0.0],
+            &[f64::INFINITY, f64::INFINITY],
+            &[
+                (to_sparse(&[2.0, 1.0]), ComparisonOp::Le, 4.0),
+                (to_sparse(&[1.0, 2.0]), ComparisonOp::Le, 4.0),
+            ],
+        )
+        .unwrap();
+        // The slack basis is the identity matrix.
+        assert_eq!(sol.stats.max_pivot_growth, 1.0);
+        assert_eq!(sol.stats.min_pivot, None);
+
+        sol.initial_solve().unwrap();
+        let stats = sol.stats.clone();
+        assert!(stats.max_pivot_growth >= 1.0);
+        let min_pivot = stats.min_pivot.unwrap();
+        assert!((EPS..=2.0).contains(&min_pivot));
+        assert_eq!(stats.rejected_pivots, 0);
+
+        sol.add_constraint(to_sparse(&[1.0, 1.0]), ComparisonOp::Le, 2.0)
+            .unwrap();
+        assert_eq!(sol.stats.constraint_refactorizations, 1);
+        assert_eq!(sol.stats.refactorizations(), stats.refactorizations() + 1);
+    }
+
+    #[test]
+    fn relative_gap() {
+        let n = 50;
+        let constraints: Vec<_> = (0..n)
+            .map(|i| {
+                let mut coeffs = vec![0.0; n];
+                for j in 0..3 {
+                    coeffs[(i + 3 * j) % n] = (1 + (i + j) % 4) as f64;
+                }
+                (to_sparse(&coeffs), ComparisonOp::Le, (10 + i % 3) as f64)
+            })
+            .collect();
+        let obj_coeffs: Vec<f64> = (0..n).map(|i| -((1 + i % 7) as f64)).collect();
+        let new_solver = |relative_gap| {
+            Solver::try_new_with_options(
+                &obj_coeffs,
+                &vec![0.0; n],
+                &vec![f64::INFINITY; n],
+                &constraints,
+                None,
+                &SolverOptions {
+                    relative_gap,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let mut optimal = new_solver(None);
+        optimal.initial_solve().unwrap();
+        assert!(optimal.is_optimal());
+        assert_eq!(optimal.relative_gap(), 0.0);
+
+        let mut sol = new_solver(Some(0.1));
+        sol.initial_solve().unwrap();
+        assert!(!sol.is_optimal());
+        assert!(sol.relative_gap() <= 0.1);
+        assert!(sol.stats.iterations < optimal.stats.iterations);
+        assert_eq!(sol.calc_primal_infeasibility().0, 0);
+        let gap = sol.cur_obj_val - optimal.cur_obj_val;
+        assert!(gap > 0.0 && gap <= sol.relative_gap() * sol.cur_obj_val.abs());
+
+        // Modifications restart the primal simplex, which stops early again.
+        let mut coeffs = vec![0.0; n];
+        coeffs[0] = 1.0;
+        coeffs[1] = 1.0;
+        sol.add_constraint(to_sparse(&coeffs), ComparisonOp::Le, 1.0)
+            .unwrap();
+        assert_eq!(sol.calc_primal_infeasibility().0, 0);
+        assert!(sol.relative_gap() <= 0.1);
+        optimal
+            .add_constraint(to_sparse(&coeffs), ComparisonOp::Le, 1.0)
+            .unwrap();
+        assert!(sol.cur_obj_val >= optimal.cur_obj_val);
+
+        // Tolerance 0 means solving to optimality.
+        let mut exact = new_solver(Some(0.0));
+        exact.initial_solve().unwrap();
+        assert!(exact.is_optimal());
     }
 }
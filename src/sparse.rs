@@ -1,35 +1,107 @@
 use crate::helpers::to_dense;
 use sprs::{CsMat, CsVec};
 
-#[derive(Clone, Debug, Default)]
-pub(crate) struct SparseVec {
+/// A sparse vector: a list of indices of nonzero elements and their values.
+///
+/// Indices are not required to be sorted, but each index should appear at most once.
+/// Operations with dense vectors take them as slices, so that it is possible to work with
+/// solver data (e.g. cut coefficients or variable values) without densifying the sparse side.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::SparseVec;
+/// let vec = SparseVec::gather(&[0.0, 2.0, 0.0, -1.0]);
+/// assert_eq!(vec.indices(), &[1, 3]);
+/// assert_eq!(vec.dot(&[1.0, 1.0, 1.0, 2.0]), 0.0);
+///
+/// let mut dense = vec![1.0; 4];
+/// vec.axpy(0.5, &mut dense);
+/// assert_eq!(dense, vec![1.0, 2.0, 1.0, 0.5]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SparseVec {
     indices: Vec<usize>,
     values: Vec<f64>,
 }
 
 impl SparseVec {
-    pub(crate) fn new() -> SparseVec {
+    /// Create an empty vector.
+    pub fn new() -> SparseVec {
         SparseVec {
             indices: vec![],
             values: vec![],
         }
     }
 
-    pub(crate) fn clear(&mut self) {
+    /// Collect the nonzero elements of a dense vector.
+    pub fn gather(dense: &[f64]) -> SparseVec {
+        dense
+            .iter()
+            .enumerate()
+            .filter(|(_, &val)| val != 0.0)
+            .map(|(i, &val)| (i, val))
+            .collect()
+    }
+
+    /// Remove all elements.
+    pub fn clear(&mut self) {
         self.indices.clear();
         self.values.clear();
     }
 
-    pub(crate) fn push(&mut self, i: usize, val: f64) {
+    /// Append an element. The index must not be present in the vector.
+    pub fn push(&mut self, i: usize, val: f64) {
         self.indices.push(i);
         self.values.push(val);
     }
 
-    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, &f64)> {
+    /// Number of stored elements.
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns true if there are no stored elements.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Indices of the stored elements.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Values of the stored elements (in the same order as [`indices`](#method.indices)).
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Iterate over the stored elements as (index, value) pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &f64)> {
         self.indices.iter().copied().zip(&self.values)
     }
 
-    pub(crate) fn sq_norm(&self) -> f64 {
+    /// Write the stored elements into a dense vector (other elements are not changed).
+    pub fn scatter(&self, dense: &mut [f64]) {
+        for (i, &val) in self.iter() {
+            dense[i] = val;
+        }
+    }
+
+    /// Compute `y += alpha * self` for a dense vector y.
+    pub fn axpy(&self, alpha: f64, y: &mut [f64]) {
+        for (i, &val) in self.iter() {
+            y[i] += alpha * val;
+        }
+    }
+
+    /// Dot product with a dense vector.
+    pub fn dot(&self, dense: &[f64]) -> f64 {
+        self.iter().map(|(i, &val)| val * dense[i]).sum()
+    }
+
+    /// Squared euclidean norm.
+    pub fn sq_norm(&self) -> f64 {
         self.values.iter().map(|&v| v * v).sum()
     }
 
@@ -38,6 +110,47 @@ impl SparseVec {
     }
 }
 
+impl std::iter::FromIterator<(usize, f64)> for SparseVec {
+    fn from_iter<I: IntoIterator<Item = (usize, f64)>>(iter: I) -> Self {
+        let mut vec = SparseVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl std::iter::Extend<(usize, f64)> for SparseVec {
+    fn extend<I: IntoIterator<Item = (usize, f64)>>(&mut self, iter: I) {
+        for (i, val) in iter {
+            self.push(i, val);
+        }
+    }
+}
+
+/// A sparse accumulator: a dense vector of values together with the list of indices
+/// of potentially nonzero elements and a marker array to test membership in that list.
+///
+/// This is the workspace used for sparse computations throughout the solver. Updating
+/// an element takes constant time, and clearing or iterating over the vector takes time
+/// proportional to the number of nonzeros, not to its dimension. Thus it is efficient to
+/// allocate one vector of the full dimension and reuse it for many sparse computations.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::{ScatteredVec, SparseVec};
+/// let mut acc = ScatteredVec::empty(5);
+/// acc.add_sparse(2.0, &[(1, 1.0), (3, 1.0)].iter().copied().collect());
+/// acc.add_sparse(-1.0, &[(3, 1.0), (4, 1.0)].iter().copied().collect());
+/// assert_eq!(acc.indices(), &[1, 3, 4]);
+/// assert_eq!(*acc.get(3), 1.0);
+///
+/// let mut res = SparseVec::new();
+/// acc.to_sparse_vec(&mut res);
+/// assert_eq!(res.values(), &[2.0, 1.0, -1.0]);
+///
+/// acc.clear();
+/// assert_eq!(acc.nnz(), 0);
+/// ```
 #[derive(Clone, Debug)]
 pub struct ScatteredVec {
     pub(crate) values: Vec<f64>,
@@ -46,6 +159,7 @@ pub struct ScatteredVec {
 }
 
 impl ScatteredVec {
+    /// Create a zero vector of dimension n.
     pub fn empty(n: usize) -> ScatteredVec {
         ScatteredVec {
             values: vec![0.0; n],
@@ -54,23 +168,41 @@ impl ScatteredVec {
         }
     }
 
+    /// Dimension of the vector.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.values.len()
     }
 
+    /// Number of potentially nonzero elements (some of them can become zero
+    /// as a result of cancellation).
+    pub fn nnz(&self) -> usize {
+        self.nonzero.len()
+    }
+
+    /// Iterate over the potentially nonzero elements as (index, value) pairs.
     pub fn iter(&self) -> impl Iterator<Item = (usize, &f64)> {
         self.nonzero.iter().map(move |&i| (i, &self.values[i]))
     }
 
+    /// Indices of the potentially nonzero elements in the order of their appearance.
     pub fn indices(&self) -> &[usize] {
         &self.nonzero
     }
 
+    /// Returns true if the element i is in the list of potentially nonzero elements.
+    pub fn contains(&self, i: usize) -> bool {
+        self.is_nonzero[i]
+    }
+
+    /// Value of the element i.
     #[inline]
     pub fn get(&self, i: usize) -> &f64 {
         &self.values[i]
     }
 
+    /// Mutable reference to the element i. The element is added to the list of potentially
+    /// nonzero elements.
     #[inline]
     pub fn get_mut(&mut self, i: usize) -> &mut f64 {
         if !std::mem::replace(&mut self.is_nonzero[i], true) {
@@ -79,6 +211,14 @@ impl ScatteredVec {
         &mut self.values[i]
     }
 
+    /// Compute `self += alpha * vec`.
+    pub fn add_sparse(&mut self, alpha: f64, vec: &SparseVec) {
+        for (i, &val) in vec.iter() {
+            *self.get_mut(i) += alpha * val;
+        }
+    }
+
+    /// Squared euclidean norm.
     pub fn sq_norm(&self) -> f64 {
         self.nonzero
             .iter()
@@ -86,6 +226,7 @@ impl ScatteredVec {
             .sum()
     }
 
+    /// Set all elements to zero.
     pub fn clear(&mut self) {
         for &i in &self.nonzero {
             self.values[i] = 0.0;
@@ -94,12 +235,14 @@ impl ScatteredVec {
         self.nonzero.clear();
     }
 
+    /// Set all elements to zero and change the dimension to n.
     pub fn clear_and_resize(&mut self, n: usize) {
         self.clear();
         self.values.resize(n, 0.0);
         self.is_nonzero.resize(n, false);
     }
 
+    /// Replace the contents with the specified (index, value) pairs.
     pub fn set<'a, T>(&mut self, rhs: T)
     where
         T: IntoIterator<Item = (usize, &'a f64)>,
@@ -112,7 +255,8 @@ impl ScatteredVec {
         }
     }
 
-    pub(crate) fn to_sparse_vec(&self, lhs: &mut SparseVec) {
+    /// Copy the potentially nonzero elements to a sparse vector, replacing its contents.
+    pub fn to_sparse_vec(&self, lhs: &mut SparseVec) {
         lhs.clear();
         for &idx in &self.nonzero {
             lhs.indices.push(idx);
@@ -135,17 +279,88 @@ impl ScatteredVec {
     }
 }
 
+/// An unsigned integer type used to store row indices in sparse matrix structures.
+///
+/// Implemented for `usize`, `u32` and `u16`. Narrower types take less memory and improve
+/// cache behavior but limit the dimensions of the matrices, so the solver stores its basis
+/// factorization with `usize` indices by default and with narrower ones if the `u32-indices`
+/// or `u16-indices` feature is enabled. Matrix structure passed to functions like
+/// [`DmDecomposition::compute`](struct.DmDecomposition.html#method.compute) can use any of
+/// these types.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::{DmDecomposition, IndexType};
+/// let cols: [&[u16]; 3] = [&[0], &[0], &[0, 1, 2]];
+/// let dm = DmDecomposition::compute(3, 3, |c| cols[c]);
+/// assert_eq!(dm.structural_rank(), 2);
+/// assert_eq!(u16::from_usize(7).index(), 7);
+/// ```
+pub trait IndexType: Copy + Ord + std::fmt::Debug + Send + Sync + 'static {
+    /// Convert an index to this type.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the index doesn't fit into the type.
+    fn from_usize(i: usize) -> Self;
+
+    /// Convert to a `usize` index.
+    fn index(self) -> usize;
+}
+
+impl IndexType for usize {
+    #[inline]
+    fn from_usize(i: usize) -> Self {
+        i
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self
+    }
+}
+
+macro_rules! impl_narrow_index_type {
+    ($($ty:ty),*) => {$(
+        impl IndexType for $ty {
+            #[inline]
+            fn from_usize(i: usize) -> Self {
+                use std::convert::TryFrom;
+                <$ty>::try_from(i)
+                    .unwrap_or_else(|_| panic!("index {} doesn't fit into {}", i, stringify!($ty)))
+            }
+
+            #[inline]
+            fn index(self) -> usize {
+                self as usize
+            }
+        }
+    )*};
+}
+
+impl_narrow_index_type!(u32, u16);
+
+/// Index type of the solver basis factorization selected by the cargo features
+/// (the widest of the enabled ones).
+#[cfg(feature = "u32-indices")]
+pub(crate) type Index = u32;
+#[cfg(all(feature = "u16-indices", not(feature = "u32-indices")))]
+pub(crate) type Index = u16;
+#[cfg(not(any(feature = "u32-indices", feature = "u16-indices")))]
+pub(crate) type Index = usize;
+
 /// Unordered sparse matrix with elements stored by columns
 #[derive(Clone, Debug)]
-pub(crate) struct SparseMat {
+pub(crate) struct SparseMat<I = Index> {
     n_rows: usize,
     indptr: Vec<usize>,
-    indices: Vec<usize>,
+    indices: Vec<I>,
     data: Vec<f64>,
 }
 
-impl SparseMat {
-    pub(crate) fn new(n_rows: usize) -> SparseMat {
+impl<I: IndexType> SparseMat<I> {
+    pub(crate) fn new(n_rows: usize) -> SparseMat<I> {
         SparseMat {
             n_rows,
             indptr: vec![0],
@@ -175,7 +390,7 @@ impl SparseMat {
     }
 
     pub(crate) fn push(&mut self, row: usize, val: f64) {
-        self.indices.push(row);
+        self.indices.push(I::from_usize(row));
         self.data.push(val);
     }
 
@@ -183,11 +398,11 @@ impl SparseMat {
         self.indptr.push(self.indices.len())
     }
 
-    pub(crate) fn col_rows(&self, i_col: usize) -> &[usize] {
+    pub(crate) fn col_rows(&self, i_col: usize) -> &[I] {
         &self.indices[self.indptr[i_col]..self.indptr[i_col + 1]]
     }
 
-    pub(crate) fn col_rows_mut(&mut self, i_col: usize) -> &mut [usize] {
+    pub(crate) fn col_rows_mut(&mut self, i_col: usize) -> &mut [I] {
         &mut self.indices[self.indptr[i_col]..self.indptr[i_col + 1]]
     }
 
@@ -198,7 +413,7 @@ impl SparseMat {
     pub(crate) fn col_iter(&self, i_col: usize) -> impl Iterator<Item = (usize, &f64)> {
         self.col_rows(i_col)
             .iter()
-            .copied()
+            .map(|&r| r.index())
             .zip(self.col_data(i_col))
     }
 
@@ -208,7 +423,7 @@ impl SparseMat {
     {
         assert_eq!(*self.indptr.last().unwrap(), self.indices.len()); // prev column is sealed
         for (idx, val) in col {
-            self.indices.push(idx);
+            self.indices.push(I::from_usize(idx));
             self.data.push(val);
         }
         self.seal_column();
@@ -218,7 +433,7 @@ impl SparseMat {
         CsMat::new_csc(
             (self.cols(), self.n_rows),
             self.indptr,
-            self.indices,
+            self.indices.into_iter().map(I::index).collect(),
             self.data,
         )
     }
@@ -227,8 +442,8 @@ impl SparseMat {
         self.clone().into_csmat()
     }
 
-    pub(crate) fn transpose(&self) -> SparseMat {
-        let mut out = SparseMat {
+    pub(crate) fn transpose(&self) -> SparseMat<I> {
+        let mut out: SparseMat<I> = SparseMat {
             n_rows: self.cols(),
             indptr: vec![],
             indices: vec![],
@@ -240,7 +455,7 @@ impl SparseMat {
         out.indptr.resize(self.rows() + 1, 0);
         for c in 0..self.cols() {
             for &r in self.col_rows(c) {
-                out.indptr[r] += 1;
+                out.indptr[r.index()] += 1;
             }
         }
 
@@ -252,13 +467,13 @@ impl SparseMat {
 
         // place the elements
         out.indices.clear();
-        out.indices.resize(self.nnz(), 0);
+        out.indices.resize(self.nnz(), I::from_usize(0));
         out.data.clear();
         out.data.resize(self.nnz(), 0.0);
         for c in 0..self.cols() {
             for (r, &val) in self.col_iter(c) {
                 out.indptr[r] -= 1;
-                out.indices[out.indptr[r]] = c;
+                out.indices[out.indptr[r]] = I::from_usize(c);
                 out.data[out.indptr[r]] = val;
             }
         }
@@ -270,13 +485,13 @@ impl SparseMat {
 }
 
 #[derive(Clone)]
-pub(crate) struct TriangleMat {
-    pub(crate) nondiag: SparseMat,
+pub(crate) struct TriangleMat<I = Index> {
+    pub(crate) nondiag: SparseMat<I>,
     /// Diag elements, None means all 1's
     pub(crate) diag: Option<Vec<f64>>,
 }
 
-impl TriangleMat {
+impl<I: IndexType> TriangleMat<I> {
     pub(crate) fn rows(&self) -> usize {
         self.nondiag.rows()
     }
@@ -285,7 +500,7 @@ impl TriangleMat {
         self.nondiag.cols()
     }
 
-    pub(crate) fn transpose(&self) -> TriangleMat {
+    pub(crate) fn transpose(&self) -> TriangleMat<I> {
         TriangleMat {
             nondiag: self.nondiag.transpose(),
             diag: self.diag.clone(),
@@ -315,23 +530,152 @@ impl TriangleMat {
     }
 }
 
-impl std::fmt::Debug for TriangleMat {
+impl<I: IndexType> std::fmt::Debug for TriangleMat<I> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "nondiag:\n")?;
+        writeln!(f, "nondiag:")?;
         for row in self.nondiag.to_csmat().to_csr().outer_iterator() {
-            write!(f, "{:?}\n", to_dense(&row))?
+            writeln!(f, "{:?}", to_dense(&row))?
         }
-        write!(f, "diag: {:?}\n", self.diag)?;
+        writeln!(f, "diag: {:?}", self.diag)?;
         Ok(())
     }
 }
 
-#[derive(Clone, Debug)]
+/// A permutation of indices `0..n`, stored in both directions: element with the index `i`
+/// in the original order has the index `orig2new[i]` in the new order and vice versa.
+///
+/// # Examples
+///
+/// ```
+/// # use minilp::Perm;
+/// let perm = Perm::from_new2orig(vec![2, 0, 1]).unwrap();
+/// assert_eq!(perm.orig2new(), &[1, 2, 0]);
+///
+/// let mut vals = ['a', 'b', 'c'];
+/// perm.apply(&mut vals);
+/// assert_eq!(vals, ['c', 'a', 'b']);
+/// perm.inverse().apply(&mut vals);
+/// assert_eq!(vals, ['a', 'b', 'c']);
+///
+/// assert!(perm.compose(&perm.inverse()).is_identity());
+/// assert!(Perm::from_new2orig(vec![0, 0]).is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Perm {
     pub(crate) orig2new: Vec<usize>,
     pub(crate) new2orig: Vec<usize>,
 }
 
+impl Perm {
+    /// The identity permutation of n elements.
+    pub fn identity(n: usize) -> Perm {
+        Perm {
+            orig2new: (0..n).collect(),
+            new2orig: (0..n).collect(),
+        }
+    }
+
+    /// Create a permutation from the list of original indices in the new order.
+    /// Returns None if the list is not a permutation of `0..n`.
+    pub fn from_new2orig(new2orig: Vec<usize>) -> Option<Perm> {
+        let mut orig2new = vec![usize::MAX; new2orig.len()];
+        for (new, &orig) in new2orig.iter().enumerate() {
+            if orig >= orig2new.len() || orig2new[orig] != usize::MAX {
+                return None;
+            }
+            orig2new[orig] = new;
+        }
+        Some(Perm { orig2new, new2orig })
+    }
+
+    /// Create a permutation from the list of new indices in the original order.
+    /// Returns None if the list is not a permutation of `0..n`.
+    pub fn from_orig2new(orig2new: Vec<usize>) -> Option<Perm> {
+        Perm::from_new2orig(orig2new).map(|perm| perm.inverse())
+    }
+
+    /// Number of permuted elements.
+    pub fn len(&self) -> usize {
+        self.new2orig.len()
+    }
+
+    /// Returns true if the permutation is of zero elements.
+    pub fn is_empty(&self) -> bool {
+        self.new2orig.is_empty()
+    }
+
+    /// New indices of the elements in the original order.
+    pub fn orig2new(&self) -> &[usize] {
+        &self.orig2new
+    }
+
+    /// Original indices of the elements in the new order.
+    pub fn new2orig(&self) -> &[usize] {
+        &self.new2orig
+    }
+
+    /// Returns true if the permutation doesn't change the order.
+    pub fn is_identity(&self) -> bool {
+        self.new2orig
+            .iter()
+            .enumerate()
+            .all(|(new, &orig)| new == orig)
+    }
+
+    /// Check that both directions are permutations of `0..n` and are inverse to each other.
+    pub fn is_valid(&self) -> bool {
+        self.orig2new.len() == self.new2orig.len()
+            && self
+                .new2orig
+                .iter()
+                .enumerate()
+                .all(|(new, &orig)| orig < self.len() && self.orig2new[orig] == new)
+    }
+
+    /// The permutation restoring the original order.
+    pub fn inverse(&self) -> Perm {
+        Perm {
+            orig2new: self.new2orig.clone(),
+            new2orig: self.orig2new.clone(),
+        }
+    }
+
+    /// The permutation equivalent to applying `self` and then `other`.
+    pub fn compose(&self, other: &Perm) -> Perm {
+        assert_eq!(self.len(), other.len());
+        let new2orig = other.new2orig.iter().map(|&i| self.new2orig[i]).collect();
+        let orig2new = self.orig2new.iter().map(|&i| other.orig2new[i]).collect();
+        Perm { orig2new, new2orig }
+    }
+
+    /// Reorder the slice in place: after the call `slice[new]` is the element that was at
+    /// `slice[new2orig[new]]`.
+    pub fn apply<T>(&self, slice: &mut [T]) {
+        assert_eq!(slice.len(), self.len());
+        let mut is_done = vec![false; self.len()];
+        for start in 0..self.len() {
+            // Follow the cycle of the permutation, moving the elements into place.
+            let mut cur = start;
+            while !is_done[cur] {
+                is_done[cur] = true;
+                let src = self.new2orig[cur];
+                if src == start {
+                    break;
+                }
+                slice.swap(cur, src);
+                cur = src;
+            }
+        }
+    }
+
+    /// Renumber the indices of the sparse vector in place from the original to the new order.
+    pub fn apply_to_sparse_vec(&self, vec: &mut SparseVec) {
+        for i in &mut vec.indices {
+            *i = self.orig2new[*i];
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Error {
     SingularMatrix,
@@ -343,7 +687,7 @@ mod tests {
 
     #[test]
     fn mat_transpose() {
-        let mut mat = SparseMat::new(2);
+        let mut mat = SparseMat::<u16>::new(2);
         mat.push(0, 1.1);
         mat.push(1, 2.2);
         mat.seal_column();
@@ -357,4 +701,66 @@ mod tests {
         assert_eq!(&transp.indices, &[2, 0, 1, 0]);
         assert_eq!(&transp.data, &[4.4, 1.1, 3.3, 2.2]);
     }
+
+    #[test]
+    fn perm() {
+        let perm = Perm::from_orig2new(vec![3, 0, 2, 1]).unwrap();
+        assert!(perm.is_valid());
+        assert_eq!(perm.new2orig(), &[1, 3, 2, 0]);
+        assert!(!perm.is_identity());
+        assert!(Perm::identity(4).is_identity());
+        assert!(Perm::from_orig2new(vec![0, 4]).is_none());
+
+        let mut vals = [10, 11, 12, 13];
+        perm.apply(&mut vals);
+        assert_eq!(vals, [11, 13, 12, 10]);
+
+        let other = Perm::from_new2orig(vec![3, 2, 1, 0]).unwrap();
+        let composed = perm.compose(&other);
+        assert!(composed.is_valid());
+        let mut twice = [10, 11, 12, 13];
+        perm.apply(&mut twice);
+        other.apply(&mut twice);
+        let mut once = [10, 11, 12, 13];
+        composed.apply(&mut once);
+        assert_eq!(once, twice);
+
+        let mut vec: SparseVec = vec![(0, 1.0), (3, 2.0)].into_iter().collect();
+        perm.apply_to_sparse_vec(&mut vec);
+        assert_eq!(vec.indices(), &[3, 1]);
+
+        let invalid = Perm {
+            orig2new: vec![0, 1],
+            new2orig: vec![1, 0],
+        };
+        assert!(!invalid.is_valid());
+    }
+
+    #[test]
+    fn sparse_vec() {
+        let mut vec: SparseVec = vec![(3, 2.0), (0, -1.0)].into_iter().collect();
+        vec.push(1, 0.5);
+        assert_eq!(vec.nnz(), 3);
+        assert_eq!(vec.values(), &[2.0, -1.0, 0.5]);
+        assert_eq!(vec.sq_norm(), 5.25);
+        assert_eq!(vec.dot(&[1.0, 2.0, 3.0, 4.0]), 8.0);
+
+        let mut dense = vec![0.0; 4];
+        vec.scatter(&mut dense);
+        assert_eq!(dense, vec![-1.0, 0.5, 0.0, 2.0]);
+        vec.axpy(-1.0, &mut dense);
+        assert_eq!(dense, vec![0.0; 4]);
+        assert!(SparseVec::gather(&dense).is_empty());
+
+        let gathered = SparseVec::gather(&[0.0, 1.0, 0.0]);
+        assert_eq!(gathered.iter().collect::<Vec<_>>(), vec![(1, &1.0)]);
+        vec.clear();
+        assert!(vec.is_empty());
+
+        let expr = crate::LinearExpr::from([(crate::Variable(2, 0), 3.0)]);
+        assert_eq!(
+            SparseVec::from(&expr).iter().collect::<Vec<_>>(),
+            vec![(2, &3.0)]
+        );
+    }
 }
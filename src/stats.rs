@@ -0,0 +1,175 @@
+use crate::{ComparisonOp, Problem};
+use std::time::Duration;
+
+/// Statistics of the simplex iterations performed by the solver.
+///
+/// Statistics are cumulative: operations on a [`Solution`] (like adding constraints or fixing
+/// variables) add to the counts accumulated during the initial solve.
+///
+/// [`Solution`]: struct.Solution.html
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SolverStats {
+    /// Total number of simplex iterations.
+    pub iterations: usize,
+    /// Number of primal simplex iterations.
+    pub primal_iterations: usize,
+    /// Number of dual simplex iterations.
+    pub dual_iterations: usize,
+    /// Number of non-basic variables moved to their other bound by the bound-flipping
+    /// ratio test of the dual simplex.
+    pub bound_flips: usize,
+    /// Number of degenerate iterations, i.e. iterations that didn't change the objective value.
+    pub degenerate_iterations: usize,
+    /// The longest sequence of consecutive degenerate iterations.
+    pub max_degenerate_streak: usize,
+    /// Number of times a stall (a long sequence of degenerate iterations) was detected.
+    /// When stalling, the solver switches to the anti-cycling Bland's pivoting rule until
+    /// it makes progress.
+    pub stalls: usize,
+    /// Number of refactorizations of the basis matrix because the accumulated basis updates
+    /// grew bigger than its LU factors.
+    pub update_refactorizations: usize,
+    /// Number of refactorizations before recomputing the values of the basic variables or
+    /// the objective coefficients from scratch to get rid of accumulated errors.
+    pub recalc_refactorizations: usize,
+    /// Number of refactorizations after adding a constraint.
+    pub constraint_refactorizations: usize,
+    /// The biggest pivot growth factor of the basis matrix factorizations, i.e. the ratio
+    /// of the biggest absolute value in the U factor to the biggest absolute value in
+    /// the (scaled) basis matrix. Large values (say, more than 1e6) indicate an ill-conditioned
+    /// basis and a loss of accuracy.
+    pub max_pivot_growth: f64,
+    /// The smallest absolute value of a pivot element accepted by the ratio tests
+    /// (`None` if there were no pivots). Values close to the pivot tolerance 1e-8 indicate
+    /// that the solution may be inaccurate.
+    pub min_pivot: Option<f64>,
+    /// Number of nonzero pivot candidates rejected by the ratio tests because their
+    /// absolute value was below the pivot tolerance.
+    pub rejected_pivots: usize,
+}
+
+impl SolverStats {
+    /// Fraction of degenerate iterations among all iterations.
+    pub fn degenerate_fraction(&self) -> f64 {
+        if self.iterations == 0 {
+            0.0
+        } else {
+            self.degenerate_iterations as f64 / self.iterations as f64
+        }
+    }
+
+    /// Total number of refactorizations of the basis matrix (not counting the initial
+    /// factorization).
+    pub fn refactorizations(&self) -> usize {
+        self.update_refactorizations
+            + self.recalc_refactorizations
+            + self.constraint_refactorizations
+    }
+}
+
+/// Wall-clock time spent in the phases of the solution process, see [`Solution::timings`].
+///
+/// Like [`SolverStats`], timings are cumulative. Only the phases listed here are timed,
+/// so the sum of the fields is less than the total solution time (the rest is spent
+/// mostly in updating the basis and the values of the variables).
+///
+/// [`Solution::timings`]: struct.Solution.html#method.timings
+/// [`SolverStats`]: struct.SolverStats.html
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SolverTimings {
+    /// Scaling of the constraint matrix.
+    pub scaling: Duration,
+    /// Solving the dual problem to find the starting point (if the problem was dualized).
+    pub dualization: Duration,
+    /// Ordering the columns of the basis matrix before factorization.
+    pub ordering: Duration,
+    /// LU factorization of the basis matrix.
+    pub factorization: Duration,
+    /// Solves with the basis matrix (FTRAN), e.g. calculating the column of the entering
+    /// variable.
+    pub ftran: Duration,
+    /// Solves with the transposed basis matrix (BTRAN), e.g. calculating the row of
+    /// the leaving variable.
+    pub btran: Duration,
+    /// Choosing the entering (primal simplex) or leaving (dual simplex) variable.
+    pub pricing: Duration,
+    /// Choosing the leaving (primal simplex) or entering (dual simplex) variable.
+    pub ratio_test: Duration,
+}
+
+impl SolverTimings {
+    /// Sum of the times of all timed phases.
+    pub fn sum(&self) -> Duration {
+        self.scaling
+            + self.dualization
+            + self.ordering
+            + self.factorization
+            + self.ftran
+            + self.btran
+            + self.pricing
+            + self.ratio_test
+    }
+}
+
+/// Size and sparsity statistics of a problem, see [`Problem::stats`].
+///
+/// [`Problem::stats`]: struct.Problem.html#method.stats
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProblemStats {
+    /// Number of variables.
+    pub num_vars: usize,
+    /// Number of constraints.
+    pub num_constraints: usize,
+    /// Number of `<=` constraints.
+    pub num_le_constraints: usize,
+    /// Number of `>=` constraints.
+    pub num_ge_constraints: usize,
+    /// Number of `==` constraints.
+    pub num_eq_constraints: usize,
+    /// Number of nonzero coefficients in the constraints.
+    pub num_nonzeros: usize,
+    /// The smallest abs. value of a nonzero constraint coefficient (0 if there are none).
+    pub min_abs_coeff: f64,
+    /// The biggest abs. value of a constraint coefficient.
+    pub max_abs_coeff: f64,
+}
+
+impl ProblemStats {
+    /// Fraction of nonzero entries in the constraint matrix.
+    pub fn density(&self) -> f64 {
+        if self.num_vars == 0 || self.num_constraints == 0 {
+            0.0
+        } else {
+            self.num_nonzeros as f64 / (self.num_vars as f64 * self.num_constraints as f64)
+        }
+    }
+}
+
+pub(crate) fn problem_stats(problem: &Problem) -> ProblemStats {
+    let mut stats = ProblemStats {
+        num_vars: problem.obj_coeffs.len(),
+        num_constraints: problem.constraints.len(),
+        min_abs_coeff: f64::INFINITY,
+        ..Default::default()
+    };
+    for (coeffs, cmp_op, _) in &problem.constraints {
+        match cmp_op {
+            ComparisonOp::Le => stats.num_le_constraints += 1,
+            ComparisonOp::Ge => stats.num_ge_constraints += 1,
+            ComparisonOp::Eq => stats.num_eq_constraints += 1,
+        }
+        for &coeff in coeffs.data() {
+            if coeff != 0.0 {
+                stats.num_nonzeros += 1;
+                stats.min_abs_coeff = f64::min(stats.min_abs_coeff, coeff.abs());
+                stats.max_abs_coeff = f64::max(stats.max_abs_coeff, coeff.abs());
+            }
+        }
+    }
+    if stats.num_nonzeros == 0 {
+        stats.min_abs_coeff = 0.0;
+    }
+    stats
+}
@@ -0,0 +1,128 @@
+use crate::Accuracy;
+
+/// Sum of the two numbers and the rounding error of that sum (the TwoSum algorithm).
+pub(crate) fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/// Split a number into two halves with non-overlapping 26-bit mantissas (Dekker's split).
+fn split(a: f64) -> (f64, f64) {
+    const FACTOR: f64 = 134_217_729.0; // 2^27 + 1
+    let c = FACTOR * a;
+    let hi = c - (c - a);
+    (hi, a - hi)
+}
+
+/// Product of the two numbers and the rounding error of that product (the TwoProduct
+/// algorithm). Doesn't rely on a fused multiply-add so that the results are the same
+/// on all platforms.
+pub(crate) fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let prod = a * b;
+    let (a_hi, a_lo) = split(a);
+    let (b_hi, b_lo) = split(b);
+    let err = ((a_hi * b_hi - prod) + a_hi * b_lo + a_lo * b_hi) + a_lo * b_lo;
+    (prod, err)
+}
+
+/// A running sum that keeps track of the rounding errors (Neumaier's variant of
+/// the Kahan summation).
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct CompensatedSum {
+    sum: f64,
+    err: f64,
+}
+
+impl CompensatedSum {
+    pub(crate) fn add(&mut self, val: f64) {
+        let (sum, err) = two_sum(self.sum, val);
+        self.sum = sum;
+        self.err += err;
+    }
+
+    pub(crate) fn value(&self) -> f64 {
+        self.sum + self.err
+    }
+}
+
+/// A running sum kept as an unevaluated sum of two numbers (double-double arithmetic).
+/// Unlike [`CompensatedSum`] the products added to it are also computed exactly.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+impl DoubleDouble {
+    pub(crate) fn add(&mut self, val: f64) {
+        let (sum, err) = two_sum(self.hi, val);
+        let (hi, lo) = two_sum(sum, err + self.lo);
+        self.hi = hi;
+        self.lo = lo;
+    }
+
+    /// Add the product `a * b`.
+    pub(crate) fn add_prod(&mut self, a: f64, b: f64) {
+        let (prod, err) = two_prod(a, b);
+        self.add(prod);
+        self.add(err);
+    }
+
+    pub(crate) fn value(&self) -> f64 {
+        self.hi + self.lo
+    }
+}
+
+/// Sum the values with the summation method required by the accuracy setting.
+pub(crate) fn sum(accuracy: Accuracy, vals: impl Iterator<Item = f64>) -> f64 {
+    match accuracy {
+        Accuracy::Standard => vals.sum(),
+        Accuracy::Compensated => {
+            let mut sum = CompensatedSum::default();
+            for val in vals {
+                sum.add(val);
+            }
+            sum.value()
+        }
+        Accuracy::DoubleDouble => {
+            let mut sum = DoubleDouble::default();
+            for val in vals {
+                sum.add(val);
+            }
+            sum.value()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compensated_sum() {
+        let vals = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(sum(Accuracy::Standard, vals.iter().cloned()), 0.0);
+        assert_eq!(sum(Accuracy::Compensated, vals.iter().cloned()), 2.0);
+
+        let vals = std::iter::repeat_n(0.1, 10);
+        assert_ne!(sum(Accuracy::Standard, vals.clone()), 1.0);
+        assert_eq!(sum(Accuracy::Compensated, vals.clone()), 1.0);
+        assert_eq!(sum(Accuracy::DoubleDouble, vals), 1.0);
+    }
+
+    #[test]
+    fn double_double() {
+        let a = 1.0 + f64::EPSILON;
+        let (prod, err) = two_prod(a, a);
+        assert_eq!(prod, 1.0 + 2.0 * f64::EPSILON);
+        assert_eq!(err, f64::EPSILON * f64::EPSILON);
+
+        // (1 + eps)^2 - (1 + 2 * eps) is lost in plain and compensated summation.
+        let mut sum = DoubleDouble::default();
+        sum.add_prod(a, a);
+        sum.add(-prod);
+        assert_eq!(sum.value(), f64::EPSILON * f64::EPSILON);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::{BTreeSet, HashMap};
+
+/// A user label attached to variables and constraints to group them semantically (e.g. all
+/// capacity constraints), see [`Problem::tag_var`] and [`Problem::tag_constraint`].
+///
+/// Tags are created from integers and strings with `From`, so methods accepting
+/// `impl Into<Tag>` can be called with `7` or `"capacity"`. Integer and string tags are
+/// distinct: `Tag::from(1)` is not equal to `Tag::from("1")`.
+///
+/// [`Problem::tag_var`]: struct.Problem.html#method.tag_var
+/// [`Problem::tag_constraint`]: struct.Problem.html#method.tag_constraint
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tag {
+    /// An integer tag.
+    Int(i64),
+    /// A string tag.
+    Str(String),
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tag::Int(val) => write!(f, "{}", val),
+            Tag::Str(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+impl From<i32> for Tag {
+    fn from(val: i32) -> Self {
+        Tag::Int(val.into())
+    }
+}
+
+impl From<u32> for Tag {
+    fn from(val: u32) -> Self {
+        Tag::Int(val.into())
+    }
+}
+
+impl From<i64> for Tag {
+    fn from(val: i64) -> Self {
+        Tag::Int(val)
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(val: &str) -> Self {
+        Tag::Str(val.to_owned())
+    }
+}
+
+impl From<String> for Tag {
+    fn from(val: String) -> Self {
+        Tag::Str(val)
+    }
+}
+
+impl From<&Tag> for Tag {
+    fn from(tag: &Tag) -> Self {
+        tag.clone()
+    }
+}
+
+/// Entities (variables or constraints) grouped by tag. Each group is kept in the order of
+/// entity indices and an entity can be in several groups.
+#[derive(Clone, Debug)]
+pub(crate) struct TagIndex<T> {
+    groups: HashMap<Tag, BTreeSet<T>>,
+}
+
+impl<T: Copy + Ord> TagIndex<T> {
+    pub(crate) fn new() -> Self {
+        TagIndex {
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Returns false if the entity already had this tag.
+    pub(crate) fn insert(&mut self, tag: Tag, entity: T) -> bool {
+        self.groups.entry(tag).or_default().insert(entity)
+    }
+
+    pub(crate) fn get(&self, tag: &Tag) -> impl Iterator<Item = T> + '_ {
+        self.groups.get(tag).into_iter().flatten().copied()
+    }
+
+    /// Index with the entities mapped by `f`, entities mapped to `None` are dropped.
+    pub(crate) fn remap(&self, f: impl Fn(T) -> Option<T>) -> Self {
+        let groups = self
+            .groups
+            .iter()
+            .map(|(tag, group)| (tag.clone(), group.iter().filter_map(|&e| f(e)).collect()))
+            .filter(|(_, group): &(Tag, BTreeSet<T>)| !group.is_empty())
+            .collect();
+        TagIndex { groups }
+    }
+
+    /// All tags of the entity in sorted order.
+    pub(crate) fn tags_of(&self, entity: T) -> Vec<&Tag> {
+        let mut tags = self
+            .groups
+            .iter()
+            .filter(|(_, group)| group.contains(&entity))
+            .map(|(tag, _)| tag)
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags
+    }
+
+    /// All tags with at least one entity in sorted order.
+    pub(crate) fn tags(&self) -> Vec<&Tag> {
+        let mut tags = self.groups.keys().collect::<Vec<_>>();
+        tags.sort();
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn groups() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let x = problem.add_var(1.0, (0.0, 1.0));
+        let y = problem.add_var(1.0, (0.0, 1.0));
+        let z = problem.add_var(1.0, (0.0, 1.0));
+        let c0 = problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Ge, 1.0);
+        let c1 = problem.add_constraint([(y, 1.0), (z, 1.0)], ComparisonOp::Ge, 1.0);
+
+        assert!(problem.tag_var(z, "flow"));
+        assert!(problem.tag_var(x, "flow"));
+        assert!(!problem.tag_var(x, "flow"));
+        assert!(problem.tag_var(x, 3));
+        assert!(problem.tag_constraint(c1, 3));
+        assert!(problem.tag_constraint(c0, String::from("cover")));
+        assert!(problem.tag_constraint(c1, "cover"));
+
+        assert_eq!(problem.vars_with_tag("flow").collect::<Vec<_>>(), [x, z]);
+        assert_eq!(problem.vars_with_tag(3).collect::<Vec<_>>(), [x]);
+        assert_eq!(problem.vars_with_tag("3").count(), 0);
+        assert_eq!(problem.vars_with_tag("cover").count(), 0);
+        assert_eq!(
+            problem.constraints_with_tag("cover").collect::<Vec<_>>(),
+            [c0, c1]
+        );
+        assert_eq!(problem.constraints_with_tag(3).collect::<Vec<_>>(), [c1]);
+
+        assert_eq!(problem.var_tags(x), [&Tag::Int(3), &Tag::from("flow")]);
+        assert!(problem.var_tags(y).is_empty());
+        assert_eq!(problem.constraint_tags(c1), [&Tag::Int(3), &"cover".into()]);
+        assert_eq!(
+            problem.tags(),
+            [&Tag::Int(3), &"cover".into(), &"flow".into()]
+        );
+        assert_eq!(Tag::from(-2).to_string(), "-2");
+    }
+}
@@ -0,0 +1,516 @@
+use crate::{ComparisonOp, ConstraintRef, CsVec, OptimizationDirection, Problem, Variable};
+use std::collections::HashMap;
+
+/// Rows and columns in which the ratio of the biggest to the smallest abs. value of nonzero
+/// coeffs exceeds this threshold are considered badly scaled.
+const BAD_SCALING_RATIO: f64 = 1e6;
+
+/// Nonzero coeffs with abs. value outside of the [TINY_COEFF, HUGE_COEFF] range are reported.
+const TINY_COEFF: f64 = 1e-9;
+const HUGE_COEFF: f64 = 1e9;
+
+/// Rows are near-duplicates if their coeffs differ by at most this (relative) amount after
+/// bringing them to the same scale.
+const DUPLICATE_TOL: f64 = 1e-9;
+
+/// A potential issue with the problem found by [`Problem::validate`].
+///
+/// [`Problem::validate`]: struct.Problem.html#method.validate
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationWarning {
+    /// Magnitudes of the nonzero coefficients of the constraint differ wildly.
+    BadlyScaledConstraint {
+        /// The offending constraint.
+        constr: ConstraintRef,
+        /// The smallest abs. value of a nonzero coefficient.
+        min_abs_coeff: f64,
+        /// The biggest abs. value of a coefficient.
+        max_abs_coeff: f64,
+    },
+    /// Magnitudes of the nonzero constraint coefficients of the variable differ wildly.
+    BadlyScaledVariable {
+        /// The offending variable.
+        var: Variable,
+        /// The smallest abs. value of a nonzero coefficient.
+        min_abs_coeff: f64,
+        /// The biggest abs. value of a coefficient.
+        max_abs_coeff: f64,
+    },
+    /// The constraint has no nonzero coefficients, so it is either redundant or infeasible.
+    EmptyConstraint {
+        /// The offending constraint.
+        constr: ConstraintRef,
+    },
+    /// A coefficient of the variable in the constraint is explicitly set to zero.
+    ZeroCoefficient {
+        /// The constraint.
+        constr: ConstraintRef,
+        /// The variable.
+        var: Variable,
+    },
+    /// The abs. value of a nonzero coefficient is tiny (below 1e-9) or huge (above 1e9).
+    /// Such coefficients are often modeling errors (e.g. big-M values that are too big) and
+    /// are prone to rounding errors.
+    ExtremeCoefficient {
+        /// The constraint or `None` for the objective coefficient.
+        constr: Option<ConstraintRef>,
+        /// The variable.
+        var: Variable,
+        /// Value of the coefficient.
+        coeff: f64,
+    },
+    /// Coefficients of the constraint are (almost) the same as the coefficients of
+    /// the previous constraint multiplied by `ratio`. One of these constraints is redundant
+    /// or they together form a range (or an infeasible) constraint.
+    DuplicateConstraint {
+        /// The offending constraint.
+        constr: ConstraintRef,
+        /// The previous constraint with the same coefficients.
+        original: ConstraintRef,
+        /// Ratio of the coefficients of `constr` to the coefficients of `original`.
+        ratio: f64,
+    },
+    /// The free (unbounded in both directions) variable appears in an equality constraint.
+    /// It can be expressed through the other variables of the constraint and eliminated.
+    FreeVariableInEquality {
+        /// The variable.
+        var: Variable,
+        /// The first equality constraint with the variable.
+        constr: ConstraintRef,
+    },
+    /// Moving the variable towards its infinite bound improves the objective without violating
+    /// any constraint, so the problem is unbounded unless it is infeasible.
+    UnboundedVariable {
+        /// The variable.
+        var: Variable,
+    },
+}
+
+impl std::fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationWarning::BadlyScaledConstraint {
+                constr,
+                min_abs_coeff,
+                max_abs_coeff,
+            } => write!(
+                f,
+                "constraint {} is badly scaled: abs. coefficients range from {:e} to {:e}",
+                constr.0, min_abs_coeff, max_abs_coeff
+            ),
+            ValidationWarning::BadlyScaledVariable {
+                var,
+                min_abs_coeff,
+                max_abs_coeff,
+            } => write!(
+                f,
+                "variable {} is badly scaled: abs. coefficients range from {:e} to {:e}",
+                var.0, min_abs_coeff, max_abs_coeff
+            ),
+            ValidationWarning::EmptyConstraint { constr } => {
+                write!(f, "constraint {} has no nonzero coefficients", constr.0)
+            }
+            ValidationWarning::ZeroCoefficient { constr, var } => write!(
+                f,
+                "coefficient of variable {} in constraint {} is zero",
+                var.0, constr.0
+            ),
+            ValidationWarning::ExtremeCoefficient {
+                constr: Some(constr),
+                var,
+                coeff,
+            } => write!(
+                f,
+                "coefficient of variable {} in constraint {} is extreme: {:e}",
+                var.0, constr.0, coeff
+            ),
+            ValidationWarning::ExtremeCoefficient {
+                constr: None,
+                var,
+                coeff,
+            } => write!(
+                f,
+                "objective coefficient of variable {} is extreme: {:e}",
+                var.0, coeff
+            ),
+            ValidationWarning::DuplicateConstraint {
+                constr,
+                original,
+                ratio,
+            } => write!(
+                f,
+                "constraint {} is constraint {} multiplied by {}",
+                constr.0, original.0, ratio
+            ),
+            ValidationWarning::FreeVariableInEquality { var, constr } => write!(
+                f,
+                "free variable {} appears in equality constraint {}",
+                var.0, constr.0
+            ),
+            ValidationWarning::UnboundedVariable { var } => write!(
+                f,
+                "variable {} can improve the objective indefinitely",
+                var.0
+            ),
+        }
+    }
+}
+
+impl ValidationWarning {
+    /// Warnings are sorted by this key: constraints first and then variables.
+    fn sort_key(&self) -> (usize, usize) {
+        match self {
+            ValidationWarning::BadlyScaledConstraint { constr, .. }
+            | ValidationWarning::EmptyConstraint { constr }
+            | ValidationWarning::ZeroCoefficient { constr, .. }
+            | ValidationWarning::ExtremeCoefficient {
+                constr: Some(constr),
+                ..
+            }
+            | ValidationWarning::DuplicateConstraint { constr, .. } => (0, constr.0),
+            ValidationWarning::BadlyScaledVariable { var, .. }
+            | ValidationWarning::ExtremeCoefficient {
+                constr: None, var, ..
+            }
+            | ValidationWarning::FreeVariableInEquality { var, .. }
+            | ValidationWarning::UnboundedVariable { var } => (1, var.0),
+        }
+    }
+}
+
+/// A list of potential issues with the problem, see [`Problem::validate`].
+///
+/// [`Problem::validate`]: struct.Problem.html#method.validate
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Issues found, constraints first and then variables.
+    pub warnings: Vec<ValidationWarning>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Returns `true` if some constraints or variables are badly scaled. Such problems
+    /// are prone to numerical difficulties unless solved with scaling enabled
+    /// (see [`SolverOptions::scaling`]).
+    ///
+    /// [`SolverOptions::scaling`]: struct.SolverOptions.html#structfield.scaling
+    pub fn is_badly_scaled(&self) -> bool {
+        self.warnings.iter().any(|w| {
+            matches!(
+                w,
+                ValidationWarning::BadlyScaledConstraint { .. }
+                    | ValidationWarning::BadlyScaledVariable { .. }
+            )
+        })
+    }
+}
+
+pub(crate) fn validate(problem: &Problem) -> ValidationReport {
+    let num_vars = problem.obj_coeffs.len();
+    let mut warnings = vec![];
+    for (r, (coeffs, _, _)) in problem.constraints.iter().enumerate() {
+        if problem.is_constraint_removed[r] {
+            continue;
+        }
+        let constr = problem.constraint_ref(r);
+        if coeffs.data().iter().all(|&coeff| coeff == 0.0) {
+            warnings.push(ValidationWarning::EmptyConstraint { constr });
+        }
+        for (c, &coeff) in coeffs.iter() {
+            if coeff == 0.0 {
+                warnings.push(ValidationWarning::ZeroCoefficient {
+                    constr,
+                    var: problem.var_ref(c),
+                });
+            } else if is_extreme(coeff) {
+                warnings.push(ValidationWarning::ExtremeCoefficient {
+                    constr: Some(constr),
+                    var: problem.var_ref(c),
+                    coeff,
+                });
+            }
+        }
+    }
+    warnings.extend(duplicate_warnings(&problem.constraints, problem.generation));
+
+    let obj_sign = match problem.direction {
+        OptimizationDirection::Minimize => 1.0,
+        OptimizationDirection::Maximize => -1.0,
+    };
+    for (c, &coeff) in problem.obj_coeffs.iter().enumerate() {
+        if is_extreme(coeff) {
+            warnings.push(ValidationWarning::ExtremeCoefficient {
+                constr: None,
+                var: problem.var_ref(c),
+                coeff: obj_sign * coeff,
+            });
+        }
+    }
+    warnings.extend(scaling_warnings(
+        num_vars,
+        &problem.constraints,
+        problem.generation,
+    ));
+    warnings.extend(var_warnings(problem));
+
+    warnings.sort_by_key(|w| w.sort_key());
+    ValidationReport { warnings }
+}
+
+fn is_extreme(coeff: f64) -> bool {
+    coeff != 0.0 && !(TINY_COEFF..=HUGE_COEFF).contains(&coeff.abs())
+}
+
+/// Find the rows that are (almost) multiples of the previous rows.
+fn duplicate_warnings(
+    constraints: &[(CsVec, ComparisonOp, f64)],
+    generation: u32,
+) -> Vec<ValidationWarning> {
+    // Only the rows with the same pattern of nonzeros can be duplicates.
+    let mut pattern2rows: HashMap<Vec<usize>, Vec<usize>> = HashMap::new();
+    let mut res = vec![];
+    for (r, (coeffs, _, _)) in constraints.iter().enumerate() {
+        let pattern = coeffs
+            .iter()
+            .filter(|(_, &coeff)| coeff != 0.0)
+            .map(|(c, _)| c)
+            .collect::<Vec<_>>();
+        if pattern.is_empty() {
+            continue;
+        }
+
+        // Rows with the same pattern that are not duplicates of each other.
+        let originals = pattern2rows.entry(pattern).or_default();
+        let nonzeros = |row: usize| {
+            constraints[row]
+                .0
+                .iter()
+                .map(|(_, &coeff)| coeff)
+                .filter(|&coeff| coeff != 0.0)
+        };
+        let duplicate = originals.iter().find_map(|&orig| {
+            let ratio = nonzeros(r).next().unwrap() / nonzeros(orig).next().unwrap();
+            let is_duplicate = nonzeros(r).zip(nonzeros(orig)).all(|(coeff, orig_coeff)| {
+                let scaled = ratio * orig_coeff;
+                f64::abs(coeff - scaled) <= DUPLICATE_TOL * f64::max(coeff.abs(), scaled.abs())
+            });
+            if is_duplicate {
+                Some((orig, ratio))
+            } else {
+                None
+            }
+        });
+        match duplicate {
+            Some((orig, ratio)) => res.push(ValidationWarning::DuplicateConstraint {
+                constr: ConstraintRef(r, generation),
+                original: ConstraintRef(orig, generation),
+                ratio,
+            }),
+            None => originals.push(r),
+        }
+    }
+    res
+}
+
+/// Find the free vars in equalities and the vars along which the objective is unbounded.
+fn var_warnings(problem: &Problem) -> Vec<ValidationWarning> {
+    let num_vars = problem.obj_coeffs.len();
+    let mut first_equality = vec![None; num_vars];
+    // Whether some constraint blocks the movement of the var in the direction of increase
+    // and decrease.
+    let mut blocks_increase = vec![false; num_vars];
+    let mut blocks_decrease = vec![false; num_vars];
+    for (r, (coeffs, cmp_op, _)) in problem.constraints.iter().enumerate() {
+        for (c, &coeff) in coeffs.iter() {
+            if coeff == 0.0 {
+                continue;
+            }
+            match cmp_op {
+                ComparisonOp::Eq => {
+                    first_equality[c].get_or_insert(problem.constraint_ref(r));
+                    blocks_increase[c] = true;
+                    blocks_decrease[c] = true;
+                }
+                ComparisonOp::Le if coeff > 0.0 => blocks_increase[c] = true,
+                ComparisonOp::Ge if coeff < 0.0 => blocks_increase[c] = true,
+                _ => blocks_decrease[c] = true,
+            }
+        }
+    }
+
+    let mut res = vec![];
+    for c in 0..num_vars {
+        let (min, max) = (problem.var_mins[c], problem.var_maxs[c]);
+        if let (Some(constr), true) = (first_equality[c], min.is_infinite() && max.is_infinite()) {
+            res.push(ValidationWarning::FreeVariableInEquality {
+                var: problem.var_ref(c),
+                constr,
+            });
+        }
+        // The objective is minimized.
+        let coeff = problem.obj_coeffs[c];
+        let is_unbounded = (coeff < 0.0 && max == f64::INFINITY && !blocks_increase[c])
+            || (coeff > 0.0 && min == f64::NEG_INFINITY && !blocks_decrease[c]);
+        if is_unbounded {
+            res.push(ValidationWarning::UnboundedVariable {
+                var: problem.var_ref(c),
+            });
+        }
+    }
+    res
+}
+
+/// Find the rows and columns with extreme ratios of coefficient magnitudes.
+pub(crate) fn scaling_warnings(
+    num_vars: usize,
+    constraints: &[(CsVec, ComparisonOp, f64)],
+    generation: u32,
+) -> Vec<ValidationWarning> {
+    let mut col_min = vec![f64::INFINITY; num_vars];
+    let mut col_max = vec![0.0; num_vars];
+
+    let mut res = vec![];
+    for (r, (coeffs, _, _)) in constraints.iter().enumerate() {
+        let mut row_min = f64::INFINITY;
+        let mut row_max = 0.0;
+        for (c, &coeff) in coeffs.iter() {
+            let val = coeff.abs();
+            if val == 0.0 {
+                continue;
+            }
+            row_min = f64::min(row_min, val);
+            row_max = f64::max(row_max, val);
+            col_min[c] = f64::min(col_min[c], val);
+            col_max[c] = f64::max(col_max[c], val);
+        }
+
+        if row_max > BAD_SCALING_RATIO * row_min {
+            res.push(ValidationWarning::BadlyScaledConstraint {
+                constr: ConstraintRef(r, generation),
+                min_abs_coeff: row_min,
+                max_abs_coeff: row_max,
+            });
+        }
+    }
+
+    for c in 0..num_vars {
+        if col_max[c] > BAD_SCALING_RATIO * col_min[c] {
+            res.push(ValidationWarning::BadlyScaledVariable {
+                var: Variable(c, generation),
+                min_abs_coeff: col_min[c],
+                max_abs_coeff: col_max[c],
+            });
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn bad_scaling() {
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        let v1 = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let v2 = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let v3 = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(v1, 1.0), (v2, 2.0), (v3, 0.0)], ComparisonOp::Ge, 1.0);
+        problem.add_constraint([(v2, 1e-2), (v3, -1e3)], ComparisonOp::Ge, 1.0);
+        assert_eq!(
+            problem.validate().warnings,
+            vec![ValidationWarning::ZeroCoefficient {
+                constr: ConstraintRef(0, 0),
+                var: v3,
+            }]
+        );
+        problem.constraints[0].0 = sprs::CsVec::new(3, vec![0, 1], vec![1.0, 2.0]);
+        assert!(problem.validate().is_clean());
+
+        problem.add_constraint([(v1, 1e-8), (v3, 1.0)], ComparisonOp::Le, 1.0);
+        let report = problem.validate();
+        assert!(report.is_badly_scaled());
+        assert_eq!(
+            report.warnings,
+            vec![
+                ValidationWarning::BadlyScaledConstraint {
+                    constr: ConstraintRef(2, 0),
+                    min_abs_coeff: 1e-8,
+                    max_abs_coeff: 1.0,
+                },
+                ValidationWarning::BadlyScaledVariable {
+                    var: v1,
+                    min_abs_coeff: 1e-8,
+                    max_abs_coeff: 1.0,
+                },
+            ]
+        );
+        assert_eq!(
+            report.warnings[0].to_string(),
+            "constraint 2 is badly scaled: abs. coefficients range from 1e-8 to 1e0"
+        );
+    }
+
+    #[test]
+    fn auto_scaling() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let v1 = problem.add_var(1e3, (0.0, f64::INFINITY));
+        let v2 = problem.add_var(1e-6, (0.0, f64::INFINITY));
+        problem.add_constraint([(v1, 1e4), (v2, 1e-4)], ComparisonOp::Le, 1e4);
+        problem.add_constraint([(v1, 1e4), (v2, -1e-4)], ComparisonOp::Le, 0.0);
+        assert!(problem.validate().is_badly_scaled());
+
+        let options = SolverOptions {
+            scaling: ScalingMode::Auto,
+            ..SolverOptions::default()
+        };
+        let sol = problem.solve_with(&options).unwrap();
+        assert!(f64::abs(sol[v1] - 0.5) < 1e-8);
+        assert!(f64::abs(sol[v2] - 5e7) < 1e-2);
+    }
+
+    #[test]
+    fn lint() {
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(1.0, (0.0, f64::INFINITY));
+        let y = problem.add_var(2.0, (f64::NEG_INFINITY, f64::INFINITY));
+        let z = problem.add_var(-1e10, (0.0, 1.0));
+        let w = problem.add_var(1.0, (0.0, f64::INFINITY));
+        problem.add_constraint([(x, 1.0), (y, 1.0)], ComparisonOp::Eq, 4.0);
+        problem.add_constraint([(y, 1.0), (z, 0.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint(
+            [(x, -3.0), (y, -3.0 * (1.0 + 1e-12))],
+            ComparisonOp::Ge,
+            2.0,
+        );
+        problem.add_constraint([(x, 1e-10), (w, -1.0)], ComparisonOp::Le, 0.0);
+        problem.add_constraint(LinearExpr::empty(), ComparisonOp::Le, 0.0);
+
+        let report = problem.validate();
+        assert!(report.is_badly_scaled());
+        let warnings = report
+            .warnings
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "coefficient of variable 2 in constraint 1 is zero",
+                "constraint 2 is constraint 0 multiplied by -3",
+                "coefficient of variable 0 in constraint 3 is extreme: 1e-10",
+                "constraint 3 is badly scaled: abs. coefficients range from 1e-10 to 1e0",
+                "constraint 4 has no nonzero coefficients",
+                "variable 0 is badly scaled: abs. coefficients range from 1e-10 to 3e0",
+                "free variable 1 appears in equality constraint 0",
+                "objective coefficient of variable 2 is extreme: -1e10",
+                "variable 3 can improve the objective indefinitely",
+            ]
+        );
+    }
+}
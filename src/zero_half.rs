@@ -0,0 +1,303 @@
+use crate::{helpers::is_integral, ComparisonOp, Cut, CutPool, LinearExpr, Solution, Variable};
+
+/// Cuts violated by less than this are not reported.
+const MIN_VIOLATION: f64 = 1e-6;
+/// Max number of rows (with the smallest slacks) considered by the separation.
+const MAX_ROWS: usize = 500;
+
+/// A row of the problem in the `coeffs * x <= rhs` form with integral coeffs and rhs.
+struct Row {
+    coeffs: Vec<(usize, f64)>,
+    rhs: f64,
+    slack: f64,
+}
+
+/// A small bit set.
+#[derive(Clone)]
+struct BitVec(Vec<u64>);
+
+impl BitVec {
+    fn new(len: usize) -> BitVec {
+        BitVec(vec![0; len.div_ceil(64)])
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.0[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn flip(&mut self, i: usize) {
+        self.0[i / 64] ^= 1 << (i % 64);
+    }
+
+    fn xor(&mut self, other: &BitVec) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a ^= b;
+        }
+    }
+
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        let len = self.0.len() * 64;
+        (0..len).filter(move |&i| self.get(i))
+    }
+}
+
+fn is_odd(val: f64) -> bool {
+    val.round().rem_euclid(2.0) == 1.0
+}
+
+/// A var bound that is added to the combination of rows to make the coefficient of
+/// the var even.
+#[derive(Clone, Copy)]
+struct ParityFix {
+    /// Slack of the bound.
+    cost: f64,
+    /// If true, x <= bound is added, otherwise -x <= -bound.
+    is_upper: bool,
+    bound: f64,
+}
+
+fn parity_fix(solution: &Solution, var: usize) -> Option<ParityFix> {
+    let (min, max) = solution.solver.var_bounds(var);
+    let val = solution[solution.var_ref(var)];
+    let lower = if min.is_finite() && is_integral(min) {
+        Some(ParityFix {
+            cost: f64::max(val - min, 0.0),
+            is_upper: false,
+            bound: min.round(),
+        })
+    } else {
+        None
+    };
+    let upper = if max.is_finite() && is_integral(max) {
+        Some(ParityFix {
+            cost: f64::max(max - val, 0.0),
+            is_upper: true,
+            bound: max.round(),
+        })
+    } else {
+        None
+    };
+    match (lower, upper) {
+        (Some(l), Some(u)) if u.cost < l.cost => Some(u),
+        (Some(l), _) => Some(l),
+        (None, u) => u,
+    }
+}
+
+pub(crate) fn zero_half_cuts(
+    solution: &Solution,
+    integer_vars: &[Variable],
+    max_cuts: usize,
+) -> Vec<Cut> {
+    let num_vars = solution.num_vars;
+    let mut is_integer = vec![false; num_vars];
+    for v in integer_vars {
+        is_integer[v.0] = true;
+    }
+    let fixes: Vec<_> = (0..num_vars)
+        .map(|v| {
+            if is_integer[v] {
+                parity_fix(solution, v)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Collect the rows with integral coeffs on integer vars only. A cut is violated only if
+    // the total slack of the combined rows is less than 1, so rows with bigger slacks are
+    // useless.
+    let mut rows = vec![];
+    for constr in 0..solution.solver.num_constraints() {
+        let (coeffs, min, max) = solution.solver.constraint_row(constr);
+        if !coeffs
+            .iter()
+            .all(|&(v, coeff)| is_integer[v] && is_integral(coeff))
+        {
+            continue;
+        }
+        let lhs: f64 = coeffs
+            .iter()
+            .map(|&(v, coeff)| coeff * solution[solution.var_ref(v)])
+            .sum();
+        let coeffs: Vec<_> = coeffs.into_iter().map(|(v, c)| (v, c.round())).collect();
+
+        if max.is_finite() && is_integral(max) && max - lhs < 1.0 {
+            rows.push(Row {
+                coeffs: coeffs.clone(),
+                rhs: max.round(),
+                slack: f64::max(max - lhs, 0.0),
+            });
+        }
+        if min.is_finite() && is_integral(min) && lhs - min < 1.0 {
+            rows.push(Row {
+                coeffs: coeffs.iter().map(|&(v, c)| (v, -c)).collect(),
+                rhs: -min.round(),
+                slack: f64::max(lhs - min, 0.0),
+            });
+        }
+    }
+    rows.sort_by(|a, b| a.slack.partial_cmp(&b.slack).unwrap());
+    rows.truncate(MAX_ROWS);
+
+    // Vars with zero parity fix cost can be always made even for free, so the mod 2 system
+    // needs columns only for the rest of the vars.
+    let mut col_of_var = vec![None; num_vars];
+    let mut num_cols = 0;
+    for (v, fix) in fixes.iter().enumerate() {
+        let is_free = matches!(fix, Some(fix) if fix.cost <= MIN_VIOLATION);
+        if is_integer[v] && !is_free {
+            col_of_var[v] = Some(num_cols);
+            num_cols += 1;
+        }
+    }
+
+    // Mod 2 rows: odd columns, odd rhs and the set of combined original rows.
+    let mut parities = vec![];
+    let mut rhs_odd = vec![];
+    let mut combinations = vec![];
+    for (r, row) in rows.iter().enumerate() {
+        let mut parity = BitVec::new(num_cols);
+        for &(v, coeff) in &row.coeffs {
+            if let (true, Some(col)) = (is_odd(coeff), col_of_var[v]) {
+                parity.flip(col);
+            }
+        }
+        parities.push(parity);
+        rhs_odd.push(is_odd(row.rhs));
+        let mut combination = BitVec::new(rows.len());
+        combination.flip(r);
+        combinations.push(combination);
+    }
+
+    // Gaussian elimination over GF(2), pivot rows are chosen in the order of increasing
+    // slacks. Each resulting row is a combination of the original rows with few odd
+    // columns and is a zero-half cut candidate.
+    let mut is_pivot = vec![false; rows.len()];
+    for col in 0..num_cols {
+        let pivot = (0..rows.len()).find(|&r| !is_pivot[r] && parities[r].get(col));
+        let pivot = if let Some(pivot) = pivot {
+            pivot
+        } else {
+            continue;
+        };
+        is_pivot[pivot] = true;
+        let (pivot_parity, pivot_combination) =
+            (parities[pivot].clone(), combinations[pivot].clone());
+        for r in 0..rows.len() {
+            if r != pivot && parities[r].get(col) {
+                parities[r].xor(&pivot_parity);
+                combinations[r].xor(&pivot_combination);
+                rhs_odd[r] ^= rhs_odd[pivot];
+            }
+        }
+    }
+
+    let mut pool = CutPool::new();
+    for r in 0..rows.len() {
+        if rhs_odd[r] {
+            if let Some(cut) = build_cut(&rows, &combinations[r], &fixes) {
+                pool.add(cut);
+            }
+        }
+    }
+    pool.separate(solution, max_cuts)
+}
+
+/// Sum the rows, make all coeffs even using the var bounds, divide by 2 and round down.
+fn build_cut(rows: &[Row], combination: &BitVec, fixes: &[Option<ParityFix>]) -> Option<Cut> {
+    let mut coeffs = vec![0.0; fixes.len()];
+    let mut rhs = 0.0;
+    let mut total_slack = 0.0;
+    for r in combination.ones() {
+        for &(v, coeff) in &rows[r].coeffs {
+            coeffs[v] += coeff;
+        }
+        rhs += rows[r].rhs;
+        total_slack += rows[r].slack;
+    }
+
+    for (v, coeff) in coeffs.iter_mut().enumerate() {
+        if is_odd(*coeff) {
+            let fix = fixes[v]?;
+            total_slack += fix.cost;
+            if fix.is_upper {
+                *coeff += 1.0;
+                rhs += fix.bound;
+            } else {
+                *coeff -= 1.0;
+                rhs -= fix.bound;
+            }
+        }
+    }
+    if !is_odd(rhs) || total_slack >= 1.0 - 2.0 * MIN_VIOLATION {
+        return None;
+    }
+
+    let (vars, coeffs) = coeffs
+        .iter()
+        .enumerate()
+        .filter(|&(_, &coeff)| coeff != 0.0)
+        .map(|(v, &coeff)| (v, coeff / 2.0))
+        .unzip();
+    let expr = LinearExpr {
+        vars,
+        coeffs,
+        generation: None,
+    };
+    Some(Cut::new(expr, ComparisonOp::Le, (rhs / 2.0).floor()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn odd_cycle() {
+        // Matching in a triangle: LP optimum is 1.5 with all edges at 0.5.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let e12 = problem.add_var(1.0, (0.0, 1.0));
+        let e13 = problem.add_var(1.0, (0.0, 1.0));
+        let e23 = problem.add_var(1.0, (0.0, 1.0));
+        problem.add_constraint([(e12, 1.0), (e13, 1.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint([(e12, 1.0), (e23, 1.0)], ComparisonOp::Le, 1.0);
+        problem.add_constraint([(e13, 1.0), (e23, 1.0)], ComparisonOp::Le, 1.0);
+        let vars = [e12, e13, e23];
+
+        let sol = problem.solve().unwrap();
+        assert_eq!(sol.objective(), 1.5);
+
+        let cuts = sol.zero_half_cuts(&vars, 10);
+        assert_eq!(cuts.len(), 1);
+        assert_eq!(cuts[0].rhs(), 1.0);
+        assert!(f64::abs(cuts[0].violation(&sol) - 0.5) < 1e-8);
+
+        let sol = sol
+            .add_constraint(cuts[0].lhs(), ComparisonOp::Le, cuts[0].rhs())
+            .unwrap();
+        assert!(f64::abs(sol.objective() - 1.0) < 1e-8);
+        assert!(sol.zero_half_cuts(&vars, 10).is_empty());
+
+        // No cuts if the vars are continuous.
+        let sol = problem.solve().unwrap();
+        assert!(sol.zero_half_cuts(&[e12, e13], 10).is_empty());
+    }
+
+    #[test]
+    fn bound_parity() {
+        // x + 2 * y <= 3 and x >= 0 give y <= 1.
+        let mut problem = Problem::new(OptimizationDirection::Maximize);
+        let x = problem.add_var(0.0, (0.0, 5.0));
+        let y = problem.add_var(1.0, (0.0, 5.0));
+        problem.add_constraint([(x, 1.0), (y, 2.0)], ComparisonOp::Le, 3.0);
+        let sol = problem.solve().unwrap();
+        assert_eq!(sol[y], 1.5);
+
+        let cuts = sol.zero_half_cuts(&[x, y], 10);
+        assert_eq!(cuts.len(), 1);
+        let sol = sol
+            .add_constraint(cuts[0].lhs(), ComparisonOp::Le, cuts[0].rhs())
+            .unwrap();
+        assert!(f64::abs(sol[y] - 1.0) < 1e-8);
+    }
+}